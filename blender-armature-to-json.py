@@ -20,6 +20,23 @@ class ExportArmatureToJSON(bpy.types.Operator):
     bl_options = {'REGISTER'}
     bl_category = 'Import-Export'
 
+    # Actions that only live in the NLA editor, or that are generated by drivers, don't have
+    # keyframes of their own so we need to bake them by sampling the evaluated pose every frame.
+    nla_mode: bpy.props.EnumProperty(
+        name='NLA Mode',
+        items=[
+            ('NONE', 'None', 'Only export actions'),
+            ('STRIPS', 'Strips', 'Also bake each NLA strip into an action named after the strip'),
+            ('STACK', 'Stack', 'Also bake the full NLA stack, including drivers, into a single action'),
+        ],
+        default='NONE'
+    )
+    # The name of the action that the NLA stack is baked into when using the 'STACK' mode
+    nla_stack_action_name: bpy.props.StringProperty(name='NLA Stack Action Name', default='NLA')
+    # JSON with the names of the actions to export, prefixes of action names to leave out and
+    # a map of action names to the names to export them as. See blender_armature::ActionFilter
+    action_filter: bpy.props.StringProperty(name='Action Filter', default='{}')
+
     def execute(self, context):
         def main():
             # Get the armature that is currently active. We will be parsing it's actions
@@ -33,19 +50,29 @@ class ExportArmatureToJSON(bpy.types.Operator):
                         bpy.context.view_layer.objects.active = activeArmature
                         break
 
+            reportArmatureWarnings(activeArmature)
+
             armatureJSON = {
                 'name': activeArmature.name,
                 'bone_space_actions': {},
                 'inverse_bind_poses': [],
+                'bone_rest_poses': [],
+                'bone_rolls': [],
+                'bone_lengths': [],
+                'bone_stable_ids': [],
+                'attachment_points': {},
                 'joint_indices': {},
                 'bone_child_to_parent': {},
-                'bone_groups': {}
+                'bone_groups': {},
+                'frames_per_second': round(bpy.context.scene.render.fps / bpy.context.scene.render.fps_base),
+                'scene_units': scene_units(bpy.context.scene)
             }
 
             # Get all of the actions
             # TODO: If we later support handling multiple armatures we'll need to only use the
             # actions that apply to the current armature
-            actionsList = list(bpy.data.actions)
+            actionFilter = json.loads(self.action_filter)
+            actionsList = [action for action in bpy.data.actions if isActionIncluded(actionFilter, action.name)]
             bpy.ops.object.mode_set(mode = 'POSE')
 
             # Select all of the armature's bones so that we can iterate over them later
@@ -159,16 +186,19 @@ class ExportArmatureToJSON(bpy.types.Operator):
                     previous_trans_y = 0
                     previous_trans_z = 0
 
-                    previous_scale_x = None
-                    previous_scale_y = None
-                    previous_scale_z = None
+                    previous_scale_x = 1
+                    previous_scale_y = 1
+                    previous_scale_z = 1
+
+                    # Scale is exported separately from the matrix since dual quaternions can't represent
+                    # non uniform scale. We only export it for bones that have scale keyframes.
+                    has_scale = any(transforms.get('scale') is not None for transforms in frames.values())
 
                     for frame in sorted(frames):
                         transforms = frames[frame]
 
                         mat_loc = mathutils.Matrix.Translation((0, 0, 0))
                         mat_rot = mathutils.Euler((0, 0, 0), 'XYZ').to_matrix()
-                        mat_scale = mathutils.Matrix.Scale(1.0, 4, (1.0, 1.0, 1.0))
 
                         poseBone = allPoseBones[boneName]
 
@@ -222,29 +252,57 @@ class ExportArmatureToJSON(bpy.types.Operator):
 
                             mat_rot = mathutils.Euler((euler[0], euler[1], euler[2]), poseBone.rotation_mode).to_matrix()
 
-                        # FIXME: Treat this like the above. Need to re-write and test all of this when we port
-                        #  to Rust.
-                        # if 'scale' in transforms:
-                        #     mat_scale = mathutils.Matrix.Scale(1.0, 4, transforms['scale'])
+                        ### ---
+
+                        s = transforms.get('scale') if transforms.get('scale') is not None else {}
+                        scale = [1, 1, 1]
+
+                        scale[0] = s.get(0) if s.get(0) is not None else previous_scale_x
+                        previous_scale_x = scale[0]
+
+                        scale[1] = s.get(1) if s.get(1) is not None else previous_scale_y
+                        previous_scale_y = scale[1]
+
+                        scale[2] = s.get(2) if s.get(2) is not None else previous_scale_z
+                        previous_scale_z = scale[2]
 
                         mat_rot = mat_rot.to_4x4()
 
-                        local_space_transform_matrix = mat_loc @ mat_rot @ mat_scale
+                        local_space_transform_matrix = mat_loc @ mat_rot
 
                         bone_idx = armatureJSON['joint_indices'][boneName]
                         if bone_idx not in armatureJSON['bone_space_actions'][actionInfo.name]['bone_keyframes']['keyframes']:
                             armatureJSON['bone_space_actions'][actionInfo.name]['bone_keyframes']['keyframes'][bone_idx] = []
 
                         # bpy.context.scene.frame_set(frame)
-                        armatureJSON['bone_space_actions'][actionInfo.name]['bone_keyframes']['keyframes'][bone_idx].append({
+                        bone_keyframe = {
                             'frame': math.floor(frame),
                             'bone': {'Matrix': matrixToArray(local_space_transform_matrix)}
-                        })
+                        }
+                        if has_scale:
+                            bone_keyframe['scale'] = scale
+
+                        armatureJSON['bone_space_actions'][actionInfo.name]['bone_keyframes']['keyframes'][bone_idx].append(bone_keyframe)
 
 
                 for pose_marker in activeArmature.animation_data.action.pose_markers:
                     armatureJSON['bone_space_actions'][actionInfo.name]['pose_markers'][pose_marker.frame] = pose_marker.name
 
+            if activeArmature.animation_data is not None:
+                if self.nla_mode == 'STRIPS':
+                    bakeNlaStrips(armatureJSON)
+                elif self.nla_mode == 'STACK':
+                    bakeNlaStack(armatureJSON)
+
+            # Baked NLA strips are named after their strips, so they're filtered here. Renames are
+            # applied last so that filters always match the names in Blender
+            renames = actionFilter.get('renames', {})
+            armatureJSON['bone_space_actions'] = {
+                renames.get(actionName, actionName): action
+                for actionName, action in armatureJSON['bone_space_actions'].items()
+                if isActionIncluded(actionFilter, actionName)
+            }
+
             # Calculate bone inverse bind poses
             for boneName in allBoneNames:
                 # Calculate the bone's inverse bind matrix
@@ -261,6 +319,29 @@ class ExportArmatureToJSON(bpy.types.Operator):
 
                 armatureJSON['inverse_bind_poses'].append({'Matrix': matrixToArray(boneInverseBind)})
 
+                # The rest pose in armature space. Our keyframes are relative to this rest pose since
+                # pose bone location / rotation / scale are applied on top of it.
+                # https://docs.blender.org/api/current/bpy.types.Bone.html#bpy.types.Bone.AxisRollFromMatrix
+                armatureJSON['bone_rest_poses'].append({'Matrix': matrixToArray(poseBone.bone.matrix_local)})
+                _axis, roll = poseBone.bone.AxisRollFromMatrix(poseBone.bone.matrix_local.to_3x3())
+                armatureJSON['bone_rolls'].append(roll)
+                armatureJSON['bone_lengths'].append(poseBone.bone.length)
+                # Assigned by `landon export --assign-stable-ids`
+                armatureJSON['bone_stable_ids'].append(poseBone.bone.get('landon_id'))
+
+            # Empties that are parented to bones mark attachment points such as muzzles, eyes
+            # and hit locators. The offset is relative to the posed bone so that it doesn't
+            # matter which frame the scene is on.
+            for child in activeArmature.children:
+                if child.type != 'EMPTY' or child.parent_type != 'BONE' or child.parent_bone not in allBoneNames:
+                    continue
+
+                boneMatrix = activeArmature.matrix_world @ activeArmature.pose.bones[child.parent_bone].matrix
+                armatureJSON['attachment_points'][child.name] = {
+                    'bone': child.parent_bone,
+                    'offset': {'Matrix': matrixToArray(boneMatrix.inverted() @ child.matrix_world)}
+                }
+
             # Exporting bone groups
             #
             # 1. Deselect all bones in the armature
@@ -300,6 +381,116 @@ class ExportArmatureToJSON(bpy.types.Operator):
 
             return {'FINISHED'}
 
+        def bakeNlaStrips(armatureJSON):
+            activeArmature = bpy.context.view_layer.objects.active
+            animationData = activeArmature.animation_data
+
+            # Remember what was muted so that we can restore it after baking
+            originalAction = animationData.action
+            originalTrackMutes = [(track, track.mute) for track in animationData.nla_tracks]
+            originalStripMutes = [
+                (strip, strip.mute) for track in animationData.nla_tracks for strip in track.strips
+            ]
+
+            animationData.action = None
+
+            for track in animationData.nla_tracks:
+                for strip in track.strips:
+                    # Only evaluate the strip that we're baking
+                    for otherTrack, _mute in originalTrackMutes:
+                        otherTrack.mute = otherTrack != track
+                    for otherStrip, _mute in originalStripMutes:
+                        otherStrip.mute = otherStrip != strip
+
+                    frameStart = math.floor(strip.frame_start)
+                    frameEnd = math.ceil(strip.frame_end)
+
+                    bakeFrames(armatureJSON, strip.name, frameStart, frameEnd, {
+                        'NlaStrip': {
+                            'track': track.name,
+                            'strip': strip.name,
+                            'frame_start': frameStart,
+                            'frame_end': frameEnd
+                        }
+                    })
+
+            for track, mute in originalTrackMutes:
+                track.mute = mute
+            for strip, mute in originalStripMutes:
+                strip.mute = mute
+
+            animationData.action = originalAction
+
+        def bakeNlaStack(armatureJSON):
+            activeArmature = bpy.context.view_layer.objects.active
+            animationData = activeArmature.animation_data
+
+            # Only the NLA tracks (and drivers) should contribute to the baked pose
+            originalAction = animationData.action
+            animationData.action = None
+
+            frameStart = bpy.context.scene.frame_start
+            frameEnd = bpy.context.scene.frame_end
+
+            bakeFrames(armatureJSON, self.nla_stack_action_name, frameStart, frameEnd, {
+                'NlaStack': {
+                    'frame_start': frameStart,
+                    'frame_end': frameEnd
+                }
+            })
+
+            animationData.action = originalAction
+
+        # Sample every bone's evaluated local transform on every frame, so that NLA strips and drivers
+        # are included, and store the samples as a new action.
+        def bakeFrames(armatureJSON, actionName, frameStart, frameEnd, source):
+            activeArmature = bpy.context.view_layer.objects.active
+            scene = bpy.context.scene
+            originalFrame = scene.frame_current
+
+            keyframes = {}
+            hasScale = False
+
+            for frame in range(frameStart, frameEnd + 1):
+                scene.frame_set(frame)
+
+                for boneName, boneIdx in armatureJSON['joint_indices'].items():
+                    poseBone = activeArmature.pose.bones[boneName]
+                    loc, rot, scale = poseBone.matrix_basis.decompose()
+
+                    mat_loc = mathutils.Matrix.Translation(loc)
+                    mat_rot = rot.to_matrix().to_4x4()
+
+                    if any(abs(axis - 1.0) > 1e-6 for axis in scale):
+                        hasScale = True
+
+                    if boneIdx not in keyframes:
+                        keyframes[boneIdx] = []
+
+                    keyframes[boneIdx].append({
+                        'frame': frame,
+                        'bone': {'Matrix': matrixToArray(mat_loc @ mat_rot)},
+                        'scale': [scale[0], scale[1], scale[2]]
+                    })
+
+            # Scale is only exported for baked actions that actually scale bones
+            if not hasScale:
+                for boneKeyframes in keyframes.values():
+                    for keyframe in boneKeyframes:
+                        del keyframe['scale']
+
+            scene.frame_set(originalFrame)
+
+            armatureJSON['bone_space_actions'][actionName] = {
+                'bone_keyframes': {
+                    'frame_range_inclusive': [frameStart, frameEnd],
+                    'keyframes': keyframes
+                },
+                'keyframes': [],
+                'pose_markers': {},
+                'source': source
+            }
+
         def getKeyframesInAction(action):
             # TODO: Right now we aren't sorting these keyframes.
             # We should sort them from lowest to highest (that's a more expected order).
@@ -324,6 +515,48 @@ class ExportArmatureToJSON(bpy.types.Operator):
         # Run our armature2json() add on
         return main()
 
+def isActionIncluded(actionFilter, actionName):
+    """
+    Whether an action is kept by the filter, which works the same way as blender_armature::ActionFilter
+    """
+    include = actionFilter.get('include', [])
+    if len(include) > 0 and actionName not in include:
+        return False
+
+    return not any(actionName.startswith(prefix) for prefix in actionFilter.get('exclude_prefixes', []))
+
+def warn(obj, kind, message):
+    """
+    Report something that couldn't be exported so that it doesn't go missing silently.
+
+    WARN_JSON {"blend_file": $BLENDER_FILEPATH, "object": $OBJECT_NAME, "kind": $KIND, "message": $MESSAGE}
+    """
+    print("WARN_JSON " + json.dumps({
+        'blend_file': bpy.data.filepath,
+        'object': obj.name,
+        'kind': kind,
+        'message': message
+    }))
+
+def reportArmatureWarnings(armature):
+    """
+    Warn about the parts of the armature object that landon skips or can't handle.
+    """
+    if len(armature.constraints) > 0:
+        warn(armature, 'Constraints', 'Constraints ' + ', '.join(c.name for c in armature.constraints) + ' are not exported')
+
+    if armature.matrix_world.determinant() < 0:
+        warn(armature, 'NegativeScale', 'The armature has a negative scale, so its bones are mirrored in the world')
+
+def scene_units(scene):
+    """
+    The scene's unit system and how many meters one Blender unit is.
+    """
+    return {
+        'system': scene.unit_settings.system.capitalize(),
+        'scale_length': scene.unit_settings.scale_length
+    }
+
 def register():
     bpy.utils.register_class(ExportArmatureToJSON)
 
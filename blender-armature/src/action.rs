@@ -1,13 +1,19 @@
 use std::collections::HashMap;
 
 pub use self::action_keyframes::*;
+pub use self::action_source::*;
 pub use self::bone_keyframes::*;
-use crate::Keyframe;
+pub use self::compressed_action::*;
+pub use self::curve_action::*;
+use crate::{BoneMask, Keyframe};
 
 type Frame = u16;
 
 mod action_keyframes;
+mod action_source;
 mod bone_keyframes;
+mod compressed_action;
+mod curve_action;
 
 /// A set of keyframes along with metadata such as pose markers.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -16,6 +22,8 @@ pub struct Action {
     pub(super) bone_keyframes: BoneKeyframes,
     #[serde(default)]
     pose_markers: HashMap<Frame, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source: Option<ActionSource>,
 }
 
 impl Action {
@@ -24,6 +32,7 @@ impl Action {
         Action {
             bone_keyframes: BoneKeyframes::default(),
             pose_markers: HashMap::new(),
+            source: None,
         }
     }
 
@@ -32,6 +41,7 @@ impl Action {
         Action {
             bone_keyframes: BoneKeyframes::new_with_keyframes(keyframes),
             pose_markers: HashMap::new(),
+            source: None,
         }
     }
 
@@ -57,6 +67,16 @@ impl Action {
         &mut self.pose_markers
     }
 
+    /// Where the action was baked from, if it was baked from Blender's NLA editor.
+    pub fn source(&self) -> Option<&ActionSource> {
+        self.source.as_ref()
+    }
+
+    /// See [`Action.method#source`]
+    pub fn set_source(&mut self, source: Option<ActionSource>) {
+        self.source = source;
+    }
+
     /// The smallest frame
     pub fn smallest_frame(&self) -> u16 {
         self.bone_keyframes.frame_range_inclusive().unwrap().0
@@ -71,6 +91,19 @@ impl Action {
     pub fn frame_duration(&self) -> u16 {
         self.bone_keyframes.frame_duration().unwrap()
     }
+
+    /// A copy of the action that only has keyframes for the bones in the mask.
+    ///
+    /// Useful for layering actions, such as playing an aim action on the upper body while a run
+    /// action plays on the rest of the armature.
+    pub fn masked(&self, bones: &BoneMask) -> Action {
+        let mut masked = self.clone();
+        masked
+            .bone_keyframes
+            .retain_bones(|bone_idx| bones.contains(bone_idx));
+
+        masked
+    }
 }
 
 // pub(crate)
@@ -1,6 +1,8 @@
 pub use self::frame_offset::*;
+pub use self::playback_clock::*;
 
 mod frame_offset;
+mod playback_clock;
 
 /// Describes how to sample animation keyframes
 #[derive(Debug, Clone, Copy)]
@@ -25,4 +27,43 @@ pub struct SampleDesc {
     /// `true` is for repeating actions such as walk cycles, `false` might be used for a one off
     /// punch animation that shouldn't repeat.
     pub should_loop: bool,
+    /// Treat the action as a cycle where the frame after the last frame is the first frame again.
+    ///
+    /// With `should_loop` the last keyframe needs to be a copy of the first keyframe in order for
+    /// the loop to be seamless. With `looping` you can leave out that duplicate keyframe - we'll
+    /// interpolate from the last keyframe back towards the first keyframe on our own.
+    ///
+    /// So if your action's frames are 0 through 9 then frame 10 will sample the first keyframe and
+    /// frame 9.5 will be halfway between the last and first keyframes.
+    ///
+    /// When `looping` is `true` the `should_loop` field is ignored.
+    pub looping: bool,
+    /// How to interpolate in between keyframes.
+    pub interpolation: Interpolation,
+}
+
+/// How to interpolate in between two keyframes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Interpolation {
+    /// Blend straight from one keyframe to the next.
+    ///
+    /// Cheap, but rotations can visibly change direction at each keyframe when keyframes are
+    /// sparse.
+    Linear,
+    /// Use the keyframes before and after the two surrounding keyframes to follow a smooth
+    /// curve through all of them.
+    ///
+    /// Rotations use spherical quadrangle interpolation ([squad]) and translations use a
+    /// Catmull-Rom spline. Only dual quaternion bones are interpolated this way, matrices
+    /// always use `Linear` interpolation.
+    ///
+    /// [squad]: https://theory.org/software/qfa/writeup/node12.html
+    Cubic,
+}
+
+/// Linear interpolation is what we've always used.
+impl Default for Interpolation {
+    fn default() -> Self {
+        Interpolation::Linear
+    }
 }
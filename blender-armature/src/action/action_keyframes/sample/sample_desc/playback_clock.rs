@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use crate::{Action, FrameOffset, Interpolation, SampleDesc};
+
+/// Convert some number of seconds into a (possibly fractional) number of frames.
+pub fn seconds_to_frames(seconds: f32, frames_per_second: u8) -> f32 {
+    seconds * frames_per_second as f32
+}
+
+/// Convert some (possibly fractional) number of frames into seconds.
+pub fn frames_to_seconds(frames: f32, frames_per_second: u8) -> f32 {
+    frames / frames_per_second as f32
+}
+
+/// Convert some (possibly fractional) number of frames into a `Duration`.
+///
+/// Negative frame counts are treated as zero.
+pub fn frames_to_duration(frames: f32, frames_per_second: u8) -> Duration {
+    Duration::from_secs_f32(frames_to_seconds(frames, frames_per_second).max(0.0))
+}
+
+/// How a [`PlaybackClock`] behaves once the elapsed time exceeds the duration of an action.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PlaybackMode {
+    /// Play the action one time and then hold the last frame.
+    Once,
+    /// Start over from the first frame after reaching the last frame.
+    ///
+    /// See [`SampleDesc.should_loop`].
+    Loop,
+    /// Start over from the first frame, interpolating from the last keyframe back towards the
+    /// first keyframe.
+    ///
+    /// See [`SampleDesc.looping`].
+    Cycle,
+    /// Play the action forwards, then backwards, then forwards again, and so on.
+    PingPong,
+}
+
+/// Converts elapsed time into the frame offsets that are used to sample actions.
+///
+/// ```
+/// # use blender_armature::{Action, BlenderArmature, Bone, BoneKeyframe, JointIndicesRef, PlaybackClock, PlaybackMode};
+/// # use nalgebra::DualQuaternion;
+/// # use std::time::Duration;
+/// # let mut armature = BlenderArmature::default();
+/// # let mut action = Action::new();
+/// # action.insert_bone_keyframe(0, BoneKeyframe::new(0, Bone::DualQuat(DualQuaternion::identity())));
+/// # action.insert_bone_keyframe(0, BoneKeyframe::new(10, Bone::DualQuat(DualQuaternion::identity())));
+/// # armature.insert_bone_space_action("Walk".to_string(), action);
+/// let clock = PlaybackClock::new(armature.frames_per_second().unwrap_or(24))
+///     .with_speed(1.5)
+///     .with_mode(PlaybackMode::PingPong);
+///
+/// let action = armature.bone_space_actions().get("Walk").unwrap();
+/// let sample_desc = clock.sample_desc(Duration::from_millis(250), action);
+///
+/// let _bones = armature.interpolate_bones("Walk", JointIndicesRef::Some(&[0]), sample_desc);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PlaybackClock {
+    frames_per_second: u8,
+    speed: f32,
+    mode: PlaybackMode,
+    interpolation: Interpolation,
+}
+
+impl PlaybackClock {
+    /// Create a clock that plays at normal speed and loops.
+    pub fn new(frames_per_second: u8) -> Self {
+        PlaybackClock {
+            frames_per_second,
+            speed: 1.0,
+            mode: PlaybackMode::Loop,
+            interpolation: Interpolation::Linear,
+        }
+    }
+
+    /// Multiply the playback rate. `2.0` plays twice as fast, `0.5` plays at half speed and
+    /// negative speeds play the action in reverse.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Set what happens once playback reaches the end of the action.
+    pub fn with_mode(mut self, mode: PlaybackMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set how to interpolate in between keyframes.
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// The number of frames that play every second at normal speed.
+    pub fn frames_per_second(&self) -> u8 {
+        self.frames_per_second
+    }
+
+    /// The playback rate multiplier.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// What happens once playback reaches the end of the action.
+    pub fn mode(&self) -> PlaybackMode {
+        self.mode
+    }
+
+    /// How to interpolate in between keyframes.
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    /// The number of frames that have played after some amount of time, factoring in the
+    /// playback speed but not the duration of any particular action.
+    pub fn frames_elapsed(&self, elapsed: Duration) -> f32 {
+        seconds_to_frames(elapsed.as_secs_f32(), self.frames_per_second) * self.speed
+    }
+
+    /// The frame offset into an action that lasts `action_duration` frames.
+    pub fn frame_offset(&self, elapsed: Duration, action_duration: u16) -> FrameOffset {
+        let frames = self.frames_elapsed(elapsed);
+        let duration = action_duration as f32;
+
+        let offset = match self.mode {
+            PlaybackMode::Once => frames.max(0.0).min(duration),
+            PlaybackMode::Loop if duration == 0.0 => 0.0,
+            PlaybackMode::Loop => frames.rem_euclid(duration),
+            // The sampler handles the wrap from the last keyframe to the first keyframe.
+            PlaybackMode::Cycle => frames,
+            PlaybackMode::PingPong if duration == 0.0 => 0.0,
+            PlaybackMode::PingPong => {
+                let offset = frames.rem_euclid(2.0 * duration);
+
+                if offset > duration {
+                    2.0 * duration - offset
+                } else {
+                    offset
+                }
+            }
+        };
+
+        FrameOffset::new(offset)
+    }
+
+    /// Describe how to sample an action after some amount of time has elapsed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the action does not have any keyframes.
+    pub fn sample_desc(&self, elapsed: Duration, action: &Action) -> SampleDesc {
+        SampleDesc {
+            frame_offset: self.frame_offset(elapsed, action.frame_duration()),
+            should_loop: false,
+            looping: self.mode == PlaybackMode::Cycle,
+            interpolation: self.interpolation,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that we convert between seconds and frames using the frame rate
+    #[test]
+    fn time_conversions() {
+        assert_eq!(seconds_to_frames(1.5, 24), 36.0);
+        assert_eq!(frames_to_seconds(36.0, 24), 1.5);
+        assert_eq!(frames_to_duration(12.0, 24), Duration::from_millis(500));
+        assert_eq!(frames_to_duration(-12.0, 24), Duration::from_secs(0));
+    }
+
+    /// Verify that the playback speed scales the number of frames that have elapsed
+    #[test]
+    fn speed_multiplier() {
+        let clock = PlaybackClock::new(10).with_speed(2.0);
+        assert_eq!(clock.frames_elapsed(Duration::from_secs(1)), 20.0);
+
+        let clock = PlaybackClock::new(10).with_speed(0.5);
+        assert_eq!(clock.frames_elapsed(Duration::from_secs(1)), 5.0);
+    }
+
+    /// Verify that each playback mode keeps the frame offset within the action
+    #[test]
+    fn frame_offset_per_mode() {
+        let tests = vec![
+            (PlaybackMode::Once, 3, 3.0),
+            (PlaybackMode::Once, 14, 10.0),
+            (PlaybackMode::Loop, 14, 4.0),
+            (PlaybackMode::Cycle, 14, 14.0),
+            (PlaybackMode::PingPong, 4, 4.0),
+            (PlaybackMode::PingPong, 14, 6.0),
+            (PlaybackMode::PingPong, 24, 4.0),
+        ];
+
+        for (idx, (mode, elapsed_secs, expected_offset)) in tests.into_iter().enumerate() {
+            let clock = PlaybackClock::new(1).with_mode(mode);
+            let offset = clock.frame_offset(Duration::from_secs(elapsed_secs), 10);
+
+            assert_eq!(offset.get(), expected_offset, "Test idx {}", idx);
+        }
+    }
+
+    /// Verify that reverse playback wraps around to the end of the action
+    #[test]
+    fn reverse_playback_loops() {
+        let clock = PlaybackClock::new(1).with_speed(-1.0);
+        let offset = clock.frame_offset(Duration::from_secs(3), 10);
+
+        assert_eq!(offset.get(), 7.0);
+    }
+}
@@ -13,7 +13,7 @@ use crate::BoneKeyframe;
 ///
 /// TODO: Binary search instead of linear
 pub fn get_surrounding_keyframes(
-    keyframes: &Vec<BoneKeyframe>,
+    keyframes: &[BoneKeyframe],
     current_frame: f32,
 ) -> (BoneKeyframe, BoneKeyframe) {
     let mut closest_lower = None;
@@ -0,0 +1,73 @@
+/// Where an action's keyframes came from when it was exported.
+///
+/// Actions that were keyframed directly don't have a source. Actions that only live in Blender's
+/// NLA editor, or that are driven by drivers, are baked by sampling every frame.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ActionSource {
+    /// Baked from a single NLA strip.
+    NlaStrip {
+        /// The name of the NLA track that the strip is on
+        track: String,
+        /// The name of the strip
+        strip: String,
+        /// The first frame of the strip in the scene's timeline
+        frame_start: u16,
+        /// The last frame of the strip in the scene's timeline, inclusive
+        frame_end: u16,
+    },
+    /// Baked from every NLA track and driver evaluated together.
+    NlaStack {
+        /// The first baked frame
+        frame_start: u16,
+        /// The last baked frame, inclusive
+        frame_end: u16,
+    },
+}
+
+impl ActionSource {
+    /// The range of frames in the scene's timeline that were baked, inclusive.
+    pub fn frame_range_inclusive(&self) -> (u16, u16) {
+        match self {
+            ActionSource::NlaStrip {
+                frame_start,
+                frame_end,
+                ..
+            }
+            | ActionSource::NlaStack {
+                frame_start,
+                frame_end,
+            } => (*frame_start, *frame_end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Action, ActionSource};
+
+    /// Verify that we can deserialize the source that the armature exporter writes for baked
+    /// NLA strips.
+    #[test]
+    fn deserialize_nla_strip_source() {
+        let action: Action = serde_json::from_str(
+            r#"{
+              "bone_keyframes": {"frame_range_inclusive": [10, 20], "keyframes": {}},
+              "pose_markers": {},
+              "source": {
+                "NlaStrip": {"track": "Locomotion", "strip": "Walk", "frame_start": 10, "frame_end": 20}
+              }
+            }"#,
+        )
+        .unwrap();
+
+        let source = action.source().unwrap();
+        assert_eq!(source.frame_range_inclusive(), (10, 20));
+        match source {
+            ActionSource::NlaStrip { track, strip, .. } => {
+                assert_eq!(track, "Locomotion");
+                assert_eq!(strip, "Walk");
+            }
+            _ => unreachable!(),
+        };
+    }
+}
@@ -5,6 +5,7 @@ use crate::serialize_hashmap_deterministic;
 
 pub use self::bone_keyframe::*;
 pub use self::sorted_keyframes::*;
+pub(crate) use self::sample::{key_time_to_sample, sample_keyframes};
 
 mod bone_keyframe;
 mod sample;
@@ -110,4 +111,10 @@ impl BoneKeyframes {
     pub(crate) fn keyframes_mut(&mut self) -> &mut HashMap<u8, SortedKeyframes> {
         &mut self.keyframes
     }
+
+    /// Remove the keyframes of every bone that doesn't pass the filter.
+    pub(crate) fn retain_bones(&mut self, mut filter: impl FnMut(u8) -> bool) {
+        self.keyframes.retain(|bone_idx, _| filter(*bone_idx));
+        self.update_frame_range_inclusive();
+    }
 }
@@ -5,12 +5,26 @@ use crate::Bone;
 pub struct BoneKeyframe {
     frame: u16,
     bone: Bone,
+    /// The bone's scale along its local X, Y and Z axes.
+    ///
+    /// Kept separate from the bone since dual quaternions can't represent non uniform scale.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scale: Option<[f32; 3]>,
 }
 
 #[allow(missing_docs)]
 impl BoneKeyframe {
     pub fn new(frame: u16, bone: Bone) -> Self {
-        BoneKeyframe { frame, bone }
+        BoneKeyframe {
+            frame,
+            bone,
+            scale: None,
+        }
+    }
+
+    pub fn with_scale(mut self, scale: [f32; 3]) -> Self {
+        self.scale = Some(scale);
+        self
     }
 
     pub fn frame(&self) -> u16 {
@@ -28,4 +42,12 @@ impl BoneKeyframe {
     pub fn set_bone(&mut self, bone: Bone) {
         self.bone = bone;
     }
+
+    pub fn scale(&self) -> Option<[f32; 3]> {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: Option<[f32; 3]>) {
+        self.scale = scale;
+    }
 }
@@ -1,42 +1,243 @@
 use crate::action::get_surrounding_keyframes;
-use crate::{interpolate_bone, Bone, BoneKeyframes, SampleDesc};
+use crate::{
+    interpolate_bone, interpolate_bone_cubic, Bone, BoneKeyframe, BoneKeyframes, Interpolation,
+    SampleDesc,
+};
 
 impl BoneKeyframes {
     /// Sample the bone transforms
     pub fn sample(&self, joint_idx: u8, sample_desc: SampleDesc) -> Bone {
         let keyframes = self.keyframes.get(&joint_idx).unwrap();
 
-        let (lowest_keyframe, highest_keyframe) = self.frame_range_inclusive().unwrap();
+        sample_keyframes(
+            keyframes,
+            self.frame_range_inclusive().unwrap(),
+            sample_desc,
+        )
+    }
 
-        let mut frames_elapsed = sample_desc.frame_offset.get();
+    /// Sample the bone's scale.
+    ///
+    /// Scale is kept separate from the bone transforms since dual quaternions can't represent
+    /// non uniform scale. It is always interpolated linearly, regardless of the sample's
+    /// [`Interpolation`].
+    ///
+    /// Keyframes without a scale are treated as having a scale of `[1.0, 1.0, 1.0]`.
+    ///
+    /// Returns `None` if none of the bone's keyframes have a scale.
+    ///
+    /// [`Interpolation`]: enum.Interpolation.html
+    pub fn sample_scale(&self, joint_idx: u8, sample_desc: SampleDesc) -> Option<[f32; 3]> {
+        let keyframes = self.keyframes.get(&joint_idx).unwrap();
 
-        let mut key_time_to_sample = lowest_keyframe as f32 + frames_elapsed;
+        if keyframes.iter().all(|keyframe| keyframe.scale().is_none()) {
+            return None;
+        }
 
-        let action_duration = (highest_keyframe - lowest_keyframe) as f32;
+        let span = keyframe_span(
+            keyframes,
+            self.frame_range_inclusive().unwrap(),
+            sample_desc,
+        );
 
-        if frames_elapsed > action_duration {
-            if sample_desc.should_loop {
-                frames_elapsed = frames_elapsed % action_duration;
-            } else {
-                frames_elapsed = action_duration;
-            }
+        let lower = keyframes[span.lower_idx].scale().unwrap_or([1.0; 3]);
+        let upper = keyframes[span.upper_idx].scale().unwrap_or([1.0; 3]);
 
-            key_time_to_sample = lowest_keyframe as f32 + frames_elapsed;
+        let mut scale = [0.0; 3];
+        for axis in 0..3 {
+            scale[axis] = lower[axis] + (upper[axis] - lower[axis]) * span.amount;
         }
 
-        let (action_lower_keyframe, action_upper_keyframe) =
-            get_surrounding_keyframes(keyframes, key_time_to_sample);
+        Some(scale)
+    }
+}
+
+/// Sample one bone's keyframes, where the frame range is the range of the whole action.
+pub(crate) fn sample_keyframes(
+    keyframes: &[BoneKeyframe],
+    frame_range_inclusive: (u16, u16),
+    sample_desc: SampleDesc,
+) -> Bone {
+    let span = keyframe_span(keyframes, frame_range_inclusive, sample_desc);
+
+    interpolate(keyframes, span, sample_desc.interpolation)
+}
+
+/// Find the two keyframes to interpolate in between.
+fn keyframe_span(
+    keyframes: &[BoneKeyframe],
+    frame_range_inclusive: (u16, u16),
+    sample_desc: SampleDesc,
+) -> KeyframeSpan {
+    let (lowest_keyframe, highest_keyframe) = frame_range_inclusive;
+
+    if sample_desc.looping {
+        return wrapping_span(
+            keyframes,
+            lowest_keyframe,
+            highest_keyframe,
+            sample_desc.frame_offset.get(),
+        );
+    }
+
+    let key_time_to_sample = key_time_to_sample(frame_range_inclusive, sample_desc);
+
+    surrounding_keyframes_span(keyframes, key_time_to_sample, false)
+}
+
+/// The frame to sample when the action isn't being played as a cycle.
+pub(crate) fn key_time_to_sample(
+    frame_range_inclusive: (u16, u16),
+    sample_desc: SampleDesc,
+) -> f32 {
+    let (lowest_keyframe, highest_keyframe) = frame_range_inclusive;
+
+    let mut frames_elapsed = sample_desc.frame_offset.get();
+
+    let action_duration = (highest_keyframe - lowest_keyframe) as f32;
 
-        let percent_elapsed_into_keyframe = if action_lower_keyframe == action_upper_keyframe {
-            0.0
+    if frames_elapsed > action_duration {
+        if sample_desc.should_loop {
+            frames_elapsed = frames_elapsed % action_duration;
         } else {
-            (key_time_to_sample - action_lower_keyframe.frame() as f32)
-                / (action_upper_keyframe.frame() - action_lower_keyframe.frame()) as f32
-        };
+            frames_elapsed = action_duration;
+        }
+    }
+
+    lowest_keyframe as f32 + frames_elapsed
+}
+
+/// The keyframes to interpolate between, and how far to interpolate from the lower keyframe
+/// to the upper keyframe.
+#[derive(Debug, Copy, Clone)]
+struct KeyframeSpan {
+    lower_idx: usize,
+    upper_idx: usize,
+    amount: f32,
+    /// Whether the keyframes are played as a cycle, so the keyframe after the last keyframe is
+    /// the first keyframe.
+    wrap: bool,
+}
 
-        let lower_bone = action_lower_keyframe.bone();
-        let upper_bone = action_upper_keyframe.bone();
+/// Sample an action as a cycle, where the frame after the highest frame is the lowest frame.
+///
+/// Times that fall after a bone's last keyframe (or before its first keyframe) are interpolated
+/// between the last keyframe and the first keyframe of the next (or previous) cycle.
+fn wrapping_span(
+    keyframes: &[BoneKeyframe],
+    lowest_frame: u16,
+    highest_frame: u16,
+    frames_elapsed: f32,
+) -> KeyframeSpan {
+    let period = (highest_frame - lowest_frame) as f32 + 1.0;
 
-        interpolate_bone(lower_bone, upper_bone, percent_elapsed_into_keyframe)
+    let key_time_to_sample = lowest_frame as f32 + frames_elapsed.rem_euclid(period);
+
+    let last_idx = keyframes.len() - 1;
+    let first = keyframes[0];
+    let last = keyframes[last_idx];
+
+    let (first_frame, last_frame) = (first.frame() as f32, last.frame() as f32);
+
+    let wrapped_span = |amount: f32| KeyframeSpan {
+        lower_idx: last_idx,
+        upper_idx: 0,
+        amount,
+        wrap: true,
+    };
+
+    if key_time_to_sample >= last_frame {
+        let wrapped_first_frame = first_frame + period;
+        let amount = (key_time_to_sample - last_frame) / (wrapped_first_frame - last_frame);
+
+        return wrapped_span(amount);
+    }
+
+    if key_time_to_sample < first_frame {
+        let wrapped_last_frame = last_frame - period;
+        let amount = (key_time_to_sample - wrapped_last_frame) / (first_frame - wrapped_last_frame);
+
+        return wrapped_span(amount);
+    }
+
+    surrounding_keyframes_span(keyframes, key_time_to_sample, true)
+}
+
+fn surrounding_keyframes_span(
+    keyframes: &[BoneKeyframe],
+    key_time_to_sample: f32,
+    wrap: bool,
+) -> KeyframeSpan {
+    let (action_lower_keyframe, action_upper_keyframe) =
+        get_surrounding_keyframes(keyframes, key_time_to_sample);
+
+    let percent_elapsed_into_keyframe = if action_lower_keyframe == action_upper_keyframe {
+        0.0
+    } else {
+        (key_time_to_sample - action_lower_keyframe.frame() as f32)
+            / (action_upper_keyframe.frame() - action_lower_keyframe.frame()) as f32
+    };
+
+    let keyframe_idx = |keyframe: BoneKeyframe| {
+        keyframes
+            .iter()
+            .position(|k| k.frame() == keyframe.frame())
+            .unwrap()
+    };
+
+    KeyframeSpan {
+        lower_idx: keyframe_idx(action_lower_keyframe),
+        upper_idx: keyframe_idx(action_upper_keyframe),
+        amount: percent_elapsed_into_keyframe,
+        wrap,
+    }
+}
+
+/// Interpolate between two of the keyframes.
+///
+/// Cubic interpolation also uses the keyframes before the lower keyframe and after the upper
+/// keyframe. At the ends of the action these wrap around if the action is being played as a
+/// cycle, otherwise the first or last keyframe is repeated.
+fn interpolate(
+    keyframes: &[BoneKeyframe],
+    span: KeyframeSpan,
+    interpolation: Interpolation,
+) -> Bone {
+    let KeyframeSpan {
+        lower_idx,
+        upper_idx,
+        amount,
+        wrap,
+    } = span;
+
+    let lower_bone = keyframes[lower_idx].bone();
+    let upper_bone = keyframes[upper_idx].bone();
+
+    match interpolation {
+        Interpolation::Linear => interpolate_bone(lower_bone, upper_bone, amount),
+        Interpolation::Cubic => {
+            let last_idx = keyframes.len() - 1;
+
+            let before_idx = match lower_idx {
+                0 if wrap => last_idx,
+                0 => 0,
+                _ => lower_idx - 1,
+            };
+            let after_idx = if upper_idx < last_idx {
+                upper_idx + 1
+            } else if wrap {
+                0
+            } else {
+                last_idx
+            };
+
+            interpolate_bone_cubic(
+                keyframes[before_idx].bone(),
+                lower_bone,
+                upper_bone,
+                keyframes[after_idx].bone(),
+                amount,
+            )
+        }
     }
 }
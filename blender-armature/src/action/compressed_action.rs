@@ -0,0 +1,400 @@
+use crate::action::bone_keyframes::sample_keyframes;
+use crate::{Action, ActionSource, BlenderArmature, Bone, BoneKeyframe, SampleDesc};
+use nalgebra::{DualQuaternion, Quaternion, Vector3};
+use std::collections::{BTreeMap, HashMap};
+
+/// The largest value that a quantized rotation component can have. Components get 15 bits, with
+/// the 16th bit of the first two components storing which component was dropped.
+const ROTATION_COMPONENT_MAX: f32 = 32767.;
+
+/// The smallest three components of a unit quaternion are always within ±1/√2.
+const ROTATION_COMPONENT_RANGE: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// An action whose keyframes are stored in a quarter of the space, for games with large movesets
+/// that need to keep many actions in memory.
+///
+/// Every rotation is stored in 6 bytes using the smallest three encoding, and every translation
+/// and scale in 6 bytes as half precision floats. Frames take 2 bytes. That is 14 bytes per
+/// keyframe instead of the 64 bytes of a matrix or the 32 bytes of a dual quaternion.
+///
+/// Rotations are accurate to within about 0.00005 per component and translations to within
+/// 0.05% of their magnitude, which isn't visible on typical characters. Translations larger than
+/// 65504 are clamped.
+///
+/// Keyframes are decoded as they are sampled with [`CompressedAction.method#sample`], or all at
+/// once with [`CompressedAction.method#decompress`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompressedAction {
+    frame_range_inclusive: Option<(u16, u16)>,
+    bones: BTreeMap<u8, CompressedBoneKeyframes>,
+    #[serde(default)]
+    pose_markers: HashMap<u16, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source: Option<ActionSource>,
+}
+
+/// The compressed keyframes of one bone, with one entry per keyframe in every list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompressedBoneKeyframes {
+    frames: Vec<u16>,
+    /// Smallest three encoded rotations
+    rotations: Vec<[u16; 3]>,
+    /// Half precision translations
+    translations: Vec<[u16; 3]>,
+    /// Half precision scales, if any of the bone's keyframes had a scale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scales: Option<Vec<[u16; 3]>>,
+    /// Whether the keyframes were matrices, so that they are decompressed as matrices.
+    #[serde(default)]
+    matrix: bool,
+}
+
+impl Action {
+    /// Compress the action's keyframes. See [`CompressedAction`].
+    ///
+    /// Bones are expected to be rigid transforms, with any scale in the keyframes' scales.
+    ///
+    /// [`CompressedAction`]: struct.CompressedAction.html
+    pub fn compress(&self) -> CompressedAction {
+        let bones = self
+            .bone_keyframes
+            .iter()
+            .map(|(joint_idx, keyframes)| {
+                let has_scale = keyframes.iter().any(|keyframe| keyframe.scale().is_some());
+                let matrix = keyframes
+                    .iter()
+                    .any(|keyframe| matches!(keyframe.bone(), Bone::Matrix(_)));
+
+                let mut compressed = CompressedBoneKeyframes {
+                    frames: vec![],
+                    rotations: vec![],
+                    translations: vec![],
+                    scales: match has_scale {
+                        true => Some(vec![]),
+                        false => None,
+                    },
+                    matrix,
+                };
+
+                for keyframe in keyframes.iter() {
+                    let (rotation, translation) = rotation_and_translation(&keyframe.bone());
+
+                    compressed.frames.push(keyframe.frame());
+                    compressed.rotations.push(encode_rotation(rotation));
+                    compressed
+                        .translations
+                        .push(encode_vector(translation.into()));
+                    if let Some(scales) = compressed.scales.as_mut() {
+                        scales.push(encode_vector(keyframe.scale().unwrap_or([1.; 3])));
+                    }
+                }
+
+                (*joint_idx, compressed)
+            })
+            .collect();
+
+        CompressedAction {
+            frame_range_inclusive: self.bone_keyframes.frame_range_inclusive(),
+            bones,
+            pose_markers: self.pose_markers().clone(),
+            source: self.source().cloned(),
+        }
+    }
+}
+
+impl CompressedAction {
+    /// Sample a bone, decoding only that bone's keyframes.
+    ///
+    /// Bones are always sampled as dual quaternions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the action doesn't have keyframes for the bone, the same as
+    /// [`BoneKeyframes.method#sample`].
+    pub fn sample(&self, joint_idx: u8, sample_desc: SampleDesc) -> Bone {
+        let keyframes = self.bones[&joint_idx].decode(false);
+
+        sample_keyframes(&keyframes, self.frame_range_inclusive.unwrap(), sample_desc)
+    }
+
+    /// Decode every keyframe back into an [`Action`].
+    ///
+    /// [`Action`]: struct.Action.html
+    pub fn decompress(&self) -> Action {
+        let mut action = Action::new();
+
+        for (joint_idx, bone) in self.bones.iter() {
+            for keyframe in bone.decode(bone.matrix) {
+                action.insert_bone_keyframe(*joint_idx, keyframe);
+            }
+        }
+        *action.pose_markers_mut() = self.pose_markers.clone();
+        action.set_source(self.source.clone());
+
+        action
+    }
+
+    /// The first and last keyframe of the action, or `None` if the action doesn't have any
+    /// keyframes.
+    pub fn frame_range_inclusive(&self) -> Option<(u16, u16)> {
+        self.frame_range_inclusive
+    }
+
+    /// Joint index -> the bone's compressed keyframes
+    pub fn bones(&self) -> &BTreeMap<u8, CompressedBoneKeyframes> {
+        &self.bones
+    }
+}
+
+impl CompressedBoneKeyframes {
+    /// The number of keyframes
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether there aren't any keyframes
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The number of bytes that the keyframes take up.
+    pub fn byte_len(&self) -> usize {
+        let per_keyframe = match self.scales.is_some() {
+            true => 20,
+            false => 14,
+        };
+
+        self.frames.len() * per_keyframe
+    }
+
+    fn decode(&self, matrix: bool) -> Vec<BoneKeyframe> {
+        let mut keyframes = Vec::with_capacity(self.frames.len());
+
+        for (idx, frame) in self.frames.iter().enumerate() {
+            let rotation = decode_rotation(self.rotations[idx]);
+            let translation = Vector3::from(decode_vector(self.translations[idx]));
+
+            let dual = Quaternion::from_imag(translation) * rotation * 0.5;
+            let bone = Bone::DualQuat(DualQuaternion::from_real_and_dual(rotation, dual));
+            let bone = match matrix {
+                true => BlenderArmature::dual_quat_to_matrix(&bone),
+                false => bone,
+            };
+
+            let mut keyframe = BoneKeyframe::new(*frame, bone);
+            if let Some(scales) = self.scales.as_ref() {
+                keyframe.set_scale(Some(decode_vector(scales[idx])));
+            }
+
+            keyframes.push(keyframe);
+        }
+
+        keyframes
+    }
+}
+
+/// The unit rotation quaternion and the translation of a rigid transform.
+fn rotation_and_translation(bone: &Bone) -> (Quaternion<f32>, Vector3<f32>) {
+    let dual_quat = match BlenderArmature::matrix_to_dual_quat(bone) {
+        Bone::DualQuat(dual_quat) => dual_quat,
+        Bone::Matrix(_) => unreachable!(),
+    };
+
+    let norm = dual_quat.real.norm();
+    let (real, dual) = (dual_quat.real / norm, dual_quat.dual / norm);
+
+    let translation = (dual * real.conjugate() * 2.).imag();
+
+    (real, translation)
+}
+
+/// Drop the largest component, which can be recovered since the quaternion has unit length, and
+/// store the other three in 15 bits each. The index of the dropped component goes in the top bits
+/// of the first two components.
+fn encode_rotation(rotation: Quaternion<f32>) -> [u16; 3] {
+    let coords = rotation.coords;
+
+    let largest = (0..4)
+        .max_by(|a, b| coords[*a].abs().partial_cmp(&coords[*b].abs()).unwrap())
+        .unwrap();
+    // q and -q are the same rotation, so we make the dropped component positive
+    let sign = match coords[largest] < 0. {
+        true => -1.,
+        false => 1.,
+    };
+
+    let mut encoded = [0; 3];
+    let smallest = (0..4).filter(|idx| *idx != largest);
+    for (encoded, idx) in encoded.iter_mut().zip(smallest) {
+        let normalized = (coords[idx] * sign / ROTATION_COMPONENT_RANGE) * 0.5 + 0.5;
+        *encoded = (normalized.clamp(0., 1.) * ROTATION_COMPONENT_MAX).round() as u16;
+    }
+
+    encoded[0] |= ((largest & 1) as u16) << 15;
+    encoded[1] |= ((largest >> 1) as u16) << 15;
+
+    encoded
+}
+
+fn decode_rotation(encoded: [u16; 3]) -> Quaternion<f32> {
+    let largest = (encoded[0] >> 15) as usize | ((encoded[1] >> 15) as usize) << 1;
+
+    let mut coords = [0.; 4];
+    let mut sum_of_squares = 0.;
+    let smallest = (0..4).filter(|idx| *idx != largest);
+    for (encoded, idx) in encoded.iter().zip(smallest) {
+        let normalized = (encoded & 0x7fff) as f32 / ROTATION_COMPONENT_MAX;
+        coords[idx] = (normalized - 0.5) * 2. * ROTATION_COMPONENT_RANGE;
+        sum_of_squares += coords[idx] * coords[idx];
+    }
+    coords[largest] = (1. - sum_of_squares).max(0.).sqrt();
+
+    // nalgebra stores quaternions as i, j, k, w
+    Quaternion::new(coords[3], coords[0], coords[1], coords[2]).normalize()
+}
+
+fn encode_vector(vector: [f32; 3]) -> [u16; 3] {
+    [
+        f32_to_f16(vector[0]),
+        f32_to_f16(vector[1]),
+        f32_to_f16(vector[2]),
+    ]
+}
+
+fn decode_vector(encoded: [u16; 3]) -> [f32; 3] {
+    [
+        f16_to_f32(encoded[0]),
+        f16_to_f32(encoded[1]),
+        f16_to_f32(encoded[2]),
+    ]
+}
+
+/// Convert to a half precision float, rounding to the nearest value. Values that are too large
+/// are clamped to the largest half precision float.
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exponent == 0xff {
+        return match mantissa {
+            0 => sign | 0x7bff,
+            _ => sign | 0x7e00,
+        };
+    }
+
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7bff;
+    }
+
+    if exponent <= 0 {
+        // Too small for a normal half precision float
+        if exponent < -10 {
+            return sign;
+        }
+
+        let mantissa = mantissa | 0x80_0000;
+        let shift = (14 - exponent) as u32;
+        let rounding = (mantissa >> (shift - 1)) & 1;
+        return sign | ((mantissa >> shift) + rounding) as u16;
+    }
+
+    let half = ((exponent as u32) << 10) | (mantissa >> 13);
+    let rounding = (mantissa >> 12) & 1;
+    // Rounding up can carry into the exponent, which gives the next power of two as expected
+    (sign as u32 | (half + rounding).min(0x7bff)) as u16
+}
+
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = match half & 0x8000 {
+        0 => 1.,
+        _ => -1.,
+    };
+    let exponent = ((half >> 10) & 0x1f) as i32;
+    let mantissa = (half & 0x3ff) as f32;
+
+    match exponent {
+        0 => sign * mantissa * 2f32.powi(-24),
+        0x1f => match mantissa == 0. {
+            true => sign * f32::INFINITY,
+            false => f32::NAN,
+        },
+        _ => sign * (1. + mantissa / 1024.) * 2f32.powi(exponent - 15),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FrameOffset, Interpolation};
+    use nalgebra::{Matrix4, UnitQuaternion};
+
+    /// Verify that compressed keyframes decode to nearly the same transforms, both when sampling
+    /// and when decompressing, in a fraction of the space.
+    #[test]
+    fn compress_and_sample() {
+        let mut action = Action::new();
+        for frame in 0..10u16 {
+            let rotation = UnitQuaternion::from_euler_angles(frame as f32 * 0.3, -1.2, 0.4);
+            let translation = Vector3::new(frame as f32 * 0.25, -3.5, 0.01);
+            let matrix = Matrix4::new_translation(&translation) * rotation.to_homogeneous();
+
+            action.insert_bone_keyframe(
+                0,
+                BoneKeyframe::new(frame, Bone::Matrix(matrix)).with_scale([1., 1.5, 1.]),
+            );
+        }
+
+        let compressed = action.compress();
+        assert_eq!(compressed.bones()[&0].byte_len(), 200);
+
+        let decompressed = action_matrices(&compressed.decompress());
+        for (original, decompressed) in action_matrices(&action).iter().zip(decompressed.iter()) {
+            assert!((original - decompressed).amax() < 0.002);
+        }
+        let scale = compressed.decompress().bone_keyframes()[&0][3].scale();
+        assert_eq!(scale, Some([1., 1.5, 1.]));
+
+        let sample_desc = SampleDesc {
+            frame_offset: FrameOffset::new(4.5),
+            should_loop: false,
+            looping: false,
+            interpolation: Interpolation::Linear,
+        };
+        let mut dual_quat_action = action.clone();
+        for keyframes in dual_quat_action.keyframes_mut().values_mut() {
+            for keyframe in keyframes.iter_mut() {
+                keyframe.set_bone(BlenderArmature::matrix_to_dual_quat(&keyframe.bone()));
+            }
+        }
+        let expected = dual_quat_action.bone_keyframes().sample(0, sample_desc);
+        let sampled = compressed.sample(0, sample_desc);
+        assert!(
+            (expected.to_matrix() - sampled.to_matrix()).amax() < 0.002,
+            "{:?} {:?}",
+            expected,
+            sampled
+        );
+    }
+
+    /// Verify that half precision floats round trip and clamp values that are too large.
+    #[test]
+    fn half_precision_floats() {
+        for value in [0., -0., 1., -2.5, 0.000123, 1000.25, 65504.].iter() {
+            let round_tripped = f16_to_f32(f32_to_f16(*value));
+            assert!((round_tripped - value).abs() <= value.abs() / 1024.);
+        }
+
+        assert_eq!(f16_to_f32(f32_to_f16(1e9)), 65504.);
+        assert_eq!(f16_to_f32(f32_to_f16(-1e9)), -65504.);
+    }
+
+    fn action_matrices(action: &Action) -> Vec<Matrix4<f32>> {
+        action.bone_keyframes()[&0]
+            .iter()
+            .map(|keyframe| keyframe.bone().to_matrix())
+            .collect()
+    }
+}
@@ -0,0 +1,440 @@
+use crate::action::bone_keyframes::key_time_to_sample;
+use crate::{
+    interpolate_dual_quats, Action, ActionSource, BlenderArmature, Bone, SampleDesc,
+    SortedKeyframes,
+};
+use nalgebra::{DualQuaternion, Quaternion};
+use std::collections::{BTreeMap, HashMap};
+
+/// An action whose keyframes have been replaced by cubic curves, for baked actions that have a
+/// keyframe on every frame.
+///
+/// Baking constraints and drivers in Blender gives every bone a keyframe on every frame even
+/// when the motion is smooth, so a long action on a large armature ends up with a lot of
+/// keyframes. Fitting curves to them keeps only as many knots as are needed to stay within an
+/// error tolerance.
+///
+/// Created with [`Action.method#fit_curves`] and sampled with [`CurveAction.method#sample`],
+/// which evaluates the curves directly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurveAction {
+    frame_range_inclusive: Option<(u16, u16)>,
+    bones: BTreeMap<u8, CurveBoneTrack>,
+    #[serde(default)]
+    pose_markers: HashMap<u16, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source: Option<ActionSource>,
+}
+
+/// The curves of one bone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurveBoneTrack {
+    /// One curve for each component of the bone's dual quaternion. The real part's i, j, k and w
+    /// followed by the dual part's i, j, k and w.
+    transform: Vec<CurveChannel>,
+    /// One curve for each axis of the bone's scale, if any of the bone's keyframes had a scale.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scale: Option<Vec<CurveChannel>>,
+}
+
+/// A cubic Hermite spline through a channel's knots.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurveChannel {
+    knots: Vec<CurveKnot>,
+}
+
+/// A point that a curve passes through.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurveKnot {
+    /// The frame of the keyframe that the knot was placed on
+    pub frame: u16,
+    /// The value of the curve at the knot
+    pub value: f32,
+    /// How much the value changes per frame at the knot
+    pub tangent: f32,
+}
+
+/// Where to evaluate a bone's curves.
+enum CurveTime {
+    /// Evaluate the curves at this frame.
+    Frame(f32),
+    /// The sample is in between two cycles of a looping action, so blend this far from the
+    /// bone's last knot towards its first knot.
+    Wrapped(f32),
+}
+
+impl Action {
+    /// Fit cubic curves to the action's keyframes. See [`CurveAction`].
+    ///
+    /// Every component of every bone's dual quaternion and scale gets its own curve, and knots
+    /// are added to a curve until none of the keyframes are further than `tolerance` from it.
+    /// Channels that are constant or that change linearly only need two knots.
+    ///
+    /// A tolerance of around `0.0001` isn't visible on typical characters. Matrix keyframes are
+    /// converted to dual quaternions first, so bones are expected to be rigid transforms.
+    ///
+    /// [`CurveAction`]: struct.CurveAction.html
+    pub fn fit_curves(&self, tolerance: f32) -> CurveAction {
+        let bones = self
+            .bone_keyframes
+            .iter()
+            .map(|(joint_idx, keyframes)| (*joint_idx, CurveBoneTrack::fit(keyframes, tolerance)))
+            .collect();
+
+        CurveAction {
+            frame_range_inclusive: self.bone_keyframes.frame_range_inclusive(),
+            bones,
+            pose_markers: self.pose_markers().clone(),
+            source: self.source().cloned(),
+        }
+    }
+}
+
+impl CurveAction {
+    /// Sample a bone by evaluating its curves.
+    ///
+    /// Bones are always sampled as dual quaternions. The curves are always cubic, so the sample's
+    /// [`Interpolation`] is ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the action doesn't have curves for the bone, the same as
+    /// [`BoneKeyframes.method#sample`].
+    ///
+    /// [`Interpolation`]: enum.Interpolation.html
+    pub fn sample(&self, joint_idx: u8, sample_desc: SampleDesc) -> Bone {
+        let track = &self.bones[&joint_idx];
+
+        let dual_quat = match self.curve_time(track, sample_desc) {
+            CurveTime::Frame(frame) => track.dual_quat_at(frame),
+            CurveTime::Wrapped(amount) => {
+                let (first_frame, last_frame) = track.frame_range_inclusive();
+
+                interpolate_dual_quats(
+                    track.dual_quat_at(last_frame),
+                    track.dual_quat_at(first_frame),
+                    amount,
+                )
+            }
+        };
+
+        Bone::DualQuat(dual_quat)
+    }
+
+    /// Sample a bone's scale. See [`BoneKeyframes.method#sample_scale`].
+    ///
+    /// Returns `None` if none of the bone's keyframes had a scale.
+    pub fn sample_scale(&self, joint_idx: u8, sample_desc: SampleDesc) -> Option<[f32; 3]> {
+        let track = &self.bones[&joint_idx];
+        let scale = track.scale.as_ref()?;
+
+        let mut sampled = [0.; 3];
+        for (axis, channel) in sampled.iter_mut().zip(scale.iter()) {
+            *axis = match self.curve_time(track, sample_desc) {
+                CurveTime::Frame(frame) => channel.evaluate(frame),
+                CurveTime::Wrapped(amount) => {
+                    let (first, last) = (channel.knots[0], channel.knots[channel.knots.len() - 1]);
+                    last.value + (first.value - last.value) * amount
+                }
+            };
+        }
+
+        Some(sampled)
+    }
+
+    /// The first and last keyframe of the action that the curves were fit to, or `None` if the
+    /// action didn't have any keyframes.
+    pub fn frame_range_inclusive(&self) -> Option<(u16, u16)> {
+        self.frame_range_inclusive
+    }
+
+    /// Joint index -> the bone's curves
+    pub fn bones(&self) -> &BTreeMap<u8, CurveBoneTrack> {
+        &self.bones
+    }
+
+    /// Labeled frame times for the action. See [`Action.method#pose_markers`].
+    pub fn pose_markers(&self) -> &HashMap<u16, String> {
+        &self.pose_markers
+    }
+
+    /// Where the action was baked from. See [`Action.method#source`].
+    pub fn source(&self) -> Option<&ActionSource> {
+        self.source.as_ref()
+    }
+
+    /// Mirrors how [`BoneKeyframes.method#sample`] picks the keyframes to interpolate between.
+    fn curve_time(&self, track: &CurveBoneTrack, sample_desc: SampleDesc) -> CurveTime {
+        let frame_range_inclusive = self.frame_range_inclusive.unwrap();
+
+        if !sample_desc.looping {
+            return CurveTime::Frame(key_time_to_sample(frame_range_inclusive, sample_desc));
+        }
+
+        let (lowest_frame, highest_frame) = frame_range_inclusive;
+        let period = (highest_frame - lowest_frame) as f32 + 1.0;
+        let frame = lowest_frame as f32 + sample_desc.frame_offset.get().rem_euclid(period);
+
+        let (first_frame, last_frame) = track.frame_range_inclusive();
+
+        if frame >= last_frame {
+            let wrapped_first_frame = first_frame + period;
+            return CurveTime::Wrapped((frame - last_frame) / (wrapped_first_frame - last_frame));
+        }
+
+        if frame < first_frame {
+            let wrapped_last_frame = last_frame - period;
+            return CurveTime::Wrapped(
+                (frame - wrapped_last_frame) / (first_frame - wrapped_last_frame),
+            );
+        }
+
+        CurveTime::Frame(frame)
+    }
+}
+
+impl CurveBoneTrack {
+    fn fit(keyframes: &SortedKeyframes, tolerance: f32) -> Self {
+        let mut previous: Option<DualQuaternion<f32>> = None;
+        let dual_quats: Vec<DualQuaternion<f32>> = keyframes
+            .iter()
+            .map(|keyframe| {
+                let mut dual_quat = match BlenderArmature::matrix_to_dual_quat(&keyframe.bone()) {
+                    Bone::DualQuat(dual_quat) => dual_quat,
+                    Bone::Matrix(_) => unreachable!(),
+                };
+
+                // q and -q are the same transform, so we keep neighboring keyframes on the same
+                // side in order for every channel to be continuous
+                if let Some(previous) = previous {
+                    if previous.real.dot(&dual_quat.real) < 0. {
+                        dual_quat *= -1.;
+                    }
+                }
+                previous = Some(dual_quat);
+
+                dual_quat
+            })
+            .collect();
+
+        let channel = |value: &dyn Fn(usize) -> f32| {
+            let samples: Vec<(u16, f32)> = keyframes
+                .iter()
+                .enumerate()
+                .map(|(idx, keyframe)| (keyframe.frame(), value(idx)))
+                .collect();
+
+            CurveChannel::fit(&samples, tolerance)
+        };
+
+        let mut transform = vec![];
+        for component in 0..4 {
+            transform.push(channel(&|idx| dual_quats[idx].real.coords[component]));
+        }
+        for component in 0..4 {
+            transform.push(channel(&|idx| dual_quats[idx].dual.coords[component]));
+        }
+
+        let has_scale = keyframes.iter().any(|keyframe| keyframe.scale().is_some());
+        let scale = match has_scale {
+            true => Some(
+                (0..3)
+                    .map(|axis| channel(&|idx| keyframes[idx].scale().unwrap_or([1.; 3])[axis]))
+                    .collect(),
+            ),
+            false => None,
+        };
+
+        CurveBoneTrack { transform, scale }
+    }
+
+    /// The curves for the real part's i, j, k and w followed by the dual part's i, j, k and w.
+    pub fn transform(&self) -> &[CurveChannel] {
+        &self.transform
+    }
+
+    /// The curves for the scale's x, y and z, if any of the bone's keyframes had a scale.
+    pub fn scale(&self) -> Option<&[CurveChannel]> {
+        self.scale.as_deref()
+    }
+
+    /// The number of knots across all of the bone's curves.
+    pub fn knot_count(&self) -> usize {
+        self.transform
+            .iter()
+            .chain(self.scale.iter().flatten())
+            .map(|channel| channel.knots.len())
+            .sum()
+    }
+
+    /// Every curve starts at the bone's first keyframe and ends at its last keyframe.
+    fn frame_range_inclusive(&self) -> (f32, f32) {
+        let knots = &self.transform[0].knots;
+        (knots[0].frame as f32, knots[knots.len() - 1].frame as f32)
+    }
+
+    fn dual_quat_at(&self, frame: f32) -> DualQuaternion<f32> {
+        let value = |component: usize| self.transform[component].evaluate(frame);
+
+        let real = Quaternion::new(value(3), value(0), value(1), value(2));
+        let dual = Quaternion::new(value(7), value(4), value(5), value(6));
+
+        // Curves don't keep the dual quaternion at unit length in between knots
+        let norm = real.norm();
+        DualQuaternion::from_real_and_dual(real / norm, dual / norm)
+    }
+}
+
+impl CurveChannel {
+    /// Keep adding a knot at the sample that is furthest from the curve until every sample is
+    /// within the tolerance.
+    fn fit(samples: &[(u16, f32)], tolerance: f32) -> Self {
+        let knot = |idx: usize| CurveKnot {
+            frame: samples[idx].0,
+            value: samples[idx].1,
+            tangent: tangent(samples, idx),
+        };
+
+        let last_idx = samples.len() - 1;
+
+        let mut knot_indices = vec![0];
+        if last_idx > 0 {
+            knot_indices.push(last_idx);
+        }
+
+        let mut segments = vec![(0, last_idx)];
+        while let Some((lower, upper)) = segments.pop() {
+            let (lower_knot, upper_knot) = (knot(lower), knot(upper));
+
+            let furthest = (lower + 1..upper)
+                .map(|idx| {
+                    let (frame, value) = samples[idx];
+                    let error = (hermite(&lower_knot, &upper_knot, frame as f32) - value).abs();
+                    (idx, error)
+                })
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+            if let Some((idx, error)) = furthest {
+                if error > tolerance {
+                    knot_indices.push(idx);
+                    segments.push((lower, idx));
+                    segments.push((idx, upper));
+                }
+            }
+        }
+
+        knot_indices.sort_unstable();
+
+        CurveChannel {
+            knots: knot_indices.into_iter().map(knot).collect(),
+        }
+    }
+
+    /// The value of the curve at a frame. Frames before the first knot or after the last knot get
+    /// the first or last knot's value.
+    pub fn evaluate(&self, frame: f32) -> f32 {
+        let first = self.knots[0];
+        let last = self.knots[self.knots.len() - 1];
+
+        if frame <= first.frame as f32 {
+            return first.value;
+        }
+        if frame >= last.frame as f32 {
+            return last.value;
+        }
+
+        let upper_idx = self
+            .knots
+            .partition_point(|knot| knot.frame as f32 <= frame);
+
+        hermite(&self.knots[upper_idx - 1], &self.knots[upper_idx], frame)
+    }
+
+    /// The knots that the curve passes through, ordered by frame.
+    pub fn knots(&self) -> &[CurveKnot] {
+        &self.knots
+    }
+}
+
+/// The slope of the samples at a sample, using its neighbors.
+fn tangent(samples: &[(u16, f32)], idx: usize) -> f32 {
+    if samples.len() < 2 {
+        return 0.;
+    }
+
+    let before = idx.saturating_sub(1);
+    let after = (idx + 1).min(samples.len() - 1);
+
+    (samples[after].1 - samples[before].1) / (samples[after].0 - samples[before].0) as f32
+}
+
+fn hermite(lower: &CurveKnot, upper: &CurveKnot, frame: f32) -> f32 {
+    let span = (upper.frame - lower.frame) as f32;
+    let s = (frame - lower.frame as f32) / span;
+    let (s2, s3) = (s * s, s * s * s);
+
+    (2. * s3 - 3. * s2 + 1.) * lower.value
+        + (s3 - 2. * s2 + s) * span * lower.tangent
+        + (-2. * s3 + 3. * s2) * upper.value
+        + (s3 - s2) * span * upper.tangent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoneKeyframe, FrameOffset, Interpolation};
+    use nalgebra::{Matrix4, UnitQuaternion, Vector3};
+
+    /// Verify that curves fit to a keyframe on every frame need far fewer knots and sample to
+    /// nearly the same transforms, including in between cycles of a looping action.
+    #[test]
+    fn fit_and_sample_baked_keyframes() {
+        let mut action = Action::new();
+        for frame in 0..60u16 {
+            let angle = (frame as f32 / 59.) * std::f32::consts::PI;
+            let rotation = UnitQuaternion::from_euler_angles(angle.sin() * 0.5, angle, 0.2);
+            let translation = Vector3::new(frame as f32 * 0.1, 2., angle.cos());
+            let matrix = Matrix4::new_translation(&translation) * rotation.to_homogeneous();
+
+            action.insert_bone_keyframe(0, BoneKeyframe::new(frame, Bone::Matrix(matrix)));
+            action.insert_bone_keyframe(
+                1,
+                BoneKeyframe::new(frame, Bone::Matrix(Matrix4::identity())).with_scale([1.5; 3]),
+            );
+        }
+
+        let curves = action.fit_curves(0.0001);
+
+        assert!(curves.bones()[&0].knot_count() < 60 * 8 / 3);
+        assert_eq!(curves.bones()[&1].knot_count(), 22);
+
+        let mut dual_quat_action = action.clone();
+        for keyframes in dual_quat_action.keyframes_mut().values_mut() {
+            for keyframe in keyframes.iter_mut() {
+                keyframe.set_bone(BlenderArmature::matrix_to_dual_quat(&keyframe.bone()));
+            }
+        }
+
+        for (frame_offset, looping) in
+            [(0., false), (17.5, false), (41.25, false), (59.5, true)].iter()
+        {
+            let sample_desc = SampleDesc {
+                frame_offset: FrameOffset::new(*frame_offset),
+                should_loop: false,
+                looping: *looping,
+                interpolation: Interpolation::Linear,
+            };
+
+            let expected = dual_quat_action.bone_keyframes().sample(0, sample_desc);
+            let sampled = curves.sample(0, sample_desc);
+            assert!(
+                (expected.to_matrix() - sampled.to_matrix()).amax() < 0.002,
+                "{:?} {:?}",
+                expected,
+                sampled
+            );
+
+            assert_eq!(curves.sample_scale(1, sample_desc), Some([1.5; 3]));
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::BlenderArmature;
+use std::collections::HashMap;
+
+/// Which actions to export and what to name them, such as to leave out work in progress actions
+/// and to rename Blender's action names to the clip names that a game expects.
+///
+/// Filters match the action names in Blender. Renames are applied after filtering.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ActionFilter {
+    /// Only keep the actions with these names. Every action is kept if this is empty.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Leave out the actions whose names start with any of these prefixes, such as `WIP_`.
+    #[serde(default)]
+    pub exclude_prefixes: Vec<String>,
+    /// Blender action name -> the name to export the action as.
+    #[serde(default)]
+    pub renames: HashMap<String, String>,
+}
+
+impl ActionFilter {
+    /// Whether the action with this name in Blender gets exported.
+    pub fn is_included(&self, action_name: &str) -> bool {
+        let is_whitelisted =
+            self.include.is_empty() || self.include.iter().any(|name| name == action_name);
+        let is_excluded = self
+            .exclude_prefixes
+            .iter()
+            .any(|prefix| action_name.starts_with(prefix.as_str()));
+
+        is_whitelisted && !is_excluded
+    }
+
+    /// The name that the action with this name in Blender gets exported as.
+    pub fn renamed<'a>(&'a self, action_name: &'a str) -> &'a str {
+        self.renames
+            .get(action_name)
+            .map(String::as_str)
+            .unwrap_or(action_name)
+    }
+
+    /// Whether the filter keeps every action under its own name.
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude_prefixes.is_empty() && self.renames.is_empty()
+    }
+}
+
+impl BlenderArmature {
+    /// Remove and rename actions the same way that `landon export` does when it is given an
+    /// action filter, such as for armatures that were exported without one.
+    pub fn filter_actions(&mut self, filter: &ActionFilter) {
+        let actions = std::mem::take(&mut self.bone_space_actions);
+
+        for (name, action) in actions {
+            if filter.is_included(&name) {
+                self.bone_space_actions
+                    .insert(filter.renamed(&name).to_string(), action);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Action;
+
+    /// Verify that actions are filtered by their Blender names before they are renamed.
+    #[test]
+    fn filter_and_rename_actions() {
+        let mut armature = BlenderArmature::default();
+        for name in ["Walk", "Run", "WIP_Jump", "Idle"].iter() {
+            armature.insert_bone_space_action(name.to_string(), Action::new());
+        }
+
+        let mut filter = ActionFilter {
+            include: vec![
+                "Walk".to_string(),
+                "Run".to_string(),
+                "WIP_Jump".to_string(),
+            ],
+            exclude_prefixes: vec!["WIP_".to_string()],
+            renames: HashMap::new(),
+        };
+        filter
+            .renames
+            .insert("Walk".to_string(), "walk_cycle".to_string());
+
+        armature.filter_actions(&filter);
+
+        let mut names: Vec<&String> = armature.bone_space_actions().keys().collect();
+        names.sort();
+        assert_eq!(names, vec!["Run", "walk_cycle"]);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{BlenderArmature, Bone};
+use nalgebra::Matrix4;
+use std::collections::HashMap;
+
+/// A named point that follows one of the armature's bones, such as the muzzle of a gun, a
+/// character's eyes or a hit locator.
+///
+/// # Blender
+///
+/// Exported from every empty that is parented to one of the armature's bones, named after the
+/// empty.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AttachmentPoint {
+    bone: String,
+    offset: Bone,
+}
+
+impl AttachmentPoint {
+    #[allow(missing_docs)]
+    pub fn new(bone: String, offset: Bone) -> Self {
+        AttachmentPoint { bone, offset }
+    }
+
+    /// The name of the bone that the point follows.
+    pub fn bone(&self) -> &String {
+        &self.bone
+    }
+
+    /// The point's transform relative to its bone.
+    pub fn offset(&self) -> &Bone {
+        &self.offset
+    }
+
+    pub(crate) fn set_bone(&mut self, bone: String) {
+        self.bone = bone;
+    }
+
+    pub(crate) fn offset_mut(&mut self) -> &mut Bone {
+        &mut self.offset
+    }
+}
+
+impl BlenderArmature {
+    /// Every attachment point, keyed by name.
+    pub fn attachment_points(&self) -> &HashMap<String, AttachmentPoint> {
+        &self.attachment_points
+    }
+
+    /// Add an attachment point, replacing any existing attachment point with the same name.
+    pub fn insert_attachment_point(&mut self, name: String, attachment_point: AttachmentPoint) {
+        self.attachment_points.insert(name, attachment_point);
+    }
+
+    /// The transform of an attachment point when the armature is in a pose, such as for placing
+    /// a muzzle flash or a sword in a character's hand.
+    ///
+    /// `pose` is every bone's armature space transform, indexed by joint index, such as the
+    /// [`bone_rest_poses`]. The attachment point is returned in the same space, which is world
+    /// space when the armature is at the origin.
+    ///
+    /// Returns `None` if there is no attachment point with the name, or if its bone isn't in the
+    /// pose.
+    ///
+    /// [`bone_rest_poses`]: #method.bone_rest_poses
+    pub fn attachment_world_transform(&self, name: &str, pose: &[Bone]) -> Option<Matrix4<f32>> {
+        let attachment_point = self.attachment_points.get(name)?;
+        let joint = *self.joint_indices.get(&attachment_point.bone)?;
+        let bone = pose.get(joint as usize)?;
+
+        Some(bone.to_matrix() * attachment_point.offset.to_matrix())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::{Point3, Vector3};
+
+    /// Verify that an attachment point follows its bone.
+    #[test]
+    fn attachment_follows_bone() {
+        let mut armature: BlenderArmature = serde_json::from_str(
+            r#"{
+                "name": "Soldier",
+                "joint_indices": {"Hips": 0, "Hand": 1},
+                "bone_child_to_parent": {"1": 0},
+                "inverse_bind_poses": [],
+                "bone_space_actions": {},
+                "bone_groups": {},
+                "attachment_points": {
+                    "Muzzle": {
+                        "bone": "Hand",
+                        "offset": {"Matrix": [1, 0, 0, 0, 0, 1, 0, 0.5, 0, 0, 1, 0, 0, 0, 0, 1]}
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let pose = [
+            Bone::Matrix(Matrix4::identity()),
+            Bone::Matrix(Matrix4::new_translation(&Vector3::new(2., 0., 1.))),
+        ];
+
+        // Blender matrices are row major
+        armature.transpose_actions();
+
+        let muzzle = armature
+            .attachment_world_transform("Muzzle", &pose)
+            .unwrap();
+        assert_eq!(
+            muzzle.transform_point(&Point3::origin()),
+            Point3::new(2., 0.5, 1.)
+        );
+
+        assert_eq!(armature.attachment_world_transform("Eyes", &pose), None);
+    }
+}
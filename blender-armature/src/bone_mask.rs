@@ -0,0 +1,125 @@
+use crate::BlenderArmature;
+use std::collections::BTreeSet;
+
+/// A set of joints, such as every joint in the upper body.
+///
+/// Used to restrict an action to part of an armature with [`Action::masked`].
+///
+/// [`Action::masked`]: struct.Action.html#method.masked
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BoneMask {
+    joint_indices: BTreeSet<u8>,
+}
+
+impl BoneMask {
+    /// Create a mask from a set of joint indices.
+    pub fn new(joint_indices: impl IntoIterator<Item = u8>) -> Self {
+        BoneMask {
+            joint_indices: joint_indices.into_iter().collect(),
+        }
+    }
+
+    /// A mask of a bone and all of its descendants, such as every bone from the spine up for
+    /// an upper body mask.
+    ///
+    /// Returns `None` if the armature does not have a bone named `root_bone`.
+    pub fn from_subtree(armature: &BlenderArmature, root_bone: &str) -> Option<Self> {
+        let root_idx = *armature.joint_indices().get(root_bone)?;
+
+        let mut mask = BoneMask::new(vec![root_idx]);
+
+        for joint_idx in armature.joint_indices().values() {
+            // Walk up towards the root of the armature until we hit a bone that's in the subtree
+            let mut ancestors = vec![*joint_idx];
+            let mut current = *joint_idx;
+
+            while let Some(parent) = armature.bone_child_to_parent().get(&current) {
+                if mask.contains(*parent) || *parent == root_idx {
+                    mask.joint_indices.extend(ancestors);
+                    break;
+                }
+
+                // Guard against malformed hierarchies that loop back on themselves
+                if ancestors.contains(parent) {
+                    break;
+                }
+
+                ancestors.push(*parent);
+                current = *parent;
+            }
+        }
+
+        Some(mask)
+    }
+
+    /// Whether the joint is in the mask
+    pub fn contains(&self, joint_idx: u8) -> bool {
+        self.joint_indices.contains(&joint_idx)
+    }
+
+    /// The joints in the mask, smallest first.
+    ///
+    /// Useful for sampling only the masked joints with [`JointIndicesRef::Some`].
+    ///
+    /// [`JointIndicesRef::Some`]: enum.JointIndicesRef.html#variant.Some
+    pub fn joint_indices(&self) -> Vec<u8> {
+        self.joint_indices.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, Bone, BoneKeyframe};
+    use nalgebra::Matrix4;
+
+    /// Verify that a subtree contains a bone and all of its descendants.
+    #[test]
+    fn subtree() {
+        let armature = armature();
+
+        let upper_body = BoneMask::from_subtree(&armature, "Spine").unwrap();
+        assert_eq!(upper_body.joint_indices(), vec![1, 2, 3]);
+
+        let hand = BoneMask::from_subtree(&armature, "Hand").unwrap();
+        assert_eq!(hand.joint_indices(), vec![3]);
+
+        assert!(BoneMask::from_subtree(&armature, "Tail").is_none());
+    }
+
+    /// Verify that masking an action removes the keyframes of bones outside of the mask.
+    #[test]
+    fn masked_action() {
+        let bone = Bone::Matrix(Matrix4::identity());
+
+        let mut action = Action::new();
+        action.insert_bone_keyframe(0, BoneKeyframe::new(1, bone));
+        action.insert_bone_keyframe(0, BoneKeyframe::new(20, bone));
+        action.insert_bone_keyframe(3, BoneKeyframe::new(5, bone));
+        action.insert_bone_keyframe(3, BoneKeyframe::new(10, bone));
+
+        let upper_body = BoneMask::from_subtree(&armature(), "Spine").unwrap();
+        let masked = action.masked(&upper_body);
+
+        assert!(masked.bone_keyframes().get(&0).is_none());
+        assert_eq!(masked.bone_keyframes()[&3].len(), 2);
+        assert_eq!(masked.smallest_frame(), 5);
+        assert_eq!(masked.largest_frame(), 10);
+    }
+
+    /// Hips (0) -> Spine (1) -> Arm (2) -> Hand (3), and Hips (0) -> Leg (4)
+    fn armature() -> BlenderArmature {
+        let mut armature = BlenderArmature::default();
+
+        for (idx, name) in ["Hips", "Spine", "Arm", "Hand", "Leg"].iter().enumerate() {
+            armature.insert_joint_index(name.to_string(), idx as u8);
+        }
+
+        armature.insert_child_to_parent(1, 0);
+        armature.insert_child_to_parent(2, 1);
+        armature.insert_child_to_parent(3, 2);
+        armature.insert_child_to_parent(4, 0);
+
+        armature
+    }
+}
@@ -0,0 +1,184 @@
+use crate::BlenderArmature;
+use std::collections::{BTreeMap, HashSet};
+
+/// Bones whose names only differ by case or by the separators between their words, such as
+/// `hand.L`, `hand_L` and `Hand L`.
+///
+/// Blender allows these, but they end up as the same joint in engines and tools that ignore case
+/// or normalize separators. They typically come from rigs that were symmetrized or that had bones
+/// duplicated for linked constraints.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoneNameConflict {
+    /// The name that the bones all share once case and separators are ignored.
+    pub normalized: String,
+    /// The names of the conflicting bones, ordered by joint index.
+    pub bones: Vec<String>,
+}
+
+/// An error while validating an armature's bone names.
+#[derive(Debug, thiserror::Error)]
+pub enum BoneNameError {
+    /// Some of the armature's bone names conflict with each other.
+    #[error("Armature {armature} has conflicting bone names: {}", fmt_conflicts(.conflicts))]
+    Conflicts {
+        /// The name of the armature
+        armature: String,
+        /// Every group of conflicting bones
+        conflicts: Vec<BoneNameConflict>,
+    },
+}
+
+impl BlenderArmature {
+    /// Find the bones whose names only differ by case or by separators. See
+    /// [`BoneNameConflict`].
+    ///
+    /// Conflicts are ordered by the joint index of their first bone.
+    ///
+    /// [`BoneNameConflict`]: struct.BoneNameConflict.html
+    pub fn bone_name_conflicts(&self) -> Vec<BoneNameConflict> {
+        let mut by_normalized: BTreeMap<String, Vec<(u8, &String)>> = BTreeMap::new();
+        for (name, joint_idx) in self.joint_indices.iter() {
+            by_normalized
+                .entry(normalize_bone_name(name))
+                .or_default()
+                .push((*joint_idx, name));
+        }
+
+        let mut conflicts: Vec<(u8, BoneNameConflict)> = by_normalized
+            .into_iter()
+            .filter(|(_, bones)| bones.len() > 1)
+            .map(|(normalized, mut bones)| {
+                bones.sort();
+                let first_joint = bones[0].0;
+
+                let conflict = BoneNameConflict {
+                    normalized,
+                    bones: bones.into_iter().map(|(_, name)| name.clone()).collect(),
+                };
+                (first_joint, conflict)
+            })
+            .collect();
+        conflicts.sort_by_key(|(first_joint, _)| *first_joint);
+
+        conflicts
+            .into_iter()
+            .map(|(_, conflict)| conflict)
+            .collect()
+    }
+
+    /// Error if any of the armature's bone names conflict. See [`BoneNameConflict`].
+    ///
+    /// [`BoneNameConflict`]: struct.BoneNameConflict.html
+    pub fn validate_bone_names(&self) -> Result<(), BoneNameError> {
+        let conflicts = self.bone_name_conflicts();
+
+        match conflicts.is_empty() {
+            true => Ok(()),
+            false => Err(BoneNameError::Conflicts {
+                armature: self.name.clone(),
+                conflicts,
+            }),
+        }
+    }
+
+    /// Rename bones so that none of their names conflict. See [`BoneNameConflict`].
+    ///
+    /// The bone with the lowest joint index keeps its name and the others get a `_2`, `_3`, ...
+    /// suffix. Joint indices don't change, so keyframes and bone groups are unaffected.
+    ///
+    /// Returns every `(old name, new name)` so that the vertex groups of the armature's meshes
+    /// can be renamed to match.
+    ///
+    /// [`BoneNameConflict`]: struct.BoneNameConflict.html
+    pub fn rename_conflicting_bones(&mut self) -> Vec<(String, String)> {
+        let mut taken: HashSet<String> = self
+            .joint_indices
+            .keys()
+            .map(|name| normalize_bone_name(name))
+            .collect();
+
+        let mut renames = vec![];
+        for conflict in self.bone_name_conflicts() {
+            for name in conflict.bones.iter().skip(1) {
+                let mut suffix = 2;
+                let renamed = loop {
+                    let renamed = format!("{}_{}", name, suffix);
+                    if taken.insert(normalize_bone_name(&renamed)) {
+                        break renamed;
+                    }
+                    suffix += 1;
+                };
+
+                let joint_idx = self.joint_indices.remove(name).unwrap();
+                self.joint_indices.insert(renamed.clone(), joint_idx);
+                for attachment_point in self.attachment_points.values_mut() {
+                    if attachment_point.bone() == name {
+                        attachment_point.set_bone(renamed.clone());
+                    }
+                }
+                renames.push((name.clone(), renamed));
+            }
+        }
+
+        renames
+    }
+}
+
+/// Lowercase, with every run of `.`, `_`, `-` and whitespace turned into one `_`.
+fn normalize_bone_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+
+    for c in name.chars() {
+        match c == '.' || c == '_' || c == '-' || c.is_whitespace() {
+            true => {
+                if !normalized.ends_with('_') {
+                    normalized.push('_');
+                }
+            }
+            false => normalized.extend(c.to_lowercase()),
+        }
+    }
+
+    normalized
+}
+
+fn fmt_conflicts(conflicts: &[BoneNameConflict]) -> String {
+    conflicts
+        .iter()
+        .map(|conflict| conflict.bones.join(" / "))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that bones that only differ by case or separators are reported and renamed, while
+    /// numbered bone chains are left alone.
+    #[test]
+    fn detect_and_rename_conflicts() {
+        let mut armature = BlenderArmature::default();
+        armature.set_name("Hero".to_string());
+        for (idx, name) in ["hand.L", "Hand_L", "spine", "spine.001", "hand.L_2"]
+            .iter()
+            .enumerate()
+        {
+            armature.joint_indices.insert(name.to_string(), idx as u8);
+        }
+
+        let error = armature.validate_bone_names().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Armature Hero has conflicting bone names: hand.L / Hand_L"
+        );
+
+        let renames = armature.rename_conflicting_bones();
+        assert_eq!(
+            renames,
+            vec![("Hand_L".to_string(), "Hand_L_3".to_string())]
+        );
+        assert_eq!(armature.joint_indices["Hand_L_3"], 1);
+        assert!(armature.validate_bone_names().is_ok());
+    }
+}
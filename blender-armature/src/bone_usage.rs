@@ -0,0 +1,146 @@
+use crate::{Action, BlenderArmature, Bone, BoneMask, KeyframeSpace};
+use nalgebra::{DualQuaternion, Matrix4};
+
+/// Transforms and scales that are within this of the rest pose are considered unchanged.
+const REST_POSE_TOLERANCE: f32 = 1e-5;
+
+impl Action {
+    /// The bones that have at least one keyframe that moves them away from their rest pose.
+    ///
+    /// A keyframe is at rest if its transform is the identity and its scale, if any, is one,
+    /// which is what [`KeyframeSpace::RelativeToRest`] keyframes look like for a bone that the
+    /// action doesn't pose. See [`BlenderArmature.method#bones_used_by`] for actions whose
+    /// keyframes are in another space.
+    ///
+    /// Pass the result to [`Action.method#masked`] to drop the keyframes of the static bones, and
+    /// sample those bones from their rest pose instead.
+    ///
+    /// [`KeyframeSpace::RelativeToRest`]: enum.KeyframeSpace.html#variant.RelativeToRest
+    pub fn bones_used(&self) -> BoneMask {
+        self.bones_moved_from(|_| Some(Bone::Matrix(Matrix4::identity())))
+    }
+
+    fn bones_moved_from(&self, rest_pose: impl Fn(u8) -> Option<Bone>) -> BoneMask {
+        let used = self.bone_keyframes.iter().filter(|(joint_idx, keyframes)| {
+            let rest_pose = match rest_pose(**joint_idx) {
+                Some(rest_pose) => rest_pose,
+                None => return true,
+            };
+
+            keyframes.iter().any(|keyframe| {
+                let is_scaled = keyframe
+                    .scale()
+                    .iter()
+                    .flatten()
+                    .any(|axis| (axis - 1.).abs() > REST_POSE_TOLERANCE);
+
+                is_scaled || !is_same_transform(&keyframe.bone(), &rest_pose)
+            })
+        });
+
+        BoneMask::new(used.map(|(joint_idx, _)| *joint_idx))
+    }
+}
+
+impl BlenderArmature {
+    /// The bones that an action moves away from their rest pose, taking the armature's
+    /// [`KeyframeSpace`] into account. See [`Action.method#bones_used`].
+    ///
+    /// Returns `None` if the armature doesn't have an action with this name.
+    ///
+    /// [`KeyframeSpace`]: enum.KeyframeSpace.html
+    pub fn bones_used_by(&self, action_name: &str) -> Option<BoneMask> {
+        let action = self.bone_space_actions.get(action_name)?;
+
+        let used = match self.keyframe_space {
+            KeyframeSpace::RelativeToRest => action.bones_used(),
+            KeyframeSpace::Absolute => {
+                action.bones_moved_from(|joint_idx| self.parent_relative_rest_pose(joint_idx).ok())
+            }
+        };
+
+        Some(used)
+    }
+
+    /// The bones that none of the armature's actions move away from their rest pose, such as
+    /// helper and twist bones that are only driven by constraints, or bones of props that the
+    /// game attaches at runtime.
+    pub fn unused_bones(&self) -> BoneMask {
+        let used: Vec<BoneMask> = self
+            .bone_space_actions
+            .keys()
+            .filter_map(|action_name| self.bones_used_by(action_name))
+            .collect();
+
+        BoneMask::new(
+            self.joint_indices
+                .values()
+                .copied()
+                .filter(|joint_idx| !used.iter().any(|used| used.contains(*joint_idx))),
+        )
+    }
+}
+
+fn is_same_transform(bone: &Bone, other: &Bone) -> bool {
+    match (bone, other) {
+        (Bone::DualQuat(dual_quat), Bone::DualQuat(other)) => is_same_dual_quat(dual_quat, other),
+        _ => {
+            let matrix = bone.to_matrix();
+            let other = other.to_matrix();
+
+            (matrix - other).amax() <= REST_POSE_TOLERANCE
+        }
+    }
+}
+
+/// A dual quaternion and its negation are the same transform.
+fn is_same_dual_quat(dual_quat: &DualQuaternion<f32>, other: &DualQuaternion<f32>) -> bool {
+    let difference = (dual_quat.real.coords - other.real.coords)
+        .amax()
+        .max((dual_quat.dual.coords - other.dual.coords).amax());
+    let negated_difference = (dual_quat.real.coords + other.real.coords)
+        .amax()
+        .max((dual_quat.dual.coords + other.dual.coords).amax());
+
+    difference.min(negated_difference) <= REST_POSE_TOLERANCE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoneKeyframe;
+    use nalgebra::Vector3;
+
+    /// Verify that bones whose keyframes all sit at their rest pose are unused, including bones
+    /// that some other action moves.
+    #[test]
+    fn find_used_and_unused_bones() {
+        let identity = Bone::Matrix(Matrix4::identity());
+        let moved = Bone::Matrix(Matrix4::new_translation(&Vector3::new(0., 1., 0.)));
+
+        let mut walk = Action::new();
+        walk.insert_bone_keyframe(0, BoneKeyframe::new(1, identity));
+        walk.insert_bone_keyframe(0, BoneKeyframe::new(10, moved));
+        walk.insert_bone_keyframe(1, BoneKeyframe::new(1, identity));
+        walk.insert_bone_keyframe(2, BoneKeyframe::new(1, identity).with_scale([1., 2., 1.]));
+
+        let mut wave = Action::new();
+        wave.insert_bone_keyframe(1, BoneKeyframe::new(1, identity));
+        wave.insert_bone_keyframe(
+            3,
+            BoneKeyframe::new(1, Bone::DualQuat(-DualQuaternion::identity())),
+        );
+
+        assert_eq!(walk.bones_used().joint_indices(), vec![0, 2]);
+        assert!(wave.bones_used().joint_indices().is_empty());
+
+        let mut armature = BlenderArmature::default();
+        for (name, joint_idx) in [("hips", 0), ("spine", 1), ("chest", 2), ("prop", 3)].iter() {
+            armature.joint_indices.insert(name.to_string(), *joint_idx);
+        }
+        armature.insert_bone_space_action("Walk".to_string(), walk);
+        armature.insert_bone_space_action("Wave".to_string(), wave);
+
+        assert_eq!(armature.unused_bones().joint_indices(), vec![1, 3]);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{Action, ArmaturesByFilename, BlenderArmature};
+
+/// The files that armatures were split into, so that engines can load an armature up front and
+/// stream in its actions one at a time as they are needed.
+///
+/// Every armature is written to its own file without any actions, and each of its actions is
+/// written to a file named `{armature}_{action}`. See [`clip_file_stem`].
+///
+/// [`clip_file_stem`]: fn.clip_file_stem.html
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ClipIndex {
+    pub(crate) armatures: Vec<ClipIndexArmature>,
+}
+
+/// What goes into one of the files that [`ClipIndex::split`] lists. Serializes as the armature
+/// or action itself.
+///
+/// [`ClipIndex::split`]: struct.ClipIndex.html#method.split
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ClipContents {
+    /// An armature without any actions.
+    Armature(BlenderArmature),
+    /// One of an armature's actions.
+    Action(Action),
+}
+
+/// An armature that was split into clips.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipIndexArmature {
+    pub(crate) name: String,
+    pub(crate) file: String,
+    pub(crate) clips: Vec<ClipIndexEntry>,
+}
+
+/// One of an armature's actions, written to its own file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipIndexEntry {
+    pub(crate) action: String,
+    pub(crate) file: String,
+    pub(crate) frame_range_inclusive: Option<(u16, u16)>,
+}
+
+impl ClipIndex {
+    /// Split every armature into an armature without actions and a clip for each of its actions.
+    ///
+    /// Returns the index along with the file name and contents of every file that it lists.
+    /// Armatures and clips are sorted by name so that the same export always gives the same files.
+    pub fn split(
+        armatures: &ArmaturesByFilename,
+        extension: &str,
+    ) -> (ClipIndex, Vec<(String, ClipContents)>) {
+        let mut sorted: Vec<(&String, &BlenderArmature)> = armatures
+            .values()
+            .flat_map(|armatures| armatures.iter())
+            .collect();
+        sorted.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut index = ClipIndex::default();
+        let mut files = vec![];
+
+        for (armature_name, armature) in sorted {
+            let mut actions: Vec<(&String, _)> = armature.bone_space_actions().iter().collect();
+            actions.sort_by(|a, b| a.0.cmp(b.0));
+
+            let mut without_actions = armature.clone();
+            without_actions.bone_space_actions.clear();
+
+            let armature_file = format!("{}.{}", clip_file_stem(armature_name, None), extension);
+            files.push((
+                armature_file.clone(),
+                ClipContents::Armature(without_actions),
+            ));
+
+            let mut clips = vec![];
+            for (action_name, action) in actions {
+                let file = format!(
+                    "{}.{}",
+                    clip_file_stem(armature_name, Some(action_name)),
+                    extension
+                );
+                files.push((file.clone(), ClipContents::Action(action.clone())));
+
+                clips.push(ClipIndexEntry {
+                    action: action_name.clone(),
+                    file,
+                    frame_range_inclusive: action.bone_keyframes().frame_range_inclusive(),
+                });
+            }
+
+            index.armatures.push(ClipIndexArmature {
+                name: armature_name.clone(),
+                file: armature_file,
+                clips,
+            });
+        }
+
+        (index, files)
+    }
+
+    /// The armatures that were split, sorted by name.
+    pub fn armatures(&self) -> &Vec<ClipIndexArmature> {
+        &self.armatures
+    }
+}
+
+impl ClipIndexArmature {
+    /// The name of the armature.
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// The file that has the armature without any of its actions.
+    pub fn file(&self) -> &String {
+        &self.file
+    }
+
+    /// The armature's actions, sorted by name.
+    pub fn clips(&self) -> &Vec<ClipIndexEntry> {
+        &self.clips
+    }
+}
+
+impl ClipIndexEntry {
+    /// The name of the action.
+    pub fn action(&self) -> &String {
+        &self.action
+    }
+
+    /// The file that has the action.
+    pub fn file(&self) -> &String {
+        &self.file
+    }
+
+    /// The first and last keyframe of the action, so that engines can plan playback before the
+    /// clip has loaded. `None` if the action doesn't have any keyframes.
+    pub fn frame_range_inclusive(&self) -> Option<(u16, u16)> {
+        self.frame_range_inclusive
+    }
+}
+
+/// The name, without an extension, of the file for an armature or for one of its actions.
+///
+/// `{armature}` for the armature and `{armature}_{action}` for an action. Characters that aren't
+/// safe in file names, such as the `|` that Blender puts in baked action names, become `_`.
+pub fn clip_file_stem(armature_name: &str, action_name: Option<&str>) -> String {
+    let stem = match action_name {
+        Some(action_name) => format!("{}_{}", armature_name, action_name),
+        None => armature_name.to_string(),
+    };
+
+    stem.chars()
+        .map(|c| match c.is_alphanumeric() || "-_.".contains(c) {
+            true => c,
+            false => '_',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Verify that every action gets its own file and that the armature's file has no actions.
+    #[test]
+    fn split_armature_into_clips() {
+        let mut armature = BlenderArmature::default();
+        armature.insert_bone_space_action("Walk".to_string(), Action::new());
+        armature.insert_bone_space_action("Armature|Run".to_string(), Action::new());
+
+        let mut armatures = ArmaturesByFilename::new();
+        armatures.insert(
+            "hero.blend".to_string(),
+            vec![("Hero".to_string(), armature)].into_iter().collect(),
+        );
+
+        let (index, files) = ClipIndex::split(&armatures, "json");
+
+        let hero = &index.armatures()[0];
+        assert_eq!(hero.file(), "Hero.json");
+        assert_eq!(hero.clips()[0].file(), "Hero_Armature_Run.json");
+        assert_eq!(hero.clips()[1].action(), "Walk");
+        assert_eq!(hero.clips()[1].frame_range_inclusive(), None);
+
+        let files: HashMap<String, ClipContents> = files.into_iter().collect();
+        assert_eq!(files.len(), 3);
+        match &files["Hero.json"] {
+            ClipContents::Armature(armature) => assert!(armature.bone_space_actions().is_empty()),
+            ClipContents::Action(_) => panic!("Expected the armature"),
+        };
+        assert_eq!(files["Hero_Walk.json"], ClipContents::Action(Action::new()));
+    }
+}
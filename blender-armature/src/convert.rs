@@ -83,6 +83,16 @@ impl BlenderArmature {
     }
 }
 
+impl Bone {
+    /// This bone's transform as a matrix, converting it from a dual quaternion if it is one.
+    pub fn to_matrix(&self) -> Matrix4<f32> {
+        match BlenderArmature::dual_quat_to_matrix(self) {
+            Bone::Matrix(matrix) => matrix,
+            Bone::DualQuat(_) => unreachable!(),
+        }
+    }
+}
+
 // https://github.com/stackgl/gl-quat/blob/master/fromMat3.js
 // [i, j, k, w]
 fn quaternion_from_mat3(m: [f32; 9]) -> [f32; 4] {
@@ -15,6 +15,51 @@ impl CoordinateSystem {
     pub fn new(up: Axis, hand: Hand) -> Self {
         CoordinateSystem { up, hand }
     }
+
+    /// The axis that points up.
+    pub fn up(&self) -> Axis {
+        self.up
+    }
+
+    /// Whether the coordinate system is right or left handed.
+    pub fn hand(&self) -> Hand {
+        self.hand
+    }
+}
+
+/// The unit settings of the Blender scene that an armature was exported from.
+///
+/// With the metric or imperial unit system one Blender unit is `scale_length` meters.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneUnits {
+    /// The scene's unit system.
+    pub system: UnitSystem,
+    /// How many meters one Blender unit is, unless the unit system is [`UnitSystem::None`].
+    ///
+    /// [`UnitSystem::None`]: enum.UnitSystem.html#variant.None
+    pub scale_length: f32,
+}
+
+/// Blender's unit systems.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum UnitSystem {
+    /// Blender units without any real world size.
+    None,
+    /// Meters, which is Blender's default.
+    #[default]
+    Metric,
+    /// Feet and inches.
+    Imperial,
+}
+
+/// Blender's default of one meter per Blender unit.
+impl Default for SceneUnits {
+    fn default() -> Self {
+        SceneUnits {
+            system: UnitSystem::Metric,
+            scale_length: 1.,
+        }
+    }
 }
 
 #[allow(missing_docs)]
@@ -47,6 +92,19 @@ impl Default for CoordinateSystem {
 }
 
 impl BlenderArmature {
+    /// The coordinate system that the armature's bones are in, which
+    /// [`BlenderArmature.method#change_coordinate_system`] updates.
+    ///
+    /// Blender's Z up right handed coordinate system unless the armature has been converted.
+    pub fn coordinate_system(&self) -> CoordinateSystem {
+        self.coordinate_system
+    }
+
+    /// The unit settings of the scene that the armature was exported from.
+    pub fn scene_units(&self) -> SceneUnits {
+        self.scene_units
+    }
+
     /// Shift around the data in the armature to a new coordinate system.
     ///
     /// For example, if the armature was previously Z up and we're switching to Y up
@@ -66,6 +124,15 @@ impl BlenderArmature {
                     *bone = dual_quat_z_up_right_to_y_up_right(*bone);
                 }
 
+                for bone in self.bone_rest_poses.iter_mut() {
+                    *bone = dual_quat_z_up_right_to_y_up_right(*bone);
+                }
+
+                for attachment_point in self.attachment_points.values_mut() {
+                    let offset = attachment_point.offset_mut();
+                    *offset = dual_quat_z_up_right_to_y_up_right(*offset);
+                }
+
                 for (_action_name, action) in self.bone_space_actions.iter_mut() {
                     for (bone_idx, keyframes) in action.keyframes_mut() {
                         for bone_keyframe in keyframes.iter_mut() {
@@ -128,6 +195,8 @@ mod tests {
 
         arm.change_coordinate_system(CoordinateSystem::new(Axis::Y, Hand::Right));
 
+        assert_eq!(arm.coordinate_system().up(), Axis::Y);
+
         assert_eq!(&arm.inverse_bind_poses[0], &expected_bone);
         assert_eq!(
             &arm.bone_space_actions[&action_name()].bone_keyframes()[&BONE_IDX][0].bone(),
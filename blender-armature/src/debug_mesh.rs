@@ -0,0 +1,85 @@
+use crate::{BlenderArmature, Bone};
+use blender_mesh::BlenderMesh;
+use nalgebra::Matrix4;
+
+impl BlenderArmature {
+    /// A mesh with an octahedron for every bone, for rendering the armature with your existing
+    /// mesh rendering while debugging its rest pose or sampled animations.
+    ///
+    /// `pose` is every bone's armature space transform, indexed by joint index, such as the
+    /// [`bone_rest_poses`]. Bones without a transform in the pose are left out.
+    ///
+    /// Every child bone's head is connected to its parent's tail by a loose edge, like the
+    /// relationship lines that Blender draws. Each octahedron's positions are in a vertex group
+    /// named after its bone.
+    ///
+    /// Bones that were exported without their lengths, such as by older versions of landon, are
+    /// drawn with a length of 1.
+    ///
+    /// [`bone_rest_poses`]: #method.bone_rest_poses
+    pub fn debug_mesh(&self, pose: &[Bone]) -> BlenderMesh {
+        let mut names: Vec<(u8, &String)> = self
+            .joint_indices
+            .iter()
+            .filter(|(_, joint)| (**joint as usize) < pose.len())
+            .map(|(name, joint)| (*joint, name))
+            .collect();
+        names.sort();
+
+        let bones: Vec<(String, Matrix4<f32>, f32)> = names
+            .iter()
+            .map(|(joint, name)| {
+                let length = self.bone_lengths.get(*joint as usize).copied();
+
+                (
+                    name.to_string(),
+                    pose[*joint as usize].to_matrix(),
+                    length.unwrap_or(1.),
+                )
+            })
+            .collect();
+
+        let mut mesh = BlenderMesh::bone_octahedrons(&bones);
+        mesh.set_name(self.name.clone());
+
+        let octahedron = |joint: u8| names.iter().position(|(j, _)| *j == joint);
+        let mut edges = vec![];
+        for (child, parent) in self.bone_child_to_parent.iter() {
+            if let (Some(child), Some(parent)) = (octahedron(*child), octahedron(*parent)) {
+                edges.push([6 * child as u32, 6 * parent as u32 + 5]);
+            }
+        }
+        edges.sort_unstable();
+        mesh.set_edges(edges);
+
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::{Point3, Vector3};
+
+    /// Verify that every bone is posed and that child bones are connected to their parents.
+    #[test]
+    fn debug_mesh() {
+        let mut armature = BlenderArmature::default();
+        armature.set_name("Arm".to_string());
+        armature.insert_joint_index("Upper".to_string(), 0);
+        armature.insert_joint_index("Lower".to_string(), 1);
+        armature.insert_child_to_parent(1, 0);
+        armature.set_bone_lengths(vec![2., 1.]);
+
+        let pose = [
+            Bone::Matrix(Matrix4::identity()),
+            Bone::Matrix(Matrix4::new_translation(&Vector3::new(0., 2., 0.))),
+        ];
+        let mesh = armature.debug_mesh(&pose);
+
+        assert_eq!(mesh.name(), "Arm");
+        assert_eq!(mesh.vertex_groups()["Lower"], vec![6, 7, 8, 9, 10, 11]);
+        assert_eq!(mesh.edges(), &vec![[6, 5]]);
+        assert_eq!(mesh.bounding_box().max_corner, Point3::new(0.2, 3., 0.2));
+    }
+}
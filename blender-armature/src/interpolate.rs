@@ -30,9 +30,12 @@ use std::time::Duration;
 use crate::{BlenderArmature, Bone, JointIndicesRef, SampleDesc};
 
 pub use self::interpolated_bones::*;
+pub use self::pose_cache::*;
+use nalgebra::{Matrix4, Vector3};
 use std::collections::BTreeMap;
 
 mod interpolated_bones;
+mod pose_cache;
 mod sample_action;
 
 /// Returns 0.0 if no time has elapsed.
@@ -69,6 +72,49 @@ impl BlenderArmature {
     ) -> BTreeMap<u8, Bone> {
         self.sample_action(action_name, joint_indices, sample_desc)
     }
+
+    /// Interpolate in between the keyframes of your BlenderArmature and combine each bone with
+    /// its scale, returning a matrix for each bone.
+    ///
+    /// Dual quaternions can't represent non uniform scale, so bones that have a scale channel,
+    /// such as those in squash and stretch rigs, need to be skinned using matrices. The scale is
+    /// applied before the bone's rotation and translation.
+    ///
+    /// Bones that don't have a scale channel are converted to matrices as is.
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`interpolate_bones`].
+    ///
+    /// [`interpolate_bones`]: #method.interpolate_bones
+    pub fn interpolate_bone_matrices(
+        &self,
+        action_name: &str,
+        joint_indices: JointIndicesRef,
+        sample_desc: SampleDesc,
+    ) -> BTreeMap<u8, Matrix4<f32>> {
+        let bone_keyframes = self
+            .bone_space_actions
+            .get(action_name)
+            .unwrap()
+            .bone_keyframes();
+
+        self.sample_action(action_name, joint_indices, sample_desc)
+            .into_iter()
+            .map(|(joint_idx, bone)| {
+                let mut matrix = match BlenderArmature::dual_quat_to_matrix(&bone) {
+                    Bone::Matrix(matrix) => matrix,
+                    Bone::DualQuat(_) => unreachable!(),
+                };
+
+                if let Some(scale) = bone_keyframes.sample_scale(joint_idx, sample_desc) {
+                    matrix = matrix * Matrix4::new_nonuniform_scaling(&Vector3::from(scale));
+                }
+
+                (joint_idx, matrix)
+            })
+            .collect()
+    }
 }
 
 // Tests originally ported from:
@@ -76,7 +122,9 @@ impl BlenderArmature {
 #[cfg(test)]
 pub(super) mod tests {
 
-    use crate::{Bone, BoneKeyframe, FrameOffset, JointIndicesRef, Keyframe, SampleDesc};
+    use crate::{
+        Bone, BoneKeyframe, FrameOffset, Interpolation, JointIndicesRef, Keyframe, SampleDesc,
+    };
 
     use super::*;
     use crate::test_util::{action_name, action_with_keyframes, BONE_IDX};
@@ -118,6 +166,8 @@ pub(super) mod tests {
                     ONE_FPS,
                 ),
                 should_loop: true,
+                looping: false,
+                interpolation: Interpolation::Linear,
             },
         }
         .test();
@@ -145,6 +195,8 @@ pub(super) mod tests {
                     ONE_FPS,
                 ),
                 should_loop: true,
+                looping: false,
+                interpolation: Interpolation::Linear,
             },
         }
         .test();
@@ -175,6 +227,8 @@ pub(super) mod tests {
                     ONE_FPS,
                 ),
                 should_loop: true,
+                looping: false,
+                interpolation: Interpolation::Linear,
             },
         }
         .test();
@@ -202,6 +256,8 @@ pub(super) mod tests {
                     ONE_FPS,
                 ),
                 should_loop: false,
+                looping: false,
+                interpolation: Interpolation::Linear,
             },
         }
         .test();
@@ -231,6 +287,8 @@ pub(super) mod tests {
                     ONE_FPS,
                 ),
                 should_loop: true,
+                looping: false,
+                interpolation: Interpolation::Linear,
             },
         }
         .test();
@@ -257,11 +315,118 @@ pub(super) mod tests {
                     10,
                 ),
                 should_loop: false,
+                looping: false,
+                interpolation: Interpolation::Linear,
             },
         }
         .test();
     }
 
+    /// Verify that when wrapping around we interpolate from the last keyframe back towards the
+    /// first keyframe, and that we land exactly on the keyframes at the wrap boundaries.
+    #[test]
+    fn looping_wraps_around_last_keyframe() {
+        let tests = vec![
+            // Exactly on the last keyframe
+            (2.0, [1.0, 0.0, 0.0, 0.0, 4.0, 4.0, 4.0, 4.0]),
+            // Halfway between the last keyframe and the first keyframe of the next cycle
+            (2.5, [1.0, 0.0, 0.0, 0.0, 2.0, 2.0, 2.0, 2.0]),
+            // Exactly one cycle in, so we're back on the first keyframe
+            (3.0, [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+            // A quarter of the way between the first and last keyframe of the second cycle
+            (3.5, [1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0]),
+        ];
+
+        for (frames_elapsed, expected_bone) in tests {
+            DualQuatTestCase {
+                keyframes: vec![
+                    TestKeyframeDualQuat {
+                        frame: 0,
+                        bone: [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+                    },
+                    TestKeyframeDualQuat {
+                        frame: 2,
+                        bone: [1.0, 0.0, 0.0, 0.0, 4.0, 4.0, 4.0, 4.0],
+                    },
+                ],
+                expected_bone,
+                sample_desc: SampleDesc {
+                    frame_offset: FrameOffset::new(frames_elapsed),
+                    should_loop: false,
+                    looping: true,
+                    interpolation: Interpolation::Linear,
+                },
+            }
+            .test();
+        }
+    }
+
+    /// Verify that scale is interpolated separately from the bone and applied before the bone's
+    /// rotation and translation.
+    #[test]
+    fn bone_matrices_with_scale() {
+        let keyframes = vec![
+            BoneKeyframe::new(0, dq_to_bone([1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]))
+                .with_scale([1.0, 1.0, 1.0]),
+            BoneKeyframe::new(2, dq_to_bone([1.0, 0.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0]))
+                .with_scale([3.0, 1.0, 0.5]),
+        ];
+
+        let armature = BlenderArmature {
+            bone_space_actions: action_with_keyframes(keyframes),
+            ..BlenderArmature::default()
+        };
+
+        let sample_desc = SampleDesc {
+            frame_offset: FrameOffset::new(1.0),
+            should_loop: false,
+            looping: false,
+            interpolation: Interpolation::Linear,
+        };
+
+        let bone_keyframes = armature.bone_space_actions[&action_name()].bone_keyframes();
+        assert_eq!(
+            bone_keyframes.sample_scale(BONE_IDX, sample_desc),
+            Some([2.0, 1.0, 0.75])
+        );
+
+        let matrices = armature.interpolate_bone_matrices(
+            &action_name(),
+            JointIndicesRef::Some(&[BONE_IDX]),
+            sample_desc,
+        );
+
+        // Translated by 2 * 0.5 along X (the dual part is half of the translation)
+        let expected = Matrix4::new_translation(&Vector3::new(2.0, 0.0, 0.0))
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(2.0, 1.0, 0.75));
+        assert_eq!(matrices[&BONE_IDX], expected);
+    }
+
+    /// Verify that bones without a scale channel don't have a scale.
+    #[test]
+    fn no_scale_channel() {
+        let keyframes = vec![BoneKeyframe::new(
+            0,
+            dq_to_bone([1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+        )];
+
+        let actions = action_with_keyframes(keyframes);
+
+        let sample_desc = SampleDesc {
+            frame_offset: FrameOffset::new(0.0),
+            should_loop: false,
+            looping: false,
+            interpolation: Interpolation::Linear,
+        };
+
+        assert_eq!(
+            actions[&action_name()]
+                .bone_keyframes()
+                .sample_scale(BONE_IDX, sample_desc),
+            None
+        );
+    }
+
     impl DualQuatTestCase {
         fn test(self) {
             let mut keyframes = vec![];
@@ -1,5 +1,5 @@
 use crate::Bone;
-use nalgebra::DualQuaternion;
+use nalgebra::{DualQuaternion, Quaternion, UnitQuaternion, Vector3};
 use std::collections::BTreeMap;
 
 /// Blend from the start bones towards the ending bones.
@@ -31,6 +31,42 @@ pub fn blend_towards_bones(
         .collect()
 }
 
+/// Blend from the start bones towards the ending bones using a separate amount for each joint.
+///
+/// Joints that don't have a weight keep their start bone, as do joints that aren't in the end
+/// bones. This makes it possible to transition only part of the body, or to feather the edges of
+/// a bone mask by giving the joints along its boundary weights between 0.0 and 1.0.
+///
+/// # Panics
+///
+/// Panics if a weight is below 0.0 or above 1.0.
+pub fn blend_towards_bones_weighted(
+    start: &BTreeMap<u8, Bone>,
+    end: &BTreeMap<u8, Bone>,
+    weights: &BTreeMap<u8, f32>,
+) -> BTreeMap<u8, Bone> {
+    start
+        .iter()
+        .map(|(joint_idx, start_bone)| {
+            let bone = match (end.get(joint_idx), weights.get(joint_idx)) {
+                (Some(end_bone), Some(weight)) => {
+                    assert!(
+                        *weight >= 0.0 && *weight <= 1.0,
+                        "Joint {} has a blend weight of {}, weights must be between 0.0 and 1.0",
+                        joint_idx,
+                        weight
+                    );
+
+                    interpolate_bone(*start_bone, *end_bone, *weight)
+                }
+                _ => *start_bone,
+            };
+
+            (*joint_idx, bone)
+        })
+        .collect()
+}
+
 /// Interpolate from the start to the end bone using the given amount between [0.0, 1.0] inclusive.
 ///
 /// When the interpolation parameter is 0.0 the start bone is used.
@@ -78,3 +114,173 @@ pub fn interpolate_dual_quats(
 
     start + ((end - start) * amount)
 }
+
+/// Interpolate from the start to the end bone along a smooth curve that also passes through the
+/// bones before and after them.
+///
+/// Rotations are interpolated using spherical quadrangle interpolation (squad) and translations
+/// using a Catmull-Rom spline. Matrix bones fall back to [`interpolate_bone`].
+///
+/// When the interpolation parameter is 0.0 the start bone is used.
+/// At 1.0 the end bone is used.
+pub fn interpolate_bone_cubic(
+    before_bone: Bone,
+    start_bone: Bone,
+    end_bone: Bone,
+    after_bone: Bone,
+    amount: f32,
+) -> Bone {
+    match (before_bone, start_bone, end_bone, after_bone) {
+        (
+            Bone::DualQuat(before),
+            Bone::DualQuat(start),
+            Bone::DualQuat(end),
+            Bone::DualQuat(after),
+        ) => Bone::DualQuat(interpolate_dual_quats_cubic(
+            before, start, end, after, amount,
+        )),
+        _ => interpolate_bone(start_bone, end_bone, amount),
+    }
+}
+
+/// See [`interpolate_bone_cubic`].
+pub fn interpolate_dual_quats_cubic(
+    before: DualQuaternion<f32>,
+    start: DualQuaternion<f32>,
+    end: DualQuaternion<f32>,
+    after: DualQuaternion<f32>,
+    amount: f32,
+) -> DualQuaternion<f32> {
+    // Keep every rotation in the same hemisphere as the one before it so that we take the
+    // shortest path between each of them.
+    let mut keys = [before, start, end, after];
+    for idx in 1..4 {
+        if keys[idx - 1].real.dot(&keys[idx].real) < 0.0 {
+            keys[idx] = keys[idx] * -1.;
+        }
+    }
+
+    let rotations: Vec<UnitQuaternion<f32>> = keys
+        .iter()
+        .map(|key| UnitQuaternion::new_normalize(key.real))
+        .collect();
+    // A dual quaternion's dual part is half of the translation multiplied by the rotation
+    let translations: Vec<Vector3<f32>> = keys
+        .iter()
+        .zip(rotations.iter())
+        .map(|(key, rotation)| (key.dual * rotation.conjugate().into_inner() * 2.).imag())
+        .collect();
+
+    let start_control = squad_control_point(rotations[0], rotations[1], rotations[2]);
+    let end_control = squad_control_point(rotations[1], rotations[2], rotations[3]);
+
+    let rotation = rotations[1].slerp(&rotations[2], amount).slerp(
+        &start_control.slerp(&end_control, amount),
+        2. * amount * (1. - amount),
+    );
+
+    let t = amount;
+    let (p0, p1, p2, p3) = (
+        translations[0],
+        translations[1],
+        translations[2],
+        translations[3],
+    );
+    let translation = (p1 * 2.
+        + (p2 - p0) * t
+        + (p0 * 2. - p1 * 5. + p2 * 4. - p3) * t * t
+        + (p1 * 3. - p0 - p2 * 3. + p3) * t * t * t)
+        * 0.5;
+
+    let real = rotation.into_inner();
+    let dual = Quaternion::from_imag(translation) * real * 0.5;
+
+    DualQuaternion::from_real_and_dual(real, dual)
+}
+
+/// The intermediate rotation that squad curves towards around `current`.
+fn squad_control_point(
+    previous: UnitQuaternion<f32>,
+    current: UnitQuaternion<f32>,
+    next: UnitQuaternion<f32>,
+) -> UnitQuaternion<f32> {
+    let inverse = current.inverse();
+
+    let to_next = (inverse * next).into_inner().ln();
+    let to_previous = (inverse * previous).into_inner().ln();
+
+    current * UnitQuaternion::new_normalize(((to_next + to_previous) * -0.25).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpolate::tests::dq_to_bone;
+    use crate::BlenderArmature;
+
+    /// Verify that every joint is blended by its own weight.
+    #[test]
+    fn weighted_blend() {
+        let mut start = BTreeMap::new();
+        let mut end = BTreeMap::new();
+        for joint_idx in 0..3 {
+            start.insert(joint_idx, dq_to_bone([1., 0., 0., 0., 0., 0., 0., 0.]));
+            end.insert(joint_idx, dq_to_bone([1., 0., 0., 0., 0., 2., 0., 0.]));
+        }
+
+        let mut weights = BTreeMap::new();
+        weights.insert(0, 1.0);
+        weights.insert(1, 0.25);
+
+        let blended = blend_towards_bones_weighted(&start, &end, &weights);
+
+        assert_eq!(blended[&0], end[&0]);
+        assert_eq!(blended[&1], dq_to_bone([1., 0., 0., 0., 0., 0.5, 0., 0.]));
+        assert_eq!(blended[&2], start[&2]);
+    }
+
+    /// Verify that cubic interpolation passes through the keyframes and follows a smooth curve
+    /// in between them.
+    #[test]
+    fn cubic_interpolation() {
+        let bone = |degrees: f32, x: f32| {
+            let rotation = UnitQuaternion::from_euler_angles(0., 0., degrees.to_radians());
+            let matrix = nalgebra::Matrix4::new_translation(&Vector3::new(x, 0., 0.))
+                * rotation.to_homogeneous();
+
+            BlenderArmature::matrix_to_dual_quat(&Bone::Matrix(matrix))
+        };
+
+        let before = bone(0., 0.);
+        let start = bone(30., 0.);
+        let end = bone(60., 1.);
+        let after = bone(90., 3.);
+
+        assert_approx_eq(interpolate_bone_cubic(before, start, end, after, 0.), start);
+        assert_approx_eq(interpolate_bone_cubic(before, start, end, after, 1.), end);
+
+        // Evenly spaced rotations around one axis are the same as linear interpolation, while
+        // the translation curves towards the keyframes on either side
+        assert_approx_eq(
+            interpolate_bone_cubic(before, start, end, after, 0.5),
+            bone(45., 0.375),
+        );
+    }
+
+    fn assert_approx_eq(actual: Bone, expected: Bone) {
+        let (actual, expected) = match (actual, expected) {
+            (Bone::DualQuat(actual), Bone::DualQuat(expected)) => (actual, expected),
+            _ => unreachable!(),
+        };
+
+        let actual = actual.real.coords.iter().chain(actual.dual.coords.iter());
+        let expected = expected
+            .real
+            .coords
+            .iter()
+            .chain(expected.dual.coords.iter());
+        for (actual, expected) in actual.zip(expected) {
+            assert!((actual - expected).abs() < 1e-5, "{} {}", actual, expected);
+        }
+    }
+}
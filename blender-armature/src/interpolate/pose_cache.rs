@@ -0,0 +1,218 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{BlenderArmature, Bone, FrameOffset, Interpolation, JointIndicesRef, SampleDesc};
+
+/// Remembers the last pose that was sampled from each action so that characters that are playing
+/// the same action at the same time can share it.
+///
+/// In a crowd many characters are often playing the same walk or idle cycle in sync. Without a
+/// cache every one of them interpolates the same keyframes every frame.
+///
+/// Sample times are rounded to the nearest `1 / steps_per_frame` of a frame, so characters that
+/// are a tiny bit out of sync still share a pose. The rounded time is the time that gets
+/// sampled, so every character that gets a cached pose gets exactly the pose that they would
+/// have sampled on their own.
+///
+/// A pose is cached for each action and set of joint indices, so an upper body and a lower body
+/// layer of the same action don't evict each other.
+///
+/// Mark an action as dirty with [`PoseCache.method#mark_dirty`] after changing its keyframes, so
+/// that its pose gets sampled again the next time it is needed.
+#[derive(Debug)]
+pub struct PoseCache {
+    steps_per_frame: u16,
+    poses: HashMap<(String, Vec<u8>), CachedPose>,
+    hits: u64,
+    misses: u64,
+}
+
+#[derive(Debug)]
+struct CachedPose {
+    sample: QuantizedSample,
+    bones: BTreeMap<u8, Bone>,
+    dirty: bool,
+}
+
+/// Everything about a sample, other than the action and joints, that affects the sampled pose.
+#[derive(Debug, PartialEq, Eq)]
+struct QuantizedSample {
+    step: i64,
+    should_loop: bool,
+    looping: bool,
+    interpolation: Interpolation,
+}
+
+impl PoseCache {
+    /// Create an empty cache that rounds sample times to the nearest `1 / steps_per_frame` of a
+    /// frame.
+    ///
+    /// Higher values are smoother but are less likely to be shared. A `steps_per_frame` of 0 is
+    /// treated as 1.
+    pub fn new(steps_per_frame: u16) -> Self {
+        PoseCache {
+            steps_per_frame: steps_per_frame.max(1),
+            poses: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Sample an action's bones, reusing the last pose that was sampled from the action if it
+    /// was for the same joints and the same rounded time. See
+    /// [`BlenderArmature.method#interpolate_bones`].
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`BlenderArmature.method#interpolate_bones`].
+    pub fn sample(
+        &mut self,
+        armature: &BlenderArmature,
+        action_name: &str,
+        joint_indices: &[u8],
+        sample_desc: SampleDesc,
+    ) -> &BTreeMap<u8, Bone> {
+        let steps_per_frame = self.steps_per_frame as f32;
+        let step = (sample_desc.frame_offset.get() * steps_per_frame).round() as i64;
+
+        let sample = QuantizedSample {
+            step,
+            should_loop: sample_desc.should_loop,
+            looping: sample_desc.looping,
+            interpolation: sample_desc.interpolation,
+        };
+
+        let key = (action_name.to_string(), joint_indices.to_vec());
+        let is_cached = match self.poses.get(&key) {
+            Some(cached) => !cached.dirty && cached.sample == sample,
+            None => false,
+        };
+
+        if is_cached {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+
+            let sample_desc = SampleDesc {
+                frame_offset: FrameOffset::new(step as f32 / steps_per_frame),
+                ..sample_desc
+            };
+            let bones = armature.interpolate_bones(
+                action_name,
+                JointIndicesRef::Some(joint_indices),
+                sample_desc,
+            );
+
+            self.poses.insert(
+                key.clone(),
+                CachedPose {
+                    sample,
+                    bones,
+                    dirty: false,
+                },
+            );
+        }
+
+        &self.poses[&key].bones
+    }
+
+    /// Sample the action's bones again the next time that they are needed, such as after
+    /// changing the action's keyframes.
+    pub fn mark_dirty(&mut self, action_name: &str) {
+        for ((cached_action, _), cached) in self.poses.iter_mut() {
+            if cached_action == action_name {
+                cached.dirty = true;
+            }
+        }
+    }
+
+    /// Sample every action again the next time that it is needed, such as after swapping out the
+    /// armature.
+    pub fn mark_all_dirty(&mut self) {
+        for cached in self.poses.values_mut() {
+            cached.dirty = true;
+        }
+    }
+
+    /// Whether the action's cached poses will be sampled again the next time that they are
+    /// needed. Actions without a cached pose aren't dirty.
+    pub fn is_dirty(&self, action_name: &str) -> bool {
+        self.poses
+            .iter()
+            .any(|((cached_action, _), cached)| cached_action == action_name && cached.dirty)
+    }
+
+    /// Remove every cached pose.
+    pub fn clear(&mut self) {
+        self.poses.clear();
+    }
+
+    /// The number of samples that reused a cached pose.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// The number of samples that had to interpolate the action's keyframes.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{action_name, action_with_keyframes, BONE_IDX};
+    use crate::BoneKeyframe;
+    use nalgebra::{DualQuaternion, Quaternion};
+
+    /// Verify that samples at nearly the same time share a pose, and that dirty actions get
+    /// sampled again.
+    #[test]
+    fn share_poses_and_resample_dirty_actions() {
+        let keyframes = vec![
+            BoneKeyframe::new(0, dual_quat(0.)),
+            BoneKeyframe::new(4, dual_quat(1.)),
+        ];
+        let armature = BlenderArmature {
+            bone_space_actions: action_with_keyframes(keyframes),
+            ..BlenderArmature::default()
+        };
+
+        let sample_desc = |frame_offset: f32| SampleDesc {
+            frame_offset: FrameOffset::new(frame_offset),
+            should_loop: false,
+            looping: false,
+            interpolation: Interpolation::Linear,
+        };
+
+        let mut cache = PoseCache::new(2);
+        let joints = [BONE_IDX];
+
+        let first = cache
+            .sample(&armature, &action_name(), &joints, sample_desc(0.98))
+            .clone();
+        let second = cache
+            .sample(&armature, &action_name(), &joints, sample_desc(1.1))
+            .clone();
+
+        assert_eq!(first[&BONE_IDX], dual_quat(0.25));
+        assert_eq!(first, second);
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        cache.sample(&armature, &action_name(), &joints, sample_desc(1.5));
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+
+        cache.mark_dirty(&action_name());
+        assert!(cache.is_dirty(&action_name()));
+
+        cache.sample(&armature, &action_name(), &joints, sample_desc(1.5));
+        assert_eq!((cache.hits(), cache.misses()), (1, 3));
+        assert!(!cache.is_dirty(&action_name()));
+    }
+
+    fn dual_quat(real_w: f32) -> Bone {
+        Bone::DualQuat(DualQuaternion::from_real_and_dual(
+            Quaternion::new(real_w, 0., 0., 0.),
+            Quaternion::new(0., 0., 0., 0.),
+        ))
+    }
+}
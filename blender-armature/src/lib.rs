@@ -10,20 +10,42 @@ use std::collections::HashMap;
 use crate::serde::serialize_hashmap_deterministic;
 
 pub use self::action::*;
+pub use self::action_filter::*;
+pub use self::attachment::*;
 pub use self::bone::*;
+pub use self::bone_mask::*;
+pub use self::bone_names::*;
+pub use self::clip::*;
 pub use self::coordinate_system::*;
 pub use self::export::*;
 pub use self::interpolate::*;
+pub use self::merge::*;
+pub use self::mirror::*;
+pub use self::object_animation::*;
+pub use self::rest_pose::*;
 use std::borrow::Borrow;
 use std::hash::Hash;
 
 mod action;
+mod action_filter;
+mod attachment;
 mod bone;
+mod bone_mask;
+mod bone_names;
+mod bone_usage;
+mod clip;
 mod convert;
 mod coordinate_system;
+#[cfg(feature = "blender-mesh")]
+mod debug_mesh;
 mod export;
 mod interpolate;
+mod merge;
+mod mirror;
+mod object_animation;
+mod rest_pose;
 mod serde;
+mod stable_id;
 
 #[cfg(test)]
 mod test_util;
@@ -55,12 +77,28 @@ pub struct BlenderArmature {
     joint_indices: HashMap<String, u8>,
     bone_child_to_parent: HashMap<u8, u8>,
     inverse_bind_poses: Vec<Bone>,
+    #[serde(default)]
+    bone_rest_poses: Vec<Bone>,
+    #[serde(default)]
+    bone_rolls: Vec<f32>,
+    #[serde(default)]
+    bone_lengths: Vec<f32>,
+    #[serde(default)]
+    bone_stable_ids: Vec<Option<u32>>,
+    #[serde(default, serialize_with = "serialize_hashmap_deterministic")]
+    attachment_points: HashMap<String, AttachmentPoint>,
+    #[serde(default)]
+    keyframe_space: KeyframeSpace,
     #[serde(serialize_with = "serialize_hashmap_deterministic")]
     bone_space_actions: HashMap<String, Action>,
     #[serde(serialize_with = "serialize_hashmap_deterministic")]
     bone_groups: HashMap<String, Vec<u8>>,
     #[serde(default)]
     coordinate_system: CoordinateSystem,
+    #[serde(default)]
+    scene_units: SceneUnits,
+    #[serde(default)]
+    frames_per_second: Option<u8>,
 }
 
 impl BlenderArmature {
@@ -89,7 +127,7 @@ impl BlenderArmature {
     /// Maps bone group name to a vector of the bones indices that are in that bone group.
     ///
     /// ```rust
-    /// # use blender_armature::{Action, BlenderArmature, FrameOffset, SampleDesc, JointIndicesRef};
+    /// # use blender_armature::{Action, BlenderArmature, FrameOffset, Interpolation, SampleDesc, JointIndicesRef};
     /// # use std::time::Duration;
     ///
     /// let armature = create_blender_armature();
@@ -101,7 +139,9 @@ impl BlenderArmature {
     ///         Duration::from_secs(2),
     ///         24,
     ///     ),
-    ///     should_loop: false
+    ///     should_loop: false,
+    ///     looping: false,
+    ///     interpolation: Interpolation::Linear,
     /// };
     ///
     /// let _bones = armature.interpolate_bones(
@@ -197,6 +237,21 @@ impl BlenderArmature {
         self.bone_space_actions.remove(name)
     }
 
+    /// The frame rate of the scene that the armature was exported from.
+    ///
+    /// Useful for creating a [`PlaybackClock`] that plays actions back at the speed that they
+    /// were authored at.
+    ///
+    /// This is `None` for armatures that were exported before we started exporting the frame rate.
+    pub fn frames_per_second(&self) -> Option<u8> {
+        self.frames_per_second
+    }
+
+    /// Set the frame rate that the armature's actions were authored at.
+    pub fn set_frames_per_second(&mut self, frames_per_second: Option<u8>) {
+        self.frames_per_second = frames_per_second;
+    }
+
     /// A map of a bone chil to its parent
     ///
     /// If a bone is not stored in this map then it does not have a parent.
@@ -270,6 +325,14 @@ impl BlenderArmature {
         for bone in self.inverse_bind_poses.iter_mut() {
             bone.transpose();
         }
+
+        for bone in self.bone_rest_poses.iter_mut() {
+            bone.transpose();
+        }
+
+        for attachment_point in self.attachment_points.values_mut() {
+            attachment_point.offset_mut().transpose();
+        }
     }
 }
 
@@ -289,6 +352,15 @@ impl BlenderArmature {
         for bone in self.inverse_bind_poses.iter_mut() {
             *bone = BlenderArmature::matrix_to_dual_quat(bone);
         }
+
+        for bone in self.bone_rest_poses.iter_mut() {
+            *bone = BlenderArmature::matrix_to_dual_quat(bone);
+        }
+
+        for attachment_point in self.attachment_points.values_mut() {
+            let offset = attachment_point.offset_mut();
+            *offset = BlenderArmature::matrix_to_dual_quat(offset);
+        }
     }
 }
 
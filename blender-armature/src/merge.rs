@@ -0,0 +1,184 @@
+use crate::BlenderArmature;
+use std::collections::HashMap;
+
+/// An error while merging armatures with [`BlenderArmature::merge_compatible`].
+///
+/// [`BlenderArmature::merge_compatible`]: struct.BlenderArmature.html#method.merge_compatible
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum MergeArmatureError {
+    /// There has to be at least one armature to merge.
+    #[error("There are no armatures to merge")]
+    NoArmatures,
+    /// The armature's bone names or joint indices aren't the same as the first armature's.
+    #[error("Armature {armature} does not have the same bones and joint indices as {expected}")]
+    Bones {
+        /// The name of the armature that doesn't match
+        armature: String,
+        /// The name of the first armature
+        expected: String,
+    },
+    /// One of the armature's bones has a different parent than in the first armature.
+    #[error("Bone {bone} of armature {armature} has a different parent than in {expected}")]
+    Hierarchy {
+        /// The name of the armature that doesn't match
+        armature: String,
+        /// The name of the first armature
+        expected: String,
+        /// The bone whose parent is different
+        bone: String,
+    },
+    /// The armature's actions can't be played on the first armature because they are in a
+    /// different keyframe space, coordinate system, unit scale or frame rate.
+    #[error("The actions of armature {armature} are not in the same space as those of {expected}")]
+    Convention {
+        /// The name of the armature that doesn't match
+        armature: String,
+        /// The name of the first armature
+        expected: String,
+    },
+    /// Two armatures with the same name both have an action with the same name.
+    #[error("More than one armature has an action named {0}")]
+    DuplicateAction(String),
+}
+
+impl BlenderArmature {
+    /// Merge armatures that share a skeleton, such as every character that was rigged with the
+    /// same rig in its own `.blend` file, into one armature that has all of their actions. So one
+    /// skeleton asset can serve every character instead of being exported again for each one.
+    ///
+    /// Every armature must have the same bones with the same joint indices and parents as the
+    /// first one. The merged armature is a copy of the first armature, including its bind poses,
+    /// with every armature's actions renamed to `{armature name}/{action name}`.
+    ///
+    /// ```
+    /// # use blender_armature::{Action, BlenderArmature};
+    /// let mut hero = BlenderArmature::default();
+    /// hero.set_name("Hero".to_string());
+    /// hero.insert_bone_space_action("Walk".to_string(), Action::new());
+    ///
+    /// let mut villain = hero.clone();
+    /// villain.set_name("Villain".to_string());
+    ///
+    /// let merged = BlenderArmature::merge_compatible(&[hero, villain]).unwrap();
+    /// assert!(merged.bone_space_actions().contains_key("Villain/Walk"));
+    /// assert_eq!(merged.bone_space_actions().len(), 2);
+    /// ```
+    pub fn merge_compatible(
+        armatures: &[BlenderArmature],
+    ) -> Result<BlenderArmature, MergeArmatureError> {
+        let first = armatures.first().ok_or(MergeArmatureError::NoArmatures)?;
+        let bone_names: HashMap<u8, &String> = first
+            .joint_indices
+            .iter()
+            .map(|(name, joint)| (*joint, name))
+            .collect();
+
+        let mut merged = first.clone();
+        merged.bone_space_actions = HashMap::new();
+
+        for armature in armatures.iter() {
+            let expected = first.name.clone();
+
+            if armature.joint_indices != first.joint_indices {
+                return Err(MergeArmatureError::Bones {
+                    armature: armature.name.clone(),
+                    expected,
+                });
+            }
+
+            let mut joints: Vec<&u8> = first.joint_indices.values().collect();
+            joints.sort();
+            for joint in joints {
+                if armature.bone_child_to_parent.get(joint) != first.bone_child_to_parent.get(joint)
+                {
+                    return Err(MergeArmatureError::Hierarchy {
+                        armature: armature.name.clone(),
+                        expected,
+                        bone: bone_names[joint].clone(),
+                    });
+                }
+            }
+
+            if armature.keyframe_space != first.keyframe_space
+                || armature.coordinate_system != first.coordinate_system
+                || armature.scene_units != first.scene_units
+                || armature.frames_per_second != first.frames_per_second
+            {
+                return Err(MergeArmatureError::Convention {
+                    armature: armature.name.clone(),
+                    expected,
+                });
+            }
+
+            for (action_name, action) in armature.bone_space_actions.iter() {
+                let name = format!("{}/{}", armature.name, action_name);
+                if merged.bone_space_actions.contains_key(&name) {
+                    return Err(MergeArmatureError::DuplicateAction(name));
+                }
+
+                merged.bone_space_actions.insert(name, action.clone());
+            }
+        }
+
+        Ok(merged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Action;
+
+    /// Verify that actions are namespaced by their armature and that armatures with a different
+    /// hierarchy or the same name are rejected.
+    #[test]
+    fn merge_armatures_with_same_skeleton() {
+        let armature = |name: &str, action: &str| {
+            let mut armature = BlenderArmature::default();
+            armature.set_name(name.to_string());
+            armature.insert_joint_index("Hips".to_string(), 0);
+            armature.insert_joint_index("Spine".to_string(), 1);
+            armature.insert_joint_index("Head".to_string(), 2);
+            armature.insert_child_to_parent(1, 0);
+            armature.insert_child_to_parent(2, 1);
+            armature.insert_bone_space_action(action.to_string(), Action::new());
+            armature
+        };
+
+        let merged = BlenderArmature::merge_compatible(&[
+            armature("Knight", "Walk"),
+            armature("Archer", "Walk"),
+            armature("Archer", "Shoot"),
+        ])
+        .unwrap();
+        let mut actions: Vec<&String> = merged.bone_space_actions().keys().collect();
+        actions.sort();
+        assert_eq!(actions, vec!["Archer/Shoot", "Archer/Walk", "Knight/Walk"]);
+        assert_eq!(merged.name(), "Knight");
+
+        let mut hunched = armature("Goblin", "Walk");
+        hunched.insert_child_to_parent(2, 0);
+        assert_eq!(
+            BlenderArmature::merge_compatible(&[armature("Knight", "Walk"), hunched]),
+            Err(MergeArmatureError::Hierarchy {
+                armature: "Goblin".to_string(),
+                expected: "Knight".to_string(),
+                bone: "Head".to_string(),
+            })
+        );
+
+        assert_eq!(
+            BlenderArmature::merge_compatible(&[
+                armature("Knight", "Walk"),
+                armature("Knight", "Walk")
+            ]),
+            Err(MergeArmatureError::DuplicateAction(
+                "Knight/Walk".to_string()
+            ))
+        );
+        assert_eq!(
+            BlenderArmature::merge_compatible(&[]),
+            Err(MergeArmatureError::NoArmatures)
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{Action, BlenderArmature, Bone, SortedKeyframes};
+use nalgebra::{DualQuaternion, Matrix4, Quaternion, Vector4};
+use std::collections::HashMap;
+
+/// The plane to reflect an action across.
+///
+/// For an armature that faces -Y in Blender this is typically `YZ`, which swaps left and right.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MirrorPlane {
+    /// Reflect across the YZ plane, negating X
+    YZ,
+    /// Reflect across the XZ plane, negating Y
+    XZ,
+    /// Reflect across the XY plane, negating Z
+    XY,
+}
+
+/// An error while mirroring an action.
+#[derive(Debug, thiserror::Error)]
+pub enum MirrorError {
+    /// One of the bone pairs refers to a bone that the armature doesn't have.
+    #[error("The armature does not have a bone named {0}")]
+    MissingBone(String),
+}
+
+impl MirrorPlane {
+    /// The index of the axis that is perpendicular to the plane.
+    fn normal_axis(&self) -> usize {
+        match self {
+            MirrorPlane::YZ => 0,
+            MirrorPlane::XZ => 1,
+            MirrorPlane::XY => 2,
+        }
+    }
+}
+
+impl BlenderArmature {
+    /// Create a mirrored copy of an action, such as a left strafe from a right strafe.
+    ///
+    /// Every keyframe is reflected across the plane and the keyframes of each pair of bones,
+    /// i.e. `("hand.L", "hand.R")`, are swapped. Bones that aren't in a pair, such as the spine,
+    /// are reflected in place.
+    ///
+    /// Keyframes are reflected in whichever [`KeyframeSpace`] they are in. This matches
+    /// Blender's "Paste X-Flipped Pose" for rigs whose left and right bones have mirrored rolls,
+    /// which is what Blender's symmetrize creates.
+    ///
+    /// Matrices are expected to be column major, so you'll want to call [`transpose_actions`]
+    /// before mirroring.
+    ///
+    /// [`KeyframeSpace`]: enum.KeyframeSpace.html
+    /// [`transpose_actions`]: #method.transpose_actions
+    pub fn mirror_action(
+        &self,
+        action: &Action,
+        plane: MirrorPlane,
+        bone_pairs: &[(&str, &str)],
+    ) -> Result<Action, MirrorError> {
+        let joint_idx = |name: &str| {
+            self.joint_indices
+                .get(name)
+                .cloned()
+                .ok_or_else(|| MirrorError::MissingBone(name.to_string()))
+        };
+
+        let mut mirrored_bones = HashMap::new();
+        for (left, right) in bone_pairs {
+            let left = joint_idx(left)?;
+            let right = joint_idx(right)?;
+
+            mirrored_bones.insert(left, right);
+            mirrored_bones.insert(right, left);
+        }
+
+        let mut mirrored_action = action.clone();
+        let keyframes = mirrored_action.keyframes_mut();
+
+        let mut mirrored_keyframes: HashMap<u8, SortedKeyframes> = HashMap::new();
+        for (bone_idx, mut bone_keyframes) in keyframes.drain() {
+            for keyframe in bone_keyframes.iter_mut() {
+                keyframe.set_bone(mirror_bone(keyframe.bone(), plane));
+            }
+
+            let mirrored_idx = *mirrored_bones.get(&bone_idx).unwrap_or(&bone_idx);
+            mirrored_keyframes.insert(mirrored_idx, bone_keyframes);
+        }
+
+        *keyframes = mirrored_keyframes;
+
+        Ok(mirrored_action)
+    }
+}
+
+/// Reflect a bone's transform across a plane.
+///
+/// For a reflection `M` this is `M * bone * M`, which keeps the rotation a proper rotation.
+fn mirror_bone(bone: Bone, plane: MirrorPlane) -> Bone {
+    let axis = plane.normal_axis();
+
+    match bone {
+        Bone::Matrix(matrix) => {
+            let mut diagonal = Vector4::new(1., 1., 1., 1.);
+            diagonal[axis] = -1.;
+            let reflection = Matrix4::from_diagonal(&diagonal);
+
+            Bone::Matrix(reflection * matrix * reflection)
+        }
+        Bone::DualQuat(dual_quat) => {
+            // Reflecting a rotation keeps the component of its axis that is perpendicular to the
+            // plane and negates the rest. Translations are negated along the perpendicular axis,
+            // which works out to the same pattern negated for the dual part.
+            let reflect = |quat: Quaternion<f32>, sign: f32| {
+                let mut coords = quat.coords * sign;
+                for imag in 0..3 {
+                    if imag != axis {
+                        coords[imag] = -coords[imag];
+                    }
+                }
+                Quaternion::from(coords)
+            };
+
+            Bone::DualQuat(DualQuaternion::from_real_and_dual(
+                reflect(dual_quat.real, 1.),
+                reflect(dual_quat.dual, -1.),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoneKeyframe;
+    use nalgebra::{UnitQuaternion, Vector3};
+
+    /// Verify that we reflect matrices and dual quaternions the same way.
+    #[test]
+    fn mirror_matrix_and_dual_quat() {
+        let matrix = Matrix4::new_translation(&Vector3::new(1., 2., 3.))
+            * UnitQuaternion::from_euler_angles(0.3, 0.5, 0.7).to_homogeneous();
+
+        for plane in [MirrorPlane::YZ, MirrorPlane::XZ, MirrorPlane::XY].iter() {
+            let mirrored = match mirror_bone(Bone::Matrix(matrix), *plane) {
+                Bone::Matrix(mirrored) => mirrored,
+                _ => unreachable!(),
+            };
+            assert_eq!(mirrored.determinant().signum(), 1.);
+            assert_eq!(
+                mirrored[(plane.normal_axis(), 3)],
+                -matrix[(plane.normal_axis(), 3)]
+            );
+
+            let dual_quat = BlenderArmature::matrix_to_dual_quat(&Bone::Matrix(matrix));
+            let mirrored_dual_quat = mirror_bone(dual_quat, *plane);
+            let from_dual_quat = match BlenderArmature::dual_quat_to_matrix(&mirrored_dual_quat) {
+                Bone::Matrix(matrix) => matrix,
+                _ => unreachable!(),
+            };
+
+            for (expected, actual) in mirrored.iter().zip(from_dual_quat.iter()) {
+                assert!(
+                    (expected - actual).abs() < 1e-5,
+                    "{} {}",
+                    mirrored,
+                    from_dual_quat
+                );
+            }
+        }
+    }
+
+    /// Verify that paired bones swap keyframes and unpaired bones are mirrored in place.
+    #[test]
+    fn swap_bone_pairs() {
+        let mut armature = BlenderArmature::default();
+        armature.insert_joint_index("hand.L".to_string(), 0);
+        armature.insert_joint_index("hand.R".to_string(), 1);
+        armature.insert_joint_index("spine".to_string(), 2);
+
+        let translation = |x: f32| Bone::Matrix(Matrix4::new_translation(&Vector3::new(x, 0., 0.)));
+
+        let mut action = Action::new();
+        action.insert_bone_keyframe(0, BoneKeyframe::new(1, translation(1.)));
+        action.insert_bone_keyframe(2, BoneKeyframe::new(1, translation(5.)));
+
+        let mirrored = armature
+            .mirror_action(&action, MirrorPlane::YZ, &[("hand.L", "hand.R")])
+            .unwrap();
+
+        assert!(mirrored.bone_keyframes().get(&0).is_none());
+        assert_eq!(mirrored.bone_keyframes()[&1][0].bone(), translation(-1.));
+        assert_eq!(mirrored.bone_keyframes()[&2][0].bone(), translation(-5.));
+        assert_eq!(mirrored.smallest_frame(), 1);
+
+        match armature.mirror_action(&action, MirrorPlane::YZ, &[("foot.L", "foot.R")]) {
+            Err(MirrorError::MissingBone(name)) => assert_eq!(name, "foot.L"),
+            other => panic!("{:?}", other),
+        };
+    }
+}
@@ -0,0 +1,203 @@
+use crate::serde::serialize_hashmap_deterministic;
+use crate::{Action, BlenderArmature, Bone, SampleDesc};
+use nalgebra::{Matrix4, Vector3};
+use std::collections::HashMap;
+
+pub use self::camera_track::*;
+pub use self::export::*;
+
+mod camera_track;
+mod export;
+
+/// The transform animations of an object that isn't an armature, such as a moving platform or a
+/// rotating fan.
+///
+/// Each action is stored the same way as an armature's actions, with the object's transform
+/// relative to its parent keyframed as the bone at [`ObjectAnimation::TRANSFORM_IDX`]. This means
+/// that object actions can be played using the same [`SampleDesc`] and [`PlaybackClock`] as
+/// armature actions.
+///
+/// [`ObjectAnimation::TRANSFORM_IDX`]: #associatedconstant.TRANSFORM_IDX
+/// [`SampleDesc`]: struct.SampleDesc.html
+/// [`PlaybackClock`]: struct.PlaybackClock.html
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ObjectAnimation {
+    name: String,
+    #[serde(default)]
+    parent: Option<String>,
+    #[serde(serialize_with = "serialize_hashmap_deterministic")]
+    actions: HashMap<String, Action>,
+    #[serde(default)]
+    frames_per_second: Option<u8>,
+}
+
+impl ObjectAnimation {
+    /// The bone index that holds the object's transform in each action's keyframes.
+    pub const TRANSFORM_IDX: u8 = 0;
+
+    /// The name of the object
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Set the name of the object
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    /// The name of the object's parent, if it has one.
+    ///
+    /// Keyframed transforms are relative to the parent.
+    pub fn parent(&self) -> Option<&String> {
+        self.parent.as_ref()
+    }
+
+    /// All of the object's transform actions
+    pub fn actions(&self) -> &HashMap<String, Action> {
+        &self.actions
+    }
+
+    /// Add a transform action
+    pub fn insert_action(&mut self, name: String, action: Action) {
+        self.actions.insert(name, action);
+    }
+
+    /// The frames per second of the scene that the object was exported from
+    pub fn frames_per_second(&self) -> Option<u8> {
+        self.frames_per_second
+    }
+
+    /// Transpose all of the transform matrices in the object's actions.
+    ///
+    /// See [`BlenderArmature::transpose_actions`].
+    ///
+    /// [`BlenderArmature::transpose_actions`]: struct.BlenderArmature.html#method.transpose_actions
+    pub fn transpose_actions(&mut self) {
+        for action in self.actions.values_mut() {
+            for keyframes in action.keyframes_mut().values_mut() {
+                for keyframe in keyframes.iter_mut() {
+                    if let Bone::Matrix(matrix) = keyframe.bone_mut() {
+                        matrix.transpose_mut();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Convert the transform matrices in the object's actions into dual quaternions so that they
+    /// can be interpolated.
+    pub fn matrices_to_dual_quats(&mut self) {
+        for action in self.actions.values_mut() {
+            for keyframes in action.keyframes_mut().values_mut() {
+                for keyframe in keyframes.iter_mut() {
+                    keyframe.set_bone(BlenderArmature::matrix_to_dual_quat(&keyframe.bone()));
+                }
+            }
+        }
+    }
+
+    /// Sample the object's transform, excluding scale.
+    ///
+    /// Returns `None` if the object doesn't have an action with the given name.
+    ///
+    /// # Panics
+    ///
+    /// We don't currently interpolate matrices, so we panic if the keyframes aren't dual
+    /// quaternions.
+    pub fn sample(&self, action_name: &str, sample_desc: SampleDesc) -> Option<Bone> {
+        let action = self.actions.get(action_name)?;
+
+        Some(
+            action
+                .bone_keyframes()
+                .sample(Self::TRANSFORM_IDX, sample_desc),
+        )
+    }
+
+    /// Sample the object's transform relative to its parent, including scale.
+    ///
+    /// Returns `None` if the object doesn't have an action with the given name.
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`sample`].
+    ///
+    /// [`sample`]: #method.sample
+    pub fn sample_matrix(
+        &self,
+        action_name: &str,
+        sample_desc: SampleDesc,
+    ) -> Option<Matrix4<f32>> {
+        let bone = self.sample(action_name, sample_desc)?;
+
+        let mut matrix = match BlenderArmature::dual_quat_to_matrix(&bone) {
+            Bone::Matrix(matrix) => matrix,
+            Bone::DualQuat(_) => unreachable!(),
+        };
+
+        let bone_keyframes = self.actions[action_name].bone_keyframes();
+        if let Some(scale) = bone_keyframes.sample_scale(Self::TRANSFORM_IDX, sample_desc) {
+            matrix = matrix * Matrix4::new_nonuniform_scaling(&Vector3::from(scale));
+        }
+
+        Some(matrix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FrameOffset, Interpolation};
+
+    /// Verify that we can sample an object action as it is written by the object animation
+    /// exporter.
+    #[test]
+    fn sample_exported_object_animation() {
+        let mut object: ObjectAnimation = serde_json::from_str(
+            r#"{
+              "name": "Fan",
+              "parent": null,
+              "actions": {
+                "Spin": {
+                  "bone_keyframes": {
+                    "frame_range_inclusive": [1, 3],
+                    "keyframes": {
+                      "0": [
+                        {
+                          "frame": 1,
+                          "bone": {"Matrix": [1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1]},
+                          "scale": [1, 1, 1]
+                        },
+                        {
+                          "frame": 3,
+                          "bone": {"Matrix": [1, 0, 0, 4, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1]},
+                          "scale": [2, 1, 1]
+                        }
+                      ]
+                    }
+                  },
+                  "pose_markers": {}
+                }
+              },
+              "frames_per_second": 24
+            }"#,
+        )
+        .unwrap();
+
+        object.transpose_actions();
+        object.matrices_to_dual_quats();
+
+        let sample_desc = SampleDesc {
+            frame_offset: FrameOffset::new(1.0),
+            should_loop: false,
+            looping: false,
+            interpolation: Interpolation::Linear,
+        };
+
+        let expected = Matrix4::new_translation(&Vector3::new(2., 0., 0.))
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(1.5, 1., 1.));
+        assert_eq!(object.sample_matrix("Spin", sample_desc).unwrap(), expected);
+
+        assert!(object.sample_matrix("Missing", sample_desc).is_none());
+    }
+}
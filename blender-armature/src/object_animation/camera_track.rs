@@ -0,0 +1,225 @@
+use crate::object_animation::export::parse_json_blocks_from_blender_stdout;
+use nalgebra::{Isometry3, Matrix4, Perspective3, Quaternion, Translation3, UnitQuaternion};
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub type CameraTracksByFilename = HashMap<String, CameraTracksByCameraName>;
+pub type CameraTracksByCameraName = HashMap<String, CameraTrack>;
+
+/// A camera's world transform and lens on every frame of a scene, such as a cutscene camera.
+///
+/// Everything that affects the camera, such as parenting, constraints and an animated focal
+/// length, is baked in when exporting.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct CameraTrack {
+    name: String,
+    frames_per_second: u8,
+    aspect_ratio: f32,
+    frames: Vec<CameraFrame>,
+}
+
+/// The camera on one frame of a [`CameraTrack`].
+///
+/// [`CameraTrack`]: struct.CameraTrack.html
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CameraFrame {
+    frame: u16,
+    translation: [f32; 3],
+    /// w, x, y, z
+    rotation: [f32; 4],
+    fov_y: f32,
+    near: f32,
+    far: f32,
+}
+
+/// The camera at a point in time.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CameraSample {
+    /// Transforms from world space into the camera's view space, where the camera looks down -Z
+    /// with +Y up.
+    pub view: Matrix4<f32>,
+    /// The vertical field of view in radians
+    pub fov_y: f32,
+    /// The distance to the near clipping plane
+    pub near: f32,
+    /// The distance to the far clipping plane
+    pub far: f32,
+    /// The width of the render divided by its height
+    pub aspect_ratio: f32,
+}
+
+impl CameraTrack {
+    /// The name of the camera
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// The frames per second of the scene that the camera was exported from
+    pub fn frames_per_second(&self) -> u8 {
+        self.frames_per_second
+    }
+
+    /// The width of the scene's render divided by its height
+    pub fn aspect_ratio(&self) -> f32 {
+        self.aspect_ratio
+    }
+
+    /// Every frame of the track, in order
+    pub fn frames(&self) -> &Vec<CameraFrame> {
+        &self.frames
+    }
+
+    /// How long the track takes to play
+    pub fn duration(&self) -> Duration {
+        let frame_count = self.frames.len().saturating_sub(1);
+
+        Duration::from_secs_f32(frame_count as f32 / self.frames_per_second as f32)
+    }
+
+    /// Sample the camera some amount of time after the first frame.
+    ///
+    /// Positions and lens parameters are interpolated linearly in between frames and rotations
+    /// are spherically interpolated. Times past the end of the track sample the last frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the track doesn't have any frames.
+    pub fn sample(&self, elapsed: Duration) -> CameraSample {
+        let last_idx = self.frames.len() - 1;
+
+        let frames_elapsed = elapsed.as_secs_f32() * self.frames_per_second as f32;
+        let lower_idx = (frames_elapsed.floor() as usize).min(last_idx);
+        let upper_idx = (lower_idx + 1).min(last_idx);
+
+        let amount = if lower_idx == upper_idx {
+            0.
+        } else {
+            frames_elapsed - lower_idx as f32
+        };
+
+        let lower = &self.frames[lower_idx];
+        let upper = &self.frames[upper_idx];
+
+        let lerp = |start: f32, end: f32| start + (end - start) * amount;
+
+        let translation = Translation3::new(
+            lerp(lower.translation[0], upper.translation[0]),
+            lerp(lower.translation[1], upper.translation[1]),
+            lerp(lower.translation[2], upper.translation[2]),
+        );
+
+        let lower_rotation = lower.rotation();
+        let mut upper_rotation = upper.rotation();
+        // Take the shortest path between the two rotations
+        if lower_rotation.coords.dot(&upper_rotation.coords) < 0. {
+            upper_rotation = UnitQuaternion::new_unchecked(-upper_rotation.into_inner());
+        }
+        let rotation = lower_rotation.slerp(&upper_rotation, amount);
+
+        let world = Isometry3::from_parts(translation, rotation);
+
+        CameraSample {
+            view: world.inverse().to_homogeneous(),
+            fov_y: lerp(lower.fov_y, upper.fov_y),
+            near: lerp(lower.near, upper.near),
+            far: lerp(lower.far, upper.far),
+            aspect_ratio: self.aspect_ratio,
+        }
+    }
+}
+
+#[allow(missing_docs)]
+impl CameraFrame {
+    pub fn frame(&self) -> u16 {
+        self.frame
+    }
+
+    /// The camera's world space position
+    pub fn translation(&self) -> [f32; 3] {
+        self.translation
+    }
+
+    /// The camera's world space rotation
+    pub fn rotation(&self) -> UnitQuaternion<f32> {
+        let [w, x, y, z] = self.rotation;
+        UnitQuaternion::from_quaternion(Quaternion::new(w, x, y, z))
+    }
+
+    /// The vertical field of view in radians
+    pub fn fov_y(&self) -> f32 {
+        self.fov_y
+    }
+
+    pub fn near(&self) -> f32 {
+        self.near
+    }
+
+    pub fn far(&self) -> f32 {
+        self.far
+    }
+}
+
+impl CameraSample {
+    /// A right handed perspective projection matrix for the sampled lens, with depth between
+    /// -1.0 and 1.0.
+    pub fn projection(&self) -> Matrix4<f32> {
+        Perspective3::new(self.aspect_ratio, self.fov_y, self.near, self.far).to_homogeneous()
+    }
+}
+
+/// Given a buffer of standard output from Blender we parse all of the camera track JSON that was
+/// written to stdout by `blender-object-animation-to-json.py`.
+///
+/// Camera track data in stdout will look like:
+///
+/// START_CAMERA_TRACK_JSON /path/to/file.blend my_camera_name
+/// {...}
+/// END_CAMERA_TRACK_JSON /path/to/file.blend my_camera_name
+///
+/// @see blender-object-animation-to-json.py - This is where we write to stdout
+pub fn parse_camera_tracks_from_blender_stdout(blender_stdout: &str) -> CameraTracksByFilename {
+    parse_json_blocks_from_blender_stdout(blender_stdout, "CAMERA_TRACK_JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::{Point3, Vector3};
+
+    /// Verify that we interpolate in between frames and clamp to the last frame.
+    #[test]
+    fn sample_between_frames() {
+        let stdout = r#"
+START_CAMERA_TRACK_JSON /cutscene.blend Camera
+{
+  "name": "Camera", "frames_per_second": 2, "aspect_ratio": 1.5,
+  "frames": [
+    {"frame": 1, "translation": [0, 0, 0], "rotation": [1, 0, 0, 0], "fov_y": 0.5, "near": 0.1, "far": 100},
+    {"frame": 2, "translation": [0, 0, 4], "rotation": [0.70710677, 0, 0, 0.70710677], "fov_y": 1.0, "near": 0.1, "far": 100}
+  ]
+}
+END_CAMERA_TRACK_JSON /cutscene.blend Camera
+"#;
+        let tracks = parse_camera_tracks_from_blender_stdout(stdout);
+        let track = &tracks["/cutscene.blend"]["Camera"];
+
+        assert_eq!(track.duration(), Duration::from_millis(500));
+
+        // Halfway between the frames, rotated 45 degrees around Z
+        let sample = track.sample(Duration::from_millis(250));
+        assert_eq!(sample.fov_y, 0.75);
+        assert_eq!(sample.aspect_ratio, 1.5);
+
+        let camera_position =
+            sample.view.try_inverse().unwrap() * Point3::origin().to_homogeneous();
+        assert!((camera_position.xyz() - Vector3::new(0., 0., 2.)).norm() < 1e-5);
+
+        let camera_x_axis = sample.view.try_inverse().unwrap() * Vector3::x().to_homogeneous();
+        let expected = Vector3::new(1., 1., 0.).normalize();
+        assert!((camera_x_axis.xyz() - expected).norm() < 1e-5);
+
+        // Past the end of the track
+        let sample = track.sample(Duration::from_secs(10));
+        assert_eq!(sample.fov_y, 1.0);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::ObjectAnimation;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+
+pub type ObjectAnimationsByFilename = HashMap<String, ObjectAnimationsByObjectName>;
+pub type ObjectAnimationsByObjectName = HashMap<String, ObjectAnimation>;
+
+/// Given a buffer of standard output from Blender we parse all of the object animation JSON that
+/// was written to stdout by `blender-object-animation-to-json.py`.
+///
+/// Object animation data in stdout will look like:
+///
+/// START_OBJECT_ANIMATION_JSON /path/to/file.blend my_object_name
+/// {...}
+/// END_OBJECT_ANIMATION_JSON /path/to/file.blend my_object_name
+///
+/// @see blender-object-animation-to-json.py - This is where we write to stdout
+pub fn parse_object_animations_from_blender_stdout(
+    blender_stdout: &str,
+) -> ObjectAnimationsByFilename {
+    parse_json_blocks_from_blender_stdout(blender_stdout, "OBJECT_ANIMATION_JSON")
+}
+
+/// Parse every `START_{block_name} /path/to/file.blend object_name` ... `END_{block_name}` block
+/// of JSON in Blender's stdout, grouped by filename and then by object name.
+pub(crate) fn parse_json_blocks_from_blender_stdout<T: DeserializeOwned>(
+    blender_stdout: &str,
+    block_name: &str,
+) -> HashMap<String, HashMap<String, T>> {
+    let start_marker = format!("START_{}", block_name);
+    let end_marker = format!("END_{}", block_name);
+
+    let mut filenames_to_objects: HashMap<String, HashMap<String, T>> = HashMap::new();
+
+    let mut remaining = blender_stdout;
+
+    while let Some(start_idx) = remaining.find(&start_marker) {
+        let end_idx = remaining.find(&end_marker).unwrap();
+
+        let mut lines = remaining[start_idx..end_idx].lines();
+
+        let first_line = lines.next().unwrap();
+        let filename = first_line.split(" ").nth(1).unwrap().to_string();
+        let object_name = first_line.split(" ").last().unwrap().to_string();
+
+        let object_data: String = lines.collect();
+        let object_data: T = serde_json::from_str(&object_data).expect(&format!(
+            "Could not deserialize Blender {} data{}",
+            block_name, &object_data
+        ));
+
+        filenames_to_objects
+            .entry(filename)
+            .or_default()
+            .insert(object_name, object_data);
+
+        remaining = &remaining[end_idx + end_marker.len()..];
+    }
+
+    filenames_to_objects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that we parse every object's animation from stdout.
+    #[test]
+    fn parse_multiple_objects() {
+        let stdout = r#"Blender 2.90
+START_OBJECT_ANIMATION_JSON /foo.blend Fan
+{"name": "Fan", "actions": {}}
+END_OBJECT_ANIMATION_JSON /foo.blend Fan
+START_OBJECT_ANIMATION_JSON /foo.blend Platform
+{"name": "Platform", "parent": "Elevator", "actions": {}}
+END_OBJECT_ANIMATION_JSON /foo.blend Platform
+"#;
+
+        let parsed = parse_object_animations_from_blender_stdout(stdout);
+        let objects = &parsed["/foo.blend"];
+
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects["Fan"].name(), "Fan");
+        assert_eq!(objects["Platform"].parent(), Some(&"Elevator".to_string()));
+    }
+}
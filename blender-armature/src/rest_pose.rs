@@ -0,0 +1,284 @@
+//! Bone rest poses and the conventions that keyframes can be expressed in.
+//!
+//! @see https://docs.blender.org/manual/en/latest/animation/armatures/bones/properties/transform.html - Bone Roll
+
+use crate::{BlenderArmature, Bone};
+use nalgebra::Matrix4;
+
+/// The space that an armature's action keyframes are expressed in.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub enum KeyframeSpace {
+    /// Each keyframe is the bone's location, rotation and scale on top of its rest pose.
+    ///
+    /// This is how Blender stores pose bones, and is what we export.
+    RelativeToRest,
+    /// Each keyframe is the bone's full transform relative to its parent bone, with the rest
+    /// pose (including the bone's roll) already multiplied in.
+    Absolute,
+}
+
+/// An error while using the armature's rest poses.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum RestPoseError {
+    /// The bone, or its parent, doesn't have a rest pose.
+    #[error("Bone {joint_idx} or its parent does not have a rest pose")]
+    MissingRestPose {
+        /// The joint index of the bone
+        joint_idx: u8,
+    },
+    /// The rest pose can't be inverted, such as when the bone was scaled to zero.
+    #[error("The rest pose of bone {joint_idx} is not invertible")]
+    NonInvertible {
+        /// The joint index of the bone whose rest pose can't be inverted
+        joint_idx: u8,
+    },
+}
+
+/// Keyframes are exported from Blender relative to each bone's rest pose.
+impl Default for KeyframeSpace {
+    fn default() -> Self {
+        KeyframeSpace::RelativeToRest
+    }
+}
+
+impl BlenderArmature {
+    /// Every bone's rest pose in armature space, indexed by joint index.
+    ///
+    /// # From Blender
+    ///
+    /// This is `bone.matrix_local`, so unlike the [`inverse_bind_poses`] the armature's world
+    /// space matrix is not included.
+    ///
+    /// [`inverse_bind_poses`]: #method.inverse_bind_poses
+    pub fn bone_rest_poses(&self) -> &Vec<Bone> {
+        &self.bone_rest_poses
+    }
+
+    /// Set the rest poses.
+    pub fn set_bone_rest_poses(&mut self, rest_poses: Vec<Bone>) {
+        self.bone_rest_poses = rest_poses;
+    }
+
+    /// Every bone's roll in radians, indexed by joint index.
+    ///
+    /// The roll is the rotation of the bone around its own head to tail axis. It is already
+    /// included in the [`bone_rest_poses`], but is exported separately since it's often needed
+    /// when re-creating bones in other tools.
+    ///
+    /// [`bone_rest_poses`]: #method.bone_rest_poses
+    pub fn bone_rolls(&self) -> &Vec<f32> {
+        &self.bone_rolls
+    }
+
+    /// Set the bone rolls.
+    pub fn set_bone_rolls(&mut self, rolls: Vec<f32>) {
+        self.bone_rolls = rolls;
+    }
+
+    /// Every bone's length from its head to its tail, indexed by joint index.
+    ///
+    /// Bones point along the Y axis of their [`bone_rest_poses`].
+    ///
+    /// [`bone_rest_poses`]: #method.bone_rest_poses
+    pub fn bone_lengths(&self) -> &Vec<f32> {
+        &self.bone_lengths
+    }
+
+    /// Set the bone lengths.
+    pub fn set_bone_lengths(&mut self, lengths: Vec<f32>) {
+        self.bone_lengths = lengths;
+    }
+
+    /// The space that the action keyframes are currently expressed in.
+    pub fn keyframe_space(&self) -> KeyframeSpace {
+        self.keyframe_space
+    }
+
+    /// A bone's rest pose relative to the rest pose of its parent.
+    ///
+    /// For bones without a parent this is the same as the armature space rest pose.
+    ///
+    /// Errors if the bone or its parent doesn't have a rest pose, or if the parent's rest pose
+    /// can't be inverted.
+    pub fn parent_relative_rest_pose(&self, joint_idx: u8) -> Result<Bone, RestPoseError> {
+        let missing = || RestPoseError::MissingRestPose { joint_idx };
+
+        let rest_pose = *self
+            .bone_rest_poses
+            .get(joint_idx as usize)
+            .ok_or_else(missing)?;
+
+        let parent_idx = match self.bone_child_to_parent.get(&joint_idx) {
+            Some(parent_idx) => *parent_idx,
+            None => return Ok(rest_pose),
+        };
+        let parent_rest_pose = self
+            .bone_rest_poses
+            .get(parent_idx as usize)
+            .ok_or_else(missing)?;
+
+        let non_invertible = RestPoseError::NonInvertible {
+            joint_idx: parent_idx,
+        };
+        let inverse_parent_rest_pose = parent_rest_pose
+            .to_matrix()
+            .try_inverse()
+            .ok_or(non_invertible)?;
+
+        Ok(same_kind_as(
+            &rest_pose,
+            inverse_parent_rest_pose * rest_pose.to_matrix(),
+        ))
+    }
+
+    /// Convert the keyframes in all of the armature's actions to a different space.
+    ///
+    /// Matrices are expected to be column major, so you'll want to call [`transpose_actions`]
+    /// before converting.
+    ///
+    /// Errors without changing any keyframes if a keyframed bone doesn't have a rest pose, or if
+    /// a rest pose that is needed for the conversion can't be inverted.
+    ///
+    /// [`transpose_actions`]: #method.transpose_actions
+    pub fn change_keyframe_space(&mut self, space: KeyframeSpace) -> Result<(), RestPoseError> {
+        if self.keyframe_space == space {
+            return Ok(());
+        }
+
+        let mut rest_poses = vec![];
+        for joint_idx in 0..self.bone_rest_poses.len() {
+            let joint_idx = joint_idx as u8;
+            let rest_pose = self.parent_relative_rest_pose(joint_idx)?.to_matrix();
+
+            let rest_pose = match space {
+                KeyframeSpace::Absolute => rest_pose,
+                KeyframeSpace::RelativeToRest => rest_pose
+                    .try_inverse()
+                    .ok_or(RestPoseError::NonInvertible { joint_idx })?,
+            };
+            rest_poses.push(rest_pose);
+        }
+
+        for action in self.bone_space_actions.values() {
+            for joint_idx in action.bone_keyframes().keys() {
+                if *joint_idx as usize >= rest_poses.len() {
+                    return Err(RestPoseError::MissingRestPose {
+                        joint_idx: *joint_idx,
+                    });
+                }
+            }
+        }
+
+        for (_action_name, action) in self.bone_space_actions.iter_mut() {
+            for (bone_idx, keyframes) in action.keyframes_mut().iter_mut() {
+                let rest_pose = rest_poses[*bone_idx as usize];
+
+                for bone_keyframe in keyframes.iter_mut() {
+                    let bone = bone_keyframe.bone();
+                    let converted = rest_pose * bone.to_matrix();
+
+                    bone_keyframe.set_bone(same_kind_as(&bone, converted));
+                }
+            }
+        }
+
+        self.keyframe_space = space;
+
+        Ok(())
+    }
+}
+
+/// Convert the matrix into the same kind of bone (matrix or dual quaternion) as the original.
+fn same_kind_as(original: &Bone, matrix: Matrix4<f32>) -> Bone {
+    match original {
+        Bone::Matrix(_) => Bone::Matrix(matrix),
+        Bone::DualQuat(_) => BlenderArmature::matrix_to_dual_quat(&Bone::Matrix(matrix)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, BoneKeyframe};
+    use nalgebra::Vector3;
+
+    fn translation(x: f32, y: f32, z: f32) -> Bone {
+        Bone::Matrix(Matrix4::new_translation(&Vector3::new(x, y, z)))
+    }
+
+    fn armature_with_child_keyframe(keyframe: Bone) -> BlenderArmature {
+        let mut armature = BlenderArmature::default();
+
+        armature.insert_child_to_parent(1, 0);
+        armature.set_bone_rest_poses(vec![translation(0., 0., 1.), translation(0., 0., 3.)]);
+
+        let mut action = Action::new();
+        action.insert_bone_keyframe(1, BoneKeyframe::new(0, keyframe));
+        armature.insert_bone_space_action("Action".to_string(), action);
+
+        armature
+    }
+
+    fn child_keyframe(armature: &BlenderArmature) -> Bone {
+        armature.bone_space_actions()["Action"].bone_keyframes()[&1][0].bone()
+    }
+
+    /// Verify that we get a bone's rest pose relative to its parent's rest pose
+    #[test]
+    fn rest_pose_relative_to_parent() {
+        let armature = armature_with_child_keyframe(translation(0., 0., 0.));
+
+        assert_eq!(
+            armature.parent_relative_rest_pose(0),
+            Ok(translation(0., 0., 1.))
+        );
+        assert_eq!(
+            armature.parent_relative_rest_pose(1),
+            Ok(translation(0., 0., 2.))
+        );
+        assert_eq!(
+            armature.parent_relative_rest_pose(2),
+            Err(RestPoseError::MissingRestPose { joint_idx: 2 })
+        );
+    }
+
+    /// Verify that we can convert keyframes to include the rest pose and then back again
+    #[test]
+    fn convert_between_keyframe_spaces() {
+        let mut armature = armature_with_child_keyframe(translation(1., 0., 0.));
+
+        armature
+            .change_keyframe_space(KeyframeSpace::Absolute)
+            .unwrap();
+        assert_eq!(armature.keyframe_space(), KeyframeSpace::Absolute);
+        assert_eq!(child_keyframe(&armature), translation(1., 0., 2.));
+
+        armature
+            .change_keyframe_space(KeyframeSpace::RelativeToRest)
+            .unwrap();
+        assert_eq!(armature.keyframe_space(), KeyframeSpace::RelativeToRest);
+        assert_eq!(child_keyframe(&armature), translation(1., 0., 0.));
+    }
+
+    /// Verify that we error instead of panicking when a bone was scaled to zero, and that the
+    /// keyframes are left as they were.
+    #[test]
+    fn zero_scale_rest_pose() {
+        let mut armature = armature_with_child_keyframe(translation(1., 0., 0.));
+        armature.set_bone_rest_poses(vec![
+            Bone::Matrix(Matrix4::zeros()),
+            translation(0., 0., 3.),
+        ]);
+
+        assert_eq!(
+            armature.parent_relative_rest_pose(1),
+            Err(RestPoseError::NonInvertible { joint_idx: 0 })
+        );
+        assert_eq!(
+            armature.change_keyframe_space(KeyframeSpace::Absolute),
+            Err(RestPoseError::NonInvertible { joint_idx: 0 })
+        );
+        assert_eq!(armature.keyframe_space(), KeyframeSpace::RelativeToRest);
+        assert_eq!(child_keyframe(&armature), translation(1., 0., 0.));
+    }
+}
@@ -0,0 +1,63 @@
+use crate::BlenderArmature;
+
+impl BlenderArmature {
+    /// Every bone's stable id, indexed by joint index.
+    ///
+    /// Unlike bone names, stable ids don't change when bones are renamed in Blender, so they can
+    /// be baked into saved games and prefabs. They're assigned by
+    /// `landon export --assign-stable-ids` and are unique within the armature.
+    ///
+    /// Bones that haven't been assigned an id are `None`, and the ids are empty for armatures
+    /// that were exported by older versions of landon.
+    pub fn bone_stable_ids(&self) -> &Vec<Option<u32>> {
+        &self.bone_stable_ids
+    }
+
+    /// Set the bone stable ids.
+    pub fn set_bone_stable_ids(&mut self, stable_ids: Vec<Option<u32>>) {
+        self.bone_stable_ids = stable_ids;
+    }
+
+    /// The stable id of the bone with this name, if it has one.
+    pub fn bone_stable_id(&self, bone: &str) -> Option<u32> {
+        let joint = *self.joint_indices.get(bone)?;
+
+        self.bone_stable_ids.get(joint as usize).copied().flatten()
+    }
+
+    /// The joint index of the bone with this stable id, such as for finding a bone that was
+    /// referenced by a saved game after it has been renamed.
+    pub fn joint_index_by_stable_id(&self, stable_id: u32) -> Option<u8> {
+        self.bone_stable_ids
+            .iter()
+            .position(|id| *id == Some(stable_id))
+            .map(|joint| joint as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that bones can be found by their stable id after they've been renamed.
+    #[test]
+    fn find_renamed_bone_by_stable_id() {
+        let mut armature = BlenderArmature::default();
+        armature.insert_joint_index("Hips".to_string(), 0);
+        armature.insert_joint_index("Spine".to_string(), 1);
+        armature.insert_joint_index("Tail".to_string(), 2);
+        armature.set_bone_stable_ids(vec![Some(4), Some(7), None]);
+
+        assert_eq!(armature.bone_stable_id("Spine"), Some(7));
+        assert_eq!(armature.bone_stable_id("Tail"), None);
+
+        let mut renamed = BlenderArmature::default();
+        renamed.insert_joint_index("Pelvis".to_string(), 0);
+        renamed.insert_joint_index("Spine".to_string(), 1);
+        renamed.set_bone_stable_ids(vec![Some(4), Some(7)]);
+
+        let joint = renamed.joint_index_by_stable_id(armature.bone_stable_id("Hips").unwrap());
+        assert_eq!(joint, Some(0));
+        assert_eq!(renamed.joint_index_by_stable_id(9), None);
+    }
+}
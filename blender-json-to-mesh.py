@@ -0,0 +1,94 @@
+# Create or update a mesh object in a .blend file from landon mesh JSON.
+#
+# This is the reverse of blender-mesh-to-json.py. It lets you review meshes that were generated
+# or processed in Rust from inside of Blender.
+#
+# blender --background --python-expr "<this script>
+# import_mesh_from_json(*sys.argv[sys.argv.index('--') + 1:])" \
+#     -- /path/to/mesh.json /path/to/file.blend
+
+import bpy
+import json
+import os
+import sys
+
+
+def import_mesh_from_json(json_filepath, blend_filepath):
+    if os.path.exists(blend_filepath):
+        bpy.ops.wm.open_mainfile(filepath=blend_filepath)
+    else:
+        bpy.ops.wm.read_homefile(use_empty=True)
+
+    with open(json_filepath) as json_file:
+        mesh_json = json.load(json_file)
+
+    name = mesh_json['name']
+
+    # Older exports used 'attribs' as the key for the vertex attributes
+    attribs = mesh_json.get('multi_indexed_vertex_attributes') or mesh_json['attribs']
+
+    positions = attribs['positions']['attribute']['data']
+    vertices = [positions[idx:idx + 3] for idx in range(0, len(positions), 3)]
+
+    # Every face gets its own loops, in the same order as the position indices. So the Nth
+    # position index corresponds to the Nth loop.
+    faces = []
+    pointer = 0
+    for num_vertices_in_face in attribs['vertices_in_each_face']:
+        faces.append(attribs['positions']['indices'][pointer:pointer + num_vertices_in_face])
+        pointer += num_vertices_in_face
+
+    mesh_data = bpy.data.meshes.new(name)
+    mesh_data.from_pydata(vertices, [], faces)
+
+    material_indices = attribs.get('material_index') or []
+    for face_idx, material_index in enumerate(material_indices):
+        mesh_data.polygons[face_idx].material_index = material_index
+
+    uvs = attribs.get('uvs')
+    if uvs:
+        uv_layer = mesh_data.uv_layers.new()
+        uv_data = uvs['attribute']['data']
+
+        for loop_idx, uv_idx in enumerate(uvs['indices']):
+            uv_layer.data[loop_idx].uv = (uv_data[uv_idx * 2], uv_data[uv_idx * 2 + 1])
+
+    # Custom normals are set before validating, while the loops still line up with the normal
+    # indices. Validating can then remove invalid faces along with their custom normals.
+    normals = attribs.get('normals')
+    if normals:
+        normal_data = normals['attribute']['data']
+        loop_normals = [normal_data[idx * 3:idx * 3 + 3] for idx in normals['indices']]
+
+        # Custom split normals are ignored unless auto smooth is on (Blender < 4.1)
+        if hasattr(mesh_data, 'use_auto_smooth'):
+            mesh_data.use_auto_smooth = True
+        mesh_data.normals_split_custom_set(loop_normals)
+
+    # By default validating also removes custom data layers, which includes the custom normals
+    mesh_data.validate(clean_customdata=False)
+    mesh_data.update()
+
+    # Replace the mesh data of an existing object with the same name, otherwise create one
+    obj = bpy.data.objects.get(name)
+    if obj is not None and obj.type == 'MESH':
+        previous_mesh_data = obj.data
+        obj.data = mesh_data
+
+        if previous_mesh_data.users == 0:
+            bpy.data.meshes.remove(previous_mesh_data)
+    else:
+        obj = bpy.data.objects.new(name, mesh_data)
+        bpy.context.scene.collection.objects.link(obj)
+
+    mesh_data.name = name
+
+    for property, typed_value in (mesh_json.get('custom_properties') or {}).items():
+        for kind, value in typed_value.items():
+            if kind == 'Vec':
+                # blender-mesh-to-json.py reads lists back out of JSON strings
+                obj[property] = json.dumps([list(item.values())[0] for item in value])
+            else:
+                obj[property] = value
+
+    bpy.ops.wm.save_as_mainfile(filepath=blend_filepath)
@@ -5,8 +5,10 @@ import ast
 import bpy
 import collections
 import json
+import math
 import os
-from mathutils import Vector
+import re
+from mathutils import Matrix, Vector
 
 bl_info = {
     "name": "Export Mesh to JSON",
@@ -34,20 +36,73 @@ class MeshToJSON(bpy.types.Operator):
     # The filepath to write out JSON to
     # filepath = bpy.props.StringProperty(name='filepath')
 
+    # Export the mesh that the object's modifiers, such as geometry nodes, generate instead of
+    # its base mesh
+    evaluate_modifiers: bpy.props.BoolProperty(name='evaluate_modifiers', default=False)
+
+    # Also export the positions on every frame of the object's cloth or soft body simulation
+    bake_point_caches: bpy.props.BoolProperty(name='bake_point_caches', default=False)
+
+    # Export the mesh as this frame of a mesh sequence instead of as a mesh. See MeshSequenceToJSON
+    sequence_frame: bpy.props.IntProperty(name='sequence_frame', default=-1)
+
     def execute(self, context):
         bpy.ops.object.mode_set(mode='OBJECT')
 
         mesh = bpy.context.view_layer.objects.active
 
+        evaluated = None
+        mesh_data = mesh.data
+        if self.evaluate_modifiers:
+            evaluated, mesh_data = evaluated_mesh_data(mesh)
+
+        # Mesh sequences report their warnings once, when the mesh itself is exported
+        if self.sequence_frame < 0:
+            report_mesh_warnings(mesh, mesh_data)
+
         mesh_json = {
             'name': mesh.name,
-            'armature_name': None,
+            # The mesh datablock, which can be shared by many objects
+            'data_name': mesh.data.name,
+            # The file that the object was linked from, if it isn't local
+            'library': bpy.path.abspath(mesh.library.filepath, library=mesh.library.library) if mesh.library else None,
+            # Assigned by `landon export --assign-stable-ids` so that renaming the object doesn't
+            # break references to it
+            'stable_id': mesh.get('landon_id'),
+            # The armatures that deform the mesh, each with its own range of bone indices
+            'armatures': [],
             # [x, y, z]
             'bounding_box': {
                 'min_corner': [], 'max_corner': []
             },
             'materials': [],
             'custom_properties': {},
+            'smoothing_groups': smoothing_groups(mesh_data),
+            # Vertex group name -> position indices of the vertices in the group
+            'vertex_groups': {group.name: [] for group in mesh.vertex_groups},
+            # Face map name -> indices of the faces in the face map. Blender 4.0 removed face maps,
+            # so they're empty there
+            'face_maps': {face_map.name: [] for face_map in getattr(mesh, 'face_maps', [])},
+            # Attribute name -> the attribute's domain and values
+            'custom_attributes': custom_attributes(mesh_data),
+            # Every shape key other than the basis, as deltas from the shape key it's relative to
+            'shape_keys': shape_keys(mesh, mesh_data, self.sequence_frame < 0),
+            # Position indices of the edges that aren't part of any face, such as on a wireframe gizmo
+            'edges': [list(edge.vertices) for edge in mesh_data.edges if edge.is_loose],
+            # The collections that directly contain the object
+            'collections': [collection.name for collection in mesh.users_collection],
+            'visibility': {
+                'hide_render': mesh.hide_render,
+                'hide_viewport': not mesh.visible_get()
+            },
+            # Blender exports Z up positions in Blender units
+            'space': {
+                'scene_units': scene_units(bpy.context.scene),
+                'up_axis': 'Z',
+                'scale_factor': 1.0
+            },
+            # Places the object's local space positions in the scene, as 16 column major values
+            'world_transform': [value for column in mesh.matrix_world.col for value in column],
             'attribs': {
                 'vertices_in_each_face': [],
                 'material_index': [],
@@ -72,6 +127,13 @@ class MeshToJSON(bpy.types.Operator):
                         'attribute_size': 2
                     }
                 },
+                'lightmap_uvs': {
+                    'indices': [],
+                    'attribute': {
+                        'data': [],
+                        'attribute_size': 2
+                    }
+                },
                 'bone_influences': {
                     'bones_per_vertex': {
                         'NonUniform': []
@@ -82,28 +144,53 @@ class MeshToJSON(bpy.types.Operator):
             }
         }
 
-        # We maintain a list of all of the parent armature's bone names so that when exporting bone indices / weights
+        # We maintain a list of all of the armatures' bone names so that when exporting bone indices / weights
         # we use the same order that our armature use.
         # i.e. vertex group 12 that we export is the same as the 12th bone in the parent armature.
         # Without this the 12th vertex group on the mesh might actually be referring the 8th bone in the armature.
         # This would be a problem since our export format is currently based on the order of the bones in the armature.
+        #
+        # Each armature's bones come after the bones of the armatures before it, so the bone index of an armature's
+        # joint is the armature's first joint plus the joint's index.
         allBoneNames = []
+        # The index of the armature in mesh_json['armatures'] for each of the bones in allBoneNames
+        boneArmatures = []
 
-        if mesh.parent is not None and mesh.parent.type == 'ARMATURE':
-            parentArmature = mesh.parent
-            mesh_json['armature_name'] = parentArmature.name
-            for poseBone in parentArmature.pose.bones:
+        for armature in deforming_armatures(mesh):
+            mesh_json['armatures'].append({
+                'name': armature.name,
+                'first_joint': len(allBoneNames),
+                'vertex_groups': []
+            })
+            for poseBone in armature.pose.bones:
                 allBoneNames.append(poseBone.name)
+                boneArmatures.append(len(mesh_json['armatures']) - 1)
+
+        for group in mesh.vertex_groups:
+            if group.name in allBoneNames:
+                armature_index = boneArmatures[allBoneNames.index(group.name)]
+                mesh_json['armatures'][armature_index]['vertex_groups'].append(group.name)
 
         # TODO: Handle triangular polygons, not just quads
         # cube.data.polygons[1].vertices[0]. Check if length
         # of face is 4... Use a triangular face in Blender to unit test.
+        face_map_layer = None
+        if hasattr(mesh_data, 'face_maps'):
+            face_map_layer = mesh_data.face_maps.active
+        lightmap_layer = lightmap_uv_layer(mesh_data)
+
         index = 0
-        for face in mesh.data.polygons:
+        for face in mesh_data.polygons:
             num_vertices_in_face = len(face.vertices)
             mesh_json['attribs']['vertices_in_each_face'].append(num_vertices_in_face)
             mesh_json['attribs']['material_index'].append(face.material_index)
 
+            if face_map_layer is not None:
+                face_map_index = face_map_layer.data[face.index].value
+                if face_map_index >= 0:
+                    face_map_name = mesh.face_maps[face_map_index].name
+                    mesh_json['face_maps'][face_map_name].append(face.index)
+
             for i in range(num_vertices_in_face):
                 mesh_json['attribs']['positions']['indices'].append(face.vertices[i])
                 # TODO: Maintain a dictionary with (x, y, z) => normal index
@@ -113,14 +200,16 @@ class MeshToJSON(bpy.types.Operator):
                 # the same normals. Test this by making a cube with to faces
                 # that have the same normal
                 mesh_json['attribs']['normals']['indices'].append(face.vertices[i])
-                if mesh.data.uv_layers:
+                if mesh_data.uv_layers:
                     mesh_json['attribs']['uvs']['indices'].append(face.loop_indices[i])
+                if lightmap_layer is not None:
+                    mesh_json['attribs']['lightmap_uvs']['indices'].append(face.loop_indices[i])
 
             # TODO: Don't append normals if we've already encountered them
 
             index += 1
 
-        for vert in mesh.data.vertices:
+        for vert in mesh_data.vertices:
             mesh_json['attribs']['positions']['attribute']['data'].append(vert.co.x)
             mesh_json['attribs']['positions']['attribute']['data'].append(vert.co.y)
             mesh_json['attribs']['positions']['attribute']['data'].append(vert.co.z)
@@ -133,6 +222,9 @@ class MeshToJSON(bpy.types.Operator):
             for group in vert.groups:
                 groupName = mesh.vertex_groups[group.group].name
 
+                if group.weight > 0:
+                    mesh_json['vertex_groups'][groupName].append(vert.index)
+
                 if groupName not in allBoneNames:
                     continue
 
@@ -141,20 +233,32 @@ class MeshToJSON(bpy.types.Operator):
                 mesh_json['attribs']['bone_influences']['bone_indices'].append(boneIndex)
                 mesh_json['attribs']['bone_influences']['bone_weights'].append(group.weight)
 
-            if mesh_json['armature_name'] is not None:
+            if mesh_json['armatures']:
                 mesh_json['attribs']['bone_influences']['bones_per_vertex']['NonUniform'].append(num_groups)
 
-        if mesh.data.uv_layers:
-            for loop in mesh.data.uv_layers.active.data:
+        if mesh_data.uv_layers:
+            for loop in mesh_data.uv_layers.active.data:
                 mesh_json['attribs']['uvs']['attribute']['data'].append(loop.uv.x)
                 mesh_json['attribs']['uvs']['attribute']['data'].append(loop.uv.y)
 
-        if not mesh_json['armature_name']:
+        if lightmap_layer is not None:
+            for loop in lightmap_layer.data:
+                mesh_json['attribs']['lightmap_uvs']['attribute']['data'].append(loop.uv.x)
+                mesh_json['attribs']['lightmap_uvs']['attribute']['data'].append(loop.uv.y)
+
+        if not mesh_json['armatures']:
             mesh_json['attribs']['bone_influences'] = None
 
+        # Normals belong to faces, so point clouds and wireframes don't have any
+        if not mesh_json['attribs']['normals']['indices']:
+            mesh_json['attribs']['normals'] = None
+
         if not mesh_json['attribs']['uvs']['indices']:
             mesh_json['attribs']['uvs'] = None
 
+        if not mesh_json['attribs']['lightmap_uvs']['indices']:
+            mesh_json['attribs']['lightmap_uvs'] = None
+
         # TODO: Add unit test for no mesh currently selected
         # if mesh == None or mesh.type != 'MESH':
         #     print("__NO_MESH_SELECTED__", file=sys.stderr)
@@ -173,7 +277,11 @@ class MeshToJSON(bpy.types.Operator):
         # which could be different from its bounding box in bind position.
         bpy.ops.object.mode_set(mode = 'EDIT')
 
-        for corner in mesh.bound_box:
+        # Geometry that was generated by modifiers doesn't have an up to date bounding box, so we use
+        # its vertices instead
+        corners = mesh.bound_box if evaluated is None else [vertex.co for vertex in mesh_data.vertices]
+
+        for corner in corners:
             # Get the Blender world space (within Blender) coordinates for the corner of this mesh.
             # This gives us the actual (x, y, z) coordinates of the corner in Blender's coordinate space,
             # instead of relative to the model's origin.
@@ -194,7 +302,7 @@ class MeshToJSON(bpy.types.Operator):
         mesh_json['bounding_box']['min_corner'] = min_corner
         mesh_json['bounding_box']['max_corner'] = max_corner
 
-        for material in mesh.data.materials:
+        for material in mesh_data.materials:
             if material.node_tree == None:
                 continue;
 
@@ -205,6 +313,10 @@ class MeshToJSON(bpy.types.Operator):
                 roughness = {}
                 metallic = {}
                 normalMap = None
+                # Texture slot -> the uv transform of the texture's mapping node
+                uvTransforms = {}
+                # Texture slot -> the color space of the texture's image
+                colorSpaces = {}
 
                 if node.type == 'BSDF_PRINCIPLED':
                     if len(node.inputs['Base Color'].links) > 0:
@@ -215,6 +327,8 @@ class MeshToJSON(bpy.types.Operator):
 
                         if link.from_node.type == 'TEX_IMAGE':
                             baseColor['ImageTexture'] = link.from_node.image.name
+                            add_uv_transform(uvTransforms, 'BaseColor', link.from_node)
+                            add_color_space(colorSpaces, 'BaseColor', link.from_node)
                         else:
                             color = link.from_node.outputs['Color'].default_value
                             baseColor['Uniform'] = [
@@ -241,6 +355,8 @@ class MeshToJSON(bpy.types.Operator):
                                 link.from_node.image.name,
                                 "R"
                             ]
+                            add_uv_transform(uvTransforms, 'Roughness', link.from_node)
+                            add_color_space(colorSpaces, 'Roughness', link.from_node)
                         elif link.from_node.type == 'SEPRGB':
                             print(mesh.name)
                             # example: ["some-texture.png", "R"]
@@ -248,6 +364,8 @@ class MeshToJSON(bpy.types.Operator):
                                 link.from_node.inputs['Image'].links[0].from_node.image.name,
                                 link.from_socket.name # R, G or B
                             ]
+                            add_uv_transform(uvTransforms, 'Roughness', link.from_node.inputs['Image'].links[0].from_node)
+                            add_color_space(colorSpaces, 'Roughness', link.from_node.inputs['Image'].links[0].from_node)
                         else:
                             roughness['Uniform'] = link.from_node.outputs['Value'].default_value
                     else:
@@ -268,12 +386,16 @@ class MeshToJSON(bpy.types.Operator):
                                 link.from_node.image.name,
                                 "G"
                             ]
+                            add_uv_transform(uvTransforms, 'Metallic', link.from_node)
+                            add_color_space(colorSpaces, 'Metallic', link.from_node)
                         elif link.from_node.type == 'SEPRGB':
                             # example: ["some-texture.png", "G"]
                             metallic['ImageTexture'] = [
                                 link.from_node.inputs['Image'].links[0].from_node.image.name,
                                 link.from_socket.name # R, G or B
                             ]
+                            add_uv_transform(uvTransforms, 'Metallic', link.from_node.inputs['Image'].links[0].from_node)
+                            add_color_space(colorSpaces, 'Metallic', link.from_node.inputs['Image'].links[0].from_node)
                         else:
                             metallic['Uniform'] = link.from_node.outputs['Value'].default_value
 
@@ -290,19 +412,26 @@ class MeshToJSON(bpy.types.Operator):
                         if link.from_node.type == 'NORMAL_MAP':
                             normalMapNode = link.from_node
                             normalMap = normalMapNode.inputs['Color'].links[0].from_node.image.name
+                            add_uv_transform(uvTransforms, 'NormalMap', normalMapNode.inputs['Color'].links[0].from_node)
+                            add_color_space(colorSpaces, 'NormalMap', normalMapNode.inputs['Color'].links[0].from_node)
 
                     mesh_json['materials'].append({
                         'name': material.name,
                         'base_color': baseColor,
                         'roughness': roughness,
                         'metallic': metallic,
-                        'normal_map': normalMap
+                        'normal_map': normalMap,
+                        'uv_transforms': uvTransforms,
+                        'color_spaces': colorSpaces
                     })
 
         for property in mesh.keys():
             # Not sure what this is but it gets automatically added into the properties. So we ignore it
             if property == '_RNA_UI':
                 continue
+            # Exported as the mesh's stable id
+            if property == 'landon_id':
+                continue
 
             # Some properties such as 'cycles_visibility' are automatically inserted by Blender, but can't be
             # serialized.
@@ -344,6 +473,28 @@ class MeshToJSON(bpy.types.Operator):
         # NOTE: Intentionally done in one print statement to get around
         # a bug where other Blender output (in this case from bpy.ops.anim.keyframe_delete(override, type='LocRotScale')
         # calls in blender-iks-to-fks) was getting mixed in with our JSON output
+        if self.sequence_frame >= 0:
+            frame_json = {
+                'frame': self.sequence_frame,
+                'frames_per_second': round(bpy.context.scene.render.fps / bpy.context.scene.render.fps_base),
+                'mesh': mesh_json
+            }
+
+            # START_MESH_SEQUENCE_FRAME_JSON $BLENDER_FILEPATH $MESH_NAME
+            # ... mesh sequence frame json ...
+            # END_MESH_SEQUENCE_FRAME_JSON $BLENDER_FILEPATH $MESH_NAME
+            output = "START_MESH_SEQUENCE_FRAME_JSON " + bpy.data.filepath + " " + mesh.name
+            output += "\n"
+            output += json.dumps(frame_json)
+            output += "\n"
+            output += "END_MESH_SEQUENCE_FRAME_JSON " + bpy.data.filepath + " " + mesh.name
+            print(output)
+
+            if evaluated is not None:
+                evaluated.to_mesh_clear()
+
+            return {'FINISHED'}
+
         output = "START_MESH_JSON " + bpy.data.filepath + " " + mesh.name
         output += "\n"
         output += json.dumps(mesh_json)
@@ -351,13 +502,777 @@ class MeshToJSON(bpy.types.Operator):
         output += "END_MESH_JSON " + bpy.data.filepath + " " + mesh.name
         print(output)
 
+        position_count = len(mesh_data.vertices)
+
+        if evaluated is not None:
+            evaluated.to_mesh_clear()
+
+        if self.bake_point_caches:
+            point_cache = point_cache_json(mesh, position_count)
+            if point_cache is not None:
+                # START_POINT_CACHE_JSON $BLENDER_FILEPATH $MESH_NAME
+                # ... point cache json ...
+                # END_POINT_CACHE_JSON $BLENDER_FILEPATH $MESH_NAME
+                output = "START_POINT_CACHE_JSON " + bpy.data.filepath + " " + mesh.name
+                output += "\n"
+                output += json.dumps(point_cache)
+                output += "\n"
+                output += "END_POINT_CACHE_JSON " + bpy.data.filepath + " " + mesh.name
+                print(output)
+
+        if len(mesh.particle_systems) > 0:
+            # START_PARTICLE_SYSTEMS_JSON $BLENDER_FILEPATH $MESH_NAME
+            # ... particle systems json ...
+            # END_PARTICLE_SYSTEMS_JSON $BLENDER_FILEPATH $MESH_NAME
+            output = "START_PARTICLE_SYSTEMS_JSON " + bpy.data.filepath + " " + mesh.name
+            output += "\n"
+            output += json.dumps(particle_systems_json(mesh))
+            output += "\n"
+            output += "END_PARTICLE_SYSTEMS_JSON " + bpy.data.filepath + " " + mesh.name
+            print(output)
+
+        return {'FINISHED'}
+
+def particle_systems_json(obj):
+    """
+    The objects that each of the object's particle systems instance, as world space transforms,
+    and the world space strands of its hair particle systems.
+
+    Instances come from the depsgraph so that both object and collection instancing are
+    included, with every instance referring to the object that it instances by name.
+    """
+    depsgraph = bpy.context.evaluated_depsgraph_get()
+    evaluated = obj.evaluated_get(depsgraph)
+
+    systems = []
+    systems_by_name = {}
+    for particle_system in evaluated.particle_systems:
+        system_json = {'name': particle_system.name, 'instances': [], 'hair': []}
+        systems.append(system_json)
+        systems_by_name[particle_system.name] = system_json
+
+        if particle_system.settings.type == 'HAIR':
+            system_json['hair'] = hair_strands(evaluated, particle_system)
+
+    for instance in depsgraph.object_instances:
+        if not instance.is_instance or instance.particle_system is None:
+            continue
+        if instance.parent is None or instance.parent.original != obj:
+            continue
+
+        system_json = systems_by_name.get(instance.particle_system.name)
+        if system_json is None:
+            continue
+
+        loc, rot, scale = instance.matrix_world.decompose()
+
+        system_json['instances'].append({
+            'object': instance.object.original.name,
+            'translation': [loc[0], loc[1], loc[2]],
+            # w, x, y, z
+            'rotation': [rot[0], rot[1], rot[2], rot[3]],
+            'scale': [scale[0], scale[1], scale[2]]
+        })
+
+    return systems
+
+def hair_strands(evaluated, particle_system):
+    """
+    Every strand of a hair particle system as a polyline with world space points, tapering from
+    the hair's root radius to its tip radius.
+    """
+    settings = particle_system.settings
+    steps = 2 ** settings.display_step + 1
+
+    root_radius = settings.root_radius * settings.radius_scale
+    tip_radius = settings.tip_radius * settings.radius_scale
+
+    strands = []
+    for particle_idx in range(len(particle_system.particles)):
+        points = []
+        thickness = []
+        for step in range(steps):
+            co = particle_system.co_hair(evaluated, particle_no=particle_idx, step=step)
+            points.append([co[0], co[1], co[2]])
+
+            amount = step / (steps - 1)
+            thickness.append(root_radius + (tip_radius - root_radius) * amount)
+
+        strands.append({
+            'layer': particle_system.name,
+            'points': points,
+            'thickness': thickness,
+            'cyclic': False
+        })
+
+    return strands
+
+def point_cache_json(obj, position_count):
+    """
+    The local space positions on every frame of the object's first cloth or soft body simulation,
+    or None if it isn't simulated.
+
+    Frames are stepped through in order so that simulations that haven't been baked get
+    simulated as we go. Simulations whose evaluated mesh doesn't have one position for every
+    exported position, such as when a subdivision surface modifier comes after the simulation,
+    are skipped.
+    """
+    simulations = [
+        modifier for modifier in obj.modifiers if modifier.type in ('CLOTH', 'SOFT_BODY')
+    ]
+    if simulations == []:
+        return None
+
+    scene = bpy.context.scene
+    original_frame = scene.frame_current
+
+    point_cache = simulations[0].point_cache
+    frame_start = point_cache.frame_start
+    frame_end = point_cache.frame_end
+
+    frames = []
+    for frame in range(frame_start, frame_end + 1):
+        scene.frame_set(frame)
+
+        depsgraph = bpy.context.evaluated_depsgraph_get()
+        evaluated = obj.evaluated_get(depsgraph)
+        mesh_data = evaluated.to_mesh()
+
+        if len(mesh_data.vertices) != position_count:
+            evaluated.to_mesh_clear()
+            scene.frame_set(original_frame)
+            return None
+
+        positions = [0.0] * (position_count * 3)
+        mesh_data.vertices.foreach_get('co', positions)
+        frames.append(positions)
+
+        evaluated.to_mesh_clear()
+
+    scene.frame_set(original_frame)
+
+    return {
+        'name': obj.name,
+        'frames_per_second': round(scene.render.fps / scene.render.fps_base),
+        'frame_range_inclusive': [frame_start, frame_end],
+        'frames': frames
+    }
+
+# Write the active mesh on every frame of the scene to stdout as a mesh sequence, for effects
+# whose topology changes from frame to frame such as fluid simulations and Alembic caches.
+#
+# Meshes without a fluid domain or mesh sequence cache modifier are skipped.
+class MeshSequenceToJSON(bpy.types.Operator):
+    """Export the active mesh on every frame of the scene as a mesh sequence"""
+    bl_idname = 'import_export.meshsequence2json'
+    bl_label = 'Export Mesh Sequence to JSON'
+    bl_options = {'REGISTER'}
+    bl_category = 'Import-Export'
+
+    def execute(self, context):
+        obj = bpy.context.view_layer.objects.active
+
+        if not is_mesh_sequence(obj):
+            return {'FINISHED'}
+
+        scene = bpy.context.scene
+        original_frame = scene.frame_current
+
+        for frame in range(scene.frame_start, scene.frame_end + 1):
+            scene.frame_set(frame)
+            bpy.ops.import_export.mesh2json(evaluate_modifiers=True, sequence_frame=frame)
+
+        scene.frame_set(original_frame)
+
+        return {'FINISHED'}
+
+def is_mesh_sequence(obj):
+    """
+    Whether the object's topology can change from frame to frame, such as a fluid domain or an
+    object that plays back an Alembic cache.
+    """
+    for modifier in obj.modifiers:
+        if modifier.type == 'MESH_SEQUENCE_CACHE':
+            return True
+        if modifier.type == 'FLUID' and modifier.fluid_type == 'DOMAIN':
+            return True
+
+    return False
+
+def add_uv_transform(uv_transforms, slot, image_node):
+    """
+    Record the uv transform of the mapping node that feeds into the image texture node's vector,
+    if there is one.
+
+    Only the X and Y of the location and scale and the Z of the rotation affect uvs. Texture
+    mappings move the texture instead of the uvs, so they are exported as an inverse transform.
+    """
+    vector = image_node.inputs.get('Vector')
+    if vector is None or len(vector.links) == 0:
+        return
+
+    mapping = vector.links[0].from_node
+    if mapping.type != 'MAPPING' or mapping.vector_type not in ('POINT', 'TEXTURE'):
+        return
+
+    # The mapping's transform became node inputs in Blender 2.81
+    if 'Location' in mapping.inputs:
+        location = mapping.inputs['Location'].default_value
+        rotation = mapping.inputs['Rotation'].default_value
+        scale = mapping.inputs['Scale'].default_value
+    else:
+        location = mapping.translation
+        rotation = mapping.rotation
+        scale = mapping.scale
+
+    uv_transforms[slot] = {
+        'translation': [location[0], location[1]],
+        'rotation': rotation[2],
+        'scale': [scale[0], scale[1]],
+        'inverse': mapping.vector_type == 'TEXTURE'
+    }
+
+# Blender color spaces -> how the texture's values are encoded
+TEXTURE_COLOR_SPACES = {
+    'sRGB': 'Srgb',
+    'Linear': 'Linear',
+    'Linear Rec.709': 'Linear',
+    'Non-Color': 'NonColor',
+    'Raw': 'NonColor',
+}
+
+def add_color_space(color_spaces, slot, image_node):
+    """
+    Record the color space setting of the image texture node's image, so that data textures
+    such as normal maps don't get gamma corrected.
+    """
+    if image_node.image is None:
+        return
+
+    name = image_node.image.colorspace_settings.name
+    color_spaces[slot] = TEXTURE_COLOR_SPACES.get(name, {'Other': name})
+
+def warn(obj, kind, message):
+    """
+    Report something that couldn't be exported so that it doesn't go missing silently.
+
+    WARN_JSON {"blend_file": $BLENDER_FILEPATH, "object": $OBJECT_NAME, "kind": $KIND, "message": $MESSAGE}
+    """
+    print("WARN_JSON " + json.dumps({
+        'blend_file': bpy.data.filepath,
+        'object': obj.name,
+        'kind': kind,
+        'message': message
+    }))
+
+def report_mesh_warnings(obj, mesh_data):
+    """
+    Warn about the parts of the mesh that landon skips or can't handle.
+    """
+    ngons = sum(1 for face in mesh_data.polygons if len(face.vertices) > 4)
+    if ngons > 0:
+        warn(obj, 'NGon', str(ngons) + ' faces have more than 4 vertices and can not be triangulated')
+
+    lightmap_layer = lightmap_uv_layer(mesh_data)
+    unexported_uv_layers = [
+        uv_layer.name for uv_layer in mesh_data.uv_layers
+        if uv_layer != mesh_data.uv_layers.active and uv_layer != lightmap_layer
+    ]
+    if unexported_uv_layers:
+        warn(obj, 'UnexportedUvLayers', 'UV maps ' + ', '.join(unexported_uv_layers) + ' are not exported')
+
+    if len(obj.constraints) > 0:
+        warn(obj, 'Constraints', 'Constraints ' + ', '.join(c.name for c in obj.constraints) + ' are not exported')
+
+    if obj.matrix_world.determinant() < 0:
+        warn(obj, 'NegativeScale', 'The object has a negative scale, so its faces are wound the other way in the world')
+
+def deforming_armatures(obj):
+    """
+    The armatures that deform the object, starting with its parent armature followed by the
+    armatures of its armature modifiers.
+    """
+    armatures = []
+
+    if obj.parent is not None and obj.parent.type == 'ARMATURE':
+        armatures.append(obj.parent)
+
+    for modifier in obj.modifiers:
+        if modifier.type == 'ARMATURE' and modifier.object is not None:
+            if modifier.object not in armatures:
+                armatures.append(modifier.object)
+
+    return armatures
+
+def evaluated_mesh_data(obj):
+    """
+    Evaluate the object's modifiers, such as geometry nodes, returning the evaluated object and
+    its realized mesh data. Free the mesh data with `to_mesh_clear` on the evaluated object.
+
+    Armature modifiers are disabled while evaluating so that the mesh stays in its bind pose.
+    """
+    armature_modifiers = [
+        modifier for modifier in obj.modifiers if modifier.type == 'ARMATURE' and modifier.show_viewport
+    ]
+    for modifier in armature_modifiers:
+        modifier.show_viewport = False
+
+    depsgraph = bpy.context.evaluated_depsgraph_get()
+    depsgraph.update()
+
+    evaluated = obj.evaluated_get(depsgraph)
+    mesh_data = evaluated.to_mesh(preserve_all_data_layers=True, depsgraph=depsgraph)
+
+    for modifier in armature_modifiers:
+        modifier.show_viewport = True
+
+    return evaluated, mesh_data
+
+def smoothing_groups(mesh_data):
+    """
+    Calculate smoothing group bitflags for every face.
+
+    Two faces that share an edge are smoothed across it if they share at least one bit.
+    Flat shaded faces are in smoothing group 0.
+    """
+    polygons = mesh_data.polygons
+
+    sharp_edge_keys = set(edge.key for edge in mesh_data.edges if edge.use_edge_sharp)
+    # Auto smooth was removed in Blender 4.1
+    use_auto_smooth = getattr(mesh_data, 'use_auto_smooth', False)
+
+    # Edge key -> the indices of the faces that use the edge
+    edge_faces = {}
+    for face in polygons:
+        for edge_key in face.edge_keys:
+            edge_faces.setdefault(edge_key, []).append(face.index)
+
+    def smooth_across(edge_key, face_a, face_b):
+        if edge_key in sharp_edge_keys:
+            return False
+        if not face_a.use_smooth or not face_b.use_smooth:
+            return False
+        if use_auto_smooth and face_a.normal.angle(face_b.normal, 0) > mesh_data.auto_smooth_angle:
+            return False
+        return True
+
+    # Flood fill across smooth edges to find the regions of faces that are smoothed together
+    region_of_face = [None] * len(polygons)
+    region_count = 0
+    for face in polygons:
+        if not face.use_smooth or region_of_face[face.index] is not None:
+            continue
+
+        region_of_face[face.index] = region_count
+        stack = [face.index]
+        while stack:
+            current = polygons[stack.pop()]
+            for edge_key in current.edge_keys:
+                for neighbor in edge_faces[edge_key]:
+                    if region_of_face[neighbor] is not None:
+                        continue
+                    if smooth_across(edge_key, current, polygons[neighbor]):
+                        region_of_face[neighbor] = region_count
+                        stack.append(neighbor)
+
+        region_count += 1
+
+    # Regions that touch each other need different bits so that they aren't smoothed together
+    touching_regions = [set() for _ in range(region_count)]
+    for faces in edge_faces.values():
+        for a in faces:
+            for b in faces:
+                region_a = region_of_face[a]
+                region_b = region_of_face[b]
+                if region_a is not None and region_b is not None and region_a != region_b:
+                    touching_regions[region_a].add(region_b)
+
+    region_bits = []
+    for region in range(region_count):
+        used_bits = set(region_bits[other] for other in touching_regions[region] if other < region)
+        bit = next((bit for bit in range(32) if bit not in used_bits), 0)
+        region_bits.append(bit)
+
+    return [0 if region is None else 1 << region_bits[region] for region in region_of_face]
+
+def scene_units(scene):
+    """
+    The scene's unit system and how many meters one Blender unit is.
+    """
+    return {
+        'system': scene.unit_settings.system.capitalize(),
+        'scale_length': scene.unit_settings.scale_length
+    }
+
+def lightmap_uv_layer(mesh_data):
+    """
+    The uv map that lightmaps are baked into, which is the first uv map other than the active one
+    whose name contains 'lightmap', such as 'Lightmap' or 'UVMap_Lightmap'.
+    """
+    for uv_layer in mesh_data.uv_layers:
+        if uv_layer == mesh_data.uv_layers.active:
+            continue
+        if 'lightmap' in uv_layer.name.lower():
+            return uv_layer
+
+    return None
+
+def shape_keys(obj, mesh_data, report_warnings):
+    """
+    Export the position delta of every vertex in every shape key, relative to the shape key that
+    the shape key is relative to, along with the shape key's current value and the driver that
+    sets its value from bone transforms.
+    """
+    if mesh_data.shape_keys is None:
+        return []
+
+    exported = []
+    for key_block in mesh_data.shape_keys.key_blocks:
+        if key_block == mesh_data.shape_keys.reference_key:
+            continue
+
+        deltas = []
+        for point, relative_point in zip(key_block.data, key_block.relative_key.data):
+            delta = point.co - relative_point.co
+            deltas.extend([delta.x, delta.y, delta.z])
+
+        exported.append({
+            'name': key_block.name,
+            'position_deltas': {
+                'data': deltas,
+                'attribute_size': 3
+            },
+            'weight': key_block.value,
+            'driver': shape_key_driver(obj, mesh_data, key_block, report_warnings)
+        })
+
+    return exported
+
+# Driver variable transform types -> our transform channels
+DRIVER_TRANSFORM_CHANNELS = {
+    'LOC_X': 'LocationX',
+    'LOC_Y': 'LocationY',
+    'LOC_Z': 'LocationZ',
+    'ROT_X': 'RotationX',
+    'ROT_Y': 'RotationY',
+    'ROT_Z': 'RotationZ',
+    'SCALE_X': 'ScaleX',
+    'SCALE_Y': 'ScaleY',
+    'SCALE_Z': 'ScaleZ',
+}
+
+# Pose bone properties that single property driver variables can read -> our transform channel
+# for every axis
+DRIVER_PROPERTY_CHANNELS = {
+    'location': ['LocationX', 'LocationY', 'LocationZ'],
+    'rotation_euler': ['RotationX', 'RotationY', 'RotationZ'],
+    'scale': ['ScaleX', 'ScaleY', 'ScaleZ'],
+}
+
+def shape_key_driver(obj, mesh_data, key_block, report_warnings):
+    """
+    Export the driver that sets the shape key's value from bone transforms, or None if the shape
+    key isn't driven or its driver can't be exported.
+    """
+    animation_data = mesh_data.shape_keys.animation_data
+    if animation_data is None:
+        return None
+
+    data_path = 'key_blocks["' + key_block.name + '"].value'
+    fcurve = next((fcurve for fcurve in animation_data.drivers if fcurve.data_path == data_path), None)
+    if fcurve is None or fcurve.mute:
+        return None
+
+    def skip(reason):
+        if report_warnings:
+            warn(obj, 'ShapeKeyDrivers', 'The driver of shape key ' + key_block.name + ' is not exported because ' + reason)
+        return None
+
+    driver = fcurve.driver
+    if not driver.is_valid:
+        return skip('it is invalid')
+
+    inputs = []
+    for variable in driver.variables:
+        driver_input = driver_variable_input(variable)
+        if driver_input is None:
+            return skip('its variable ' + variable.name + ' does not read a local bone transform')
+        inputs.append(driver_input)
+
+    combine = 'Sum'
+    offset = 0.0
+    if driver.type == 'AVERAGE':
+        for driver_input in inputs:
+            driver_input['coefficient'] = 1.0 / len(inputs)
+    elif driver.type == 'MIN':
+        combine = 'Min'
+    elif driver.type == 'MAX':
+        combine = 'Max'
+    elif driver.type == 'SCRIPTED':
+        # Only simple expressions are safe to evaluate without Python scripts being trusted
+        linear = None
+        if getattr(driver, 'is_simple_expression', False):
+            linear = linear_expression(driver.expression, [variable.name for variable in driver.variables])
+        if linear is None:
+            return skip('its expression ' + driver.expression + ' is not linear')
+        offset, coefficients = linear
+        for driver_input, coefficient in zip(inputs, coefficients):
+            driver_input['coefficient'] = coefficient
+
+    curve = []
+    keyframes = sorted(point.co.x for point in fcurve.keyframe_points)
+    if keyframes:
+        # Sample the F-Curve so that bezier interpolation and modifiers are kept
+        start, end = keyframes[0], keyframes[-1]
+        samples = 32 if end > start else 0
+        inputs_to_sample = [start + (end - start) * sample / max(samples, 1) for sample in range(samples + 1)]
+        if fcurve.extrapolation == 'LINEAR':
+            inputs_to_sample = [start - 1000.0] + inputs_to_sample + [end + 1000.0]
+        curve = [[x, fcurve.evaluate(x)] for x in inputs_to_sample]
+    elif len(fcurve.modifiers) > 0:
+        # Modifiers such as generators scale and offset the driver's value, which we fold into
+        # the coefficients and the offset
+        scale = fcurve.evaluate(1.0) - fcurve.evaluate(0.0)
+        shift = fcurve.evaluate(0.0)
+        for x in [2.0, -3.0]:
+            if abs(fcurve.evaluate(x) - (shift + scale * x)) > 1e-4 * max(1.0, abs(shift + scale * x)):
+                return skip('its F-Curve modifiers are not linear')
+
+        for driver_input in inputs:
+            driver_input['coefficient'] *= scale
+        offset = offset * scale + shift
+        if scale < 0 and combine != 'Sum':
+            combine = 'Max' if combine == 'Min' else 'Min'
+
+    return {
+        'inputs': inputs,
+        'combine': combine,
+        'offset': offset,
+        'curve': curve
+    }
+
+def driver_variable_input(variable):
+    """
+    The armature, bone and transform channel that a driver variable reads, or None if it reads
+    anything other than a bone's transform relative to its rest pose.
+    """
+    target = variable.targets[0]
+    armature = target.id
+    if not isinstance(armature, bpy.types.Object) or armature.type != 'ARMATURE':
+        return None
+
+    if variable.type == 'TRANSFORMS':
+        pose_bone = armature.pose.bones.get(target.bone_target)
+        channel = DRIVER_TRANSFORM_CHANNELS.get(target.transform_type)
+        if pose_bone is None or channel is None:
+            return None
+        if target.transform_space not in ('LOCAL_SPACE', 'TRANSFORM_SPACE'):
+            return None
+        # Rotations are read as XYZ euler angles
+        if target.transform_type.startswith('ROT_'):
+            uses_other_euler_order = pose_bone.rotation_mode not in ('XYZ', 'QUATERNION', 'AXIS_ANGLE')
+            if target.rotation_mode != 'XYZ' and (target.rotation_mode != 'AUTO' or uses_other_euler_order):
+                return None
+        bone_name = pose_bone.name
+    elif variable.type == 'SINGLE_PROP':
+        # Such as pose.bones["jaw"].location[1]
+        match = re.fullmatch(r'pose\.bones\["(.+)"\]\.(location|rotation_euler|scale)\[([0-2])\]', target.data_path)
+        if match is None or match.group(1) not in armature.pose.bones:
+            return None
+        bone_name, property_name, axis = match.group(1), match.group(2), int(match.group(3))
+        if property_name == 'rotation_euler' and armature.pose.bones[bone_name].rotation_mode != 'XYZ':
+            return None
+        channel = DRIVER_PROPERTY_CHANNELS[property_name][axis]
+    else:
+        return None
+
+    return {
+        'armature': armature.name,
+        'bone': bone_name,
+        'channel': channel,
+        'coefficient': 1.0
+    }
+
+def linear_expression(expression, variable_names):
+    """
+    The constant and the coefficient of every variable of a driver expression, or None if the
+    expression isn't linear in its variables, such as `0.5 * jaw - 0.1`.
+    """
+    namespace = {name: getattr(math, name) for name in dir(math) if not name.startswith('_')}
+    namespace.update({'__builtins__': {}, 'min': min, 'max': max, 'abs': abs, 'round': round})
+
+    def evaluate(values):
+        return float(eval(expression, namespace, dict(zip(variable_names, values))))
+
+    count = len(variable_names)
+    try:
+        constant = evaluate([0.0] * count)
+        coefficients = [
+            evaluate([1.0 if other == variable else 0.0 for other in range(count)]) - constant
+            for variable in range(count)
+        ]
+
+        # A linear expression matches its coefficients everywhere, not just where we measured them
+        for values in ([2.0] * count, [0.7 * variable - 1.5 for variable in range(count)]):
+            expected = constant + sum(coefficient * value for coefficient, value in zip(coefficients, values))
+            if abs(evaluate(values) - expected) > 1e-4 * max(1.0, abs(expected)):
+                return None
+    except Exception:
+        return None
+
+    return constant, coefficients
+
+# Blender attribute data type -> (our data type, attribute size, the property that holds the value)
+CUSTOM_ATTRIBUTE_TYPES = {
+    'FLOAT': ('Float', 1, 'value'),
+    'FLOAT2': ('Float', 2, 'vector'),
+    'FLOAT_VECTOR': ('Float', 3, 'vector'),
+    'FLOAT_COLOR': ('Float', 4, 'color'),
+    'BYTE_COLOR': ('Float', 4, 'color'),
+    'INT': ('Int', 1, 'value'),
+    'INT8': ('Int', 1, 'value'),
+}
+
+CUSTOM_ATTRIBUTE_DOMAINS = {
+    'POINT': 'Point',
+    'CORNER': 'Corner',
+    'FACE': 'Face',
+}
+
+# Attributes that Blender creates for its own data, some of which we already export
+BUILTIN_ATTRIBUTES = {'position', 'material_index', 'sharp_face', 'crease_vert', 'bevel_weight_vert'}
+
+def custom_attributes(mesh_data):
+    """
+    Export the named attributes that were added to the mesh, such as by geometry nodes.
+
+    Corner values are exported in the same order as the position indices.
+    """
+    # Named attributes were added in Blender 2.91
+    if not hasattr(mesh_data, 'attributes'):
+        return {}
+
+    skipped = set(BUILTIN_ATTRIBUTES)
+    skipped.update(uv_layer.name for uv_layer in mesh_data.uv_layers)
+
+    exported = {}
+    for attribute in mesh_data.attributes:
+        # Attributes that start with a '.' are internal to Blender
+        if attribute.name.startswith('.') or attribute.name in skipped:
+            continue
+        if attribute.domain not in CUSTOM_ATTRIBUTE_DOMAINS:
+            continue
+        if attribute.data_type not in CUSTOM_ATTRIBUTE_TYPES:
+            continue
+
+        data_type, attribute_size, value_property = CUSTOM_ATTRIBUTE_TYPES[attribute.data_type]
+
+        if attribute.domain == 'CORNER':
+            elements = [attribute.data[loop] for face in mesh_data.polygons for loop in face.loop_indices]
+        else:
+            elements = attribute.data
+
+        data = []
+        for element in elements:
+            value = getattr(element, value_property)
+            if attribute_size == 1:
+                data.append(value)
+            else:
+                data.extend(value)
+
+        exported[attribute.name] = {
+            'domain': CUSTOM_ATTRIBUTE_DOMAINS[attribute.domain],
+            'data': {
+                data_type: {'data': data, 'attribute_size': attribute_size}
+            }
+        }
+
+    return exported
+
+# Write the strokes of the active grease pencil object, or the scene's annotations, to stdout as
+# polylines.
+#
+# START_POLYLINES_JSON $BLENDER_FILEPATH $OBJECT_NAME
+# ... polylines json ...
+# END_POLYLINES_JSON $BLENDER_FILEPATH $OBJECT_NAME
+class GreasePencilToJSON(bpy.types.Operator):
+    """Export the strokes of the active grease pencil object as polylines"""
+    bl_idname = 'import_export.gpencil2json'
+    bl_label = 'Export Grease Pencil to JSON'
+    bl_options = {'REGISTER'}
+    bl_category = 'Import-Export'
+
+    # Export the scene's annotations, named 'Annotations', instead of the active object
+    annotations: bpy.props.BoolProperty(name='annotations', default=False)
+
+    def execute(self, context):
+        if self.annotations:
+            name = 'Annotations'
+            gpencil = context.scene.grease_pencil
+            # Annotations are drawn in world space
+            matrix_world = Matrix.Identity(4)
+        else:
+            obj = context.view_layer.objects.active
+            name = obj.name
+            gpencil = obj.data
+            matrix_world = obj.matrix_world
+
+        if gpencil is None:
+            return {'FINISHED'}
+
+        output = "START_POLYLINES_JSON " + bpy.data.filepath + " " + name
+        output += "\n"
+        output += json.dumps(grease_pencil_polylines(gpencil, matrix_world))
+        output += "\n"
+        output += "END_POLYLINES_JSON " + bpy.data.filepath + " " + name
+        print(output)
+
         return {'FINISHED'}
 
+def grease_pencil_polylines(gpencil, matrix_world):
+    """
+    Every stroke on the current frame of each layer, with world space points.
+    """
+    polylines = []
+
+    for layer in gpencil.layers:
+        # Grease pencil was rewritten in Blender 4.3
+        if hasattr(layer, 'current_frame'):
+            frame = layer.current_frame()
+            if frame is None or frame.drawing is None:
+                continue
+
+            for stroke in frame.drawing.strokes:
+                polylines.append({
+                    'layer': layer.name,
+                    'points': [list(matrix_world @ point.position) for point in stroke.points],
+                    'thickness': [point.radius * 2 for point in stroke.points],
+                    'cyclic': stroke.cyclic,
+                })
+        else:
+            frame = layer.active_frame
+            if frame is None:
+                continue
+
+            for stroke in frame.strokes:
+                polylines.append({
+                    'layer': layer.info,
+                    'points': [list(matrix_world @ point.co) for point in stroke.points],
+                    'thickness': [stroke.line_width * point.pressure for point in stroke.points],
+                    'cyclic': stroke.use_cyclic,
+                })
+
+    return polylines
+
 def register():
     bpy.utils.register_class(MeshToJSON)
+    bpy.utils.register_class(GreasePencilToJSON)
+    bpy.utils.register_class(MeshSequenceToJSON)
 
 def unregister():
     bpy.utils.unregister_class(MeshToJSON)
+    bpy.utils.unregister_class(GreasePencilToJSON)
+    bpy.utils.unregister_class(MeshSequenceToJSON)
 
 if __name__ == "__main__":
     register()
@@ -0,0 +1,204 @@
+use crate::vertex_attributes::VertexAttribute;
+use crate::{AttributeDomain, BlenderMesh, CustomAttribute, CustomAttributeData};
+use nalgebra::{Point3, Vector3};
+use std::f32::consts::PI;
+
+/// The name of the custom attribute that [`BlenderMesh.method#bake_vertex_ao`] writes to.
+pub const AMBIENT_OCCLUSION_ATTRIBUTE: &str = "ambient_occlusion";
+
+impl BlenderMesh {
+    /// Bake ambient occlusion into a point custom attribute named
+    /// [`AMBIENT_OCCLUSION_ATTRIBUTE`], with one value per position.
+    ///
+    /// Each vertex casts `samples` rays across the hemisphere around its normal and every ray
+    /// that hits the mesh within `ray_distance` occludes the vertex. A value of `1.0` means that
+    /// nothing occludes the vertex and `0.0` means that every ray was blocked, so the value can be
+    /// multiplied into the vertex's lighting for cheap contact shading.
+    ///
+    /// Rays are spread deterministically, so baking the same mesh twice gives the same result.
+    ///
+    /// Uses the mesh's normals, averaged across the faces that share each position, or the face
+    /// normals if the mesh doesn't have normals.
+    ///
+    /// [`AMBIENT_OCCLUSION_ATTRIBUTE`]: constant.AMBIENT_OCCLUSION_ATTRIBUTE.html
+    pub fn bake_vertex_ao(&mut self, samples: u32, ray_distance: f32) {
+        let positions: Vec<Point3<f32>> = self
+            .multi_indexed_vertex_attributes
+            .positions
+            .attribute
+            .iter_vertices()
+            .map(|position| Point3::new(position[0], position[1], position[2]))
+            .collect();
+        let normals = self.vertex_normals(positions.len());
+
+        // Start rays slightly off of the surface so that they don't hit the faces that they
+        // start on
+        let bounding_box = self.bounding_box();
+        let bias = (bounding_box.max_corner - bounding_box.min_corner).norm() * 1e-4;
+
+        let directions = hemisphere_directions(samples);
+
+        let mut ambient_occlusion = Vec::with_capacity(positions.len());
+        for (position, normal) in positions.iter().zip(normals.iter()) {
+            let normal = match normal.try_normalize(f32::EPSILON) {
+                Some(normal) if samples > 0 => normal,
+                _ => {
+                    ambient_occlusion.push(1.);
+                    continue;
+                }
+            };
+
+            let (tangent, bitangent) = orthonormal_basis(&normal);
+            let origin = position + normal * bias;
+
+            let mut occluded = 0;
+            for direction in directions.iter() {
+                let direction =
+                    tangent * direction.x + bitangent * direction.y + normal * direction.z;
+
+                if let Some(hit) = self.raycast(origin, direction) {
+                    if hit.distance <= ray_distance {
+                        occluded += 1;
+                    }
+                }
+            }
+
+            ambient_occlusion.push(1. - occluded as f32 / samples as f32);
+        }
+
+        self.insert_custom_attribute(
+            AMBIENT_OCCLUSION_ATTRIBUTE.to_string(),
+            CustomAttribute::new(
+                AttributeDomain::Point,
+                CustomAttributeData::Float(VertexAttribute::new(ambient_occlusion, 1).unwrap()),
+            ),
+        );
+    }
+
+    /// The sum of the normals at every corner that uses each position.
+    fn vertex_normals(&self, position_count: usize) -> Vec<Vector3<f32>> {
+        let multi = &self.multi_indexed_vertex_attributes;
+        let mut normals = vec![Vector3::zeros(); position_count];
+
+        match multi.normals.as_ref() {
+            Some(corner_normals) => {
+                for (corner, position) in multi.positions.indices.iter().enumerate() {
+                    let normal = corner_normals.data_at_corner(corner);
+                    normals[*position as usize] += Vector3::new(normal[0], normal[1], normal[2]);
+                }
+            }
+            None => {
+                let positions = &multi.positions.attribute;
+
+                for triangle in self.bvh().triangles() {
+                    let [a, b, c] = triangle.corners(positions);
+                    // Not normalized so that larger faces have more influence
+                    let face_normal = (b - a).cross(&(c - a));
+
+                    for position in triangle.positions().iter() {
+                        normals[*position as usize] += face_normal;
+                    }
+                }
+            }
+        }
+
+        normals
+    }
+}
+
+/// Cosine weighted directions around the +Z axis, so that the fraction of rays that aren't
+/// blocked estimates how much light reaches a point.
+fn hemisphere_directions(samples: u32) -> Vec<Vector3<f32>> {
+    (0..samples)
+        .map(|sample| {
+            // A Hammersley point set spreads the samples evenly without any randomness
+            let u = (sample as f32 + 0.5) / samples as f32;
+            let v = sample.reverse_bits() as f32 / (u32::MAX as f32 + 1.);
+
+            let radius = u.sqrt();
+            let angle = 2. * PI * v;
+
+            Vector3::new(
+                radius * angle.cos(),
+                radius * angle.sin(),
+                (1. - u).max(0.).sqrt(),
+            )
+        })
+        .collect()
+}
+
+/// Two unit vectors that are perpendicular to the normal and to each other.
+fn orthonormal_basis(normal: &Vector3<f32>) -> (Vector3<f32>, Vector3<f32>) {
+    let helper = match normal.x.abs() < 0.9 {
+        true => Vector3::x(),
+        false => Vector3::y(),
+    };
+
+    let tangent = normal.cross(&helper).normalize();
+    let bitangent = normal.cross(&tangent);
+
+    (tangent, bitangent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TerrainConfig;
+
+    fn baked_ao(mesh: &BlenderMesh) -> Vec<f32> {
+        match mesh.custom_attributes()[AMBIENT_OCCLUSION_ATTRIBUTE].data() {
+            CustomAttributeData::Float(ao) => ao.data().clone(),
+            _ => panic!(),
+        }
+    }
+
+    /// Verify that nothing occludes the corners of a convex mesh.
+    #[test]
+    fn convex_mesh_is_unoccluded() {
+        let mut cube = BlenderMesh::cube(1.);
+
+        cube.bake_vertex_ao(32, 10.);
+
+        assert_eq!(baked_ao(&cube), vec![1.; 8]);
+    }
+
+    /// Verify that the bottom of a pit is occluded by its walls.
+    #[test]
+    fn pit_is_occluded() {
+        #[rustfmt::skip]
+        let heights = vec![
+            2., 2., 2.,
+            2., 0., 2.,
+            2., 2., 2.,
+        ];
+        let mut pit =
+            BlenderMesh::terrain_from_heightmap(&heights, 3, &TerrainConfig::default()).unwrap();
+
+        pit.bake_vertex_ao(64, 10.);
+
+        let bottom = baked_ao(&pit)[4];
+        assert!(bottom < 0.5, "{}", bottom);
+    }
+
+    /// Verify that geometry that is further away than the ray distance doesn't occlude.
+    #[test]
+    fn ray_distance_limits_occlusion() {
+        #[rustfmt::skip]
+        let heights = vec![
+            0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0.,
+            0., 0., 4., 0., 0.,
+            0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let mut pillar =
+            BlenderMesh::terrain_from_heightmap(&heights, 5, &TerrainConfig::default()).unwrap();
+
+        pillar.bake_vertex_ao(64, 10.);
+        let corner = baked_ao(&pillar)[0];
+        assert!(corner < 1., "{}", corner);
+
+        pillar.bake_vertex_ao(64, 0.5);
+        assert_eq!(baked_ao(&pillar)[0], 1.);
+    }
+}
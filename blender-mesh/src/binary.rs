@@ -0,0 +1,867 @@
+//! A versioned binary format for single indexed meshes.
+
+use crate::vertex_attributes::{ComponentType, LayoutAttribute, VertexAttributeLayout};
+use crate::{MeshKind, SingleIndexedVertexAttributes, VertexLayout};
+use std::convert::TryInto;
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "integrity")]
+pub use self::integrity::{sign_binary, verify_integrity, IntegrityError};
+
+#[cfg(feature = "integrity")]
+mod integrity;
+
+/// The bytes at the start of every binary mesh.
+pub const BINARY_MAGIC: [u8; 4] = *b"LNDN";
+
+/// The version of the binary layout that [`SingleIndexedVertexAttributes.method#to_binary`]
+/// writes.
+///
+/// Incremented whenever a change would stop older readers from reading newer files.
+pub const BINARY_VERSION: u16 = 1;
+
+const HEADER_SIZE: usize = 12;
+const SECTION_ENTRY_SIZE: usize = 12;
+
+/// The order of the bytes within each number.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Endianness {
+    /// Least significant byte first, such as on x86 and most ARM targets.
+    Little,
+    /// Most significant byte first, such as on some consoles.
+    Big,
+}
+
+impl Endianness {
+    /// The byte order of the platform that this is running on.
+    pub fn native() -> Endianness {
+        match cfg!(target_endian = "big") {
+            true => Endianness::Big,
+            false => Endianness::Little,
+        }
+    }
+
+    pub(crate) fn u16_bytes(&self, value: u16) -> [u8; 2] {
+        match self {
+            Endianness::Little => value.to_le_bytes(),
+            Endianness::Big => value.to_be_bytes(),
+        }
+    }
+
+    pub(crate) fn u32_bytes(&self, value: u32) -> [u8; 4] {
+        match self {
+            Endianness::Little => value.to_le_bytes(),
+            Endianness::Big => value.to_be_bytes(),
+        }
+    }
+
+    pub(crate) fn f32_bytes(&self, value: f32) -> [u8; 4] {
+        self.u32_bytes(value.to_bits())
+    }
+
+    fn read_u16(&self, bytes: [u8; 2]) -> u16 {
+        match self {
+            Endianness::Little => u16::from_le_bytes(bytes),
+            Endianness::Big => u16::from_be_bytes(bytes),
+        }
+    }
+
+    fn read_u32(&self, bytes: [u8; 4]) -> u32 {
+        match self {
+            Endianness::Little => u32::from_le_bytes(bytes),
+            Endianness::Big => u32::from_be_bytes(bytes),
+        }
+    }
+}
+
+/// An error while reading a binary mesh.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum BinaryError {
+    /// The file ends before the header or section table does.
+    #[error("The file is {len} bytes but its header and section table need {needed} bytes")]
+    TooShort {
+        /// The number of bytes needed
+        needed: usize,
+        /// The number of bytes in the file
+        len: usize,
+    },
+    /// The file doesn't start with [`BINARY_MAGIC`].
+    #[error("The file starts with {0:?} instead of LNDN, so it isn't a binary mesh")]
+    BadMagic([u8; 4]),
+    /// The byte order in the header is neither little nor big.
+    #[error("Unknown byte order {0}")]
+    UnknownEndianness(u8),
+    /// The file was written by a newer version of the binary layout.
+    #[error(
+        "Version {0} is not supported, the newest supported version is {}",
+        BINARY_VERSION
+    )]
+    UnsupportedVersion(u16),
+    /// A section extends past the end of the file.
+    #[error(
+        "The {tag} section at offset {offset} with length {length} ends past the end of the file"
+    )]
+    SectionOutOfBounds {
+        /// The section's tag
+        tag: String,
+        /// The section's offset
+        offset: u32,
+        /// The section's length
+        length: u32,
+    },
+    /// A section's contents don't match its format.
+    #[error("The {tag} section is malformed: {reason}")]
+    MalformedSection {
+        /// The section's tag
+        tag: String,
+        /// What is wrong with the section
+        reason: String,
+    },
+}
+
+/// The groups of attributes that [`SingleIndexedVertexAttributes.method#to_chunked_binary`]
+/// writes to their own sections, in the order that engines usually stream them in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum VertexChunk {
+    /// Positions, which are all that physics and shadow casting need.
+    Positions,
+    /// Normals, uvs and face tangents for shading.
+    Surface,
+    /// Bone indices and weights for skinning.
+    Skinning,
+}
+
+impl VertexChunk {
+    /// Every chunk in the order that they are written.
+    pub const ALL: [VertexChunk; 3] = [
+        VertexChunk::Positions,
+        VertexChunk::Surface,
+        VertexChunk::Skinning,
+    ];
+
+    /// Whether the attribute belongs in this chunk.
+    pub fn contains(&self, kind: LayoutAttribute) -> bool {
+        let chunk = match kind {
+            LayoutAttribute::Position => VertexChunk::Positions,
+            LayoutAttribute::Normal | LayoutAttribute::Uv | LayoutAttribute::FaceTangent => {
+                VertexChunk::Surface
+            }
+            LayoutAttribute::BoneIndices | LayoutAttribute::BoneWeights => VertexChunk::Skinning,
+        };
+
+        chunk == *self
+    }
+
+    /// The tag of the chunk's layout section, such as `LAY0`.
+    fn layout_tag(&self) -> [u8; 4] {
+        [b'L', b'A', b'Y', self.digit()]
+    }
+
+    /// The tag of the chunk's vertex section, such as `VTX0`.
+    fn vertex_tag(&self) -> [u8; 4] {
+        [b'V', b'T', b'X', self.digit()]
+    }
+
+    fn digit(&self) -> u8 {
+        match self {
+            VertexChunk::Positions => b'0',
+            VertexChunk::Surface => b'1',
+            VertexChunk::Skinning => b'2',
+        }
+    }
+
+    fn from_digit(digit: u8) -> Option<VertexChunk> {
+        VertexChunk::ALL
+            .iter()
+            .find(|chunk| chunk.digit() == digit)
+            .cloned()
+    }
+}
+
+/// An entry in a binary mesh's section table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinarySection {
+    pub(crate) tag: String,
+    pub(crate) offset: u32,
+    pub(crate) length: u32,
+}
+
+impl BinarySection {
+    /// The section's four character tag, such as `VERT`.
+    pub fn tag(&self) -> &String {
+        &self.tag
+    }
+
+    /// The number of bytes from the start of the file to the start of the section.
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// The number of bytes in the section.
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+}
+
+/// A summary of a binary mesh, for debugging assets on the machine that they were built on
+/// without a target to load them on.
+///
+/// Displays as a human readable summary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryInspection {
+    pub(crate) byte_len: usize,
+    pub(crate) version: u16,
+    pub(crate) endianness: Endianness,
+    pub(crate) sections: Vec<BinarySection>,
+    pub(crate) name: Option<String>,
+    pub(crate) kind: Option<MeshKind>,
+    pub(crate) vertex_count: Option<u32>,
+    pub(crate) index_count: Option<u32>,
+    pub(crate) layout: Option<VertexLayout>,
+    pub(crate) chunk_layouts: Vec<(VertexChunk, VertexLayout)>,
+}
+
+impl BinaryInspection {
+    /// The version of the binary layout that the file was written with.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// The byte order of the numbers in the file.
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Every section in the order of the section table, including sections that aren't
+    /// recognized.
+    pub fn sections(&self) -> &Vec<BinarySection> {
+        &self.sections
+    }
+
+    /// The mesh's name, or `None` if the file doesn't have a `NAME` section.
+    pub fn name(&self) -> Option<&String> {
+        self.name.as_ref()
+    }
+
+    /// What the indices form, or `None` if the file doesn't have a `MESH` section.
+    pub fn kind(&self) -> Option<MeshKind> {
+        self.kind
+    }
+
+    /// The number of vertices, or `None` if the file doesn't have a `MESH` section.
+    pub fn vertex_count(&self) -> Option<u32> {
+        self.vertex_count
+    }
+
+    /// The number of indices, or `None` if the file doesn't have a `MESH` section.
+    pub fn index_count(&self) -> Option<u32> {
+        self.index_count
+    }
+
+    /// The layout of the `VERT` section, or `None` if the file doesn't have a `LAYT` section.
+    pub fn layout(&self) -> Option<&VertexLayout> {
+        self.layout.as_ref()
+    }
+
+    /// The layout of each chunk's vertex section, in the order of the section table. Empty
+    /// unless the file was written by [`SingleIndexedVertexAttributes.method#to_chunked_binary`].
+    pub fn chunk_layouts(&self) -> &Vec<(VertexChunk, VertexLayout)> {
+        &self.chunk_layouts
+    }
+}
+
+impl Display for BinaryInspection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let endianness = match self.endianness {
+            Endianness::Little => "little",
+            Endianness::Big => "big",
+        };
+        writeln!(
+            f,
+            "Landon binary mesh version {}, {} endian, {} bytes",
+            self.version, endianness, self.byte_len
+        )?;
+
+        if let Some(name) = self.name.as_ref() {
+            writeln!(f, "Name: {}", name)?;
+        }
+        if let (Some(kind), Some(vertex_count), Some(index_count)) =
+            (self.kind, self.vertex_count, self.index_count)
+        {
+            writeln!(
+                f,
+                "Mesh: {:?}, {} vertices, {} indices",
+                kind, vertex_count, index_count
+            )?;
+        }
+
+        if let Some(layout) = self.layout.as_ref() {
+            writeln!(f, "Layout: {} bytes per vertex", layout.stride())?;
+            fmt_layout_attributes(f, layout)?;
+        }
+        for (chunk, layout) in self.chunk_layouts.iter() {
+            writeln!(
+                f,
+                "{:?} chunk layout: {} bytes per vertex",
+                chunk,
+                layout.stride()
+            )?;
+            fmt_layout_attributes(f, layout)?;
+        }
+
+        writeln!(f, "Sections:")?;
+        for section in self.sections.iter() {
+            writeln!(
+                f,
+                "  {:<4} at byte {:<8} {} bytes",
+                section.tag, section.offset, section.length
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn fmt_layout_attributes(f: &mut Formatter<'_>, layout: &VertexLayout) -> std::fmt::Result {
+    for attribute in layout.attributes() {
+        writeln!(
+            f,
+            "  {:<13} {:?} x{} at byte {}{}",
+            attribute.name(),
+            attribute.component_type(),
+            attribute.component_count(),
+            attribute.byte_offset(),
+            if attribute.normalized() {
+                ", normalized"
+            } else {
+                ""
+            }
+        )?;
+    }
+
+    Ok(())
+}
+
+impl SingleIndexedVertexAttributes {
+    /// Write the vertex data in a versioned binary layout, so that engines can copy it straight
+    /// into GPU buffers without parsing JSON. Use [`inspect_binary`] to summarize a file.
+    ///
+    /// Every number is written in the given byte order, which is stored in the header, so assets
+    /// can be built for a big endian target and still be inspected anywhere. Meshlets and custom
+    /// attributes aren't included.
+    ///
+    /// # Layout
+    ///
+    /// | Offset | Size               | Field                                      |
+    /// | ---    | ---                | ---                                        |
+    /// | 0      | 4                  | [`BINARY_MAGIC`]                           |
+    /// | 4      | 1                  | Byte order, `0` for little and `1` for big |
+    /// | 5      | 1                  | Reserved, always `0`                       |
+    /// | 6      | 2                  | [`BINARY_VERSION`] as a u16                |
+    /// | 8      | 4                  | Section count as a u32                     |
+    /// | 12     | 12 * section count | Section table                              |
+    ///
+    /// Every entry in the section table is a four byte tag followed by the section's offset from
+    /// the start of the file and its length in bytes, both u32s. Sections start on four byte
+    /// boundaries. Readers should skip sections that they don't recognize, so that sections can
+    /// be added without changing the version.
+    ///
+    /// | Tag    | Contents |
+    /// | ---    | ---      |
+    /// | `NAME` | The mesh's name as UTF-8 |
+    /// | `MESH` | The [`MeshKind`] as a u8 (`0` for points, `1` for edges and `2` for triangles), 3 reserved bytes, then the vertex count and index count as u32s |
+    /// | `LAYT` | The stride and attribute count as u32s, then 8 bytes per attribute: its [`LayoutAttribute.method#shader_location`], its component type (`0` for f32 and `1` for u8), its component count, whether it is normalized and its byte offset as a u32 |
+    /// | `VERT` | The vertices, interleaved as described by the `LAYT` section |
+    /// | `INDX` | The indices as u16s |
+    /// | `HASH` or `HMAC` | Only written by [`sign_binary`], always last. A digest of the rest of the file |
+    ///
+    /// [`inspect_binary`]: fn.inspect_binary.html
+    /// [`sign_binary`]: fn.sign_binary.html
+    /// [`BINARY_MAGIC`]: constant.BINARY_MAGIC.html
+    /// [`BINARY_VERSION`]: constant.BINARY_VERSION.html
+    /// [`MeshKind`]: enum.MeshKind.html
+    pub fn to_binary(&self, name: &str, endianness: Endianness) -> Vec<u8> {
+        let sections: Vec<([u8; 4], Vec<u8>)> = vec![
+            (*b"NAME", name.as_bytes().to_vec()),
+            (*b"MESH", self.mesh_section(endianness)),
+            (*b"LAYT", layout_section(&self.vertex_layout(), endianness)),
+            (*b"VERT", self.interleaved_bytes_with_endianness(endianness)),
+            (*b"INDX", self.index_section(endianness)),
+        ];
+
+        write_sections(sections, endianness)
+    }
+
+    /// Write the vertex data in the same binary layout as
+    /// [`SingleIndexedVertexAttributes.method#to_binary`], but with each [`VertexChunk`] of
+    /// attributes in its own section so that engines can stream in very large meshes a piece at
+    /// a time.
+    ///
+    /// The header, section table and the small sections come first, followed by the indices and
+    /// then each chunk's vertices. An engine can read everything up to the end of the section
+    /// table, load the indices and positions for physics, and then load the surface and skinning
+    /// chunks as it needs them by reading the byte ranges in the section table.
+    ///
+    /// Instead of the `LAYT` and `VERT` sections each chunk that the vertices have attributes for
+    /// gets a layout section and a vertex section, laid out the same way as `LAYT` and `VERT`
+    /// with attribute offsets relative to the start of the chunk's vertex.
+    ///
+    /// | Chunk                     | Layout tag | Vertex tag |
+    /// | ---                       | ---        | ---        |
+    /// | [`VertexChunk::Positions`] | `LAY0`     | `VTX0`     |
+    /// | [`VertexChunk::Surface`]   | `LAY1`     | `VTX1`     |
+    /// | [`VertexChunk::Skinning`]  | `LAY2`     | `VTX2`     |
+    ///
+    /// [`VertexChunk`]: enum.VertexChunk.html
+    /// [`VertexChunk::Positions`]: enum.VertexChunk.html#variant.Positions
+    /// [`VertexChunk::Surface`]: enum.VertexChunk.html#variant.Surface
+    /// [`VertexChunk::Skinning`]: enum.VertexChunk.html#variant.Skinning
+    pub fn to_chunked_binary(&self, name: &str, endianness: Endianness) -> Vec<u8> {
+        let layout = self.vertex_layout();
+        let chunks: Vec<(VertexChunk, VertexLayout)> = VertexChunk::ALL
+            .iter()
+            .map(|chunk| (*chunk, layout.subset(|kind| chunk.contains(kind))))
+            .filter(|(_, layout)| !layout.attributes().is_empty())
+            .collect();
+
+        let mut sections: Vec<([u8; 4], Vec<u8>)> = vec![
+            (*b"NAME", name.as_bytes().to_vec()),
+            (*b"MESH", self.mesh_section(endianness)),
+        ];
+        for (chunk, layout) in chunks.iter() {
+            sections.push((chunk.layout_tag(), layout_section(layout, endianness)));
+        }
+        sections.push((*b"INDX", self.index_section(endianness)));
+        for (chunk, layout) in chunks.iter() {
+            sections.push((
+                chunk.vertex_tag(),
+                self.interleaved_bytes_with_layout(layout, endianness),
+            ));
+        }
+
+        write_sections(sections, endianness)
+    }
+
+    fn mesh_section(&self, endianness: Endianness) -> Vec<u8> {
+        let mut mesh = vec![mesh_kind_to_byte(self.kind), 0, 0, 0];
+        mesh.extend_from_slice(&endianness.u32_bytes(self.vertices.len() as u32));
+        mesh.extend_from_slice(&endianness.u32_bytes(self.indices.len() as u32));
+        mesh
+    }
+
+    fn index_section(&self, endianness: Endianness) -> Vec<u8> {
+        self.indices
+            .iter()
+            .flat_map(|index| endianness.u16_bytes(*index).to_vec())
+            .collect()
+    }
+}
+
+fn layout_section(layout: &VertexLayout, endianness: Endianness) -> Vec<u8> {
+    let mut bytes = vec![];
+    bytes.extend_from_slice(&endianness.u32_bytes(layout.stride() as u32));
+    bytes.extend_from_slice(&endianness.u32_bytes(layout.attributes().len() as u32));
+    for attribute in layout.attributes() {
+        bytes.push(attribute.kind().shader_location() as u8);
+        bytes.push(match attribute.component_type() {
+            ComponentType::F32 => 0,
+            ComponentType::U8 => 1,
+        });
+        bytes.push(attribute.component_count());
+        bytes.push(attribute.normalized() as u8);
+        bytes.extend_from_slice(&endianness.u32_bytes(attribute.byte_offset() as u32));
+    }
+    bytes
+}
+
+/// The header, the section table and then the sections, each padded to four bytes.
+fn write_sections(sections: Vec<([u8; 4], Vec<u8>)>, endianness: Endianness) -> Vec<u8> {
+    let mut bytes = BINARY_MAGIC.to_vec();
+    bytes.push(match endianness {
+        Endianness::Little => 0,
+        Endianness::Big => 1,
+    });
+    bytes.push(0);
+    bytes.extend_from_slice(&endianness.u16_bytes(BINARY_VERSION));
+    bytes.extend_from_slice(&endianness.u32_bytes(sections.len() as u32));
+
+    let mut offset = HEADER_SIZE + SECTION_ENTRY_SIZE * sections.len();
+    for (tag, contents) in sections.iter() {
+        bytes.extend_from_slice(tag);
+        bytes.extend_from_slice(&endianness.u32_bytes(offset as u32));
+        bytes.extend_from_slice(&endianness.u32_bytes(contents.len() as u32));
+
+        offset += align_to_four(contents.len());
+    }
+
+    for (_, contents) in sections {
+        let padding = align_to_four(contents.len()) - contents.len();
+        bytes.extend(contents);
+        bytes.resize(bytes.len() + padding, 0);
+    }
+
+    bytes
+}
+
+/// Read the header and section table of a binary mesh along with a summary of the sections that
+/// describe the mesh, such as its name and vertex layout.
+///
+/// The vertices and indices aren't read, but their sections are checked to be the size that the
+/// mesh and layout sections say they should be.
+pub fn inspect_binary(bytes: &[u8]) -> Result<BinaryInspection, BinaryError> {
+    if bytes.len() < HEADER_SIZE {
+        return Err(BinaryError::TooShort {
+            needed: HEADER_SIZE,
+            len: bytes.len(),
+        });
+    }
+
+    let magic: [u8; 4] = bytes[0..4].try_into().unwrap();
+    if magic != BINARY_MAGIC {
+        return Err(BinaryError::BadMagic(magic));
+    }
+
+    let endianness = match bytes[4] {
+        0 => Endianness::Little,
+        1 => Endianness::Big,
+        other => return Err(BinaryError::UnknownEndianness(other)),
+    };
+    let u32_at = |offset: usize| endianness.read_u32(bytes[offset..offset + 4].try_into().unwrap());
+
+    let version = endianness.read_u16(bytes[6..8].try_into().unwrap());
+    if version > BINARY_VERSION {
+        return Err(BinaryError::UnsupportedVersion(version));
+    }
+
+    let section_count = u32_at(8) as usize;
+    let needed = HEADER_SIZE + SECTION_ENTRY_SIZE * section_count;
+    if bytes.len() < needed {
+        return Err(BinaryError::TooShort {
+            needed,
+            len: bytes.len(),
+        });
+    }
+
+    let mut inspection = BinaryInspection {
+        byte_len: bytes.len(),
+        version,
+        endianness,
+        sections: vec![],
+        name: None,
+        kind: None,
+        vertex_count: None,
+        index_count: None,
+        layout: None,
+        chunk_layouts: vec![],
+    };
+
+    for entry in 0..section_count {
+        let entry = HEADER_SIZE + SECTION_ENTRY_SIZE * entry;
+        let section = BinarySection {
+            tag: String::from_utf8_lossy(&bytes[entry..entry + 4]).to_string(),
+            offset: u32_at(entry + 4),
+            length: u32_at(entry + 8),
+        };
+
+        let start = section.offset as usize;
+        let end = start + section.length as usize;
+        if end > bytes.len() {
+            return Err(BinaryError::SectionOutOfBounds {
+                tag: section.tag,
+                offset: section.offset,
+                length: section.length,
+            });
+        }
+
+        inspection.inspect_section(&section.tag, &bytes[start..end])?;
+        inspection.sections.push(section);
+    }
+
+    inspection.check_section_lengths()?;
+
+    Ok(inspection)
+}
+
+impl BinaryInspection {
+    fn inspect_section(&mut self, tag: &str, contents: &[u8]) -> Result<(), BinaryError> {
+        let malformed = |reason: &str| BinaryError::MalformedSection {
+            tag: tag.to_string(),
+            reason: reason.to_string(),
+        };
+        let endianness = self.endianness;
+        let u32_at =
+            |offset: usize| endianness.read_u32(contents[offset..offset + 4].try_into().unwrap());
+
+        match tag {
+            "NAME" => {
+                let name = std::str::from_utf8(contents).map_err(|_| malformed("Not UTF-8"))?;
+                self.name = Some(name.to_string());
+            }
+            "MESH" => {
+                if contents.len() != 12 {
+                    return Err(malformed("Expected 12 bytes"));
+                }
+
+                self.kind = Some(match contents[0] {
+                    0 => MeshKind::Points,
+                    1 => MeshKind::Edges,
+                    2 => MeshKind::Triangles,
+                    _ => return Err(malformed("Unknown mesh kind")),
+                });
+                self.vertex_count = Some(u32_at(4));
+                self.index_count = Some(u32_at(8));
+            }
+            "LAYT" => self.layout = Some(read_layout(contents, endianness, malformed)?),
+            _ if tag.starts_with("LAY") => {
+                if let Some(chunk) = VertexChunk::from_digit(tag.as_bytes()[3]) {
+                    let layout = read_layout(contents, endianness, malformed)?;
+                    self.chunk_layouts.push((chunk, layout));
+                }
+            }
+            _ => {}
+        };
+
+        Ok(())
+    }
+
+    fn check_section_lengths(&self) -> Result<(), BinaryError> {
+        let section_length = |tag: &str| {
+            self.sections
+                .iter()
+                .find(|section| section.tag == tag)
+                .map(|section| section.length as usize)
+        };
+
+        if let (Some(vertex_count), Some(layout), Some(length)) = (
+            self.vertex_count,
+            self.layout.as_ref(),
+            section_length("VERT"),
+        ) {
+            if length != vertex_count as usize * layout.stride() {
+                return Err(BinaryError::MalformedSection {
+                    tag: "VERT".to_string(),
+                    reason: format!(
+                        "Expected {} vertices of {} bytes but found {} bytes",
+                        vertex_count,
+                        layout.stride(),
+                        length
+                    ),
+                });
+            }
+        }
+
+        if let Some(vertex_count) = self.vertex_count {
+            for (chunk, layout) in self.chunk_layouts.iter() {
+                let tag = String::from_utf8(chunk.vertex_tag().to_vec()).unwrap();
+                let length = section_length(&tag).unwrap_or(0);
+
+                if length != vertex_count as usize * layout.stride() {
+                    return Err(BinaryError::MalformedSection {
+                        reason: format!(
+                            "Expected {} vertices of {} bytes but found {} bytes",
+                            vertex_count,
+                            layout.stride(),
+                            length
+                        ),
+                        tag,
+                    });
+                }
+            }
+        }
+
+        if let (Some(index_count), Some(length)) = (self.index_count, section_length("INDX")) {
+            if length != index_count as usize * 2 {
+                return Err(BinaryError::MalformedSection {
+                    tag: "INDX".to_string(),
+                    reason: format!(
+                        "Expected {} u16 indices but found {} bytes",
+                        index_count, length
+                    ),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Read a `LAYT` section or a chunk's layout section.
+fn read_layout(
+    contents: &[u8],
+    endianness: Endianness,
+    malformed: impl Fn(&str) -> BinaryError,
+) -> Result<VertexLayout, BinaryError> {
+    let u32_at =
+        |offset: usize| endianness.read_u32(contents[offset..offset + 4].try_into().unwrap());
+
+    if contents.len() < 8 {
+        return Err(malformed("Expected at least 8 bytes"));
+    }
+
+    let stride = u32_at(0) as usize;
+    let attribute_count = u32_at(4) as usize;
+    if contents.len() != 8 + 8 * attribute_count {
+        return Err(malformed("Expected 8 bytes per attribute"));
+    }
+
+    let mut attributes = vec![];
+    for attribute in contents[8..].chunks(8) {
+        let kind =
+            layout_attribute(attribute[0] as u32).ok_or_else(|| malformed("Unknown attribute"))?;
+        let component_type = match attribute[1] {
+            0 => ComponentType::F32,
+            1 => ComponentType::U8,
+            _ => return Err(malformed("Unknown component type")),
+        };
+
+        attributes.push(VertexAttributeLayout {
+            kind,
+            component_type,
+            component_count: attribute[2],
+            byte_offset: endianness.read_u32(attribute[4..8].try_into().unwrap()) as usize,
+            stride,
+            normalized: attribute[3] != 0,
+        });
+    }
+
+    Ok(VertexLayout { stride, attributes })
+}
+
+fn mesh_kind_to_byte(kind: MeshKind) -> u8 {
+    match kind {
+        MeshKind::Points => 0,
+        MeshKind::Edges => 1,
+        MeshKind::Triangles => 2,
+    }
+}
+
+fn layout_attribute(shader_location: u32) -> Option<LayoutAttribute> {
+    [
+        LayoutAttribute::Position,
+        LayoutAttribute::Normal,
+        LayoutAttribute::Uv,
+        LayoutAttribute::FaceTangent,
+        LayoutAttribute::BoneIndices,
+        LayoutAttribute::BoneWeights,
+    ]
+    .iter()
+    .find(|attribute| attribute.shader_location() == shader_location)
+    .cloned()
+}
+
+fn align_to_four(len: usize) -> usize {
+    match len % 4 {
+        0 => len,
+        remainder => len + 4 - remainder,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlenderMesh, CreateSingleIndexConfig};
+
+    /// Verify that the header, section table and sections are written in the requested byte
+    /// order and can be inspected.
+    #[test]
+    fn write_and_inspect_big_endian() {
+        let cube =
+            BlenderMesh::cube(1.).combine_vertex_indices(&CreateSingleIndexConfig::default());
+
+        let bytes = cube.to_binary("Cube", Endianness::Big);
+        assert_eq!(&bytes[0..8], &[b'L', b'N', b'D', b'N', 1, 0, 0, 1]);
+
+        let inspection = inspect_binary(&bytes).unwrap();
+
+        assert_eq!(inspection.endianness(), Endianness::Big);
+        assert_eq!(inspection.name(), Some(&"Cube".to_string()));
+        assert_eq!(inspection.kind(), Some(MeshKind::Triangles));
+        assert_eq!(
+            inspection.vertex_count(),
+            Some(cube.vertices().len() as u32)
+        );
+        assert_eq!(inspection.index_count(), Some(cube.indices().len() as u32));
+        assert_eq!(inspection.layout(), Some(&cube.vertex_layout()));
+
+        let tags: Vec<&str> = inspection
+            .sections()
+            .iter()
+            .map(|section| section.tag().as_str())
+            .collect();
+        assert_eq!(tags, vec!["NAME", "MESH", "LAYT", "VERT", "INDX"]);
+        assert!(inspection
+            .sections()
+            .iter()
+            .all(|section| section.offset() % 4 == 0));
+
+        let vert = &inspection.sections()[3];
+        let first_x = &bytes[vert.offset() as usize..vert.offset() as usize + 4];
+        assert_eq!(first_x, &cube.vertices()[0].position()[0].to_be_bytes());
+
+        let summary = inspection.to_string();
+        assert!(summary.contains("big endian"), "{}", summary);
+        assert!(summary.contains("Name: Cube"), "{}", summary);
+    }
+
+    /// Verify that each chunk gets its own layout and vertex sections, with the positions before
+    /// the rest of the vertex data.
+    #[test]
+    fn write_and_inspect_chunks() {
+        let mut cube = BlenderMesh::cube(1.);
+        cube.multi_indexed_vertex_attributes.uvs = None;
+        let cube = cube.combine_vertex_indices(&CreateSingleIndexConfig {
+            bone_influences_per_vertex: None,
+            calculate_face_tangents: false,
+        });
+
+        let bytes = cube.to_chunked_binary("Cube", Endianness::Little);
+        let inspection = inspect_binary(&bytes).unwrap();
+
+        let tags: Vec<&str> = inspection
+            .sections()
+            .iter()
+            .map(|section| section.tag().as_str())
+            .collect();
+        assert_eq!(
+            tags,
+            vec!["NAME", "MESH", "LAY0", "LAY1", "INDX", "VTX0", "VTX1"]
+        );
+        assert_eq!(inspection.layout(), None);
+
+        let (chunk, surface) = &inspection.chunk_layouts()[1];
+        assert_eq!(*chunk, VertexChunk::Surface);
+        assert_eq!(surface.stride(), 12);
+        assert_eq!(surface.attributes()[0].kind(), LayoutAttribute::Normal);
+        assert_eq!(surface.attributes()[0].byte_offset(), 0);
+
+        let positions = &inspection.sections()[5];
+        assert_eq!(positions.length() as usize, cube.vertices().len() * 12);
+        let second_x = &bytes[positions.offset() as usize + 12..positions.offset() as usize + 16];
+        assert_eq!(second_x, &cube.vertices()[1].position()[0].to_le_bytes());
+    }
+
+    /// Verify that files that aren't binary meshes or that were cut short are rejected.
+    #[test]
+    fn inspect_errors() {
+        assert_eq!(
+            inspect_binary(b"{\"meshes\": {}}"),
+            Err(BinaryError::BadMagic(*b"{\"me"))
+        );
+
+        let cube =
+            BlenderMesh::cube(1.).combine_vertex_indices(&CreateSingleIndexConfig::default());
+        let bytes = cube.to_binary("Cube", Endianness::Little);
+
+        assert!(matches!(
+            inspect_binary(&bytes[..bytes.len() - 4]),
+            Err(BinaryError::SectionOutOfBounds { tag, .. }) if tag == "INDX"
+        ));
+
+        let mut newer = bytes;
+        newer[6..8].copy_from_slice(&(BINARY_VERSION + 1).to_le_bytes());
+        assert_eq!(
+            inspect_binary(&newer),
+            Err(BinaryError::UnsupportedVersion(BINARY_VERSION + 1))
+        );
+    }
+}
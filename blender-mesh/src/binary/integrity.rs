@@ -0,0 +1,187 @@
+use super::{inspect_binary, write_sections, BinaryError, HEADER_SIZE, SECTION_ENTRY_SIZE};
+use hmac::{Hmac, Mac, NewMac};
+use sha2::{Digest, Sha256};
+use std::convert::TryInto;
+
+/// The number of bytes in a `HASH` or `HMAC` section.
+const DIGEST_SIZE: usize = 32;
+
+/// An error while verifying the integrity of a binary mesh.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum IntegrityError {
+    /// The file isn't a valid binary mesh, such as when it was cut short.
+    #[error(transparent)]
+    Binary(#[from] BinaryError),
+    /// The file doesn't have a `HASH` or `HMAC` section, so it was written without
+    /// [`sign_binary`].
+    ///
+    /// [`sign_binary`]: fn.sign_binary.html
+    #[error("The file doesn't have a HASH or HMAC section")]
+    Missing,
+    /// The `HASH` or `HMAC` section isn't 32 bytes at the end of the file.
+    #[error("The {0} section must be the last 32 bytes of the file")]
+    Misplaced(String),
+    /// The file was signed with a key but no key was given to verify it with.
+    #[error("The file was signed with a key but no key was given to verify it")]
+    KeyRequired,
+    /// A key was given but the file only has a `HASH` section, which anyone can write.
+    #[error("Expected the file to be signed with a key but it only has a HASH section")]
+    NotSigned,
+    /// The file's contents don't match its `HASH` or `HMAC` section, or the key is wrong.
+    #[error("The {0} section doesn't match the file, so it is corrupted or was tampered with")]
+    Mismatch(String),
+}
+
+/// Add a `HASH` section to the end of a binary mesh, or an `HMAC` section when given a key, so
+/// that games can check that the file wasn't corrupted or tampered with before loading it with
+/// [`verify_integrity`].
+///
+/// A `HASH` section is the SHA-256 of every byte of the file before the section, and an `HMAC`
+/// section is the HMAC-SHA256 of the same bytes using the key. Any `HASH` or `HMAC` section that
+/// the file already has is replaced.
+///
+/// Only binary meshes can be signed. Meshes exported as JSON have nowhere to store a digest, so
+/// games that need to trust them should check them some other way, such as against the hashes
+/// in an export manifest.
+///
+/// ```
+/// # use blender_mesh::{sign_binary, verify_integrity, BlenderMesh, CreateSingleIndexConfig, Endianness, IntegrityError};
+/// let cube = BlenderMesh::cube(1.).combine_vertex_indices(&CreateSingleIndexConfig::default());
+/// let bytes = cube.to_binary("Cube", Endianness::Little);
+///
+/// let signed = sign_binary(&bytes, Some(b"project key")).unwrap();
+/// assert_eq!(verify_integrity(&signed, Some(b"project key")), Ok(()));
+/// assert_eq!(verify_integrity(&signed, None), Err(IntegrityError::KeyRequired));
+/// ```
+///
+/// [`verify_integrity`]: fn.verify_integrity.html
+pub fn sign_binary(bytes: &[u8], key: Option<&[u8]>) -> Result<Vec<u8>, BinaryError> {
+    let inspection = inspect_binary(bytes)?;
+
+    let mut sections: Vec<([u8; 4], Vec<u8>)> = vec![];
+    for (idx, section) in inspection.sections.iter().enumerate() {
+        let entry = HEADER_SIZE + SECTION_ENTRY_SIZE * idx;
+        let tag: [u8; 4] = bytes[entry..entry + 4].try_into().unwrap();
+        if is_integrity_tag(&tag) {
+            continue;
+        }
+
+        let start = section.offset as usize;
+        sections.push((tag, bytes[start..start + section.length as usize].to_vec()));
+    }
+
+    // Written as zeros so that the section table is final before the file is hashed
+    let tag = match key {
+        Some(_) => *b"HMAC",
+        None => *b"HASH",
+    };
+    sections.push((tag, vec![0; DIGEST_SIZE]));
+
+    let mut signed = write_sections(sections, inspection.endianness);
+    let start = signed.len() - DIGEST_SIZE;
+    let digest = match key {
+        Some(key) => {
+            let mut mac = hmac_sha256(key);
+            mac.update(&signed[..start]);
+            mac.finalize().into_bytes()
+        }
+        None => Sha256::digest(&signed[..start]),
+    };
+    signed[start..].copy_from_slice(&digest);
+
+    Ok(signed)
+}
+
+/// Check a binary mesh against the `HASH` or `HMAC` section written by [`sign_binary`], such as
+/// when a game loads its assets at startup, so that corrupted or tampered files are rejected
+/// instead of being rendered as garbage.
+///
+/// Pass the key that the file was signed with to require an `HMAC` section. Without a key only
+/// files with a `HASH` section can be verified.
+///
+/// [`sign_binary`]: fn.sign_binary.html
+pub fn verify_integrity(bytes: &[u8], key: Option<&[u8]>) -> Result<(), IntegrityError> {
+    let inspection = inspect_binary(bytes)?;
+
+    let section = inspection
+        .sections
+        .iter()
+        .find(|section| is_integrity_tag(section.tag.as_bytes()))
+        .ok_or(IntegrityError::Missing)?;
+
+    let start = section.offset as usize;
+    if section.length as usize != DIGEST_SIZE || start + DIGEST_SIZE != bytes.len() {
+        return Err(IntegrityError::Misplaced(section.tag.clone()));
+    }
+    let (contents, expected) = bytes.split_at(start);
+
+    let matches = match (section.tag.as_str(), key) {
+        ("HMAC", Some(key)) => {
+            let mut mac = hmac_sha256(key);
+            mac.update(contents);
+            mac.verify(expected).is_ok()
+        }
+        ("HMAC", None) => return Err(IntegrityError::KeyRequired),
+        (_, Some(_)) => return Err(IntegrityError::NotSigned),
+        (_, None) => Sha256::digest(contents)[..] == *expected,
+    };
+
+    match matches {
+        true => Ok(()),
+        false => Err(IntegrityError::Mismatch(section.tag.clone())),
+    }
+}
+
+fn is_integrity_tag(tag: &[u8]) -> bool {
+    tag == b"HASH" || tag == b"HMAC"
+}
+
+fn hmac_sha256(key: &[u8]) -> Hmac<Sha256> {
+    // HMAC accepts keys of any length
+    Hmac::<Sha256>::new_from_slice(key).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlenderMesh, CreateSingleIndexConfig, Endianness};
+
+    /// Verify that changing any byte of a signed file, or verifying with the wrong key, is
+    /// detected, and that signing again replaces the previous signature.
+    #[test]
+    fn detects_corruption_and_wrong_keys() {
+        let cube =
+            BlenderMesh::cube(1.).combine_vertex_indices(&CreateSingleIndexConfig::default());
+        let bytes = cube.to_binary("Cube", Endianness::Big);
+
+        let hashed = sign_binary(&bytes, None).unwrap();
+        assert_eq!(verify_integrity(&hashed, None), Ok(()));
+        assert_eq!(
+            verify_integrity(&hashed, Some(b"key")),
+            Err(IntegrityError::NotSigned)
+        );
+        assert_eq!(verify_integrity(&bytes, None), Err(IntegrityError::Missing));
+
+        let mut corrupted = hashed.clone();
+        let vert = &inspect_binary(&hashed).unwrap().sections[3];
+        corrupted[vert.offset as usize + 5] ^= 1;
+        assert_eq!(
+            verify_integrity(&corrupted, None),
+            Err(IntegrityError::Mismatch("HASH".to_string()))
+        );
+
+        let signed = sign_binary(&hashed, Some(b"key")).unwrap();
+        let tags: Vec<String> = inspect_binary(&signed)
+            .unwrap()
+            .sections
+            .into_iter()
+            .map(|section| section.tag)
+            .collect();
+        assert_eq!(tags, vec!["NAME", "MESH", "LAYT", "VERT", "INDX", "HMAC"]);
+        assert_eq!(verify_integrity(&signed, Some(b"key")), Ok(()));
+        assert_eq!(
+            verify_integrity(&signed, Some(b"other key")),
+            Err(IntegrityError::Mismatch("HMAC".to_string()))
+        );
+    }
+}
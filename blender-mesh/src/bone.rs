@@ -1,9 +1,11 @@
 use crate::vertex_attributes::{MultiIndexedVertexAttributes, VertexBoneInfluences};
+use crate::BlenderMesh;
+use std::cmp::Ordering;
 
 /// The number of bones that influence each uniform.
 ///
 /// When exported from Blender this is non uniform, but becomes uniform when
-/// we call `.set_groups_per_vertex` to make every vertex have the same number
+/// we call [`BlenderMesh.method#set_groups_per_vertex`] to make every vertex have the same number
 /// of influences.
 ///
 /// TODO: Remove this and use VertexAttribute with something like attribute_size: Varies(vec![])
@@ -26,6 +28,103 @@ impl From<Vec<u8>> for BoneInfluencesPerVertex {
     }
 }
 
+/// How many bones should influence each vertex.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GroupsPerVertex {
+    /// Every vertex keeps up to this many of its strongest influences.
+    Fixed(u8),
+    /// Every vertex drops the influences whose weight is below the threshold, but always keeps
+    /// its strongest influence. Every vertex is then padded to the largest number of influences
+    /// that any vertex kept.
+    Auto {
+        /// Influences with a smaller weight than this are dropped.
+        weight_threshold: f32,
+    },
+}
+
+impl BlenderMesh {
+    /// The largest number of bones that influence a single vertex, not counting influences with
+    /// a weight of zero.
+    ///
+    /// Returns 0 if the mesh doesn't have any bone influences.
+    pub fn max_bone_influences(&self) -> u8 {
+        let bone_influences = match self.multi_indexed_vertex_attributes.bone_influences() {
+            Some(bone_influences) => bone_influences,
+            None => return 0,
+        };
+
+        bone_influences
+            .influences_per_vertex()
+            .iter()
+            .map(|(_, weights)| weights.iter().filter(|weight| **weight > 0.).count() as u8)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Give every vertex the same number of bone influences, so that they can be stored in
+    /// fixed size vertex attributes.
+    ///
+    /// Vertices with fewer influences are padded with bone 0 at a weight of zero. Weights are
+    /// not normalized after influences are dropped.
+    ///
+    /// Returns the number of influences that every vertex now has, which is what your GPU
+    /// attributes need to fit, or 0 if the mesh doesn't have any bone influences.
+    pub fn set_groups_per_vertex(&mut self, groups: GroupsPerVertex) -> u8 {
+        let multi = &mut self.multi_indexed_vertex_attributes;
+
+        let bone_influences = match multi.bone_influences.as_mut() {
+            Some(bone_influences) => bone_influences,
+            None => return 0,
+        };
+
+        let count = match groups {
+            GroupsPerVertex::Fixed(count) => count,
+            GroupsPerVertex::Auto { weight_threshold } => {
+                bone_influences.prune(weight_threshold);
+
+                match &bone_influences.bones_per_vertex {
+                    BoneInfluencesPerVertex::NonUniform(counts) => {
+                        counts.iter().max().cloned().unwrap_or(0)
+                    }
+                    BoneInfluencesPerVertex::Uniform(count) => *count,
+                }
+            }
+        };
+
+        multi.set_bone_influences_per_vertex(count);
+        count
+    }
+}
+
+impl VertexBoneInfluences {
+    /// Drop every influence that is weaker than the threshold, other than each vertex's strongest
+    /// influence.
+    fn prune(&mut self, weight_threshold: f32) {
+        let mut counts = vec![];
+        let mut bone_indices = vec![];
+        let mut bone_weights = vec![];
+
+        for (indices, weights) in self.influences_per_vertex() {
+            let influences = sanitized_influences(indices, weights);
+            let strongest = influences.iter().min_by(|a, b| strongest_first(a, b));
+
+            let mut kept = 0;
+            for influence in influences.iter() {
+                if influence.1 >= weight_threshold || Some(influence) == strongest {
+                    bone_indices.push(influence.0);
+                    bone_weights.push(influence.1);
+                    kept += 1;
+                }
+            }
+            counts.push(kept);
+        }
+
+        self.bones_per_vertex = BoneInfluencesPerVertex::NonUniform(counts);
+        self.bone_indices = bone_indices;
+        self.bone_weights = bone_weights;
+    }
+}
+
 impl MultiIndexedVertexAttributes {
     /// Different vertices might have different numbers of bones that influence them.
     /// A vertex near the shoulder might be influenced by the neck and upper arm and sternum,
@@ -38,77 +137,57 @@ impl MultiIndexedVertexAttributes {
     ///  - If a vertex has one vertex group (bone) we will create two fake bones with 0.0 weight.
     ///  - If a vertex has 5 bones we'll remove the one with the smallest weighting (influence).
     ///
-    /// TODO: I wrote landon when I was a Rust noob and so a lot of things are just wrong. This
-    /// method doesn't work if you try to set the bone influences to two different constants in
-    /// a row - for example.
-    ///       Need to eventually rewrite a lot of the library with TDD - but we can clean up over
-    ///       time.
+    /// Influences are sorted from strongest to weakest, with ties going to the smaller bone index
+    /// so that the same weights always produce the same bytes.
     pub(crate) fn set_bone_influences_per_vertex(&mut self, count: u8) {
+        let bone_influences = match self.bone_influences.as_mut() {
+            Some(bone_influences) => bone_influences,
+            None => return,
+        };
+
         let mut normalized_group_indices = vec![];
         let mut normalized_group_weights = vec![];
 
-        let mut current_index: u32 = 0;
+        for (indices, weights) in bone_influences.influences_per_vertex() {
+            let mut influences = sanitized_influences(indices, weights);
 
-        // TODO: Error handling
-        if self.bone_influences.is_none() {
-            return;
-        }
+            influences.sort_by(strongest_first);
+            influences.resize(count as usize, (0, 0.0));
 
-        {
-            let VertexBoneInfluences {
-                bones_per_vertex,
-                bone_indices,
-                bone_weights,
-            } = self.bone_influences.as_mut().unwrap();
-
-            if let BoneInfluencesPerVertex::NonUniform(bone_influences_per_vertex) =
-                &bones_per_vertex
-            {
-                for group_count in bone_influences_per_vertex.iter() {
-                    let mut vertex_indices = vec![];
-                    let mut vertex_weights = vec![];
-
-                    for index in current_index..(current_index + *group_count as u32) {
-                        vertex_indices.push(index);
-                        vertex_weights.push(bone_weights[index as usize]);
-                    }
-
-                    vertex_weights.sort_by(|a, b| b.partial_cmp(a).unwrap());
-                    vertex_indices.sort_by(|a, b| {
-                        bone_weights[*b as usize]
-                            .partial_cmp(&bone_weights[*a as usize])
-                            .unwrap()
-                    });
-
-                    let mut vertex_indices: Vec<u8> = vertex_indices
-                        .iter()
-                        .map(|i| bone_indices[*i as usize])
-                        .collect();
-
-                    vertex_indices.resize(count as usize, 0);
-                    vertex_weights.resize(count as usize, 0.0);
-
-                    normalized_group_indices.append(&mut vertex_indices);
-                    normalized_group_weights.append(&mut vertex_weights);
-
-                    current_index += *group_count as u32;
-                }
+            for (bone, weight) in influences {
+                normalized_group_indices.push(bone);
+                normalized_group_weights.push(weight);
             }
         }
 
-        let mut bone_influences = self.bone_influences.as_mut().unwrap();
-
         bone_influences.bones_per_vertex = BoneInfluencesPerVertex::Uniform(count);
         bone_influences.bone_indices = normalized_group_indices;
         bone_influences.bone_weights = normalized_group_weights;
     }
 }
 
+/// Pair every bone with its weight, treating NaN weights as zero so that they can be sorted.
+fn sanitized_influences(indices: &[u8], weights: &[f32]) -> Vec<(u8, f32)> {
+    indices
+        .iter()
+        .zip(weights.iter())
+        .map(|(bone, weight)| match weight.is_nan() {
+            true => (*bone, 0.),
+            false => (*bone, *weight),
+        })
+        .collect()
+}
+
+/// Orders influences from the largest weight to the smallest, breaking ties with the smallest
+/// bone index.
+fn strongest_first(a: &(u8, f32), b: &(u8, f32)) -> Ordering {
+    b.1.total_cmp(&a.1).then(a.0.cmp(&b.0))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::combine_indices::tests::TodoDeleteMeMultiConverter;
-    use crate::BlenderMesh;
 
     #[test]
     fn set_joints_per_vert() {
@@ -141,4 +220,112 @@ mod tests {
 
         assert_eq!(three_joints_per_vert, expected_mesh);
     }
+
+    fn mesh_with_influences() -> BlenderMesh {
+        BlenderMesh {
+            multi_indexed_vertex_attributes: TodoDeleteMeMultiConverter {
+                vertex_group_indices: Some(vec![0, 2, 3, 4, 0, 1, 3, 2]),
+                bone_influences_per_vertex: Some(vec![1, 3, 4].into()),
+                vertex_group_weights: Some(vec![1.0, 0.5, 0.2, 0.3, 0.6, 0.15, 0.1, 0.15]),
+                ..TodoDeleteMeMultiConverter::default()
+            }
+            .into(),
+            ..BlenderMesh::default()
+        }
+    }
+
+    /// Verify that weak influences are dropped and that every vertex gets padded to the largest
+    /// number of influences that remain.
+    #[test]
+    fn auto_groups_per_vertex() {
+        let mut mesh = mesh_with_influences();
+        assert_eq!(mesh.max_bone_influences(), 4);
+
+        let count = mesh.set_groups_per_vertex(GroupsPerVertex::Auto {
+            weight_threshold: 0.25,
+        });
+
+        assert_eq!(count, 2);
+        assert_eq!(mesh.max_bone_influences(), 2);
+
+        let bone_influences = mesh
+            .multi_indexed_vertex_attributes
+            .bone_influences()
+            .unwrap();
+        assert_eq!(
+            bone_influences.bones_per_vertex,
+            BoneInfluencesPerVertex::Uniform(2)
+        );
+        assert_eq!(bone_influences.bone_indices, vec![0, 0, 2, 4, 0, 0]);
+        assert_eq!(
+            bone_influences.bone_weights,
+            vec![1.0, 0.0, 0.5, 0.3, 0.6, 0.0]
+        );
+    }
+
+    /// Verify that a vertex keeps its strongest influence even if it is below the threshold, and
+    /// that the number of influences can be changed again afterwards.
+    #[test]
+    fn auto_groups_keep_strongest_influence() {
+        let mut mesh = mesh_with_influences();
+
+        assert_eq!(
+            mesh.set_groups_per_vertex(GroupsPerVertex::Auto {
+                weight_threshold: 2.0,
+            }),
+            1
+        );
+        assert_eq!(mesh.set_groups_per_vertex(GroupsPerVertex::Fixed(2)), 2);
+
+        let bone_influences = mesh
+            .multi_indexed_vertex_attributes
+            .bone_influences()
+            .unwrap();
+        assert_eq!(bone_influences.bone_indices, vec![0, 0, 2, 0, 0, 0]);
+        assert_eq!(
+            bone_influences.bone_weights,
+            vec![1.0, 0.0, 0.5, 0.0, 0.6, 0.0]
+        );
+    }
+
+    /// Verify that a mesh without bone influences is left alone.
+    #[test]
+    fn no_bone_influences() {
+        let mut mesh = BlenderMesh::cube(1.);
+
+        assert_eq!(mesh.max_bone_influences(), 0);
+        assert_eq!(mesh.set_groups_per_vertex(GroupsPerVertex::Fixed(4)), 0);
+        assert!(mesh
+            .multi_indexed_vertex_attributes
+            .bone_influences
+            .is_none());
+    }
+
+    /// Verify that NaN weights are treated as zero and that influences with the same weight are
+    /// ordered by their bone index, regardless of the order that they were exported in.
+    #[test]
+    fn nan_weights_and_ties() {
+        let mut mesh = BlenderMesh {
+            multi_indexed_vertex_attributes: TodoDeleteMeMultiConverter {
+                vertex_group_indices: Some(vec![4, 1, 3, 2, 7, 5]),
+                bone_influences_per_vertex: Some(vec![4, 2].into()),
+                vertex_group_weights: Some(vec![0.25, 0.25, f32::NAN, 0.5, 0.5, 0.5]),
+                ..TodoDeleteMeMultiConverter::default()
+            }
+            .into(),
+            ..BlenderMesh::default()
+        };
+
+        mesh.set_groups_per_vertex(GroupsPerVertex::Fixed(3));
+
+        let bone_influences = mesh
+            .multi_indexed_vertex_attributes
+            .bone_influences()
+            .unwrap();
+        assert_eq!(bone_influences.bone_indices, vec![2, 1, 4, 5, 7, 0]);
+        assert_eq!(
+            bone_influences.bone_weights,
+            vec![0.5, 0.25, 0.25, 0.5, 0.5, 0.]
+        );
+    }
 }
@@ -0,0 +1,418 @@
+use crate::{BlenderMesh, BoundingBox, VertexAttribute};
+use nalgebra::{Point3, Vector3};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// Leaves get split until they hold this many triangles or fewer.
+const MAX_TRIANGLES_PER_LEAF: usize = 4;
+
+/// A bounding volume hierarchy over the triangles of a mesh.
+///
+/// Faces with more than three vertices are fan triangulated, so a BVH can be built for a mesh
+/// regardless of whether or not it has been triangulated.
+///
+/// The BVH only stores indices into the mesh's positions, so it is only valid for the mesh (and
+/// the positions) that it was built from.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Bvh {
+    pub(crate) nodes: Vec<BvhNode>,
+    pub(crate) triangles: Vec<BvhTriangle>,
+}
+
+/// A node in a [`Bvh`]. The root node is the first node.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct BvhNode {
+    pub(crate) bounding_box: BoundingBox,
+    /// For leaf nodes this is the index of the leaf's first triangle.
+    /// For interior nodes this is the index of the left child. The right child comes right after.
+    pub(crate) first: u32,
+    /// The number of triangles in a leaf node. Always 0 for interior nodes.
+    pub(crate) triangle_count: u32,
+}
+
+/// A triangle in a [`Bvh`].
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BvhTriangle {
+    pub(crate) positions: [u32; 3],
+    pub(crate) face: u32,
+}
+
+impl Bvh {
+    /// Build a BVH over all of the faces in a mesh.
+    pub fn new(mesh: &BlenderMesh) -> Bvh {
+        let multi = &mesh.multi_indexed_vertex_attributes;
+        let positions = &multi.positions.attribute;
+
+        let mut triangles = vec![];
+        let mut face_start = 0;
+
+        for (face, vertex_count) in multi.vertices_in_each_face.iter().enumerate() {
+            let vertex_count = *vertex_count as usize;
+            let indices = &multi.positions.indices[face_start..face_start + vertex_count];
+
+            for corner in 1..vertex_count.saturating_sub(1) {
+                let triangle = BvhTriangle {
+                    positions: [indices[0], indices[corner], indices[corner + 1]],
+                    face: face as u32,
+                };
+                let centroid = triangle.centroid(positions);
+
+                triangles.push((triangle, centroid));
+            }
+
+            face_start += vertex_count;
+        }
+
+        let mut nodes = vec![];
+        if !triangles.is_empty() {
+            nodes.push(BvhNode::default());
+            subdivide(&mut nodes, 0, &mut triangles, 0, positions);
+        }
+
+        Bvh {
+            nodes,
+            triangles: triangles
+                .into_iter()
+                .map(|(triangle, _)| triangle)
+                .collect(),
+        }
+    }
+
+    /// The nodes in the hierarchy, starting with the root node.
+    pub fn nodes(&self) -> &Vec<BvhNode> {
+        &self.nodes
+    }
+
+    /// The triangles in the hierarchy, ordered such that every leaf's triangles are contiguous.
+    pub fn triangles(&self) -> &Vec<BvhTriangle> {
+        &self.triangles
+    }
+}
+
+impl BvhNode {
+    /// The smallest box that contains every triangle below this node
+    pub fn bounding_box(&self) -> BoundingBox {
+        self.bounding_box
+    }
+
+    /// The indices of the left and right child nodes, or None if this is a leaf.
+    pub fn children(&self) -> Option<(usize, usize)> {
+        match self.triangle_count {
+            0 => Some((self.first as usize, self.first as usize + 1)),
+            _ => None,
+        }
+    }
+
+    /// The range of triangles in this leaf, or None if this is an interior node.
+    pub fn triangle_range(&self) -> Option<Range<usize>> {
+        match self.triangle_count {
+            0 => None,
+            count => Some(self.first as usize..(self.first + count) as usize),
+        }
+    }
+}
+
+impl BvhTriangle {
+    /// The indices of this triangle's three positions, wound in the same order as the mesh.
+    pub fn positions(&self) -> [u32; 3] {
+        self.positions
+    }
+
+    /// The index of the mesh face that this triangle belongs to.
+    pub fn face(&self) -> usize {
+        self.face as usize
+    }
+
+    pub(crate) fn corners(&self, positions: &VertexAttribute<f32>) -> [Point3<f32>; 3] {
+        let corner = |idx: u32| {
+            let position = positions.data_at_idx(idx);
+            Point3::new(position[0], position[1], position[2])
+        };
+
+        [
+            corner(self.positions[0]),
+            corner(self.positions[1]),
+            corner(self.positions[2]),
+        ]
+    }
+
+    fn centroid(&self, positions: &VertexAttribute<f32>) -> Point3<f32> {
+        let [a, b, c] = self.corners(positions);
+        Point3::from((a.coords + b.coords + c.coords) / 3.)
+    }
+}
+
+fn subdivide(
+    nodes: &mut Vec<BvhNode>,
+    node_idx: usize,
+    triangles: &mut [(BvhTriangle, Point3<f32>)],
+    first_triangle: usize,
+    positions: &VertexAttribute<f32>,
+) {
+    let mut bounding_box = BoundingBox {
+        min_corner: Point3::new(std::f32::MAX, std::f32::MAX, std::f32::MAX),
+        max_corner: Point3::new(std::f32::MIN, std::f32::MIN, std::f32::MIN),
+    };
+    let mut centroid_min = bounding_box.min_corner;
+    let mut centroid_max = bounding_box.max_corner;
+
+    for (triangle, centroid) in triangles.iter() {
+        let corners = triangle.corners(positions);
+
+        for axis in 0..3 {
+            for corner in corners.iter() {
+                bounding_box.min_corner[axis] = bounding_box.min_corner[axis].min(corner[axis]);
+                bounding_box.max_corner[axis] = bounding_box.max_corner[axis].max(corner[axis]);
+            }
+
+            centroid_min[axis] = centroid_min[axis].min(centroid[axis]);
+            centroid_max[axis] = centroid_max[axis].max(centroid[axis]);
+        }
+    }
+
+    if triangles.len() <= MAX_TRIANGLES_PER_LEAF {
+        nodes[node_idx] = BvhNode {
+            bounding_box,
+            first: first_triangle as u32,
+            triangle_count: triangles.len() as u32,
+        };
+        return;
+    }
+
+    // Split at the median centroid along the axis where the centroids are the most spread out
+    let extent = centroid_max - centroid_min;
+    let axis = (0..3)
+        .max_by(|a, b| {
+            extent[*a]
+                .partial_cmp(&extent[*b])
+                .unwrap_or(Ordering::Equal)
+        })
+        .unwrap();
+    triangles.sort_by(|a, b| a.1[axis].partial_cmp(&b.1[axis]).unwrap_or(Ordering::Equal));
+
+    let left = nodes.len();
+    nodes.push(BvhNode::default());
+    nodes.push(BvhNode::default());
+
+    nodes[node_idx] = BvhNode {
+        bounding_box,
+        first: left as u32,
+        triangle_count: 0,
+    };
+
+    let middle = triangles.len() / 2;
+    let (left_triangles, right_triangles) = triangles.split_at_mut(middle);
+
+    subdivide(nodes, left, left_triangles, first_triangle, positions);
+    subdivide(
+        nodes,
+        left + 1,
+        right_triangles,
+        first_triangle + middle,
+        positions,
+    );
+}
+
+/// A BVH that gets built the first time that a mesh is queried.
+///
+/// A precomputed BVH gets serialized alongside the mesh so that it does not need to be built when
+/// the mesh is loaded. Lazily built BVHs are never serialized.
+///
+/// Two caches are always equal so that comparing meshes only compares their data.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BvhCache {
+    bvh: OnceLock<Bvh>,
+    precomputed: bool,
+}
+
+impl BvhCache {
+    /// Used to skip serializing BVHs that were not precomputed.
+    pub(crate) fn is_lazy(&self) -> bool {
+        !self.precomputed
+    }
+}
+
+impl PartialEq for BvhCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Serialize for BvhCache {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.bvh.get().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BvhCache {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let cache = match Option::<Bvh>::deserialize(deserializer)? {
+            Some(bvh) => BvhCache {
+                bvh: OnceLock::from(bvh),
+                precomputed: true,
+            },
+            None => BvhCache::default(),
+        };
+
+        Ok(cache)
+    }
+}
+
+impl BlenderMesh {
+    /// A bounding volume hierarchy over this mesh's faces.
+    ///
+    /// The BVH is built the first time that it is needed and then re-used by subsequent queries
+    /// such as [`BlenderMesh::raycast`] and [`BlenderMesh::closest_point`].
+    pub fn bvh(&self) -> &Bvh {
+        self.bvh.bvh.get_or_init(|| Bvh::new(self))
+    }
+
+    /// Build this mesh's BVH now and include it when serializing the mesh.
+    ///
+    /// Useful for large static meshes, since a deserialized mesh can start answering spatial
+    /// queries without first needing to build its BVH.
+    pub fn precompute_bvh(&mut self) {
+        self.bvh();
+        self.bvh.precomputed = true;
+    }
+
+    /// Whether or not this mesh's BVH gets serialized along with the mesh.
+    pub fn has_precomputed_bvh(&self) -> bool {
+        self.bvh.precomputed
+    }
+
+    /// Drop the cached BVH. Needs to be called whenever the mesh's positions change.
+    ///
+    /// A precomputed BVH gets rebuilt right away.
+    pub(crate) fn invalidate_bvh(&mut self) {
+        let precomputed = self.bvh.precomputed;
+
+        self.bvh = BvhCache::default();
+        if precomputed {
+            self.precompute_bvh();
+        }
+    }
+}
+
+/// The squared distance from a point to the closest point inside of a bounding box.
+pub(crate) fn distance_squared_to_box(point: &Point3<f32>, bounding_box: &BoundingBox) -> f32 {
+    let mut distance_squared = 0.;
+
+    for axis in 0..3 {
+        let below = bounding_box.min_corner[axis] - point[axis];
+        let above = point[axis] - bounding_box.max_corner[axis];
+        let outside = below.max(above).max(0.);
+
+        distance_squared += outside * outside;
+    }
+
+    distance_squared
+}
+
+/// The distance along a ray at which it enters a bounding box, or None if it misses the box.
+///
+/// `inverse_direction` is 1.0 divided by each component of the ray's direction.
+pub(crate) fn ray_box_entry(
+    origin: &Point3<f32>,
+    inverse_direction: &Vector3<f32>,
+    bounding_box: &BoundingBox,
+) -> Option<f32> {
+    let mut entry: f32 = 0.;
+    let mut exit = std::f32::INFINITY;
+
+    for axis in 0..3 {
+        let near = (bounding_box.min_corner[axis] - origin[axis]) * inverse_direction[axis];
+        let far = (bounding_box.max_corner[axis] - origin[axis]) * inverse_direction[axis];
+
+        entry = entry.max(near.min(far));
+        exit = exit.min(near.max(far));
+    }
+
+    match entry <= exit {
+        true => Some(entry),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that every triangle ends up in exactly one leaf and that every node contains the
+    /// triangles below it.
+    #[test]
+    fn leaves_cover_every_triangle() {
+        let mesh = BlenderMesh::uv_sphere(1.0, 16, 8);
+        let bvh = mesh.bvh();
+        let positions = &mesh.multi_indexed_vertex_attributes.positions.attribute;
+
+        let mut covered = vec![0; bvh.triangles.len()];
+        let mut stack = vec![(0, None)];
+
+        while let Some((node_idx, parent)) = stack.pop() {
+            let node: &BvhNode = &bvh.nodes[node_idx];
+
+            if let Some(parent) = parent {
+                let parent: &BvhNode = &bvh.nodes[parent];
+                assert!(contains(
+                    &parent.bounding_box,
+                    &node.bounding_box.min_corner
+                ));
+                assert!(contains(
+                    &parent.bounding_box,
+                    &node.bounding_box.max_corner
+                ));
+            }
+
+            match node.children() {
+                Some((left, right)) => {
+                    stack.push((left, Some(node_idx)));
+                    stack.push((right, Some(node_idx)));
+                }
+                None => {
+                    for triangle_idx in node.triangle_range().unwrap() {
+                        covered[triangle_idx] += 1;
+
+                        for corner in bvh.triangles[triangle_idx].corners(positions).iter() {
+                            assert!(contains(&node.bounding_box, corner));
+                        }
+                    }
+                }
+            }
+        }
+
+        assert!(covered.iter().all(|count| *count == 1));
+    }
+
+    fn contains(bounding_box: &BoundingBox, point: &Point3<f32>) -> bool {
+        distance_squared_to_box(point, bounding_box) == 0.
+    }
+
+    /// Verify that precomputed BVHs survive a serialization round trip and that lazily built
+    /// BVHs are left out.
+    #[test]
+    fn serialize_precomputed_bvh() {
+        let mut mesh = BlenderMesh::cube(1.0);
+        mesh.bvh();
+        assert!(!serde_json::to_string(&mesh).unwrap().contains("bvh"));
+
+        mesh.precompute_bvh();
+        let serialized = serde_json::to_string(&mesh).unwrap();
+        assert!(serialized.contains("bvh"));
+
+        let deserialized: BlenderMesh = serde_json::from_str(&serialized).unwrap();
+        assert!(deserialized.has_precomputed_bvh());
+        assert_eq!(deserialized.bvh.bvh.get(), Some(mesh.bvh()));
+    }
+
+    /// Verify that faces with more than 3 vertices get split into triangles.
+    #[test]
+    fn fan_triangulates_faces() {
+        let bvh = Bvh::new(&BlenderMesh::cube(1.0));
+
+        assert_eq!(bvh.triangles.len(), 12);
+        assert_eq!(bvh.triangles.iter().filter(|t| t.face() == 5).count(), 2);
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{PrincipledBSDF, TextureSlot};
+use std::collections::BTreeMap;
+
+/// How the values of a texture are encoded.
+///
+/// # Blender
+///
+/// The color space setting of the texture's image.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub enum ColorSpace {
+    /// Gamma encoded color, which should be decoded to linear color before it is lit, such as
+    /// by sampling it from an sRGB texture format.
+    Srgb,
+    /// Linear color that is lit as it is.
+    Linear,
+    /// Data that isn't a color, such as normal maps and roughness maps, which must not be gamma
+    /// corrected.
+    ///
+    /// # Blender
+    ///
+    /// Non-Color and Raw.
+    NonColor,
+    /// A color space that landon doesn't know about, such as Filmic or ACES, by its name in
+    /// Blender.
+    Other(String),
+}
+
+impl ColorSpace {
+    /// Whether the texture should be sampled from an sRGB texture format.
+    pub fn is_srgb(&self) -> bool {
+        *self == ColorSpace::Srgb
+    }
+}
+
+impl PrincipledBSDF {
+    /// The color space of every texture slot whose color space was exported.
+    pub fn color_spaces(&self) -> &BTreeMap<TextureSlot, ColorSpace> {
+        &self.color_spaces
+    }
+
+    /// The color space of a texture slot's texture, or `None` if the slot isn't textured or the
+    /// material was exported by an older version of landon.
+    pub fn color_space(&self, slot: TextureSlot) -> Option<&ColorSpace> {
+        self.color_spaces.get(&slot)
+    }
+
+    /// See [`PrincipledBSDF.method#color_space`]
+    pub fn set_color_space(&mut self, slot: TextureSlot, color_space: Option<ColorSpace>) {
+        match color_space {
+            Some(color_space) => self.color_spaces.insert(slot, color_space),
+            None => self.color_spaces.remove(&slot),
+        };
+    }
+}
@@ -4,6 +4,12 @@ pub struct CreateSingleIndexConfig {
     /// The number of bones that influence each vertex.
     ///
     /// If unset then no bone influences will be included in the final single indexed VertexData.
+    ///
+    /// To pick a count based on the mesh's weights, call
+    /// [`BlenderMesh.method#set_groups_per_vertex`] with [`GroupsPerVertex::Auto`] first and pass
+    /// in the count that it returns.
+    ///
+    /// [`GroupsPerVertex::Auto`]: enum.GroupsPerVertex.html#variant.Auto
     pub bone_influences_per_vertex: Option<u8>,
     /// Whether or not to calculate the tangents for each vertex.
     ///
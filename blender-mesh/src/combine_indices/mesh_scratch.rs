@@ -0,0 +1,94 @@
+use super::EncounteredIndexCombinations;
+use crate::custom_attribute::Corner;
+use std::collections::HashSet;
+
+/// Working memory for [`BlenderMesh.method#try_combine_vertex_indices_with_scratch`] that can be
+/// reused from one mesh to the next.
+///
+/// Combining indices builds a handful of buffers that are as large as the mesh and are thrown
+/// away once the vertices have been created. When processing thousands of meshes in a build step,
+/// passing the same `MeshScratch` to every call clears and refills these buffers instead of
+/// allocating new ones, so after the largest mesh has been combined no more buffers need to grow.
+///
+/// The combined results are the same as [`BlenderMesh.method#try_combine_vertex_indices`].
+///
+/// ```
+/// # use blender_mesh::{BlenderMesh, CreateSingleIndexConfig, MeshScratch};
+/// let mut scratch = MeshScratch::default();
+///
+/// for size in 1..4 {
+///     let single = BlenderMesh::cube(size as f32)
+///         .try_combine_vertex_indices_with_scratch(&CreateSingleIndexConfig::default(), &mut scratch)
+///         .unwrap();
+/// #   assert_eq!(single.vertices().len(), 24);
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct MeshScratch {
+    pub(super) corners: Vec<Corner>,
+    pub(super) encountered_vert_data: EncounteredIndexCombinations,
+    pub(super) encountered_vert_ids: HashSet<u32>,
+    pub(super) expanded_positions: Vec<f32>,
+    pub(super) expanded_normals: Vec<f32>,
+    pub(super) expanded_material_index: Vec<u16>,
+    pub(super) expanded_uvs: Vec<f32>,
+    pub(super) expanded_pos_indices: Vec<u16>,
+    pub(super) expanded_tangents: Vec<f32>,
+}
+
+impl MeshScratch {
+    /// Empty every buffer while keeping their capacity.
+    pub(super) fn clear(&mut self) {
+        self.corners.clear();
+        self.encountered_vert_data.clear();
+        self.encountered_vert_ids.clear();
+        self.expanded_positions.clear();
+        self.expanded_normals.clear();
+        self.expanded_material_index.clear();
+        self.expanded_uvs.clear();
+        self.expanded_pos_indices.clear();
+        self.expanded_tangents.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlenderMesh, CreateSingleIndexConfig};
+
+    /// Verify that reusing scratch buffers gives the same vertices as fresh buffers, and that a
+    /// smaller mesh after a larger one doesn't need the buffers to grow.
+    #[test]
+    fn reused_scratch_matches_fresh() {
+        let config = CreateSingleIndexConfig {
+            bone_influences_per_vertex: None,
+            calculate_face_tangents: true,
+        };
+        let mut scratch = MeshScratch::default();
+
+        let large = BlenderMesh::plane(2., 2., 8)
+            .try_combine_vertex_indices_with_scratch(&config, &mut scratch)
+            .unwrap();
+        let capacity = scratch.expanded_positions.capacity();
+        let hash_capacity = scratch.encountered_vert_data.capacity();
+
+        let small = BlenderMesh::cube(1.)
+            .try_combine_vertex_indices_with_scratch(&config, &mut scratch)
+            .unwrap();
+
+        assert_eq!(
+            large,
+            BlenderMesh::plane(2., 2., 8)
+                .try_combine_vertex_indices(&config)
+                .unwrap()
+        );
+        assert_eq!(
+            small,
+            BlenderMesh::cube(1.)
+                .try_combine_vertex_indices(&config)
+                .unwrap()
+        );
+        assert_eq!(scratch.expanded_positions.capacity(), capacity);
+        assert_eq!(scratch.encountered_vert_data.capacity(), hash_capacity);
+    }
+}
@@ -1,12 +1,15 @@
 pub use self::create_single_index_config::CreateSingleIndexConfig;
+pub use self::mesh_scratch::MeshScratch;
+pub use self::weighted_normals::WeightedNormalsError;
+use crate::custom_attribute::{corner_value_ids, fill_corners, Corner, CustomAttribute};
 use crate::face_tangents::face_tangent_at_idx;
 use crate::vertex_attributes::{BoneAttributes, SingleIndexedVertexAttributes, VertexAttribute};
-use crate::{BlenderMesh, BoneInfluence, Vertex};
+use crate::{AttributeDomain, BlenderMesh, BoneInfluence, MeshKind, Vertex};
 use std::collections::HashMap;
-use std::collections::HashSet;
 use std::ops::{Deref, DerefMut};
 
 mod create_single_index_config;
+mod mesh_scratch;
 mod weighted_normals;
 
 /// Used to set temporary data that should get overwritten.
@@ -18,6 +21,20 @@ mod weighted_normals;
 /// to more easily notice any errors.
 const EASILY_RECOGNIZABLE_NUMBER: f32 = 123456789.;
 
+/// The most vertices that a single index can point to.
+const MAX_SINGLE_INDEXED_VERTICES: usize = SingleIndex::MAX as usize + 1;
+
+/// An error while combining a mesh's indices into a single index.
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum CombineIndicesError {
+    /// Single indices are u16s, so they can only point to so many vertices.
+    #[error(
+        "Mesh {mesh} needs {vertex_count} vertices after combining indices, but u16 indices can \
+         only point to 65536 vertices"
+    )]
+    TooManyVertices { mesh: String, vertex_count: usize },
+}
+
 impl BlenderMesh {
     /// We store our exported Blender mesh with indices for positions, normals and uvs because
     ///
@@ -43,10 +60,47 @@ impl BlenderMesh {
     ///
     /// TODO: There are unexpected (based on the method's name) mutations in here such as
     /// triangulation. Lot's to refactor in this crate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mesh needs more vertices than fit in u16 indices. Use
+    /// [`BlenderMesh.method#try_combine_vertex_indices`] to handle that instead.
     pub fn combine_vertex_indices(
         &mut self,
         config: &CreateSingleIndexConfig,
     ) -> SingleIndexedVertexAttributes {
+        match self.try_combine_vertex_indices(config) {
+            Ok(single) => single,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// See [`BlenderMesh.method#combine_vertex_indices`].
+    ///
+    /// Returns an error instead of wrapping around if the mesh needs more vertices than fit in
+    /// u16 indices, such as a large mesh whose positions get split along many uv seams.
+    ///
+    /// Meshes without faces are combined into points or lines depending on their
+    /// [`BlenderMesh.method#kind`], with one vertex per position. They don't have normals, uvs or
+    /// face tangents since those belong to faces.
+    pub fn try_combine_vertex_indices(
+        &mut self,
+        config: &CreateSingleIndexConfig,
+    ) -> Result<SingleIndexedVertexAttributes, CombineIndicesError> {
+        self.try_combine_vertex_indices_with_scratch(config, &mut MeshScratch::default())
+    }
+
+    /// See [`BlenderMesh.method#try_combine_vertex_indices`].
+    ///
+    /// Uses the buffers in the [`MeshScratch`] instead of allocating new ones, so that combining
+    /// many meshes one after the other only allocates the combined vertices and indices.
+    ///
+    /// [`MeshScratch`]: struct.MeshScratch.html
+    pub fn try_combine_vertex_indices_with_scratch(
+        &mut self,
+        config: &CreateSingleIndexConfig,
+        scratch: &mut MeshScratch,
+    ) -> Result<SingleIndexedVertexAttributes, CombineIndicesError> {
         let mut face_tangents = None;
 
         if let Some(bone_influences_per_vertex) = config.bone_influences_per_vertex {
@@ -54,6 +108,10 @@ impl BlenderMesh {
                 .set_bone_influences_per_vertex(bone_influences_per_vertex);
         }
 
+        if self.kind() != MeshKind::Triangles {
+            return self.combine_loose_vertex_indices(config.bone_influences_per_vertex);
+        }
+
         // Important to calculate face tangents before we modify / weight the normals
         if config.calculate_face_tangents {
             face_tangents = Some(self.calculate_face_tangents().unwrap());
@@ -63,24 +121,32 @@ impl BlenderMesh {
 
         let mut largest_vert_id = *multi.positions.indices.iter().max().unwrap() as usize;
 
-        let mut encountered_vert_data = EncounteredIndexCombinations::default();
-
-        let mut encountered_vert_ids = HashSet::new();
+        scratch.clear();
+        let MeshScratch {
+            corners,
+            encountered_vert_data,
+            encountered_vert_ids,
+            expanded_positions,
+            expanded_normals,
+            expanded_material_index,
+            expanded_uvs,
+            expanded_pos_indices,
+            expanded_tangents,
+        } = scratch;
+
+        // Corners that share a position but have different custom attribute values can't share
+        // a vertex
+        fill_corners(multi, corners);
+        let custom_attribute_ids = corner_value_ids(&self.custom_attributes, corners);
 
-        let mut expanded_positions = vec![];
         expanded_positions.resize((largest_vert_id + 1) * 3, EASILY_RECOGNIZABLE_NUMBER);
 
-        let mut expanded_normals = vec![];
         expanded_normals.resize((largest_vert_id + 1) * 3, EASILY_RECOGNIZABLE_NUMBER);
 
-        let mut expanded_material_index = vec![];
         expanded_material_index.resize( largest_vert_id +1, 0u16);
 
-        let mut expanded_uvs = vec![];
         expanded_uvs.resize((largest_vert_id + 1) * 2, EASILY_RECOGNIZABLE_NUMBER);
 
-        let mut expanded_pos_indices = vec![];
-
         let mut new_group_indices = multi
             .bone_influences
             .as_ref()
@@ -95,7 +161,6 @@ impl BlenderMesh {
         let mut face_idx = 0;
         let mut vertices_until_next_face = multi.vertices_in_each_face[0];
 
-        let mut expanded_tangents = vec![];
         expanded_tangents.resize((largest_vert_id + 1) * 3, EASILY_RECOGNIZABLE_NUMBER);
 
         // FIXME: Split this loop into a function
@@ -111,8 +176,14 @@ impl BlenderMesh {
                 None => None,
             };
 
-            let vert_id_to_reuse =
-                encountered_vert_data.get(&(start_vert_id, normal_index, uv_index));
+            let custom_attribute_id = custom_attribute_ids[elem_array_index];
+
+            let vert_id_to_reuse = encountered_vert_data.get(&(
+                start_vert_id,
+                normal_index,
+                uv_index,
+                custom_attribute_id,
+            ));
 
             // If we've already seen this combination of vertex indices we'll re-use the index
             if vert_id_to_reuse.is_some() {
@@ -140,17 +211,18 @@ impl BlenderMesh {
                 // to pass it around everywhere ..
                 self.handle_first_vertex_encounter(
                     &face_tangents,
-                    &mut encountered_vert_data,
-                    &mut expanded_pos_indices,
+                    encountered_vert_data,
+                    expanded_pos_indices,
                     start_vert_id,
                     elem_array_index,
-                    &mut expanded_positions,
-                    &mut expanded_material_index,
-                    &mut expanded_normals,
-                    &mut expanded_uvs,
-                    &mut expanded_tangents,
+                    expanded_positions,
+                    expanded_material_index,
+                    expanded_normals,
+                    expanded_uvs,
+                    expanded_tangents,
                     normal_index,
                     uv_index,
+                    custom_attribute_id,
                     face_idx,
                 );
             } else {
@@ -170,16 +242,16 @@ impl BlenderMesh {
                     config.bone_influences_per_vertex,
                     new_group_indices.as_mut(),
                     new_group_weights.as_mut(),
-                    &mut expanded_positions,
-                    &mut expanded_material_index,
-                    &mut expanded_normals,
-                    &mut expanded_uvs,
-                    &mut expanded_tangents,
+                    expanded_positions,
+                    expanded_material_index,
+                    expanded_normals,
+                    expanded_uvs,
+                    expanded_tangents,
                     face_idx,
                 );
 
                 encountered_vert_data.insert(
-                    (start_vert_id as u16, normal_index, uv_index),
+                    (start_vert_id, normal_index, uv_index, custom_attribute_id),
                     largest_vert_id as u16,
                 );
             }
@@ -196,13 +268,22 @@ impl BlenderMesh {
             }
         }
 
+        // Any vertex past the largest single index had its index wrap around to an earlier vertex
+        let vertex_count = largest_vert_id + 1;
+        if vertex_count > MAX_SINGLE_INDEXED_VERTICES {
+            return Err(CombineIndicesError::TooManyVertices {
+                mesh: self.name.clone(),
+                vertex_count,
+            });
+        }
+
         let normals = match self.multi_indexed_vertex_attributes.normals.is_some() {
             false => None,
-            true => Some(expanded_normals),
+            true => Some(expanded_normals.as_slice()),
         };
         let uvs = match self.multi_indexed_vertex_attributes.uvs.is_some() {
             false => None,
-            true => Some(expanded_uvs),
+            true => Some(expanded_uvs.as_slice()),
         };
 
         let bones = match (
@@ -227,17 +308,140 @@ impl BlenderMesh {
             _ => None,
         };
 
-        let tangents = face_tangents.map(|_| expanded_tangents);
+        let tangents = face_tangents.map(|_| expanded_tangents.as_slice());
+
+        let vertices = make_vertices(
+            expanded_material_index,
+            expanded_positions,
+            normals,
+            uvs,
+            tangents,
+            bones,
+        );
+        let custom_attributes =
+            self.combine_custom_attributes(corners, expanded_pos_indices, vertices.len());
+        let indices = self.triangulate(expanded_pos_indices);
+
+        // Every vertex gets the same attributes and every index points to a vertex that we
+        // created, so this can't fail.
+        let mut single = SingleIndexedVertexAttributes::new(indices, vertices).unwrap();
+        single.custom_attributes = custom_attributes;
+
+        Ok(single)
+    }
+
+    /// Meshes without faces don't have any corners to split positions along, so every position
+    /// becomes one vertex.
+    fn combine_loose_vertex_indices(
+        &self,
+        bone_influences_per_vertex: Option<u8>,
+    ) -> Result<SingleIndexedVertexAttributes, CombineIndicesError> {
+        let multi = &self.multi_indexed_vertex_attributes;
+
+        let vertex_count = multi.positions.attribute.data.len() / 3;
+        if vertex_count > MAX_SINGLE_INDEXED_VERTICES {
+            return Err(CombineIndicesError::TooManyVertices {
+                mesh: self.name.clone(),
+                vertex_count,
+            });
+        }
+
+        let bones = match (&multi.bone_influences, bone_influences_per_vertex) {
+            (Some(bone_influences), Some(bone_influences_per_vertex)) => Some((
+                BoneAttributes {
+                    bone_influencers: VertexAttribute::new(
+                        bone_influences.bone_indices.clone(),
+                        bone_influences_per_vertex,
+                    )
+                    .unwrap(),
+                    bone_weights: VertexAttribute::new(
+                        bone_influences.bone_weights.clone(),
+                        bone_influences_per_vertex,
+                    )
+                    .unwrap(),
+                },
+                bone_influences_per_vertex,
+            )),
+            _ => None,
+        };
+
+        let vertices = make_vertices(
+            &vec![0; vertex_count],
+            &multi.positions.attribute.data,
+            None,
+            None,
+            None,
+            bones,
+        );
 
-        let mut single_indexed_vertex_attributes = SingleIndexedVertexAttributes {
-            indices: expanded_pos_indices,
-            vertices: make_vertices(expanded_material_index,expanded_positions,  normals, uvs, tangents, bones),
+        let kind = self.kind();
+        let indices = match kind {
+            MeshKind::Edges => self
+                .edges
+                .iter()
+                .flat_map(|edge| edge.iter().map(|position| *position as u16))
+                .collect(),
+            _ => (0..vertex_count).map(|vertex| vertex as u16).collect(),
         };
 
-        let indices = self.triangulate(&single_indexed_vertex_attributes.indices);
-        single_indexed_vertex_attributes.indices = indices;
+        // Face and corner attributes don't have any values without faces
+        let custom_attributes = self
+            .custom_attributes
+            .iter()
+            .filter(|(_, attribute)| attribute.domain == AttributeDomain::Point)
+            .map(|(name, attribute)| (name.clone(), attribute.clone()))
+            .collect();
+
+        // Every vertex gets the same attributes and edges point to existing positions, so this
+        // can't fail.
+        let mut single =
+            SingleIndexedVertexAttributes::new_with_kind(kind, indices, vertices).unwrap();
+        single.custom_attributes = custom_attributes;
+
+        Ok(single)
+    }
 
-        single_indexed_vertex_attributes
+    /// Give every vertex the custom attribute values of the corners that use it.
+    ///
+    /// Corners only share a vertex if they have the same values, so we can use any of them.
+    fn combine_custom_attributes(
+        &self,
+        corners: &[Corner],
+        expanded_pos_indices: &[u16],
+        vertex_count: usize,
+    ) -> HashMap<String, CustomAttribute> {
+        // Vertices that no corner uses fall back to the data for their position and the first
+        // corner, since all of their other data is unused too.
+        let mut vertex_corners: Vec<Option<Corner>> = vec![None; vertex_count];
+        for (corner, vertex) in corners.iter().zip(expanded_pos_indices.iter()) {
+            vertex_corners[*vertex as usize].get_or_insert(*corner);
+        }
+        let vertex_corners: Vec<Corner> = vertex_corners
+            .into_iter()
+            .enumerate()
+            .map(|(vertex, corner)| {
+                corner.unwrap_or(Corner {
+                    position: vertex as u32,
+                    corner: 0,
+                    face: 0,
+                })
+            })
+            .collect();
+
+        self.custom_attributes
+            .iter()
+            .map(|(name, attribute)| {
+                let indices: Vec<u32> = vertex_corners
+                    .iter()
+                    .map(|corner| attribute.value_index(corner))
+                    .collect();
+
+                (
+                    name.clone(),
+                    CustomAttribute::new(AttributeDomain::Point, attribute.data.gather(&indices)),
+                )
+            })
+            .collect()
     }
 
     // TODO: Way too many parameters - just working on splitting things up into smaller functions..
@@ -246,31 +450,32 @@ impl BlenderMesh {
         face_tangents: &Option<Vec<f32>>,
         encountered_vert_data: &mut EncounteredIndexCombinations,
         expanded_pos_indices: &mut Vec<u16>,
-        start_vert_id: u16,
+        start_vert_id: u32,
         elem_array_index: usize,
         expanded_positions: &mut Vec<f32>,
         expanded_material_index: &mut Vec<u16>,
         expanded_normals: &mut Vec<f32>,
         expanded_uvs: &mut Vec<f32>,
         expanded_tangents: &mut Vec<f32>,
-        normal_index: Option<u16>,
-        uv_index: Option<u16>,
+        normal_index: Option<u32>,
+        uv_index: Option<u32>,
+        custom_attribute_id: CustomAttributesId,
         face_idx: usize,
     ) {
         let multi = &self.multi_indexed_vertex_attributes;
 
-        expanded_pos_indices[elem_array_index] = start_vert_id;
+        expanded_pos_indices[elem_array_index] = start_vert_id as u16;
 
-        let start_vert_id = start_vert_id as usize;
+        let vert_idx = start_vert_id as usize;
 
         // TODO: Six methods to get and set the normal, pos, and uv for a vertex_num
-        if let &[x, y, z] = multi.positions.attribute.data_at_idx(start_vert_id as u16) {
-            expanded_positions[start_vert_id * 3] = x;
-            expanded_positions[start_vert_id * 3 + 1] = y;
-            expanded_positions[start_vert_id * 3 + 2] = z;
+        if let &[x, y, z] = multi.positions.attribute.data_at_idx(start_vert_id) {
+            expanded_positions[vert_idx * 3] = x;
+            expanded_positions[vert_idx * 3 + 1] = y;
+            expanded_positions[vert_idx * 3 + 2] = z;
         }
 
-        expanded_material_index[start_vert_id] = multi.material_index[face_idx];
+        expanded_material_index[vert_idx] = multi.material_index[face_idx];
 
         if let Some(normal_index) = normal_index {
             if let &[x, y, z] = multi
@@ -280,40 +485,41 @@ impl BlenderMesh {
                 .attribute
                 .data_at_idx(normal_index)
             {
-                expanded_normals[start_vert_id * 3] = x;
-                expanded_normals[start_vert_id * 3 + 1] = y;
-                expanded_normals[start_vert_id * 3 + 2] = z;
+                expanded_normals[vert_idx * 3] = x;
+                expanded_normals[vert_idx * 3 + 1] = y;
+                expanded_normals[vert_idx * 3 + 2] = z;
             }
         }
 
         if let Some(uv_index) = uv_index {
             if let &[u, v] = multi.uvs.as_ref().unwrap().attribute.data_at_idx(uv_index) {
-                expanded_uvs[start_vert_id * 2] = u;
-                expanded_uvs[start_vert_id * 2 + 1] = v;
+                expanded_uvs[vert_idx * 2] = u;
+                expanded_uvs[vert_idx * 2 + 1] = v;
             }
         }
 
         if let Some(face_tangents) = face_tangents {
             if face_tangents.len() > 0 {
                 let (x, y, z) = face_tangent_at_idx(&face_tangents, face_idx);
-                expanded_tangents[start_vert_id * 3] = x;
-                expanded_tangents[start_vert_id * 3 + 1] = y;
-                expanded_tangents[start_vert_id * 3 + 2] = z;
+                expanded_tangents[vert_idx * 3] = x;
+                expanded_tangents[vert_idx * 3 + 1] = y;
+                expanded_tangents[vert_idx * 3 + 2] = z;
             }
         }
 
-        let start_vert_id = start_vert_id as u16;
-
-        encountered_vert_data.insert((start_vert_id, normal_index, uv_index), start_vert_id);
+        encountered_vert_data.insert(
+            (start_vert_id, normal_index, uv_index, custom_attribute_id),
+            start_vert_id as u16,
+        );
     }
 
     // TODO: Way too many parameters - just working on splitting things up into smaller functions..
     fn push_generated_vertex_data(
         &self,
-        pos_idx: u16,
-        normal_idx: Option<u16>,
+        pos_idx: u32,
+        normal_idx: Option<u32>,
         face_tangents: &Option<Vec<f32>>,
-        uv_idx: Option<u16>,
+        uv_idx: Option<u32>,
         bone_influences_per_vertex: Option<u8>,
         new_group_indices: Option<&mut Vec<u8>>,
         new_group_weights: Option<&mut Vec<f32>>,
@@ -399,16 +605,18 @@ impl BlenderMesh {
     }
 }
 
-type PosIndex = u16;
-type NormalIndex = Option<u16>;
-type UvIndex = Option<u16>;
+type PosIndex = u32;
+type NormalIndex = Option<u32>;
+type UvIndex = Option<u32>;
+type CustomAttributesId = u32;
+type SingleIndex = u16;
 #[derive(Debug, Default)]
 struct EncounteredIndexCombinations {
-    encountered: HashMap<(PosIndex, NormalIndex, UvIndex), PosIndex>,
+    encountered: HashMap<(PosIndex, NormalIndex, UvIndex, CustomAttributesId), SingleIndex>,
 }
 
 impl Deref for EncounteredIndexCombinations {
-    type Target = HashMap<(PosIndex, NormalIndex, UvIndex), PosIndex>;
+    type Target = HashMap<(PosIndex, NormalIndex, UvIndex, CustomAttributesId), SingleIndex>;
 
     fn deref(&self) -> &Self::Target {
         &self.encountered
@@ -423,14 +631,14 @@ impl DerefMut for EncounteredIndexCombinations {
 
 // TODO: We're just throwing things around as we work to refactor this crate ...
 fn make_vertices(
-    material_index: Vec<u16>,
-    vertex_positions: Vec<f32>,
-    vertex_normals: Option<Vec<f32>>,
-    vertex_uvs: Option<Vec<f32>>,
-    tangents: Option<Vec<f32>>,
+    material_index: &[u16],
+    vertex_positions: &[f32],
+    vertex_normals: Option<&[f32]>,
+    vertex_uvs: Option<&[f32]>,
+    tangents: Option<&[f32]>,
     bones: Option<(BoneAttributes, u8)>,
 ) -> Vec<Vertex> {
-    let mut vertices = vec![];
+    let mut vertices = Vec::with_capacity(vertex_positions.len() / 3);
     for idx in 0..vertex_positions.len() / 3 {
         let position = [
             vertex_positions[idx * 3],
@@ -580,6 +788,34 @@ pub mod tests {
         .test();
     }
 
+    /// Verify that we return an error instead of wrapping around when there are more vertices
+    /// than fit in a u16 index.
+    #[test]
+    fn too_many_vertices() {
+        let mut mesh_to_combine = BlenderMesh {
+            name: "Terrain".to_string(),
+            multi_indexed_vertex_attributes: TodoDeleteMeMultiConverter {
+                vertex_positions: vec![0.; 70_001 * 3],
+                vertex_normals: v(10),
+                num_vertices_in_each_face: vec![3],
+                material_index: vec![0],
+                vertex_position_indices: vec![70_000, 1, 0],
+                vertex_normal_indices: vec![0, 0, 0],
+                ..TodoDeleteMeMultiConverter::default()
+            }
+            .into(),
+            ..BlenderMesh::default()
+        };
+
+        assert_eq!(
+            mesh_to_combine.try_combine_vertex_indices(&CreateSingleIndexConfig::default()),
+            Err(CombineIndicesError::TooManyVertices {
+                mesh: "Terrain".to_string(),
+                vertex_count: 70_001
+            })
+        );
+    }
+
     // We create a mesh that might have been triangulated before it was exported from Blender.
     // Before this test we weren't combining our normals properly after using the `triangulate`
     // modifier in Blender.
@@ -841,12 +1077,12 @@ pub mod tests {
     #[derive(Default)]
     pub struct TodoDeleteMeMultiConverter {
         pub vertex_positions: Vec<f32>,
-        pub vertex_position_indices: Vec<u16>,
+        pub vertex_position_indices: Vec<u32>,
         pub num_vertices_in_each_face: Vec<u8>,
         pub material_index: Vec<u16>,
         pub vertex_normals: Vec<f32>,
-        pub vertex_normal_indices: Vec<u16>,
-        pub vertex_uv_indices: Option<Vec<u16>>,
+        pub vertex_normal_indices: Vec<u32>,
+        pub vertex_uv_indices: Option<Vec<u32>>,
         pub vertex_uvs: Option<Vec<f32>>,
         pub(crate) bone_influences_per_vertex: Option<BoneInfluencesPerVertex>,
         // Config.bone_influences_per_vertex = 3
@@ -889,6 +1125,7 @@ pub mod tests {
                 },
                 normals,
                 uvs,
+                lightmap_uvs: None,
                 bone_influences: parent_armature_bone_influences,
             }
         }
@@ -930,15 +1167,18 @@ pub mod tests {
             };
 
             SingleIndexedVertexAttributes {
+                kind: MeshKind::Triangles,
                 indices: self.vertex_position_indices,
                 vertices: make_vertices(
-                    self.material_index,
-                    self.vertex_positions,
-                    Some(self.vertex_normals),
-                    self.vertex_uvs,
-                    self.tangents,
+                    &self.material_index,
+                    &self.vertex_positions,
+                    Some(&self.vertex_normals),
+                    self.vertex_uvs.as_deref(),
+                    self.tangents.as_deref(),
                     bones,
                 ),
+                custom_attributes: HashMap::new(),
+                meshlets: vec![],
             }
         }
     }
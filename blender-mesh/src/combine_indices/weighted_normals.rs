@@ -25,6 +25,10 @@ impl SingleIndexedVertexAttributes {
     /// TODO: When we combine normals we'll end up with a lot of vertices that have the same data
     ///  so we should dedupe the vertices / indices
     pub fn face_weight_normals(&mut self) -> Result<(), WeightedNormalsError> {
+        if !self.present_attributes().normals {
+            return Err(WeightedNormalsError::NoNormals);
+        }
+
         let mut encountered_positions: HashMap<[u32; 3], SharedVertexPositionWeightedNormal> =
             HashMap::new();
 
@@ -216,6 +220,18 @@ mod tests {
         assert_eq!(face_weighted_normals, normals);
     }
 
+    /// Verify that we return an error instead of panicking when there are no normals.
+    #[test]
+    fn no_normals_to_weight() {
+        let mut single_indexed =
+            SingleIndexedVertexAttributes::new(vec![0, 0, 0], vec![Vertex::default()]).unwrap();
+
+        match single_indexed.face_weight_normals() {
+            Err(WeightedNormalsError::NoNormals) => {}
+            _ => unreachable!(),
+        };
+    }
+
     /// We repeat position index 0 twice - meaning that there are two vertices that share
     /// a position.
     /// The corresponding normals should be blended
@@ -1 +1,5 @@
+pub use self::terrain::{TerrainConfig, TerrainError};
+
 mod pbr_cube_without_textures;
+mod primitives;
+mod terrain;
@@ -3,7 +3,7 @@ use crate::{
     BlenderMesh, BoundingBox, MaterialInput, MultiIndexedVertexAttributes, PrincipledBSDF,
     VertexAttribute,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use crate::CustomProperty::Vec;
 
 impl BlenderMesh {
@@ -18,6 +18,8 @@ impl BlenderMesh {
                 roughness: MaterialInput::Uniform(0.2),
                 metallic: MaterialInput::Uniform(0.3),
                 normal_map: None,
+                uv_transforms: BTreeMap::new(),
+                color_spaces: BTreeMap::new(),
             },
         ];
 
@@ -50,12 +52,17 @@ impl BlenderMesh {
                 .unwrap(),
             )),
             uvs: None,
+            lightmap_uvs: None,
             bone_influences: None,
         };
 
         Self {
             name: "CubeWithoutTextures".to_string(),
-            armature_name: None,
+            data_name: "CubeWithoutTextures".to_string(),
+            library: None,
+            stable_id: None,
+            thumbnail: None,
+            armatures: vec![],
             bounding_box: BoundingBox {
                 min_corner: [-1.; 3].into(),
                 max_corner: [1.; 3].into(),
@@ -63,6 +70,17 @@ impl BlenderMesh {
             multi_indexed_vertex_attributes,
             materials,
             custom_properties: Default::default(),
+            smoothing_groups: vec![],
+            vertex_groups: Default::default(),
+            face_maps: Default::default(),
+            custom_attributes: Default::default(),
+            shape_keys: vec![],
+            edges: vec![],
+            collections: vec![],
+            visibility: Default::default(),
+            space: Default::default(),
+            world_transform: None,
+            bvh: Default::default(),
         }
     }
 }
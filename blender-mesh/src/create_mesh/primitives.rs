@@ -0,0 +1,553 @@
+use crate::vertex_attributes::IndexedAttribute;
+use crate::{BlenderMesh, BoundingBox, MultiIndexedVertexAttributes, VertexAttribute};
+use nalgebra::{Matrix4, Point3, Vector3};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+impl BlenderMesh {
+    /// A cube centered about the origin with sides of length `size`.
+    ///
+    /// Every face gets a flat normal and its own [0, 1] uv square.
+    pub fn cube(size: f32) -> Self {
+        let half = size / 2.;
+        let mut builder = PrimitiveBuilder::default();
+
+        // Position index = x_bit + 2 * y_bit + 4 * z_bit
+        for idx in 0..8 {
+            let coord = |bit: u8| if idx & bit == 0 { -half } else { half };
+            builder.push_position([coord(1), coord(2), coord(4)]);
+        }
+
+        let uvs = [
+            builder.push_uv([0., 0.]),
+            builder.push_uv([1., 0.]),
+            builder.push_uv([1., 1.]),
+            builder.push_uv([0., 1.]),
+        ];
+
+        // (normal, u axis, v axis) where u cross v = normal so that corners are counter clockwise
+        let faces: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+            ([1., 0., 0.], [0., 1., 0.], [0., 0., 1.]),
+            ([-1., 0., 0.], [0., -1., 0.], [0., 0., 1.]),
+            ([0., 1., 0.], [-1., 0., 0.], [0., 0., 1.]),
+            ([0., -1., 0.], [1., 0., 0.], [0., 0., 1.]),
+            ([0., 0., 1.], [1., 0., 0.], [0., 1., 0.]),
+            ([0., 0., -1.], [-1., 0., 0.], [0., 1., 0.]),
+        ];
+
+        for (normal, u_axis, v_axis) in faces.iter() {
+            let normal_idx = builder.push_normal(*normal);
+
+            let mut corners = vec![];
+            for (corner, (su, sv)) in [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)]
+                .iter()
+                .enumerate()
+            {
+                let mut position_idx = 0;
+                for axis in 0..3 {
+                    if normal[axis] + su * u_axis[axis] + sv * v_axis[axis] > 0. {
+                        position_idx += 1 << axis;
+                    }
+                }
+
+                corners.push((position_idx, normal_idx, uvs[corner]));
+            }
+
+            builder.push_face(&corners);
+        }
+
+        builder.build("Cube")
+    }
+
+    /// A flat plane on the XY axis centered about the origin, facing +Z.
+    ///
+    /// Each side gets cut `subdivisions` times, so `0` subdivisions is a single quad and `1`
+    /// subdivision is four quads.
+    ///
+    /// The uvs stretch from 0.0 to 1.0 across the entire plane.
+    pub fn plane(width: f32, height: f32, subdivisions: u16) -> Self {
+        let segments = u32::from(subdivisions) + 1;
+        let mut builder = PrimitiveBuilder::default();
+
+        let normal_idx = builder.push_normal([0., 0., 1.]);
+
+        for row in 0..=segments {
+            for col in 0..=segments {
+                let u = col as f32 / segments as f32;
+                let v = row as f32 / segments as f32;
+
+                builder.push_position([(u - 0.5) * width, (v - 0.5) * height, 0.]);
+                builder.push_uv([u, v]);
+            }
+        }
+
+        let vertex = |row: u32, col: u32| {
+            let idx = row * (segments + 1) + col;
+            (idx, normal_idx, idx)
+        };
+
+        for row in 0..segments {
+            for col in 0..segments {
+                builder.push_face(&[
+                    vertex(row, col),
+                    vertex(row, col + 1),
+                    vertex(row + 1, col + 1),
+                    vertex(row + 1, col),
+                ]);
+            }
+        }
+
+        builder.build("Plane")
+    }
+
+    /// A smooth shaded sphere centered about the origin with Z as its up axis.
+    ///
+    /// `segments` are the vertical cuts around the sphere and `rings` are the horizontal cuts.
+    /// The faces touching the poles are triangles and the rest are quads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer than 3 segments or fewer than 2 rings.
+    pub fn uv_sphere(radius: f32, segments: u16, rings: u16) -> Self {
+        assert!(segments >= 3, "A uv sphere needs at least 3 segments");
+        assert!(rings >= 2, "A uv sphere needs at least 2 rings");
+        let (segments, rings) = (u32::from(segments), u32::from(rings));
+
+        let mut builder = PrimitiveBuilder::default();
+
+        let push_vertex = |builder: &mut PrimitiveBuilder, position: [f32; 3]| {
+            let normal = Vector3::from(position).normalize();
+            builder.push_normal([normal.x, normal.y, normal.z]);
+            builder.push_position(position)
+        };
+
+        let top = push_vertex(&mut builder, [0., 0., radius]);
+        for ring in 1..rings {
+            let phi = PI * ring as f32 / rings as f32;
+
+            for segment in 0..segments {
+                let theta = 2. * PI * segment as f32 / segments as f32;
+
+                push_vertex(
+                    &mut builder,
+                    [
+                        radius * phi.sin() * theta.cos(),
+                        radius * phi.sin() * theta.sin(),
+                        radius * phi.cos(),
+                    ],
+                );
+            }
+        }
+        let bottom = push_vertex(&mut builder, [0., 0., -radius]);
+
+        // Uvs need an extra column since the first and last column meet at the seam
+        for ring in 0..=rings {
+            for segment in 0..=segments {
+                builder.push_uv([
+                    segment as f32 / segments as f32,
+                    1. - ring as f32 / rings as f32,
+                ]);
+            }
+        }
+        let uv_idx = |ring: u32, segment: u32| ring * (segments + 1) + segment;
+
+        // Poles use the uv in the middle of their segment
+        let mut pole_uvs = vec![];
+        for v in [1., 0.].iter() {
+            let first = (builder.uvs.len() / 2) as u32;
+            for segment in 0..segments {
+                builder.push_uv([(segment as f32 + 0.5) / segments as f32, *v]);
+            }
+            pole_uvs.push(first);
+        }
+
+        let ring_vertex = |ring: u32, segment: u32| {
+            let position_idx = 1 + (ring - 1) * segments + (segment % segments);
+            (position_idx, position_idx, uv_idx(ring, segment))
+        };
+
+        for segment in 0..segments {
+            builder.push_face(&[
+                ring_vertex(1, segment),
+                ring_vertex(1, segment + 1),
+                (top, top, pole_uvs[0] + segment),
+            ]);
+        }
+
+        for ring in 1..rings - 1 {
+            for segment in 0..segments {
+                builder.push_face(&[
+                    ring_vertex(ring + 1, segment),
+                    ring_vertex(ring + 1, segment + 1),
+                    ring_vertex(ring, segment + 1),
+                    ring_vertex(ring, segment),
+                ]);
+            }
+        }
+
+        for segment in 0..segments {
+            builder.push_face(&[
+                (bottom, bottom, pole_uvs[1] + segment),
+                ring_vertex(rings - 1, segment + 1),
+                ring_vertex(rings - 1, segment),
+            ]);
+        }
+
+        builder.build("Sphere")
+    }
+
+    /// A cylinder centered about the origin that stretches along the Z axis.
+    ///
+    /// `vertices` is the number of vertices in each of the two circles. The sides are smooth
+    /// shaded and the caps are flat triangle fans.
+    ///
+    /// The side wraps around the bottom half of the uv space and the caps are circles in the top
+    /// half.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer than 3 vertices.
+    pub fn cylinder(radius: f32, depth: f32, vertices: u16) -> Self {
+        assert!(vertices >= 3, "A cylinder needs at least 3 vertices");
+        let vertices = u32::from(vertices);
+
+        let mut builder = PrimitiveBuilder::default();
+        let half_depth = depth / 2.;
+
+        for z in [-half_depth, half_depth].iter() {
+            for idx in 0..vertices {
+                let theta = 2. * PI * idx as f32 / vertices as f32;
+                builder.push_position([radius * theta.cos(), radius * theta.sin(), *z]);
+            }
+        }
+        let bottom_center = builder.push_position([0., 0., -half_depth]);
+        let top_center = builder.push_position([0., 0., half_depth]);
+
+        for idx in 0..vertices {
+            let theta = 2. * PI * idx as f32 / vertices as f32;
+            builder.push_normal([theta.cos(), theta.sin(), 0.]);
+        }
+        let down = builder.push_normal([0., 0., -1.]);
+        let up = builder.push_normal([0., 0., 1.]);
+
+        for v in [0., 0.5].iter() {
+            for idx in 0..=vertices {
+                builder.push_uv([idx as f32 / vertices as f32, *v]);
+            }
+        }
+
+        let mut cap_uvs = vec![];
+        for center_u in [0.75, 0.25].iter() {
+            let first = (builder.uvs.len() / 2) as u32;
+            for idx in 0..vertices {
+                let theta = 2. * PI * idx as f32 / vertices as f32;
+                builder.push_uv([center_u + 0.25 * theta.cos(), 0.75 + 0.25 * theta.sin()]);
+            }
+            builder.push_uv([*center_u, 0.75]);
+            cap_uvs.push(first);
+        }
+
+        for idx in 0..vertices {
+            let next = (idx + 1) % vertices;
+
+            builder.push_face(&[
+                (idx, idx, idx),
+                (next, next, idx + 1),
+                (vertices + next, next, vertices + 1 + idx + 1),
+                (vertices + idx, idx, vertices + 1 + idx),
+            ]);
+        }
+
+        let (bottom_uvs, top_uvs) = (cap_uvs[0], cap_uvs[1]);
+        for idx in 0..vertices {
+            let next = (idx + 1) % vertices;
+
+            builder.push_face(&[
+                (top_center, up, top_uvs + vertices),
+                (vertices + idx, up, top_uvs + idx),
+                (vertices + next, up, top_uvs + next),
+            ]);
+
+            builder.push_face(&[
+                (bottom_center, down, bottom_uvs + vertices),
+                (next, down, bottom_uvs + next),
+                (idx, down, bottom_uvs + idx),
+            ]);
+        }
+
+        builder.build("Cylinder")
+    }
+
+    /// An octahedron for every bone, in the shape that Blender draws bones in, for rendering
+    /// armatures while debugging.
+    ///
+    /// Every bone is its name, its transform and its length. A bone points along the Y axis of
+    /// its transform, from its head at the transform's origin to its tail `length` away.
+    ///
+    /// Every octahedron has 6 positions that are in a vertex group named after its bone.
+    /// Position `6 * i` is the head of the `i`th bone and position `6 * i + 5` is its tail.
+    pub fn bone_octahedrons(bones: &[(String, Matrix4<f32>, f32)]) -> Self {
+        let mut builder = PrimitiveBuilder::default();
+        let uv = builder.push_uv([0., 0.]);
+        let mut vertex_groups = HashMap::new();
+
+        for (name, transform, length) in bones.iter() {
+            // The widest part of the bone is a tenth of the way from its head to its tail
+            let width = 0.1 * length;
+            let shape = [
+                [0., 0., 0.],
+                [width, width, 0.],
+                [0., width, width],
+                [-width, width, 0.],
+                [0., width, -width],
+                [0., *length, 0.],
+            ];
+
+            let mut positions = vec![];
+            for [x, y, z] in shape.iter() {
+                let position = transform.transform_point(&Point3::new(*x, *y, *z));
+                positions.push((builder.push_position(position.into()), position));
+            }
+            let (head, tail) = (positions[0], positions[5]);
+
+            for idx in 0..4 {
+                let (current, next) = (positions[1 + idx], positions[1 + (idx + 1) % 4]);
+
+                for (a, b, c) in [(head, current, next), (tail, next, current)].iter() {
+                    let normal = (b.1 - a.1).cross(&(c.1 - a.1)).normalize();
+                    let normal = builder.push_normal(normal.into());
+
+                    builder.push_face(&[(a.0, normal, uv), (b.0, normal, uv), (c.0, normal, uv)]);
+                }
+            }
+
+            vertex_groups.insert(
+                name.clone(),
+                positions.iter().map(|(idx, _)| *idx).collect(),
+            );
+        }
+
+        let mut mesh = builder.build("Bones");
+        mesh.vertex_groups = vertex_groups;
+
+        mesh
+    }
+}
+
+/// (position index, normal index, uv index)
+pub(super) type Corner = (u32, u32, u32);
+
+/// Accumulates the multi indexed data for a procedurally generated mesh.
+#[derive(Debug, Default)]
+pub(super) struct PrimitiveBuilder {
+    vertices_in_each_face: Vec<u8>,
+    positions: Vec<f32>,
+    position_indices: Vec<u32>,
+    normals: Vec<f32>,
+    normal_indices: Vec<u32>,
+    uvs: Vec<f32>,
+    uv_indices: Vec<u32>,
+}
+
+impl PrimitiveBuilder {
+    pub(super) fn push_position(&mut self, position: [f32; 3]) -> u32 {
+        self.positions.extend_from_slice(&position);
+        (self.positions.len() / 3 - 1) as u32
+    }
+
+    pub(super) fn push_normal(&mut self, normal: [f32; 3]) -> u32 {
+        self.normals.extend_from_slice(&normal);
+        (self.normals.len() / 3 - 1) as u32
+    }
+
+    pub(super) fn push_uv(&mut self, uv: [f32; 2]) -> u32 {
+        self.uvs.extend_from_slice(&uv);
+        (self.uvs.len() / 2 - 1) as u32
+    }
+
+    /// Corners should be in counter clockwise order when looking at the front of the face.
+    pub(super) fn push_face(&mut self, corners: &[Corner]) {
+        self.vertices_in_each_face.push(corners.len() as u8);
+
+        for (position_idx, normal_idx, uv_idx) in corners {
+            self.position_indices.push(*position_idx);
+            self.normal_indices.push(*normal_idx);
+            self.uv_indices.push(*uv_idx);
+        }
+    }
+
+    pub(super) fn build(self, name: &str) -> BlenderMesh {
+        let mut min_corner = Point3::new(std::f32::MAX, std::f32::MAX, std::f32::MAX);
+        let mut max_corner = Point3::new(std::f32::MIN, std::f32::MIN, std::f32::MIN);
+
+        for position in self.positions.chunks(3) {
+            for axis in 0..3 {
+                min_corner[axis] = min_corner[axis].min(position[axis]);
+                max_corner[axis] = max_corner[axis].max(position[axis]);
+            }
+        }
+
+        let face_count = self.vertices_in_each_face.len();
+
+        BlenderMesh {
+            name: name.to_string(),
+            bounding_box: BoundingBox {
+                min_corner,
+                max_corner,
+            },
+            multi_indexed_vertex_attributes: MultiIndexedVertexAttributes {
+                vertices_in_each_face: self.vertices_in_each_face,
+                material_index: vec![0; face_count],
+                positions: IndexedAttribute::new(
+                    self.position_indices,
+                    VertexAttribute::new(self.positions, 3).unwrap(),
+                ),
+                normals: Some(IndexedAttribute::new(
+                    self.normal_indices,
+                    VertexAttribute::new(self.normals, 3).unwrap(),
+                )),
+                uvs: Some(IndexedAttribute::new(
+                    self.uv_indices,
+                    VertexAttribute::new(self.uvs, 2).unwrap(),
+                )),
+                lightmap_uvs: None,
+                bone_influences: None,
+            },
+            ..BlenderMesh::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CreateSingleIndexConfig;
+
+    /// Verify that every face winds counter clockwise around its normals and that every index
+    /// points at data that exists.
+    fn assert_valid(mesh: &BlenderMesh, expected_face_count: usize) {
+        let multi = &mesh.multi_indexed_vertex_attributes;
+        let positions = &multi.positions;
+        let normals = multi.normals.as_ref().unwrap();
+        let uvs = multi.uvs.as_ref().unwrap();
+
+        assert_eq!(multi.vertices_in_each_face.len(), expected_face_count);
+        assert_eq!(multi.material_index.len(), expected_face_count);
+
+        let corner_count: usize = multi
+            .vertices_in_each_face
+            .iter()
+            .map(|c| *c as usize)
+            .sum();
+        assert_eq!(positions.indices.len(), corner_count);
+        assert_eq!(normals.indices.len(), corner_count);
+        assert_eq!(uvs.indices.len(), corner_count);
+        assert!(uvs
+            .indices
+            .iter()
+            .all(|idx| (*idx as usize) < uvs.attribute.len() / 2));
+
+        let mut start = 0;
+        for vertices_in_face in multi.vertices_in_each_face.iter() {
+            let position = |corner: usize| {
+                let data = positions
+                    .attribute
+                    .data_at_idx(positions.indices[start + corner]);
+                Vector3::new(data[0], data[1], data[2])
+            };
+
+            let face_normal = (position(1) - position(0)).cross(&(position(2) - position(0)));
+
+            let normal = normals.attribute.data_at_idx(normals.indices[start]);
+            let normal = Vector3::new(normal[0], normal[1], normal[2]);
+
+            assert!(face_normal.dot(&normal) > 0., "Face starting at {}", start);
+
+            start += *vertices_in_face as usize;
+        }
+
+        mesh.clone()
+            .combine_vertex_indices(&CreateSingleIndexConfig::default());
+    }
+
+    #[test]
+    fn cube() {
+        let cube = BlenderMesh::cube(2.);
+
+        assert_valid(&cube, 6);
+        assert_eq!(cube.bounding_box().min_corner, Point3::new(-1., -1., -1.));
+        assert_eq!(cube.bounding_box().max_corner, Point3::new(1., 1., 1.));
+    }
+
+    #[test]
+    fn plane() {
+        let plane = BlenderMesh::plane(4., 2., 2);
+
+        assert_valid(&plane, 9);
+        assert_eq!(plane.bounding_box().min_corner, Point3::new(-2., -1., 0.));
+        assert_eq!(plane.bounding_box().max_corner, Point3::new(2., 1., 0.));
+    }
+
+    /// Verify that planes with more vertices than fit in a u16 index point at the right data.
+    #[test]
+    fn plane_with_more_than_u16_vertices() {
+        let plane = BlenderMesh::plane(1., 1., 255);
+        let multi = &plane.multi_indexed_vertex_attributes;
+
+        // 256 quads on each side with 257 vertices on each side
+        assert_eq!(multi.vertices_in_each_face.len(), 256 * 256);
+        assert!(multi.positions.attribute.len() / 3 > usize::from(u16::MAX) + 1);
+        assert_eq!(multi.positions.attribute.len() / 3, 257 * 257);
+
+        let last_face = &multi.positions.indices[multi.positions.indices.len() - 4..];
+        assert_eq!(last_face, &[65790, 65791, 66048, 66047]);
+        assert_eq!(
+            multi.positions.attribute.data_at_idx(66048),
+            &[0.5, 0.5, 0.]
+        );
+        assert_eq!(multi.uvs.as_ref().unwrap().indices, multi.positions.indices);
+    }
+
+    #[test]
+    fn uv_sphere() {
+        let sphere = BlenderMesh::uv_sphere(1., 8, 4);
+
+        // Two rings of triangles at the poles and two rings of quads in between
+        assert_valid(&sphere, 8 * 4);
+        assert_eq!(
+            sphere
+                .multi_indexed_vertex_attributes
+                .positions
+                .attribute
+                .len()
+                / 3,
+            2 + 8 * 3
+        );
+    }
+
+    #[test]
+    fn cylinder() {
+        let cylinder = BlenderMesh::cylinder(1., 2., 6);
+
+        // The sides and two triangle fans
+        assert_valid(&cylinder, 6 * 3);
+        assert_eq!(cylinder.bounding_box().min_corner.z, -1.);
+        assert_eq!(cylinder.bounding_box().max_corner.z, 1.);
+    }
+
+    #[test]
+    fn bone_octahedrons() {
+        let bones = BlenderMesh::bone_octahedrons(&[
+            ("Root".to_string(), Matrix4::identity(), 1.),
+            (
+                "Child".to_string(),
+                Matrix4::new_translation(&Vector3::new(0., 1., 0.)),
+                2.,
+            ),
+        ]);
+
+        assert_valid(&bones, 8 * 2);
+        assert_eq!(bones.vertex_groups()["Child"], vec![6, 7, 8, 9, 10, 11]);
+        assert_eq!(bones.bounding_box().max_corner.y, 3.);
+    }
+}
@@ -0,0 +1,246 @@
+use super::primitives::PrimitiveBuilder;
+use crate::BlenderMesh;
+use nalgebra::Vector3;
+
+/// Describes how to turn a heightmap into a terrain mesh.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TerrainConfig {
+    /// The distance along the X and Y axis between two neighboring heightmap samples.
+    pub cell_size: f32,
+    /// Every height gets multiplied by this before becoming a Z coordinate.
+    pub height_scale: f32,
+    /// How many cells one repetition of a texture spans.
+    ///
+    /// With `1.0` the uvs go from 0.0 to 1.0 across every cell, with `4.0` they go from 0.0 to
+    /// 1.0 across every 4 cells. Use a repeating texture wrap mode when sampling.
+    pub cells_per_uv_tile: f32,
+}
+
+impl Default for TerrainConfig {
+    fn default() -> Self {
+        TerrainConfig {
+            cell_size: 1.0,
+            height_scale: 1.0,
+            cells_per_uv_tile: 1.0,
+        }
+    }
+}
+
+/// An error while creating a terrain mesh from a heightmap.
+#[derive(Debug, thiserror::Error)]
+pub enum TerrainError {
+    /// A terrain needs at least one quad.
+    #[error("A heightmap needs at least 2x2 samples, but it was {width}x{depth}")]
+    TooSmall { width: usize, depth: usize },
+    /// Every row of the heightmap needs to have `width` samples.
+    #[error("The heightmap has {len} samples which is not a multiple of its width {width}")]
+    NotRectangular { len: usize, width: usize },
+}
+
+impl BlenderMesh {
+    /// Create a terrain grid from a heightmap.
+    ///
+    /// The heights are stored row by row, so the height at some column and row is found at
+    /// `heights[row * width + col]`. Columns go along the +X axis, rows go along the +Y axis and
+    /// heights become the Z coordinate. The first sample sits at the origin.
+    ///
+    /// Normals are smoothed across neighboring samples.
+    ///
+    /// ```
+    /// # use blender_mesh::{BlenderMesh, TerrainConfig};
+    /// let heights = vec![
+    ///     0.0, 0.0, 0.0,
+    ///     0.0, 1.0, 0.0,
+    ///     0.0, 0.0, 0.0,
+    /// ];
+    ///
+    /// let terrain = BlenderMesh::terrain_from_heightmap(&heights, 3, &TerrainConfig::default()).unwrap();
+    /// ```
+    pub fn terrain_from_heightmap(
+        heights: &[f32],
+        width: usize,
+        config: &TerrainConfig,
+    ) -> Result<BlenderMesh, TerrainError> {
+        if width == 0 || heights.len() % width != 0 {
+            return Err(TerrainError::NotRectangular {
+                len: heights.len(),
+                width,
+            });
+        }
+
+        let depth = heights.len() / width;
+
+        if width < 2 || depth < 2 {
+            return Err(TerrainError::TooSmall { width, depth });
+        }
+
+        let height_at = |col: usize, row: usize| heights[row * width + col] * config.height_scale;
+
+        let mut builder = PrimitiveBuilder::default();
+
+        for row in 0..depth {
+            for col in 0..width {
+                builder.push_position([
+                    col as f32 * config.cell_size,
+                    row as f32 * config.cell_size,
+                    height_at(col, row),
+                ]);
+
+                // Central differences, falling back to one sided differences along the edges
+                let (left, right) = (col.saturating_sub(1), (col + 1).min(width - 1));
+                let (down, up) = (row.saturating_sub(1), (row + 1).min(depth - 1));
+
+                let dz_dx = (height_at(right, row) - height_at(left, row))
+                    / ((right - left) as f32 * config.cell_size);
+                let dz_dy = (height_at(col, up) - height_at(col, down))
+                    / ((up - down) as f32 * config.cell_size);
+
+                let normal = Vector3::new(-dz_dx, -dz_dy, 1.0).normalize();
+                builder.push_normal([normal.x, normal.y, normal.z]);
+
+                builder.push_uv([
+                    col as f32 / config.cells_per_uv_tile,
+                    row as f32 / config.cells_per_uv_tile,
+                ]);
+            }
+        }
+
+        let vertex = |col: usize, row: usize| {
+            let idx = (row * width + col) as u32;
+            (idx, idx, idx)
+        };
+
+        for row in 0..depth - 1 {
+            for col in 0..width - 1 {
+                builder.push_face(&[
+                    vertex(col, row),
+                    vertex(col + 1, row),
+                    vertex(col + 1, row + 1),
+                    vertex(col, row + 1),
+                ]);
+            }
+        }
+
+        Ok(builder.build("Terrain"))
+    }
+
+    /// Create a terrain grid from a grayscale heightmap image.
+    ///
+    /// Black pixels have a height of 0.0 and white pixels have a height of 1.0 before the
+    /// `height_scale` is applied. The top row of the image becomes the row furthest along +Y.
+    ///
+    /// See [`BlenderMesh::terrain_from_heightmap`].
+    #[cfg(feature = "image")]
+    pub fn terrain_from_image(
+        image: &image::GrayImage,
+        config: &TerrainConfig,
+    ) -> Result<BlenderMesh, TerrainError> {
+        let (width, depth) = (image.width(), image.height());
+
+        let mut heights = Vec::with_capacity((width * depth) as usize);
+        for row in (0..depth).rev() {
+            for col in 0..width {
+                heights.push(image.get_pixel(col, row).0[0] as f32 / 255.0);
+            }
+        }
+
+        BlenderMesh::terrain_from_heightmap(&heights, width as usize, config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that a flat heightmap creates a flat grid with normals pointing up
+    #[test]
+    fn flat_terrain() {
+        let config = TerrainConfig {
+            cell_size: 2.0,
+            ..TerrainConfig::default()
+        };
+        let terrain = BlenderMesh::terrain_from_heightmap(&[0.0; 6], 3, &config).unwrap();
+
+        let multi = &terrain.multi_indexed_vertex_attributes;
+        assert_eq!(multi.vertices_in_each_face, vec![4, 4]);
+        assert_eq!(multi.positions.attribute.data_at_idx(5), &[4.0, 2.0, 0.0]);
+        assert!(multi
+            .normals
+            .as_ref()
+            .unwrap()
+            .attribute
+            .chunks(3)
+            .all(|normal| normal == [0.0, 0.0, 1.0]));
+    }
+
+    /// Verify that normals lean away from the uphill direction
+    #[test]
+    fn sloped_terrain_normals() {
+        let heights = [0.0, 1.0, 2.0, 0.0, 1.0, 2.0];
+        let terrain =
+            BlenderMesh::terrain_from_heightmap(&heights, 3, &TerrainConfig::default()).unwrap();
+
+        let normals = &terrain.multi_indexed_vertex_attributes.normals.unwrap();
+        let expected = Vector3::new(-1.0, 0.0, 1.0).normalize();
+
+        for idx in 0..6 {
+            let normal = normals.attribute.data_at_idx(idx);
+            assert_eq!(normal, &[expected.x, expected.y, expected.z]);
+        }
+    }
+
+    /// Verify that uvs repeat every `cells_per_uv_tile` cells
+    #[test]
+    fn tiled_uvs() {
+        let config = TerrainConfig {
+            cells_per_uv_tile: 2.0,
+            ..TerrainConfig::default()
+        };
+        let terrain = BlenderMesh::terrain_from_heightmap(&[0.0; 9], 3, &config).unwrap();
+
+        let uvs = terrain.multi_indexed_vertex_attributes.uvs.unwrap();
+        assert_eq!(uvs.attribute.data_at_idx(8), &[1.0, 1.0]);
+        assert_eq!(uvs.attribute.data_at_idx(1), &[0.5, 0.0]);
+    }
+
+    /// Verify that heightmaps with more samples than fit in a u16 index point at the right data
+    #[test]
+    fn large_heightmap() {
+        let width = 300;
+        let terrain = BlenderMesh::terrain_from_heightmap(
+            &vec![0.0; width * width],
+            width,
+            &TerrainConfig::default(),
+        )
+        .unwrap();
+
+        let positions = &terrain.multi_indexed_vertex_attributes.positions;
+        assert_eq!(
+            terrain
+                .multi_indexed_vertex_attributes
+                .vertices_in_each_face
+                .len(),
+            299 * 299
+        );
+
+        let last_face = &positions.indices[positions.indices.len() - 4..];
+        assert_eq!(last_face, &[89698, 89699, 89999, 89998]);
+        assert_eq!(positions.attribute.data_at_idx(89999), &[299.0, 299.0, 0.0]);
+    }
+
+    /// Verify that we error on heightmaps that cannot become a grid
+    #[test]
+    fn invalid_heightmaps() {
+        let config = TerrainConfig::default();
+
+        match BlenderMesh::terrain_from_heightmap(&[0.0; 5], 2, &config) {
+            Err(TerrainError::NotRectangular { len: 5, width: 2 }) => {}
+            _ => unreachable!(),
+        };
+
+        match BlenderMesh::terrain_from_heightmap(&[0.0; 3], 3, &config) {
+            Err(TerrainError::TooSmall { width: 3, depth: 1 }) => {}
+            _ => unreachable!(),
+        };
+    }
+}
@@ -0,0 +1,308 @@
+use crate::triangulate::triangulate_indices;
+use crate::vertex_attributes::VertexAttribute;
+use crate::MultiIndexedVertexAttributes;
+use std::collections::HashMap;
+
+/// A [named attribute][named-attribute] that was added to a mesh, such as by geometry nodes, in
+/// order to pass data to shaders.
+///
+/// [named-attribute]: https://docs.blender.org/manual/en/latest/modeling/geometry_nodes/attributes_reference.html
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CustomAttribute {
+    pub(crate) domain: AttributeDomain,
+    pub(crate) data: CustomAttributeData,
+}
+
+/// What a custom attribute has one value for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AttributeDomain {
+    /// One value per position, shared by every face that uses the position.
+    Point,
+    /// One value per face corner, in the same order as the position indices.
+    Corner,
+    /// One value per face.
+    Face,
+}
+
+/// The values of a custom attribute.
+///
+/// The attribute size is 1 for floats and ints, 2 for 2D vectors, 3 for 3D vectors and 4 for
+/// colors.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[allow(missing_docs)]
+pub enum CustomAttributeData {
+    Float(VertexAttribute<f32>),
+    Int(VertexAttribute<i32>),
+}
+
+impl CustomAttribute {
+    #[allow(missing_docs)]
+    pub fn new(domain: AttributeDomain, data: CustomAttributeData) -> Self {
+        CustomAttribute { domain, data }
+    }
+
+    /// What the attribute has one value for.
+    pub fn domain(&self) -> AttributeDomain {
+        self.domain
+    }
+
+    /// The attribute's values, in the order of the points, corners or faces that they belong to.
+    pub fn data(&self) -> &CustomAttributeData {
+        &self.data
+    }
+
+    /// Rearrange corner and face values to match the faces after they've been triangulated.
+    pub(crate) fn triangulate(&mut self, vertices_in_each_face: &[u8]) {
+        let indices: Vec<u32> = match self.domain {
+            AttributeDomain::Point => return,
+            AttributeDomain::Corner => {
+                let corner_count: u32 = vertices_in_each_face.iter().map(|v| *v as u32).sum();
+                let corners: Vec<u32> = (0..corner_count).collect();
+                triangulate_indices(vertices_in_each_face, &corners)
+            }
+            AttributeDomain::Face => {
+                let mut faces = vec![];
+                for (face, vertex_count) in vertices_in_each_face.iter().enumerate() {
+                    let triangles = if *vertex_count == 4 { 2 } else { 1 };
+                    for _ in 0..triangles {
+                        faces.push(face as u32);
+                    }
+                }
+                faces
+            }
+        };
+
+        self.data = self.data.gather(&indices);
+    }
+
+    /// Reverse the order of each face's corner values to match faces whose winding was reversed.
+    pub(crate) fn reverse_face_winding(&mut self, vertices_in_each_face: &[u8]) {
+        if self.domain != AttributeDomain::Corner {
+            return;
+        }
+
+        let mut corners = vec![];
+        let mut start = 0;
+        for vertex_count in vertices_in_each_face.iter() {
+            let end = start + *vertex_count as u32;
+            corners.extend((start..end).rev());
+            start = end;
+        }
+
+        self.data = self.data.gather(&corners);
+    }
+
+    /// The index of the value in this attribute's domain that the corner uses.
+    pub(crate) fn value_index(&self, corner: &Corner) -> u32 {
+        match self.domain {
+            AttributeDomain::Point => corner.position,
+            AttributeDomain::Corner => corner.corner,
+            AttributeDomain::Face => corner.face,
+        }
+    }
+}
+
+impl CustomAttributeData {
+    /// The number of values per point, corner or face.
+    pub fn attribute_size(&self) -> u8 {
+        match self {
+            CustomAttributeData::Float(attribute) => attribute.attribute_size(),
+            CustomAttributeData::Int(attribute) => attribute.attribute_size(),
+        }
+    }
+
+    /// The values at each of the indices, in order.
+    pub(crate) fn gather(&self, indices: &[u32]) -> CustomAttributeData {
+        match self {
+            CustomAttributeData::Float(attribute) => {
+                CustomAttributeData::Float(gather_values(attribute, indices))
+            }
+            CustomAttributeData::Int(attribute) => {
+                CustomAttributeData::Int(gather_values(attribute, indices))
+            }
+        }
+    }
+
+    fn push_value_bits(&self, idx: u32, bits: &mut Vec<u32>) {
+        match self {
+            CustomAttributeData::Float(attribute) => bits.extend(
+                attribute
+                    .data_at_idx(idx)
+                    .iter()
+                    .map(|value| value.to_bits()),
+            ),
+            CustomAttributeData::Int(attribute) => {
+                bits.extend(attribute.data_at_idx(idx).iter().map(|value| *value as u32))
+            }
+        }
+    }
+}
+
+/// A face corner along with the indices of the point, corner and face that it belongs to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Corner {
+    pub(crate) position: u32,
+    pub(crate) corner: u32,
+    pub(crate) face: u32,
+}
+
+/// Replace `corners` with every face corner of the mesh, in the same order as the position
+/// indices.
+pub(crate) fn fill_corners(multi: &MultiIndexedVertexAttributes, corners: &mut Vec<Corner>) {
+    corners.clear();
+
+    for (face, vertex_count) in multi.vertices_in_each_face.iter().enumerate() {
+        for _ in 0..*vertex_count {
+            let corner = corners.len() as u32;
+            corners.push(Corner {
+                position: multi.positions.indices[corner as usize],
+                corner,
+                face: face as u32,
+            });
+        }
+    }
+}
+
+/// An id for every corner that is shared by the corners whose corner and face attributes have
+/// the same values.
+///
+/// Two corners that share a position but have different ids need their own vertices when
+/// combining indices. Point attributes are left out since corners that share a position always
+/// share their values.
+pub(crate) fn corner_value_ids(
+    custom_attributes: &HashMap<String, CustomAttribute>,
+    corners: &[Corner],
+) -> Vec<u32> {
+    let attributes: Vec<&CustomAttribute> = custom_attributes
+        .values()
+        .filter(|attribute| attribute.domain != AttributeDomain::Point)
+        .collect();
+    if attributes.is_empty() {
+        return vec![0; corners.len()];
+    }
+
+    let mut ids = HashMap::new();
+    let mut corner_ids = Vec::with_capacity(corners.len());
+
+    for corner in corners {
+        let mut bits = vec![];
+        for attribute in attributes.iter() {
+            attribute
+                .data
+                .push_value_bits(attribute.value_index(corner), &mut bits);
+        }
+
+        let next_id = ids.len() as u32;
+        corner_ids.push(*ids.entry(bits).or_insert(next_id));
+    }
+
+    corner_ids
+}
+
+pub(crate) fn gather_values<T: Copy>(
+    attribute: &VertexAttribute<T>,
+    indices: &[u32],
+) -> VertexAttribute<T> {
+    let mut data = Vec::with_capacity(indices.len() * attribute.attribute_size as usize);
+    for idx in indices {
+        data.extend_from_slice(attribute.data_at_idx(*idx));
+    }
+
+    VertexAttribute {
+        data,
+        attribute_size: attribute.attribute_size,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlenderMesh, CreateSingleIndexConfig};
+
+    /// Verify that corner and face values follow their faces when quads are split into
+    /// triangles.
+    #[test]
+    fn triangulate_custom_attributes() {
+        let mut plane = BlenderMesh::plane(1., 1., 0);
+        plane.insert_custom_attribute(
+            "wear".to_string(),
+            CustomAttribute::new(
+                AttributeDomain::Corner,
+                CustomAttributeData::Float(VertexAttribute::new(vec![0., 1., 2., 3.], 1).unwrap()),
+            ),
+        );
+        plane.insert_custom_attribute(
+            "piece".to_string(),
+            CustomAttribute::new(
+                AttributeDomain::Face,
+                CustomAttributeData::Int(VertexAttribute::new(vec![7], 1).unwrap()),
+            ),
+        );
+
+        plane.triangulate_faces();
+
+        assert_eq!(
+            plane.custom_attributes()["wear"].data(),
+            &CustomAttributeData::Float(
+                VertexAttribute::new(vec![0., 1., 2., 0., 2., 3.], 1).unwrap()
+            )
+        );
+        assert_eq!(
+            plane.custom_attributes()["piece"].data(),
+            &CustomAttributeData::Int(VertexAttribute::new(vec![7, 7], 1).unwrap())
+        );
+    }
+
+    /// Verify that corners that share a position but have different face values get their own
+    /// vertices, and that every vertex gets the values of the corners that use it.
+    #[test]
+    fn combine_custom_attributes() {
+        // 2x2 quads with 9 positions
+        let mut plane = BlenderMesh::plane(2., 2., 1);
+        plane.insert_custom_attribute(
+            "piece".to_string(),
+            CustomAttribute::new(
+                AttributeDomain::Face,
+                CustomAttributeData::Int(VertexAttribute::new(vec![0, 1, 2, 3], 1).unwrap()),
+            ),
+        );
+        plane.insert_custom_attribute(
+            "height".to_string(),
+            CustomAttribute::new(
+                AttributeDomain::Point,
+                CustomAttributeData::Float(
+                    VertexAttribute::new((0..9).map(|p| p as f32).collect(), 1).unwrap(),
+                ),
+            ),
+        );
+
+        let single = plane.combine_vertex_indices(&CreateSingleIndexConfig::default());
+
+        // The center is used by 4 pieces, the edges by 2 and the corners by 1
+        assert_eq!(single.vertices().len(), 4 + 4 * 2 + 4);
+
+        let piece = match single.custom_attributes()["piece"].data() {
+            CustomAttributeData::Int(piece) => piece.clone(),
+            _ => panic!(),
+        };
+        let height = match single.custom_attributes()["height"].data() {
+            CustomAttributeData::Float(height) => height.clone(),
+            _ => panic!(),
+        };
+        assert_eq!(
+            single.custom_attributes()["piece"].domain(),
+            AttributeDomain::Point
+        );
+
+        for (triangle_idx, triangle) in single.indices().chunks(3).enumerate() {
+            for vertex in triangle {
+                assert_eq!(piece[*vertex as usize], triangle_idx as i32 / 2);
+            }
+        }
+        for vertex in single.vertices().iter().zip(height.iter()) {
+            let [x, y, _] = vertex.0.position();
+            // Positions go row by row from (-1, -1) to (1, 1)
+            assert_eq!(*vertex.1, (y + 1.) * 3. + (x + 1.));
+        }
+    }
+}
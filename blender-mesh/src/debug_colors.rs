@@ -0,0 +1,219 @@
+use crate::face_tangents::face_tangent_at_idx;
+use crate::vertex_attributes::VertexAttribute;
+use crate::{AttributeDomain, BlenderMesh, CustomAttribute, CustomAttributeData};
+use nalgebra::Vector3;
+
+/// The name of the custom attribute that [`BlenderMesh.method#debug_colors`] writes to.
+pub const DEBUG_COLOR_ATTRIBUTE: &str = "debug_color";
+
+/// The number of checker squares along each side of the uv space in [`DebugView::UvChecker`].
+///
+/// [`DebugView::UvChecker`]: enum.DebugView.html#variant.UvChecker
+const UV_CHECKER_SQUARES: f32 = 8.;
+
+/// Which of a mesh's data [`BlenderMesh.method#debug_colors`] encodes as colors.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DebugView {
+    /// Every normal axis remapped from between -1 and 1 to between 0 and 1, so that +X is red,
+    /// +Y is green and +Z is blue.
+    Normals,
+    /// Every face's tangent remapped the same way as the normals.
+    Tangents,
+    /// How much the joint with this index influences every vertex, from blue for none to red
+    /// for fully.
+    BoneWeights(u8),
+    /// A checkerboard over the uvs, with U in the red channel and V in the green channel, for
+    /// spotting stretched, flipped or misaligned uvs.
+    UvChecker,
+}
+
+/// An error while generating debug colors
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum DebugColorError {
+    /// Tangents and the uv checker are calculated from the uvs.
+    #[error("The {view:?} debug view needs uvs but the mesh doesn't have any")]
+    NoUvs {
+        /// The view that needs uvs
+        view: DebugView,
+    },
+}
+
+impl BlenderMesh {
+    /// Fill a corner custom attribute named [`DEBUG_COLOR_ATTRIBUTE`] with an RGBA color for
+    /// every face corner that encodes the chosen data, so that exported data can be checked by
+    /// drawing the vertex colors in any engine without writing custom shaders.
+    ///
+    /// Meshes without normals show their face normals.
+    ///
+    /// [`DEBUG_COLOR_ATTRIBUTE`]: constant.DEBUG_COLOR_ATTRIBUTE.html
+    pub fn debug_colors(&mut self, view: DebugView) -> Result<(), DebugColorError> {
+        let multi = &self.multi_indexed_vertex_attributes;
+        let face_tangents = match view {
+            DebugView::Tangents => self
+                .calculate_face_tangents()
+                .map_err(|_| DebugColorError::NoUvs { view })?,
+            _ => vec![],
+        };
+        let uvs = multi.uvs.as_ref();
+        if view == DebugView::UvChecker && uvs.is_none() {
+            return Err(DebugColorError::NoUvs { view });
+        }
+
+        let bone_weights: Vec<f32> = match (view, multi.bone_influences.as_ref()) {
+            (DebugView::BoneWeights(joint), Some(influences)) => influences
+                .influences_per_vertex()
+                .iter()
+                .map(|(joints, weights)| {
+                    joints
+                        .iter()
+                        .zip(weights.iter())
+                        .filter(|(j, _)| **j == joint)
+                        .map(|(_, weight)| *weight)
+                        .sum()
+                })
+                .collect(),
+            _ => vec![],
+        };
+
+        let positions = &multi.positions;
+        let mut colors = Vec::with_capacity(positions.indices.len() * 4);
+
+        let mut corner = 0;
+        for (face, vertex_count) in multi.vertices_in_each_face.iter().enumerate() {
+            let position = |corner: usize| {
+                let position = positions.attribute.data_at_idx(positions.indices[corner]);
+                Vector3::new(position[0], position[1], position[2])
+            };
+            let face_normal = match multi.normals.is_some() {
+                true => Vector3::zeros(),
+                false => {
+                    let first = position(corner);
+                    (position(corner + 1) - first)
+                        .cross(&(position(corner + 2) - first))
+                        .normalize()
+                }
+            };
+
+            for _ in 0..*vertex_count {
+                let color = match view {
+                    DebugView::Normals => {
+                        let normal = match multi.normals.as_ref() {
+                            Some(normals) => {
+                                let normal = normals.data_at_corner(corner);
+                                Vector3::new(normal[0], normal[1], normal[2])
+                            }
+                            None => face_normal,
+                        };
+                        direction_color(normal)
+                    }
+                    DebugView::Tangents => {
+                        let (x, y, z) = face_tangent_at_idx(&face_tangents, face);
+                        direction_color(Vector3::new(x, y, z).normalize())
+                    }
+                    DebugView::BoneWeights(_) => {
+                        let position_idx = positions.indices[corner] as usize;
+                        let weight = bone_weights.get(position_idx).copied().unwrap_or(0.);
+                        let weight = weight.clamp(0., 1.);
+
+                        [weight, 0., 1. - weight, 1.]
+                    }
+                    DebugView::UvChecker => {
+                        let uv = uvs.unwrap().data_at_corner(corner);
+                        let square = (uv[0] * UV_CHECKER_SQUARES).floor()
+                            + (uv[1] * UV_CHECKER_SQUARES).floor();
+                        let brightness = match square.rem_euclid(2.) == 0. {
+                            true => 1.,
+                            false => 0.25,
+                        };
+
+                        [uv[0] * brightness, uv[1] * brightness, brightness, 1.]
+                    }
+                };
+
+                colors.extend_from_slice(&color);
+                corner += 1;
+            }
+        }
+
+        self.insert_custom_attribute(
+            DEBUG_COLOR_ATTRIBUTE.to_string(),
+            CustomAttribute::new(
+                AttributeDomain::Corner,
+                CustomAttributeData::Float(VertexAttribute::new(colors, 4).unwrap()),
+            ),
+        );
+
+        Ok(())
+    }
+}
+
+fn direction_color(direction: Vector3<f32>) -> [f32; 4] {
+    [
+        direction.x * 0.5 + 0.5,
+        direction.y * 0.5 + 0.5,
+        direction.z * 0.5 + 0.5,
+        1.,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bone::BoneInfluencesPerVertex;
+    use crate::vertex_attributes::VertexBoneInfluences;
+
+    fn debug_colors(mesh: &BlenderMesh) -> Vec<&[f32]> {
+        match mesh.custom_attributes()[DEBUG_COLOR_ATTRIBUTE].data() {
+            CustomAttributeData::Float(colors) => colors.iter_vertices().collect(),
+            _ => panic!(),
+        }
+    }
+
+    /// Verify that normals and bone weights are encoded for every corner.
+    #[test]
+    fn normal_and_bone_weight_colors() {
+        // Positions (-1, -1, 0), (1, -1, 0), (-1, 1, 0) and (1, 1, 0)
+        let mut plane = BlenderMesh::plane(2., 2., 0);
+
+        plane.debug_colors(DebugView::Normals).unwrap();
+        assert_eq!(debug_colors(&plane), vec![&[0.5, 0.5, 1., 1.][..]; 4]);
+
+        plane.multi_indexed_vertex_attributes.bone_influences = Some(VertexBoneInfluences {
+            bones_per_vertex: BoneInfluencesPerVertex::NonUniform(vec![1, 2, 1, 0]),
+            bone_indices: vec![0, 0, 1, 1],
+            bone_weights: vec![1., 0.25, 0.75, 1.],
+        });
+        plane.debug_colors(DebugView::BoneWeights(1)).unwrap();
+
+        // Corners are in the order of positions 0, 1, 3 and 2
+        assert_eq!(
+            debug_colors(&plane),
+            vec![
+                &[0., 0., 1., 1.][..],
+                &[0.75, 0., 0.25, 1.][..],
+                &[0., 0., 1., 1.][..],
+                &[1., 0., 0., 1.][..],
+            ]
+        );
+    }
+
+    /// Verify that views that are calculated from the uvs need uvs.
+    #[test]
+    fn uv_views_need_uvs() {
+        let mut plane = BlenderMesh::plane(2., 2., 0);
+        plane.multi_indexed_vertex_attributes.uvs = None;
+
+        assert_eq!(
+            plane.debug_colors(DebugView::UvChecker),
+            Err(DebugColorError::NoUvs {
+                view: DebugView::UvChecker
+            })
+        );
+        assert_eq!(
+            plane.debug_colors(DebugView::Tangents),
+            Err(DebugColorError::NoUvs {
+                view: DebugView::Tangents
+            })
+        );
+    }
+}
@@ -1,10 +1,10 @@
-use crate::BlenderMesh;
-use failure::Fail;
+use crate::{BlenderMesh, ExportOptions, MeshKey};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::path::Path;
 
 pub type MeshesByFilename = HashMap<String, MeshesByMeshName>;
-pub type MeshesByMeshName = HashMap<String, BlenderMesh>;
+pub type MeshesByMeshName = HashMap<MeshKey, BlenderMesh>;
 
 /// Given a buffer of standard output from Blender we parse all of the mesh JSON that was
 /// written to stdout by `blender-mesh-to-json.py`.
@@ -15,6 +15,8 @@ pub type MeshesByMeshName = HashMap<String, BlenderMesh>;
 /// {...}
 /// END_MESH_JSON /path/to/file.blend my_mesh_name
 ///
+/// Meshes are keyed by the [`MeshKey`] of their object.
+///
 /// @see blender-mesh-to-json.py - This is where we write to stdout
 pub fn parse_meshes_from_blender_stdout(blender_stdout: &str) -> MeshesByFilename {
     let mut filenames_to_meshes = HashMap::new();
@@ -41,7 +43,64 @@ pub fn parse_meshes_from_blender_stdout(blender_stdout: &str) -> MeshesByFilenam
     filenames_to_meshes
 }
 
-pub type FlattenedExportedMeshes = HashMap<String, BlenderMesh>;
+/// Parse meshes from Blender's stdout and then post process them according to the
+/// [`ExportOptions`].
+///
+/// @see [`parse_meshes_from_blender_stdout`]
+pub fn parse_meshes_from_blender_stdout_with_options(
+    blender_stdout: &str,
+    options: &ExportOptions,
+) -> MeshesByFilename {
+    let mut filenames_to_meshes = parse_meshes_from_blender_stdout(blender_stdout);
+    apply_export_options(&mut filenames_to_meshes, options);
+
+    filenames_to_meshes
+}
+
+/// Post process meshes that were parsed from Blender's stdout according to the
+/// [`ExportOptions`], such as after parsing them some other way than with
+/// [`parse_meshes_from_blender_stdout_with_options`].
+pub fn apply_export_options(filenames_to_meshes: &mut MeshesByFilename, options: &ExportOptions) {
+    for (filename, meshes) in filenames_to_meshes.iter_mut() {
+        let blend_file = Path::new(filename);
+
+        for mesh in meshes.values_mut() {
+            for material in mesh.materials_mut().iter_mut() {
+                material.map_texture_names(|texture_name| {
+                    options.normalize_texture_name(texture_name, Some(blend_file))
+                });
+            }
+        }
+    }
+}
+
+/// Collection name -> mesh name -> mesh
+pub type MeshesByCollection<'a> = HashMap<String, HashMap<&'a str, &'a BlenderMesh>>;
+
+/// Group the meshes across all of the files by the Blender collections that their objects are in,
+/// for projects that organize their `.blend` files into collections such as `Props`,
+/// `Characters` and `Gameplay`.
+///
+/// A mesh whose object is in more than one collection is in every one of their groups. Meshes
+/// with the same name in different files end up as one entry, the same as when flattening.
+///
+/// @see [`BlenderMesh.method#collections`]
+pub fn assets_by_collection(meshes_by_filename: &MeshesByFilename) -> MeshesByCollection<'_> {
+    let mut by_collection: MeshesByCollection = HashMap::new();
+
+    for (mesh_name, mesh) in meshes_by_filename.values().flat_map(|meshes| meshes.iter()) {
+        for collection in mesh.collections.iter() {
+            by_collection
+                .entry(collection.clone())
+                .or_default()
+                .insert(mesh_name.as_str(), mesh);
+        }
+    }
+
+    by_collection
+}
+
+pub type FlattenedExportedMeshes = HashMap<MeshKey, BlenderMesh>;
 
 /// Convert MesheshByFilename into a HashMap<MeshName, BlenderMesh> that flattens all of the
 /// meshes across all of the files into one HashMap.
@@ -124,9 +183,10 @@ fn validate_no_duplicates(
 
 /// An error when trying to flatten your exported data across multiple files into one HashMap of
 /// mesh name to mesh data.
-#[derive(Debug, Fail)]
+#[derive(Debug, thiserror::Error)]
 pub enum FlattenMeshError {
-    #[fail(display = "Duplicate meshes found: {:#?}", duplicates)]
+    /// Two or more files have a mesh with the same name
+    #[error("Duplicate meshes found: {:#?}", duplicates)]
     DuplicateMeshNamesAcrossFiles {
         // HashMap<MeshName, Vec<FilesThatItAppearsIn>>
         duplicates: HashMap<String, Vec<String>>,
@@ -154,12 +214,10 @@ fn find_first_mesh_after_index(
         let mesh_filename: Vec<&str> = first_line.split(" ").collect();
         let mesh_filename = mesh_filename[1].to_string();
 
-        let mesh_name = first_line.split(" ").last().unwrap().to_string();
-
         let mesh_data: String = lines.collect();
         let mesh_data: BlenderMesh = serde_json::from_str(&mesh_data).unwrap();
 
-        mesh_name_to_data.insert(mesh_name, mesh_data);
+        mesh_name_to_data.insert(mesh_data.key(), mesh_data);
         filenames_to_meshes.insert(mesh_filename, mesh_name_to_data);
 
         return Some((filenames_to_meshes, index + mesh_end_index + 1));
@@ -167,3 +225,35 @@ fn find_first_mesh_after_index(
 
     return None;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that meshes are grouped under every collection that their object is in.
+    #[test]
+    fn group_meshes_by_collection() {
+        let mut barrel = BlenderMesh::cube(1.);
+        barrel.set_collections(vec!["Props".to_string(), "Destructible".to_string()]);
+        let mut hero = BlenderMesh::cube(1.);
+        hero.set_collections(vec!["Characters".to_string()]);
+
+        let mut meshes = MeshesByFilename::new();
+        meshes.insert(
+            "level.blend".to_string(),
+            vec![("Barrel".into(), barrel), ("Hero".into(), hero)]
+                .into_iter()
+                .collect(),
+        );
+
+        let by_collection = assets_by_collection(&meshes);
+
+        assert_eq!(by_collection.len(), 3);
+        assert!(by_collection["Props"].contains_key("Barrel"));
+        assert!(by_collection["Destructible"].contains_key("Barrel"));
+        assert_eq!(
+            by_collection["Characters"].keys().collect::<Vec<_>>(),
+            vec![&"Hero"]
+        );
+    }
+}
@@ -2,9 +2,10 @@ use crate::vertex_attributes::MultiIndexedVertexAttributes;
 use crate::BlenderMesh;
 
 /// Indicates an error while calculating the tangents for a mesh's verticies
-#[derive(Debug, Fail)]
+#[derive(Debug, thiserror::Error)]
 pub enum TangentError {
-    #[fail(display = "Cannot calculate vertex tangents for a mesh with no uvs")]
+    /// Tangents are calculated from the direction that the uvs run in
+    #[error("Cannot calculate vertex tangents for a mesh with no uvs")]
     NoVertexUvs,
 }
 
@@ -44,7 +45,7 @@ impl BlenderMesh {
         for vertices_in_face in vertices_in_each_face.iter() {
             let vertices_in_face = *vertices_in_face;
 
-            let idx = total_indices_processed as usize;
+            let idx = total_indices_processed;
 
             // Get the first three vertex indices for this face
             let pos_idx_0 = positions.indices[idx];
@@ -80,7 +81,7 @@ impl BlenderMesh {
             face_tangents.push(tangent_y);
             face_tangents.push(tangent_z);
 
-            total_indices_processed += vertices_in_face as u16;
+            total_indices_processed += vertices_in_face as usize;
         }
 
         Ok(face_tangents)
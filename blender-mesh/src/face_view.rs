@@ -0,0 +1,177 @@
+use crate::{BlenderMesh, IndexedAttribute, MultiIndexedVertexAttributes};
+use std::ops::Range;
+
+/// One face of a multi indexed mesh, with the indices and data of each of its corners.
+///
+/// Every multi indexed attribute has one index per face corner, with the corners of each face
+/// next to each other in the order of [`MultiIndexedVertexAttributes.method#vertices_in_each_face`].
+/// A `FaceView` finds a face's corners so that code that works a face at a time doesn't need to
+/// keep track of where each face starts.
+///
+/// ```
+/// # use blender_mesh::BlenderMesh;
+/// let cube = BlenderMesh::cube(1.);
+///
+/// for face in cube.faces() {
+///     assert_eq!(face.corner_count(), 4);
+///     assert!(face.positions().all(|position| position[2].abs() <= 0.5));
+/// }
+/// assert_eq!(cube.faces().count(), 6);
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct FaceView<'a> {
+    multi: &'a MultiIndexedVertexAttributes,
+    index: usize,
+    first_corner: usize,
+    corner_count: usize,
+}
+
+impl<'a> FaceView<'a> {
+    /// The face's index, such as for looking up its smoothing group or face maps.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The index of the material that the face uses, or 0 if the mesh doesn't have material
+    /// indices.
+    pub fn material_index(&self) -> u16 {
+        self.multi
+            .material_index
+            .get(self.index)
+            .cloned()
+            .unwrap_or(0)
+    }
+
+    /// The number of corners, which is 3 for a triangle and 4 for a quad.
+    pub fn corner_count(&self) -> usize {
+        self.corner_count
+    }
+
+    /// The mesh wide indices of the face's corners, such as for corner domain
+    /// [`CustomAttribute`]s.
+    ///
+    /// [`CustomAttribute`]: struct.CustomAttribute.html
+    pub fn corners(&self) -> Range<usize> {
+        self.first_corner..self.first_corner + self.corner_count
+    }
+
+    /// The position index of each corner, which doubles as the corner's vertex id.
+    pub fn position_indices(&self) -> &'a [u32] {
+        &self.multi.positions.indices[self.corners()]
+    }
+
+    /// The normal index of each corner, if the mesh has normals.
+    pub fn normal_indices(&self) -> Option<&'a [u32]> {
+        let corners = self.corners();
+        self.multi
+            .normals
+            .as_ref()
+            .map(|normals| &normals.indices[corners])
+    }
+
+    /// The uv index of each corner, if the mesh has uvs.
+    pub fn uv_indices(&self) -> Option<&'a [u32]> {
+        let corners = self.corners();
+        self.multi.uvs.as_ref().map(|uvs| &uvs.indices[corners])
+    }
+
+    /// The position of each corner.
+    pub fn positions(&self) -> impl Iterator<Item = &'a [f32]> + 'a {
+        corner_data(&self.multi.positions, self.corners())
+    }
+
+    /// The normal of each corner, if the mesh has normals.
+    pub fn normals(&self) -> Option<impl Iterator<Item = &'a [f32]> + 'a> {
+        let corners = self.corners();
+        self.multi
+            .normals
+            .as_ref()
+            .map(|normals| corner_data(normals, corners))
+    }
+
+    /// The uv of each corner, if the mesh has uvs.
+    pub fn uvs(&self) -> Option<impl Iterator<Item = &'a [f32]> + 'a> {
+        let corners = self.corners();
+        self.multi.uvs.as_ref().map(|uvs| corner_data(uvs, corners))
+    }
+}
+
+fn corner_data<'a>(
+    attribute: &'a IndexedAttribute,
+    corners: Range<usize>,
+) -> impl Iterator<Item = &'a [f32]> + 'a {
+    attribute.indices[corners]
+        .iter()
+        .map(move |idx| attribute.attribute.data_at_idx(*idx))
+}
+
+impl MultiIndexedVertexAttributes {
+    /// Every face in order. See [`FaceView`].
+    ///
+    /// [`FaceView`]: struct.FaceView.html
+    pub fn faces(&self) -> impl Iterator<Item = FaceView<'_>> {
+        let mut first_corner = 0;
+
+        self.vertices_in_each_face
+            .iter()
+            .enumerate()
+            .map(move |(index, corner_count)| {
+                let face = FaceView {
+                    multi: self,
+                    index,
+                    first_corner,
+                    corner_count: *corner_count as usize,
+                };
+                first_corner += face.corner_count;
+                face
+            })
+    }
+}
+
+impl BlenderMesh {
+    /// Every face in order. See [`FaceView`].
+    ///
+    /// [`FaceView`]: struct.FaceView.html
+    pub fn faces(&self) -> impl Iterator<Item = FaceView<'_>> {
+        self.multi_indexed_vertex_attributes.faces()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that each face's corners, indices and data line up with the flat index arrays when
+    /// faces have different numbers of corners.
+    #[test]
+    fn faces_of_mixed_sizes() {
+        let mut mesh = BlenderMesh::cube(1.);
+        let multi = &mut mesh.multi_indexed_vertex_attributes;
+        multi.triangulate();
+        multi.vertices_in_each_face = vec![3; 10];
+        multi.vertices_in_each_face.push(6);
+        multi.material_index[10] = 2;
+
+        let faces: Vec<FaceView> = mesh.faces().collect();
+        let multi = &mesh.multi_indexed_vertex_attributes;
+
+        assert_eq!(faces.len(), 11);
+        assert_eq!(faces[10].corners(), 30..36);
+        assert_eq!(faces[10].material_index(), 2);
+        assert_eq!(
+            faces[4].position_indices(),
+            &multi.positions.indices[12..15]
+        );
+        assert_eq!(
+            faces[4].uv_indices().unwrap(),
+            &multi.uvs.as_ref().unwrap().indices[12..15]
+        );
+
+        let normals: Vec<&[f32]> = faces[10].normals().unwrap().collect();
+        assert_eq!(normals.len(), 6);
+        assert_eq!(
+            normals[5],
+            multi.normals.as_ref().unwrap().data_at_corner(35)
+        );
+    }
+}
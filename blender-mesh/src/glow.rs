@@ -0,0 +1,141 @@
+//! Create [glow] buffers from single indexed vertex data.
+//!
+//! Enabled by the `glow` feature.
+//!
+//! ```no_run
+//! # use blender_mesh::{BlenderMesh, CreateSingleIndexConfig};
+//! # use glow::HasContext;
+//! # unsafe fn example(gl: &glow::Context) -> Result<(), String> {
+//! let mesh = BlenderMesh::cube(1.).combine_vertex_indices(&CreateSingleIndexConfig::default());
+//!
+//! let buffers = blender_mesh::glow::create_vertex_buffers(gl, &mesh)?;
+//!
+//! gl.bind_vertex_array(Some(buffers.vertex_array()));
+//! gl.draw_elements(glow::TRIANGLES, buffers.index_count(), glow::UNSIGNED_SHORT, 0);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [glow]: https://github.com/grovesNL/glow
+
+use crate::{ComponentType, SingleIndexedVertexAttributes};
+use ::glow::HasContext;
+
+/// The GPU buffers for a mesh, along with a vertex array object that has the vertex buffer's
+/// attributes enabled and the index buffer bound.
+#[derive(Debug)]
+pub struct MeshBuffers<C: HasContext> {
+    pub(crate) vertex_array: C::VertexArray,
+    pub(crate) vertex_buffer: C::Buffer,
+    pub(crate) index_buffer: C::Buffer,
+    pub(crate) index_count: i32,
+}
+
+impl<C: HasContext> MeshBuffers<C> {
+    /// Bind this before drawing the mesh.
+    pub fn vertex_array(&self) -> C::VertexArray {
+        self.vertex_array
+    }
+
+    /// The interleaved vertex data, laid out as described by
+    /// [`SingleIndexedVertexAttributes.method#vertex_layout`].
+    pub fn vertex_buffer(&self) -> C::Buffer {
+        self.vertex_buffer
+    }
+
+    /// The mesh's indices, stored as `glow::UNSIGNED_SHORT`s.
+    pub fn index_buffer(&self) -> C::Buffer {
+        self.index_buffer
+    }
+
+    /// The number of indices in the index buffer.
+    pub fn index_count(&self) -> i32 {
+        self.index_count
+    }
+
+    /// Delete the vertex array and buffers.
+    ///
+    /// # Safety
+    ///
+    /// Must be called with the same context that created the buffers.
+    pub unsafe fn delete(self, gl: &C) {
+        gl.delete_vertex_array(self.vertex_array);
+        gl.delete_buffer(self.vertex_buffer);
+        gl.delete_buffer(self.index_buffer);
+    }
+}
+
+/// Upload the mesh's interleaved vertex data and indices to the GPU and create a vertex array
+/// object that points each attribute at its [`LayoutAttribute.method#shader_location`].
+///
+/// Bone indices are integer attributes, so they should be read as a `uvec4` in your shaders.
+///
+/// # Safety
+///
+/// Makes raw OpenGL calls, so the context must be current. This leaves the vertex array unbound.
+///
+/// [`LayoutAttribute.method#shader_location`]: ../enum.LayoutAttribute.html#method.shader_location
+pub unsafe fn create_vertex_buffers<C: HasContext>(
+    gl: &C,
+    mesh: &SingleIndexedVertexAttributes,
+) -> Result<MeshBuffers<C>, String> {
+    let layout = mesh.vertex_layout();
+
+    let vertex_array = gl.create_vertex_array()?;
+    gl.bind_vertex_array(Some(vertex_array));
+
+    let vertex_buffer = gl.create_buffer()?;
+    gl.bind_buffer(::glow::ARRAY_BUFFER, Some(vertex_buffer));
+    gl.buffer_data_u8_slice(
+        ::glow::ARRAY_BUFFER,
+        &mesh.interleaved_bytes(),
+        ::glow::STATIC_DRAW,
+    );
+
+    for attribute in layout.attributes() {
+        let location = attribute.kind().shader_location();
+        let size = attribute.component_count() as i32;
+        let stride = attribute.stride() as i32;
+        let offset = attribute.byte_offset() as i32;
+
+        match attribute.component_type() {
+            ComponentType::F32 => gl.vertex_attrib_pointer_f32(
+                location,
+                size,
+                ::glow::FLOAT,
+                attribute.normalized(),
+                stride,
+                offset,
+            ),
+            ComponentType::U8 if attribute.normalized() => gl.vertex_attrib_pointer_f32(
+                location,
+                size,
+                ::glow::UNSIGNED_BYTE,
+                true,
+                stride,
+                offset,
+            ),
+            ComponentType::U8 => {
+                gl.vertex_attrib_pointer_i32(location, size, ::glow::UNSIGNED_BYTE, stride, offset)
+            }
+        };
+        gl.enable_vertex_attrib_array(location);
+    }
+
+    let index_buffer = gl.create_buffer()?;
+    gl.bind_buffer(::glow::ELEMENT_ARRAY_BUFFER, Some(index_buffer));
+    gl.buffer_data_u8_slice(
+        ::glow::ELEMENT_ARRAY_BUFFER,
+        &mesh.index_bytes(),
+        ::glow::STATIC_DRAW,
+    );
+
+    gl.bind_vertex_array(None);
+
+    Ok(MeshBuffers {
+        vertex_array,
+        vertex_buffer,
+        index_buffer,
+        index_count: mesh.indices().len() as i32,
+    })
+}
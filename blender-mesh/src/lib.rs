@@ -16,49 +16,153 @@
 //! @see https://docs.blender.org/manual/en/dev/modeling/meshes/introduction.html - Mesh Introduction
 //! @see https://github.com/chinedufn/blender-actions-to-json - Exporting blender armatures / actions
 
-#[macro_use]
-extern crate failure;
 #[macro_use]
 extern crate serde_derive;
 
-pub use self::combine_indices::CreateSingleIndexConfig;
+pub use self::ambient_occlusion::AMBIENT_OCCLUSION_ATTRIBUTE;
+pub use self::binary::{
+    inspect_binary, BinaryError, BinaryInspection, BinarySection, Endianness, VertexChunk,
+    BINARY_MAGIC, BINARY_VERSION,
+};
+#[cfg(feature = "integrity")]
+pub use self::binary::{sign_binary, verify_integrity, IntegrityError};
+pub use self::bone::GroupsPerVertex;
+pub use self::bvh::{Bvh, BvhNode, BvhTriangle};
+pub use self::color_space::ColorSpace;
+pub use self::combine_indices::{
+    CombineIndicesError, CreateSingleIndexConfig, MeshScratch, WeightedNormalsError,
+};
+pub use self::create_mesh::{TerrainConfig, TerrainError};
+pub use self::custom_attribute::{AttributeDomain, CustomAttribute, CustomAttributeData};
+pub use self::debug_colors::{DebugColorError, DebugView, DEBUG_COLOR_ATTRIBUTE};
 pub use self::export::*;
+pub use self::face_tangents::TangentError;
+pub use self::face_view::FaceView;
+pub use self::mesh_armature::MeshArmature;
+pub use self::mesh_builder::{BlenderMeshBuilder, FaceCorner, MeshBuilderError};
+#[cfg(feature = "dedupe")]
+pub use self::mesh_instance::{deduplicate_meshes, MeshInstance};
+pub use self::mesh_key::MeshKey;
+pub use self::mesh_kind::MeshKind;
+pub use self::mesh_sequence::*;
+pub use self::meshlet::{Meshlet, MeshletConfig, MeshletError};
+pub use self::mirror::{BoneRename, MirrorAxis};
+pub use self::navmesh::{NavMesh, NavMeshConfig, NavPolygon};
+pub use self::occluder::{Occluder, OccluderConfig};
+pub use self::particle_system::*;
+pub use self::ply::{Ply, PlyError, PlyFormat};
+pub use self::point_cache::*;
+pub use self::polyline::*;
+pub use self::shape_key::{EvaluatePositionsError, ShapeKey};
+pub use self::shape_key_driver::{DriverCombine, DriverInput, ShapeKeyDriver, TransformChannel};
+pub use self::spatial_query::{ClosestPoint, RaycastHit};
+pub use self::submesh::{SubmeshError, SubmeshSelector};
+pub use self::texel_density::TexelDensity;
+#[cfg(feature = "image")]
+pub use self::texture_colors::{TextureColorError, TEXTURE_COLOR_ATTRIBUTE};
+pub use self::texture_name::ExportOptions;
+pub use self::topology::{Topology, TopologyReport};
+pub use self::units::{SceneUnits, SpaceConvention, UnitSystem, UpAxis, UvOrigin};
+pub use self::uv_island::{UvBoundaryEdge, UvIsland, UvLayer};
+pub use self::uv_query::{UvBvh, UvHit};
+pub use self::uv_transform::{TextureSlot, UvTransform};
+pub use self::visibility::ObjectVisibility;
+pub use self::world_cell::{rebase_origins, WorldCell};
 pub use crate::bounding_box::BoundingBox;
+use crate::bvh::BvhCache;
 use crate::custom_property::CustomProperty;
 pub use crate::material::PrincipledBSDF;
 use crate::serde::serialize_hashmap_deterministic;
 pub use crate::vertex_attributes::{
-    BoneInfluence, MultiIndexedVertexAttributes, SingleIndexedVertexAttributes, Vertex,
-    VertexAttribute,
+    BoneAttributes, BoneInfluence, ComponentType, IndexedAttribute, InterleaveError,
+    LayoutAttribute, MultiIndexedVertexAttributes, PresentAttributes, SingleIndexedError,
+    SingleIndexedVertexAttributes, Vertex, VertexAttribute, VertexAttributeError,
+    VertexAttributeLayout, VertexBoneInfluences, VertexLayout,
 };
 pub use material::{Channel, MaterialInput};
+use nalgebra::Matrix4;
 use std::collections::HashMap;
 
+mod ambient_occlusion;
+mod binary;
 mod bone;
 mod bounding_box;
+mod bvh;
+mod color_space;
 mod combine_indices;
+mod custom_attribute;
 mod custom_property;
+mod debug_colors;
 mod export;
 mod face_tangents;
+mod face_view;
 mod interleave;
 mod material;
+mod mesh_armature;
+mod mesh_builder;
+#[cfg(feature = "dedupe")]
+mod mesh_instance;
+mod mesh_key;
+mod mesh_kind;
+mod mesh_sequence;
+mod meshlet;
+mod mirror;
+mod navmesh;
+mod normal_seams;
+mod occluder;
+mod origin;
+mod particle_system;
+mod ply;
+mod point_cache;
+mod polyline;
 mod serde;
+mod shape_key;
+mod shape_key_driver;
+mod simd;
+mod snap;
+mod spatial_query;
+mod submesh;
+mod texel_density;
+#[cfg(feature = "image")]
+mod texture_colors;
+mod texture_name;
+mod topology;
+mod transform;
 mod triangulate;
+mod units;
+mod uv_flip;
+mod uv_island;
+mod uv_query;
+mod uv_transform;
 mod vertex_attributes;
+mod visibility;
+mod wireframe;
+mod world_cell;
 mod y_up;
 
 mod create_mesh;
 
+#[cfg(feature = "glow")]
+pub mod glow;
+#[cfg(feature = "macroquad")]
+pub mod macroquad;
+#[cfg(feature = "rapier3d")]
+pub mod rapier;
+#[cfg(feature = "three-d")]
+pub mod three_d;
+#[cfg(feature = "wgpu")]
+pub mod wgpu;
+
 #[cfg(test)]
 mod test_utils;
 
 /// Something went wrong in the Blender child process that was trying to parse your mesh data.
-#[derive(Debug, Fail)]
+#[derive(Debug, thiserror::Error)]
 pub enum BlenderError {
     /// Errors in Blender are written to stderr. We capture the stderr from the `blender` child
     /// process that we spawned when attempting to export meshes from a `.blend` file.
-    #[fail(
-        display = "There was an issue while exporting meshes: Blender stderr output: {}",
+    #[error(
+        "There was an issue while exporting meshes: Blender stderr output: {}",
         _0
     )]
     Stderr(String),
@@ -71,24 +175,66 @@ pub enum BlenderError {
 #[serde(deny_unknown_fields)]
 pub struct BlenderMesh {
     name: String,
-    armature_name: Option<String>,
+    #[serde(default)]
+    data_name: String,
+    #[serde(default)]
+    library: Option<String>,
+    #[serde(default)]
+    stable_id: Option<u32>,
+    #[serde(default)]
+    thumbnail: Option<String>,
+    #[serde(
+        default,
+        alias = "armature_name",
+        deserialize_with = "crate::mesh_armature::deserialize_armatures"
+    )]
+    armatures: Vec<MeshArmature>,
     bounding_box: BoundingBox,
     #[serde(alias = "attribs")]
     multi_indexed_vertex_attributes: MultiIndexedVertexAttributes,
     materials: Vec<PrincipledBSDF>,
     #[serde(default, serialize_with = "serialize_hashmap_deterministic")]
     custom_properties: HashMap<String, CustomProperty>,
+    #[serde(default)]
+    smoothing_groups: Vec<u32>,
+    #[serde(default, serialize_with = "serialize_hashmap_deterministic")]
+    vertex_groups: HashMap<String, Vec<u32>>,
+    #[serde(default, serialize_with = "serialize_hashmap_deterministic")]
+    face_maps: HashMap<String, Vec<u32>>,
+    #[serde(default, serialize_with = "serialize_hashmap_deterministic")]
+    custom_attributes: HashMap<String, CustomAttribute>,
+    #[serde(default)]
+    shape_keys: Vec<ShapeKey>,
+    #[serde(default)]
+    edges: Vec<[u32; 2]>,
+    #[serde(default)]
+    collections: Vec<String>,
+    #[serde(default)]
+    visibility: ObjectVisibility,
+    #[serde(default)]
+    space: SpaceConvention,
+    #[serde(default)]
+    world_transform: Option<Matrix4<f64>>,
+    #[serde(default, skip_serializing_if = "BvhCache::is_lazy")]
+    bvh: BvhCache,
 }
 
 impl BlenderMesh {
-    /// The name of this mesh's parent armature
+    /// The name of the first armature that deforms this mesh, which is its parent armature if it
+    /// is parented to one. See [`BlenderMesh.method#armatures`].
     pub fn armature_name(&self) -> Option<&String> {
-        self.armature_name.as_ref()
+        self.armatures.first().map(|armature| &armature.name)
     }
 
-    /// Set the name of this mesh's parent armature
+    /// Deform this mesh by only this armature, or by no armature.
     pub fn set_armature_name(&mut self, armature_name: Option<String>) {
-        self.armature_name = armature_name;
+        self.armatures = armature_name
+            .map(|name| MeshArmature {
+                name,
+                ..MeshArmature::default()
+            })
+            .into_iter()
+            .collect();
     }
 
     /// A map of material name to the material's data
@@ -116,6 +262,139 @@ impl BlenderMesh {
         &self.custom_properties
     }
 
+    /// The path of a small rendered preview of the mesh's object, such as for showing the mesh
+    /// in an asset browser.
+    ///
+    /// Set by `landon export --thumbnail-dir`, otherwise `None`.
+    pub fn thumbnail(&self) -> Option<&String> {
+        self.thumbnail.as_ref()
+    }
+
+    /// Set the path of the mesh's thumbnail.
+    pub fn set_thumbnail(&mut self, thumbnail: Option<String>) {
+        self.thumbnail = thumbnail;
+    }
+
+    /// The smoothing groups of every face, for formats that use smoothing groups instead of
+    /// explicit normals.
+    ///
+    /// Each face's smoothing group is a set of bitflags. Two faces that share an edge are smoothed
+    /// across that edge if they have at least one bit in common, and a face of `0` is flat shaded.
+    ///
+    /// Empty if the mesh was not exported from Blender.
+    ///
+    /// # Blender
+    ///
+    /// Faces are split into different smoothing groups by edges that are marked sharp, by flat
+    /// shaded faces and, when auto smooth is enabled, by edges whose faces meet at an angle larger
+    /// than the mesh's auto smooth angle.
+    pub fn smoothing_groups(&self) -> &Vec<u32> {
+        &self.smoothing_groups
+    }
+
+    /// Set the smoothing groups of every face.
+    pub fn set_smoothing_groups(&mut self, smoothing_groups: Vec<u32>) {
+        self.smoothing_groups = smoothing_groups;
+    }
+
+    /// A map of vertex group name to the position indices of the vertices in the group.
+    ///
+    /// Vertices with a weight of zero are not considered to be in the group.
+    pub fn vertex_groups(&self) -> &HashMap<String, Vec<u32>> {
+        &self.vertex_groups
+    }
+
+    /// Rename a vertex group, such as after renaming the bone that it belongs to. Does nothing if
+    /// the mesh doesn't have a vertex group with the old name.
+    pub fn rename_vertex_group(&mut self, old_name: &str, new_name: String) {
+        if let Some(vertices) = self.vertex_groups.remove(old_name) {
+            self.vertex_groups.insert(new_name.clone(), vertices);
+        }
+
+        for armature in self.armatures.iter_mut() {
+            for vertex_group in armature.vertex_groups.iter_mut() {
+                if vertex_group == old_name {
+                    *vertex_group = new_name.clone();
+                }
+            }
+        }
+    }
+
+    /// A map of face map name to the indices of the faces in the face map.
+    pub fn face_maps(&self) -> &HashMap<String, Vec<u32>> {
+        &self.face_maps
+    }
+
+    /// A map of attribute name to the named attributes that were added to the mesh, such as by
+    /// geometry nodes.
+    ///
+    /// They are kept when triangulating with [`BlenderMesh.method#triangulate_faces`] and when
+    /// combining indices, where every attribute becomes a point attribute with one value per
+    /// vertex.
+    ///
+    /// # Blender
+    ///
+    /// Float, 2D vector, 3D vector, color and integer attributes on points, face corners and
+    /// faces are exported. Attributes that Blender uses internally, along with uv maps and
+    /// material indices which are already exported, are not.
+    pub fn custom_attributes(&self) -> &HashMap<String, CustomAttribute> {
+        &self.custom_attributes
+    }
+
+    /// Add a custom attribute, replacing any existing attribute with the same name.
+    pub fn insert_custom_attribute(&mut self, name: String, attribute: CustomAttribute) {
+        self.custom_attributes.insert(name, attribute);
+    }
+
+    /// The mesh's shape keys, in the order that they were added in Blender.
+    ///
+    /// Every shape key has one position delta per position, so they are kept when triangulating
+    /// but not when combining indices. Use [`BlenderMesh.method#evaluate_vertex_positions`] to
+    /// apply them.
+    ///
+    /// # Blender
+    ///
+    /// The basis is not exported and every other shape key's deltas are relative to the shape key
+    /// that it is relative to, which is usually the basis.
+    pub fn shape_keys(&self) -> &Vec<ShapeKey> {
+        &self.shape_keys
+    }
+
+    /// Add a shape key after the existing shape keys.
+    pub fn push_shape_key(&mut self, shape_key: ShapeKey) {
+        self.shape_keys.push(shape_key);
+    }
+
+    /// The position indices at both ends of every loose edge, which are edges that aren't part
+    /// of any face.
+    ///
+    /// Edges of faces aren't included since they're already described by the faces. See
+    /// [`BlenderMesh.method#kind`].
+    pub fn edges(&self) -> &Vec<[u32; 2]> {
+        &self.edges
+    }
+
+    /// Set the loose edges of the mesh.
+    pub fn set_edges(&mut self, edges: Vec<[u32; 2]>) {
+        self.edges = edges;
+    }
+
+    /// The names of the Blender collections that the mesh's object is in, such as `Props` or
+    /// `Characters`. See [`assets_by_collection`].
+    ///
+    /// Only the collections that directly contain the object are listed, not their parents. An
+    /// object that isn't in any other collection is in the scene's `Scene Collection`.
+    ///
+    /// [`assets_by_collection`]: fn.assets_by_collection.html
+    pub fn collections(&self) -> &Vec<String> {
+        &self.collections
+    }
+
+    /// Set the names of the collections that the mesh's object is in.
+    pub fn set_collections(&mut self, collections: Vec<String>) {
+        self.collections = collections;
+    }
+
     /// The smallest box that contains the entire mesh
     pub fn bounding_box(&self) -> BoundingBox {
         self.bounding_box
@@ -126,6 +405,11 @@ impl BlenderMesh {
         self.bounding_box = bounding_box;
     }
 
+    /// The mesh's vertex data, with separate indices for positions, normals and uvs.
+    pub fn multi_indexed_vertex_attributes(&self) -> &MultiIndexedVertexAttributes {
+        &self.multi_indexed_vertex_attributes
+    }
+
     /// The name of the mesh
     pub fn name(&self) -> &String {
         &self.name
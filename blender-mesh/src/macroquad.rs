@@ -0,0 +1,72 @@
+//! Convert single indexed vertex data into [macroquad] meshes.
+//!
+//! Enabled by the `macroquad` feature.
+//!
+//! ```no_run
+//! # use blender_mesh::{BlenderMesh, CreateSingleIndexConfig};
+//! let mesh = BlenderMesh::cube(1.).combine_vertex_indices(&CreateSingleIndexConfig::default());
+//!
+//! let mesh = blender_mesh::macroquad::to_macroquad_mesh(&mesh);
+//! macroquad::models::draw_mesh(&mesh);
+//! ```
+//!
+//! [macroquad]: https://github.com/not-fl3/macroquad
+
+use crate::SingleIndexedVertexAttributes;
+use ::macroquad::math::{Vec2, Vec3, Vec4};
+use ::macroquad::models::{Mesh, Vertex};
+
+/// Convert the mesh into a macroquad mesh without a texture.
+///
+/// Vertices are white. Macroquad doesn't use normals, but they're stored in each vertex's
+/// `normal` (with a `w` of 0) for use in custom shaders. Vertices without uvs get a uv of
+/// `(0, 0)`.
+pub fn to_macroquad_mesh(mesh: &SingleIndexedVertexAttributes) -> Mesh {
+    let vertices = mesh
+        .vertices()
+        .iter()
+        .map(|vertex| {
+            let [x, y, z] = vertex.position();
+            let [u, v] = vertex.uv().unwrap_or([0., 0.]);
+            let [nx, ny, nz] = vertex.normal().unwrap_or([0., 0., 0.]);
+
+            Vertex {
+                position: Vec3::new(x, y, z),
+                uv: Vec2::new(u, v),
+                color: [255, 255, 255, 255],
+                normal: Vec4::new(nx, ny, nz, 0.),
+            }
+        })
+        .collect();
+
+    Mesh {
+        vertices,
+        indices: mesh.indices().clone(),
+        texture: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Vertex as BlenderVertex;
+
+    /// Verify that every vertex's position, uv and normal make it into the macroquad mesh.
+    #[test]
+    fn macroquad_mesh() {
+        let vertex = BlenderVertex {
+            position: [1., 2., 3.],
+            normal: Some([0., 0., 1.]),
+            uv: Some([0.5, 0.25]),
+            ..BlenderVertex::default()
+        };
+        let mesh = SingleIndexedVertexAttributes::new(vec![0, 0, 0], vec![vertex]).unwrap();
+
+        let mesh = to_macroquad_mesh(&mesh);
+
+        assert_eq!(mesh.indices, vec![0, 0, 0]);
+        assert_eq!(mesh.vertices[0].position, Vec3::new(1., 2., 3.));
+        assert_eq!(mesh.vertices[0].uv, Vec2::new(0.5, 0.25));
+        assert_eq!(mesh.vertices[0].normal, Vec4::new(0., 0., 1., 0.));
+    }
+}
@@ -1,3 +1,6 @@
+use crate::{ColorSpace, TextureSlot, UvTransform};
+use std::collections::BTreeMap;
+
 /// Material data for a mesh
 ///
 /// # Blender
@@ -18,6 +21,12 @@ pub struct PrincipledBSDF {
     pub(crate) metallic: MaterialInput<f32, (String, Channel)>,
     /// The filename for the material's normal map
     pub(crate) normal_map: Option<String>,
+    /// How the uvs are transformed before sampling each texture
+    #[serde(default)]
+    pub(crate) uv_transforms: BTreeMap<TextureSlot, UvTransform>,
+    /// How the values of each texture are encoded
+    #[serde(default)]
+    pub(crate) color_spaces: BTreeMap<TextureSlot, ColorSpace>,
 }
 
 /// An input to a material property.
@@ -90,9 +99,17 @@ impl PrincipledBSDF {
             roughness,
             metallic,
             normal_map,
+            uv_transforms: BTreeMap::new(),
+            color_spaces: BTreeMap::new(),
         }
     }
 
+    /// The name of the material.
+    #[inline]
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
     /// The base_color of the material.
     ///
     /// https://docs.blender.org/api/blender2.8/bpy.types.Material.html#bpy.types.Material.diffuse_color
@@ -123,3 +140,21 @@ impl PrincipledBSDF {
         self.normal_map.as_ref()
     }
 }
+
+impl PrincipledBSDF {
+    /// Replace the name of every texture that the material uses.
+    pub fn map_texture_names(&mut self, mut map: impl FnMut(&str) -> String) {
+        if let MaterialInput::ImageTexture(name) = &mut self.base_color {
+            *name = map(name);
+        }
+        if let MaterialInput::ImageTexture((name, _)) = &mut self.roughness {
+            *name = map(name);
+        }
+        if let MaterialInput::ImageTexture((name, _)) = &mut self.metallic {
+            *name = map(name);
+        }
+        if let Some(name) = &mut self.normal_map {
+            *name = map(name);
+        }
+    }
+}
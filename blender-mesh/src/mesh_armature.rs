@@ -0,0 +1,208 @@
+use crate::{BlenderMesh, EvaluatePositionsError, VertexAttribute};
+use nalgebra::Matrix4;
+use serde::{Deserialize, Deserializer};
+
+/// An armature that deforms a mesh.
+///
+/// Most meshes are deformed by a single armature, but some are deformed by several, such as a
+/// character whose clothes are driven by a separate cloth rig.
+///
+/// Every armature gets its own range of the mesh's bone indices. A bone influence of
+/// `first_joint + 2` is the armature's joint 2, so the mesh can be skinned by laying the
+/// armatures' palettes out one after the other. See
+/// [`BlenderMesh.method#evaluate_vertex_positions_with_palettes`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct MeshArmature {
+    /// The name of the armature
+    pub name: String,
+    /// The bone index of the armature's first joint.
+    #[serde(default)]
+    pub first_joint: u8,
+    /// The mesh's vertex groups that belong to the armature's bones.
+    #[serde(default)]
+    pub vertex_groups: Vec<String>,
+}
+
+/// Meshes exported before meshes could have several armatures stored their parent armature in
+/// an `armature_name` field that is either null or the armature's name.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ArmaturesOrLegacyName {
+    Armatures(Vec<MeshArmature>),
+    LegacyName(Option<String>),
+}
+
+/// Deserialize [`BlenderMesh.method#armatures`] from either a list of armatures or a legacy
+/// `armature_name`.
+pub(crate) fn deserialize_armatures<'de, D>(deserializer: D) -> Result<Vec<MeshArmature>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let armatures = match ArmaturesOrLegacyName::deserialize(deserializer)? {
+        ArmaturesOrLegacyName::Armatures(armatures) => armatures,
+        ArmaturesOrLegacyName::LegacyName(name) => name
+            .map(|name| MeshArmature {
+                name,
+                ..MeshArmature::default()
+            })
+            .into_iter()
+            .collect(),
+    };
+
+    Ok(armatures)
+}
+
+impl BlenderMesh {
+    /// Every armature that deforms this mesh, starting with its parent armature followed by the
+    /// armatures of its armature modifiers.
+    pub fn armatures(&self) -> &Vec<MeshArmature> {
+        &self.armatures
+    }
+
+    /// See [`BlenderMesh.method#armatures`]
+    pub fn set_armatures(&mut self, armatures: Vec<MeshArmature>) {
+        self.armatures = armatures;
+    }
+
+    /// The armature whose bone a vertex group belongs to, if any.
+    pub fn vertex_group_armature(&self, vertex_group: &str) -> Option<&MeshArmature> {
+        self.armatures.iter().find(|armature| {
+            armature
+                .vertex_groups
+                .iter()
+                .any(|group| group == vertex_group)
+        })
+    }
+
+    /// Lay out a palette of joint matrices for every armature in
+    /// [`BlenderMesh.method#armatures`] so that every bone index of the mesh points at its
+    /// armature's joint.
+    ///
+    /// `palettes` has one palette for each armature, in the same order. Joints that no armature's
+    /// palette covers are left as the identity.
+    pub fn skinning_palette(
+        &self,
+        palettes: &[&[Matrix4<f32>]],
+    ) -> Result<Vec<Matrix4<f32>>, EvaluatePositionsError> {
+        if palettes.len() != self.armatures.len() {
+            return Err(EvaluatePositionsError::PaletteCount {
+                armatures: self.armatures.len(),
+                palettes: palettes.len(),
+            });
+        }
+
+        let joint_count = self
+            .armatures
+            .iter()
+            .zip(palettes.iter())
+            .map(|(armature, palette)| armature.first_joint as usize + palette.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut combined = vec![Matrix4::identity(); joint_count];
+        for (armature, palette) in self.armatures.iter().zip(palettes.iter()) {
+            let first_joint = armature.first_joint as usize;
+            combined[first_joint..first_joint + palette.len()].copy_from_slice(palette);
+        }
+
+        Ok(combined)
+    }
+
+    /// Evaluate where every position ends up after applying the shape keys and then skinning the
+    /// mesh with a palette for each of its armatures.
+    ///
+    /// See [`BlenderMesh.method#skinning_palette`] and
+    /// [`BlenderMesh.method#evaluate_vertex_positions`].
+    pub fn evaluate_vertex_positions_with_palettes(
+        &self,
+        palettes: &[&[Matrix4<f32>]],
+        morph_weights: &[f32],
+    ) -> Result<VertexAttribute<f32>, EvaluatePositionsError> {
+        let pose = self.skinning_palette(palettes)?;
+
+        self.evaluate_vertex_positions(&pose, morph_weights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bone::BoneInfluencesPerVertex;
+    use crate::VertexBoneInfluences;
+    use nalgebra::Vector3;
+
+    /// Verify that meshes that were exported with a legacy `armature_name` deserialize into a
+    /// single armature and serialize back out as a list of armatures.
+    #[test]
+    fn deserialize_legacy_armature_name() {
+        let mut json = serde_json::to_value(BlenderMesh::default()).unwrap();
+        let fields = json.as_object_mut().unwrap();
+        fields.remove("armatures");
+        fields.insert("armature_name".to_string(), "LetterFArmature".into());
+
+        let mesh: BlenderMesh = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(
+            mesh.armatures(),
+            &vec![MeshArmature {
+                name: "LetterFArmature".to_string(),
+                ..MeshArmature::default()
+            }]
+        );
+
+        let round_tripped = serde_json::to_string(&mesh).unwrap();
+        assert!(!round_tripped.contains("armature_name"));
+        let round_tripped: BlenderMesh = serde_json::from_str(&round_tripped).unwrap();
+        assert_eq!(round_tripped, mesh);
+
+        json.as_object_mut()
+            .unwrap()
+            .insert("armature_name".to_string(), serde_json::Value::Null);
+        let mesh: BlenderMesh = serde_json::from_value(json).unwrap();
+        assert!(mesh.armatures().is_empty());
+    }
+
+    /// Verify that every armature's palette moves the vertices that its joints influence.
+    #[test]
+    fn skin_with_multiple_armatures() {
+        let mut plane = BlenderMesh::plane(2., 2., 0);
+        plane.set_armatures(vec![
+            MeshArmature {
+                name: "Body".to_string(),
+                first_joint: 0,
+                vertex_groups: vec!["Hips".to_string(), "Spine".to_string()],
+            },
+            MeshArmature {
+                name: "Cloth".to_string(),
+                first_joint: 2,
+                vertex_groups: vec!["Cape".to_string()],
+            },
+        ]);
+        plane.multi_indexed_vertex_attributes.bone_influences = Some(VertexBoneInfluences {
+            bones_per_vertex: BoneInfluencesPerVertex::Uniform(1),
+            bone_indices: vec![0, 1, 2, 2],
+            bone_weights: vec![1.; 4],
+        });
+
+        assert_eq!(plane.armature_name(), Some(&"Body".to_string()));
+        assert_eq!(plane.vertex_group_armature("Cape").unwrap().name, "Cloth");
+        assert!(plane.vertex_group_armature("Tail").is_none());
+
+        let up = Matrix4::new_translation(&Vector3::new(0., 0., 1.));
+        let body = [Matrix4::identity(), up];
+        let cloth = [up * up];
+
+        let positions = plane
+            .evaluate_vertex_positions_with_palettes(&[&body, &cloth], &[])
+            .unwrap();
+        let heights: Vec<f32> = positions.iter_vertices().map(|vertex| vertex[2]).collect();
+        assert_eq!(heights, vec![0., 1., 2., 2.]);
+
+        assert_eq!(
+            plane.skinning_palette(&[&body]),
+            Err(EvaluatePositionsError::PaletteCount {
+                armatures: 2,
+                palettes: 1
+            })
+        );
+    }
+}
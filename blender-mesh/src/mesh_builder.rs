@@ -0,0 +1,438 @@
+use crate::bone::BoneInfluencesPerVertex;
+use crate::{
+    BlenderMesh, BoundingBox, IndexedAttribute, MultiIndexedVertexAttributes, VertexAttribute,
+    VertexBoneInfluences,
+};
+use nalgebra::Point3;
+
+/// The indices of a face corner's data within a [`BlenderMeshBuilder`].
+///
+/// [`BlenderMeshBuilder`]: struct.BlenderMeshBuilder.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct FaceCorner {
+    /// The index returned by [`BlenderMeshBuilder.method#add_position`]. Corners that share a
+    /// position are the same vertex.
+    pub position: u32,
+    /// The index returned by [`BlenderMeshBuilder.method#add_normal`].
+    pub normal: Option<u32>,
+    /// The index returned by [`BlenderMeshBuilder.method#add_uv`].
+    pub uv: Option<u32>,
+}
+
+/// An error while building a [`BlenderMesh`].
+///
+/// [`BlenderMesh`]: struct.BlenderMesh.html
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum MeshBuilderError {
+    /// A face has fewer than 3 corners or more than 255 corners.
+    #[error("Face {face} has {corners} corners, faces need between 3 and 255 corners")]
+    InvalidCornerCount {
+        /// The index of the face
+        face: usize,
+        /// The number of corners
+        corners: usize,
+    },
+    /// A corner points past the end of the positions, normals or uvs.
+    #[error("Face {face} uses {attribute} {index} but there are only {count}")]
+    IndexOutOfBounds {
+        /// The index of the face
+        face: usize,
+        /// `positions`, `normals` or `uvs`
+        attribute: &'static str,
+        /// The out of bounds index
+        index: u32,
+        /// The number of positions, normals or uvs
+        count: usize,
+    },
+    /// Some corners have a normal or uv while others don't. Every corner must have them or none
+    /// can.
+    #[error("Face {face} has corners with and without {attribute}")]
+    InconsistentCorners {
+        /// The index of the first face with a corner that differs from the first corner
+        face: usize,
+        /// `normals` or `uvs`
+        attribute: &'static str,
+    },
+    /// Bone influences were set for a position that doesn't exist.
+    #[error("Bone influences were set for position {position} but there are only {count}")]
+    InfluenceOutOfBounds {
+        /// The position index
+        position: u32,
+        /// The number of positions
+        count: usize,
+    },
+    /// A bone weight is negative or not finite.
+    #[error("Position {position} has an invalid bone weight {weight}")]
+    InvalidWeight {
+        /// The position index
+        position: u32,
+        /// The invalid weight
+        weight: f32,
+    },
+}
+
+/// Constructs a [`BlenderMesh`] a face at a time, for procedural tools and tests that need
+/// meshes without exporting them from Blender.
+///
+/// Positions, normals and uvs are added separately and then referenced by each face's corners,
+/// the same way that Blender shares data between faces. [`BlenderMeshBuilder.method#build`]
+/// checks that everything the faces reference exists, so the mesh upholds the invariants that
+/// the rest of this crate relies on.
+///
+/// ```
+/// # use blender_mesh::{BlenderMeshBuilder, CreateSingleIndexConfig, FaceCorner};
+/// let mut builder = BlenderMeshBuilder::new("Triangle");
+///
+/// let up = builder.add_normal([0., 0., 1.]);
+/// let corners: Vec<FaceCorner> = [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]
+///     .iter()
+///     .map(|position| FaceCorner {
+///         position: builder.add_position(*position),
+///         normal: Some(up),
+///         uv: None,
+///     })
+///     .collect();
+/// builder.add_face(&corners, 0);
+///
+/// let mut mesh = builder.build().unwrap();
+/// let single = mesh.combine_vertex_indices(&CreateSingleIndexConfig::default());
+/// assert_eq!(single.vertices().len(), 3);
+/// ```
+///
+/// [`BlenderMesh`]: struct.BlenderMesh.html
+#[derive(Debug, Clone, Default)]
+pub struct BlenderMeshBuilder {
+    name: String,
+    positions: Vec<f32>,
+    normals: Vec<f32>,
+    uvs: Vec<f32>,
+    corners_in_each_face: Vec<usize>,
+    material_index: Vec<u16>,
+    corners: Vec<FaceCorner>,
+    bone_influences: Vec<(u32, Vec<(u8, f32)>)>,
+    armature_name: Option<String>,
+}
+
+impl BlenderMeshBuilder {
+    /// Start building a mesh with this name.
+    pub fn new(name: &str) -> Self {
+        BlenderMeshBuilder {
+            name: name.to_string(),
+            ..BlenderMeshBuilder::default()
+        }
+    }
+
+    /// Add a position and return its index. Every position is one of the mesh's vertices.
+    pub fn add_position(&mut self, position: [f32; 3]) -> u32 {
+        self.positions.extend_from_slice(&position);
+        (self.positions.len() / 3 - 1) as u32
+    }
+
+    /// Add a normal and return its index.
+    pub fn add_normal(&mut self, normal: [f32; 3]) -> u32 {
+        self.normals.extend_from_slice(&normal);
+        (self.normals.len() / 3 - 1) as u32
+    }
+
+    /// Add a uv and return its index.
+    pub fn add_uv(&mut self, uv: [f32; 2]) -> u32 {
+        self.uvs.extend_from_slice(&uv);
+        (self.uvs.len() / 2 - 1) as u32
+    }
+
+    /// Add a face that uses one of the mesh's materials.
+    ///
+    /// Corners should be in counter clockwise order when looking at the front of the face.
+    pub fn add_face(&mut self, corners: &[FaceCorner], material_index: u16) -> &mut Self {
+        self.corners_in_each_face.push(corners.len());
+        self.material_index.push(material_index);
+        self.corners.extend_from_slice(corners);
+        self
+    }
+
+    /// Set the bones, as `(bone index, weight)`, that deform the vertex at a position, replacing
+    /// any influences that were already set for it. Vertices without any influences aren't
+    /// deformed.
+    pub fn set_bone_influences(&mut self, position: u32, influences: &[(u8, f32)]) -> &mut Self {
+        self.bone_influences
+            .retain(|(existing, _)| *existing != position);
+        self.bone_influences.push((position, influences.to_vec()));
+        self
+    }
+
+    /// Deform the mesh by the armature with this name. See [`BlenderMesh.method#armature_name`].
+    pub fn set_armature_name(&mut self, armature_name: Option<String>) -> &mut Self {
+        self.armature_name = armature_name;
+        self
+    }
+
+    /// Check every face and bone influence and create the mesh.
+    pub fn build(self) -> Result<BlenderMesh, MeshBuilderError> {
+        self.validate()?;
+
+        let position_count = self.positions.len() / 3;
+        let bounding_box = bounding_box(&self.positions);
+        let first = self.corners.first().cloned().unwrap_or_default();
+
+        let corners = &self.corners;
+        let indexed = |data: Vec<f32>, size: u8, index: fn(&FaceCorner) -> Option<u32>| {
+            let indices = corners.iter().filter_map(index).collect();
+            IndexedAttribute::new(indices, VertexAttribute::new(data, size).unwrap())
+        };
+
+        let positions = indexed(self.positions, 3, |corner| Some(corner.position));
+        let normals = match first.normal.is_some() {
+            true => Some(indexed(self.normals, 3, |corner| corner.normal)),
+            false => None,
+        };
+        let uvs = match first.uv.is_some() {
+            true => Some(indexed(self.uvs, 2, |corner| corner.uv)),
+            false => None,
+        };
+
+        let bone_influences = match self.bone_influences.is_empty() {
+            true => None,
+            false => {
+                let mut per_position = vec![vec![]; position_count];
+                for (position, influences) in self.bone_influences.iter() {
+                    per_position[*position as usize] = influences.clone();
+                }
+
+                Some(VertexBoneInfluences {
+                    bones_per_vertex: BoneInfluencesPerVertex::NonUniform(
+                        per_position
+                            .iter()
+                            .map(|influences| influences.len() as u8)
+                            .collect(),
+                    ),
+                    bone_indices: per_position
+                        .iter()
+                        .flatten()
+                        .map(|(bone, _)| *bone)
+                        .collect(),
+                    bone_weights: per_position
+                        .iter()
+                        .flatten()
+                        .map(|(_, weight)| *weight)
+                        .collect(),
+                })
+            }
+        };
+
+        let mut mesh = BlenderMesh {
+            name: self.name,
+            bounding_box,
+            multi_indexed_vertex_attributes: MultiIndexedVertexAttributes {
+                vertices_in_each_face: self
+                    .corners_in_each_face
+                    .iter()
+                    .map(|corners| *corners as u8)
+                    .collect(),
+                material_index: self.material_index,
+                positions,
+                normals,
+                uvs,
+                lightmap_uvs: None,
+                bone_influences,
+            },
+            ..BlenderMesh::default()
+        };
+        mesh.set_armature_name(self.armature_name);
+
+        Ok(mesh)
+    }
+
+    fn validate(&self) -> Result<(), MeshBuilderError> {
+        let counts = [
+            ("positions", self.positions.len() / 3),
+            ("normals", self.normals.len() / 3),
+            ("uvs", self.uvs.len() / 2),
+        ];
+        let first = self.corners.first().cloned().unwrap_or_default();
+
+        let mut start = 0;
+        for (face, corner_count) in self.corners_in_each_face.iter().enumerate() {
+            if *corner_count < 3 || *corner_count > 255 {
+                return Err(MeshBuilderError::InvalidCornerCount {
+                    face,
+                    corners: *corner_count,
+                });
+            }
+
+            for corner in self.corners[start..start + corner_count].iter() {
+                if corner.normal.is_some() != first.normal.is_some() {
+                    return Err(MeshBuilderError::InconsistentCorners {
+                        face,
+                        attribute: "normals",
+                    });
+                }
+                if corner.uv.is_some() != first.uv.is_some() {
+                    return Err(MeshBuilderError::InconsistentCorners {
+                        face,
+                        attribute: "uvs",
+                    });
+                }
+
+                let indices = [Some(corner.position), corner.normal, corner.uv];
+                for ((attribute, count), index) in counts.iter().zip(indices.iter()) {
+                    if let Some(index) = index.filter(|index| *index as usize >= *count) {
+                        return Err(MeshBuilderError::IndexOutOfBounds {
+                            face,
+                            attribute,
+                            index,
+                            count: *count,
+                        });
+                    }
+                }
+            }
+
+            start += corner_count;
+        }
+
+        for (position, influences) in self.bone_influences.iter() {
+            if *position as usize >= counts[0].1 {
+                return Err(MeshBuilderError::InfluenceOutOfBounds {
+                    position: *position,
+                    count: counts[0].1,
+                });
+            }
+
+            if let Some((_, weight)) = influences
+                .iter()
+                .find(|(_, weight)| !weight.is_finite() || *weight < 0.)
+            {
+                return Err(MeshBuilderError::InvalidWeight {
+                    position: *position,
+                    weight: *weight,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn bounding_box(positions: &[f32]) -> BoundingBox {
+    let mut min_corner = Point3::new(f32::MAX, f32::MAX, f32::MAX);
+    let mut max_corner = Point3::new(f32::MIN, f32::MIN, f32::MIN);
+
+    for position in positions.chunks(3) {
+        for axis in 0..3 {
+            min_corner[axis] = min_corner[axis].min(position[axis]);
+            max_corner[axis] = max_corner[axis].max(position[axis]);
+        }
+    }
+
+    BoundingBox {
+        min_corner,
+        max_corner,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CreateSingleIndexConfig;
+
+    /// Verify that a skinned quad and triangle can be built and combined into single indexed
+    /// vertices.
+    #[test]
+    fn build_skinned_mesh() {
+        let mut builder = BlenderMeshBuilder::new("Skinned");
+        let positions: Vec<u32> = [[0., 0., 0.], [1., 0., 0.], [1., 1., 0.], [0., 1., 0.]]
+            .iter()
+            .map(|position| builder.add_position(*position))
+            .collect();
+        let up = builder.add_normal([0., 0., 1.]);
+        let uv = builder.add_uv([0.5, 0.5]);
+        let corner = |position: u32| FaceCorner {
+            position,
+            normal: Some(up),
+            uv: Some(uv),
+        };
+
+        builder
+            .add_face(&[corner(0), corner(1), corner(2), corner(3)], 0)
+            .add_face(&[corner(0), corner(2), corner(3)], 1)
+            .set_bone_influences(positions[2], &[(0, 0.25), (3, 0.75)])
+            .set_armature_name(Some("Rig".to_string()));
+
+        let mut mesh = builder.build().unwrap();
+
+        assert_eq!(mesh.armature_name(), Some(&"Rig".to_string()));
+        assert_eq!(mesh.bounding_box().max_corner, Point3::new(1., 1., 0.));
+        let multi = mesh.multi_indexed_vertex_attributes();
+        assert_eq!(multi.vertices_in_each_face(), &vec![4, 3]);
+        assert_eq!(multi.material_index(), &vec![0, 1]);
+        assert_eq!(
+            multi.bone_influences().unwrap().influences_per_vertex()[2],
+            (&[0, 3][..], &[0.25, 0.75][..])
+        );
+
+        let single = mesh
+            .try_combine_vertex_indices(&CreateSingleIndexConfig {
+                bone_influences_per_vertex: Some(4),
+                calculate_face_tangents: true,
+            })
+            .unwrap();
+        assert_eq!(single.indices().len(), 9);
+    }
+
+    /// Verify that faces referencing data that doesn't exist, or mixing corners with and without
+    /// normals, are rejected.
+    #[test]
+    fn build_errors() {
+        let mut builder = BlenderMeshBuilder::new("Invalid");
+        builder.add_position([0., 0., 0.]);
+        builder.add_normal([0., 0., 1.]);
+        let corner = FaceCorner {
+            position: 0,
+            normal: Some(0),
+            uv: None,
+        };
+
+        let mut out_of_bounds = builder.clone();
+        out_of_bounds.add_face(
+            &[
+                corner,
+                corner,
+                FaceCorner {
+                    position: 3,
+                    ..corner
+                },
+            ],
+            0,
+        );
+        assert_eq!(
+            out_of_bounds.build().unwrap_err(),
+            MeshBuilderError::IndexOutOfBounds {
+                face: 0,
+                attribute: "positions",
+                index: 3,
+                count: 1
+            }
+        );
+
+        let mut inconsistent = builder;
+        inconsistent
+            .add_face(&[corner, corner, corner], 0)
+            .add_face(
+                &[
+                    corner,
+                    corner,
+                    FaceCorner {
+                        normal: None,
+                        ..corner
+                    },
+                ],
+                0,
+            );
+        assert_eq!(
+            inconsistent.build().unwrap_err(),
+            MeshBuilderError::InconsistentCorners {
+                face: 1,
+                attribute: "normals"
+            }
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use crate::bvh::BvhCache;
+use crate::{BlenderMesh, BoundingBox, MeshKey, MeshesByFilename};
+use nalgebra::{Matrix4, Vector3};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// A mesh that [`deduplicate_meshes`] removed because another mesh has the same contents, so it
+/// is drawn by placing the canonical mesh instead.
+///
+/// [`deduplicate_meshes`]: fn.deduplicate_meshes.html
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeshInstance {
+    pub(crate) filename: String,
+    pub(crate) mesh: MeshKey,
+    pub(crate) canonical_filename: String,
+    pub(crate) canonical_mesh: MeshKey,
+    pub(crate) transform: Matrix4<f64>,
+}
+
+impl MeshInstance {
+    /// The file that the removed mesh was exported from.
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// The key of the removed mesh.
+    pub fn mesh(&self) -> &MeshKey {
+        &self.mesh
+    }
+
+    /// The file of the mesh that was kept in its place.
+    pub fn canonical_filename(&self) -> &str {
+        &self.canonical_filename
+    }
+
+    /// The key of the mesh that was kept in its place.
+    pub fn canonical_mesh(&self) -> &MeshKey {
+        &self.canonical_mesh
+    }
+
+    /// Where to draw the canonical mesh's positions to put them where the removed mesh was.
+    ///
+    /// This is the removed mesh's [`BlenderMesh.method#world_transform`], or the identity if it
+    /// doesn't have one, followed by the offset to its [`SpaceConvention.method#origin`].
+    pub fn transform(&self) -> &Matrix4<f64> {
+        &self.transform
+    }
+}
+
+impl BlenderMesh {
+    /// A SHA-256 of everything about the mesh except for what identifies it, as lowercase hex.
+    ///
+    /// Meshes with the same hash have the same geometry, materials and attributes, even if they
+    /// have different names, came from different files or are placed differently. So the
+    /// [`BlenderMesh.method#world_transform`], the [`SpaceConvention.method#origin`] and the
+    /// bounding box, which Blender exports in world space, are left out.
+    pub fn content_hash(&self) -> String {
+        let mut contents = self.clone();
+        contents.name = String::new();
+        contents.data_name = String::new();
+        contents.library = None;
+        contents.stable_id = None;
+        contents.thumbnail = None;
+        contents.bounding_box = BoundingBox::default();
+        contents.world_transform = None;
+        contents.space.origin = [0., 0., 0.];
+        contents.bvh = BvhCache::default();
+
+        // Maps are serialized in a deterministic order so equal meshes give equal JSON
+        let json = serde_json::to_vec(&contents).unwrap();
+        format!("{:x}", Sha256::digest(&json))
+    }
+}
+
+/// Remove every mesh that has the same [`BlenderMesh.method#content_hash`] as another mesh, such
+/// as a prop that was copied into several `.blend` files, so that batch exports only contain
+/// its geometry once.
+///
+/// Of every group of identical meshes, the one whose file and then key sorts first is kept.
+/// Returns the meshes that were removed along with where to draw the kept mesh in their place,
+/// sorted by file and then key.
+///
+/// Identical geometry is only found when the positions are exactly the same, so deduplicate
+/// before rebasing meshes onto different origins with [`rebase_origins`].
+///
+/// [`rebase_origins`]: fn.rebase_origins.html
+pub fn deduplicate_meshes(meshes_by_filename: &mut MeshesByFilename) -> Vec<MeshInstance> {
+    let mut meshes: Vec<(String, MeshKey, String)> = meshes_by_filename
+        .iter()
+        .flat_map(|(filename, meshes)| {
+            meshes
+                .iter()
+                .map(move |(key, mesh)| (filename.clone(), key.clone(), mesh.content_hash()))
+        })
+        .collect();
+    meshes.sort();
+
+    let mut canonical: BTreeMap<String, (String, MeshKey)> = BTreeMap::new();
+    let mut instances = vec![];
+
+    for (filename, key, hash) in meshes {
+        match canonical.get(&hash) {
+            Some((canonical_filename, canonical_mesh)) => {
+                let mesh = meshes_by_filename
+                    .get_mut(&filename)
+                    .unwrap()
+                    .remove(&key)
+                    .unwrap();
+
+                // The canonical mesh has the same positions relative to its own origin
+                let world_transform = mesh.world_transform.unwrap_or_else(Matrix4::identity);
+                let origin = Vector3::from(mesh.space.origin);
+
+                instances.push(MeshInstance {
+                    filename,
+                    mesh: key,
+                    canonical_filename: canonical_filename.clone(),
+                    canonical_mesh: canonical_mesh.clone(),
+                    transform: world_transform * Matrix4::new_translation(&origin),
+                });
+            }
+            None => {
+                canonical.insert(hash, (filename, key));
+            }
+        };
+    }
+
+    instances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that copies of a mesh in other files are replaced by instances of the first copy,
+    /// and that meshes with different contents are kept.
+    #[test]
+    fn deduplicate_copies_across_files() {
+        let mut moved_crate = BlenderMesh::cube(1.);
+        moved_crate.apply_transform_f64(&Matrix4::new_translation(&Vector3::new(5., 0., 0.)));
+
+        let mut meshes = MeshesByFilename::new();
+        meshes.insert(
+            "a.blend".to_string(),
+            vec![
+                ("Crate".into(), BlenderMesh::cube(1.)),
+                ("Big Crate".into(), BlenderMesh::cube(2.)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        meshes.insert(
+            "b.blend".to_string(),
+            vec![("Crate.001".into(), moved_crate)]
+                .into_iter()
+                .collect(),
+        );
+
+        let instances = deduplicate_meshes(&mut meshes);
+
+        assert_eq!(
+            instances,
+            vec![MeshInstance {
+                filename: "b.blend".to_string(),
+                mesh: "Crate.001".into(),
+                canonical_filename: "a.blend".to_string(),
+                canonical_mesh: "Crate".into(),
+                transform: Matrix4::new_translation(&Vector3::new(5., 0., 0.)),
+            }]
+        );
+        assert_eq!(meshes["a.blend"].len(), 2);
+        assert!(meshes["b.blend"].is_empty());
+    }
+
+    /// Verify that copies of a mesh that were placed at different spots in the scene are
+    /// deduplicated, even though their world space bounding boxes differ, and that every
+    /// instance keeps its placement.
+    #[test]
+    fn deduplicate_translated_copies() {
+        let placed = |x: f64| {
+            let translation = Matrix4::new_translation(&Vector3::new(x, 0., 0.));
+
+            // Blender exports local positions with a world space bounding box
+            let mut world_cube = BlenderMesh::cube(1.);
+            world_cube.apply_transform(&translation.map(|value| value as f32));
+
+            let mut cube = BlenderMesh::cube(1.);
+            cube.set_bounding_box(world_cube.bounding_box());
+            cube.set_world_transform(Some(translation));
+            cube
+        };
+
+        let mut meshes = MeshesByFilename::new();
+        meshes.insert(
+            "level.blend".to_string(),
+            vec![
+                ("Crate".into(), placed(2.)),
+                ("Crate.001".into(), placed(7.)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let instances = deduplicate_meshes(&mut meshes);
+
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0].canonical_mesh(), &MeshKey::from("Crate"));
+        assert_eq!(instances[0].mesh(), &MeshKey::from("Crate.001"));
+        assert_eq!(
+            instances[0].transform(),
+            &Matrix4::new_translation(&Vector3::new(7., 0., 0.))
+        );
+        assert_eq!(meshes["level.blend"].len(), 1);
+    }
+}
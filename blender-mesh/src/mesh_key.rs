@@ -0,0 +1,139 @@
+use crate::BlenderMesh;
+use std::borrow::Borrow;
+use std::fmt::{Display, Formatter};
+
+/// Identifies a mesh within the `.blend` file that it was exported from.
+///
+/// Object names are unique within a `.blend` file, except that objects that are linked in from
+/// another file can have the same name as a local object. So, like Blender's `name_full`, keys of
+/// linked objects end with the path of their library, such as `Rock [/assets/props.blend]`.
+///
+/// Keys of local objects are just the object's name, so maps keyed by `MeshKey` can be indexed
+/// with a `&str` object name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MeshKey(String);
+
+impl MeshKey {
+    /// The key of an object, with the absolute path of its library if it is linked.
+    pub fn new(object: &str, library: Option<&str>) -> Self {
+        match library {
+            Some(library) => MeshKey(format!("{} [{}]", object, library)),
+            None => MeshKey(object.to_string()),
+        }
+    }
+
+    /// The name of the mesh's object.
+    pub fn object(&self) -> &str {
+        match self.split_library() {
+            Some((object, _)) => object,
+            None => &self.0,
+        }
+    }
+
+    /// The absolute path of the library that the object was linked from, or `None` if the
+    /// object is local to the file that it was exported from.
+    pub fn library(&self) -> Option<&str> {
+        self.split_library().map(|(_, library)| library)
+    }
+
+    /// The key as one string, such as `Rock` or `Rock [/assets/props.blend]`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn split_library(&self) -> Option<(&str, &str)> {
+        let without_bracket = self.0.strip_suffix(']')?;
+        let library_start = without_bracket.rfind(" [")?;
+
+        Some((
+            &without_bracket[..library_start],
+            &without_bracket[library_start + 2..],
+        ))
+    }
+}
+
+impl Borrow<str> for MeshKey {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for MeshKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The key of a local object.
+impl From<&str> for MeshKey {
+    fn from(object: &str) -> Self {
+        MeshKey(object.to_string())
+    }
+}
+
+/// The key of a local object.
+impl From<String> for MeshKey {
+    fn from(object: String) -> Self {
+        MeshKey(object)
+    }
+}
+
+impl BlenderMesh {
+    /// The name of the mesh datablock that the object uses, which can differ from the object's
+    /// [`BlenderMesh.method#name`] and is shared by every object that instances the same mesh.
+    ///
+    /// Empty for meshes that were exported by older versions of landon.
+    pub fn data_name(&self) -> &String {
+        &self.data_name
+    }
+
+    /// The absolute path of the `.blend` file that the mesh's object was linked from, or `None`
+    /// if the object is local to the file that it was exported from.
+    pub fn library(&self) -> Option<&String> {
+        self.library.as_ref()
+    }
+
+    /// The id that identifies the mesh's object even after it has been renamed in Blender, so that
+    /// it can be baked into saved games and prefabs.
+    ///
+    /// Assigned by `landon export --assign-stable-ids`, unique within the `.blend` file that the
+    /// object belongs to. `None` for objects that haven't been assigned an id.
+    pub fn stable_id(&self) -> Option<u32> {
+        self.stable_id
+    }
+
+    /// Set the stable id of the mesh's object.
+    pub fn set_stable_id(&mut self, stable_id: Option<u32>) {
+        self.stable_id = stable_id;
+    }
+
+    /// The key that identifies the mesh within the file that it was exported from.
+    pub fn key(&self) -> MeshKey {
+        MeshKey::new(&self.name, self.library.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Verify that linked objects don't collide with local objects that have the same name, and
+    /// that local objects can be looked up by name.
+    #[test]
+    fn linked_and_local_keys() {
+        let local = MeshKey::new("Rock", None);
+        let linked = MeshKey::new("Rock", Some("/assets/props.blend"));
+
+        assert_ne!(local, linked);
+        assert_eq!(linked.as_str(), "Rock [/assets/props.blend]");
+        assert_eq!(linked.object(), "Rock");
+        assert_eq!(linked.library(), Some("/assets/props.blend"));
+        assert_eq!(local.library(), None);
+
+        let meshes: HashMap<MeshKey, u8> = vec![(local, 1), (linked, 2)].into_iter().collect();
+        assert_eq!(meshes["Rock"], 1);
+        assert_eq!(meshes["Rock [/assets/props.blend]"], 2);
+    }
+}
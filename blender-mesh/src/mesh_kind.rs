@@ -0,0 +1,106 @@
+use crate::BlenderMesh;
+
+/// What a mesh's positions are connected into, which decides how its indices should be drawn.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MeshKind {
+    /// Positions that aren't connected to each other, such as a point cloud.
+    Points,
+    /// Positions that are connected by edges but not faces, such as a wireframe gizmo.
+    Edges,
+    /// Positions that are connected by faces, which become triangles when combining indices.
+    #[default]
+    Triangles,
+}
+
+impl BlenderMesh {
+    /// What the mesh's positions are connected into.
+    ///
+    /// A mesh with any faces is [`MeshKind::Triangles`], even if it also has loose edges, and a
+    /// mesh without faces is [`MeshKind::Edges`] if it has loose edges and [`MeshKind::Points`]
+    /// otherwise.
+    ///
+    /// [`MeshKind::Triangles`]: enum.MeshKind.html#variant.Triangles
+    /// [`MeshKind::Edges`]: enum.MeshKind.html#variant.Edges
+    /// [`MeshKind::Points`]: enum.MeshKind.html#variant.Points
+    pub fn kind(&self) -> MeshKind {
+        match (
+            self.multi_indexed_vertex_attributes
+                .vertices_in_each_face
+                .is_empty(),
+            self.edges.is_empty(),
+        ) {
+            (false, _) => MeshKind::Triangles,
+            (true, false) => MeshKind::Edges,
+            (true, true) => MeshKind::Points,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vertex_attributes::{IndexedAttribute, MultiIndexedVertexAttributes};
+    use crate::{CreateSingleIndexConfig, VertexAttribute};
+
+    fn loose_positions() -> BlenderMesh {
+        BlenderMesh {
+            multi_indexed_vertex_attributes: MultiIndexedVertexAttributes {
+                positions: IndexedAttribute::new(
+                    vec![],
+                    VertexAttribute::new(vec![0., 0., 0., 1., 0., 0., 1., 1., 0.], 3).unwrap(),
+                ),
+                ..MultiIndexedVertexAttributes::default()
+            },
+            ..BlenderMesh::default()
+        }
+    }
+
+    /// Verify that a mesh without faces keeps one vertex per position and is indexed as points.
+    #[test]
+    fn combine_points() {
+        let mut points = loose_positions();
+        assert_eq!(points.kind(), MeshKind::Points);
+
+        points.y_up();
+        let single = points.combine_vertex_indices(&CreateSingleIndexConfig {
+            bone_influences_per_vertex: None,
+            calculate_face_tangents: true,
+        });
+
+        assert_eq!(single.kind(), MeshKind::Points);
+        assert_eq!(single.indices(), &vec![0, 1, 2]);
+        assert_eq!(single.vertices()[2].position(), [1., 0., -1.]);
+        assert_eq!(single.normals(), None);
+        assert_eq!(single.face_tangents(), None);
+    }
+
+    /// Verify that a mesh without faces is indexed as lines along its loose edges.
+    #[test]
+    fn combine_edges() {
+        let mut wire = loose_positions();
+        wire.edges = vec![[0, 1], [1, 2]];
+        assert_eq!(wire.kind(), MeshKind::Edges);
+
+        let single = wire.combine_vertex_indices(&CreateSingleIndexConfig::default());
+
+        assert_eq!(single.kind(), MeshKind::Edges);
+        assert_eq!(single.indices(), &vec![0, 1, 1, 2]);
+        assert_eq!(single.vertices().len(), 3);
+    }
+
+    /// Verify that faces without normals are combined into triangles without normals.
+    #[test]
+    fn combine_triangles_without_normals() {
+        let mut plane = BlenderMesh::plane(2., 2., 0);
+        plane.multi_indexed_vertex_attributes.normals = None;
+        plane.edges = vec![[0, 3]];
+        assert_eq!(plane.kind(), MeshKind::Triangles);
+
+        plane.y_up();
+        let single = plane.combine_vertex_indices(&CreateSingleIndexConfig::default());
+
+        assert_eq!(single.kind(), MeshKind::Triangles);
+        assert_eq!(single.indices().len(), 6);
+        assert_eq!(single.normals(), None);
+    }
+}
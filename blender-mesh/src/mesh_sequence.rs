@@ -0,0 +1,484 @@
+use crate::BlenderMesh;
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub type MeshSequencesByFilename = HashMap<String, MeshSequencesByMeshName>;
+pub type MeshSequencesByMeshName = HashMap<String, MeshSequence>;
+
+/// The first four bytes of a mesh sequence file
+pub const MESH_SEQUENCE_MAGIC: [u8; 4] = *b"LNSQ";
+
+/// The version of the mesh sequence file layout that [`MeshSequence.method#to_bytes`] writes.
+pub const MESH_SEQUENCE_VERSION: u16 = 1;
+
+/// Magic, version and the length of the header
+const PREAMBLE_LEN: usize = 10;
+
+/// A mesh on every frame of an effect whose topology changes from frame to frame, such as a
+/// fluid simulation or an Alembic cache, where the positions can't be baked into a single
+/// [`PointCache`].
+///
+/// Every frame is a complete [`BlenderMesh`] with its modifiers applied.
+///
+/// [`PointCache`]: struct.PointCache.html
+/// [`BlenderMesh`]: struct.BlenderMesh.html
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct MeshSequence {
+    pub(crate) name: String,
+    pub(crate) frames_per_second: u8,
+    pub(crate) frame_range_inclusive: (u16, u16),
+    pub(crate) frames: Vec<BlenderMesh>,
+}
+
+/// Everything about a [`MeshSequence`] other than its meshes, stored at the start of a mesh
+/// sequence file along with where every frame's mesh is.
+///
+/// [`MeshSequence`]: struct.MeshSequence.html
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct MeshSequenceHeader {
+    name: String,
+    frames_per_second: u8,
+    frame_range_inclusive: (u16, u16),
+    /// The offset and length of every frame's mesh, relative to the end of the header
+    frames: Vec<(u64, u64)>,
+}
+
+/// An error while reading a mesh sequence file.
+#[derive(Debug, thiserror::Error)]
+pub enum MeshSequenceError {
+    /// The file doesn't start with [`MESH_SEQUENCE_MAGIC`].
+    #[error("The file starts with {0:?} instead of LNSQ, so it isn't a mesh sequence")]
+    BadMagic([u8; 4]),
+    /// The file was written by a newer version of the mesh sequence layout.
+    #[error(
+        "Version {0} is not supported, the newest supported version is {}",
+        MESH_SEQUENCE_VERSION
+    )]
+    UnsupportedVersion(u16),
+    /// The file ends before its header or one of its frames does.
+    #[error("The file is {len} bytes but needs at least {needed} bytes")]
+    TooShort {
+        /// The number of bytes needed
+        needed: usize,
+        /// The number of bytes in the file
+        len: usize,
+    },
+    /// The sequence doesn't have a frame at this index.
+    #[error("There is no frame {idx}, the sequence has {frame_count} frames")]
+    FrameOutOfBounds {
+        /// The index of the frame
+        idx: usize,
+        /// The number of frames in the sequence
+        frame_count: usize,
+    },
+    /// The header or a frame's mesh couldn't be deserialized.
+    #[error("Could not deserialize the mesh sequence: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The file couldn't be opened or memory mapped.
+    #[error("Could not read the mesh sequence file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl MeshSequence {
+    #[allow(missing_docs)]
+    pub fn new(
+        name: String,
+        frames_per_second: u8,
+        frame_range_inclusive: (u16, u16),
+        frames: Vec<BlenderMesh>,
+    ) -> Self {
+        MeshSequence {
+            name,
+            frames_per_second,
+            frame_range_inclusive,
+            frames,
+        }
+    }
+
+    /// The name of the mesh
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// The frames per second of the scene that the sequence was exported from
+    pub fn frames_per_second(&self) -> u8 {
+        self.frames_per_second
+    }
+
+    /// The first and last frame of the sequence
+    pub fn frame_range_inclusive(&self) -> (u16, u16) {
+        self.frame_range_inclusive
+    }
+
+    /// The mesh on every frame, in order
+    pub fn frames(&self) -> &Vec<BlenderMesh> {
+        &self.frames
+    }
+
+    /// How long the sequence takes to play
+    pub fn duration(&self) -> Duration {
+        sequence_duration(self.frames.len(), self.frames_per_second)
+    }
+
+    /// The mesh some amount of time after the first frame. See
+    /// [`MeshSequencePlayer.method#mesh_at`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sequence doesn't have any frames.
+    pub fn mesh_at(&self, elapsed: Duration, should_loop: bool) -> &BlenderMesh {
+        let frame_idx = frame_idx_at(
+            elapsed,
+            self.frames_per_second,
+            self.frames.len(),
+            should_loop,
+        );
+
+        &self.frames[frame_idx]
+    }
+
+    /// Write the sequence as a mesh sequence file, which starts with the sequence's name, frame
+    /// rate and where every frame's mesh is so that [`MeshSequenceFile`] can deserialize one
+    /// frame at a time.
+    ///
+    /// [`MeshSequenceFile`]: struct.MeshSequenceFile.html
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut frames = vec![];
+        let mut frame_bytes = vec![];
+
+        for mesh in self.frames.iter() {
+            let offset = frame_bytes.len() as u64;
+            frame_bytes.extend(serde_json::to_vec(mesh).unwrap());
+            frames.push((offset, frame_bytes.len() as u64 - offset));
+        }
+
+        let header = MeshSequenceHeader {
+            name: self.name.clone(),
+            frames_per_second: self.frames_per_second,
+            frame_range_inclusive: self.frame_range_inclusive,
+            frames,
+        };
+        let header = serde_json::to_vec(&header).unwrap();
+
+        let mut bytes = Vec::with_capacity(PREAMBLE_LEN + header.len() + frame_bytes.len());
+        bytes.extend_from_slice(&MESH_SEQUENCE_MAGIC);
+        bytes.extend_from_slice(&MESH_SEQUENCE_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        bytes.extend(header);
+        bytes.extend(frame_bytes);
+
+        bytes
+    }
+}
+
+/// A mesh sequence file whose frames are only deserialized when they are needed, so that
+/// sequences that are too large to hold in memory can be played back from a memory mapped file.
+///
+/// `B` is usually a `Vec<u8>`, or a `memmap2::Mmap` when the `memmap2` feature is enabled. See
+/// [`MeshSequenceFile.method#open`].
+#[derive(Debug)]
+pub struct MeshSequenceFile<B> {
+    bytes: B,
+    header: MeshSequenceHeader,
+    frames_start: usize,
+}
+
+impl<B: AsRef<[u8]>> MeshSequenceFile<B> {
+    /// Read the header of a file that was written by [`MeshSequence.method#to_bytes`].
+    pub fn from_bytes(bytes: B) -> Result<Self, MeshSequenceError> {
+        let contents = bytes.as_ref();
+
+        if contents.len() < PREAMBLE_LEN {
+            return Err(MeshSequenceError::TooShort {
+                needed: PREAMBLE_LEN,
+                len: contents.len(),
+            });
+        }
+        if contents[0..4] != MESH_SEQUENCE_MAGIC {
+            let mut magic = [0; 4];
+            magic.copy_from_slice(&contents[0..4]);
+            return Err(MeshSequenceError::BadMagic(magic));
+        }
+
+        let version = u16::from_le_bytes([contents[4], contents[5]]);
+        if version > MESH_SEQUENCE_VERSION {
+            return Err(MeshSequenceError::UnsupportedVersion(version));
+        }
+
+        let header_len =
+            u32::from_le_bytes([contents[6], contents[7], contents[8], contents[9]]) as usize;
+        let frames_start = PREAMBLE_LEN + header_len;
+        if contents.len() < frames_start {
+            return Err(MeshSequenceError::TooShort {
+                needed: frames_start,
+                len: contents.len(),
+            });
+        }
+
+        let header: MeshSequenceHeader =
+            serde_json::from_slice(&contents[PREAMBLE_LEN..frames_start])?;
+
+        Ok(MeshSequenceFile {
+            bytes,
+            header,
+            frames_start,
+        })
+    }
+
+    /// The name of the mesh
+    pub fn name(&self) -> &String {
+        &self.header.name
+    }
+
+    /// The frames per second of the scene that the sequence was exported from
+    pub fn frames_per_second(&self) -> u8 {
+        self.header.frames_per_second
+    }
+
+    /// The first and last frame of the sequence
+    pub fn frame_range_inclusive(&self) -> (u16, u16) {
+        self.header.frame_range_inclusive
+    }
+
+    /// The number of frames in the sequence
+    pub fn frame_count(&self) -> usize {
+        self.header.frames.len()
+    }
+
+    /// How long the sequence takes to play
+    pub fn duration(&self) -> Duration {
+        sequence_duration(self.frame_count(), self.frames_per_second())
+    }
+
+    /// Deserialize the mesh of one frame, where 0 is the first frame of the sequence.
+    pub fn frame(&self, idx: usize) -> Result<BlenderMesh, MeshSequenceError> {
+        let (offset, length) =
+            *self
+                .header
+                .frames
+                .get(idx)
+                .ok_or(MeshSequenceError::FrameOutOfBounds {
+                    idx,
+                    frame_count: self.frame_count(),
+                })?;
+
+        let contents = self.bytes.as_ref();
+        let start = self.frames_start + offset as usize;
+        let end = start + length as usize;
+        if contents.len() < end {
+            return Err(MeshSequenceError::TooShort {
+                needed: end,
+                len: contents.len(),
+            });
+        }
+
+        Ok(serde_json::from_slice(&contents[start..end])?)
+    }
+}
+
+#[cfg(feature = "memmap2")]
+impl MeshSequenceFile<memmap2::Mmap> {
+    /// Memory map a mesh sequence file, so that only the frames that get played are read from
+    /// disk.
+    ///
+    /// The file must not be modified while it is mapped.
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, MeshSequenceError> {
+        let file = std::fs::File::open(path)?;
+        // Safety: the caller promises not to modify the file while it is mapped
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+
+        MeshSequenceFile::from_bytes(mmap)
+    }
+}
+
+/// Plays a [`MeshSequenceFile`] back, deserializing a frame's mesh when playback reaches it and
+/// holding onto it until playback moves on to another frame.
+///
+/// [`MeshSequenceFile`]: struct.MeshSequenceFile.html
+#[derive(Debug)]
+pub struct MeshSequencePlayer<B> {
+    file: MeshSequenceFile<B>,
+    should_loop: bool,
+    current: Option<(usize, BlenderMesh)>,
+}
+
+impl<B: AsRef<[u8]>> MeshSequencePlayer<B> {
+    /// Play the sequence, either wrapping back around to the first frame or holding the last
+    /// frame once playback reaches the end.
+    pub fn new(file: MeshSequenceFile<B>, should_loop: bool) -> Self {
+        MeshSequencePlayer {
+            file,
+            should_loop,
+            current: None,
+        }
+    }
+
+    /// The file that is being played
+    pub fn file(&self) -> &MeshSequenceFile<B> {
+        &self.file
+    }
+
+    /// The mesh some amount of time after the first frame.
+    ///
+    /// Meshes aren't interpolated since their topology can change from one frame to the next,
+    /// so this is the mesh of the frame that was most recently reached.
+    pub fn mesh_at(&mut self, elapsed: Duration) -> Result<&BlenderMesh, MeshSequenceError> {
+        let frame_idx = frame_idx_at(
+            elapsed,
+            self.file.frames_per_second(),
+            self.file.frame_count(),
+            self.should_loop,
+        );
+
+        let is_current = match self.current.as_ref() {
+            Some((current_idx, _)) => *current_idx == frame_idx,
+            None => false,
+        };
+        if !is_current {
+            self.current = Some((frame_idx, self.file.frame(frame_idx)?));
+        }
+
+        Ok(&self.current.as_ref().unwrap().1)
+    }
+}
+
+/// Given a buffer of standard output from Blender we parse all of the mesh sequence frames that
+/// were written to stdout by `blender-mesh-to-json.py`, ordering every mesh's frames by their
+/// frame number.
+///
+/// Mesh sequence data in stdout will look like:
+///
+/// START_MESH_SEQUENCE_FRAME_JSON /path/to/file.blend my_mesh_name
+/// {"frame": 1, "frames_per_second": 24, "mesh": {...}}
+/// END_MESH_SEQUENCE_FRAME_JSON /path/to/file.blend my_mesh_name
+///
+/// @see blender-mesh-to-json.py - This is where we write to stdout
+pub fn parse_mesh_sequences_from_blender_stdout(blender_stdout: &str) -> MeshSequencesByFilename {
+    let start_marker = "START_MESH_SEQUENCE_FRAME_JSON";
+    let end_marker = "END_MESH_SEQUENCE_FRAME_JSON";
+
+    let mut frames: HashMap<String, HashMap<String, Vec<MeshSequenceFrame>>> = HashMap::new();
+
+    let mut remaining = blender_stdout;
+
+    while let Some(start_idx) = remaining.find(start_marker) {
+        let end_idx = remaining.find(end_marker).unwrap();
+
+        let mut lines = remaining[start_idx..end_idx].lines();
+
+        let first_line = lines.next().unwrap();
+        let filename = first_line.split(' ').nth(1).unwrap().to_string();
+        let mesh_name = first_line.split(' ').next_back().unwrap().to_string();
+
+        let frame: String = lines.collect();
+        let frame: MeshSequenceFrame = serde_json::from_str(&frame).unwrap();
+
+        frames
+            .entry(filename)
+            .or_default()
+            .entry(mesh_name)
+            .or_default()
+            .push(frame);
+
+        remaining = &remaining[end_idx + end_marker.len()..];
+    }
+
+    frames
+        .into_iter()
+        .map(|(filename, meshes)| {
+            let sequences = meshes
+                .into_iter()
+                .map(|(mesh_name, mut frames)| {
+                    frames.sort_by_key(|frame| frame.frame);
+
+                    let sequence = MeshSequence {
+                        name: mesh_name.clone(),
+                        frames_per_second: frames[0].frames_per_second,
+                        frame_range_inclusive: (frames[0].frame, frames[frames.len() - 1].frame),
+                        frames: frames.into_iter().map(|frame| frame.mesh).collect(),
+                    };
+
+                    (mesh_name, sequence)
+                })
+                .collect();
+
+            (filename, sequences)
+        })
+        .collect()
+}
+
+/// One frame of a mesh sequence in Blender's stdout
+#[derive(Debug, Deserialize)]
+struct MeshSequenceFrame {
+    frame: u16,
+    frames_per_second: u8,
+    mesh: BlenderMesh,
+}
+
+fn sequence_duration(frame_count: usize, frames_per_second: u8) -> Duration {
+    let frame_count = frame_count.saturating_sub(1);
+
+    Duration::from_secs_f32(frame_count as f32 / frames_per_second as f32)
+}
+
+/// The index of the frame that was most recently reached.
+///
+/// Looping sequences play every frame for one frame's worth of time, including the last, before
+/// wrapping back around to the first frame.
+fn frame_idx_at(
+    elapsed: Duration,
+    frames_per_second: u8,
+    frame_count: usize,
+    should_loop: bool,
+) -> usize {
+    let frames_elapsed = (elapsed.as_secs_f32() * frames_per_second as f32).floor() as usize;
+
+    match should_loop {
+        true => frames_elapsed % frame_count.max(1),
+        false => frames_elapsed.min(frame_count.saturating_sub(1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that we play back a sequence from a mesh sequence file, and that the sequence
+    /// file's frames match the sequence that was written.
+    #[test]
+    fn play_back_mesh_sequence_file() {
+        let frames = vec![
+            BlenderMesh::plane(1., 1., 0),
+            BlenderMesh::plane(1., 1., 1),
+            BlenderMesh::plane(1., 1., 2),
+        ];
+        let sequence = MeshSequence::new("Fluid".to_string(), 2, (1, 3), frames.clone());
+
+        let file = MeshSequenceFile::from_bytes(sequence.to_bytes()).unwrap();
+        assert_eq!(file.name(), "Fluid");
+        assert_eq!(file.frame_count(), 3);
+        assert_eq!(file.duration(), Duration::from_secs(1));
+        assert_eq!(file.frame(2).unwrap(), frames[2]);
+        assert!(matches!(
+            file.frame(3),
+            Err(MeshSequenceError::FrameOutOfBounds { idx: 3, .. })
+        ));
+
+        let mut player = MeshSequencePlayer::new(file, true);
+        assert_eq!(
+            player.mesh_at(Duration::from_millis(750)).unwrap(),
+            &frames[1]
+        );
+        assert_eq!(
+            player.mesh_at(Duration::from_millis(1600)).unwrap(),
+            &frames[0]
+        );
+
+        assert_eq!(sequence.mesh_at(Duration::from_secs(5), false), &frames[2]);
+
+        assert!(matches!(
+            MeshSequenceFile::from_bytes(b"{\"name\": \"Fluid\"}".to_vec()),
+            Err(MeshSequenceError::BadMagic(_))
+        ));
+    }
+}
@@ -0,0 +1,318 @@
+//! Small clusters of triangles for mesh shaders and GPU driven culling.
+
+use crate::{MeshKind, SingleIndexedVertexAttributes};
+use nalgebra::{Point3, Vector3};
+use std::collections::HashMap;
+
+/// How many vertices and triangles each meshlet can have.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeshletConfig {
+    /// The most vertices in one meshlet, between 3 and 256 since meshlet triangles index into
+    /// the meshlet's vertices with u8s.
+    pub max_vertices: usize,
+    /// The most triangles in one meshlet.
+    pub max_triangles: usize,
+}
+
+impl Default for MeshletConfig {
+    /// 64 vertices and 124 triangles, which suits most GPUs' mesh shaders.
+    fn default() -> Self {
+        MeshletConfig {
+            max_vertices: 64,
+            max_triangles: 124,
+        }
+    }
+}
+
+/// An error while building meshlets.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum MeshletError {
+    /// Meshlets are made of triangles.
+    #[error("Meshlets can only be built from triangles, not {kind:?}")]
+    NotTriangles {
+        /// What the indices form
+        kind: MeshKind,
+    },
+    /// Every meshlet needs room for at least one triangle.
+    #[error(
+        "Meshlets need between 3 and 256 vertices and at least 1 triangle, not {max_vertices} \
+         vertices and {max_triangles} triangles"
+    )]
+    InvalidLimits {
+        /// See [`MeshletConfig.max_vertices`]
+        max_vertices: usize,
+        /// See [`MeshletConfig.max_triangles`]
+        max_triangles: usize,
+    },
+}
+
+/// A small cluster of neighboring triangles along with the bounds that a GPU can cull it with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Meshlet {
+    pub(crate) vertices: Vec<u16>,
+    pub(crate) triangles: Vec<u8>,
+    pub(crate) center: [f32; 3],
+    pub(crate) radius: f32,
+    pub(crate) cone_apex: [f32; 3],
+    pub(crate) cone_axis: [f32; 3],
+    pub(crate) cone_cutoff: f32,
+}
+
+impl Meshlet {
+    /// The indices of the meshlet's vertices within the mesh's vertices.
+    pub fn vertices(&self) -> &Vec<u16> {
+        &self.vertices
+    }
+
+    /// Three indices into [`Meshlet.method#vertices`] for every triangle, in the same winding
+    /// order as the mesh.
+    pub fn triangles(&self) -> &Vec<u8> {
+        &self.triangles
+    }
+
+    /// The center of a sphere that contains every vertex of the meshlet, for frustum and
+    /// occlusion culling.
+    pub fn center(&self) -> [f32; 3] {
+        self.center
+    }
+
+    /// The radius of the sphere around [`Meshlet.method#center`].
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// The tip of a cone that every triangle in the meshlet faces away from, for backface
+    /// culling the whole meshlet at once.
+    ///
+    /// The meshlet is facing away from the camera if
+    /// `dot(normalize(cone_apex - camera_position), cone_axis) >= cone_cutoff`.
+    pub fn cone_apex(&self) -> [f32; 3] {
+        self.cone_apex
+    }
+
+    /// The direction that the meshlet's triangles face on average, or zero if they don't face
+    /// any direction, such as when the triangles have no area.
+    pub fn cone_axis(&self) -> [f32; 3] {
+        self.cone_axis
+    }
+
+    /// The sine of how far the meshlet's triangles face away from the
+    /// [`Meshlet.method#cone_axis`].
+    ///
+    /// `1.0` if the triangles face in opposite directions, in which case the meshlet can never
+    /// be backface culled.
+    pub fn cone_cutoff(&self) -> f32 {
+        self.cone_cutoff
+    }
+}
+
+impl SingleIndexedVertexAttributes {
+    /// Split the triangles into meshlets, which are stored alongside the vertex data so that
+    /// they're serialized with it. See [`SingleIndexedVertexAttributes.method#meshlets`].
+    ///
+    /// Triangles are added to a meshlet in index order until the next triangle would exceed one
+    /// of the limits, so optimizing the indices for the vertex cache first gives tighter
+    /// meshlets. Any existing meshlets are replaced.
+    pub fn build_meshlets(&mut self, config: &MeshletConfig) -> Result<(), MeshletError> {
+        if self.kind != MeshKind::Triangles {
+            return Err(MeshletError::NotTriangles { kind: self.kind });
+        }
+        if config.max_vertices < 3 || config.max_vertices > 256 || config.max_triangles == 0 {
+            return Err(MeshletError::InvalidLimits {
+                max_vertices: config.max_vertices,
+                max_triangles: config.max_triangles,
+            });
+        }
+
+        let mut meshlets = vec![];
+
+        let mut vertices = vec![];
+        let mut triangles = vec![];
+        let mut local_vertices: HashMap<u16, u8> = HashMap::new();
+
+        for triangle in self.indices.chunks(3) {
+            let new_vertices = triangle
+                .iter()
+                .filter(|vertex| !local_vertices.contains_key(vertex))
+                .count();
+
+            if vertices.len() + new_vertices > config.max_vertices
+                || triangles.len() / 3 == config.max_triangles
+            {
+                meshlets.push(self.meshlet(vertices, triangles));
+
+                vertices = vec![];
+                triangles = vec![];
+                local_vertices.clear();
+            }
+
+            for vertex in triangle {
+                let local = *local_vertices.entry(*vertex).or_insert_with(|| {
+                    vertices.push(*vertex);
+                    (vertices.len() - 1) as u8
+                });
+                triangles.push(local);
+            }
+        }
+
+        if !triangles.is_empty() {
+            meshlets.push(self.meshlet(vertices, triangles));
+        }
+
+        self.meshlets = meshlets;
+
+        Ok(())
+    }
+
+    /// The meshlets from the last call to [`SingleIndexedVertexAttributes.method#build_meshlets`].
+    ///
+    /// Empty if meshlets were never built.
+    pub fn meshlets(&self) -> &Vec<Meshlet> {
+        &self.meshlets
+    }
+
+    fn meshlet(&self, vertices: Vec<u16>, triangles: Vec<u8>) -> Meshlet {
+        let position = |local: u8| {
+            let position = self.vertices[vertices[local as usize] as usize].position;
+            Point3::new(position[0], position[1], position[2])
+        };
+
+        let (mut min, mut max) = (position(0), position(0));
+        for local in 0..vertices.len() {
+            let position = position(local as u8);
+            min = min.inf(&position);
+            max = max.sup(&position);
+        }
+        let center = nalgebra::center(&min, &max);
+        let radius = (0..vertices.len())
+            .map(|local| nalgebra::distance(&center, &position(local as u8)))
+            .fold(0., f32::max);
+
+        // Triangles without any area don't face in any direction so they can't be backface culled
+        // and don't limit the cone
+        let faces: Vec<(Point3<f32>, Vector3<f32>)> = triangles
+            .chunks(3)
+            .filter_map(|triangle| {
+                let [a, b, c] = [
+                    position(triangle[0]),
+                    position(triangle[1]),
+                    position(triangle[2]),
+                ];
+                let normal = (b - a).cross(&(c - a)).try_normalize(f32::EPSILON)?;
+                Some((a, normal))
+            })
+            .collect();
+
+        let axis = faces
+            .iter()
+            .map(|(_, normal)| normal)
+            .sum::<Vector3<f32>>()
+            .try_normalize(f32::EPSILON);
+        let min_dot = axis.map(|axis| {
+            faces
+                .iter()
+                .map(|(_, normal)| normal.dot(&axis))
+                .fold(1., f32::min)
+        });
+
+        let (cone_apex, cone_axis, cone_cutoff) = match (axis, min_dot) {
+            (Some(axis), Some(min_dot)) if min_dot > 0. => {
+                // Move the apex back along the axis until it is behind every triangle's plane
+                let distance = faces
+                    .iter()
+                    .map(|(corner, normal)| (center - corner).dot(normal) / normal.dot(&axis))
+                    .fold(0., f32::max);
+                let apex = center - axis * distance;
+
+                (
+                    [apex.x, apex.y, apex.z],
+                    [axis.x, axis.y, axis.z],
+                    (1. - min_dot * min_dot).sqrt(),
+                )
+            }
+            _ => ([center.x, center.y, center.z], [0.; 3], 1.),
+        };
+
+        Meshlet {
+            vertices,
+            triangles,
+            center: [center.x, center.y, center.z],
+            radius,
+            cone_apex,
+            cone_axis,
+            cone_cutoff,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlenderMesh, CreateSingleIndexConfig};
+
+    /// Verify that every triangle ends up in exactly one meshlet and that no meshlet exceeds the
+    /// limits.
+    #[test]
+    fn meshlets_cover_every_triangle() {
+        let mut sphere = BlenderMesh::uv_sphere(1., 16, 8)
+            .combine_vertex_indices(&CreateSingleIndexConfig::default());
+        let config = MeshletConfig {
+            max_vertices: 32,
+            max_triangles: 20,
+        };
+
+        sphere.build_meshlets(&config).unwrap();
+
+        let mut indices = vec![];
+        for meshlet in sphere.meshlets() {
+            assert!(meshlet.vertices().len() <= 32);
+            assert!(meshlet.triangles().len() / 3 <= 20);
+
+            for local in meshlet.triangles() {
+                let vertex = meshlet.vertices()[*local as usize];
+                indices.push(vertex);
+
+                let position = Vector3::from(sphere.vertices()[vertex as usize].position());
+                let center = Vector3::from(meshlet.center());
+                assert!((position - center).norm() <= meshlet.radius() + 1e-5);
+            }
+        }
+        assert_eq!(&indices, sphere.indices());
+    }
+
+    /// Verify that a flat meshlet's cone points along its normal and culls it when viewed from
+    /// behind.
+    #[test]
+    fn flat_meshlet_cone() {
+        let mut plane = BlenderMesh::plane(2., 2., 1)
+            .combine_vertex_indices(&CreateSingleIndexConfig::default());
+
+        plane.build_meshlets(&MeshletConfig::default()).unwrap();
+
+        let meshlet = &plane.meshlets()[0];
+        assert_eq!(plane.meshlets().len(), 1);
+        assert_eq!(meshlet.cone_axis(), [0., 0., 1.]);
+        assert_eq!(meshlet.cone_cutoff(), 0.);
+
+        let is_culled = |camera: Vector3<f32>| {
+            let apex = Vector3::from(meshlet.cone_apex());
+            (apex - camera)
+                .normalize()
+                .dot(&Vector3::from(meshlet.cone_axis()))
+                >= meshlet.cone_cutoff()
+        };
+        assert!(is_culled(Vector3::new(0.5, 0.5, -3.)));
+        assert!(!is_culled(Vector3::new(0.5, 0.5, 3.)));
+
+        assert_eq!(
+            plane.build_meshlets(&MeshletConfig {
+                max_vertices: 2,
+                max_triangles: 1
+            }),
+            Err(MeshletError::InvalidLimits {
+                max_vertices: 2,
+                max_triangles: 1
+            })
+        );
+    }
+}
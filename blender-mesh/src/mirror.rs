@@ -0,0 +1,247 @@
+use crate::BlenderMesh;
+use std::collections::HashMap;
+
+/// The axis to mirror a mesh along.
+///
+/// Mirroring along X reflects the mesh across the YZ plane.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MirrorAxis {
+    #[allow(missing_docs)]
+    X,
+    #[allow(missing_docs)]
+    Y,
+    #[allow(missing_docs)]
+    Z,
+}
+
+/// Swaps the bones that vertices are weighted to when mirroring a mesh, such as weighting the
+/// vertices of a mirrored right hand to `hand.R` instead of `hand.L`.
+pub struct BoneRename<'a> {
+    /// The names of the bones in the mesh's parent armature, in the same order as the bone
+    /// indices in the mesh's bone influences.
+    pub bone_names: &'a [String],
+    /// Maps a bone name to the name of its mirrored bone, i.e. `"hand.L"` to `"hand.R"`.
+    ///
+    /// Bones that don't have a mirrored bone can be returned unchanged.
+    pub rename: &'a dyn Fn(&str) -> String,
+}
+
+impl MirrorAxis {
+    fn idx(&self) -> usize {
+        match self {
+            MirrorAxis::X => 0,
+            MirrorAxis::Y => 1,
+            MirrorAxis::Z => 2,
+        }
+    }
+}
+
+impl BlenderMesh {
+    /// Mirror the mesh along an axis.
+    ///
+    /// Positions, normals and shape key deltas are reflected and the winding order of every face
+    /// is reversed so that faces keep facing outwards. Face tangents are calculated from the
+    /// mirrored data, so they end up mirrored as well.
+    ///
+    /// If a `bone_rename` is provided every vertex's bone influences and every vertex group are
+    /// moved to their mirrored bone, so that a symmetric character only needs one side to be
+    /// weighted.
+    pub fn mirror(&mut self, axis: MirrorAxis, bone_rename: Option<&BoneRename>) {
+        let axis = axis.idx();
+        let multi = &mut self.multi_indexed_vertex_attributes;
+
+        for position in multi.positions.attribute.data.chunks_mut(3) {
+            position[axis] = -position[axis];
+        }
+        if let Some(normals) = multi.normals.as_mut() {
+            for normal in normals.attribute.data.chunks_mut(3) {
+                normal[axis] = -normal[axis];
+            }
+        }
+        for shape_key in self.shape_keys.iter_mut() {
+            for delta in shape_key.position_deltas.data.chunks_mut(3) {
+                delta[axis] = -delta[axis];
+            }
+        }
+
+        self.reverse_face_winding();
+
+        let min = self.bounding_box.min_corner[axis];
+        self.bounding_box.min_corner[axis] = -self.bounding_box.max_corner[axis];
+        self.bounding_box.max_corner[axis] = -min;
+
+        if let Some(bone_rename) = bone_rename {
+            let bone_indices: HashMap<&str, u8> = bone_rename
+                .bone_names
+                .iter()
+                .enumerate()
+                .map(|(idx, name)| (name.as_str(), idx as u8))
+                .collect();
+
+            let mirrored_bones: Vec<u8> = bone_rename
+                .bone_names
+                .iter()
+                .enumerate()
+                .map(|(idx, name)| {
+                    let mirrored = (bone_rename.rename)(name);
+                    *bone_indices.get(mirrored.as_str()).unwrap_or(&(idx as u8))
+                })
+                .collect();
+
+            let multi = &mut self.multi_indexed_vertex_attributes;
+            if let Some(bone_influences) = multi.bone_influences.as_mut() {
+                for bone_idx in bone_influences.bone_indices.iter_mut() {
+                    if let Some(mirrored) = mirrored_bones.get(*bone_idx as usize) {
+                        *bone_idx = *mirrored;
+                    }
+                }
+            }
+
+            self.vertex_groups = self
+                .vertex_groups
+                .drain()
+                .map(|(name, vertices)| ((bone_rename.rename)(&name), vertices))
+                .collect();
+        }
+
+        self.invalidate_bvh();
+    }
+
+    /// Reverse the order of every face's corners, so that faces that were turned inside out by
+    /// a reflection face outwards again.
+    pub(crate) fn reverse_face_winding(&mut self) {
+        let multi = &mut self.multi_indexed_vertex_attributes;
+
+        let mut start = 0;
+        for vertex_count in multi.vertices_in_each_face.iter() {
+            let face = start..start + *vertex_count as usize;
+
+            multi.positions.indices[face.clone()].reverse();
+            if let Some(normals) = multi.normals.as_mut() {
+                normals.indices[face.clone()].reverse();
+            }
+            for uvs in multi.uvs.iter_mut().chain(multi.lightmap_uvs.iter_mut()) {
+                uvs.indices[face.clone()].reverse();
+            }
+
+            start += *vertex_count as usize;
+        }
+
+        for attribute in self.custom_attributes.values_mut() {
+            attribute.reverse_face_winding(&multi.vertices_in_each_face);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bone::BoneInfluencesPerVertex;
+    use crate::vertex_attributes::VertexAttribute;
+    use crate::{
+        AttributeDomain, BoundingBox, CustomAttribute, CustomAttributeData, VertexBoneInfluences,
+    };
+    use nalgebra::Point3;
+
+    /// Verify that we reflect positions and normals and reverse the winding order of faces.
+    #[test]
+    fn mirror_geometry() {
+        let mut mesh = BlenderMesh::plane(2., 2., 0);
+        mesh.set_bounding_box(BoundingBox {
+            min_corner: Point3::new(0., -1., 0.),
+            max_corner: Point3::new(2., 1., 0.),
+        });
+
+        mesh.mirror(MirrorAxis::Z, None);
+        let multi = mesh.multi_indexed_vertex_attributes();
+
+        assert_eq!(multi.positions().indices(), &vec![2, 3, 1, 0]);
+        assert_eq!(multi.uvs().unwrap().indices(), &vec![2, 3, 1, 0]);
+        assert_eq!(
+            multi.normals().unwrap().attribute().data(),
+            &vec![0., 0., -1.]
+        );
+
+        mesh.mirror(MirrorAxis::X, None);
+        let multi = mesh.multi_indexed_vertex_attributes();
+
+        assert_eq!(&multi.positions().attribute().data()[0..3], &[1., -1., 0.]);
+        assert_eq!(mesh.bounding_box().min_corner, Point3::new(-2., -1., 0.));
+        assert_eq!(mesh.bounding_box().max_corner, Point3::new(0., 1., 0.));
+    }
+
+    /// Verify that corner attributes follow the reversed corners and that point and face
+    /// attributes are left alone.
+    #[test]
+    fn mirror_custom_attributes() {
+        let mut mesh = BlenderMesh::plane(2., 2., 0);
+        mesh.insert_custom_attribute(
+            "wear".to_string(),
+            CustomAttribute::new(
+                AttributeDomain::Corner,
+                CustomAttributeData::Float(VertexAttribute::new(vec![0., 1., 2., 3.], 1).unwrap()),
+            ),
+        );
+        mesh.insert_custom_attribute(
+            "height".to_string(),
+            CustomAttribute::new(
+                AttributeDomain::Point,
+                CustomAttributeData::Float(VertexAttribute::new(vec![0., 1., 2., 3.], 1).unwrap()),
+            ),
+        );
+
+        mesh.mirror(MirrorAxis::X, None);
+
+        assert_eq!(
+            mesh.custom_attributes()["wear"].data(),
+            &CustomAttributeData::Float(VertexAttribute::new(vec![3., 2., 1., 0.], 1).unwrap())
+        );
+        assert_eq!(
+            mesh.custom_attributes()["height"].data(),
+            &CustomAttributeData::Float(VertexAttribute::new(vec![0., 1., 2., 3.], 1).unwrap())
+        );
+    }
+
+    /// Verify that we swap bones with their mirrored bones.
+    #[test]
+    fn mirror_bone_weights() {
+        let mut mesh = BlenderMesh::plane(2., 2., 0);
+        mesh.multi_indexed_vertex_attributes.bone_influences = Some(VertexBoneInfluences {
+            bones_per_vertex: BoneInfluencesPerVertex::Uniform(1),
+            bone_indices: vec![0, 1, 2, 2],
+            bone_weights: vec![1.; 4],
+        });
+        mesh.vertex_groups
+            .insert("hand.L".to_string(), vec![1, 2, 3]);
+
+        let bone_names = vec![
+            "hand.L".to_string(),
+            "hand.R".to_string(),
+            "spine".to_string(),
+        ];
+        let rename = |name: &str| {
+            if name.ends_with(".L") {
+                name.replace(".L", ".R")
+            } else {
+                name.replace(".R", ".L")
+            }
+        };
+
+        mesh.mirror(
+            MirrorAxis::X,
+            Some(&BoneRename {
+                bone_names: &bone_names,
+                rename: &rename,
+            }),
+        );
+
+        assert_eq!(
+            mesh.multi_indexed_vertex_attributes
+                .bone_influences
+                .unwrap()
+                .bone_indices,
+            vec![1, 0, 2, 2]
+        );
+        assert_eq!(mesh.vertex_groups["hand.R"], vec![1, 2, 3]);
+    }
+}
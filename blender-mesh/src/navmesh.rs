@@ -0,0 +1,216 @@
+//! Generate navigation meshes from level geometry.
+//!
+//! Generation follows the same broad steps as [Recast](https://github.com/recastnavigation/recastnavigation).
+//! Triangles are voxelized into a heightfield, the spans that an agent can stand on get connected
+//! to their neighbors, the walkable area is shrunk by the agent's radius and what remains is
+//! merged into polygons.
+
+use self::heightfield::Heightfield;
+use self::open_heightfield::OpenHeightfield;
+use crate::BlenderMesh;
+use nalgebra::Point3;
+
+mod heightfield;
+mod open_heightfield;
+mod polygons;
+
+/// Describes the agent that will be navigating and how finely to voxelize the level.
+///
+/// Meshes are expected to be Z up, as they are when they are first exported from Blender.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavMeshConfig {
+    /// The width and depth of a voxel. Smaller cells follow the geometry more closely but take
+    /// longer to generate and produce more polygons.
+    pub cell_size: f32,
+    /// The height of a voxel.
+    pub cell_height: f32,
+    /// The minimum clearance that an agent needs above a surface in order to stand on it.
+    pub agent_height: f32,
+    /// How far the navmesh is kept away from walls and ledges.
+    pub agent_radius: f32,
+    /// The largest step up or down that an agent can take.
+    pub agent_max_climb: f32,
+    /// The steepest slope, in degrees, that an agent can walk on.
+    pub agent_max_slope_degrees: f32,
+}
+
+impl Default for NavMeshConfig {
+    fn default() -> Self {
+        NavMeshConfig {
+            cell_size: 0.3,
+            cell_height: 0.2,
+            agent_height: 2.0,
+            agent_radius: 0.6,
+            agent_max_climb: 0.9,
+            agent_max_slope_degrees: 45.0,
+        }
+    }
+}
+
+impl NavMeshConfig {
+    fn agent_height_cells(&self) -> i32 {
+        (self.agent_height / self.cell_height).ceil() as i32
+    }
+
+    fn agent_max_climb_cells(&self) -> i32 {
+        (self.agent_max_climb / self.cell_height).floor() as i32
+    }
+
+    fn agent_radius_cells(&self) -> i32 {
+        (self.agent_radius / self.cell_size).ceil() as i32
+    }
+}
+
+/// A polygon navigation mesh.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct NavMesh {
+    vertices: Vec<Point3<f32>>,
+    polygons: Vec<NavPolygon>,
+}
+
+/// A convex walkable area in a [`NavMesh`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavPolygon {
+    vertices: Vec<u32>,
+    neighbors: Vec<u32>,
+}
+
+impl NavMesh {
+    /// Generate a navigation mesh for the walkable surfaces of a level.
+    ///
+    /// All of the meshes are treated as one merged piece of level geometry.
+    pub fn from_meshes(meshes: &[&BlenderMesh], config: &NavMeshConfig) -> NavMesh {
+        let heightfield = match Heightfield::rasterize(meshes, config) {
+            Some(heightfield) => heightfield,
+            None => return NavMesh::default(),
+        };
+
+        let mut open_heightfield = OpenHeightfield::new(&heightfield, config);
+        open_heightfield.erode(config.agent_radius_cells());
+
+        polygons::build(&open_heightfield, heightfield.origin, config)
+    }
+
+    /// Every polygon vertex in the navmesh
+    pub fn vertices(&self) -> &Vec<Point3<f32>> {
+        &self.vertices
+    }
+
+    /// The walkable polygons
+    pub fn polygons(&self) -> &Vec<NavPolygon> {
+        &self.polygons
+    }
+}
+
+impl NavPolygon {
+    /// Indices into the navmesh's vertices, counter clockwise when looking down the Z axis.
+    pub fn vertices(&self) -> &Vec<u32> {
+        &self.vertices
+    }
+
+    /// Indices of the polygons that an agent can walk to directly from this polygon.
+    pub fn neighbors(&self) -> &Vec<u32> {
+        &self.neighbors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> NavMeshConfig {
+        NavMeshConfig {
+            cell_size: 0.5,
+            cell_height: 0.25,
+            agent_radius: 0.0,
+            ..NavMeshConfig::default()
+        }
+    }
+
+    /// Verify that an open floor becomes a single polygon.
+    #[test]
+    fn flat_floor() {
+        let floor = BlenderMesh::plane(10.0, 10.0, 0);
+        let navmesh = NavMesh::from_meshes(&[&floor], &config());
+
+        assert_eq!(navmesh.polygons.len(), 1);
+        assert_eq!(
+            polygon_corners(&navmesh, 0),
+            vec![
+                Point3::new(-5.0, -5.0, 0.0),
+                Point3::new(5.0, -5.0, 0.0),
+                Point3::new(5.0, 5.0, 0.0),
+                Point3::new(-5.0, 5.0, 0.0),
+            ]
+        );
+    }
+
+    /// Verify that the walkable area is kept an agent's radius away from ledges.
+    #[test]
+    fn erodes_by_agent_radius() {
+        let floor = BlenderMesh::plane(10.0, 10.0, 0);
+        let config = NavMeshConfig {
+            agent_radius: 0.5,
+            ..config()
+        };
+        let navmesh = NavMesh::from_meshes(&[&floor], &config);
+
+        assert_eq!(navmesh.polygons.len(), 1);
+        assert_eq!(
+            polygon_corners(&navmesh, 0)[0],
+            Point3::new(-4.5, -4.5, 0.0)
+        );
+    }
+
+    /// Verify that we can't walk underneath or onto a block that is too tall to climb, but that
+    /// we can walk on top of it.
+    #[test]
+    fn obstacles() {
+        let floor = BlenderMesh::plane(10.0, 10.0, 0);
+        let block = BlenderMesh::cube(2.0);
+        let navmesh = NavMesh::from_meshes(&[&floor, &block], &config());
+
+        let on_floor = |polygon: usize| {
+            polygon_corners(&navmesh, polygon)
+                .iter()
+                .all(|corner| corner.z == 0.0)
+        };
+
+        let block_top: Vec<usize> = (0..navmesh.polygons.len())
+            .filter(|polygon| !on_floor(*polygon))
+            .collect();
+        assert!(block_top.len() > 0);
+
+        for polygon in 0..navmesh.polygons.len() {
+            let corners = polygon_corners(&navmesh, polygon);
+
+            if on_floor(polygon) {
+                let center = corners
+                    .iter()
+                    .fold(Point3::origin(), |sum, corner| sum + corner.coords / 4.0);
+                assert!(center.x.abs() > 1.0 || center.y.abs() > 1.0);
+
+                for neighbor in navmesh.polygons[polygon].neighbors.iter() {
+                    assert!(!block_top.contains(&(*neighbor as usize)));
+                }
+            } else {
+                assert!(corners.iter().all(|corner| corner.z == 1.0));
+            }
+        }
+    }
+
+    /// Verify that we don't generate anything when there is nothing to walk on.
+    #[test]
+    fn no_geometry() {
+        let navmesh = NavMesh::from_meshes(&[], &config());
+        assert_eq!(navmesh, NavMesh::default());
+    }
+
+    fn polygon_corners(navmesh: &NavMesh, polygon: usize) -> Vec<Point3<f32>> {
+        navmesh.polygons[polygon]
+            .vertices
+            .iter()
+            .map(|vertex| navmesh.vertices[*vertex as usize])
+            .collect()
+    }
+}
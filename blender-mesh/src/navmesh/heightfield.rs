@@ -0,0 +1,245 @@
+use super::NavMeshConfig;
+use crate::BlenderMesh;
+use nalgebra::Point3;
+
+/// A solid region of a heightfield column, measured in multiples of the cell height above the
+/// heightfield's origin.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(super) struct Span {
+    pub(super) min: i32,
+    pub(super) max: i32,
+    /// Whether or not the top of the span is a surface that can be walked on
+    pub(super) walkable: bool,
+}
+
+/// The voxelized level geometry.
+#[derive(Debug)]
+pub(super) struct Heightfield {
+    /// The corner with the lowest x, y and z values
+    pub(super) origin: Point3<f32>,
+    pub(super) width: usize,
+    pub(super) depth: usize,
+    /// The spans in every column, sorted from lowest to highest. Indexed by `y * width + x`.
+    pub(super) columns: Vec<Vec<Span>>,
+}
+
+impl Heightfield {
+    /// Voxelize every triangle in the meshes. Returns None if there are no triangles.
+    pub(super) fn rasterize(
+        meshes: &[&BlenderMesh],
+        config: &NavMeshConfig,
+    ) -> Option<Heightfield> {
+        let triangles: Vec<[Point3<f32>; 3]> = meshes
+            .iter()
+            .flat_map(|mesh| mesh_triangles(mesh))
+            .collect();
+        if triangles.is_empty() {
+            return None;
+        }
+
+        let mut min = Point3::new(std::f32::MAX, std::f32::MAX, std::f32::MAX);
+        let mut max = Point3::new(std::f32::MIN, std::f32::MIN, std::f32::MIN);
+        for corner in triangles.iter().flat_map(|triangle| triangle.iter()) {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(corner[axis]);
+                max[axis] = max[axis].max(corner[axis]);
+            }
+        }
+
+        let width = (((max.x - min.x) / config.cell_size).ceil() as usize).max(1);
+        let depth = (((max.y - min.y) / config.cell_size).ceil() as usize).max(1);
+
+        let mut heightfield = Heightfield {
+            origin: min,
+            width,
+            depth,
+            columns: vec![vec![]; width * depth],
+        };
+
+        let min_walkable_normal_z = config.agent_max_slope_degrees.to_radians().cos();
+
+        for triangle in triangles.iter() {
+            let [a, b, c] = triangle;
+
+            let normal = match (b - a).cross(&(c - a)).try_normalize(std::f32::EPSILON) {
+                Some(normal) => normal,
+                None => continue,
+            };
+
+            heightfield.rasterize_triangle(triangle, normal.z >= min_walkable_normal_z, config);
+        }
+
+        Some(heightfield)
+    }
+
+    fn rasterize_triangle(
+        &mut self,
+        triangle: &[Point3<f32>; 3],
+        walkable: bool,
+        config: &NavMeshConfig,
+    ) {
+        let cell_size = config.cell_size;
+        let cell = |value: f32, origin: f32, count: usize| {
+            (((value - origin) / cell_size).floor().max(0.) as usize).min(count - 1)
+        };
+
+        let axis_range = |axis: usize, origin: f32, count: usize| {
+            let min = triangle.iter().fold(std::f32::MAX, |m, c| m.min(c[axis]));
+            let max = triangle.iter().fold(std::f32::MIN, |m, c| m.max(c[axis]));
+            cell(min, origin, count)..=cell(max, origin, count)
+        };
+
+        for y in axis_range(1, self.origin.y, self.depth) {
+            let row_start = self.origin.y + y as f32 * cell_size;
+            let row = clip(
+                &clip(triangle, 1, row_start, true),
+                1,
+                row_start + cell_size,
+                false,
+            );
+
+            for x in axis_range(0, self.origin.x, self.width) {
+                let column_start = self.origin.x + x as f32 * cell_size;
+                let clipped = clip(
+                    &clip(&row, 0, column_start, true),
+                    0,
+                    column_start + cell_size,
+                    false,
+                );
+                if clipped.is_empty() {
+                    continue;
+                }
+
+                let min_z = clipped.iter().fold(std::f32::MAX, |m, c| m.min(c.z));
+                let max_z = clipped.iter().fold(std::f32::MIN, |m, c| m.max(c.z));
+
+                let span = Span {
+                    min: ((min_z - self.origin.z) / config.cell_height).floor() as i32,
+                    max: ((max_z - self.origin.z) / config.cell_height).ceil() as i32,
+                    walkable,
+                };
+                self.add_span(y * self.width + x, span, config.agent_max_climb_cells());
+            }
+        }
+    }
+
+    /// Insert a span into a column, merging it with any spans that it touches.
+    ///
+    /// When merged spans end within climbing distance of each other the merged span is walkable
+    /// if either of them was. Otherwise the span with the higher top decides.
+    fn add_span(&mut self, column: usize, mut span: Span, max_climb: i32) {
+        let spans = &mut self.columns[column];
+
+        let mut idx = 0;
+        while idx < spans.len() {
+            let existing = spans[idx];
+
+            if existing.min > span.max {
+                break;
+            }
+            if existing.max < span.min {
+                idx += 1;
+                continue;
+            }
+
+            if (existing.max - span.max).abs() <= max_climb {
+                span.walkable |= existing.walkable;
+            } else if existing.max > span.max {
+                span.walkable = existing.walkable;
+            }
+
+            span.min = span.min.min(existing.min);
+            span.max = span.max.max(existing.max);
+            spans.remove(idx);
+        }
+
+        spans.insert(idx, span);
+    }
+}
+
+/// Every face of a mesh, fan triangulated.
+fn mesh_triangles(mesh: &BlenderMesh) -> Vec<[Point3<f32>; 3]> {
+    let multi = &mesh.multi_indexed_vertex_attributes;
+    let position = |idx: u32| {
+        let position = multi.positions.attribute.data_at_idx(idx);
+        Point3::new(position[0], position[1], position[2])
+    };
+
+    let mut triangles = vec![];
+    let mut face_start = 0;
+
+    for vertex_count in multi.vertices_in_each_face.iter() {
+        let vertex_count = *vertex_count as usize;
+        let indices = &multi.positions.indices[face_start..face_start + vertex_count];
+
+        for corner in 1..vertex_count.saturating_sub(1) {
+            triangles.push([
+                position(indices[0]),
+                position(indices[corner]),
+                position(indices[corner + 1]),
+            ]);
+        }
+
+        face_start += vertex_count;
+    }
+
+    triangles
+}
+
+/// Clip a polygon against an axis aligned plane, keeping the part of the polygon that is above
+/// or below the plane.
+fn clip(polygon: &[Point3<f32>], axis: usize, value: f32, keep_above: bool) -> Vec<Point3<f32>> {
+    let inside = |point: &Point3<f32>| match keep_above {
+        true => point[axis] >= value,
+        false => point[axis] <= value,
+    };
+
+    let mut clipped = vec![];
+
+    for (idx, current) in polygon.iter().enumerate() {
+        let next = &polygon[(idx + 1) % polygon.len()];
+
+        if inside(current) {
+            clipped.push(*current);
+        }
+
+        if inside(current) != inside(next) {
+            let t = (value - current[axis]) / (next[axis] - current[axis]);
+            clipped.push(current + (next - current) * t);
+        }
+    }
+
+    clipped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that overlapping spans get merged and that the higher span decides whether or not
+    /// the merged span is walkable.
+    #[test]
+    fn merge_spans() {
+        let mut heightfield = Heightfield {
+            origin: Point3::origin(),
+            width: 1,
+            depth: 1,
+            columns: vec![vec![]],
+        };
+
+        let span = |min, max, walkable| Span { min, max, walkable };
+
+        heightfield.add_span(0, span(0, 0, true), 1);
+        heightfield.add_span(0, span(5, 10, false), 1);
+        assert_eq!(heightfield.columns[0].len(), 2);
+
+        heightfield.add_span(0, span(8, 11, true), 1);
+        assert_eq!(
+            heightfield.columns[0],
+            vec![span(0, 0, true), span(5, 11, true)]
+        );
+
+        heightfield.add_span(0, span(-2, 20, false), 1);
+        assert_eq!(heightfield.columns[0], vec![span(-2, 20, false)]);
+    }
+}
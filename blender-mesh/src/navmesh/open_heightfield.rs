@@ -0,0 +1,215 @@
+use super::heightfield::Heightfield;
+use super::NavMeshConfig;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// The offsets to the neighboring columns in the -x, +y, +x and -y directions
+pub(super) const DIRECTIONS: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+pub(super) const UP: usize = 1;
+pub(super) const RIGHT: usize = 2;
+
+/// The open space above a walkable span.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(super) struct OpenSpan {
+    pub(super) x: usize,
+    pub(super) y: usize,
+    pub(super) floor: i32,
+    pub(super) ceiling: i32,
+    /// The span that an agent can step to in each of the [`DIRECTIONS`]
+    pub(super) connections: [Option<u32>; 4],
+}
+
+/// The surfaces that an agent can stand on, along with which surfaces they can walk between.
+#[derive(Debug)]
+pub(super) struct OpenHeightfield {
+    pub(super) width: usize,
+    pub(super) depth: usize,
+    /// Sorted by column and then by floor height
+    pub(super) spans: Vec<OpenSpan>,
+    /// The range of spans in every column. Indexed by `y * width + x`.
+    columns: Vec<Range<usize>>,
+}
+
+impl OpenHeightfield {
+    /// Find every walkable span with enough room above it for an agent to stand and connect it
+    /// to its neighbors.
+    pub(super) fn new(heightfield: &Heightfield, config: &NavMeshConfig) -> OpenHeightfield {
+        let agent_height = config.agent_height_cells();
+        let max_climb = config.agent_max_climb_cells();
+
+        let mut spans = vec![];
+        let mut columns = vec![];
+
+        for y in 0..heightfield.depth {
+            for x in 0..heightfield.width {
+                let column = &heightfield.columns[y * heightfield.width + x];
+                let start = spans.len();
+
+                for (idx, span) in column.iter().enumerate() {
+                    let ceiling = column
+                        .get(idx + 1)
+                        .map(|above| above.min)
+                        .unwrap_or(std::i32::MAX);
+
+                    if span.walkable && ceiling - span.max >= agent_height {
+                        spans.push(OpenSpan {
+                            x,
+                            y,
+                            floor: span.max,
+                            ceiling,
+                            connections: [None; 4],
+                        });
+                    }
+                }
+
+                columns.push(start..spans.len());
+            }
+        }
+
+        let mut open_heightfield = OpenHeightfield {
+            width: heightfield.width,
+            depth: heightfield.depth,
+            spans,
+            columns,
+        };
+
+        for idx in 0..open_heightfield.spans.len() {
+            let span = open_heightfield.spans[idx];
+
+            for direction in 0..DIRECTIONS.len() {
+                let neighbor = open_heightfield
+                    .neighbor_column(span.x, span.y, direction)
+                    .and_then(|column| {
+                        open_heightfield.spans[column.clone()]
+                            .iter()
+                            .position(|neighbor| {
+                                let clearance = span.ceiling.min(neighbor.ceiling)
+                                    - span.floor.max(neighbor.floor);
+                                let step = (span.floor - neighbor.floor).abs();
+
+                                clearance >= agent_height && step <= max_climb
+                            })
+                            .map(|offset| (column.start + offset) as u32)
+                    });
+
+                open_heightfield.spans[idx].connections[direction] = neighbor;
+            }
+        }
+
+        open_heightfield
+    }
+
+    /// Remove every span that is within `radius` cells of a wall or ledge.
+    pub(super) fn erode(&mut self, radius: i32) {
+        if radius <= 0 {
+            return;
+        }
+
+        // Breadth first search outwards from the spans on the edge of the walkable area
+        let mut distance = vec![std::i32::MAX; self.spans.len()];
+        let mut queue = VecDeque::new();
+
+        for (idx, span) in self.spans.iter().enumerate() {
+            if span.connections.iter().any(Option::is_none) {
+                distance[idx] = 0;
+                queue.push_back(idx);
+            }
+        }
+
+        while let Some(idx) = queue.pop_front() {
+            for neighbor in self.spans[idx].connections.iter().flatten() {
+                let neighbor = *neighbor as usize;
+
+                if distance[neighbor] == std::i32::MAX {
+                    distance[neighbor] = distance[idx] + 1;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        let keep: Vec<bool> = distance.iter().map(|d| *d >= radius).collect();
+        self.retain(&keep);
+    }
+
+    fn retain(&mut self, keep: &[bool]) {
+        let mut new_indices = vec![None; self.spans.len()];
+        let mut spans = vec![];
+
+        for column in self.columns.iter_mut() {
+            let start = spans.len();
+
+            for idx in column.clone() {
+                if keep[idx] {
+                    new_indices[idx] = Some(spans.len() as u32);
+                    spans.push(self.spans[idx]);
+                }
+            }
+
+            *column = start..spans.len();
+        }
+
+        for span in spans.iter_mut() {
+            for connection in span.connections.iter_mut() {
+                *connection = connection.and_then(|idx| new_indices[idx as usize]);
+            }
+        }
+
+        self.spans = spans;
+    }
+
+    fn neighbor_column(&self, x: usize, y: usize, direction: usize) -> Option<Range<usize>> {
+        let (dx, dy) = DIRECTIONS[direction];
+        let x = x as isize + dx;
+        let y = y as isize + dy;
+
+        if x < 0 || y < 0 || x >= self.width as isize || y >= self.depth as isize {
+            return None;
+        }
+
+        Some(self.columns[y as usize * self.width + x as usize].clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::heightfield::Span;
+    use super::*;
+    use nalgebra::Point3;
+
+    /// Verify that we connect spans that are within climbing distance and have enough room
+    /// above them.
+    #[test]
+    fn connect_spans() {
+        let span = |min, max| Span {
+            min,
+            max,
+            walkable: true,
+        };
+
+        let heightfield = Heightfield {
+            origin: Point3::origin(),
+            width: 3,
+            depth: 1,
+            columns: vec![
+                vec![span(0, 0)],
+                // The upper span is too high to step onto
+                vec![span(0, 3), span(20, 30)],
+                // Not enough room to stand on the lower span
+                vec![span(0, 3), span(5, 6)],
+            ],
+        };
+
+        let config = NavMeshConfig {
+            cell_height: 1.0,
+            agent_height: 10.0,
+            agent_max_climb: 3.0,
+            ..NavMeshConfig::default()
+        };
+        let open = OpenHeightfield::new(&heightfield, &config);
+
+        assert_eq!(open.spans.len(), 4);
+        assert_eq!(open.spans[0].connections, [None, None, Some(1), None]);
+        assert_eq!(open.spans[1].connections, [Some(0), None, Some(3), None]);
+        assert_eq!(open.spans[2].connections, [None, None, None, None]);
+    }
+}
@@ -0,0 +1,129 @@
+use super::open_heightfield::{OpenHeightfield, RIGHT, UP};
+use super::{NavMesh, NavMeshConfig, NavPolygon};
+use nalgebra::Point3;
+use std::collections::{BTreeSet, HashMap};
+
+/// Greedily merge connected spans into rectangles and turn every rectangle into a polygon.
+///
+/// Rectangles grow along +x first and then along +y one row at a time, so long as every span
+/// in the new row is connected to the row below it and to its neighbors in the row.
+pub(super) fn build(
+    open_heightfield: &OpenHeightfield,
+    origin: Point3<f32>,
+    config: &NavMeshConfig,
+) -> NavMesh {
+    let spans = &open_heightfield.spans;
+
+    let mut polygon_of_span: Vec<Option<u32>> = vec![None; spans.len()];
+    let mut vertices = vec![];
+    let mut vertex_indices: HashMap<(usize, usize, i32), u32> = HashMap::new();
+    let mut polygons = vec![];
+
+    for start in 0..spans.len() {
+        if polygon_of_span[start].is_some() {
+            continue;
+        }
+        let polygon_idx = polygons.len() as u32;
+
+        let mut bottom_row = vec![start];
+        polygon_of_span[start] = Some(polygon_idx);
+
+        while let Some(right) = spans[*bottom_row.last().unwrap()].connections[RIGHT] {
+            let right = right as usize;
+            if polygon_of_span[right].is_some() {
+                break;
+            }
+
+            polygon_of_span[right] = Some(polygon_idx);
+            bottom_row.push(right);
+        }
+
+        let mut rows = vec![bottom_row];
+
+        'grow: loop {
+            let mut next_row: Vec<usize> = vec![];
+
+            for span in rows.last().unwrap().iter() {
+                let up = match spans[*span].connections[UP] {
+                    Some(up) if polygon_of_span[up as usize].is_none() => up,
+                    _ => break 'grow,
+                };
+
+                if let Some(left) = next_row.last() {
+                    if spans[*left].connections[RIGHT] != Some(up) {
+                        break 'grow;
+                    }
+                }
+
+                next_row.push(up as usize);
+            }
+
+            for span in next_row.iter() {
+                polygon_of_span[*span] = Some(polygon_idx);
+            }
+            rows.push(next_row);
+        }
+
+        let bottom_row = rows.first().unwrap();
+        let top_row = rows.last().unwrap();
+
+        let bottom_left = &spans[bottom_row[0]];
+        let bottom_right = &spans[*bottom_row.last().unwrap()];
+        let top_left = &spans[top_row[0]];
+        let top_right = &spans[*top_row.last().unwrap()];
+
+        let min_x = bottom_left.x;
+        let min_y = bottom_left.y;
+        let max_x = bottom_right.x + 1;
+        let max_y = top_left.y + 1;
+
+        // Counter clockwise when looking down the Z axis
+        let corners = [
+            (min_x, min_y, bottom_left.floor),
+            (max_x, min_y, bottom_right.floor),
+            (max_x, max_y, top_right.floor),
+            (min_x, max_y, top_left.floor),
+        ];
+
+        let polygon_vertices = corners
+            .iter()
+            .map(|corner| {
+                *vertex_indices.entry(*corner).or_insert_with(|| {
+                    let (x, y, floor) = *corner;
+
+                    vertices.push(Point3::new(
+                        origin.x + x as f32 * config.cell_size,
+                        origin.y + y as f32 * config.cell_size,
+                        origin.z + floor as f32 * config.cell_height,
+                    ));
+
+                    (vertices.len() - 1) as u32
+                })
+            })
+            .collect();
+
+        polygons.push(NavPolygon {
+            vertices: polygon_vertices,
+            neighbors: vec![],
+        });
+    }
+
+    let mut neighbors = vec![BTreeSet::new(); polygons.len()];
+    for (idx, span) in spans.iter().enumerate() {
+        let polygon = polygon_of_span[idx].unwrap();
+
+        for connection in span.connections.iter().flatten() {
+            let neighbor = polygon_of_span[*connection as usize].unwrap();
+
+            if neighbor != polygon {
+                neighbors[polygon as usize].insert(neighbor);
+            }
+        }
+    }
+
+    for (polygon, neighbors) in polygons.iter_mut().zip(neighbors.into_iter()) {
+        polygon.neighbors = neighbors.into_iter().collect();
+    }
+
+    NavMesh { vertices, polygons }
+}
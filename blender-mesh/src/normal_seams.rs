@@ -0,0 +1,226 @@
+use crate::BlenderMesh;
+use nalgebra::{Point3, Vector3};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// A face corner that lies on the open boundary of one of the meshes being reconciled.
+struct BoundaryCorner {
+    mesh: usize,
+    corner: usize,
+    position: Point3<f32>,
+    normal: Vector3<f32>,
+}
+
+impl BlenderMesh {
+    /// Average the normals where the open boundaries of different meshes meet, so that modular
+    /// pieces that get placed next to each other don't show a seam where they are joined.
+    ///
+    /// The meshes' positions need to already be in the same space, such as after moving every
+    /// piece to where it sits in the level. Boundary vertices of different meshes that are within
+    /// `position_epsilon` of each other get the average of all of their normals. Vertices that
+    /// only meet boundary vertices of their own mesh are left alone, so hard edges within a mesh
+    /// are kept.
+    ///
+    /// Returns the number of face corners whose normal was changed.
+    pub fn reconcile_boundary_normals(
+        meshes: &mut [&mut BlenderMesh],
+        position_epsilon: f32,
+    ) -> usize {
+        let corners = boundary_corners(meshes);
+
+        // Corners within the epsilon of each other are always in the same or in neighboring cells
+        let cell_size = position_epsilon.max(std::f32::EPSILON);
+        let cell = |position: &Point3<f32>| {
+            let cell = position.coords / cell_size;
+            [
+                cell.x.floor() as i64,
+                cell.y.floor() as i64,
+                cell.z.floor() as i64,
+            ]
+        };
+
+        let mut cells: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        for (idx, corner) in corners.iter().enumerate() {
+            cells.entry(cell(&corner.position)).or_default().push(idx);
+        }
+
+        let mut groups: Vec<usize> = (0..corners.len()).collect();
+        for (idx, corner) in corners.iter().enumerate() {
+            let [x, y, z] = cell(&corner.position);
+
+            for neighbor in neighboring_cells(x, y, z) {
+                for other in cells.get(&neighbor).into_iter().flatten() {
+                    let distance = (corners[*other].position - corner.position).norm();
+                    if distance <= position_epsilon {
+                        union(&mut groups, idx, *other);
+                    }
+                }
+            }
+        }
+
+        // Sorted so that averaged normals are always added in the same order
+        let mut members: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for idx in 0..corners.len() {
+            let group = find(&mut groups, idx);
+            members.entry(group).or_default().push(idx);
+        }
+
+        let mut changed = 0;
+        for group in members.values() {
+            let meshes_in_group: HashSet<usize> =
+                group.iter().map(|idx| corners[*idx].mesh).collect();
+            if meshes_in_group.len() < 2 {
+                continue;
+            }
+
+            let sum: Vector3<f32> = group.iter().map(|idx| corners[*idx].normal).sum();
+            let average = match sum.try_normalize(std::f32::EPSILON) {
+                Some(average) => average,
+                None => continue,
+            };
+
+            // The averaged normal gets added once per mesh so that the normals of corners that
+            // aren't on the seam stay the same
+            let mut averaged_normal_idx: HashMap<usize, u32> = HashMap::new();
+            for idx in group.iter() {
+                let corner = &corners[*idx];
+                let normals = meshes[corner.mesh]
+                    .multi_indexed_vertex_attributes
+                    .normals
+                    .as_mut()
+                    .unwrap();
+
+                let normal_idx = *averaged_normal_idx.entry(corner.mesh).or_insert_with(|| {
+                    let normal_idx = normals.attribute.data.len() / 3;
+                    normals.attribute.data.extend(average.iter());
+                    normal_idx as u32
+                });
+
+                normals.indices[corner.corner] = normal_idx;
+                changed += 1;
+            }
+        }
+
+        changed
+    }
+}
+
+/// Every face corner, in every mesh that has normals, whose position is on an open boundary.
+fn boundary_corners(meshes: &[&mut BlenderMesh]) -> Vec<BoundaryCorner> {
+    let mut corners = vec![];
+
+    for (mesh_idx, mesh) in meshes.iter().enumerate() {
+        let multi = &mesh.multi_indexed_vertex_attributes;
+        let normals = match multi.normals.as_ref() {
+            Some(normals) => normals,
+            None => continue,
+        };
+
+        let boundary_positions: HashSet<u32> = mesh
+            .topology_report()
+            .boundary_edges
+            .iter()
+            .flat_map(|edge| edge.iter().copied())
+            .collect();
+
+        for (corner, position_idx) in multi.positions.indices.iter().enumerate() {
+            if !boundary_positions.contains(position_idx) {
+                continue;
+            }
+
+            let position = multi.positions.attribute.data_at_idx(*position_idx);
+            let normal = normals.attribute.data_at_idx(normals.indices[corner]);
+
+            corners.push(BoundaryCorner {
+                mesh: mesh_idx,
+                corner,
+                position: Point3::new(position[0], position[1], position[2]),
+                normal: Vector3::new(normal[0], normal[1], normal[2]),
+            });
+        }
+    }
+
+    corners
+}
+
+fn neighboring_cells(x: i64, y: i64, z: i64) -> impl Iterator<Item = [i64; 3]> {
+    (-1..=1).flat_map(move |dx| {
+        (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| [x + dx, y + dy, z + dz]))
+    })
+}
+
+fn find(groups: &mut [usize], idx: usize) -> usize {
+    let mut root = idx;
+    while groups[root] != root {
+        root = groups[root];
+    }
+
+    let mut idx = idx;
+    while groups[idx] != root {
+        let next = groups[idx];
+        groups[idx] = root;
+        idx = next;
+    }
+
+    root
+}
+
+fn union(groups: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(groups, a), find(groups, b));
+    groups[a.max(b)] = a.min(b);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that the corners where two pieces meet get the same normal and that the rest of
+    /// the corners keep theirs.
+    #[test]
+    fn average_normals_where_pieces_meet() {
+        // The right edge of the left piece meets the left edge of the right piece at x = 1
+        let mut left = BlenderMesh::plane(2., 2., 0);
+        left.multi_indexed_vertex_attributes
+            .normals
+            .as_mut()
+            .unwrap()
+            .attribute
+            .data = vec![0.6, 0., 0.8];
+
+        let mut right = BlenderMesh::plane(2., 2., 0);
+        for position in right
+            .multi_indexed_vertex_attributes
+            .positions
+            .attribute
+            .data
+            .chunks_mut(3)
+        {
+            position[0] += 2.0001;
+        }
+
+        let changed = BlenderMesh::reconcile_boundary_normals(&mut [&mut left, &mut right], 0.001);
+        assert_eq!(changed, 4);
+
+        let normal = |mesh: &BlenderMesh, corner: usize| {
+            let normals = mesh
+                .multi_indexed_vertex_attributes
+                .normals
+                .as_ref()
+                .unwrap();
+            normals
+                .attribute
+                .data_at_idx(normals.indices[corner])
+                .to_vec()
+        };
+
+        let expected = Vector3::new(0.6, 0., 1.8).normalize();
+        let expected = vec![expected.x, expected.y, expected.z];
+        // Corners 1 and 2 of the left plane and 0 and 3 of the right plane are at x = 1
+        assert_eq!(normal(&left, 1), expected);
+        assert_eq!(normal(&left, 2), expected);
+        assert_eq!(normal(&right, 0), expected);
+        assert_eq!(normal(&right, 3), expected);
+
+        assert_eq!(normal(&left, 0), vec![0.6, 0., 0.8]);
+        assert_eq!(normal(&right, 1), vec![0., 0., 1.]);
+    }
+}
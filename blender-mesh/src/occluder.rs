@@ -0,0 +1,618 @@
+//! Simplified occluder meshes for software occlusion culling.
+
+use crate::BlenderMesh;
+use nalgebra::{Matrix4, Point3, Vector3, Vector4};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+/// Open edges get a much larger error for moving away from them so that holes and the outline
+/// of flat meshes keep their shape.
+const BOUNDARY_WEIGHT: f64 = 1000.;
+
+/// How aggressively to simplify a render mesh into an occluder.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OccluderConfig {
+    /// Stop simplifying once the occluder has this many triangles or fewer.
+    pub target_triangle_count: usize,
+    /// The largest error that a single simplification step is allowed to introduce, roughly
+    /// measured as the distance that the surface moves.
+    ///
+    /// Simplification stops before reaching the target triangle count if every remaining step
+    /// would exceed this.
+    pub max_error: f32,
+    /// Neighboring triangles whose normals are within this many degrees of each other get
+    /// flattened onto a shared plane, so long as no vertex moves further than `max_error`.
+    pub planar_angle_degrees: f32,
+}
+
+impl Default for OccluderConfig {
+    fn default() -> Self {
+        OccluderConfig {
+            target_triangle_count: 64,
+            max_error: 0.05,
+            planar_angle_degrees: 10.,
+        }
+    }
+}
+
+/// A heavily simplified stand in for a render mesh, used to decide what is hidden behind it.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Occluder {
+    source_mesh: String,
+    positions: Vec<f32>,
+    indices: Vec<u16>,
+}
+
+impl Occluder {
+    /// The name of the mesh that this occluder was generated from
+    pub fn source_mesh(&self) -> &String {
+        &self.source_mesh
+    }
+
+    /// The x, y and z coordinates of every vertex
+    pub fn positions(&self) -> &Vec<f32> {
+        &self.positions
+    }
+
+    /// Three indices into the positions for every triangle, in the same winding order as the
+    /// source mesh.
+    pub fn indices(&self) -> &Vec<u16> {
+        &self.indices
+    }
+
+    /// The number of triangles in the occluder
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+}
+
+impl BlenderMesh {
+    /// Generate a simplified occluder from this mesh.
+    ///
+    /// The mesh is decimated using quadric error metric edge collapses and then nearly coplanar
+    /// regions are flattened.
+    pub fn occluder(&self, config: &OccluderConfig) -> Occluder {
+        let mut simplifier = Simplifier::new(self);
+
+        simplifier.decimate(config);
+        simplifier.planarize(config);
+
+        simplifier.into_occluder(self.name.clone())
+    }
+}
+
+struct Simplifier {
+    positions: Vec<Point3<f64>>,
+    quadrics: Vec<Matrix4<f64>>,
+    triangles: Vec<[usize; 3]>,
+    removed: Vec<bool>,
+    vertex_triangles: Vec<Vec<usize>>,
+    /// Incremented whenever a vertex changes so that we can skip outdated collapses
+    versions: Vec<u32>,
+    live_triangles: usize,
+}
+
+/// Merging the `remove` vertex into the `keep` vertex and moving it to `position`.
+struct Collapse {
+    error: f64,
+    keep: usize,
+    remove: usize,
+    position: Point3<f64>,
+    versions: (u32, u32),
+}
+
+impl Simplifier {
+    fn new(mesh: &BlenderMesh) -> Simplifier {
+        let multi = &mesh.multi_indexed_vertex_attributes;
+
+        let positions: Vec<Point3<f64>> = multi
+            .positions
+            .attribute
+            .chunks(3)
+            .map(|p| Point3::new(p[0] as f64, p[1] as f64, p[2] as f64))
+            .collect();
+
+        let mut triangles = vec![];
+        for face in multi.faces() {
+            let vertex_count = face.corner_count();
+            let indices = face.position_indices();
+
+            for corner in 1..vertex_count.saturating_sub(1) {
+                let triangle = [
+                    indices[0] as usize,
+                    indices[corner] as usize,
+                    indices[corner + 1] as usize,
+                ];
+
+                if triangle[0] != triangle[1]
+                    && triangle[1] != triangle[2]
+                    && triangle[0] != triangle[2]
+                {
+                    triangles.push(triangle);
+                }
+            }
+        }
+
+        let mut simplifier = Simplifier {
+            quadrics: vec![Matrix4::zeros(); positions.len()],
+            vertex_triangles: vec![vec![]; positions.len()],
+            versions: vec![0; positions.len()],
+            removed: vec![false; triangles.len()],
+            live_triangles: triangles.len(),
+            positions,
+            triangles,
+        };
+
+        for (idx, triangle) in simplifier.triangles.iter().enumerate() {
+            for corner in triangle.iter() {
+                simplifier.vertex_triangles[*corner].push(idx);
+            }
+        }
+
+        simplifier.compute_quadrics();
+        simplifier
+    }
+
+    fn compute_quadrics(&mut self) {
+        for (idx, triangle) in self.triangles.iter().enumerate() {
+            let normal = match self.triangle_normal(idx).try_normalize(std::f64::EPSILON) {
+                Some(normal) => normal,
+                None => continue,
+            };
+
+            let quadric = plane_quadric(&normal, &self.positions[triangle[0]]);
+            for corner in triangle.iter() {
+                self.quadrics[*corner] += quadric;
+            }
+        }
+
+        for ((a, b), triangles) in self.edge_triangles() {
+            if triangles.len() != 1 {
+                continue;
+            }
+
+            let edge = self.positions[b] - self.positions[a];
+            let face_normal = self.triangle_normal(triangles[0]);
+
+            if let Some(normal) = edge.cross(&face_normal).try_normalize(std::f64::EPSILON) {
+                let quadric = plane_quadric(&normal, &self.positions[a]) * BOUNDARY_WEIGHT;
+                self.quadrics[a] += quadric;
+                self.quadrics[b] += quadric;
+            }
+        }
+    }
+
+    fn decimate(&mut self, config: &OccluderConfig) {
+        let mut collapses = BinaryHeap::new();
+        for (a, b) in self.edge_triangles().keys() {
+            collapses.push(self.cheapest_collapse(*a, *b));
+        }
+
+        while self.live_triangles > config.target_triangle_count {
+            let collapse = match collapses.pop() {
+                Some(collapse) => collapse,
+                None => break,
+            };
+
+            if collapse.versions != (self.versions[collapse.keep], self.versions[collapse.remove]) {
+                continue;
+            }
+
+            // Every remaining collapse is at least this expensive
+            if collapse.error.sqrt() > config.max_error as f64 {
+                break;
+            }
+
+            if self.flips_triangle(&collapse) {
+                continue;
+            }
+
+            self.collapse(&collapse);
+
+            for neighbor in self.neighbors(collapse.keep) {
+                collapses.push(self.cheapest_collapse(collapse.keep, neighbor));
+            }
+        }
+    }
+
+    /// Flatten regions of nearly coplanar triangles onto their average plane.
+    ///
+    /// Vertices on the border between regions are left alone so that creases stay sharp.
+    fn planarize(&mut self, config: &OccluderConfig) {
+        let min_cos = (config.planar_angle_degrees as f64).to_radians().cos();
+        let max_error = config.max_error as f64;
+
+        let mut adjacent_triangles: Vec<Vec<usize>> = vec![vec![]; self.triangles.len()];
+        for (_, triangles) in self.edge_triangles() {
+            for a in triangles.iter() {
+                for b in triangles.iter().filter(|b| *b != a) {
+                    adjacent_triangles[*a].push(*b);
+                }
+            }
+        }
+
+        let mut region_of_triangle = vec![None; self.triangles.len()];
+        let mut regions = vec![];
+
+        for seed in 0..self.triangles.len() {
+            if self.removed[seed] || region_of_triangle[seed].is_some() {
+                continue;
+            }
+
+            let seed_normal = match self.triangle_normal(seed).try_normalize(std::f64::EPSILON) {
+                Some(normal) => normal,
+                None => continue,
+            };
+
+            let region = regions.len();
+            let mut triangles = vec![seed];
+            let mut normal_sum = Vector3::zeros();
+            let mut queue = VecDeque::new();
+
+            region_of_triangle[seed] = Some(region);
+            queue.push_back(seed);
+
+            while let Some(triangle) = queue.pop_front() {
+                normal_sum += self.triangle_normal(triangle);
+
+                for neighbor in adjacent_triangles[triangle].iter() {
+                    if region_of_triangle[*neighbor].is_some() {
+                        continue;
+                    }
+
+                    let neighbor_normal = self.triangle_normal(*neighbor);
+                    match neighbor_normal.try_normalize(std::f64::EPSILON) {
+                        Some(normal) if normal.dot(&seed_normal) >= min_cos => {
+                            region_of_triangle[*neighbor] = Some(region);
+                            triangles.push(*neighbor);
+                            queue.push_back(*neighbor);
+                        }
+                        _ => {}
+                    };
+                }
+            }
+
+            regions.push((normal_sum, triangles));
+        }
+
+        for (region, (normal_sum, triangles)) in regions.iter().enumerate() {
+            if triangles.len() < 2 {
+                continue;
+            }
+
+            // Area weighted, since the triangle normals have not been normalized
+            let normal = match normal_sum.try_normalize(std::f64::EPSILON) {
+                Some(normal) => normal,
+                None => continue,
+            };
+
+            let mut vertices: Vec<usize> = triangles
+                .iter()
+                .flat_map(|triangle| self.triangles[*triangle].iter().copied())
+                .collect();
+            vertices.sort();
+            vertices.dedup();
+
+            let offset = vertices
+                .iter()
+                .map(|vertex| normal.dot(&self.positions[*vertex].coords))
+                .sum::<f64>()
+                / vertices.len() as f64;
+            let distance = |position: &Point3<f64>| normal.dot(&position.coords) - offset;
+
+            if vertices
+                .iter()
+                .any(|vertex| distance(&self.positions[*vertex]).abs() > max_error)
+            {
+                continue;
+            }
+
+            for vertex in vertices {
+                let interior = self.vertex_triangles[vertex]
+                    .iter()
+                    .filter(|triangle| !self.removed[**triangle])
+                    .all(|triangle| region_of_triangle[*triangle] == Some(region));
+
+                if interior {
+                    let position = self.positions[vertex];
+                    self.positions[vertex] = position - normal * distance(&position);
+                }
+            }
+        }
+    }
+
+    fn into_occluder(self, source_mesh: String) -> Occluder {
+        let mut new_indices = HashMap::new();
+        let mut positions = vec![];
+        let mut indices = vec![];
+
+        for (idx, triangle) in self.triangles.iter().enumerate() {
+            if self.removed[idx] {
+                continue;
+            }
+
+            for corner in triangle.iter() {
+                let index = *new_indices.entry(*corner).or_insert_with(|| {
+                    let position = self.positions[*corner];
+                    positions.push(position.x as f32);
+                    positions.push(position.y as f32);
+                    positions.push(position.z as f32);
+
+                    (positions.len() / 3 - 1) as u16
+                });
+
+                indices.push(index);
+            }
+        }
+
+        Occluder {
+            source_mesh,
+            positions,
+            indices,
+        }
+    }
+
+    /// Pick the cheapest of the two endpoints and the midpoint to collapse an edge to.
+    fn cheapest_collapse(&self, a: usize, b: usize) -> Collapse {
+        let quadric = self.quadrics[a] + self.quadrics[b];
+
+        let (pa, pb) = (self.positions[a], self.positions[b]);
+        let midpoint = Point3::from((pa.coords + pb.coords) / 2.);
+
+        let (error, position) = [pa, pb, midpoint]
+            .iter()
+            .map(|position| (quadric_error(&quadric, position), *position))
+            .min_by(|x, y| x.0.partial_cmp(&y.0).unwrap_or(Ordering::Equal))
+            .unwrap();
+
+        Collapse {
+            error,
+            keep: a,
+            remove: b,
+            position,
+            versions: (self.versions[a], self.versions[b]),
+        }
+    }
+
+    /// Whether or not a collapse would turn any of the surrounding triangles inside out.
+    fn flips_triangle(&self, collapse: &Collapse) -> bool {
+        let surrounding = self.vertex_triangles[collapse.keep]
+            .iter()
+            .chain(self.vertex_triangles[collapse.remove].iter());
+
+        for triangle in surrounding {
+            let corners = self.triangles[*triangle];
+            if self.removed[*triangle]
+                || (corners.contains(&collapse.keep) && corners.contains(&collapse.remove))
+            {
+                continue;
+            }
+
+            let moved = |corner: usize| match corner {
+                c if c == collapse.keep || c == collapse.remove => collapse.position,
+                c => self.positions[c],
+            };
+
+            let before = self.triangle_normal(*triangle);
+            let after = (moved(corners[1]) - moved(corners[0]))
+                .cross(&(moved(corners[2]) - moved(corners[0])));
+
+            if before.norm_squared() > std::f64::EPSILON && before.dot(&after) <= 0. {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn collapse(&mut self, collapse: &Collapse) {
+        let (keep, remove) = (collapse.keep, collapse.remove);
+
+        self.positions[keep] = collapse.position;
+        self.quadrics[keep] = self.quadrics[keep] + self.quadrics[remove];
+
+        for triangle in std::mem::replace(&mut self.vertex_triangles[remove], vec![]) {
+            if self.removed[triangle] {
+                continue;
+            }
+
+            if self.triangles[triangle].contains(&keep) {
+                self.removed[triangle] = true;
+                self.live_triangles -= 1;
+            } else {
+                for corner in self.triangles[triangle].iter_mut() {
+                    if *corner == remove {
+                        *corner = keep;
+                    }
+                }
+                self.vertex_triangles[keep].push(triangle);
+            }
+        }
+
+        let removed = &self.removed;
+        self.vertex_triangles[keep].retain(|triangle| !removed[*triangle]);
+
+        self.versions[keep] += 1;
+        self.versions[remove] += 1;
+    }
+
+    fn neighbors(&self, vertex: usize) -> Vec<usize> {
+        let mut neighbors: Vec<usize> = self.vertex_triangles[vertex]
+            .iter()
+            .flat_map(|triangle| self.triangles[*triangle].iter().copied())
+            .filter(|neighbor| *neighbor != vertex)
+            .collect();
+
+        neighbors.sort();
+        neighbors.dedup();
+        neighbors
+    }
+
+    /// The live triangles that use each edge, keyed by the edge's (lower, higher) vertex indices.
+    fn edge_triangles(&self) -> HashMap<(usize, usize), Vec<usize>> {
+        let mut edges: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+
+        for (idx, triangle) in self.triangles.iter().enumerate() {
+            if self.removed[idx] {
+                continue;
+            }
+
+            for corner in 0..3 {
+                let a = triangle[corner];
+                let b = triangle[(corner + 1) % 3];
+
+                edges.entry((a.min(b), a.max(b))).or_default().push(idx);
+            }
+        }
+
+        edges
+    }
+
+    /// The unnormalized normal of a triangle, which has a length of twice the triangle's area.
+    fn triangle_normal(&self, triangle: usize) -> Vector3<f64> {
+        let [a, b, c] = self.triangles[triangle];
+        let (a, b, c) = (self.positions[a], self.positions[b], self.positions[c]);
+
+        (b - a).cross(&(c - a))
+    }
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.error == other.error
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    /// Reversed so that the BinaryHeap pops the cheapest collapse first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .error
+            .partial_cmp(&self.error)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+/// The quadric that measures the squared distance to a plane.
+fn plane_quadric(normal: &Vector3<f64>, point_on_plane: &Point3<f64>) -> Matrix4<f64> {
+    let plane = Vector4::new(
+        normal.x,
+        normal.y,
+        normal.z,
+        -normal.dot(&point_on_plane.coords),
+    );
+
+    plane * plane.transpose()
+}
+
+fn quadric_error(quadric: &Matrix4<f64>, position: &Point3<f64>) -> f64 {
+    let position = position.to_homogeneous();
+    (position.transpose() * quadric * position)[0].max(0.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that we simplify down to the target triangle count when the error bound allows it
+    /// and that we never grow past the original surface.
+    #[test]
+    fn decimates_to_target() {
+        let sphere = BlenderMesh::uv_sphere(1.0, 32, 16);
+        let config = OccluderConfig {
+            target_triangle_count: 60,
+            max_error: 100.0,
+            planar_angle_degrees: 0.,
+        };
+
+        let occluder = sphere.occluder(&config);
+
+        assert!(occluder.triangle_count() <= 60);
+        assert!(occluder.triangle_count() > 0);
+        for position in occluder.positions().chunks(3) {
+            let distance = Vector3::new(position[0], position[1], position[2]).norm();
+            assert!(distance <= 1.0 + 1e-5);
+        }
+    }
+
+    /// Verify that flat meshes collapse while keeping their outline.
+    #[test]
+    fn keeps_boundaries() {
+        let plane = BlenderMesh::plane(2.0, 2.0, 3);
+        let config = OccluderConfig {
+            target_triangle_count: 2,
+            max_error: 0.01,
+            ..OccluderConfig::default()
+        };
+
+        let occluder = plane.occluder(&config);
+
+        // The plane starts out with 32 triangles
+        assert!(occluder.triangle_count() < 32);
+        for axis in 0..2 {
+            let coords = occluder.positions().iter().skip(axis).step_by(3);
+            assert_eq!(coords.clone().cloned().fold(std::f32::MAX, f32::min), -1.0);
+            assert_eq!(coords.cloned().fold(std::f32::MIN, f32::max), 1.0);
+        }
+    }
+
+    /// Verify that we stop simplifying when it would introduce too much error.
+    #[test]
+    fn respects_error_bound() {
+        let mut cube = BlenderMesh::cube(2.0);
+        cube.set_name("Crate".to_string());
+
+        let config = OccluderConfig {
+            target_triangle_count: 0,
+            max_error: 0.01,
+            ..OccluderConfig::default()
+        };
+
+        let occluder = cube.occluder(&config);
+
+        assert_eq!(occluder.source_mesh(), "Crate");
+        assert_eq!(occluder.triangle_count(), 12);
+    }
+
+    /// Verify that a slightly bumpy surface gets flattened.
+    #[test]
+    fn planarizes_nearly_flat_regions() {
+        let mut plane = BlenderMesh::plane(2.0, 2.0, 1);
+
+        // Nudge the center vertex up a bit
+        let positions = &mut plane
+            .multi_indexed_vertex_attributes
+            .positions
+            .attribute
+            .data;
+        for position in positions.chunks_mut(3) {
+            if position[0] == 0.0 && position[1] == 0.0 {
+                position[2] = 0.02;
+            }
+        }
+
+        let config = OccluderConfig {
+            target_triangle_count: 1000,
+            max_error: 0.05,
+            planar_angle_degrees: 10.,
+        };
+        let occluder = plane.occluder(&config);
+
+        assert_eq!(occluder.triangle_count(), 8);
+        let max_z = occluder
+            .positions()
+            .iter()
+            .skip(2)
+            .step_by(3)
+            .fold(0f32, |max, z| max.max(z.abs()));
+        assert!(max_z < 0.02);
+    }
+}
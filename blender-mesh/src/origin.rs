@@ -0,0 +1,160 @@
+use crate::BlenderMesh;
+use nalgebra::{Matrix4, Point3, Vector3};
+
+impl BlenderMesh {
+    /// Move the mesh's [`SpaceConvention.method#origin`] without moving the mesh, so that its
+    /// positions are stored relative to the new origin.
+    ///
+    /// Meshes that are far from the world origin, such as the pieces of a large level, lose
+    /// precision in their f32 positions. Rebasing them onto an origin near their own center keeps
+    /// their positions small, and the origin is kept in f64 so that engines can place the mesh
+    /// relative to the camera without losing that precision again.
+    ///
+    /// ```
+    /// # use blender_mesh::BlenderMesh;
+    /// let mut cube = BlenderMesh::cube(1.);
+    /// cube.rebase_origin([0.5, 0., 0.]);
+    ///
+    /// let positions = cube.multi_indexed_vertex_attributes().positions().attribute().data();
+    /// assert_eq!(positions[0..3], [-1., -0.5, -0.5]);
+    /// assert_eq!(cube.positions_f64()[0..3], [-0.5, -0.5, -0.5]);
+    /// ```
+    pub fn rebase_origin(&mut self, origin: [f64; 3]) {
+        let old = Vector3::from(self.space.origin);
+        let offset = old - Vector3::from(origin);
+
+        let positions = &mut self
+            .multi_indexed_vertex_attributes
+            .positions
+            .attribute
+            .data;
+        for position in positions.chunks_exact_mut(3) {
+            for (axis, value) in position.iter_mut().enumerate() {
+                *value = (*value as f64 + offset[axis]) as f32;
+            }
+        }
+
+        let offset_f32 = offset.map(|axis| axis as f32);
+        self.bounding_box.min_corner += offset_f32;
+        self.bounding_box.max_corner += offset_f32;
+
+        self.space.origin = origin;
+        self.invalidate_bvh();
+    }
+
+    /// Transform the mesh by a double precision matrix, such as an object's world transform in a
+    /// large level.
+    ///
+    /// The matrix's translation only moves the [`SpaceConvention.method#origin`], which is
+    /// transformed in f64, while the positions are rotated and scaled in place by
+    /// [`BlenderMesh.method#apply_transform`]. So moving a mesh far from the world origin doesn't
+    /// cost its positions any precision.
+    pub fn apply_transform_f64(&mut self, matrix: &Matrix4<f64>) {
+        let origin = Point3::from(Vector3::from(self.space.origin));
+        let origin = matrix.transform_point(&origin);
+
+        let mut linear = matrix.map(|value| value as f32);
+        for row in 0..3 {
+            linear[(row, 3)] = 0.;
+        }
+        self.apply_transform(&linear);
+
+        self.space.origin = [origin.x, origin.y, origin.z];
+    }
+
+    /// The center of the box around the mesh's positions, added to the
+    /// [`SpaceConvention.method#origin`] in f64.
+    ///
+    /// This is in the same space as the origin, unlike the [`BlenderMesh.method#bounding_box`]
+    /// which Blender exports in world space, so it is the origin to give
+    /// [`BlenderMesh.method#rebase_origin`] to center the mesh's positions on themselves.
+    ///
+    /// Meshes without any positions are centered on their origin.
+    pub fn positions_center(&self) -> [f64; 3] {
+        let positions = &self
+            .multi_indexed_vertex_attributes
+            .positions
+            .attribute
+            .data;
+        if positions.len() < 3 {
+            return self.space.origin;
+        }
+
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        for position in positions.chunks_exact(3) {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(position[axis] as f64);
+                max[axis] = max[axis].max(position[axis] as f64);
+            }
+        }
+
+        let origin = self.space.origin;
+        let center = |axis: usize| origin[axis] + (min[axis] + max[axis]) / 2.;
+        [center(0), center(1), center(2)]
+    }
+
+    /// Every position added to the [`SpaceConvention.method#origin`] in f64, for processing that
+    /// needs the mesh's positions in the scene that it was exported from.
+    pub fn positions_f64(&self) -> Vec<f64> {
+        let origin = self.space.origin;
+        self.multi_indexed_vertex_attributes
+            .positions
+            .attribute
+            .data
+            .chunks_exact(3)
+            .flat_map(|position| (0..3).map(move |axis| origin[axis] + position[axis] as f64))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that positions keep their precision when a mesh is moved far from the world origin
+    /// and rebased back onto itself.
+    #[test]
+    fn transform_far_from_origin() {
+        let mut cube = BlenderMesh::cube(1.);
+        let far = 10_000_000.25;
+
+        let mut matrix = Matrix4::new_nonuniform_scaling(&Vector3::new(2., 2., 2.));
+        matrix[(0, 3)] = far;
+        cube.apply_transform_f64(&matrix);
+
+        assert_eq!(cube.space().origin(), [far, 0., 0.]);
+        assert_eq!(cube.positions_f64()[0..3], [far - 1., -1., -1.]);
+
+        // A whole number origin keeps the positions exact
+        cube.rebase_origin([far.floor(), 0., 0.]);
+        assert_eq!(cube.positions_f64()[0..3], [far - 1., -1., -1.]);
+        assert_eq!(cube.bounding_box().min_corner, Point3::new(-0.75, -1., -1.));
+
+        // The origin moves when the mesh is scaled or converted to Y up
+        cube.scale(0.5);
+        cube.y_up();
+        assert_eq!(cube.space().origin(), [far.floor() / 2., 0., 0.]);
+    }
+
+    /// Verify that a placed object is centered on its own positions and not on its bounding box,
+    /// which is in world space.
+    #[test]
+    fn center_placed_object() {
+        let mut cube = BlenderMesh::cube(1.);
+        cube.apply_transform_f64(&Matrix4::new_translation(&Vector3::new(3., 0., 0.)));
+
+        let world_transform = Matrix4::new_translation(&Vector3::new(100., 0., 0.));
+        let mut world_cube = cube.clone();
+        world_cube.apply_transform(&world_transform.map(|value| value as f32));
+        cube.set_bounding_box(world_cube.bounding_box());
+        cube.set_world_transform(Some(world_transform));
+
+        assert_eq!(cube.positions_center(), [3., 0., 0.]);
+
+        let positions = cube.positions_f64();
+        cube.rebase_origin(cube.positions_center());
+        assert_eq!(cube.space().origin(), [3., 0., 0.]);
+        assert_eq!(cube.positions_f64(), positions);
+    }
+}
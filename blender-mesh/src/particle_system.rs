@@ -0,0 +1,191 @@
+use crate::Polyline;
+use nalgebra::{Matrix4, Quaternion, Translation3, UnitQuaternion, Vector3};
+use std::collections::{BTreeMap, HashMap};
+
+pub type ParticleSystemsByFilename = HashMap<String, ParticleSystemsByObjectName>;
+pub type ParticleSystemsByObjectName = HashMap<String, Vec<ParticleSystem>>;
+
+/// A particle system on a mesh, such as rocks or grass that were scattered over terrain.
+///
+/// Particles that instance objects or collections are exported as the transforms of the
+/// instances, and hair particles are exported as one polyline for every strand so that they can
+/// be used as guides for grass or fur.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ParticleSystem {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) instances: Vec<ParticleInstance>,
+    #[serde(default)]
+    pub(crate) hair: Vec<Polyline>,
+}
+
+/// An object that a [`ParticleSystem`] places in the world.
+///
+/// [`ParticleSystem`]: struct.ParticleSystem.html
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ParticleInstance {
+    pub(crate) object: String,
+    pub(crate) translation: [f32; 3],
+    /// w, x, y, z
+    pub(crate) rotation: [f32; 4],
+    pub(crate) scale: [f32; 3],
+}
+
+impl ParticleSystem {
+    /// The name of the particle system
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Every object that the particles instance
+    pub fn instances(&self) -> &Vec<ParticleInstance> {
+        &self.instances
+    }
+
+    /// The world space strands of hair particles, with the particle system's name as their
+    /// layer.
+    ///
+    /// Every strand's thickness tapers from the hair's root radius to its tip radius.
+    pub fn hair(&self) -> &Vec<Polyline> {
+        &self.hair
+    }
+
+    /// The world matrices of the instances, grouped by the name of the object that they instance
+    /// so that every object can be drawn with a single instanced draw call.
+    pub fn instance_matrices(&self) -> BTreeMap<&str, Vec<Matrix4<f32>>> {
+        let mut matrices: BTreeMap<&str, Vec<Matrix4<f32>>> = BTreeMap::new();
+
+        for instance in self.instances.iter() {
+            matrices
+                .entry(instance.object.as_str())
+                .or_default()
+                .push(instance.matrix());
+        }
+
+        matrices
+    }
+}
+
+impl ParticleInstance {
+    /// The name of the object that is instanced, which is the name that its mesh is exported
+    /// under.
+    pub fn object(&self) -> &String {
+        &self.object
+    }
+
+    /// The instance's world space position
+    pub fn translation(&self) -> [f32; 3] {
+        self.translation
+    }
+
+    /// The instance's world space rotation
+    pub fn rotation(&self) -> UnitQuaternion<f32> {
+        let [w, x, y, z] = self.rotation;
+        UnitQuaternion::from_quaternion(Quaternion::new(w, x, y, z))
+    }
+
+    /// The instance's world space scale
+    pub fn scale(&self) -> [f32; 3] {
+        self.scale
+    }
+
+    /// The instance's world matrix, which scales, then rotates, then translates.
+    pub fn matrix(&self) -> Matrix4<f32> {
+        let [x, y, z] = self.translation;
+        let translation = Translation3::new(x, y, z).to_homogeneous();
+
+        let [x, y, z] = self.scale;
+        let scale = Matrix4::new_nonuniform_scaling(&Vector3::new(x, y, z));
+
+        translation * self.rotation().to_homogeneous() * scale
+    }
+}
+
+/// Given a buffer of standard output from Blender we parse all of the particle system JSON that
+/// was written to stdout by `blender-mesh-to-json.py`.
+///
+/// Particle system data in stdout will look like:
+///
+/// START_PARTICLE_SYSTEMS_JSON /path/to/file.blend my_mesh_name
+/// [...]
+/// END_PARTICLE_SYSTEMS_JSON /path/to/file.blend my_mesh_name
+///
+/// @see blender-mesh-to-json.py - This is where we write to stdout
+pub fn parse_particle_systems_from_blender_stdout(
+    blender_stdout: &str,
+) -> ParticleSystemsByFilename {
+    let start_marker = "START_PARTICLE_SYSTEMS_JSON";
+    let end_marker = "END_PARTICLE_SYSTEMS_JSON";
+
+    let mut filenames_to_particle_systems = ParticleSystemsByFilename::new();
+
+    let mut remaining = blender_stdout;
+
+    while let Some(start_idx) = remaining.find(start_marker) {
+        let end_idx = remaining.find(end_marker).unwrap();
+
+        let mut lines = remaining[start_idx..end_idx].lines();
+
+        let first_line = lines.next().unwrap();
+        let filename = first_line.split(' ').nth(1).unwrap().to_string();
+        let mesh_name = first_line.split(' ').next_back().unwrap().to_string();
+
+        let particle_systems: String = lines.collect();
+        let particle_systems: Vec<ParticleSystem> =
+            serde_json::from_str(&particle_systems).unwrap();
+
+        filenames_to_particle_systems
+            .entry(filename)
+            .or_default()
+            .insert(mesh_name, particle_systems);
+
+        remaining = &remaining[end_idx + end_marker.len()..];
+    }
+
+    filenames_to_particle_systems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Point3;
+
+    /// Verify that we parse instances and hair, and group the instances by the object that they
+    /// instance.
+    #[test]
+    fn parse_particle_systems() {
+        let stdout = r#"
+START_PARTICLE_SYSTEMS_JSON /meadow.blend Ground
+[
+  {
+    "name": "Rocks",
+    "instances": [
+      {"object": "Boulder", "translation": [1, 0, 0], "rotation": [0.70710677, 0, 0, 0.70710677], "scale": [2, 2, 2]},
+      {"object": "Pebble", "translation": [0, 0, 0], "rotation": [1, 0, 0, 0], "scale": [1, 1, 1]},
+      {"object": "Boulder", "translation": [5, 0, 0], "rotation": [1, 0, 0, 0], "scale": [1, 1, 1]}
+    ]
+  },
+  {
+    "name": "Grass",
+    "hair": [{"layer": "Grass", "points": [[0, 0, 0], [0, 0, 0.5]], "thickness": [0.02, 0.01], "cyclic": false}]
+  }
+]
+END_PARTICLE_SYSTEMS_JSON /meadow.blend Ground
+"#;
+        let parsed = parse_particle_systems_from_blender_stdout(stdout);
+        let systems = &parsed["/meadow.blend"]["Ground"];
+
+        let rocks = &systems[0];
+        let matrices = rocks.instance_matrices();
+        assert_eq!(matrices["Boulder"].len(), 2);
+        assert_eq!(matrices["Pebble"].len(), 1);
+
+        // Scaled, rotated 90 degrees around Z, then translated
+        let moved = matrices["Boulder"][0].transform_point(&Point3::new(1., 0., 0.));
+        assert!((moved - Point3::new(1., 2., 0.)).norm() < 1e-5);
+
+        let grass = &systems[1];
+        assert!(grass.instances().is_empty());
+        assert_eq!(grass.hair()[0].length(), 0.5);
+    }
+}
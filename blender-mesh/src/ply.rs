@@ -0,0 +1,732 @@
+//! Read and write [PLY] files, useful for exchanging scan data and for inspecting intermediate
+//! processing results in tools such as MeshLab.
+//!
+//! [PLY]: http://paulbourke.net/dataformats/ply/
+
+use crate::vertex_attributes::{IndexedAttribute, MultiIndexedVertexAttributes, VertexAttribute};
+use crate::{BlenderMesh, BoundingBox};
+use nalgebra::Point3;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+/// The vertices and faces of a PLY file.
+///
+/// Every vertex has a position and optionally a normal and an RGB color.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Ply {
+    pub(crate) positions: Vec<f32>,
+    pub(crate) normals: Option<Vec<f32>>,
+    pub(crate) colors: Option<Vec<u8>>,
+    pub(crate) faces: Vec<Vec<u32>>,
+}
+
+/// How the body of a PLY file is encoded.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PlyFormat {
+    /// Human readable text
+    Ascii,
+    /// Little endian binary
+    BinaryLittleEndian,
+}
+
+/// An error while reading a PLY file or converting it into a mesh.
+#[derive(Debug, thiserror::Error)]
+pub enum PlyError {
+    #[error("Failed to read PLY data: {0}")]
+    Io(#[from] io::Error),
+    /// The header is missing or malformed.
+    #[error("Invalid PLY header: {0}")]
+    InvalidHeader(String),
+    /// Only ASCII and binary little endian files are supported.
+    #[error("Unsupported PLY format: {0}")]
+    UnsupportedFormat(String),
+    /// The body has fewer values than the header describes, or a value could not be parsed.
+    #[error("Invalid PLY body: {0}")]
+    InvalidBody(String),
+    /// A face refers to a vertex that does not exist.
+    #[error("Face {face} refers to vertex {vertex} but there are only {vertex_count} vertices")]
+    VertexOutOfRange {
+        face: usize,
+        vertex: u32,
+        vertex_count: usize,
+    },
+    /// Meshes store the number of vertices in each face as a u8.
+    #[error("Face {face} has {vertex_count} vertices, but faces can have at most 255")]
+    FaceTooLarge { face: usize, vertex_count: usize },
+}
+
+impl Ply {
+    /// Create PLY data from a mesh's positions, normals and faces.
+    ///
+    /// PLY vertices only have one index, so every unique combination of position and normal in
+    /// the mesh becomes a vertex. A mesh without any faces is treated as a point cloud.
+    pub fn from_mesh(mesh: &BlenderMesh) -> Ply {
+        let multi = &mesh.multi_indexed_vertex_attributes;
+        let positions = &multi.positions;
+
+        if multi.vertices_in_each_face.is_empty() {
+            let vertex_count = positions.attribute.data.len() / 3;
+            let normals = multi
+                .normals
+                .as_ref()
+                .map(|normals| normals.attribute.data.clone())
+                .filter(|normals| normals.len() == vertex_count * 3);
+
+            return Ply {
+                positions: positions.attribute.data.clone(),
+                normals,
+                ..Ply::default()
+            };
+        }
+
+        let mut ply = Ply {
+            normals: multi.normals.as_ref().map(|_| vec![]),
+            ..Ply::default()
+        };
+        let mut vertex_ids: HashMap<(u32, Option<u32>), u32> = HashMap::new();
+
+        let mut corner = 0;
+        for vertex_count in multi.vertices_in_each_face.iter() {
+            let mut face = vec![];
+
+            for corner in corner..corner + *vertex_count as usize {
+                let position_idx = positions.indices[corner];
+                let normal_idx = multi.normals.as_ref().map(|n| n.indices[corner]);
+
+                let vertex_id =
+                    *vertex_ids
+                        .entry((position_idx, normal_idx))
+                        .or_insert_with(|| {
+                            ply.positions
+                                .extend_from_slice(positions.attribute.data_at_idx(position_idx));
+
+                            if let (Some(normals), Some(normal_idx)) =
+                                (ply.normals.as_mut(), normal_idx)
+                            {
+                                let normal_data = &multi.normals.as_ref().unwrap().attribute;
+                                normals.extend_from_slice(normal_data.data_at_idx(normal_idx));
+                            }
+
+                            (ply.positions.len() / 3 - 1) as u32
+                        });
+
+                face.push(vertex_id);
+            }
+
+            ply.faces.push(face);
+            corner += *vertex_count as usize;
+        }
+
+        ply
+    }
+
+    /// Convert the PLY data into a mesh. Colors are not kept since meshes don't have vertex
+    /// colors.
+    pub fn to_mesh(&self, name: &str) -> Result<BlenderMesh, PlyError> {
+        let vertex_count = self.vertex_count();
+
+        let mut vertices_in_each_face = vec![];
+        let mut indices = vec![];
+
+        for (face_idx, face) in self.faces.iter().enumerate() {
+            if face.len() > std::u8::MAX as usize {
+                return Err(PlyError::FaceTooLarge {
+                    face: face_idx,
+                    vertex_count: face.len(),
+                });
+            }
+            vertices_in_each_face.push(face.len() as u8);
+
+            for vertex in face.iter() {
+                if *vertex as usize >= vertex_count {
+                    return Err(PlyError::VertexOutOfRange {
+                        face: face_idx,
+                        vertex: *vertex,
+                        vertex_count,
+                    });
+                }
+                indices.push(*vertex);
+            }
+        }
+
+        let mut min_corner = Point3::new(std::f32::MAX, std::f32::MAX, std::f32::MAX);
+        let mut max_corner = Point3::new(std::f32::MIN, std::f32::MIN, std::f32::MIN);
+        for position in self.positions.chunks(3) {
+            for axis in 0..3 {
+                min_corner[axis] = min_corner[axis].min(position[axis]);
+                max_corner[axis] = max_corner[axis].max(position[axis]);
+            }
+        }
+        if vertex_count == 0 {
+            min_corner = Point3::origin();
+            max_corner = Point3::origin();
+        }
+
+        let face_count = vertices_in_each_face.len();
+        let normals = self.normals.as_ref().map(|normals| {
+            IndexedAttribute::new(
+                indices.clone(),
+                VertexAttribute {
+                    data: normals.clone(),
+                    attribute_size: 3,
+                },
+            )
+        });
+
+        Ok(BlenderMesh {
+            name: name.to_string(),
+            bounding_box: BoundingBox {
+                min_corner,
+                max_corner,
+            },
+            multi_indexed_vertex_attributes: MultiIndexedVertexAttributes {
+                vertices_in_each_face,
+                material_index: vec![0; face_count],
+                positions: IndexedAttribute::new(
+                    indices,
+                    VertexAttribute {
+                        data: self.positions.clone(),
+                        attribute_size: 3,
+                    },
+                ),
+                normals,
+                uvs: None,
+                lightmap_uvs: None,
+                bone_influences: None,
+            },
+            ..BlenderMesh::default()
+        })
+    }
+
+    /// Parse a PLY file.
+    ///
+    /// Vertex positions (`x`, `y`, `z`), normals (`nx`, `ny`, `nz`), colors (`red`, `green`,
+    /// `blue`) and face indices (`vertex_indices` or `vertex_index`) are read. All other elements
+    /// and properties are skipped.
+    pub fn read(mut reader: impl Read) -> Result<Ply, PlyError> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+
+        let (header, body_start) = Header::parse(&bytes)?;
+        let body = &bytes[body_start..];
+
+        let mut values: Box<dyn ValueSource> = match header.format {
+            PlyFormat::Ascii => {
+                let text = std::str::from_utf8(body)
+                    .map_err(|_| PlyError::InvalidBody("ASCII body is not UTF-8".to_string()))?;
+                Box::new(AsciiValues {
+                    tokens: text.split_whitespace(),
+                })
+            }
+            PlyFormat::BinaryLittleEndian => Box::new(BinaryValues { bytes: body }),
+        };
+
+        let mut ply = Ply::default();
+
+        for element in header.elements.iter() {
+            let is_vertex = element.name == "vertex";
+            let is_face = element.name == "face";
+
+            let property_idx = |name: &str| {
+                element
+                    .properties
+                    .iter()
+                    .position(|property| property.name == name)
+            };
+            let normal_properties = ["nx", "ny", "nz"]
+                .iter()
+                .map(|name| property_idx(name))
+                .collect::<Option<Vec<usize>>>();
+            let color_properties = ["red", "green", "blue"]
+                .iter()
+                .map(|name| property_idx(name))
+                .collect::<Option<Vec<usize>>>();
+
+            if is_vertex {
+                if normal_properties.is_some() {
+                    ply.normals = Some(vec![]);
+                }
+                if color_properties.is_some() {
+                    ply.colors = Some(vec![]);
+                }
+            }
+
+            for _ in 0..element.count {
+                let mut scalars = vec![];
+                let mut lists = vec![];
+
+                for property in element.properties.iter() {
+                    match property.kind {
+                        PropertyKind::Scalar(scalar) => {
+                            scalars.push(values.next(scalar)?);
+                            lists.push(vec![]);
+                        }
+                        PropertyKind::List { count, item } => {
+                            let len = values.next(count)? as usize;
+                            let mut list = Vec::with_capacity(len);
+                            for _ in 0..len {
+                                list.push(values.next(item)?);
+                            }
+                            scalars.push(0.);
+                            lists.push(list);
+                        }
+                    };
+                }
+
+                if is_vertex {
+                    for axis in ["x", "y", "z"].iter() {
+                        let value = property_idx(axis).map(|idx| scalars[idx]).unwrap_or(0.);
+                        ply.positions.push(value as f32);
+                    }
+
+                    if let (Some(normals), Some(properties)) =
+                        (ply.normals.as_mut(), normal_properties.as_ref())
+                    {
+                        normals.extend(properties.iter().map(|idx| scalars[*idx] as f32));
+                    }
+
+                    if let (Some(colors), Some(properties)) =
+                        (ply.colors.as_mut(), color_properties.as_ref())
+                    {
+                        for idx in properties.iter() {
+                            let color = match &element.properties[*idx].kind {
+                                PropertyKind::Scalar(ScalarType::Float)
+                                | PropertyKind::Scalar(ScalarType::Double) => scalars[*idx] * 255.,
+                                _ => scalars[*idx],
+                            };
+                            colors.push(color.round().max(0.).min(255.) as u8);
+                        }
+                    }
+                }
+
+                if is_face {
+                    let indices = property_idx("vertex_indices")
+                        .or_else(|| property_idx("vertex_index"))
+                        .map(|idx| &lists[idx]);
+
+                    if let Some(indices) = indices {
+                        ply.faces
+                            .push(indices.iter().map(|index| *index as u32).collect());
+                    }
+                }
+            }
+        }
+
+        Ok(ply)
+    }
+
+    /// Write the PLY data.
+    pub fn write(&self, out: &mut dyn Write, format: PlyFormat) -> io::Result<()> {
+        let format_name = match format {
+            PlyFormat::Ascii => "ascii",
+            PlyFormat::BinaryLittleEndian => "binary_little_endian",
+        };
+
+        writeln!(out, "ply")?;
+        writeln!(out, "format {} 1.0", format_name)?;
+        writeln!(out, "comment Created by landon")?;
+        writeln!(out, "element vertex {}", self.vertex_count())?;
+        writeln!(out, "property float x")?;
+        writeln!(out, "property float y")?;
+        writeln!(out, "property float z")?;
+        if self.normals.is_some() {
+            writeln!(out, "property float nx")?;
+            writeln!(out, "property float ny")?;
+            writeln!(out, "property float nz")?;
+        }
+        if self.colors.is_some() {
+            writeln!(out, "property uchar red")?;
+            writeln!(out, "property uchar green")?;
+            writeln!(out, "property uchar blue")?;
+        }
+        if !self.faces.is_empty() {
+            writeln!(out, "element face {}", self.faces.len())?;
+            writeln!(out, "property list uchar uint vertex_indices")?;
+        }
+        writeln!(out, "end_header")?;
+
+        for vertex in 0..self.vertex_count() {
+            let mut floats = self.positions[vertex * 3..vertex * 3 + 3].to_vec();
+            if let Some(normals) = self.normals.as_ref() {
+                floats.extend_from_slice(&normals[vertex * 3..vertex * 3 + 3]);
+            }
+            let colors = self
+                .colors
+                .as_ref()
+                .map(|colors| &colors[vertex * 3..vertex * 3 + 3])
+                .unwrap_or(&[]);
+
+            match format {
+                PlyFormat::Ascii => {
+                    let mut line: Vec<String> = floats.iter().map(f32::to_string).collect();
+                    line.extend(colors.iter().map(u8::to_string));
+                    writeln!(out, "{}", line.join(" "))?;
+                }
+                PlyFormat::BinaryLittleEndian => {
+                    for value in floats.iter() {
+                        out.write_all(&value.to_le_bytes())?;
+                    }
+                    out.write_all(colors)?;
+                }
+            };
+        }
+
+        for face in self.faces.iter() {
+            match format {
+                PlyFormat::Ascii => {
+                    let mut line = vec![face.len().to_string()];
+                    line.extend(face.iter().map(u32::to_string));
+                    writeln!(out, "{}", line.join(" "))?;
+                }
+                PlyFormat::BinaryLittleEndian => {
+                    out.write_all(&[face.len() as u8])?;
+                    for index in face.iter() {
+                        out.write_all(&index.to_le_bytes())?;
+                    }
+                }
+            };
+        }
+
+        Ok(())
+    }
+
+    /// The number of vertices
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len() / 3
+    }
+
+    /// The [x, y, z] position of every vertex
+    pub fn positions(&self) -> &Vec<f32> {
+        &self.positions
+    }
+
+    /// The [x, y, z] normal of every vertex
+    pub fn normals(&self) -> Option<&Vec<f32>> {
+        self.normals.as_ref()
+    }
+
+    /// The [r, g, b] color of every vertex
+    pub fn colors(&self) -> Option<&Vec<u8>> {
+        self.colors.as_ref()
+    }
+
+    /// Set the [r, g, b] color of every vertex
+    pub fn set_colors(&mut self, colors: Option<Vec<u8>>) {
+        self.colors = colors;
+    }
+
+    /// The vertex indices of every face
+    pub fn faces(&self) -> &Vec<Vec<u32>> {
+        &self.faces
+    }
+}
+
+#[derive(Debug)]
+struct Header {
+    format: PlyFormat,
+    elements: Vec<Element>,
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+    kind: PropertyKind,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum PropertyKind {
+    Scalar(ScalarType),
+    List { count: ScalarType, item: ScalarType },
+}
+
+#[derive(Debug, Copy, Clone)]
+enum ScalarType {
+    Char,
+    UChar,
+    Short,
+    UShort,
+    Int,
+    UInt,
+    Float,
+    Double,
+}
+
+impl Header {
+    /// Parse the header, returning it along with the index of the first byte of the body.
+    fn parse(bytes: &[u8]) -> Result<(Header, usize), PlyError> {
+        let invalid = |message: &str| PlyError::InvalidHeader(message.to_string());
+
+        let mut format = None;
+        let mut elements: Vec<Element> = vec![];
+        let mut line_start = 0;
+
+        loop {
+            let line_len = bytes[line_start..]
+                .iter()
+                .position(|byte| *byte == b'\n')
+                .ok_or_else(|| invalid("Missing end_header"))?;
+            let line = std::str::from_utf8(&bytes[line_start..line_start + line_len])
+                .map_err(|_| invalid("Header is not UTF-8"))?
+                .trim();
+            let is_first_line = line_start == 0;
+            line_start += line_len + 1;
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            if is_first_line {
+                if line != "ply" {
+                    return Err(invalid("Missing ply magic number"));
+                }
+                continue;
+            }
+
+            match words.as_slice() {
+                ["end_header"] => break,
+                ["format", name, _version] => {
+                    format = Some(match *name {
+                        "ascii" => PlyFormat::Ascii,
+                        "binary_little_endian" => PlyFormat::BinaryLittleEndian,
+                        other => return Err(PlyError::UnsupportedFormat(other.to_string())),
+                    });
+                }
+                ["element", name, count] => elements.push(Element {
+                    name: name.to_string(),
+                    count: count.parse().map_err(|_| invalid(line))?,
+                    properties: vec![],
+                }),
+                ["property", "list", count, item, name] => {
+                    let kind = PropertyKind::List {
+                        count: ScalarType::parse(count).ok_or_else(|| invalid(line))?,
+                        item: ScalarType::parse(item).ok_or_else(|| invalid(line))?,
+                    };
+                    push_property(&mut elements, name, kind).ok_or_else(|| invalid(line))?;
+                }
+                ["property", scalar, name] => {
+                    let kind = PropertyKind::Scalar(
+                        ScalarType::parse(scalar).ok_or_else(|| invalid(line))?,
+                    );
+                    push_property(&mut elements, name, kind).ok_or_else(|| invalid(line))?;
+                }
+                ["comment", ..] | ["obj_info", ..] | [] => {}
+                _ => return Err(invalid(line)),
+            };
+        }
+
+        let format = format.ok_or_else(|| invalid("Missing format"))?;
+
+        Ok((Header { format, elements }, line_start))
+    }
+}
+
+fn push_property(elements: &mut Vec<Element>, name: &str, kind: PropertyKind) -> Option<()> {
+    elements.last_mut()?.properties.push(Property {
+        name: name.to_string(),
+        kind,
+    });
+    Some(())
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Option<ScalarType> {
+        let scalar = match name {
+            "char" | "int8" => ScalarType::Char,
+            "uchar" | "uint8" => ScalarType::UChar,
+            "short" | "int16" => ScalarType::Short,
+            "ushort" | "uint16" => ScalarType::UShort,
+            "int" | "int32" => ScalarType::Int,
+            "uint" | "uint32" => ScalarType::UInt,
+            "float" | "float32" => ScalarType::Float,
+            "double" | "float64" => ScalarType::Double,
+            _ => return None,
+        };
+        Some(scalar)
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            ScalarType::Char | ScalarType::UChar => 1,
+            ScalarType::Short | ScalarType::UShort => 2,
+            ScalarType::Int | ScalarType::UInt | ScalarType::Float => 4,
+            ScalarType::Double => 8,
+        }
+    }
+}
+
+/// The values in the body of a PLY file, in order.
+trait ValueSource {
+    fn next(&mut self, scalar: ScalarType) -> Result<f64, PlyError>;
+}
+
+struct AsciiValues<'a> {
+    tokens: std::str::SplitWhitespace<'a>,
+}
+
+impl<'a> ValueSource for AsciiValues<'a> {
+    fn next(&mut self, _scalar: ScalarType) -> Result<f64, PlyError> {
+        let token = self
+            .tokens
+            .next()
+            .ok_or_else(|| PlyError::InvalidBody("Unexpected end of file".to_string()))?;
+
+        token
+            .parse()
+            .map_err(|_| PlyError::InvalidBody(format!("Invalid number {}", token)))
+    }
+}
+
+struct BinaryValues<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ValueSource for BinaryValues<'a> {
+    fn next(&mut self, scalar: ScalarType) -> Result<f64, PlyError> {
+        let size = scalar.size();
+        if self.bytes.len() < size {
+            return Err(PlyError::InvalidBody("Unexpected end of file".to_string()));
+        }
+
+        let (value, rest) = self.bytes.split_at(size);
+        self.bytes = rest;
+
+        let mut buf = [0; 8];
+        buf[..size].copy_from_slice(value);
+        let [b0, b1, b2, b3, ..] = buf;
+
+        let value = match scalar {
+            ScalarType::Char => value[0] as i8 as f64,
+            ScalarType::UChar => value[0] as f64,
+            ScalarType::Short => i16::from_le_bytes([b0, b1]) as f64,
+            ScalarType::UShort => u16::from_le_bytes([b0, b1]) as f64,
+            ScalarType::Int => i32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            ScalarType::UInt => u32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            ScalarType::Float => f32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            ScalarType::Double => f64::from_le_bytes(buf),
+        };
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that we can write a mesh and read it back in both formats.
+    #[test]
+    fn round_trip() {
+        let plane = BlenderMesh::plane(2.0, 2.0, 1);
+
+        let mut ply = Ply::from_mesh(&plane);
+        assert_eq!(ply.vertex_count(), 9);
+        assert_eq!(ply.faces.len(), 4);
+
+        ply.set_colors(Some((0..27).collect()));
+
+        for format in [PlyFormat::Ascii, PlyFormat::BinaryLittleEndian].iter() {
+            let mut bytes = vec![];
+            ply.write(&mut bytes, *format).unwrap();
+
+            assert_eq!(Ply::read(bytes.as_slice()).unwrap(), ply);
+        }
+    }
+
+    /// Verify that we skip the elements and properties that we don't use and convert float
+    /// colors to bytes.
+    #[test]
+    fn read_ascii() {
+        let ply = r#"ply
+format ascii 1.0
+comment A scan
+element vertex 3
+property float x
+property float y
+property float z
+property float confidence
+property float red
+property float green
+property float blue
+element face 1
+property uchar flags
+property list uchar int vertex_index
+element camera 1
+property float fov
+end_header
+0 0 0 0.5 1 0 0
+1 0 0 0.5 0 1 0
+0 1 0 0.5 0 0 1
+7 3 0 1 2
+60
+"#;
+
+        let ply = Ply::read(ply.as_bytes()).unwrap();
+
+        assert_eq!(ply.positions, vec![0., 0., 0., 1., 0., 0., 0., 1., 0.]);
+        assert_eq!(ply.normals, None);
+        assert_eq!(ply.colors, Some(vec![255, 0, 0, 0, 255, 0, 0, 0, 255]));
+        assert_eq!(ply.faces, vec![vec![0, 1, 2]]);
+
+        let mesh = ply.to_mesh("Scan").unwrap();
+        assert_eq!(
+            mesh.multi_indexed_vertex_attributes.vertices_in_each_face,
+            vec![3]
+        );
+        assert_eq!(mesh.bounding_box.max_corner, Point3::new(1., 1., 0.));
+    }
+
+    /// Verify that a mesh without faces is written as a point cloud.
+    #[test]
+    fn point_cloud() {
+        let ply = Ply {
+            positions: vec![0., 0., 0., 1., 2., 3.],
+            normals: Some(vec![0., 0., 1., 0., 1., 0.]),
+            ..Ply::default()
+        };
+
+        let mesh = ply.to_mesh("Points").unwrap();
+        assert_eq!(Ply::from_mesh(&mesh), ply);
+
+        let mut bytes = vec![];
+        ply.write(&mut bytes, PlyFormat::Ascii).unwrap();
+        assert!(!String::from_utf8(bytes).unwrap().contains("element face"));
+    }
+
+    /// Verify that we reject invalid files.
+    #[test]
+    fn invalid_files() {
+        let missing_magic = "format ascii 1.0\nend_header\n";
+        match Ply::read(missing_magic.as_bytes()) {
+            Err(PlyError::InvalidHeader(_)) => {}
+            other => panic!("{:?}", other),
+        };
+
+        let big_endian = "ply\nformat binary_big_endian 1.0\nend_header\n";
+        match Ply::read(big_endian.as_bytes()) {
+            Err(PlyError::UnsupportedFormat(_)) => {}
+            other => panic!("{:?}", other),
+        };
+
+        let truncated =
+            "ply\nformat ascii 1.0\nelement vertex 2\nproperty float x\nend_header\n1\n";
+        match Ply::read(truncated.as_bytes()) {
+            Err(PlyError::InvalidBody(_)) => {}
+            other => panic!("{:?}", other),
+        };
+
+        let ply = Ply {
+            positions: vec![0.; 3],
+            faces: vec![vec![0, 1, 2]],
+            ..Ply::default()
+        };
+        match ply.to_mesh("Invalid") {
+            Err(PlyError::VertexOutOfRange { vertex: 1, .. }) => {}
+            other => panic!("{:?}", other),
+        };
+    }
+}
@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub type PointCachesByFilename = HashMap<String, PointCachesByObjectName>;
+pub type PointCachesByObjectName = HashMap<String, PointCache>;
+
+/// The simulated positions of a mesh on every frame of its cloth or soft body simulation, such
+/// as a flag or a cape, so that the simulation can be replayed without running a simulator.
+///
+/// Every frame has a position for each of the mesh's position indices, in the same local space
+/// as [`BlenderMesh.method#evaluate_vertex_positions`]. Anything that changes the exported mesh's
+/// positions, such as snapping them to a grid, isn't applied to the cache.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct PointCache {
+    pub(crate) name: String,
+    pub(crate) frames_per_second: u8,
+    pub(crate) frame_range_inclusive: (u16, u16),
+    /// [x, y, z, x, y, z, ...] for every frame
+    pub(crate) frames: Vec<Vec<f32>>,
+}
+
+/// The offsets of a [`PointCache`] from a mesh's rest positions, laid out as a texture with a
+/// column for every position and a row for every frame, for playing the simulation back in a
+/// vertex shader.
+///
+/// [`PointCache`]: struct.PointCache.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct VertexAnimationTexture {
+    width: u32,
+    height: u32,
+    offsets: Vec<[f32; 3]>,
+    min_offset: [f32; 3],
+    max_offset: [f32; 3],
+}
+
+/// An error while creating a [`VertexAnimationTexture`]
+///
+/// [`VertexAnimationTexture`]: struct.VertexAnimationTexture.html
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum PointCacheError {
+    /// The rest positions are for a different mesh than the point cache.
+    #[error("The point cache has {cache} positions per frame but there are {rest} rest positions")]
+    PositionCount {
+        /// The number of positions on each frame of the point cache
+        cache: usize,
+        /// The number of rest positions
+        rest: usize,
+    },
+}
+
+impl PointCache {
+    #[allow(missing_docs)]
+    pub fn new(
+        name: String,
+        frames_per_second: u8,
+        frame_range_inclusive: (u16, u16),
+        frames: Vec<Vec<f32>>,
+    ) -> Self {
+        PointCache {
+            name,
+            frames_per_second,
+            frame_range_inclusive,
+            frames,
+        }
+    }
+
+    /// The name of the mesh that was simulated
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// The frames per second of the scene that the simulation was baked in
+    pub fn frames_per_second(&self) -> u8 {
+        self.frames_per_second
+    }
+
+    /// The first and last frame of the simulation
+    pub fn frame_range_inclusive(&self) -> (u16, u16) {
+        self.frame_range_inclusive
+    }
+
+    /// The positions on every frame, in order, as [x, y, z, x, y, z, ...]
+    pub fn frames(&self) -> &Vec<Vec<f32>> {
+        &self.frames
+    }
+
+    /// The number of positions on each frame
+    pub fn position_count(&self) -> usize {
+        self.frames
+            .first()
+            .map(|frame| frame.len() / 3)
+            .unwrap_or(0)
+    }
+
+    /// How long the simulation takes to play
+    pub fn duration(&self) -> Duration {
+        let frame_count = self.frames.len().saturating_sub(1);
+
+        Duration::from_secs_f32(frame_count as f32 / self.frames_per_second as f32)
+    }
+
+    /// Sample the positions some amount of time after the first frame, interpolating linearly in
+    /// between frames.
+    ///
+    /// Looping simulations wrap around to the first frame, otherwise times past the end of the
+    /// simulation sample the last frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the point cache doesn't have any frames.
+    pub fn sample_positions(&self, elapsed: Duration, should_loop: bool) -> Vec<f32> {
+        let last_idx = self.frames.len() - 1;
+
+        let mut frames_elapsed = elapsed.as_secs_f32() * self.frames_per_second as f32;
+        if should_loop && last_idx > 0 {
+            frames_elapsed %= last_idx as f32;
+        }
+
+        let lower_idx = (frames_elapsed.floor() as usize).min(last_idx);
+        let upper_idx = (lower_idx + 1).min(last_idx);
+
+        let amount = match lower_idx == upper_idx {
+            true => 0.,
+            false => frames_elapsed - lower_idx as f32,
+        };
+
+        self.frames[lower_idx]
+            .iter()
+            .zip(self.frames[upper_idx].iter())
+            .map(|(lower, upper)| lower + (upper - lower) * amount)
+            .collect()
+    }
+
+    /// Lay the offset of every position from its rest position out as a
+    /// [`VertexAnimationTexture`].
+    ///
+    /// `rest_positions` are usually the mesh's positions, as [x, y, z, x, y, z, ...].
+    ///
+    /// [`VertexAnimationTexture`]: struct.VertexAnimationTexture.html
+    pub fn vertex_animation_texture(
+        &self,
+        rest_positions: &[f32],
+    ) -> Result<VertexAnimationTexture, PointCacheError> {
+        let position_count = self.position_count();
+        if rest_positions.len() / 3 != position_count
+            || self
+                .frames
+                .iter()
+                .any(|frame| frame.len() != rest_positions.len())
+        {
+            return Err(PointCacheError::PositionCount {
+                cache: position_count,
+                rest: rest_positions.len() / 3,
+            });
+        }
+
+        let mut offsets = Vec::with_capacity(position_count * self.frames.len());
+        let mut min_offset = [f32::INFINITY; 3];
+        let mut max_offset = [f32::NEG_INFINITY; 3];
+
+        for frame in self.frames.iter() {
+            for (position, rest) in frame.chunks(3).zip(rest_positions.chunks(3)) {
+                let offset = [
+                    position[0] - rest[0],
+                    position[1] - rest[1],
+                    position[2] - rest[2],
+                ];
+
+                for axis in 0..3 {
+                    min_offset[axis] = min_offset[axis].min(offset[axis]);
+                    max_offset[axis] = max_offset[axis].max(offset[axis]);
+                }
+
+                offsets.push(offset);
+            }
+        }
+
+        if offsets.is_empty() {
+            min_offset = [0.; 3];
+            max_offset = [0.; 3];
+        }
+
+        Ok(VertexAnimationTexture {
+            width: position_count as u32,
+            height: self.frames.len() as u32,
+            offsets,
+            min_offset,
+            max_offset,
+        })
+    }
+}
+
+impl VertexAnimationTexture {
+    /// The number of columns, one for every position
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The number of rows, one for every frame
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The offset of every position from its rest position, row by row, for floating point
+    /// textures.
+    pub fn offsets(&self) -> &Vec<[f32; 3]> {
+        &self.offsets
+    }
+
+    /// The smallest offset along each axis
+    pub fn min_offset(&self) -> [f32; 3] {
+        self.min_offset
+    }
+
+    /// The largest offset along each axis
+    pub fn max_offset(&self) -> [f32; 3] {
+        self.max_offset
+    }
+
+    /// The offsets as RGBA texels for 16 bit normalized textures, with every axis remapped from
+    /// between [`VertexAnimationTexture.method#min_offset`] and
+    /// [`VertexAnimationTexture.method#max_offset`] to between 0 and `u16::MAX`.
+    ///
+    /// Alpha is always `u16::MAX`.
+    pub fn to_rgba16(&self) -> Vec<u16> {
+        let mut texels = Vec::with_capacity(self.offsets.len() * 4);
+
+        for offset in self.offsets.iter() {
+            let bounds = self.min_offset.iter().zip(self.max_offset.iter());
+
+            for (axis, (min, max)) in offset.iter().zip(bounds) {
+                let range = max - min;
+                let normalized = match range > 0. {
+                    true => (axis - min) / range,
+                    false => 0.,
+                };
+
+                texels.push((normalized * u16::MAX as f32).round() as u16);
+            }
+            texels.push(u16::MAX);
+        }
+
+        texels
+    }
+}
+
+/// Given a buffer of standard output from Blender we parse all of the point cache JSON that was
+/// written to stdout by `blender-mesh-to-json.py`.
+///
+/// Point cache data in stdout will look like:
+///
+/// START_POINT_CACHE_JSON /path/to/file.blend my_mesh_name
+/// {...}
+/// END_POINT_CACHE_JSON /path/to/file.blend my_mesh_name
+///
+/// @see blender-mesh-to-json.py - This is where we write to stdout
+pub fn parse_point_caches_from_blender_stdout(blender_stdout: &str) -> PointCachesByFilename {
+    let start_marker = "START_POINT_CACHE_JSON";
+    let end_marker = "END_POINT_CACHE_JSON";
+
+    let mut filenames_to_point_caches = PointCachesByFilename::new();
+
+    let mut remaining = blender_stdout;
+
+    while let Some(start_idx) = remaining.find(start_marker) {
+        let end_idx = remaining.find(end_marker).unwrap();
+
+        let mut lines = remaining[start_idx..end_idx].lines();
+
+        let first_line = lines.next().unwrap();
+        let filename = first_line.split(' ').nth(1).unwrap().to_string();
+        let mesh_name = first_line.split(' ').next_back().unwrap().to_string();
+
+        let point_cache: String = lines.collect();
+        let point_cache: PointCache = serde_json::from_str(&point_cache).unwrap();
+
+        filenames_to_point_caches
+            .entry(filename)
+            .or_default()
+            .insert(mesh_name, point_cache);
+
+        remaining = &remaining[end_idx + end_marker.len()..];
+    }
+
+    filenames_to_point_caches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that we interpolate in between frames, loop, and lay the offsets out as a vertex
+    /// animation texture.
+    #[test]
+    fn play_back_point_cache() {
+        let stdout = r#"
+START_POINT_CACHE_JSON /castle.blend Flag
+{
+  "name": "Flag", "frames_per_second": 2, "frame_range_inclusive": [1, 3],
+  "frames": [[0, 0, 0, 1, 0, 0], [0, 0, 2, 1, 0, 2], [0, 0, 0, 1, 0, 4]]
+}
+END_POINT_CACHE_JSON /castle.blend Flag
+"#;
+        let point_caches = parse_point_caches_from_blender_stdout(stdout);
+        let flag = &point_caches["/castle.blend"]["Flag"];
+
+        assert_eq!(flag.position_count(), 2);
+        assert_eq!(flag.duration(), Duration::from_secs(1));
+
+        let positions = flag.sample_positions(Duration::from_millis(750), false);
+        assert_eq!(positions, vec![0., 0., 1., 1., 0., 3.]);
+
+        // Past the end of the simulation
+        let positions = flag.sample_positions(Duration::from_millis(1250), false);
+        assert_eq!(positions, vec![0., 0., 0., 1., 0., 4.]);
+        let positions = flag.sample_positions(Duration::from_millis(1250), true);
+        assert_eq!(positions, vec![0., 0., 1., 1., 0., 1.]);
+
+        let texture = flag
+            .vertex_animation_texture(&[0., 0., 0., 1., 0., 0.])
+            .unwrap();
+        assert_eq!((texture.width(), texture.height()), (2, 3));
+        assert_eq!(texture.offsets()[5], [0., 0., 4.]);
+        assert_eq!(texture.max_offset(), [0., 0., 4.]);
+        assert_eq!(&texture.to_rgba16()[12..16], &[0, 0, 32768, u16::MAX]);
+
+        assert_eq!(
+            flag.vertex_animation_texture(&[0., 0., 0.]),
+            Err(PointCacheError::PositionCount { cache: 2, rest: 1 })
+        );
+    }
+}
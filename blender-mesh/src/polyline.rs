@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+pub type PolylinesByFilename = HashMap<String, PolylinesByObjectName>;
+pub type PolylinesByObjectName = HashMap<String, Vec<Polyline>>;
+
+/// A grease pencil stroke, such as a gameplay volume or marker that was blocked out by hand.
+///
+/// The strokes on a grease pencil object are exported under the object's name and the scene's
+/// annotations are exported under the name `Annotations`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Polyline {
+    pub(crate) layer: String,
+    pub(crate) points: Vec<[f32; 3]>,
+    pub(crate) thickness: Vec<f32>,
+    pub(crate) cyclic: bool,
+}
+
+impl Polyline {
+    #[allow(missing_docs)]
+    pub fn new(layer: String, points: Vec<[f32; 3]>, thickness: Vec<f32>, cyclic: bool) -> Self {
+        Polyline {
+            layer,
+            points,
+            thickness,
+            cyclic,
+        }
+    }
+
+    /// The name of the grease pencil layer that the stroke was drawn on.
+    pub fn layer(&self) -> &String {
+        &self.layer
+    }
+
+    /// The world space position of every point along the stroke.
+    pub fn points(&self) -> &Vec<[f32; 3]> {
+        &self.points
+    }
+
+    /// The thickness of the stroke at every point.
+    ///
+    /// # Blender
+    ///
+    /// Since Blender 4.3 this is the stroke's diameter in Blender units. Before that it is the
+    /// stroke's line width in pixels multiplied by the pen pressure of the point.
+    pub fn thickness(&self) -> &Vec<f32> {
+        &self.thickness
+    }
+
+    /// Whether the last point connects back to the first point, such as the outline of a
+    /// volume.
+    pub fn cyclic(&self) -> bool {
+        self.cyclic
+    }
+
+    /// The length of the stroke, including the segment that closes it if it's cyclic.
+    pub fn length(&self) -> f32 {
+        let closing = match self.cyclic && self.points.len() > 2 {
+            true => Some((self.points[self.points.len() - 1], self.points[0])),
+            false => None,
+        };
+
+        self.points
+            .windows(2)
+            .map(|segment| (segment[0], segment[1]))
+            .chain(closing)
+            .map(|(a, b)| {
+                let [x, y, z] = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+                (x * x + y * y + z * z).sqrt()
+            })
+            .sum()
+    }
+}
+
+/// Given a buffer of standard output from Blender we parse all of the grease pencil polylines
+/// that were written to stdout by `blender-mesh-to-json.py`.
+///
+/// Polylines in stdout will look like:
+///
+/// START_POLYLINES_JSON /path/to/file.blend my_object_name
+/// [...]
+/// END_POLYLINES_JSON /path/to/file.blend my_object_name
+///
+/// @see blender-mesh-to-json.py - This is where we write to stdout
+pub fn parse_polylines_from_blender_stdout(blender_stdout: &str) -> PolylinesByFilename {
+    let start_marker = "START_POLYLINES_JSON";
+    let end_marker = "END_POLYLINES_JSON";
+
+    let mut filenames_to_polylines = PolylinesByFilename::new();
+
+    let mut remaining = blender_stdout;
+
+    while let Some(start_idx) = remaining.find(start_marker) {
+        let end_idx = remaining.find(end_marker).unwrap();
+
+        let mut lines = remaining[start_idx..end_idx].lines();
+
+        let first_line = lines.next().unwrap();
+        let filename = first_line.split(' ').nth(1).unwrap().to_string();
+        let object_name = first_line.split(' ').next_back().unwrap().to_string();
+
+        let polylines: String = lines.collect();
+        let polylines: Vec<Polyline> = serde_json::from_str(&polylines).unwrap();
+
+        filenames_to_polylines
+            .entry(filename)
+            .or_default()
+            .insert(object_name, polylines);
+
+        remaining = &remaining[end_idx + end_marker.len()..];
+    }
+
+    filenames_to_polylines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that we parse the strokes of every grease pencil object and the annotations.
+    #[test]
+    fn parse_polylines() {
+        let stdout = r#"Blender 2.93
+START_POLYLINES_JSON /level.blend Volumes
+[{"layer": "Triggers", "points": [[0, 0, 0], [3, 0, 0], [3, 4, 0]], "thickness": [3, 3, 3], "cyclic": true}]
+END_POLYLINES_JSON /level.blend Volumes
+START_POLYLINES_JSON /level.blend Annotations
+[]
+END_POLYLINES_JSON /level.blend Annotations
+"#;
+
+        let parsed = parse_polylines_from_blender_stdout(stdout);
+        let polylines = &parsed["/level.blend"];
+
+        assert_eq!(polylines["Annotations"], vec![]);
+
+        let trigger = &polylines["Volumes"][0];
+        assert_eq!(trigger.layer(), "Triggers");
+        assert_eq!(trigger.points().len(), 3);
+        assert_eq!(trigger.length(), 12.);
+    }
+}
@@ -0,0 +1,205 @@
+//! Create [rapier] colliders from meshes.
+//!
+//! Enabled by the `rapier3d` feature.
+//!
+//! ```
+//! # use blender_mesh::BlenderMesh;
+//! use blender_mesh::rapier::{collider_builder, ColliderConfig, ColliderShape};
+//!
+//! let level = BlenderMesh::cube(10.);
+//!
+//! let collider = collider_builder(
+//!     &level,
+//!     &ColliderConfig {
+//!         shape: ColliderShape::TriMesh,
+//!         ..ColliderConfig::default()
+//!     },
+//! )
+//! .unwrap()
+//! .friction(0.7)
+//! .build();
+//! ```
+//!
+//! [rapier]: https://rapier.rs
+
+use crate::triangulate::triangulate_indices;
+use crate::BlenderMesh;
+use ::rapier3d::geometry::ColliderBuilder;
+use ::rapier3d::math::{Point, Vector};
+
+/// How to build a collider from a mesh.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColliderShape {
+    /// Collide against every triangle in the mesh.
+    ///
+    /// The most accurate shape, typically used for static level geometry.
+    TriMesh,
+    /// The smallest convex shape that contains every vertex.
+    ConvexHull,
+    /// Split the mesh into convex parts. Slower to build than a convex hull, but keeps
+    /// concave shapes such as doorways.
+    ConvexDecomposition,
+    /// A box that fits the mesh's bounding box.
+    Cuboid,
+    /// A ball at the center of the mesh's bounding box that contains every vertex.
+    Ball,
+}
+
+/// How to turn a mesh into a collider.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColliderConfig {
+    /// The shape of the collider.
+    pub shape: ColliderShape,
+    /// Every position is multiplied by this, such as when your game's units aren't meters.
+    pub scale: f32,
+    /// Convert the positions from Blender's Z up coordinate system to Y up.
+    ///
+    /// See [`BlenderMesh.method#y_up`]
+    pub y_up: bool,
+}
+
+impl Default for ColliderConfig {
+    fn default() -> Self {
+        ColliderConfig {
+            shape: ColliderShape::TriMesh,
+            scale: 1.,
+            y_up: false,
+        }
+    }
+}
+
+/// An error while creating a collider.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ColliderError {
+    /// Colliders need at least one triangle.
+    #[error("Cannot create a collider for a mesh without any faces")]
+    NoFaces,
+    /// The vertices don't enclose a volume, such as when they all lie on a plane.
+    #[error("Could not compute the convex hull of the mesh's vertices")]
+    ConvexHull,
+}
+
+/// Create a collider builder for the mesh, which can be further configured (friction,
+/// sensors, ...) before being built.
+///
+/// The collider is centered wherever the mesh's origin was, except for cuboids and balls which
+/// are translated to the center of the mesh's bounding box.
+pub fn collider_builder(
+    mesh: &BlenderMesh,
+    config: &ColliderConfig,
+) -> Result<ColliderBuilder, ColliderError> {
+    let multi = mesh.multi_indexed_vertex_attributes();
+    let positions = multi.positions();
+
+    let triangles: Vec<[u32; 3]> =
+        triangulate_indices(multi.vertices_in_each_face(), positions.indices())
+            .chunks_exact(3)
+            .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+            .collect();
+    if triangles.is_empty() {
+        return Err(ColliderError::NoFaces);
+    }
+
+    let points: Vec<Point<f32>> = positions
+        .attribute()
+        .iter_vertices()
+        .map(|position| {
+            let [x, y, z] = match config.y_up {
+                true => [position[0], position[2], -position[1]],
+                false => [position[0], position[1], position[2]],
+            };
+            Point::new(x, y, z) * config.scale
+        })
+        .collect();
+
+    let builder = match config.shape {
+        ColliderShape::TriMesh => ColliderBuilder::trimesh(points, triangles),
+        ColliderShape::ConvexHull => {
+            ColliderBuilder::convex_hull(&points).ok_or(ColliderError::ConvexHull)?
+        }
+        ColliderShape::ConvexDecomposition => {
+            ColliderBuilder::convex_decomposition(&points, &triangles)
+        }
+        ColliderShape::Cuboid => {
+            let (min, max) = bounds(&points);
+            let half_extents = (max - min) / 2.;
+
+            ColliderBuilder::cuboid(half_extents.x, half_extents.y, half_extents.z)
+                .translation(center(min, max))
+        }
+        ColliderShape::Ball => {
+            let (min, max) = bounds(&points);
+            let center = Point::from(center(min, max));
+            let radius = points
+                .iter()
+                .map(|point| (point - center).norm())
+                .fold(0., f32::max);
+
+            ColliderBuilder::ball(radius).translation(center.coords)
+        }
+    };
+
+    Ok(builder)
+}
+
+fn bounds(points: &[Point<f32>]) -> (Point<f32>, Point<f32>) {
+    points
+        .iter()
+        .skip(1)
+        .fold((points[0], points[0]), |(min, max), point| {
+            (min.inf(point), max.sup(point))
+        })
+}
+
+fn center(min: Point<f32>, max: Point<f32>) -> Vector<f32> {
+    (min.coords + max.coords) / 2.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that primitives fit the mesh after it has been scaled and converted to y up.
+    #[test]
+    fn fitted_primitives() {
+        let mut mesh = BlenderMesh::cube(2.);
+        for position in mesh
+            .multi_indexed_vertex_attributes
+            .positions
+            .attribute
+            .iter_vertices_mut()
+        {
+            // Move the cube up 3 units in Blender
+            position[2] += 3.;
+        }
+
+        let config = ColliderConfig {
+            shape: ColliderShape::Cuboid,
+            scale: 2.,
+            y_up: true,
+        };
+        let cuboid = collider_builder(&mesh, &config).unwrap().build();
+        let half_extents = cuboid.shape().as_cuboid().unwrap().half_extents;
+
+        assert_eq!(half_extents, Vector::new(2., 2., 2.));
+        assert_eq!(*cuboid.translation(), Vector::new(0., 6., 0.));
+
+        let config = ColliderConfig {
+            shape: ColliderShape::Ball,
+            ..config
+        };
+        let ball = collider_builder(&mesh, &config).unwrap().build();
+
+        assert!((ball.shape().as_ball().unwrap().radius - 12f32.sqrt()).abs() < 1e-5);
+    }
+
+    /// Verify that we build a triangle mesh with every triangle in the mesh.
+    #[test]
+    fn trimesh_collider() {
+        let collider = collider_builder(&BlenderMesh::cube(1.), &ColliderConfig::default())
+            .unwrap()
+            .build();
+
+        assert_eq!(collider.shape().as_trimesh().unwrap().indices().len(), 12);
+    }
+}
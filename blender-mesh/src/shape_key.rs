@@ -0,0 +1,220 @@
+use crate::simd;
+use crate::vertex_attributes::VertexAttribute;
+use crate::{BlenderMesh, ShapeKeyDriver};
+use nalgebra::Matrix4;
+
+/// A [shape key][shape-keys] that morphs a mesh's positions, such as a facial expression.
+///
+/// [shape-keys]: https://docs.blender.org/manual/en/latest/animation/shape_keys/introduction.html
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ShapeKey {
+    pub(crate) name: String,
+    pub(crate) position_deltas: VertexAttribute<f32>,
+    pub(crate) weight: f32,
+    #[serde(default)]
+    pub(crate) driver: Option<ShapeKeyDriver>,
+}
+
+/// An error while evaluating a mesh's vertex positions.
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum EvaluatePositionsError {
+    /// Every shape key needs a weight, even if it is zero.
+    #[error("The mesh has {shape_keys} shape keys but {weights} morph weights were provided")]
+    MorphWeightCount { shape_keys: usize, weights: usize },
+    /// A vertex is influenced by a joint that is missing from the pose.
+    #[error("A vertex is influenced by joint {joint} but the pose only has {joints} joints")]
+    MissingJoint { joint: u8, joints: usize },
+    /// Every armature that deforms the mesh needs a palette.
+    #[error("The mesh is deformed by {armatures} armatures but {palettes} palettes were provided")]
+    PaletteCount { armatures: usize, palettes: usize },
+}
+
+impl ShapeKey {
+    #[allow(missing_docs)]
+    pub fn new(name: String, position_deltas: VertexAttribute<f32>, weight: f32) -> Self {
+        ShapeKey {
+            name,
+            position_deltas,
+            weight,
+            driver: None,
+        }
+    }
+
+    /// The name of the shape key.
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// How far the shape key moves every position when its weight is `1.0`, with one delta per
+    /// position.
+    pub fn position_deltas(&self) -> &VertexAttribute<f32> {
+        &self.position_deltas
+    }
+
+    /// The shape key's weight when it was exported.
+    ///
+    /// Driven shape keys can be weighted by their [`ShapeKey.method#driver`] instead.
+    ///
+    /// # Blender
+    ///
+    /// This is the shape key's value.
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+}
+
+impl BlenderMesh {
+    /// Evaluate where every position ends up after applying the shape keys and then skinning
+    /// the mesh to a pose, with one position per position index.
+    ///
+    /// `morph_weights` has a weight for every shape key in [`BlenderMesh.method#shape_keys`], and
+    /// `pose` has a matrix for every joint that transforms the mesh from its bind pose into the
+    /// pose, such as a [`BlenderArmature`] keyframe after its inverse bind poses were applied and
+    /// its bones were converted to matrices.
+    ///
+    /// Positions are skinned with linear blend skinning. The weights of every vertex are
+    /// normalized and vertices without any weight stay where the shape keys left them. The pose
+    /// is ignored if the mesh doesn't have any bone influences.
+    ///
+    /// This runs on the CPU, so it's meant as a reference to validate shaders against rather
+    /// than for rendering every frame.
+    ///
+    /// [`BlenderArmature`]: https://docs.rs/blender-armature
+    pub fn evaluate_vertex_positions(
+        &self,
+        pose: &[Matrix4<f32>],
+        morph_weights: &[f32],
+    ) -> Result<VertexAttribute<f32>, EvaluatePositionsError> {
+        if morph_weights.len() != self.shape_keys.len() {
+            return Err(EvaluatePositionsError::MorphWeightCount {
+                shape_keys: self.shape_keys.len(),
+                weights: morph_weights.len(),
+            });
+        }
+
+        let multi = &self.multi_indexed_vertex_attributes;
+        let mut positions = multi.positions.attribute.clone();
+
+        for (shape_key, weight) in self.shape_keys.iter().zip(morph_weights.iter()) {
+            if *weight == 0. {
+                continue;
+            }
+
+            simd::add_scaled(
+                &mut positions.data,
+                &shape_key.position_deltas.data,
+                *weight,
+            );
+        }
+
+        let bone_influences = match multi.bone_influences.as_ref() {
+            Some(bone_influences) => bone_influences,
+            None => return Ok(positions),
+        };
+
+        for (position, (joints, weights)) in positions
+            .iter_vertices_mut()
+            .zip(bone_influences.influences_per_vertex())
+        {
+            let total_weight: f32 = weights.iter().sum();
+            if total_weight <= 0. {
+                continue;
+            }
+
+            let morphed = [position[0], position[1], position[2]];
+            let mut skinned = [0.; 4];
+
+            for (joint, weight) in joints.iter().zip(weights.iter()) {
+                let matrix =
+                    pose.get(*joint as usize)
+                        .ok_or(EvaluatePositionsError::MissingJoint {
+                            joint: *joint,
+                            joints: pose.len(),
+                        })?;
+
+                simd::blend_transformed_point(&mut skinned, matrix, morphed, weight / total_weight);
+            }
+
+            position.copy_from_slice(&skinned[..3]);
+        }
+
+        Ok(positions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bone::BoneInfluencesPerVertex;
+    use crate::vertex_attributes::VertexBoneInfluences;
+    use nalgebra::Vector3;
+
+    fn vertices(positions: &VertexAttribute<f32>) -> Vec<&[f32]> {
+        positions.iter_vertices().collect()
+    }
+
+    /// Verify that shape keys are applied before the vertices are skinned, so that the joints
+    /// move the morphed positions.
+    #[test]
+    fn morph_then_skin() {
+        // Positions (-1, -1, 0), (1, -1, 0), (-1, 1, 0) and (1, 1, 0)
+        let mut plane = BlenderMesh::plane(2., 2., 0);
+        plane.shape_keys.push(ShapeKey::new(
+            "Raise".to_string(),
+            VertexAttribute::new(vec![0., 0., 1., 0., 0., 1., 0., 0., 0., 0., 0., 0.], 3).unwrap(),
+            0.,
+        ));
+        plane.multi_indexed_vertex_attributes.bone_influences = Some(VertexBoneInfluences {
+            bones_per_vertex: BoneInfluencesPerVertex::NonUniform(vec![1, 2, 1, 0]),
+            bone_indices: vec![0, 0, 1, 1],
+            bone_weights: vec![1., 1., 3., 2.],
+        });
+
+        let pose = [
+            Matrix4::new_translation(&Vector3::new(10., 0., 0.)),
+            Matrix4::new_scaling(2.),
+        ];
+
+        let positions = plane.evaluate_vertex_positions(&pose, &[0.5]).unwrap();
+
+        assert_eq!(
+            vertices(&positions),
+            vec![
+                &[9., -1., 0.5][..],
+                // A quarter translated and three quarters scaled
+                &[4.25, -1.75, 0.875][..],
+                &[-2., 2., 0.][..],
+                // No joints
+                &[1., 1., 0.][..],
+            ]
+        );
+    }
+
+    /// Verify that every shape key needs a weight and that every joint needs to be posed.
+    #[test]
+    fn evaluate_errors() {
+        let mut cube = BlenderMesh::cube(2.);
+
+        assert_eq!(
+            cube.evaluate_vertex_positions(&[], &[1.]),
+            Err(EvaluatePositionsError::MorphWeightCount {
+                shape_keys: 0,
+                weights: 1
+            })
+        );
+
+        cube.multi_indexed_vertex_attributes.bone_influences = Some(VertexBoneInfluences {
+            bones_per_vertex: BoneInfluencesPerVertex::Uniform(1),
+            bone_indices: vec![2; 8],
+            bone_weights: vec![1.; 8],
+        });
+
+        assert_eq!(
+            cube.evaluate_vertex_positions(&[Matrix4::identity()], &[]),
+            Err(EvaluatePositionsError::MissingJoint {
+                joint: 2,
+                joints: 1
+            })
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{BlenderMesh, ShapeKey};
+use nalgebra::{Matrix3, Matrix4, Rotation3, Vector3};
+
+/// Sets a [`ShapeKey`]'s weight from the transforms of an armature's bones, such as a jaw bone
+/// that opens a mouth shape, so that facial rigs can be driven at runtime instead of baking
+/// every facial pose into the armature's actions.
+///
+/// The inputs are scaled by their coefficients and combined, the offset is added and then the
+/// result is mapped through the curve.
+///
+/// # Blender
+///
+/// Exported from the driver on the shape key's value. Averaged, summed, minimum and maximum
+/// drivers are exported as they are, while scripted drivers are only exported if their
+/// expression is linear, such as `0.5 * jaw - 0.1`. Drivers that can't be exported are reported
+/// as warnings.
+///
+/// [`ShapeKey`]: struct.ShapeKey.html
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ShapeKeyDriver {
+    pub(crate) inputs: Vec<DriverInput>,
+    pub(crate) combine: DriverCombine,
+    #[serde(default)]
+    pub(crate) offset: f32,
+    #[serde(default)]
+    pub(crate) curve: Vec<[f32; 2]>,
+}
+
+/// One of the bone transform channels that a [`ShapeKeyDriver`] reads.
+///
+/// [`ShapeKeyDriver`]: struct.ShapeKeyDriver.html
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DriverInput {
+    /// The name of the armature object that the bone belongs to.
+    pub armature: String,
+    /// The name of the bone.
+    pub bone: String,
+    /// Which part of the bone's transform is read.
+    pub channel: TransformChannel,
+    /// How much the channel's value is scaled by before it is combined with the other inputs.
+    pub coefficient: f32,
+}
+
+/// A part of a bone's transform relative to its rest pose, such as the bone-space keyframes of
+/// an armature's actions.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum TransformChannel {
+    LocationX,
+    LocationY,
+    LocationZ,
+    /// Rotations are XYZ euler angles in radians.
+    RotationX,
+    RotationY,
+    RotationZ,
+    ScaleX,
+    ScaleY,
+    ScaleZ,
+}
+
+/// How a [`ShapeKeyDriver`] combines its scaled inputs.
+///
+/// [`ShapeKeyDriver`]: struct.ShapeKeyDriver.html
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DriverCombine {
+    /// Add up the inputs. Averages and linear expressions are exported as sums.
+    Sum,
+    /// Use the smallest input.
+    Min,
+    /// Use the largest input.
+    Max,
+}
+
+impl ShapeKeyDriver {
+    #[allow(missing_docs)]
+    pub fn new(
+        inputs: Vec<DriverInput>,
+        combine: DriverCombine,
+        offset: f32,
+        curve: Vec<[f32; 2]>,
+    ) -> Self {
+        ShapeKeyDriver {
+            inputs,
+            combine,
+            offset,
+            curve,
+        }
+    }
+
+    /// The bone transform channels that the driver reads.
+    pub fn inputs(&self) -> &Vec<DriverInput> {
+        &self.inputs
+    }
+
+    /// How the scaled inputs are combined.
+    pub fn combine(&self) -> DriverCombine {
+        self.combine
+    }
+
+    /// Added to the combined inputs.
+    pub fn offset(&self) -> f32 {
+        self.offset
+    }
+
+    /// `[input, weight]` points, sorted by input, that the combined inputs are mapped through
+    /// with linear interpolation. Inputs outside of the curve use the weight of the nearest
+    /// point. Empty if the combined inputs are the weight.
+    ///
+    /// # Blender
+    ///
+    /// Sampled from the driver's F-Curve when it has keyframes, such as for driven keys.
+    pub fn curve(&self) -> &Vec<[f32; 2]> {
+        &self.curve
+    }
+
+    /// Calculate the shape key's weight, given a function that returns the value of one of the
+    /// driver's inputs in the current pose before it is scaled by its coefficient.
+    ///
+    /// See [`TransformChannel.method#value`] for reading channels out of a bone's transform.
+    pub fn weight(&self, mut channel_value: impl FnMut(&DriverInput) -> f32) -> f32 {
+        let values = self
+            .inputs
+            .iter()
+            .map(|input| input.coefficient * channel_value(input));
+
+        let combined = match self.combine {
+            DriverCombine::Sum => values.sum(),
+            DriverCombine::Min => values.fold(f32::INFINITY, f32::min),
+            DriverCombine::Max => values.fold(f32::NEG_INFINITY, f32::max),
+        };
+        // Blender drivers without any valid inputs evaluate to zero
+        let combined = match combined.is_finite() {
+            true => combined,
+            false => 0.,
+        };
+
+        map_through_curve(&self.curve, combined + self.offset)
+    }
+}
+
+impl TransformChannel {
+    /// Read the channel out of a bone's transform relative to its rest pose.
+    pub fn value(&self, transform: &Matrix4<f32>) -> f32 {
+        let scale = |axis: usize| {
+            Vector3::new(
+                transform[(0, axis)],
+                transform[(1, axis)],
+                transform[(2, axis)],
+            )
+            .norm()
+        };
+        let rotation = || {
+            let rotation = Matrix3::from_fn(|row, column| transform[(row, column)] / scale(column));
+            Rotation3::from_matrix_unchecked(rotation).euler_angles()
+        };
+
+        match self {
+            TransformChannel::LocationX => transform.m14,
+            TransformChannel::LocationY => transform.m24,
+            TransformChannel::LocationZ => transform.m34,
+            TransformChannel::RotationX => rotation().0,
+            TransformChannel::RotationY => rotation().1,
+            TransformChannel::RotationZ => rotation().2,
+            TransformChannel::ScaleX => scale(0),
+            TransformChannel::ScaleY => scale(1),
+            TransformChannel::ScaleZ => scale(2),
+        }
+    }
+}
+
+impl ShapeKey {
+    /// The driver that sets the shape key's weight from bone transforms, if it has one.
+    pub fn driver(&self) -> Option<&ShapeKeyDriver> {
+        self.driver.as_ref()
+    }
+
+    /// Set the shape key's driver.
+    pub fn set_driver(&mut self, driver: Option<ShapeKeyDriver>) {
+        self.driver = driver;
+    }
+}
+
+impl BlenderMesh {
+    /// The weight of every shape key in [`BlenderMesh.method#shape_keys`], ready to be passed to
+    /// [`BlenderMesh.method#evaluate_vertex_positions`] or uploaded as morph target weights.
+    ///
+    /// Driven shape keys are weighted by their [`ShapeKeyDriver`] and the rest keep the weight
+    /// that they were exported with.
+    ///
+    /// [`ShapeKeyDriver`]: struct.ShapeKeyDriver.html
+    pub fn driven_morph_weights(
+        &self,
+        mut channel_value: impl FnMut(&DriverInput) -> f32,
+    ) -> Vec<f32> {
+        self.shape_keys
+            .iter()
+            .map(|shape_key| match shape_key.driver.as_ref() {
+                Some(driver) => driver.weight(&mut channel_value),
+                None => shape_key.weight,
+            })
+            .collect()
+    }
+}
+
+fn map_through_curve(curve: &[[f32; 2]], input: f32) -> f32 {
+    let (first, last) = match (curve.first(), curve.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return input,
+    };
+
+    if input <= first[0] {
+        return first[1];
+    }
+    if input >= last[0] {
+        return last[1];
+    }
+
+    for points in curve.windows(2) {
+        let ([x0, y0], [x1, y1]) = (points[0], points[1]);
+        if input <= x1 {
+            let progress = match x1 > x0 {
+                true => (input - x0) / (x1 - x0),
+                false => 1.,
+            };
+            return y0 + (y1 - y0) * progress;
+        }
+    }
+
+    last[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(bone: &str, channel: TransformChannel, coefficient: f32) -> DriverInput {
+        DriverInput {
+            armature: "FaceRig".to_string(),
+            bone: bone.to_string(),
+            channel,
+            coefficient,
+        }
+    }
+
+    /// Verify that a driver scales and combines its inputs before mapping them through its
+    /// curve, and that undriven shape keys keep their weights.
+    #[test]
+    fn driven_weights() {
+        let jaw = Matrix4::new_translation(&Vector3::new(0., -0.2, 0.))
+            * Rotation3::from_euler_angles(0.5, 0., 0.).to_homogeneous();
+        let lip = Matrix4::new_nonuniform_scaling(&Vector3::new(1., 1., 1.5));
+
+        let mut smile = ShapeKey::new(
+            "Smile".to_string(),
+            crate::VertexAttribute::new(vec![], 3).unwrap(),
+            0.3,
+        );
+        let mut open = smile.clone();
+        open.name = "JawOpen".to_string();
+        open.set_driver(Some(ShapeKeyDriver::new(
+            vec![
+                input("Jaw", TransformChannel::RotationX, 2.),
+                input("Jaw", TransformChannel::LocationY, -1.),
+            ],
+            DriverCombine::Sum,
+            -0.2,
+            vec![],
+        )));
+        let mut pucker = smile.clone();
+        pucker.set_driver(Some(ShapeKeyDriver::new(
+            vec![input("Lip", TransformChannel::ScaleZ, 1.)],
+            DriverCombine::Max,
+            0.,
+            vec![[1., 0.], [2., 1.]],
+        )));
+        smile.set_driver(None);
+
+        let mut mesh = BlenderMesh::default();
+        mesh.push_shape_key(smile);
+        mesh.push_shape_key(open);
+        mesh.push_shape_key(pucker);
+
+        let weights = mesh.driven_morph_weights(|input| match input.bone.as_str() {
+            "Jaw" => input.channel.value(&jaw),
+            _ => input.channel.value(&lip),
+        });
+
+        // 2 * 0.5 - 1 * -0.2 - 0.2
+        assert!((weights[1] - 1.).abs() < 1e-5);
+        assert_eq!(weights[0], 0.3);
+        assert_eq!(weights[2], 0.5);
+    }
+}
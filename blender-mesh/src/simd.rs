@@ -0,0 +1,216 @@
+//! Hot loops over large arrays of vertex data.
+//!
+//! With the `simd` feature these run four lanes at a time using SSE2 on x86_64, which every
+//! x86_64 CPU supports. Other targets, and builds without the feature, use the scalar loops,
+//! which the SIMD loops are tested against.
+
+use nalgebra::Matrix4;
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+pub(crate) use self::sse::{add_scaled, blend_transformed_point, transform_vec3s, y_up};
+
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+pub(crate) use self::scalar::{add_scaled, blend_transformed_point, transform_vec3s, y_up};
+
+// The SIMD loops only use some of these for their leftovers, but every loop is kept as the
+// reference that they're tested against.
+#[cfg_attr(all(feature = "simd", target_arch = "x86_64"), allow(dead_code))]
+mod scalar {
+    use super::*;
+
+    /// Convert tightly packed xyz data from Z up to Y up, turning `(x, y, z)` into `(x, z, -y)`.
+    pub(crate) fn y_up(data: &mut [f32]) {
+        for vec3 in data.chunks_exact_mut(3) {
+            let new_z = -vec3[1];
+            vec3[1] = vec3[2];
+            vec3[2] = new_z;
+        }
+    }
+
+    /// `dst += src * scale`, such as for applying a shape key's deltas.
+    pub(crate) fn add_scaled(dst: &mut [f32], src: &[f32], scale: f32) {
+        for (dst, src) in dst.iter_mut().zip(src.iter()) {
+            *dst += *src * scale;
+        }
+    }
+
+    /// Transform tightly packed xyz data by a matrix, with a `w` of 1 for points and 0 for
+    /// directions.
+    pub(crate) fn transform_vec3s(matrix: &Matrix4<f32>, data: &mut [f32], w: f32) {
+        for vec3 in data.chunks_exact_mut(3) {
+            let transformed = transform(matrix, [vec3[0], vec3[1], vec3[2]], w);
+            vec3.copy_from_slice(&transformed[..3]);
+        }
+    }
+
+    /// `sum += matrix * point * weight`, such as for one of the joints that skin a vertex.
+    pub(crate) fn blend_transformed_point(
+        sum: &mut [f32; 4],
+        matrix: &Matrix4<f32>,
+        point: [f32; 3],
+        weight: f32,
+    ) {
+        let transformed = transform(matrix, point, 1.);
+        for (sum, transformed) in sum.iter_mut().zip(transformed.iter()) {
+            *sum += *transformed * weight;
+        }
+    }
+
+    // Summed in the same order as the SIMD version so that both give the same results.
+    fn transform(matrix: &Matrix4<f32>, [x, y, z]: [f32; 3], w: f32) -> [f32; 4] {
+        let mut transformed = [0.; 4];
+        for (row, transformed) in transformed.iter_mut().enumerate() {
+            *transformed = matrix[(row, 0)] * x
+                + matrix[(row, 1)] * y
+                + matrix[(row, 2)] * z
+                + matrix[(row, 3)] * w;
+        }
+
+        transformed
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod sse {
+    use super::*;
+    use std::arch::x86_64::*;
+
+    // SAFETY: Every function in here only uses SSE2, which is part of the x86_64 baseline, and
+    // only reads and writes within the bounds of the slices and arrays that it was given.
+
+    /// See [`scalar::y_up`]. Four vertices are converted at a time.
+    pub(crate) fn y_up(data: &mut [f32]) {
+        let mut blocks = data.chunks_exact_mut(12);
+
+        for block in &mut blocks {
+            unsafe {
+                let ptr = block.as_mut_ptr();
+
+                // [x0 y0 z0 x1] [y1 z1 x2 y2] [z2 x3 y3 z3] with every y negated
+                let a = _mm_xor_ps(_mm_loadu_ps(ptr), _mm_setr_ps(0., -0., 0., 0.));
+                let b = _mm_xor_ps(_mm_loadu_ps(ptr.add(4)), _mm_setr_ps(-0., 0., 0., -0.));
+                let c = _mm_xor_ps(_mm_loadu_ps(ptr.add(8)), _mm_setr_ps(0., 0., -0., 0.));
+
+                // [x0 z0 -y0 x1]
+                let first = _mm_shuffle_ps(a, a, 0b11_01_10_00);
+                // [b2 _ c0 _] then [z1 -y1 x2 z2]
+                let x2_z2 = _mm_shuffle_ps(b, c, 0b00_00_00_10);
+                let second = _mm_shuffle_ps(b, x2_z2, 0b10_00_00_01);
+                // [b3 _ c1 _] then [-y2 x3 z3 -y3]
+                let y2_x3 = _mm_shuffle_ps(b, c, 0b01_01_11_11);
+                let third = _mm_shuffle_ps(y2_x3, c, 0b10_11_10_00);
+
+                _mm_storeu_ps(ptr, first);
+                _mm_storeu_ps(ptr.add(4), second);
+                _mm_storeu_ps(ptr.add(8), third);
+            }
+        }
+
+        scalar::y_up(blocks.into_remainder());
+    }
+
+    /// See [`scalar::add_scaled`].
+    pub(crate) fn add_scaled(dst: &mut [f32], src: &[f32], scale: f32) {
+        let len = dst.len().min(src.len());
+        let mut dst_chunks = dst[..len].chunks_exact_mut(4);
+        let mut src_chunks = src[..len].chunks_exact(4);
+
+        for (dst, src) in (&mut dst_chunks).zip(&mut src_chunks) {
+            unsafe {
+                let scaled = _mm_mul_ps(_mm_loadu_ps(src.as_ptr()), _mm_set1_ps(scale));
+                let sum = _mm_add_ps(_mm_loadu_ps(dst.as_ptr()), scaled);
+                _mm_storeu_ps(dst.as_mut_ptr(), sum);
+            }
+        }
+
+        scalar::add_scaled(dst_chunks.into_remainder(), src_chunks.remainder(), scale);
+    }
+
+    /// See [`scalar::transform_vec3s`].
+    pub(crate) fn transform_vec3s(matrix: &Matrix4<f32>, data: &mut [f32], w: f32) {
+        for vec3 in data.chunks_exact_mut(3) {
+            let mut transformed = [0.; 4];
+            unsafe {
+                _mm_storeu_ps(
+                    transformed.as_mut_ptr(),
+                    transform(matrix, [vec3[0], vec3[1], vec3[2]], w),
+                );
+            }
+            vec3.copy_from_slice(&transformed[..3]);
+        }
+    }
+
+    /// See [`scalar::blend_transformed_point`].
+    pub(crate) fn blend_transformed_point(
+        sum: &mut [f32; 4],
+        matrix: &Matrix4<f32>,
+        point: [f32; 3],
+        weight: f32,
+    ) {
+        unsafe {
+            let weighted = _mm_mul_ps(transform(matrix, point, 1.), _mm_set1_ps(weight));
+            let blended = _mm_add_ps(_mm_loadu_ps(sum.as_ptr()), weighted);
+            _mm_storeu_ps(sum.as_mut_ptr(), blended);
+        }
+    }
+
+    /// Every column of the matrix scaled by one of the components and summed.
+    unsafe fn transform(matrix: &Matrix4<f32>, [x, y, z]: [f32; 3], w: f32) -> __m128 {
+        // Matrices are column major
+        let columns = matrix.as_slice().as_ptr();
+
+        let x = _mm_mul_ps(_mm_loadu_ps(columns), _mm_set1_ps(x));
+        let y = _mm_mul_ps(_mm_loadu_ps(columns.add(4)), _mm_set1_ps(y));
+        let z = _mm_mul_ps(_mm_loadu_ps(columns.add(8)), _mm_set1_ps(z));
+        let w = _mm_mul_ps(_mm_loadu_ps(columns.add(12)), _mm_set1_ps(w));
+
+        _mm_add_ps(_mm_add_ps(_mm_add_ps(x, y), z), w)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::{Rotation3, Vector3};
+
+    /// Lengths that aren't a multiple of the SIMD block sizes so that the leftovers get handled
+    /// by the scalar loops.
+    fn data(len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|idx| (idx as f32 * 0.37).sin() * 10.)
+            .collect()
+    }
+
+    /// Verify that the loops that the `simd` feature enables give the same results as the scalar
+    /// loops.
+    #[test]
+    fn matches_scalar() {
+        let matrix = Matrix4::new_translation(&Vector3::new(1., -2., 3.))
+            * Rotation3::from_euler_angles(0.3, -1.1, 2.).to_homogeneous()
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(2., 0.5, 1.5));
+
+        let mut expected = data(3 * 41);
+        let mut actual = expected.clone();
+        scalar::y_up(&mut expected);
+        y_up(&mut actual);
+        assert_eq!(actual, expected);
+
+        scalar::add_scaled(&mut expected, &data(3 * 41), 0.25);
+        add_scaled(&mut actual, &data(3 * 41), 0.25);
+        assert_eq!(actual, expected);
+
+        for w in [0., 1.].iter() {
+            scalar::transform_vec3s(&matrix, &mut expected, *w);
+            transform_vec3s(&matrix, &mut actual, *w);
+            assert_eq!(actual, expected);
+        }
+
+        let (mut expected, mut actual) = ([0.5; 4], [0.5; 4]);
+        for point in data(3 * 5).chunks(3) {
+            let point = [point[0], point[1], point[2]];
+            scalar::blend_transformed_point(&mut expected, &matrix, point, 0.2);
+            blend_transformed_point(&mut actual, &matrix, point, 0.2);
+        }
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::BlenderMesh;
+use std::collections::HashMap;
+
+impl BlenderMesh {
+    /// Round every position to the nearest multiple of `grid_size` and then weld the positions
+    /// that end up in the same place.
+    ///
+    /// Useful for modular kit pieces that need to line up exactly at their tile boundaries, since
+    /// positions that Blender exports are often a tiny bit off of the grid that they were modeled
+    /// on.
+    ///
+    /// Welding points every face corner at the first of the positions that share a grid point.
+    /// Positions are only welded if they have the same bone influences and shape key deltas, so
+    /// that they can't get pulled apart when the mesh is animated. Per position data, such as
+    /// point custom attributes, is taken from the position that the others were welded to.
+    /// Welded positions stay in the position data but are no longer used by any face.
+    ///
+    /// Returns the number of positions that were welded. Does nothing if `grid_size` is not
+    /// greater than zero.
+    pub fn snap_positions(&mut self, grid_size: f32) -> usize {
+        if grid_size <= 0. || grid_size.is_nan() {
+            return 0;
+        }
+        let snap = |value: f32| (value / grid_size).round() * grid_size;
+
+        let multi = &mut self.multi_indexed_vertex_attributes;
+        for position in multi.positions.attribute.data.iter_mut() {
+            *position = snap(*position);
+        }
+
+        for corner in self.bounding_box.min_corner.iter_mut() {
+            *corner = snap(*corner);
+        }
+        for corner in self.bounding_box.max_corner.iter_mut() {
+            *corner = snap(*corner);
+        }
+
+        let influences = multi
+            .bone_influences
+            .as_ref()
+            .map(|bone_influences| bone_influences.influences_per_vertex());
+
+        let mut grid_points: HashMap<Vec<u32>, u32> = HashMap::new();
+        let mut welded_to = vec![];
+
+        // Adding 0 turns -0 into 0 so that both land on the same grid point
+        for (idx, position) in multi.positions.attribute.data.chunks(3).enumerate() {
+            let mut key: Vec<u32> = position
+                .iter()
+                .map(|value| (value + 0.).to_bits())
+                .collect();
+
+            for shape_key in self.shape_keys.iter() {
+                let delta = shape_key.position_deltas.data_at_idx(idx as u32);
+                key.extend(delta.iter().map(|value| (value + 0.).to_bits()));
+            }
+            if let Some((bones, weights)) = influences.as_ref().and_then(|i| i.get(idx)) {
+                key.extend(bones.iter().map(|bone| *bone as u32));
+                key.extend(weights.iter().map(|weight| weight.to_bits()));
+            }
+
+            welded_to.push(*grid_points.entry(key).or_insert(idx as u32));
+        }
+
+        let mut welded = 0;
+        for (idx, target) in welded_to.iter().enumerate() {
+            if *target != idx as u32 {
+                welded += 1;
+            }
+        }
+
+        for position_idx in multi.positions.indices.iter_mut() {
+            *position_idx = welded_to[*position_idx as usize];
+        }
+
+        self.invalidate_bvh();
+
+        welded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that positions that are a tiny bit off of the grid get snapped onto it and that the
+    /// positions that land on the same grid point get welded.
+    #[test]
+    fn snap_and_weld() {
+        let mut plane = BlenderMesh::plane(2., 2., 0);
+        let multi = &mut plane.multi_indexed_vertex_attributes;
+
+        // A duplicate of the top left position that is slightly off, used by the last corner
+        multi
+            .positions
+            .attribute
+            .data
+            .extend_from_slice(&[-1.0003, 0.9998, 0.0001]);
+        multi.positions.indices[3] = 4;
+        multi.positions.attribute.data[3] = 1.0004;
+
+        let welded = plane.snap_positions(0.25);
+
+        let multi = plane.multi_indexed_vertex_attributes();
+        assert_eq!(welded, 1);
+        assert_eq!(multi.positions().indices()[3], 2);
+        assert_eq!(
+            &multi.positions().attribute().data()[0..6],
+            &[-1., -1., 0., 1., -1., 0.]
+        );
+
+        assert_eq!(plane.snap_positions(0.), 0);
+    }
+}
@@ -0,0 +1,334 @@
+//! Picking and proximity queries against a mesh's faces.
+
+use crate::bvh::{distance_squared_to_box, ray_box_entry};
+use crate::BlenderMesh;
+use nalgebra::{Point3, Vector3};
+
+/// Where a ray intersected a mesh.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RaycastHit {
+    /// The distance from the ray's origin to the hit
+    pub distance: f32,
+    /// The point where the ray hit the mesh
+    pub position: Point3<f32>,
+    /// The unit length normal of the triangle that was hit, based on its winding order
+    pub normal: Vector3<f32>,
+    /// The index of the face that was hit
+    pub face: usize,
+}
+
+/// The point on a mesh's surface that is closest to some other point.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ClosestPoint {
+    /// The distance from the queried point to the closest point
+    pub distance: f32,
+    /// The closest point on the mesh
+    pub position: Point3<f32>,
+    /// The index of the face that the closest point lies on
+    pub face: usize,
+}
+
+impl BlenderMesh {
+    /// Find the first face that a ray intersects.
+    ///
+    /// Faces are hit from either side. The direction does not need to be normalized.
+    ///
+    /// Returns None if the ray misses the mesh or the direction has no length.
+    ///
+    /// ```
+    /// # use blender_mesh::BlenderMesh;
+    /// # use nalgebra::{Point3, Vector3};
+    /// let cube = BlenderMesh::cube(2.0);
+    ///
+    /// let hit = cube
+    ///     .raycast(Point3::new(5.0, 0.0, 0.0), Vector3::new(-1.0, 0.0, 0.0))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(hit.distance, 4.0);
+    /// assert_eq!(hit.normal, Vector3::new(1.0, 0.0, 0.0));
+    /// ```
+    pub fn raycast(&self, origin: Point3<f32>, direction: Vector3<f32>) -> Option<RaycastHit> {
+        let direction = direction.try_normalize(std::f32::EPSILON)?;
+        let inverse_direction = direction.map(|component| 1. / component);
+
+        let bvh = self.bvh();
+        let positions = &self.multi_indexed_vertex_attributes.positions.attribute;
+
+        let mut closest: Option<(f32, usize)> = None;
+        let mut stack = vec![];
+        if !bvh.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(node_idx) = stack.pop() {
+            let node = &bvh.nodes[node_idx];
+
+            let entry = match ray_box_entry(&origin, &inverse_direction, &node.bounding_box) {
+                Some(entry) => entry,
+                None => continue,
+            };
+            if let Some((distance, _)) = closest {
+                if entry > distance {
+                    continue;
+                }
+            }
+
+            match node.children() {
+                Some((left, right)) => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+                None => {
+                    for triangle_idx in node.triangle_range().unwrap() {
+                        let corners = bvh.triangles[triangle_idx].corners(positions);
+
+                        if let Some(distance) = ray_triangle(&origin, &direction, &corners) {
+                            if closest.map_or(true, |(closest, _)| distance < closest) {
+                                closest = Some((distance, triangle_idx));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        closest.map(|(distance, triangle_idx)| {
+            let triangle = &bvh.triangles[triangle_idx];
+            let [a, b, c] = triangle.corners(positions);
+
+            RaycastHit {
+                distance,
+                position: origin + direction * distance,
+                normal: (b - a).cross(&(c - a)).normalize(),
+                face: triangle.face(),
+            }
+        })
+    }
+
+    /// Find the point on the mesh's surface that is closest to the provided point.
+    ///
+    /// Returns None if the mesh has no faces.
+    pub fn closest_point(&self, point: Point3<f32>) -> Option<ClosestPoint> {
+        let bvh = self.bvh();
+        let positions = &self.multi_indexed_vertex_attributes.positions.attribute;
+
+        let mut closest: Option<(f32, Point3<f32>, usize)> = None;
+        let mut stack = vec![];
+        if !bvh.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(node_idx) = stack.pop() {
+            let node = &bvh.nodes[node_idx];
+
+            if let Some((distance_squared, _, _)) = closest {
+                if distance_squared_to_box(&point, &node.bounding_box) > distance_squared {
+                    continue;
+                }
+            }
+
+            match node.children() {
+                Some((left, right)) => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+                None => {
+                    for triangle_idx in node.triangle_range().unwrap() {
+                        let corners = bvh.triangles[triangle_idx].corners(positions);
+
+                        let candidate = closest_point_on_triangle(&point, &corners);
+                        let distance_squared = (candidate - point).norm_squared();
+
+                        if closest.map_or(true, |(closest, _, _)| distance_squared < closest) {
+                            closest = Some((distance_squared, candidate, triangle_idx));
+                        }
+                    }
+                }
+            }
+        }
+
+        closest.map(|(distance_squared, position, triangle_idx)| ClosestPoint {
+            distance: distance_squared.sqrt(),
+            position,
+            face: bvh.triangles[triangle_idx].face(),
+        })
+    }
+}
+
+/// Möller–Trumbore ray triangle intersection. Returns the distance along the ray to the hit.
+fn ray_triangle(
+    origin: &Point3<f32>,
+    direction: &Vector3<f32>,
+    [a, b, c]: &[Point3<f32>; 3],
+) -> Option<f32> {
+    let edge_1 = b - a;
+    let edge_2 = c - a;
+
+    let p = direction.cross(&edge_2);
+    let determinant = edge_1.dot(&p);
+
+    // The ray is parallel to the triangle
+    if determinant.abs() < std::f32::EPSILON {
+        return None;
+    }
+    let inverse_determinant = 1. / determinant;
+
+    let s = origin - a;
+    let u = s.dot(&p) * inverse_determinant;
+    if u < 0. || u > 1. {
+        return None;
+    }
+
+    let q = s.cross(&edge_1);
+    let v = direction.dot(&q) * inverse_determinant;
+    if v < 0. || u + v > 1. {
+        return None;
+    }
+
+    let distance = edge_2.dot(&q) * inverse_determinant;
+    match distance >= 0. {
+        true => Some(distance),
+        false => None,
+    }
+}
+
+/// The closest point on a triangle, found by checking which of the triangle's vertex, edge or face
+/// regions the point lies in.
+///
+/// @see Real-Time Collision Detection, Christer Ericson, section 5.1.5
+fn closest_point_on_triangle(point: &Point3<f32>, [a, b, c]: &[Point3<f32>; 3]) -> Point3<f32> {
+    let ab = b - a;
+    let ac = c - a;
+
+    let ap = point - a;
+    let d1 = ab.dot(&ap);
+    let d2 = ac.dot(&ap);
+    if d1 <= 0. && d2 <= 0. {
+        return *a;
+    }
+
+    let bp = point - b;
+    let d3 = ab.dot(&bp);
+    let d4 = ac.dot(&bp);
+    if d3 >= 0. && d4 <= d3 {
+        return *b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0. && d1 >= 0. && d3 <= 0. {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = point - c;
+    let d5 = ab.dot(&cp);
+    let d6 = ac.dot(&cp);
+    if d6 >= 0. && d5 <= d6 {
+        return *c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0. && d2 >= 0. && d6 <= 0. {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0. && d4 - d3 >= 0. && d5 - d6 >= 0. {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denominator = 1. / (va + vb + vc);
+    a + ab * (vb * denominator) + ac * (vc * denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that we hit the closest face and report which face was hit.
+    #[test]
+    fn raycast_hits_closest_face() {
+        let cube = BlenderMesh::cube(2.0);
+
+        let hit = cube
+            .raycast(Point3::new(0.5, 0.25, 10.0), Vector3::new(0.0, 0.0, -3.0))
+            .unwrap();
+
+        assert_eq!(hit.distance, 9.0);
+        assert_eq!(hit.position, Point3::new(0.5, 0.25, 1.0));
+        assert_eq!(hit.normal, Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!(hit.face, 4);
+    }
+
+    /// Verify that rays that point away from or past the mesh do not hit it.
+    #[test]
+    fn raycast_misses() {
+        let cube = BlenderMesh::cube(2.0);
+
+        assert!(cube
+            .raycast(Point3::new(0.0, 0.0, 10.0), Vector3::new(0.0, 0.0, 1.0))
+            .is_none());
+        assert!(cube
+            .raycast(Point3::new(3.0, 0.0, 10.0), Vector3::new(0.0, 0.0, -1.0))
+            .is_none());
+        assert!(cube
+            .raycast(Point3::new(0.0, 0.0, 10.0), Vector3::zeros())
+            .is_none());
+    }
+
+    /// Verify that we find the closest point on faces, edges and corners.
+    #[test]
+    fn closest_point() {
+        let cube = BlenderMesh::cube(2.0);
+
+        let tests = vec![
+            // In front of a face
+            (
+                Point3::new(0.5, 0.25, 4.0),
+                Point3::new(0.5, 0.25, 1.0),
+                3.0,
+            ),
+            // Diagonally out from a corner
+            (
+                Point3::new(2.0, 2.0, 2.0),
+                Point3::new(1.0, 1.0, 1.0),
+                3f32.sqrt(),
+            ),
+            // Inside of the cube
+            (
+                Point3::new(0.0, 0.0, 0.75),
+                Point3::new(0.0, 0.0, 1.0),
+                0.25,
+            ),
+        ];
+
+        for (point, expected_position, expected_distance) in tests {
+            let closest = cube.closest_point(point).unwrap();
+
+            assert!((closest.position - expected_position).norm() < 1e-6);
+            assert!((closest.distance - expected_distance).abs() < 1e-6);
+        }
+    }
+
+    /// Verify that a mesh without faces has nothing to query.
+    #[test]
+    fn empty_mesh() {
+        let mesh = BlenderMesh::default();
+
+        assert!(mesh.closest_point(Point3::origin()).is_none());
+        assert!(mesh
+            .raycast(Point3::origin(), Vector3::new(1.0, 0.0, 0.0))
+            .is_none());
+    }
+
+    /// Verify that we query the new positions after converting to y up.
+    #[test]
+    fn y_up_rebuilds_bvh() {
+        let mut plane = BlenderMesh::plane(2.0, 2.0, 0);
+        let down = Vector3::new(0.0, 0.0, -1.0);
+
+        assert!(plane.raycast(Point3::new(0.0, 0.0, 1.0), down).is_some());
+
+        plane.y_up();
+        assert!(plane.raycast(Point3::new(0.0, 0.0, 1.0), down).is_none());
+    }
+}
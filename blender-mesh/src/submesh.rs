@@ -0,0 +1,385 @@
+use crate::bone::BoneInfluencesPerVertex;
+use crate::custom_attribute::gather_values;
+use crate::vertex_attributes::{IndexedAttribute, VertexAttribute, VertexBoneInfluences};
+use crate::{
+    AttributeDomain, BlenderMesh, BoundingBox, CustomAttribute, FaceView,
+    MultiIndexedVertexAttributes, ShapeKey,
+};
+use nalgebra::Point3;
+use std::collections::{HashMap, HashSet};
+
+/// The faces to extract from a mesh into a sub mesh.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmeshSelector {
+    /// Every face whose vertices all belong to the vertex group with this name.
+    VertexGroup(String),
+    /// Every face that is assigned to the face map with this name.
+    FaceMap(String),
+}
+
+/// An error while extracting a sub mesh.
+#[derive(Debug, thiserror::Error)]
+pub enum SubmeshError {
+    /// The mesh does not have a vertex group with this name.
+    #[error("The mesh does not have a vertex group named {0}")]
+    MissingVertexGroup(String),
+    /// The mesh does not have a face map with this name.
+    #[error("The mesh does not have a face map named {0}")]
+    MissingFaceMap(String),
+}
+
+impl BlenderMesh {
+    /// Extract the selected faces into a standalone mesh named `{mesh name}.{group name}`.
+    ///
+    /// Only the positions, normals, uvs, bone influences, custom attribute values and shape key
+    /// deltas that the selected faces use are kept, and their indices are remapped to match.
+    /// Loose edges are kept if both of their positions are.
+    /// Materials are kept as they are so that the faces' material indices stay valid.
+    ///
+    /// Useful for destructible props where each piece is authored as a face map on a single
+    /// object.
+    pub fn extract_submesh(&self, selector: &SubmeshSelector) -> Result<BlenderMesh, SubmeshError> {
+        let multi = &self.multi_indexed_vertex_attributes;
+        let face_count = multi.vertices_in_each_face.len();
+
+        let (group_name, selected_faces) = match selector {
+            SubmeshSelector::VertexGroup(name) => {
+                let vertices: HashSet<u32> = self
+                    .vertex_groups
+                    .get(name)
+                    .ok_or_else(|| SubmeshError::MissingVertexGroup(name.clone()))?
+                    .iter()
+                    .cloned()
+                    .collect();
+
+                let mut selected = vec![];
+                for face in multi.faces() {
+                    if face
+                        .position_indices()
+                        .iter()
+                        .all(|vertex| vertices.contains(vertex))
+                    {
+                        selected.push(face.index());
+                    }
+                }
+
+                (name, selected)
+            }
+            SubmeshSelector::FaceMap(name) => {
+                let faces = self
+                    .face_maps
+                    .get(name)
+                    .ok_or_else(|| SubmeshError::MissingFaceMap(name.clone()))?;
+
+                let mut selected: Vec<usize> = faces
+                    .iter()
+                    .map(|face| *face as usize)
+                    .filter(|face| *face < face_count)
+                    .collect();
+                selected.sort();
+                selected.dedup();
+
+                (name, selected)
+            }
+        };
+
+        let faces: Vec<FaceView> = multi.faces().collect();
+        let mut corners = vec![];
+        for face_idx in selected_faces.iter() {
+            corners.extend(faces[*face_idx].corners());
+        }
+
+        let (positions, position_remap) = extract_attribute(&multi.positions, &corners);
+        let normals = multi
+            .normals
+            .as_ref()
+            .map(|normals| extract_attribute(normals, &corners).0);
+        let uvs = multi
+            .uvs
+            .as_ref()
+            .map(|uvs| extract_attribute(uvs, &corners).0);
+        let lightmap_uvs = multi
+            .lightmap_uvs
+            .as_ref()
+            .map(|uvs| extract_attribute(uvs, &corners).0);
+
+        // Vertices in their new order
+        let mut old_vertices = vec![0; position_remap.len()];
+        for (old, new) in position_remap.iter() {
+            old_vertices[*new as usize] = *old;
+        }
+
+        let bone_influences = multi.bone_influences.as_ref().map(|bone_influences| {
+            let influences = bone_influences.influences_per_vertex();
+            let mut extracted = VertexBoneInfluences::default();
+            let mut counts = vec![];
+
+            for old in old_vertices.iter() {
+                let (indices, weights) = influences[*old as usize];
+
+                counts.push(indices.len() as u8);
+                extracted.bone_indices.extend_from_slice(indices);
+                extracted.bone_weights.extend_from_slice(weights);
+            }
+
+            extracted.bones_per_vertex = match &bone_influences.bones_per_vertex {
+                BoneInfluencesPerVertex::Uniform(count) => BoneInfluencesPerVertex::Uniform(*count),
+                BoneInfluencesPerVertex::NonUniform(_) => {
+                    BoneInfluencesPerVertex::NonUniform(counts)
+                }
+            };
+
+            extracted
+        });
+
+        let mut vertex_groups = HashMap::new();
+        for (name, vertices) in self.vertex_groups.iter() {
+            let vertices: Vec<u32> = vertices
+                .iter()
+                .filter_map(|vertex| position_remap.get(vertex).cloned())
+                .collect();
+            if !vertices.is_empty() {
+                vertex_groups.insert(name.clone(), vertices);
+            }
+        }
+
+        let face_remap: HashMap<u32, u32> = selected_faces
+            .iter()
+            .enumerate()
+            .map(|(new, old)| (*old as u32, new as u32))
+            .collect();
+        let mut face_maps = HashMap::new();
+        for (name, faces) in self.face_maps.iter() {
+            let faces: Vec<u32> = faces
+                .iter()
+                .filter_map(|face| face_remap.get(face).cloned())
+                .collect();
+            if !faces.is_empty() {
+                face_maps.insert(name.clone(), faces);
+            }
+        }
+
+        let smoothing_groups = match self.smoothing_groups.len() == face_count {
+            true => selected_faces
+                .iter()
+                .map(|face| self.smoothing_groups[*face])
+                .collect(),
+            false => vec![],
+        };
+
+        let corner_indices: Vec<u32> = corners.iter().map(|corner| *corner as u32).collect();
+        let face_indices: Vec<u32> = selected_faces.iter().map(|face| *face as u32).collect();
+        let mut custom_attributes = HashMap::new();
+        for (name, attribute) in self.custom_attributes.iter() {
+            let indices = match attribute.domain {
+                AttributeDomain::Point => &old_vertices,
+                AttributeDomain::Corner => &corner_indices,
+                AttributeDomain::Face => &face_indices,
+            };
+            custom_attributes.insert(
+                name.clone(),
+                CustomAttribute::new(attribute.domain, attribute.data.gather(indices)),
+            );
+        }
+
+        let shape_keys = self
+            .shape_keys
+            .iter()
+            .map(|shape_key| ShapeKey {
+                name: shape_key.name.clone(),
+                position_deltas: gather_values(&shape_key.position_deltas, &old_vertices),
+                weight: shape_key.weight,
+                driver: shape_key.driver.clone(),
+            })
+            .collect();
+
+        // Loose edges are kept if the selected faces use both of their positions
+        let edges = self
+            .edges
+            .iter()
+            .filter_map(|[start, end]| {
+                Some([*position_remap.get(start)?, *position_remap.get(end)?])
+            })
+            .collect();
+
+        let bounding_box = bounding_box(&positions.attribute.data);
+
+        Ok(BlenderMesh {
+            name: format!("{}.{}", self.name, group_name),
+            data_name: self.data_name.clone(),
+            library: self.library.clone(),
+            // Only the mesh's object has a stable id, so ids stay unique
+            stable_id: None,
+            thumbnail: self.thumbnail.clone(),
+            armatures: self.armatures.clone(),
+            bounding_box,
+            multi_indexed_vertex_attributes: MultiIndexedVertexAttributes {
+                vertices_in_each_face: selected_faces
+                    .iter()
+                    .map(|face| multi.vertices_in_each_face[*face])
+                    .collect(),
+                material_index: selected_faces
+                    .iter()
+                    .map(|face| multi.material_index.get(*face).cloned().unwrap_or(0))
+                    .collect(),
+                positions,
+                normals,
+                uvs,
+                lightmap_uvs,
+                bone_influences,
+            },
+            materials: self.materials.clone(),
+            custom_properties: self.custom_properties.clone(),
+            smoothing_groups,
+            vertex_groups,
+            face_maps,
+            custom_attributes,
+            shape_keys,
+            edges,
+            collections: self.collections.clone(),
+            visibility: self.visibility,
+            space: self.space,
+            world_transform: self.world_transform,
+            bvh: Default::default(),
+        })
+    }
+}
+
+/// Copy the data used by the given corners into a new attribute, returning it along with a map
+/// of old index to new index.
+fn extract_attribute<T: Clone>(
+    attribute: &IndexedAttribute<T>,
+    corners: &[usize],
+) -> (IndexedAttribute<T>, HashMap<u32, u32>) {
+    let mut remap = HashMap::new();
+    let mut indices = vec![];
+    let mut data = vec![];
+
+    for corner in corners {
+        let old = attribute.indices[*corner];
+
+        let new = *remap.entry(old).or_insert_with(|| {
+            data.extend_from_slice(attribute.attribute.data_at_idx(old));
+            (data.len() / attribute.attribute.attribute_size as usize - 1) as u32
+        });
+        indices.push(new);
+    }
+
+    let extracted = IndexedAttribute::new(
+        indices,
+        VertexAttribute {
+            data,
+            attribute_size: attribute.attribute.attribute_size,
+        },
+    );
+
+    (extracted, remap)
+}
+
+fn bounding_box(positions: &[f32]) -> BoundingBox {
+    if positions.is_empty() {
+        return BoundingBox::default();
+    }
+
+    let mut min_corner = Point3::new(std::f32::MAX, std::f32::MAX, std::f32::MAX);
+    let mut max_corner = Point3::new(std::f32::MIN, std::f32::MIN, std::f32::MIN);
+
+    for position in positions.chunks(3) {
+        for axis in 0..3 {
+            min_corner[axis] = min_corner[axis].min(position[axis]);
+            max_corner[axis] = max_corner[axis].max(position[axis]);
+        }
+    }
+
+    BoundingBox {
+        min_corner,
+        max_corner,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that we extract the faces of a face map and remap their indices.
+    #[test]
+    fn extract_face_map() {
+        let mut plane = BlenderMesh::plane(2., 2., 1);
+        plane.face_maps.insert("Corner".to_string(), vec![3]);
+        plane.vertex_groups.insert("Center".to_string(), vec![4]);
+
+        let corner = plane
+            .extract_submesh(&SubmeshSelector::FaceMap("Corner".to_string()))
+            .unwrap();
+        let multi = &corner.multi_indexed_vertex_attributes;
+
+        assert_eq!(corner.name, "Plane.Corner");
+        assert_eq!(multi.vertices_in_each_face, vec![4]);
+        assert_eq!(multi.positions.indices, vec![0, 1, 2, 3]);
+        assert_eq!(
+            multi.positions.attribute.data,
+            vec![0., 0., 0., 1., 0., 0., 1., 1., 0., 0., 1., 0.]
+        );
+        assert_eq!(
+            multi.normals.as_ref().unwrap().attribute.data,
+            vec![0., 0., 1.]
+        );
+        assert_eq!(corner.bounding_box.min_corner, Point3::new(0., 0., 0.));
+        assert_eq!(corner.face_maps["Corner"], vec![0]);
+        assert_eq!(corner.vertex_groups["Center"], vec![0]);
+    }
+
+    /// Verify that a face map still selects the right faces after the mesh is triangulated.
+    #[test]
+    fn extract_face_map_after_triangulating() {
+        let mut plane = BlenderMesh::plane(2., 2., 1);
+        plane.face_maps.insert("Corner".to_string(), vec![3]);
+        plane.triangulate_faces();
+
+        assert_eq!(plane.face_maps["Corner"], vec![6, 7]);
+
+        let corner = plane
+            .extract_submesh(&SubmeshSelector::FaceMap("Corner".to_string()))
+            .unwrap();
+        let multi = &corner.multi_indexed_vertex_attributes;
+
+        assert_eq!(multi.vertices_in_each_face, vec![3, 3]);
+        assert_eq!(multi.positions.indices, vec![0, 1, 2, 0, 2, 3]);
+        assert_eq!(
+            multi.positions.attribute.data,
+            vec![0., 0., 0., 1., 0., 0., 1., 1., 0., 0., 1., 0.]
+        );
+        assert_eq!(corner.face_maps["Corner"], vec![0, 1]);
+    }
+
+    /// Verify that a face is only extracted if all of its vertices are in the vertex group.
+    #[test]
+    fn extract_vertex_group() {
+        let mut plane = BlenderMesh::plane(2., 2., 1);
+        // The bottom and middle rows of vertices
+        plane
+            .vertex_groups
+            .insert("Bottom".to_string(), vec![0, 1, 2, 3, 4, 5]);
+
+        let bottom = plane
+            .extract_submesh(&SubmeshSelector::VertexGroup("Bottom".to_string()))
+            .unwrap();
+
+        assert_eq!(
+            bottom.multi_indexed_vertex_attributes.vertices_in_each_face,
+            vec![4, 4]
+        );
+        assert_eq!(bottom.bounding_box.max_corner, Point3::new(1., 0., 0.));
+    }
+
+    /// Verify that we return an error for groups that don't exist.
+    #[test]
+    fn missing_group() {
+        let plane = BlenderMesh::plane(2., 2., 1);
+
+        match plane.extract_submesh(&SubmeshSelector::FaceMap("Missing".to_string())) {
+            Err(SubmeshError::MissingFaceMap(name)) => assert_eq!(name, "Missing"),
+            other => panic!("{:?}", other),
+        };
+    }
+}
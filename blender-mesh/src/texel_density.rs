@@ -0,0 +1,186 @@
+use crate::topology::face_area;
+use crate::uv_island::uv_face_area;
+use crate::{BlenderMesh, UvIsland, UvLayer, UvTransform};
+
+/// How many texels of a texture cover one meter of a mesh's surface, which environment artists
+/// keep consistent across props so that no prop looks blurrier than the ones around it.
+///
+/// Densities are in texels per object space unit, which is a meter in a scene that uses metric
+/// units. A face or island has no density if it has no area in either uv or object space.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TexelDensity {
+    pub(crate) faces: Vec<Option<f32>>,
+    pub(crate) islands: Vec<Option<f32>>,
+    pub(crate) min: f32,
+    pub(crate) average: f32,
+    pub(crate) max: f32,
+}
+
+impl TexelDensity {
+    /// The density of every face, in face order.
+    pub fn faces(&self) -> &Vec<Option<f32>> {
+        &self.faces
+    }
+
+    /// The density of every island, in the order of [`BlenderMesh.method#uv_islands`].
+    pub fn islands(&self) -> &Vec<Option<f32>> {
+        &self.islands
+    }
+
+    /// The density of the face with the fewest texels per meter.
+    pub fn min(&self) -> f32 {
+        self.min
+    }
+
+    /// The density of the mesh as a whole, so larger faces count for more than smaller ones.
+    pub fn average(&self) -> f32 {
+        self.average
+    }
+
+    /// The density of the face with the most texels per meter.
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+}
+
+impl BlenderMesh {
+    /// The texel density of the mesh's faces and uv islands when one of its uv maps samples a
+    /// square texture that is `texture_resolution` texels wide.
+    ///
+    /// None if the mesh doesn't have the uv map or none of its faces have any area.
+    ///
+    /// ```
+    /// # use blender_mesh::{BlenderMesh, UvLayer};
+    /// // A 2 meter wide plane with its uvs covering the whole texture
+    /// let plane = BlenderMesh::plane(2., 2., 1);
+    ///
+    /// let density = plane.texel_density(UvLayer::Uv, 1024).unwrap();
+    /// assert_eq!(density.average(), 512.);
+    /// ```
+    pub fn texel_density(&self, layer: UvLayer, texture_resolution: u32) -> Option<TexelDensity> {
+        let multi = &self.multi_indexed_vertex_attributes;
+        let uvs = match layer {
+            UvLayer::Uv => multi.uvs.as_ref(),
+            UvLayer::Lightmap => multi.lightmap_uvs.as_ref(),
+        }?;
+        let resolution = texture_resolution as f32;
+
+        let (mut total_uv_area, mut total_surface_area) = (0., 0.);
+        let (mut min, mut max) = (f32::INFINITY, f32::NEG_INFINITY);
+
+        let faces = self
+            .faces()
+            .map(|face| {
+                let uv_area = uv_face_area(uvs, face.corners());
+                let surface_area = face_area(face.position_indices(), |idx| {
+                    multi.positions.attribute.data_at_idx(idx)
+                });
+                total_uv_area += uv_area;
+                total_surface_area += surface_area;
+
+                let density = density(resolution, uv_area, surface_area)?;
+                min = min.min(density);
+                max = max.max(density);
+                Some(density)
+            })
+            .collect();
+
+        let islands = self
+            .uv_islands(layer)
+            .iter()
+            .map(|island| density(resolution, island.uv_area(), island.surface_area()))
+            .collect();
+
+        Some(TexelDensity {
+            faces,
+            islands,
+            min,
+            average: density(resolution, total_uv_area, total_surface_area)?,
+            max,
+        })
+    }
+
+    /// Scale every uv island around its center so that it has `target` texels per meter when
+    /// sampling a square texture that is `texture_resolution` texels wide.
+    ///
+    /// Islands grow or shrink in place, so they can overlap or leave the 0 to 1 range afterwards
+    /// and should be packed again. Islands without a density are left alone.
+    ///
+    /// Returns the islands with their new bounds, ready for packing. Does nothing if the mesh
+    /// doesn't have the uv map.
+    pub fn normalize_texel_density(
+        &mut self,
+        layer: UvLayer,
+        texture_resolution: u32,
+        target: f32,
+    ) -> Vec<UvIsland> {
+        let mut islands = self.uv_islands(layer);
+
+        for island in islands.iter_mut() {
+            let current = density(
+                texture_resolution as f32,
+                island.uv_area(),
+                island.surface_area(),
+            );
+
+            if let Some(current) = current {
+                let scale = target / current;
+                let transform = UvTransform {
+                    scale: [scale, scale],
+                    ..UvTransform::default()
+                };
+                self.transform_uv_island(layer, island, &transform);
+            }
+        }
+
+        islands
+    }
+}
+
+/// Texels per unit of a surface whose uvs cover `uv_area` of a texture `resolution` texels wide.
+fn density(resolution: f32, uv_area: f32, surface_area: f32) -> Option<f32> {
+    match uv_area > 0. && surface_area > 0. {
+        true => Some(resolution * (uv_area / surface_area).sqrt()),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlenderMeshBuilder, FaceCorner};
+
+    /// Verify that a small and a large quad that both cover the whole texture are reported with
+    /// different densities, and that normalizing gives them both the target density.
+    #[test]
+    fn report_and_normalize() {
+        let mut builder = BlenderMeshBuilder::new("Quads");
+        for size in [1., 2.].iter() {
+            let corners: Vec<FaceCorner> = [[0., 0.], [1., 0.], [1., 1.], [0., 1.]]
+                .iter()
+                .map(|corner: &[f32; 2]| FaceCorner {
+                    position: builder.add_position([corner[0] * size, corner[1] * size, 0.]),
+                    uv: Some(builder.add_uv(*corner)),
+                    ..FaceCorner::default()
+                })
+                .collect();
+            builder.add_face(&corners, 0);
+        }
+        let mut mesh = builder.build().unwrap();
+
+        let density = mesh.texel_density(UvLayer::Uv, 1024).unwrap();
+        assert_eq!(density.faces(), &vec![Some(1024.), Some(512.)]);
+        assert_eq!(density.islands(), &vec![Some(1024.), Some(512.)]);
+        assert_eq!(density.min(), 512.);
+        assert_eq!(density.max(), 1024.);
+        assert!((density.average() - 1024. * 0.4f32.sqrt()).abs() < 1e-3);
+        assert_eq!(mesh.texel_density(UvLayer::Lightmap, 1024), None);
+
+        let islands = mesh.normalize_texel_density(UvLayer::Uv, 1024, 256.);
+        assert_eq!(islands[1].uv_bounds(), ([0.25, 0.25], [0.75, 0.75]));
+
+        let density = mesh.texel_density(UvLayer::Uv, 1024).unwrap();
+        assert!((density.min() - 256.).abs() < 1e-3);
+        assert!((density.max() - 256.).abs() < 1e-3);
+    }
+}
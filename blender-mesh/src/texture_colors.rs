@@ -0,0 +1,156 @@
+use crate::vertex_attributes::VertexAttribute;
+use crate::{AttributeDomain, BlenderMesh, CustomAttribute, CustomAttributeData, UvOrigin};
+use image::RgbaImage;
+
+/// The name of the custom attribute that [`BlenderMesh.method#bake_texture_colors`] writes to.
+pub const TEXTURE_COLOR_ATTRIBUTE: &str = "texture_color";
+
+/// An error while baking a texture into vertex colors
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum TextureColorError {
+    /// The texture is sampled at the uvs.
+    #[error("The mesh doesn't have uvs to sample the texture at")]
+    NoUvs,
+    /// The texture has no texels to sample.
+    #[error("The texture is empty")]
+    EmptyTexture,
+}
+
+impl BlenderMesh {
+    /// Fill a corner custom attribute named [`TEXTURE_COLOR_ATTRIBUTE`] with the RGBA color of
+    /// the texture at every face corner's uv, so that low end renderers and quick previews can
+    /// draw the mesh with vertex colors instead of sampling its diffuse texture.
+    ///
+    /// The texture is sampled bilinearly and repeats outside of the 0 to 1 uv range. Which row of
+    /// the texture is at V = 0 follows the mesh's [`SpaceConvention.method#uv_origin`].
+    ///
+    /// Colors are the texture's values between 0 and 1 without any color space conversion, so an
+    /// sRGB texture gives sRGB colors. Materials that move their uvs before sampling should be
+    /// baked with [`BlenderMesh.method#bake_uv_transforms`] first.
+    ///
+    /// [`TEXTURE_COLOR_ATTRIBUTE`]: constant.TEXTURE_COLOR_ATTRIBUTE.html
+    pub fn bake_texture_colors(&mut self, texture: &RgbaImage) -> Result<(), TextureColorError> {
+        let uvs = self
+            .multi_indexed_vertex_attributes
+            .uvs
+            .as_ref()
+            .ok_or(TextureColorError::NoUvs)?;
+        if texture.width() == 0 || texture.height() == 0 {
+            return Err(TextureColorError::EmptyTexture);
+        }
+
+        let flip_v = self.space.uv_origin == UvOrigin::BottomLeft;
+        let mut colors = Vec::with_capacity(uvs.indices.len() * 4);
+
+        for corner in 0..uvs.indices.len() {
+            let uv = uvs.data_at_corner(corner);
+            let v = match flip_v {
+                true => 1. - uv[1],
+                false => uv[1],
+            };
+
+            colors.extend_from_slice(&sample_bilinear(texture, uv[0], v));
+        }
+
+        self.insert_custom_attribute(
+            TEXTURE_COLOR_ATTRIBUTE.to_string(),
+            CustomAttribute::new(
+                AttributeDomain::Corner,
+                CustomAttributeData::Float(VertexAttribute::new(colors, 4).unwrap()),
+            ),
+        );
+
+        Ok(())
+    }
+}
+
+/// Sample the texture with U going right and V going down from its top left corner, blending
+/// the four nearest texels by their distance from their centers.
+fn sample_bilinear(texture: &RgbaImage, u: f32, v: f32) -> [f32; 4] {
+    let (width, height) = (texture.width() as i64, texture.height() as i64);
+
+    let x = u * width as f32 - 0.5;
+    let y = v * height as f32 - 0.5;
+    let (left, top) = (x.floor(), y.floor());
+    let (right_weight, bottom_weight) = (x - left, y - top);
+
+    let texel = |col: i64, row: i64| {
+        let pixel = texture.get_pixel(col.rem_euclid(width) as u32, row.rem_euclid(height) as u32);
+        pixel.0
+    };
+
+    let (left, top) = (left as i64, top as i64);
+    let mut color = [0.; 4];
+    for (col, row, weight) in [
+        (left, top, (1. - right_weight) * (1. - bottom_weight)),
+        (left + 1, top, right_weight * (1. - bottom_weight)),
+        (left, top + 1, (1. - right_weight) * bottom_weight),
+        (left + 1, top + 1, right_weight * bottom_weight),
+    ]
+    .iter()
+    {
+        let texel = texel(*col, *row);
+        for channel in 0..4 {
+            color[channel] += texel[channel] as f32 / 255. * weight;
+        }
+    }
+
+    color
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vertex_attributes::IndexedAttribute;
+    use image::Rgba;
+
+    /// Verify that every corner gets the color of the texel under its uv, with V pointing up for
+    /// Blender's uvs and down once the uvs are flipped.
+    #[test]
+    fn sample_texel_under_each_corner() {
+        // Red and green along the top row, blue and white along the bottom row
+        let mut texture = RgbaImage::new(2, 2);
+        texture.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        texture.put_pixel(1, 0, Rgba([0, 255, 0, 255]));
+        texture.put_pixel(0, 1, Rgba([0, 0, 255, 255]));
+        texture.put_pixel(1, 1, Rgba([255, 255, 255, 255]));
+
+        // Every corner sits on the center of a texel
+        let mut plane = BlenderMesh::plane(2., 2., 0);
+        plane.multi_indexed_vertex_attributes.uvs = Some(IndexedAttribute::new(
+            vec![0, 1, 2, 3],
+            VertexAttribute::new(vec![0.25, 0.25, 0.75, 0.25, 0.75, 0.75, 0.25, 0.75], 2).unwrap(),
+        ));
+
+        let colors = |mesh: &BlenderMesh| -> Vec<Vec<f32>> {
+            match mesh.custom_attributes()[TEXTURE_COLOR_ATTRIBUTE].data() {
+                CustomAttributeData::Float(colors) => {
+                    colors.iter_vertices().map(|color| color.to_vec()).collect()
+                }
+                _ => panic!(),
+            }
+        };
+
+        plane.bake_texture_colors(&texture).unwrap();
+        assert_eq!(
+            colors(&plane),
+            vec![
+                vec![0., 0., 1., 1.],
+                vec![1., 1., 1., 1.],
+                vec![0., 1., 0., 1.],
+                vec![1., 0., 0., 1.],
+            ]
+        );
+
+        plane.flip_uv_v();
+        plane.bake_texture_colors(&texture).unwrap();
+        assert_eq!(colors(&plane)[0], vec![0., 0., 1., 1.]);
+        assert_eq!(colors(&plane)[2], vec![0., 1., 0., 1.]);
+
+        plane.multi_indexed_vertex_attributes.uvs = None;
+        assert_eq!(
+            plane.bake_texture_colors(&texture),
+            Err(TextureColorError::NoUvs)
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Options for post processing meshes as they are parsed from Blender's stdout.
+///
+/// @see [`parse_meshes_from_blender_stdout_with_options`]
+///
+/// [`parse_meshes_from_blender_stdout_with_options`]: crate::parse_meshes_from_blender_stdout_with_options
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Reduce texture paths that are not within one of the `texture_search_paths` to only their
+    /// file name. `/Users/me/textures/wood.png` becomes `wood.png`.
+    pub strip_texture_directories: bool,
+    /// Directories that textures are expected to live in, searched in order.
+    ///
+    /// A texture inside of one of these directories is identified by its path relative to that
+    /// directory, so with a search path of `/Users/me/game/assets`
+    /// `/Users/me/game/assets/textures/wood.png` becomes `textures/wood.png`.
+    pub texture_search_paths: Vec<PathBuf>,
+    /// Renames that get applied after a texture name has been normalized.
+    ///
+    /// For example, `"wood.png" => "wood_albedo"`.
+    pub texture_renames: HashMap<String, String>,
+}
+
+impl ExportOptions {
+    /// Turn a texture name from Blender into an engine friendly identifier.
+    ///
+    /// Backslashes become forward slashes, Blender's `//` relative paths get resolved against
+    /// the directory of the `.blend` file that they came from (if it is known) and `.` / `..`
+    /// segments are collapsed.
+    ///
+    /// The search paths, directory stripping and renames are then applied in that order.
+    pub fn normalize_texture_name(&self, texture_name: &str, blend_file: Option<&Path>) -> String {
+        let mut path = forward_slashes(texture_name);
+
+        if path.starts_with("//") {
+            let relative = &path[2..];
+
+            let blend_dir = blend_file
+                .and_then(Path::parent)
+                .map(|dir| forward_slashes(&dir.to_string_lossy()))
+                .filter(|dir| !dir.is_empty());
+
+            path = match blend_dir {
+                Some(dir) => format!("{}/{}", dir.trim_end_matches('/'), relative),
+                None => relative.to_string(),
+            };
+        }
+        let path = collapse_dot_segments(&path);
+
+        let within_search_path = self.texture_search_paths.iter().find_map(|search_path| {
+            let search_path = forward_slashes(&search_path.to_string_lossy());
+            let prefix = format!("{}/", search_path.trim_end_matches('/'));
+
+            if path.starts_with(&prefix) {
+                Some(path[prefix.len()..].to_string())
+            } else {
+                None
+            }
+        });
+
+        let name = match within_search_path {
+            Some(relative) => relative,
+            None if self.strip_texture_directories => path.rsplit('/').next().unwrap().to_string(),
+            None => path,
+        };
+
+        match self.texture_renames.get(&name) {
+            Some(renamed) => renamed.to_string(),
+            None => name,
+        }
+    }
+}
+
+fn forward_slashes(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+/// `/a/./b/../c.png` -> `/a/c.png`. Leading `..` segments of relative paths are kept.
+fn collapse_dot_segments(path: &str) -> String {
+    let mut segments: Vec<&str> = vec![];
+
+    for segment in path.split('/') {
+        match segment {
+            "." => {}
+            ".." => match segments.last() {
+                Some(last) if *last != ".." && !last.is_empty() => {
+                    segments.pop();
+                }
+                _ => segments.push(segment),
+            },
+            _ => segments.push(segment),
+        }
+    }
+
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that texture names are left alone by default.
+    #[test]
+    fn default_options_are_a_no_op() {
+        let options = ExportOptions::default();
+
+        assert_eq!(
+            options.normalize_texture_name("textures/wood.png", None),
+            "textures/wood.png"
+        );
+    }
+
+    /// Verify that we strip everything but the file name from absolute, relative and Windows
+    /// paths.
+    #[test]
+    fn strip_directories() {
+        let options = ExportOptions {
+            strip_texture_directories: true,
+            ..ExportOptions::default()
+        };
+
+        for texture_name in [
+            "/Users/me/textures/wood.png",
+            "//textures/wood.png",
+            "C:\\textures\\wood.png",
+            "wood.png",
+        ]
+        .iter()
+        {
+            assert_eq!(
+                options.normalize_texture_name(texture_name, None),
+                "wood.png"
+            );
+        }
+    }
+
+    /// Verify that Blender relative paths are resolved against the .blend file's directory
+    /// before being made relative to a search path.
+    #[test]
+    fn search_paths() {
+        let options = ExportOptions {
+            strip_texture_directories: true,
+            texture_search_paths: vec![
+                PathBuf::from("/Users/me/game/assets/"),
+                PathBuf::from("/Users/me/game"),
+            ],
+            ..ExportOptions::default()
+        };
+        let blend_file = Path::new("/Users/me/game/assets/levels/level1.blend");
+
+        assert_eq!(
+            options.normalize_texture_name("//../textures/wood.png", Some(blend_file)),
+            "textures/wood.png"
+        );
+        assert_eq!(
+            options.normalize_texture_name("//wood.png", Some(blend_file)),
+            "levels/wood.png"
+        );
+        assert_eq!(
+            options.normalize_texture_name("/Users/me/game/ui/button.png", None),
+            "ui/button.png"
+        );
+        assert_eq!(
+            options.normalize_texture_name("/Users/me/other/button.png", None),
+            "button.png"
+        );
+    }
+
+    /// Verify that renames are applied to the normalized name.
+    #[test]
+    fn renames() {
+        let mut texture_renames = HashMap::new();
+        texture_renames.insert("wood.png".to_string(), "wood_albedo".to_string());
+
+        let options = ExportOptions {
+            strip_texture_directories: true,
+            texture_renames,
+            ..ExportOptions::default()
+        };
+
+        assert_eq!(
+            options.normalize_texture_name("//textures/wood.png", None),
+            "wood_albedo"
+        );
+        assert_eq!(
+            options.normalize_texture_name("//textures/stone.png", None),
+            "stone.png"
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! Convert single indexed vertex data into [three-d] meshes.
+//!
+//! Enabled by the `three-d` feature.
+//!
+//! ```no_run
+//! # use blender_mesh::{BlenderMesh, CreateSingleIndexConfig};
+//! # fn example(context: &three_d::Context) {
+//! let mesh = BlenderMesh::cube(1.).combine_vertex_indices(&CreateSingleIndexConfig::default());
+//!
+//! let cpu_mesh = blender_mesh::three_d::to_cpu_mesh(&mesh);
+//! let mesh = three_d::Mesh::new(context, &cpu_mesh);
+//! # }
+//! ```
+//!
+//! [three-d]: https://github.com/asny/three-d
+
+use crate::SingleIndexedVertexAttributes;
+use ::three_d::{vec2, vec3, vec4, CpuMesh, Indices, Positions, Vec2, Vec3, Vec4};
+
+/// Convert the mesh into a three-d `CpuMesh`.
+///
+/// Normals, uvs and tangents are included when the mesh has them. Tangents come from the mesh's
+/// face tangents, with a `w` of 1.
+pub fn to_cpu_mesh(mesh: &SingleIndexedVertexAttributes) -> CpuMesh {
+    let vertices = mesh.vertices();
+
+    let positions: Vec<Vec3> = vertices
+        .iter()
+        .map(|vertex| {
+            let [x, y, z] = vertex.position();
+            vec3(x, y, z)
+        })
+        .collect();
+
+    let normals: Option<Vec<Vec3>> = vertices
+        .iter()
+        .map(|vertex| vertex.normal().map(|[x, y, z]| vec3(x, y, z)))
+        .collect();
+
+    let uvs: Option<Vec<Vec2>> = vertices
+        .iter()
+        .map(|vertex| vertex.uv().map(|[u, v]| vec2(u, v)))
+        .collect();
+
+    let tangents: Option<Vec<Vec4>> = vertices
+        .iter()
+        .map(|vertex| vertex.face_tangent().map(|[x, y, z]| vec4(x, y, z, 1.)))
+        .collect();
+
+    CpuMesh {
+        positions: Positions::F32(positions),
+        indices: Indices::U16(mesh.indices().clone()),
+        normals,
+        tangents,
+        uvs,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,171 @@
+use crate::BlenderMesh;
+use nalgebra::Vector3;
+use std::collections::HashMap;
+
+pub use self::half_edge::Topology;
+
+mod half_edge;
+
+/// Faces with an area at or below this are considered degenerate.
+const DEGENERATE_AREA: f32 = 1e-10;
+
+/// Problems with the connectivity and shape of a mesh's faces.
+///
+/// Edges are identified by the two position indices at their ends, smallest first. Vertices are
+/// only connected if they share a position index, so two vertices that happen to be at the same
+/// location are not considered connected.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TopologyReport {
+    pub(crate) non_manifold_edges: Vec<[u32; 2]>,
+    pub(crate) boundary_edges: Vec<[u32; 2]>,
+    pub(crate) duplicate_faces: Vec<usize>,
+    pub(crate) degenerate_faces: Vec<usize>,
+}
+
+impl TopologyReport {
+    /// Edges that are shared by more than two faces.
+    pub fn non_manifold_edges(&self) -> &Vec<[u32; 2]> {
+        &self.non_manifold_edges
+    }
+
+    /// Edges that only belong to one face, such as the outline of a plane or the rim of a hole.
+    pub fn boundary_edges(&self) -> &Vec<[u32; 2]> {
+        &self.boundary_edges
+    }
+
+    /// The indices of faces that use the same positions as an earlier face, regardless of
+    /// winding order.
+    pub fn duplicate_faces(&self) -> &Vec<usize> {
+        &self.duplicate_faces
+    }
+
+    /// The indices of faces that have (nearly) zero area, such as triangles with all of their
+    /// corners on one line.
+    pub fn degenerate_faces(&self) -> &Vec<usize> {
+        &self.degenerate_faces
+    }
+
+    /// Whether every edge is shared by at most two faces.
+    pub fn is_manifold(&self) -> bool {
+        self.non_manifold_edges.is_empty()
+    }
+
+    /// Whether the mesh is a closed, manifold surface without any duplicate or degenerate faces.
+    ///
+    /// Useful for checking that a mesh can be 3D printed.
+    pub fn is_watertight(&self) -> bool {
+        self.non_manifold_edges.is_empty()
+            && self.boundary_edges.is_empty()
+            && self.duplicate_faces.is_empty()
+            && self.degenerate_faces.is_empty()
+    }
+}
+
+impl BlenderMesh {
+    /// Find non manifold edges, open boundaries, duplicate faces and degenerate faces.
+    pub fn topology_report(&self) -> TopologyReport {
+        let multi = &self.multi_indexed_vertex_attributes;
+        let positions = &multi.positions;
+
+        let mut report = TopologyReport::default();
+        let mut first_face_with_positions: HashMap<Vec<u32>, usize> = HashMap::new();
+
+        for face_view in multi.faces() {
+            let face_idx = face_view.index();
+            let face = face_view.position_indices();
+
+            let mut sorted_face = face.to_vec();
+            sorted_face.sort();
+            if first_face_with_positions
+                .insert(sorted_face, face_idx)
+                .is_some()
+            {
+                report.duplicate_faces.push(face_idx);
+            }
+
+            if face_area(face, |idx| positions.attribute.data_at_idx(idx)) <= DEGENERATE_AREA {
+                report.degenerate_faces.push(face_idx);
+            }
+        }
+
+        for (edge, face_count) in Topology::new(self).edges() {
+            match face_count {
+                1 => report.boundary_edges.push(edge),
+                2 => {}
+                _ => report.non_manifold_edges.push(edge),
+            };
+        }
+        // Sorted so that the edges in the report are in a deterministic order
+        report.boundary_edges.sort_unstable();
+        report.non_manifold_edges.sort_unstable();
+
+        report
+    }
+}
+
+/// The area of a polygon, using Newell's method so that non planar and concave faces are handled.
+pub(crate) fn face_area<'a>(face: &[u32], position: impl Fn(u32) -> &'a [f32]) -> f32 {
+    let mut vector_area = Vector3::zeros();
+
+    for (idx, start) in face.iter().enumerate() {
+        let start = position(*start);
+        let end = position(face[(idx + 1) % face.len()]);
+
+        vector_area +=
+            Vector3::new(start[0], start[1], start[2]).cross(&Vector3::new(end[0], end[1], end[2]));
+    }
+
+    vector_area.norm() / 2.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that a closed mesh has no problems.
+    #[test]
+    fn watertight_cube() {
+        let report = BlenderMesh::cube(2.).topology_report();
+
+        assert!(report.is_watertight());
+        assert_eq!(report, TopologyReport::default());
+    }
+
+    /// Verify that we find the outline of a plane.
+    #[test]
+    fn plane_boundary() {
+        let report = BlenderMesh::plane(2., 2., 1).topology_report();
+
+        assert!(report.is_manifold());
+        assert!(!report.is_watertight());
+        assert_eq!(report.boundary_edges().len(), 8);
+        assert!(report.boundary_edges().contains(&[0, 1]));
+    }
+
+    /// Verify that we find duplicate faces, the non manifold edges that they cause and faces
+    /// without any area.
+    #[test]
+    fn duplicate_and_degenerate_faces() {
+        let mut mesh = BlenderMesh::plane(2., 2., 0);
+        let multi = &mut mesh.multi_indexed_vertex_attributes;
+
+        // The same quad with the opposite winding, followed by a triangle whose corners are on
+        // one line
+        multi.vertices_in_each_face.extend_from_slice(&[4, 3]);
+        multi
+            .positions
+            .indices
+            .extend_from_slice(&[2, 3, 1, 0, 0, 1, 4]);
+        multi
+            .positions
+            .attribute
+            .data
+            .extend_from_slice(&[0., -1., 0.]);
+
+        let report = mesh.topology_report();
+
+        assert_eq!(report.duplicate_faces(), &vec![1]);
+        assert_eq!(report.degenerate_faces(), &vec![2]);
+        assert_eq!(report.non_manifold_edges(), &vec![[0, 1]]);
+    }
+}
@@ -0,0 +1,316 @@
+use crate::BlenderMesh;
+use std::collections::HashMap;
+
+/// The connectivity of a mesh's faces, for algorithms that need to walk from a face or vertex to
+/// its neighbors.
+///
+/// Every face corner is a half edge that runs from the corner's vertex to the next corner's
+/// vertex in the same face, so half edges are identified by their corner's index. The half edges
+/// of neighboring faces that run along the same edge are twins.
+///
+/// Unlike a strict half edge structure, meshes straight from Blender are supported: edges that
+/// are shared by more than two faces are kept as non manifold edges without twins, and faces
+/// with inconsistent winding are still each other's neighbors.
+///
+/// Vertices are identified by their position index, the same as [`TopologyReport`].
+///
+/// ```
+/// # use blender_mesh::{BlenderMesh, Topology};
+/// let topology = Topology::new(&BlenderMesh::cube(1.));
+///
+/// assert!(topology.boundary_loops().is_empty());
+/// assert_eq!(topology.faces_around_vertex(0).len(), 3);
+/// assert_eq!(topology.one_ring(0).len(), 3);
+/// ```
+///
+/// [`TopologyReport`]: struct.TopologyReport.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Topology {
+    /// The vertex that each half edge starts at.
+    pub(crate) corner_vertices: Vec<u32>,
+    pub(crate) corner_faces: Vec<usize>,
+    pub(crate) next_corners: Vec<usize>,
+    pub(crate) previous_corners: Vec<usize>,
+    /// The edge that each half edge runs along, or `None` if both of its ends are the same
+    /// vertex.
+    pub(crate) corner_edges: Vec<Option<usize>>,
+    /// The two vertices at the ends of every edge, smallest first.
+    pub(crate) edges: Vec<[u32; 2]>,
+    /// The half edges that run along every edge, in either direction.
+    pub(crate) edge_corners: Vec<Vec<usize>>,
+    /// The half edges that start at every vertex.
+    pub(crate) vertex_corners: Vec<Vec<usize>>,
+    pub(crate) face_starts: Vec<usize>,
+}
+
+impl Topology {
+    /// Find the half edges and edges of every face of the mesh.
+    pub fn new(mesh: &BlenderMesh) -> Topology {
+        let multi = mesh.multi_indexed_vertex_attributes();
+        let corner_vertices = multi.positions.indices.clone();
+        let corner_count = corner_vertices.len();
+        let vertex_count = multi.positions.attribute.data.len() / 3;
+
+        let mut topology = Topology {
+            corner_faces: vec![0; corner_count],
+            next_corners: vec![0; corner_count],
+            previous_corners: vec![0; corner_count],
+            corner_edges: vec![None; corner_count],
+            edges: vec![],
+            edge_corners: vec![],
+            vertex_corners: vec![vec![]; vertex_count],
+            face_starts: vec![],
+            corner_vertices,
+        };
+
+        let mut edge_ids: HashMap<[u32; 2], usize> = HashMap::new();
+
+        for face in mesh.faces() {
+            topology.face_starts.push(face.corners().start);
+
+            for corner in face.corners() {
+                let next = match corner + 1 == face.corners().end {
+                    true => face.corners().start,
+                    false => corner + 1,
+                };
+                topology.corner_faces[corner] = face.index();
+                topology.next_corners[corner] = next;
+                topology.previous_corners[next] = corner;
+
+                let start = topology.corner_vertices[corner];
+                let end = topology.corner_vertices[next];
+                topology.vertex_corners[start as usize].push(corner);
+                if start == end {
+                    continue;
+                }
+
+                let edge = [start.min(end), start.max(end)];
+                let edge_id = *edge_ids.entry(edge).or_insert_with(|| {
+                    topology.edges.push(edge);
+                    topology.edge_corners.push(vec![]);
+                    topology.edges.len() - 1
+                });
+                topology.edge_corners[edge_id].push(corner);
+                topology.corner_edges[corner] = Some(edge_id);
+            }
+        }
+
+        topology
+    }
+
+    /// The number of faces.
+    pub fn face_count(&self) -> usize {
+        self.face_starts.len()
+    }
+
+    /// The half edge on the neighboring face that runs along the same edge, or `None` if the
+    /// edge is on a boundary or is shared by more than two faces.
+    pub fn twin(&self, corner: usize) -> Option<usize> {
+        let edge = &self.edge_corners[self.corner_edges[corner]?];
+
+        match edge.len() == 2 {
+            true => edge.iter().find(|other| **other != corner).cloned(),
+            false => None,
+        }
+    }
+
+    /// The half edge that follows this one around its face.
+    pub fn next(&self, corner: usize) -> usize {
+        self.next_corners[corner]
+    }
+
+    /// The half edge that comes before this one around its face.
+    pub fn previous(&self, corner: usize) -> usize {
+        self.previous_corners[corner]
+    }
+
+    /// The face that the half edge belongs to.
+    pub fn face(&self, corner: usize) -> usize {
+        self.corner_faces[corner]
+    }
+
+    /// The vertex that the half edge starts at.
+    pub fn vertex(&self, corner: usize) -> u32 {
+        self.corner_vertices[corner]
+    }
+
+    /// Whether the half edge is the only one along its edge, such as on the rim of a hole.
+    pub fn is_boundary(&self, corner: usize) -> bool {
+        self.corner_edges[corner].map(|edge| self.edge_corners[edge].len()) == Some(1)
+    }
+
+    /// The faces that use the vertex, sorted.
+    pub fn faces_around_vertex(&self, vertex: u32) -> Vec<usize> {
+        let mut faces: Vec<usize> = self.corners_at(vertex).map(|c| self.face(c)).collect();
+        faces.sort_unstable();
+        faces.dedup();
+        faces
+    }
+
+    /// The vertices that share an edge with the vertex, sorted.
+    pub fn one_ring(&self, vertex: u32) -> Vec<u32> {
+        let mut ring = vec![];
+        for corner in self.corners_at(vertex) {
+            ring.push(self.vertex(self.next(corner)));
+            ring.push(self.vertex(self.previous(corner)));
+        }
+
+        ring.retain(|neighbor| *neighbor != vertex);
+        ring.sort_unstable();
+        ring.dedup();
+        ring
+    }
+
+    /// The faces that share an edge with the face, including across non manifold edges, sorted.
+    pub fn adjacent_faces(&self, face: usize) -> Vec<usize> {
+        let start = self.face_starts[face];
+        let end = self
+            .face_starts
+            .get(face + 1)
+            .cloned()
+            .unwrap_or(self.corner_vertices.len());
+
+        let mut faces = vec![];
+        for edge in (start..end).filter_map(|corner| self.corner_edges[corner]) {
+            faces.extend(self.edge_corners[edge].iter().map(|c| self.face(*c)));
+        }
+
+        faces.retain(|other| *other != face);
+        faces.sort_unstable();
+        faces.dedup();
+        faces
+    }
+
+    /// Every edge, as the vertices at its ends with the smallest first, along with the number
+    /// of faces that share it.
+    pub fn edges(&self) -> impl Iterator<Item = ([u32; 2], usize)> + '_ {
+        self.edges
+            .iter()
+            .zip(self.edge_corners.iter())
+            .map(|(edge, corners)| (*edge, corners.len()))
+    }
+
+    /// The vertices around every hole or open edge of the mesh, in the order of their boundary
+    /// half edges.
+    ///
+    /// Boundaries that can't be followed back to where they started, such as where two
+    /// boundaries touch at a single vertex, end where they could no longer be followed.
+    pub fn boundary_loops(&self) -> Vec<Vec<u32>> {
+        let mut visited = vec![false; self.corner_vertices.len()];
+        let mut loops = vec![];
+
+        for first in 0..self.corner_vertices.len() {
+            if visited[first] || !self.is_boundary(first) {
+                continue;
+            }
+
+            let mut boundary = vec![];
+            let mut corner = first;
+            loop {
+                visited[corner] = true;
+                boundary.push(self.vertex(corner));
+
+                let end = self.vertex(self.next(corner));
+                let next = self
+                    .corners_at(end)
+                    .find(|next| !visited[*next] && self.is_boundary(*next));
+
+                match next {
+                    Some(next) => corner = next,
+                    None => break,
+                };
+            }
+
+            loops.push(boundary);
+        }
+
+        loops
+    }
+
+    /// Groups of faces that are connected to each other by their edges, such as the separate
+    /// pieces of a mesh that was joined from several objects. Each group is sorted.
+    pub fn connected_faces(&self) -> Vec<Vec<usize>> {
+        let mut component = vec![None; self.face_count()];
+        let mut components = vec![];
+
+        for first in 0..self.face_count() {
+            if component[first].is_some() {
+                continue;
+            }
+
+            let mut faces = vec![];
+            let mut stack = vec![first];
+            component[first] = Some(components.len());
+
+            while let Some(face) = stack.pop() {
+                faces.push(face);
+
+                for neighbor in self.adjacent_faces(face) {
+                    if component[neighbor].is_none() {
+                        component[neighbor] = Some(components.len());
+                        stack.push(neighbor);
+                    }
+                }
+            }
+
+            faces.sort_unstable();
+            components.push(faces);
+        }
+
+        components
+    }
+
+    fn corners_at(&self, vertex: u32) -> impl Iterator<Item = usize> + '_ {
+        self.vertex_corners
+            .get(vertex as usize)
+            .into_iter()
+            .flat_map(|corners| corners.iter().cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlenderMeshBuilder, FaceCorner};
+
+    /// Verify adjacency queries on a grid of two by two quads next to a separate triangle.
+    #[test]
+    fn grid_and_triangle() {
+        let mut builder = BlenderMeshBuilder::new("Grid");
+        for y in 0..3 {
+            for x in 0..3 {
+                builder.add_position([x as f32, y as f32, 0.]);
+            }
+        }
+        let corners = |positions: &[u32]| -> Vec<FaceCorner> {
+            positions
+                .iter()
+                .map(|position| FaceCorner {
+                    position: *position,
+                    ..FaceCorner::default()
+                })
+                .collect()
+        };
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)].iter() {
+            let corner = y * 3 + x;
+            builder.add_face(&corners(&[corner, corner + 1, corner + 4, corner + 3]), 0);
+        }
+        let triangle: Vec<u32> = (0..3)
+            .map(|idx| builder.add_position([5. + idx as f32, 0., 0.]))
+            .collect();
+        builder.add_face(&corners(&triangle), 0);
+
+        let topology = Topology::new(&builder.build().unwrap());
+
+        assert_eq!(topology.faces_around_vertex(4), vec![0, 1, 2, 3]);
+        assert_eq!(topology.one_ring(4), vec![1, 3, 5, 7]);
+        assert_eq!(topology.adjacent_faces(0), vec![1, 2]);
+        assert_eq!(topology.twin(1).map(|c| topology.face(c)), Some(1));
+        assert_eq!(topology.connected_faces(), vec![vec![0, 1, 2, 3], vec![4]]);
+
+        let loops = topology.boundary_loops();
+        assert_eq!(loops.len(), 2);
+        assert_eq!(loops[0], vec![0, 1, 2, 5, 8, 7, 6, 3]);
+        assert_eq!(loops[1], vec![9, 10, 11]);
+    }
+}
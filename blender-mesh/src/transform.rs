@@ -0,0 +1,121 @@
+use crate::simd;
+use crate::BlenderMesh;
+use nalgebra::{Matrix3, Matrix4, Point3};
+
+impl BlenderMesh {
+    /// The world transform of the object that the mesh was exported from, which places the
+    /// mesh's positions, offset by its [`SpaceConvention.method#origin`], in the scene.
+    ///
+    /// `None` if the mesh wasn't exported from an object, such as a procedurally generated mesh.
+    pub fn world_transform(&self) -> Option<&Matrix4<f64>> {
+        self.world_transform.as_ref()
+    }
+
+    /// See [`BlenderMesh.method#world_transform`]
+    pub fn set_world_transform(&mut self, world_transform: Option<Matrix4<f64>>) {
+        self.world_transform = world_transform;
+    }
+
+    /// Transform the positions, normals, shape keys and bounding box by a matrix, such as to
+    /// bake an object's world transform into its mesh.
+    ///
+    /// Normals are transformed by the inverse transpose of the matrix so that they stay
+    /// perpendicular to their faces when the matrix scales non-uniformly, and shape key deltas
+    /// are transformed without the matrix's translation. Matrices that reflect the mesh reverse
+    /// the winding order of every face, the same as [`BlenderMesh.method#mirror`].
+    ///
+    /// The matrix is expected to be affine.
+    pub fn apply_transform(&mut self, matrix: &Matrix4<f32>) {
+        let linear = Matrix3::from_fn(|row, column| matrix[(row, column)]);
+        let multi = &mut self.multi_indexed_vertex_attributes;
+
+        simd::transform_vec3s(matrix, &mut multi.positions.attribute.data, 1.);
+
+        if let Some(normals) = multi.normals.as_mut() {
+            let normal_matrix = linear
+                .try_inverse()
+                .unwrap_or(linear)
+                .transpose()
+                .to_homogeneous();
+
+            simd::transform_vec3s(&normal_matrix, &mut normals.attribute.data, 0.);
+            for normal in normals.attribute.data.chunks_exact_mut(3) {
+                let length =
+                    (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
+                if length > 0. {
+                    normal.iter_mut().for_each(|axis| *axis /= length);
+                }
+            }
+        }
+
+        for shape_key in self.shape_keys.iter_mut() {
+            simd::transform_vec3s(matrix, &mut shape_key.position_deltas.data, 0.);
+        }
+
+        let (min, max) = (self.bounding_box.min_corner, self.bounding_box.max_corner);
+        let mut corners = (0..8).map(|corner| {
+            let corner = Point3::new(
+                match corner & 1 == 0 {
+                    true => min.x,
+                    false => max.x,
+                },
+                match corner & 2 == 0 {
+                    true => min.y,
+                    false => max.y,
+                },
+                match corner & 4 == 0 {
+                    true => min.z,
+                    false => max.z,
+                },
+            );
+            matrix.transform_point(&corner)
+        });
+        let first = corners.next().unwrap();
+        let (min, max) = corners.fold((first, first), |(min, max), corner| {
+            (min.inf(&corner), max.sup(&corner))
+        });
+        self.bounding_box.min_corner = min;
+        self.bounding_box.max_corner = max;
+
+        if linear.determinant() < 0. {
+            self.reverse_face_winding();
+        }
+
+        self.invalidate_bvh();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector3;
+
+    /// Verify that positions are moved, normals stay unit length and perpendicular, and that a
+    /// reflection keeps faces facing outwards.
+    #[test]
+    fn apply_scale_and_translation() {
+        let mut plane = BlenderMesh::plane(2., 2., 0);
+        let indices = plane
+            .multi_indexed_vertex_attributes
+            .positions
+            .indices
+            .clone();
+
+        plane.apply_transform(
+            &(Matrix4::new_translation(&Vector3::new(0., 0., 5.))
+                * Matrix4::new_nonuniform_scaling(&Vector3::new(3., 1., -2.))),
+        );
+
+        let multi = &plane.multi_indexed_vertex_attributes;
+        assert_eq!(multi.positions.attribute.data_at_idx(3), &[3., 1., 5.][..]);
+        assert_eq!(
+            multi.normals.as_ref().unwrap().attribute.data_at_idx(0),
+            &[0., 0., -1.][..]
+        );
+        assert_eq!(plane.bounding_box.min_corner, Point3::new(-3., -1., 5.));
+        assert_eq!(plane.bounding_box.max_corner, Point3::new(3., 1., 5.));
+
+        let reversed: Vec<u32> = indices.iter().rev().copied().collect();
+        assert_eq!(multi.positions.indices, reversed);
+    }
+}
@@ -1,5 +1,61 @@
 use crate::BlenderMesh;
 impl BlenderMesh {
+    /// Split every quad into two triangles, along with the mesh's corner and face custom
+    /// attributes.
+    ///
+    /// Smoothing groups and face maps are updated to refer to the new triangles.
+    ///
+    /// See [`crate::MultiIndexedVertexAttributes::triangulate`]
+    ///
+    /// # Panics
+    ///
+    /// Panics if a face has more than 4 vertices.
+    pub fn triangulate_faces(&mut self) {
+        let vertices_in_each_face = self
+            .multi_indexed_vertex_attributes
+            .vertices_in_each_face
+            .clone();
+
+        self.multi_indexed_vertex_attributes.triangulate();
+        for attribute in self.custom_attributes.values_mut() {
+            attribute.triangulate(&vertices_in_each_face);
+        }
+
+        let mut smoothing_groups = Vec::with_capacity(self.smoothing_groups.len());
+        for (face, vertex_count) in vertices_in_each_face.iter().enumerate() {
+            let triangles = if *vertex_count == 4 { 2 } else { 1 };
+            if let Some(group) = self.smoothing_groups.get(face) {
+                for _ in 0..triangles {
+                    smoothing_groups.push(*group);
+                }
+            }
+        }
+        self.smoothing_groups = smoothing_groups;
+
+        // Face maps refer to faces by index, so point them at the triangles of each face
+        let mut first_triangle = Vec::with_capacity(vertices_in_each_face.len());
+        let mut triangle_count = 0;
+        for vertex_count in vertices_in_each_face.iter() {
+            first_triangle.push(triangle_count);
+            triangle_count += if *vertex_count == 4 { 2 } else { 1 };
+        }
+        for faces in self.face_maps.values_mut() {
+            let mut triangles = Vec::with_capacity(faces.len() * 2);
+            for face in faces.iter() {
+                if let Some(first) = first_triangle.get(*face as usize) {
+                    triangles.push(*first);
+                    if vertices_in_each_face[*face as usize] == 4 {
+                        triangles.push(*first + 1);
+                    }
+                }
+            }
+            *faces = triangles;
+        }
+
+        // Faces are renumbered, so a BVH built before triangulating points at the wrong faces
+        self.invalidate_bvh();
+    }
+
     /// When exporting a mesh from Blender, faces will usually have 4 vertices (quad) but some
     /// faces might have 3 (triangle).
     ///
@@ -12,45 +68,48 @@ impl BlenderMesh {
     ///
     /// Panics if a face has more than 4 vertices. In the future we might support 5+ vertices,
     /// but I haven't run into that yet. Not even sure if Blender can have faces with 5 vertices..
-    pub(crate) fn triangulate(&self, indices: &Vec<u16>) -> Vec<u16> {
-        let mut triangulated_position_indices = vec![];
-        let mut triangulated_face_vertex_counts = vec![];
+    pub(crate) fn triangulate<I: Copy>(&self, indices: &[I]) -> Vec<I> {
+        triangulate_indices(
+            &self.multi_indexed_vertex_attributes.vertices_in_each_face,
+            indices,
+        )
+    }
+}
 
-        let mut face_pointer = 0;
+/// Split the indices of every quad into the indices of two triangles.
+///
+/// Works with any index type so that both multi indexed attributes and single indexed vertex
+/// data can be triangulated.
+///
+/// # Panics
+///
+/// Panics if a face has more than 4 vertices.
+pub(crate) fn triangulate_indices<I: Copy>(vertices_in_each_face: &[u8], indices: &[I]) -> Vec<I> {
+    let mut triangulated_indices = vec![];
 
-        for num_verts_in_face in self
-            .multi_indexed_vertex_attributes
-            .vertices_in_each_face
-            .iter()
-        {
-            triangulated_position_indices.push(indices[face_pointer]);
-            triangulated_position_indices.push(indices[face_pointer + 1]);
-            triangulated_position_indices.push(indices[face_pointer + 2]);
-
-            triangulated_face_vertex_counts.push(3);
-
-            match num_verts_in_face {
-                &3 => {}
-                &4 => {
-                    triangulated_position_indices.push(indices[face_pointer]);
-                    triangulated_position_indices.push(indices[face_pointer + 2]);
-                    triangulated_position_indices.push(indices[face_pointer + 3]);
-
-                    triangulated_face_vertex_counts.push(3);
-                }
-                _ => {
-                    panic!("blender-mesh currently only supports triangulating faces with 3 or 4 vertices");
-                }
-            };
+    let mut face_pointer = 0;
 
-            face_pointer += *num_verts_in_face as usize;
-        }
+    for num_verts_in_face in vertices_in_each_face.iter() {
+        triangulated_indices.push(indices[face_pointer]);
+        triangulated_indices.push(indices[face_pointer + 1]);
+        triangulated_indices.push(indices[face_pointer + 2]);
 
-        // TODO: ? Set to Uniform(3)
-        // multi.vertices_in_each_face = triangulated_face_vertex_counts;
+        match num_verts_in_face {
+            &3 => {}
+            &4 => {
+                triangulated_indices.push(indices[face_pointer]);
+                triangulated_indices.push(indices[face_pointer + 2]);
+                triangulated_indices.push(indices[face_pointer + 3]);
+            }
+            _ => {
+                panic!("blender-mesh currently only supports triangulating faces with 3 or 4 vertices");
+            }
+        };
 
-        return triangulated_position_indices;
+        face_pointer += *num_verts_in_face as usize;
     }
+
+    triangulated_indices
 }
 
 #[cfg(test)]
@@ -75,10 +134,60 @@ mod tests {
             ..BlenderMesh::default()
         };
 
-        let triangulated_indices = start_mesh.triangulate(&vec![0, 1, 2, 3, 4, 5, 6, 7]);
+        let triangulated_indices = start_mesh.triangulate(&[0u16, 1, 2, 3, 4, 5, 6, 7]);
         assert_eq!(
             triangulated_indices,
             vec![0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7]
         );
     }
+
+    /// Verify that every indexed attribute is triangulated and that each triangle keeps the
+    /// material of its face.
+    #[test]
+    fn triangulate_indexed_attributes() {
+        let mut multi = MultiIndexedVertexAttributes {
+            vertices_in_each_face: vec![3, 4],
+            material_index: vec![1, 2],
+            positions: IndexedAttribute::new(
+                vec![0, 1, 2, 0, 2, 3, 4],
+                VertexAttribute::new(vec![0.; 15], 3).unwrap(),
+            ),
+            uvs: Some(IndexedAttribute::new(
+                vec![6, 5, 4, 3, 2, 1, 0],
+                VertexAttribute::new(vec![0.; 14], 2).unwrap(),
+            )),
+            ..MultiIndexedVertexAttributes::default()
+        };
+
+        multi.triangulate();
+
+        assert_eq!(multi.positions.indices(), &vec![0, 1, 2, 0, 2, 3, 0, 3, 4]);
+        assert_eq!(
+            multi.uvs.as_ref().unwrap().indices(),
+            &vec![6, 5, 4, 3, 2, 1, 3, 1, 0]
+        );
+        assert_eq!(multi.material_index, vec![1, 2, 2]);
+        assert_eq!(multi.vertices_in_each_face, vec![3, 3, 3]);
+    }
+
+    /// Verify that both triangles of a quad keep the smoothing group of the quad.
+    #[test]
+    fn triangulate_smoothing_groups() {
+        let mut mesh = BlenderMesh {
+            multi_indexed_vertex_attributes: MultiIndexedVertexAttributes {
+                positions: IndexedAttribute::new(
+                    vec![0, 1, 2, 0, 2, 3, 4],
+                    VertexAttribute::new(vec![0.; 15], 3).unwrap(),
+                ),
+                vertices_in_each_face: vec![3, 4],
+                ..MultiIndexedVertexAttributes::default()
+            },
+            smoothing_groups: vec![1, 2],
+            ..BlenderMesh::default()
+        };
+
+        mesh.triangulate_faces();
+
+        assert_eq!(mesh.smoothing_groups(), &vec![1, 2, 2]);
+    }
 }
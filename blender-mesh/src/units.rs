@@ -0,0 +1,204 @@
+use crate::BlenderMesh;
+
+/// The unit settings of the Blender scene that an asset was exported from.
+///
+/// Blender stores positions in Blender units. With the metric or imperial unit system one
+/// Blender unit is `scale_length` meters.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneUnits {
+    /// The scene's unit system.
+    pub system: UnitSystem,
+    /// How many meters one Blender unit is, unless the unit system is [`UnitSystem::None`].
+    ///
+    /// [`UnitSystem::None`]: enum.UnitSystem.html#variant.None
+    pub scale_length: f32,
+}
+
+/// Blender's unit systems.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum UnitSystem {
+    /// Blender units without any real world size.
+    None,
+    /// Meters, which is Blender's default.
+    #[default]
+    Metric,
+    /// Feet and inches.
+    Imperial,
+}
+
+/// Which axis points up.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum UpAxis {
+    /// Y up, such as after [`BlenderMesh.method#y_up`].
+    Y,
+    /// Z up, which is how Blender exports meshes.
+    #[default]
+    Z,
+}
+
+/// Which corner of a texture a uv of `[0, 0]` samples.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum UvOrigin {
+    /// The bottom left corner, with V pointing up, which is how Blender and OpenGL sample
+    /// textures.
+    #[default]
+    BottomLeft,
+    /// The top left corner, with V pointing down, which is how Direct3D, Metal and Vulkan sample
+    /// textures. See [`BlenderMesh.method#flip_uv_v`].
+    TopLeft,
+}
+
+/// How a mesh's positions and uvs relate to the Blender scene that it was exported from.
+///
+/// The functions that convert positions and uvs, such as [`BlenderMesh.method#y_up`],
+/// [`BlenderMesh.method#scale`] and [`BlenderMesh.method#flip_uv_v`], update it. This lets consumers tell which conversions have
+/// already been applied, such as to catch a mesh that was converted to Y up twice.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpaceConvention {
+    #[serde(default)]
+    pub(crate) scene_units: SceneUnits,
+    #[serde(default)]
+    pub(crate) up_axis: UpAxis,
+    #[serde(default = "one")]
+    pub(crate) scale_factor: f32,
+    #[serde(default)]
+    pub(crate) uv_origin: UvOrigin,
+    #[serde(default)]
+    pub(crate) origin: [f64; 3],
+}
+
+impl Default for SceneUnits {
+    /// Blender's default of one meter per Blender unit.
+    fn default() -> Self {
+        SceneUnits {
+            system: UnitSystem::Metric,
+            scale_length: 1.,
+        }
+    }
+}
+
+impl Default for SpaceConvention {
+    /// A Z up mesh in Blender's default units that hasn't been scaled since it was exported.
+    fn default() -> Self {
+        SpaceConvention {
+            scene_units: SceneUnits::default(),
+            up_axis: UpAxis::Z,
+            scale_factor: 1.,
+            uv_origin: UvOrigin::BottomLeft,
+            origin: [0., 0., 0.],
+        }
+    }
+}
+
+impl SpaceConvention {
+    /// The unit settings of the scene that the mesh was exported from.
+    pub fn scene_units(&self) -> SceneUnits {
+        self.scene_units
+    }
+
+    /// Which axis the mesh's positions are pointing up along.
+    pub fn up_axis(&self) -> UpAxis {
+        self.up_axis
+    }
+
+    /// Which corner of a texture the mesh's uvs treat as their origin.
+    pub fn uv_origin(&self) -> UvOrigin {
+        self.uv_origin
+    }
+
+    /// Where the positions are relative to, in double precision. Zero unless the mesh was moved
+    /// with [`BlenderMesh.method#rebase_origin`] or [`BlenderMesh.method#apply_transform_f64`].
+    pub fn origin(&self) -> [f64; 3] {
+        self.origin
+    }
+
+    /// Everything that the positions have been scaled by since they were exported.
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    /// How many meters one unit of the mesh's positions is, or `None` if the scene's unit system
+    /// is [`UnitSystem::None`].
+    ///
+    /// [`UnitSystem::None`]: enum.UnitSystem.html#variant.None
+    pub fn meters_per_unit(&self) -> Option<f32> {
+        match self.scene_units.system {
+            UnitSystem::None => None,
+            UnitSystem::Metric | UnitSystem::Imperial => {
+                Some(self.scene_units.scale_length / self.scale_factor)
+            }
+        }
+    }
+}
+
+fn one() -> f32 {
+    1.
+}
+
+impl BlenderMesh {
+    /// The units and axis convention of the mesh's positions.
+    pub fn space(&self) -> SpaceConvention {
+        self.space
+    }
+
+    /// Scale the positions, shape keys and bounding box, such as by
+    /// [`SpaceConvention.method#meters_per_unit`] in order to convert them to meters.
+    pub fn scale(&mut self, factor: f32) {
+        let multi = &mut self.multi_indexed_vertex_attributes;
+        for position in multi.positions.attribute.data.iter_mut() {
+            *position *= factor;
+        }
+
+        for shape_key in self.shape_keys.iter_mut() {
+            for delta in shape_key.position_deltas.data.iter_mut() {
+                *delta *= factor;
+            }
+        }
+
+        let (min, max) = (self.bounding_box.min_corner, self.bounding_box.max_corner);
+        self.bounding_box.min_corner = (min.coords * factor).into();
+        self.bounding_box.max_corner = (max.coords * factor).into();
+        if factor < 0. {
+            std::mem::swap(
+                &mut self.bounding_box.min_corner,
+                &mut self.bounding_box.max_corner,
+            );
+        }
+
+        self.space.scale_factor *= factor;
+        for axis in self.space.origin.iter_mut() {
+            *axis *= factor as f64;
+        }
+        // Scaling the scene only moves where objects are, not how they are rotated or scaled
+        if let Some(world_transform) = self.world_transform.as_mut() {
+            for row in 0..3 {
+                world_transform[(row, 3)] *= factor as f64;
+            }
+        }
+
+        self.invalidate_bvh();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that converting a mesh records the conversion so that it isn't applied twice.
+    #[test]
+    fn conversions_update_space() {
+        let mut json = serde_json::to_value(BlenderMesh::cube(1.)).unwrap();
+        json["space"]["scene_units"]["scale_length"] = 0.01.into();
+        let mut cube: BlenderMesh = serde_json::from_value(json).unwrap();
+        assert_eq!(cube.space().up_axis(), UpAxis::Z);
+        assert_eq!(cube.space().meters_per_unit(), Some(0.01));
+
+        cube.y_up();
+        cube.scale(cube.space().meters_per_unit().unwrap());
+
+        assert_eq!(cube.space().up_axis(), UpAxis::Y);
+        assert_eq!(cube.space().scale_factor(), 0.01);
+        assert_eq!(cube.space().meters_per_unit(), Some(1.));
+        assert_eq!(cube.bounding_box().max_corner.x, 0.005);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{BlenderMesh, UvOrigin, UvTransform};
+
+impl BlenderMesh {
+    /// Flip the V of every uv and lightmap uv, for engines whose textures start at the top left
+    /// corner instead of the bottom left corner like Blender's.
+    ///
+    /// The materials' uv transforms are flipped along with the uvs so that they move the flipped
+    /// uvs the same way. Flipping again undoes the flip. See [`SpaceConvention.method#uv_origin`]
+    /// to tell whether the uvs have already been flipped.
+    pub fn flip_uv_v(&mut self) {
+        let multi = &mut self.multi_indexed_vertex_attributes;
+
+        for uvs in multi.uvs.iter_mut().chain(multi.lightmap_uvs.iter_mut()) {
+            for uv in uvs.attribute.iter_vertices_mut() {
+                uv[1] = 1. - uv[1];
+            }
+        }
+
+        for material in self.materials.iter_mut() {
+            for uv_transform in material.uv_transforms.values_mut() {
+                *uv_transform = uv_transform.flip_v();
+            }
+        }
+
+        self.space.uv_origin = match self.space.uv_origin {
+            UvOrigin::BottomLeft => UvOrigin::TopLeft,
+            UvOrigin::TopLeft => UvOrigin::BottomLeft,
+        };
+    }
+}
+
+impl UvTransform {
+    /// The transform that moves flipped uvs the same way that this transform moves uvs.
+    fn flip_v(&self) -> UvTransform {
+        let (sin, cos) = self.rotation.sin_cos();
+        let [x, y] = self.translation;
+
+        UvTransform {
+            translation: [x - sin * self.scale[1], 1. - y - cos * self.scale[1]],
+            rotation: -self.rotation,
+            scale: self.scale,
+            inverse: self.inverse,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that flipped uv transforms move flipped uvs the same way that they moved the uvs,
+    /// and that flipping twice undoes the flip.
+    #[test]
+    fn flip_uvs_and_uv_transforms() {
+        let flip = |uv: [f32; 2]| [uv[0], 1. - uv[1]];
+
+        for inverse in [false, true].iter() {
+            let transform = UvTransform {
+                translation: [0.2, -0.4],
+                rotation: 0.7,
+                scale: [2., 3.],
+                inverse: *inverse,
+            };
+
+            let expected = flip(transform.apply([0.3, 0.6]));
+            let flipped = transform.flip_v().apply(flip([0.3, 0.6]));
+            assert!((flipped[0] - expected[0]).abs() < 1e-5);
+            assert!((flipped[1] - expected[1]).abs() < 1e-5);
+        }
+
+        let mut plane = BlenderMesh::plane(2., 2., 0);
+        let uvs = plane.multi_indexed_vertex_attributes.uvs.clone();
+
+        plane.flip_uv_v();
+        assert_eq!(plane.space().uv_origin(), UvOrigin::TopLeft);
+        assert_ne!(plane.multi_indexed_vertex_attributes.uvs, uvs);
+
+        plane.flip_uv_v();
+        assert_eq!(plane.space().uv_origin(), UvOrigin::BottomLeft);
+        assert_eq!(plane.multi_indexed_vertex_attributes.uvs, uvs);
+    }
+}
@@ -0,0 +1,490 @@
+use crate::topology::face_area;
+use crate::vertex_attributes::IndexedAttribute;
+use crate::{BlenderMesh, UvTransform};
+use nalgebra::{Matrix3, Point2, Vector2};
+use std::collections::HashMap;
+
+/// One of the uv maps that a mesh can have.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UvLayer {
+    /// The uvs that textures are sampled with.
+    Uv,
+    /// The uvs that light is baked into, from the uv map whose name contains `lightmap`.
+    Lightmap,
+}
+
+/// A group of faces that are connected in uv space, also known as a chart.
+///
+/// Corners are indices into the mesh's face corners, the same as indices into the position or
+/// uv indices, so the corners of an edge can be used to look up both its positions and its uvs.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UvIsland {
+    pub(crate) faces: Vec<usize>,
+    pub(crate) boundary_edges: Vec<UvBoundaryEdge>,
+    pub(crate) uv_min: [f32; 2],
+    pub(crate) uv_max: [f32; 2],
+    pub(crate) uv_area: f32,
+    pub(crate) surface_area: f32,
+}
+
+/// An edge along the outline of a uv island.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UvBoundaryEdge {
+    pub(crate) corners: [usize; 2],
+    pub(crate) seam: Option<[usize; 2]>,
+}
+
+impl UvIsland {
+    /// The indices of the faces in the island, in ascending order.
+    pub fn faces(&self) -> &Vec<usize> {
+        &self.faces
+    }
+
+    /// The edges that the island doesn't share with any of its other faces.
+    pub fn boundary_edges(&self) -> &Vec<UvBoundaryEdge> {
+        &self.boundary_edges
+    }
+
+    /// The smallest and largest uv coordinates in the island.
+    pub fn uv_bounds(&self) -> ([f32; 2], [f32; 2]) {
+        (self.uv_min, self.uv_max)
+    }
+
+    /// The area that the island covers in uv space.
+    pub fn uv_area(&self) -> f32 {
+        self.uv_area
+    }
+
+    /// The middle of the island's uv bounds, which islands are rotated and scaled around by
+    /// [`BlenderMesh.method#transform_uv_island`].
+    pub fn uv_center(&self) -> [f32; 2] {
+        [
+            (self.uv_min[0] + self.uv_max[0]) / 2.,
+            (self.uv_min[1] + self.uv_max[1]) / 2.,
+        ]
+    }
+
+    /// The area of the island's faces in object space.
+    pub fn surface_area(&self) -> f32 {
+        self.surface_area
+    }
+
+    /// How many object space units one uv unit covers, on average across the island.
+    ///
+    /// Light bakers can scale islands by this when packing them so that every island gets the
+    /// same texel density.
+    ///
+    /// None if the island has no area in uv space.
+    pub fn world_units_per_uv(&self) -> Option<f32> {
+        match self.uv_area > 0. {
+            true => Some((self.surface_area / self.uv_area).sqrt()),
+            false => None,
+        }
+    }
+}
+
+impl UvBoundaryEdge {
+    /// The corners at the start and end of the edge, following the winding of its face.
+    pub fn corners(&self) -> [usize; 2] {
+        self.corners
+    }
+
+    /// The corners of a face on the other side of the edge that uses different uvs, in the same
+    /// order of positions as [`UvBoundaryEdge.method#corners`].
+    ///
+    /// Bakers sample across seams so that lighting doesn't show a line where the uvs split.
+    ///
+    /// None if the edge is on the outline of the mesh.
+    pub fn seam(&self) -> Option<[usize; 2]> {
+        self.seam
+    }
+}
+
+/// A face's edge, along with the corners at its ends.
+#[derive(Debug, Copy, Clone)]
+struct FaceEdge {
+    face: usize,
+    corners: [usize; 2],
+}
+
+impl BlenderMesh {
+    /// Group the faces into the islands that they form in one of the uv maps.
+    ///
+    /// Two faces are in the same island if they share an edge and the uvs at both ends of the
+    /// edge are the same in both faces. Islands are ordered by their first face.
+    ///
+    /// Returns an empty Vec if the mesh doesn't have the uv map.
+    pub fn uv_islands(&self, layer: UvLayer) -> Vec<UvIsland> {
+        let multi = &self.multi_indexed_vertex_attributes;
+        let uvs = match layer {
+            UvLayer::Uv => multi.uvs.as_ref(),
+            UvLayer::Lightmap => multi.lightmap_uvs.as_ref(),
+        };
+        let uvs = match uvs {
+            Some(uvs) => uvs,
+            None => return vec![],
+        };
+        let positions = &multi.positions.indices;
+
+        let mut face_starts = Vec::with_capacity(multi.vertices_in_each_face.len());
+        let mut edges: HashMap<[u32; 2], Vec<FaceEdge>> = HashMap::new();
+
+        let mut corner = 0;
+        for (face, vertex_count) in multi.vertices_in_each_face.iter().enumerate() {
+            let vertex_count = *vertex_count as usize;
+            face_starts.push(corner);
+
+            for idx in 0..vertex_count {
+                let corners = [corner + idx, corner + (idx + 1) % vertex_count];
+                let (start, end) = (positions[corners[0]], positions[corners[1]]);
+
+                edges
+                    .entry([start.min(end), start.max(end)])
+                    .or_default()
+                    .push(FaceEdge { face, corners });
+            }
+
+            corner += vertex_count;
+        }
+
+        let mut island_of_face: Vec<usize> = (0..face_starts.len()).collect();
+        let mut boundary_edges = vec![];
+
+        for face_edges in edges.values() {
+            for edge in face_edges.iter() {
+                let mut connected = false;
+                let mut seam = None;
+
+                for other in face_edges.iter() {
+                    if other.face == edge.face && other.corners == edge.corners {
+                        continue;
+                    }
+
+                    let other_corners = match positions[other.corners[0]] {
+                        position if position == positions[edge.corners[0]] => other.corners,
+                        _ => [other.corners[1], other.corners[0]],
+                    };
+
+                    if same_uvs(uvs, edge.corners, other_corners) {
+                        union(&mut island_of_face, edge.face, other.face);
+                        connected = true;
+                    } else if seam.is_none() {
+                        seam = Some(other_corners);
+                    }
+                }
+
+                if !connected {
+                    boundary_edges.push((
+                        edge.face,
+                        UvBoundaryEdge {
+                            corners: edge.corners,
+                            seam,
+                        },
+                    ));
+                }
+            }
+        }
+
+        let mut islands: Vec<UvIsland> = vec![];
+        let mut island_indices: HashMap<usize, usize> = HashMap::new();
+
+        for (face, start) in face_starts.iter().enumerate() {
+            let root = find(&mut island_of_face, face);
+            let island_idx = *island_indices.entry(root).or_insert_with(|| {
+                islands.push(UvIsland {
+                    faces: vec![],
+                    boundary_edges: vec![],
+                    uv_min: [f32::INFINITY; 2],
+                    uv_max: [f32::NEG_INFINITY; 2],
+                    uv_area: 0.,
+                    surface_area: 0.,
+                });
+                islands.len() - 1
+            });
+            let island = &mut islands[island_idx];
+
+            let corners = *start..start + multi.vertices_in_each_face[face] as usize;
+
+            for corner in corners.clone() {
+                let uv = uvs.data_at_corner(corner);
+                island.uv_min = [island.uv_min[0].min(uv[0]), island.uv_min[1].min(uv[1])];
+                island.uv_max = [island.uv_max[0].max(uv[0]), island.uv_max[1].max(uv[1])];
+            }
+
+            island.uv_area += uv_face_area(uvs, corners.clone());
+            island.surface_area += face_area(&positions[corners], |idx| {
+                multi.positions.attribute.data_at_idx(idx)
+            });
+            island.faces.push(face);
+        }
+
+        // Sorted so that the edges are in a deterministic order
+        boundary_edges.sort_by_key(|(_, edge)| edge.corners);
+        for (face, edge) in boundary_edges {
+            let root = find(&mut island_of_face, face);
+            islands[island_indices[&root]].boundary_edges.push(edge);
+        }
+
+        islands
+    }
+
+    /// Scale, rotate and then move the uvs of one island, such as when packing islands into an
+    /// atlas or evening out texel density across a lightmap.
+    ///
+    /// The transform is applied as if the island's [`UvIsland.method#uv_center`] were the origin,
+    /// so scaling and rotating keep the island in place and the translation moves it. The
+    /// island's bounds and uv area are updated to match.
+    ///
+    /// Uvs that the island shares with faces outside of it, such as when islands are stacked on
+    /// top of each other to share texels, are copied first so that the other faces don't move.
+    ///
+    /// Does nothing if the mesh doesn't have the uv map.
+    pub fn transform_uv_island(
+        &mut self,
+        layer: UvLayer,
+        island: &mut UvIsland,
+        transform: &UvTransform,
+    ) {
+        let face_count = self
+            .multi_indexed_vertex_attributes
+            .vertices_in_each_face
+            .len();
+        let mut in_island = vec![false; face_count];
+        for face in island.faces.iter() {
+            in_island[*face] = true;
+        }
+
+        let mut island_corners = vec![];
+        let mut outside_corners = vec![];
+        for face in self.faces() {
+            match in_island[face.index()] {
+                true => island_corners.extend(face.corners()),
+                false => outside_corners.extend(face.corners()),
+            };
+        }
+
+        let multi = &mut self.multi_indexed_vertex_attributes;
+        let uvs = match layer {
+            UvLayer::Uv => multi.uvs.as_mut(),
+            UvLayer::Lightmap => multi.lightmap_uvs.as_mut(),
+        };
+        let uvs = match uvs {
+            Some(uvs) => uvs,
+            None => return,
+        };
+
+        let mut used_outside = vec![false; uvs.attribute.data.len() / 2];
+        for corner in outside_corners {
+            used_outside[uvs.indices[corner] as usize] = true;
+        }
+
+        let center = Vector2::from(island.uv_center());
+        let matrix = Matrix3::new_translation(&center)
+            * transform.matrix()
+            * Matrix3::new_translation(&-center);
+
+        // Island uv indices to the index of their transformed uvs
+        let mut transformed: HashMap<u32, u32> = HashMap::new();
+        island.uv_min = [f32::INFINITY; 2];
+        island.uv_max = [f32::NEG_INFINITY; 2];
+
+        for corner in island_corners {
+            let idx = uvs.indices[corner];
+            let new_idx = *transformed.entry(idx).or_insert_with(|| {
+                let uv = uvs.attribute.data_at_idx(idx);
+                let uv = matrix.transform_point(&Point2::new(uv[0], uv[1]));
+
+                match used_outside[idx as usize] {
+                    true => {
+                        uvs.attribute.data.extend_from_slice(&[uv.x, uv.y]);
+                        (uvs.attribute.data.len() / 2 - 1) as u32
+                    }
+                    false => {
+                        let start = idx as usize * 2;
+                        uvs.attribute.data[start..start + 2].copy_from_slice(&[uv.x, uv.y]);
+                        idx
+                    }
+                }
+            });
+            uvs.indices[corner] = new_idx;
+
+            let uv = uvs.attribute.data_at_idx(new_idx);
+            island.uv_min = [island.uv_min[0].min(uv[0]), island.uv_min[1].min(uv[1])];
+            island.uv_max = [island.uv_max[0].max(uv[0]), island.uv_max[1].max(uv[1])];
+        }
+
+        // The bottom row of a homogeneous 2d transform is (0, 0, 1), so this is how much the
+        // transform scales areas by
+        island.uv_area *= matrix.determinant().abs();
+    }
+}
+
+fn same_uvs(uvs: &IndexedAttribute, corners: [usize; 2], other_corners: [usize; 2]) -> bool {
+    corners
+        .iter()
+        .zip(other_corners.iter())
+        .all(|(corner, other)| uvs.data_at_corner(*corner) == uvs.data_at_corner(*other))
+}
+
+/// The area of a face in uv space, using the shoelace formula.
+pub(crate) fn uv_face_area(uvs: &IndexedAttribute, corners: std::ops::Range<usize>) -> f32 {
+    let (first, count) = (corners.start, corners.len());
+
+    let twice_area: f32 = corners
+        .map(|corner| {
+            let start = uvs.data_at_corner(corner);
+            let end = uvs.data_at_corner(first + (corner - first + 1) % count);
+
+            start[0] * end[1] - end[0] * start[1]
+        })
+        .sum();
+
+    twice_area.abs() / 2.
+}
+
+fn find(parents: &mut [usize], face: usize) -> usize {
+    let mut root = face;
+    while parents[root] != root {
+        root = parents[root];
+    }
+
+    // Point everything along the way straight at the root so that later finds are fast
+    let mut face = face;
+    while parents[face] != root {
+        let parent = parents[face];
+        parents[face] = root;
+        face = parent;
+    }
+
+    root
+}
+
+fn union(parents: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parents, a), find(parents, b));
+
+    // The smaller face becomes the root so that islands are ordered by their first face
+    parents[a.max(b)] = a.min(b);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vertex_attributes::VertexAttribute;
+    use crate::{BlenderMeshBuilder, FaceCorner};
+
+    /// Verify that the faces of a plane form one island with the plane's outline as its
+    /// boundary.
+    #[test]
+    fn plane_is_one_island() {
+        let plane = BlenderMesh::plane(2., 2., 1);
+
+        let islands = plane.uv_islands(UvLayer::Uv);
+
+        assert_eq!(islands.len(), 1);
+        assert_eq!(islands[0].faces(), &vec![0, 1, 2, 3]);
+        assert_eq!(islands[0].boundary_edges().len(), 8);
+        assert!(islands[0]
+            .boundary_edges()
+            .iter()
+            .all(|edge| edge.seam().is_none()));
+        assert_eq!(islands[0].uv_bounds(), ([0., 0.], [1., 1.]));
+        assert_eq!(islands[0].world_units_per_uv(), Some(2.));
+
+        assert_eq!(plane.uv_islands(UvLayer::Lightmap), vec![]);
+    }
+
+    /// Verify that faces that share positions but not uvs are split into islands along a seam.
+    #[test]
+    fn lightmap_islands_split_at_seams() {
+        // Faces 0 and 2 are the left column and faces 1 and 3 are the right column
+        let mut plane = BlenderMesh::plane(2., 2., 1);
+        let multi = &mut plane.multi_indexed_vertex_attributes;
+
+        // Every corner gets its own uv, with the right column moved off to the side
+        let mut lightmap = vec![];
+        for corner in 0..16 {
+            let uv = multi.uvs.as_ref().unwrap().data_at_corner(corner);
+            let offset = if (corner / 4) % 2 == 1 { 0.5 } else { 0. };
+            lightmap.extend_from_slice(&[uv[0] + offset, uv[1]]);
+        }
+        multi.lightmap_uvs = Some(IndexedAttribute::new(
+            (0..16).collect(),
+            VertexAttribute::new(lightmap, 2).unwrap(),
+        ));
+
+        let islands = plane.uv_islands(UvLayer::Lightmap);
+
+        assert_eq!(islands.len(), 2);
+        assert_eq!(islands[0].faces(), &vec![0, 2]);
+        assert_eq!(islands[1].faces(), &vec![1, 3]);
+
+        assert_eq!(islands[1].uv_bounds(), ([1., 0.], [1.5, 1.]));
+        assert_eq!(islands[1].uv_area(), 0.5);
+        assert_eq!(islands[1].surface_area(), 2.);
+        assert_eq!(islands[1].world_units_per_uv(), Some(2.));
+
+        for island in islands.iter() {
+            assert_eq!(island.boundary_edges().len(), 6);
+
+            let seams: Vec<&UvBoundaryEdge> = island
+                .boundary_edges()
+                .iter()
+                .filter(|edge| edge.seam().is_some())
+                .collect();
+            assert_eq!(seams.len(), 2);
+
+            // The other side of the seam is in the other island and uses the same positions
+            let positions = plane.multi_indexed_vertex_attributes.positions.indices();
+            for edge in seams {
+                let [start, end] = edge.seam().unwrap();
+                assert!(!island.faces().contains(&(start / 4)));
+                assert_eq!(positions[start], positions[edge.corners()[0]]);
+                assert_eq!(positions[end], positions[edge.corners()[1]]);
+            }
+        }
+    }
+
+    /// Verify that transforming an island that is stacked on another island's uvs moves only
+    /// its own uvs and updates its bounds and area.
+    #[test]
+    fn transform_stacked_island() {
+        let mut builder = BlenderMeshBuilder::new("Stacked");
+        let uvs: Vec<u32> = [[0., 0.], [1., 0.], [1., 1.], [0., 1.]]
+            .iter()
+            .map(|uv| builder.add_uv(*uv))
+            .collect();
+        for offset in [0., 5.].iter() {
+            let corners: Vec<FaceCorner> = [[0., 0.], [1., 0.], [1., 1.], [0., 1.]]
+                .iter()
+                .zip(uvs.iter())
+                .map(|(position, uv)| FaceCorner {
+                    position: builder.add_position([position[0] + offset, position[1], 0.]),
+                    uv: Some(*uv),
+                    ..FaceCorner::default()
+                })
+                .collect();
+            builder.add_face(&corners, 0);
+        }
+        let mut mesh = builder.build().unwrap();
+
+        let mut islands = mesh.uv_islands(UvLayer::Uv);
+        assert_eq!(islands.len(), 2);
+
+        let transform = UvTransform {
+            translation: [2., 0.],
+            scale: [0.5, 0.5],
+            ..UvTransform::default()
+        };
+        mesh.transform_uv_island(UvLayer::Uv, &mut islands[1], &transform);
+
+        assert_eq!(islands[1].uv_bounds(), ([2.25, 0.25], [2.75, 0.75]));
+        assert_eq!(islands[1].uv_area(), 0.25);
+        assert_eq!(islands[1], mesh.uv_islands(UvLayer::Uv)[1]);
+
+        let uvs = mesh.multi_indexed_vertex_attributes.uvs.as_ref().unwrap();
+        assert_eq!(uvs.indices()[..4], [0, 1, 2, 3]);
+        assert_eq!(uvs.indices()[4..], [4, 5, 6, 7]);
+        assert_eq!(uvs.data_at_corner(2), &[1., 1.]);
+        assert_eq!(uvs.data_at_corner(6), &[2.75, 0.75]);
+    }
+}
@@ -0,0 +1,430 @@
+//! Texture space queries, such as finding where on a mesh a texel gets painted or which uv a
+//! decal that was projected onto the mesh lands on.
+
+use crate::{BlenderMesh, IndexedAttribute, UvLayer};
+use nalgebra::Point3;
+use std::cmp::Ordering;
+
+/// Leaves get split until they hold this many triangles or fewer.
+const MAX_TRIANGLES_PER_LEAF: usize = 4;
+
+/// How far outside of a triangle a point can be, in barycentric coordinates, and still be treated
+/// as inside of it, so that points on the shared edge of two triangles always hit one of them.
+const BARYCENTRIC_EPSILON: f32 = 1e-5;
+
+/// A bounding volume hierarchy over the triangles of one of a mesh's uv layers.
+///
+/// Faces with more than three vertices are fan triangulated, the same as in a [`Bvh`].
+///
+/// Build one with [`BlenderMesh.method#uv_bvh`] when making many queries, such as once per
+/// brush stroke in a texture painting tool.
+///
+/// [`Bvh`]: struct.Bvh.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct UvBvh {
+    nodes: Vec<UvBvhNode>,
+    triangles: Vec<UvTriangle>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+struct UvBvhNode {
+    min: [f32; 2],
+    max: [f32; 2],
+    /// For leaf nodes this is the index of the leaf's first triangle.
+    /// For interior nodes this is the index of the left child. The right child comes right after.
+    first: u32,
+    /// The number of triangles in a leaf node. Always 0 for interior nodes.
+    triangle_count: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct UvTriangle {
+    corners: [u32; 3],
+    uvs: [[f32; 2]; 3],
+    face: u32,
+}
+
+/// The triangle that a uv coordinate lands on.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct UvHit {
+    /// The index of the face that the triangle belongs to
+    pub face: usize,
+    /// The triangle's face corners, the same as indices into the position or uv indices
+    pub corners: [usize; 3],
+    /// The weight of each of the triangle's corners at the uv coordinate. Sums to 1.
+    pub barycentric: [f32; 3],
+}
+
+impl UvBvh {
+    /// Build a BVH over every face in one of a mesh's uv layers, or None if the mesh does not
+    /// have that layer.
+    pub fn new(mesh: &BlenderMesh, layer: UvLayer) -> Option<UvBvh> {
+        let multi = &mesh.multi_indexed_vertex_attributes;
+        let uvs = uv_layer(mesh, layer)?;
+
+        let uv = |corner: u32| {
+            let uv = uvs.attribute.data_at_idx(uvs.indices[corner as usize]);
+            [uv[0], uv[1]]
+        };
+
+        let mut triangles = vec![];
+        let mut face_start = 0;
+
+        for (face, vertex_count) in multi.vertices_in_each_face.iter().enumerate() {
+            let vertex_count = *vertex_count as usize;
+            let first = face_start as u32;
+
+            for corner in 1..vertex_count.saturating_sub(1) {
+                let corners = [first, first + corner as u32, first + corner as u32 + 1];
+                let triangle = UvTriangle {
+                    corners,
+                    uvs: [uv(corners[0]), uv(corners[1]), uv(corners[2])],
+                    face: face as u32,
+                };
+
+                triangles.push(triangle);
+            }
+
+            face_start += vertex_count;
+        }
+
+        let mut nodes = vec![];
+        if !triangles.is_empty() {
+            nodes.push(UvBvhNode::default());
+            subdivide(&mut nodes, 0, &mut triangles, 0);
+        }
+
+        Some(UvBvh { nodes, triangles })
+    }
+
+    /// Find the triangle that a uv coordinate lands on.
+    ///
+    /// Returns None if no triangle covers the coordinate. If uv islands overlap, such as for
+    /// mirrored halves that share a texture, any of the overlapping triangles may be returned.
+    pub fn triangle_at(&self, u: f32, v: f32) -> Option<UvHit> {
+        let point = [u, v];
+
+        let mut stack = vec![];
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(node_idx) = stack.pop() {
+            let node = &self.nodes[node_idx];
+
+            let outside =
+                (0..2).any(|axis| point[axis] < node.min[axis] || point[axis] > node.max[axis]);
+            if outside {
+                continue;
+            }
+
+            if node.triangle_count == 0 {
+                stack.push(node.first as usize);
+                stack.push(node.first as usize + 1);
+                continue;
+            }
+
+            let first = node.first as usize;
+            for triangle in self.triangles[first..first + node.triangle_count as usize].iter() {
+                let [a, b, c] = triangle.uvs;
+                let barycentric = match barycentric_2d(point, a, b, c) {
+                    Some(barycentric) => barycentric,
+                    None => continue,
+                };
+
+                if barycentric
+                    .iter()
+                    .all(|weight| *weight >= -BARYCENTRIC_EPSILON)
+                {
+                    return Some(UvHit {
+                        face: triangle.face as usize,
+                        corners: [
+                            triangle.corners[0] as usize,
+                            triangle.corners[1] as usize,
+                            triangle.corners[2] as usize,
+                        ],
+                        barycentric,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl BlenderMesh {
+    /// A bounding volume hierarchy over one of this mesh's uv layers, or None if the mesh does
+    /// not have that layer.
+    ///
+    /// Unlike [`BlenderMesh.method#bvh`] it is not cached, so hold on to it when making many
+    /// queries.
+    pub fn uv_bvh(&self, layer: UvLayer) -> Option<UvBvh> {
+        UvBvh::new(self, layer)
+    }
+
+    /// Find the triangle that a uv coordinate lands on, such as to find where on the mesh a
+    /// texel of a painted texture is.
+    ///
+    /// Builds a [`UvBvh`] for every call. See [`BlenderMesh.method#uv_bvh`] when making many
+    /// queries.
+    ///
+    /// ```
+    /// # use blender_mesh::{BlenderMesh, UvLayer};
+    /// # use nalgebra::Point3;
+    /// let plane = BlenderMesh::plane(2.0, 2.0, 0);
+    ///
+    /// let hit = plane.triangle_at_uv(UvLayer::Uv, 0.75, 0.5).unwrap();
+    ///
+    /// assert_eq!(plane.barycentric_to_position(&hit), Point3::new(0.5, 0.0, 0.0));
+    /// ```
+    ///
+    /// [`UvBvh`]: struct.UvBvh.html
+    pub fn triangle_at_uv(&self, layer: UvLayer, u: f32, v: f32) -> Option<UvHit> {
+        self.uv_bvh(layer)?.triangle_at(u, v)
+    }
+
+    /// The position at a hit's barycentric coordinates.
+    pub fn barycentric_to_position(&self, hit: &UvHit) -> Point3<f32> {
+        let positions = &self.multi_indexed_vertex_attributes.positions;
+
+        let mut position = Point3::origin();
+        for (corner, weight) in hit.corners.iter().zip(hit.barycentric.iter()) {
+            let corner = positions.attribute.data_at_idx(positions.indices[*corner]);
+            position.x += corner[0] * weight;
+            position.y += corner[1] * weight;
+            position.z += corner[2] * weight;
+        }
+
+        position
+    }
+
+    /// The uv coordinate at a hit's barycentric coordinates in one of this mesh's uv layers, or
+    /// None if the mesh does not have that layer.
+    ///
+    /// Useful for converting between layers, such as to find the lightmap texel that a texture
+    /// texel lands on.
+    pub fn barycentric_to_uv(&self, layer: UvLayer, hit: &UvHit) -> Option<[f32; 2]> {
+        let uvs = uv_layer(self, layer)?;
+
+        let mut uv = [0., 0.];
+        for (corner, weight) in hit.corners.iter().zip(hit.barycentric.iter()) {
+            let corner = uvs.attribute.data_at_idx(uvs.indices[*corner]);
+            uv[0] += corner[0] * weight;
+            uv[1] += corner[1] * weight;
+        }
+
+        Some(uv)
+    }
+
+    /// The uv coordinate of a point on one of this mesh's faces, such as the point that a decal
+    /// was projected onto by [`BlenderMesh.method#raycast`].
+    ///
+    /// Returns None if the mesh does not have the uv layer or the face does not exist. Points that
+    /// are off of the face get the uv of the face's closest triangle, extrapolated.
+    pub fn position_to_uv(
+        &self,
+        layer: UvLayer,
+        face: usize,
+        position: Point3<f32>,
+    ) -> Option<[f32; 2]> {
+        let multi = &self.multi_indexed_vertex_attributes;
+        uv_layer(self, layer)?;
+
+        let vertex_count = *multi.vertices_in_each_face.get(face)? as usize;
+        let first: usize = multi.vertices_in_each_face[..face]
+            .iter()
+            .map(|count| *count as usize)
+            .sum();
+
+        let corner_position = |corner: usize| {
+            let position = multi
+                .positions
+                .attribute
+                .data_at_idx(multi.positions.indices[corner]);
+            Point3::new(position[0], position[1], position[2])
+        };
+
+        let mut closest: Option<(f32, UvHit)> = None;
+        for corner in 1..vertex_count.saturating_sub(1) {
+            let corners = [first, first + corner, first + corner + 1];
+            let [a, b, c] = [
+                corner_position(corners[0]),
+                corner_position(corners[1]),
+                corner_position(corners[2]),
+            ];
+
+            // Barycentric coordinates of the position projected onto the triangle's plane
+            let (ab, ac, ap) = (b - a, c - a, position - a);
+            let normal = ab.cross(&ac);
+            let area_squared = normal.norm_squared();
+            if area_squared <= std::f32::EPSILON {
+                continue;
+            }
+            let weight_b = ap.cross(&ac).dot(&normal) / area_squared;
+            let weight_c = ab.cross(&ap).dot(&normal) / area_squared;
+            let barycentric = [1. - weight_b - weight_c, weight_b, weight_c];
+
+            // How far outside of the triangle the position is, in barycentric coordinates
+            let outside = barycentric
+                .iter()
+                .fold(0f32, |outside, weight| outside.max(-weight));
+
+            let is_closer = match closest {
+                Some((closest, _)) => outside < closest,
+                None => true,
+            };
+            if is_closer {
+                let hit = UvHit {
+                    face,
+                    corners,
+                    barycentric,
+                };
+                closest = Some((outside, hit));
+            }
+        }
+
+        self.barycentric_to_uv(layer, &closest?.1)
+    }
+}
+
+fn uv_layer(mesh: &BlenderMesh, layer: UvLayer) -> Option<&IndexedAttribute> {
+    let multi = &mesh.multi_indexed_vertex_attributes;
+    match layer {
+        UvLayer::Uv => multi.uvs.as_ref(),
+        UvLayer::Lightmap => multi.lightmap_uvs.as_ref(),
+    }
+}
+
+/// The barycentric coordinates of a point in a 2D triangle, or None if the triangle has no area.
+fn barycentric_2d(point: [f32; 2], a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> Option<[f32; 3]> {
+    let ab = [b[0] - a[0], b[1] - a[1]];
+    let ac = [c[0] - a[0], c[1] - a[1]];
+    let ap = [point[0] - a[0], point[1] - a[1]];
+
+    let area = ab[0] * ac[1] - ab[1] * ac[0];
+    if area.abs() <= std::f32::EPSILON {
+        return None;
+    }
+
+    let weight_b = (ap[0] * ac[1] - ap[1] * ac[0]) / area;
+    let weight_c = (ab[0] * ap[1] - ab[1] * ap[0]) / area;
+
+    Some([1. - weight_b - weight_c, weight_b, weight_c])
+}
+
+fn subdivide(
+    nodes: &mut Vec<UvBvhNode>,
+    node_idx: usize,
+    triangles: &mut [UvTriangle],
+    first_triangle: usize,
+) {
+    let mut min = [std::f32::MAX; 2];
+    let mut max = [std::f32::MIN; 2];
+    let mut centroid_min = min;
+    let mut centroid_max = max;
+
+    for triangle in triangles.iter() {
+        let centroid = triangle.centroid();
+
+        for axis in 0..2 {
+            for uv in triangle.uvs.iter() {
+                min[axis] = min[axis].min(uv[axis]);
+                max[axis] = max[axis].max(uv[axis]);
+            }
+
+            centroid_min[axis] = centroid_min[axis].min(centroid[axis]);
+            centroid_max[axis] = centroid_max[axis].max(centroid[axis]);
+        }
+    }
+
+    if triangles.len() <= MAX_TRIANGLES_PER_LEAF {
+        nodes[node_idx] = UvBvhNode {
+            min,
+            max,
+            first: first_triangle as u32,
+            triangle_count: triangles.len() as u32,
+        };
+        return;
+    }
+
+    // Split at the median centroid along the axis where the centroids are the most spread out
+    let axis = match centroid_max[0] - centroid_min[0] >= centroid_max[1] - centroid_min[1] {
+        true => 0,
+        false => 1,
+    };
+    triangles.sort_by(|a, b| {
+        a.centroid()[axis]
+            .partial_cmp(&b.centroid()[axis])
+            .unwrap_or(Ordering::Equal)
+    });
+
+    let left = nodes.len();
+    nodes.push(UvBvhNode::default());
+    nodes.push(UvBvhNode::default());
+
+    nodes[node_idx] = UvBvhNode {
+        min,
+        max,
+        first: left as u32,
+        triangle_count: 0,
+    };
+
+    let middle = triangles.len() / 2;
+    let (left_triangles, right_triangles) = triangles.split_at_mut(middle);
+
+    subdivide(nodes, left, left_triangles, first_triangle);
+    subdivide(nodes, left + 1, right_triangles, first_triangle + middle);
+}
+
+impl UvTriangle {
+    fn centroid(&self) -> [f32; 2] {
+        let [a, b, c] = self.uvs;
+        [(a[0] + b[0] + c[0]) / 3., (a[1] + b[1] + c[1]) / 3.]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that a uv coordinate finds the face that it lands on and that converting the hit
+    /// back to a position and a uv gives the point that was queried.
+    #[test]
+    fn uv_round_trip() {
+        // Faces 0 and 1 are the bottom row and faces 2 and 3 are the top row
+        let plane = BlenderMesh::plane(2., 2., 1);
+
+        let hit = plane.triangle_at_uv(UvLayer::Uv, 0.75, 0.25).unwrap();
+        assert_eq!(hit.face, 1);
+
+        let position = plane.barycentric_to_position(&hit);
+        assert!((position - Point3::new(0.5, -0.5, 0.)).norm() < 1e-5);
+
+        let uv = plane
+            .position_to_uv(UvLayer::Uv, hit.face, position)
+            .unwrap();
+        assert!((uv[0] - 0.75).abs() < 1e-5 && (uv[1] - 0.25).abs() < 1e-5);
+
+        assert_eq!(plane.triangle_at_uv(UvLayer::Uv, 1.5, 0.5), None);
+        assert_eq!(plane.triangle_at_uv(UvLayer::Lightmap, 0.5, 0.5), None);
+    }
+
+    /// Verify that every texel of a subdivided plane lands on the face below it.
+    #[test]
+    fn every_texel_lands_on_its_face() {
+        let plane = BlenderMesh::plane(1., 1., 7);
+        let bvh = plane.uv_bvh(UvLayer::Uv).unwrap();
+
+        for row in 0..8 {
+            for col in 0..8 {
+                let u = (col as f32 + 0.5) / 8.;
+                let v = (row as f32 + 0.5) / 8.;
+
+                let hit = bvh.triangle_at(u, v).unwrap();
+                assert_eq!(hit.face, row * 8 + col);
+                assert!((hit.barycentric.iter().sum::<f32>() - 1.).abs() < 1e-5);
+            }
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use crate::{BlenderMesh, MaterialInput, PrincipledBSDF};
+use nalgebra::{Matrix3, Point2, Rotation2, Vector2};
+use std::collections::{BTreeMap, HashMap};
+
+/// A texture that a material samples.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone)]
+#[allow(missing_docs)]
+pub enum TextureSlot {
+    BaseColor,
+    Roughness,
+    Metallic,
+    NormalMap,
+}
+
+/// How a material moves the uvs before sampling a texture, such as to tile a texture across a
+/// large floor.
+///
+/// # Blender
+///
+/// Read from the Mapping node that feeds into the Vector input of an image texture node. Only
+/// the X and Y of the mapping's location and scale and the Z of its rotation affect uvs.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Copy, Clone)]
+pub struct UvTransform {
+    /// Added to the uvs after they are scaled and rotated.
+    pub translation: [f32; 2],
+    /// Counter clockwise rotation in radians, applied after scaling.
+    pub rotation: f32,
+    /// Multiplies the uvs.
+    pub scale: [f32; 2],
+    /// Undo the transform instead of applying it, which moves the texture by the transform
+    /// instead of the uvs.
+    ///
+    /// # Blender
+    ///
+    /// True for Mapping nodes whose type is Texture.
+    #[serde(default)]
+    pub inverse: bool,
+}
+
+impl Default for UvTransform {
+    fn default() -> Self {
+        UvTransform {
+            translation: [0., 0.],
+            rotation: 0.,
+            scale: [1., 1.],
+            inverse: false,
+        }
+    }
+}
+
+impl UvTransform {
+    /// The transform as a matrix that multiplies homogeneous uvs.
+    ///
+    /// An inverse transform with a scale of zero along an axis collapses that axis to zero, the
+    /// same as Blender does.
+    pub fn matrix(&self) -> Matrix3<f32> {
+        let [x, y] = self.translation;
+        let translation = Vector2::new(x, y);
+        let rotation = Rotation2::new(self.rotation).to_homogeneous();
+
+        match self.inverse {
+            false => {
+                let [x, y] = self.scale;
+                let scale = Matrix3::new_nonuniform_scaling(&Vector2::new(x, y));
+
+                Matrix3::new_translation(&translation) * rotation * scale
+            }
+            true => {
+                let inverse_scale = |axis: f32| match axis == 0. {
+                    true => 0.,
+                    false => 1. / axis,
+                };
+                let [x, y] = self.scale;
+                let scale = Matrix3::new_nonuniform_scaling(&Vector2::new(
+                    inverse_scale(x),
+                    inverse_scale(y),
+                ));
+
+                scale * rotation.transpose() * Matrix3::new_translation(&-translation)
+            }
+        }
+    }
+
+    /// Move a uv by the transform.
+    pub fn apply(&self, uv: [f32; 2]) -> [f32; 2] {
+        let uv = self.matrix().transform_point(&Point2::new(uv[0], uv[1]));
+
+        [uv.x, uv.y]
+    }
+}
+
+impl PrincipledBSDF {
+    /// The uv transform of every texture slot whose texture is sampled with transformed uvs.
+    pub fn uv_transforms(&self) -> &BTreeMap<TextureSlot, UvTransform> {
+        &self.uv_transforms
+    }
+
+    /// The uv transform of a texture slot, if its uvs are transformed.
+    pub fn uv_transform(&self, slot: TextureSlot) -> Option<&UvTransform> {
+        self.uv_transforms.get(&slot)
+    }
+
+    /// See [`PrincipledBSDF.method#uv_transform`]
+    pub fn set_uv_transform(&mut self, slot: TextureSlot, uv_transform: Option<UvTransform>) {
+        match uv_transform {
+            Some(uv_transform) => self.uv_transforms.insert(slot, uv_transform),
+            None => self.uv_transforms.remove(&slot),
+        };
+    }
+
+    /// The slots that sample a texture instead of a uniform value.
+    pub fn textured_slots(&self) -> Vec<TextureSlot> {
+        let mut slots = vec![];
+
+        if let MaterialInput::ImageTexture(_) = self.base_color {
+            slots.push(TextureSlot::BaseColor);
+        }
+        if let MaterialInput::ImageTexture(_) = self.roughness {
+            slots.push(TextureSlot::Roughness);
+        }
+        if let MaterialInput::ImageTexture(_) = self.metallic {
+            slots.push(TextureSlot::Metallic);
+        }
+        if self.normal_map.is_some() {
+            slots.push(TextureSlot::NormalMap);
+        }
+
+        slots
+    }
+
+    /// The uv transform that every one of the material's textures shares, or `None` if its
+    /// textures are sampled with different uvs.
+    ///
+    /// Textures without a uv transform are sampled with the identity transform.
+    fn shared_uv_transform(&self) -> Option<UvTransform> {
+        let mut transforms = self
+            .textured_slots()
+            .into_iter()
+            .chain(self.uv_transforms.keys().copied())
+            .map(|slot| self.uv_transform(slot).copied().unwrap_or_default());
+
+        let first = transforms.next().unwrap_or_default();
+        match transforms.all(|transform| transform.matrix() == first.matrix()) {
+            true => Some(first),
+            false => None,
+        }
+    }
+}
+
+impl BlenderMesh {
+    /// Apply every material's uv transform to the uvs of the faces that use the material, and
+    /// then remove the transforms from the materials, for engines that sample every texture with
+    /// the mesh's uvs as they are.
+    ///
+    /// Uvs that are shared by faces with different materials are split so that each material's
+    /// faces get their own copy.
+    ///
+    /// A mesh only has one set of uvs, so materials whose textures have different uv transforms
+    /// keep their transforms and their uvs are left as they are. The names of these materials are
+    /// returned.
+    pub fn bake_uv_transforms(&mut self) -> Vec<String> {
+        let mut skipped = vec![];
+        let mut baked: HashMap<u16, UvTransform> = HashMap::new();
+
+        for (material_idx, material) in self.materials.iter_mut().enumerate() {
+            if material.uv_transforms.is_empty() {
+                continue;
+            }
+
+            match material.shared_uv_transform() {
+                Some(uv_transform) => {
+                    baked.insert(material_idx as u16, uv_transform);
+                    material.uv_transforms.clear();
+                }
+                None => skipped.push(material.name.clone()),
+            };
+        }
+
+        let multi = &mut self.multi_indexed_vertex_attributes;
+        let uvs = match multi.uvs.as_mut() {
+            Some(uvs) if !baked.is_empty() => uvs,
+            _ => return skipped,
+        };
+
+        let mut data = vec![];
+        let mut new_indices: HashMap<(u32, Option<u16>), u32> = HashMap::new();
+
+        let mut corner = 0;
+        for (face_idx, vertex_count) in multi.vertices_in_each_face.iter().enumerate() {
+            let material_idx = multi.material_index.get(face_idx).copied().unwrap_or(0);
+            let uv_transform = baked.get(&material_idx);
+
+            for _ in 0..*vertex_count {
+                let old_idx = uvs.indices[corner];
+                let key = (old_idx, uv_transform.map(|_| material_idx));
+
+                let new_idx = *new_indices.entry(key).or_insert_with(|| {
+                    let uv = uvs.attribute.data_at_idx(old_idx);
+                    let uv = [uv[0], uv[1]];
+                    let uv = match uv_transform {
+                        Some(uv_transform) => uv_transform.apply(uv),
+                        None => uv,
+                    };
+
+                    data.extend_from_slice(&uv);
+                    (data.len() / 2 - 1) as u32
+                });
+
+                uvs.indices[corner] = new_idx;
+                corner += 1;
+            }
+        }
+
+        uvs.attribute.data = data;
+
+        skipped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that a material's uv transform is baked into the uvs of its faces, and that uvs
+    /// shared with another material's faces are split.
+    #[test]
+    fn bake_uv_transforms() {
+        let mut mesh = BlenderMesh::plane(2., 2., 1);
+
+        let tiling = UvTransform {
+            translation: [1., 0.],
+            scale: [4., 4.],
+            ..UvTransform::default()
+        };
+        let mut tiled = material("Tiled");
+        tiled.set_uv_transform(TextureSlot::BaseColor, Some(tiling));
+        tiled.set_uv_transform(TextureSlot::NormalMap, Some(tiling));
+
+        let mut mixed = material("Mixed");
+        mixed.set_uv_transform(TextureSlot::BaseColor, Some(UvTransform::default()));
+        mixed.set_uv_transform(
+            TextureSlot::NormalMap,
+            Some(UvTransform {
+                rotation: 1.,
+                ..UvTransform::default()
+            }),
+        );
+
+        mesh.materials = vec![material("Plain"), tiled, mixed];
+        mesh.multi_indexed_vertex_attributes.material_index = vec![0, 1, 1, 2];
+
+        let original = mesh.multi_indexed_vertex_attributes.uvs.clone().unwrap();
+
+        let skipped = mesh.bake_uv_transforms();
+        assert_eq!(skipped, vec!["Mixed".to_string()]);
+        assert!(mesh.materials[1].uv_transforms().is_empty());
+        assert_eq!(mesh.materials[2].uv_transforms().len(), 2);
+
+        let uvs = mesh.multi_indexed_vertex_attributes.uvs.as_ref().unwrap();
+        for corner in 0..uvs.indices().len() {
+            let before = original.data_at_corner(corner);
+            let after = uvs.data_at_corner(corner);
+
+            let expected = match corner / 4 {
+                1 | 2 => [before[0] * 4. + 1., before[1] * 4.],
+                _ => [before[0], before[1]],
+            };
+            assert_eq!(after, &expected);
+        }
+    }
+
+    /// Verify that an inverse transform undoes the transform.
+    #[test]
+    fn inverse_uv_transform() {
+        let transform = UvTransform {
+            translation: [0.5, 0.25],
+            rotation: 0.7,
+            scale: [2., 3.],
+            inverse: false,
+        };
+        let inverse = UvTransform {
+            inverse: true,
+            ..transform
+        };
+
+        let uv = inverse.apply(transform.apply([0.3, 0.6]));
+        assert!((uv[0] - 0.3).abs() < 1e-5 && (uv[1] - 0.6).abs() < 1e-5);
+    }
+
+    fn material(name: &str) -> PrincipledBSDF {
+        PrincipledBSDF::new(
+            name.to_string(),
+            MaterialInput::ImageTexture("albedo.png".to_string()),
+            MaterialInput::Uniform(0.5),
+            MaterialInput::Uniform(0.),
+            None,
+        )
+    }
+}
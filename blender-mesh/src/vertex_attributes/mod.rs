@@ -1,7 +1,8 @@
 mod vertex_attribute;
 
-pub use self::vertex_attribute::{BoneAttributes, VertexAttribute};
+pub use self::vertex_attribute::{BoneAttributes, VertexAttribute, VertexAttributeError};
 use crate::bone::BoneInfluencesPerVertex;
+use crate::triangulate::triangulate_indices;
 
 mod single_indexed;
 pub use self::single_indexed::*;
@@ -53,7 +54,7 @@ pub use self::single_indexed::*;
 /// triangle.
 ///
 /// [`vertices_in_each_face`]: struct.MultiIndexVertexData.html#method.vertices_in_each_face
-pub type VertexIndices = Vec<u16>;
+pub type VertexIndices = Vec<u32>;
 
 /// Per vertex data from the BlenderMesh.
 ///
@@ -98,24 +99,137 @@ pub struct MultiIndexedVertexAttributes {
     pub(crate) positions: IndexedAttribute,
     pub(crate) normals: Option<IndexedAttribute>,
     pub(crate) uvs: Option<IndexedAttribute>,
+    pub(crate) lightmap_uvs: Option<IndexedAttribute>,
     pub(crate) bone_influences: Option<VertexBoneInfluences>,
 }
 
+/// Vertex data along with the indices that point into it.
+///
+/// Keeping the two together means that the indices can't be separated from the data that they
+/// index into, so anything that rearranges the indices (such as triangulation) works the same way
+/// for positions, normals, uvs or any other attribute.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
-pub struct IndexedAttribute {
+pub struct IndexedAttribute<T = f32> {
     pub(crate) indices: VertexIndices,
-    pub(crate) attribute: VertexAttribute<f32>,
+    pub(crate) attribute: VertexAttribute<T>,
 }
 
 #[allow(missing_docs)]
-impl IndexedAttribute {
-    pub fn new(indices: VertexIndices, attribute: VertexAttribute<f32>) -> Self {
+impl<T> IndexedAttribute<T> {
+    pub fn new(indices: VertexIndices, attribute: VertexAttribute<T>) -> Self {
         IndexedAttribute { indices, attribute }
     }
 }
 
-impl From<(VertexIndices, VertexAttribute<f32>)> for IndexedAttribute {
-    fn from(v: (VertexIndices, VertexAttribute<f32>)) -> Self {
+impl<T> IndexedAttribute<T> {
+    /// Indices into the attribute's data, one per face corner.
+    pub fn indices(&self) -> &VertexIndices {
+        &self.indices
+    }
+
+    /// The data that the indices point into.
+    pub fn attribute(&self) -> &VertexAttribute<T> {
+        &self.attribute
+    }
+
+    /// The data for the vertex that the index at `corner` points to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `corner` is out of bounds.
+    pub fn data_at_corner(&self, corner: usize) -> &[T] {
+        self.attribute.data_at_idx(self.indices[corner])
+    }
+
+    /// Split every quad into two triangles by rearranging the indices.
+    ///
+    /// The data is left untouched since the triangles reuse the quad's vertices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a face has more than 4 vertices.
+    pub fn triangulate(&mut self, vertices_in_each_face: &[u8]) {
+        self.indices = triangulate_indices(vertices_in_each_face, &self.indices);
+    }
+}
+
+impl MultiIndexedVertexAttributes {
+    /// The number of vertices that comprise each face of the mesh.
+    pub fn vertices_in_each_face(&self) -> &Vec<u8> {
+        &self.vertices_in_each_face
+    }
+
+    /// The index of the material that each face uses.
+    pub fn material_index(&self) -> &Vec<u16> {
+        &self.material_index
+    }
+
+    /// The position of every face corner. Position indices double as the mesh's vertex ids.
+    pub fn positions(&self) -> &IndexedAttribute {
+        &self.positions
+    }
+
+    /// The normal of every face corner.
+    pub fn normals(&self) -> Option<&IndexedAttribute> {
+        self.normals.as_ref()
+    }
+
+    /// The uv coordinates of every face corner.
+    pub fn uvs(&self) -> Option<&IndexedAttribute> {
+        self.uvs.as_ref()
+    }
+
+    /// The lightmap uv coordinates of every face corner, from the uv map whose name contains
+    /// `lightmap`.
+    ///
+    /// These are kept separate from the vertices that are created when combining indices, for
+    /// light bakers that work on the mesh's faces.
+    pub fn lightmap_uvs(&self) -> Option<&IndexedAttribute> {
+        self.lightmap_uvs.as_ref()
+    }
+
+    /// The bones that influence each vertex.
+    pub fn bone_influences(&self) -> Option<&VertexBoneInfluences> {
+        self.bone_influences.as_ref()
+    }
+
+    /// Split every quad into two triangles.
+    ///
+    /// Every indexed attribute is triangulated and each triangle keeps the material of the face
+    /// that it came from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a face has more than 4 vertices.
+    pub fn triangulate(&mut self) {
+        let vertices_in_each_face = self.vertices_in_each_face.clone();
+
+        self.positions.triangulate(&vertices_in_each_face);
+        for attribute in self
+            .normals
+            .iter_mut()
+            .chain(self.uvs.iter_mut())
+            .chain(self.lightmap_uvs.iter_mut())
+        {
+            attribute.triangulate(&vertices_in_each_face);
+        }
+
+        let mut material_index = vec![];
+        for (face, vertex_count) in vertices_in_each_face.iter().enumerate() {
+            let triangles = if *vertex_count == 4 { 2 } else { 1 };
+
+            if let Some(material) = self.material_index.get(face) {
+                material_index.extend(std::iter::repeat(*material).take(triangles));
+            }
+        }
+        self.material_index = material_index;
+
+        self.vertices_in_each_face = vec![3; self.positions.indices.len() / 3];
+    }
+}
+
+impl<T> From<(VertexIndices, VertexAttribute<T>)> for IndexedAttribute<T> {
+    fn from(v: (VertexIndices, VertexAttribute<T>)) -> Self {
         Self {
             indices: v.0,
             attribute: v.1,
@@ -156,3 +270,31 @@ pub struct VertexBoneInfluences {
     /// The corresponding weights of each bone index
     pub(crate) bone_weights: Vec<f32>,
 }
+
+impl VertexBoneInfluences {
+    /// The bone indices and weights that influence each vertex, in vertex order.
+    pub fn influences_per_vertex(&self) -> Vec<(&[u8], &[f32])> {
+        let counts: Vec<usize> = match &self.bones_per_vertex {
+            BoneInfluencesPerVertex::NonUniform(counts) => {
+                counts.iter().map(|count| *count as usize).collect()
+            }
+            BoneInfluencesPerVertex::Uniform(0) => vec![],
+            BoneInfluencesPerVertex::Uniform(count) => {
+                vec![*count as usize; self.bone_indices.len() / *count as usize]
+            }
+        };
+
+        let mut influences = vec![];
+        let mut start = 0;
+
+        for count in counts {
+            influences.push((
+                &self.bone_indices[start..start + count],
+                &self.bone_weights[start..start + count],
+            ));
+            start += count;
+        }
+
+        influences
+    }
+}
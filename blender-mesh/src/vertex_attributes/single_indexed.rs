@@ -1,6 +1,14 @@
+use crate::serde::serialize_hashmap_deterministic;
+use crate::vertex_attributes::{BoneAttributes, VertexAttribute};
+use crate::{CustomAttribute, MeshKind, Meshlet};
+use std::collections::HashMap;
+
+mod adjacency;
 mod interleave;
+mod vertex_layout;
 
 pub use self::interleave::*;
+pub use self::vertex_layout::*;
 
 /// Most 3D model file formats export vertex data with multiple indices.
 ///
@@ -11,11 +19,78 @@ pub use self::interleave::*;
 /// When we've run [`BlenderMesh.combine_vertex_indices`] we'll end up generating
 /// `SingleIndexVertexData`
 ///
+/// Every index points to a vertex, every three indices form a triangle (or every two an edge or
+/// every one a point, see [`SingleIndexedVertexAttributes.method#kind`]) and every vertex has the
+/// same attributes. So if one vertex has a normal then all of them do, which lets you get
+/// each attribute for the whole mesh at once without checking every vertex.
+///
 /// [`BlenderMesh.combine_vertex_indices`]: ../struct.BlenderMesh.html#method.combine_vertex_indices
 #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
 pub struct SingleIndexedVertexAttributes {
+    #[serde(default)]
+    pub(crate) kind: MeshKind,
     pub(crate) indices: Vec<u16>,
     pub(crate) vertices: Vec<Vertex>,
+    #[serde(default, serialize_with = "serialize_hashmap_deterministic")]
+    pub(crate) custom_attributes: HashMap<String, CustomAttribute>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) meshlets: Vec<Meshlet>,
+}
+
+/// An error while creating [`SingleIndexedVertexAttributes`].
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum SingleIndexedError {
+    /// Every three indices should form a triangle.
+    #[error("{index_count} indices cannot be split into triangles")]
+    NotTriangulated {
+        /// The number of indices
+        index_count: usize,
+    },
+    /// Every two indices should form an edge.
+    #[error("{index_count} indices cannot be split into edges")]
+    NotEdges {
+        /// The number of indices
+        index_count: usize,
+    },
+    /// An index points past the end of the vertices.
+    #[error("Index {index} is out of bounds for {vertex_count} vertices")]
+    IndexOutOfBounds {
+        /// The out of bounds index
+        index: u16,
+        /// The number of vertices
+        vertex_count: usize,
+    },
+    /// A vertex has different attributes than the first vertex, such as a uv when the first
+    /// vertex does not have one.
+    #[error("Vertex {vertex} has different attributes than the first vertex")]
+    InconsistentAttributes {
+        /// The index of the vertex
+        vertex: usize,
+    },
+}
+
+/// Which of the optional attributes are present on every vertex.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct PresentAttributes {
+    /// See [`Vertex.method#normal`]
+    pub normals: bool,
+    /// See [`Vertex.method#uv`]
+    pub uvs: bool,
+    /// See [`Vertex.method#face_tangent`]
+    pub face_tangents: bool,
+    /// See [`Vertex.method#bones`]
+    pub bones: bool,
+}
+
+impl PresentAttributes {
+    fn of(vertex: &Vertex) -> Self {
+        PresentAttributes {
+            normals: vertex.normal.is_some(),
+            uvs: vertex.uv.is_some(),
+            face_tangents: vertex.face_tangent.is_some(),
+            bones: vertex.bones.is_some(),
+        }
+    }
 }
 
 /// A vertex within a mesh.
@@ -90,9 +165,77 @@ impl BoneInfluence {
 }
 
 impl SingleIndexedVertexAttributes {
+    /// Create single indexed vertex data, verifying that the indices form triangles that point
+    /// to existing vertices and that every vertex has the same attributes.
+    pub fn new(
+        indices: Vec<u16>,
+        vertices: Vec<Vertex>,
+    ) -> Result<SingleIndexedVertexAttributes, SingleIndexedError> {
+        SingleIndexedVertexAttributes::new_with_kind(MeshKind::Triangles, indices, vertices)
+    }
+
+    /// Create single indexed vertex data whose indices form points, edges or triangles,
+    /// verifying it the same way as [`SingleIndexedVertexAttributes.method#new`].
+    pub fn new_with_kind(
+        kind: MeshKind,
+        indices: Vec<u16>,
+        vertices: Vec<Vertex>,
+    ) -> Result<SingleIndexedVertexAttributes, SingleIndexedError> {
+        match kind {
+            MeshKind::Triangles if indices.len() % 3 != 0 => {
+                return Err(SingleIndexedError::NotTriangulated {
+                    index_count: indices.len(),
+                });
+            }
+            MeshKind::Edges if indices.len() % 2 != 0 => {
+                return Err(SingleIndexedError::NotEdges {
+                    index_count: indices.len(),
+                });
+            }
+            _ => {}
+        };
+
+        if let Some(index) = indices.iter().find(|idx| **idx as usize >= vertices.len()) {
+            return Err(SingleIndexedError::IndexOutOfBounds {
+                index: *index,
+                vertex_count: vertices.len(),
+            });
+        }
+
+        if let Some(first) = vertices.first() {
+            let present = PresentAttributes::of(first);
+
+            if let Some(vertex) = vertices
+                .iter()
+                .position(|vertex| PresentAttributes::of(vertex) != present)
+            {
+                return Err(SingleIndexedError::InconsistentAttributes { vertex });
+            }
+        }
+
+        Ok(SingleIndexedVertexAttributes {
+            kind,
+            indices,
+            vertices,
+            custom_attributes: HashMap::new(),
+            meshlets: vec![],
+        })
+    }
+
+    /// What the indices form.
+    ///
+    /// Meshes without faces are combined into points or edges, so that renderers can pick a
+    /// matching primitive topology.
+    pub fn kind(&self) -> MeshKind {
+        self.kind
+    }
+
     /// For `SingleIndexVertexData` every 3 indices corresponds to one triangle.
     ///
     /// There can not be any other faces (quads, ngons) - only triangles.
+    ///
+    /// If the [`SingleIndexedVertexAttributes.method#kind`] is points or edges then every index is
+    /// one point or every 2 indices are one edge instead.
     pub fn indices(&self) -> &Vec<u16> {
         &self.indices
     }
@@ -104,7 +247,164 @@ impl SingleIndexedVertexAttributes {
         &self.vertices
     }
 
+    /// The mesh's custom attributes, each with one value per vertex.
+    ///
+    /// See [`BlenderMesh.method#custom_attributes`]
+    pub fn custom_attributes(&self) -> &HashMap<String, CustomAttribute> {
+        &self.custom_attributes
+    }
+
     pub(crate) fn vertices_mut(&mut self) -> &mut Vec<Vertex> {
         &mut self.vertices
     }
+
+    /// Which optional attributes the vertices have.
+    pub fn present_attributes(&self) -> PresentAttributes {
+        self.vertices
+            .first()
+            .map(PresentAttributes::of)
+            .unwrap_or_default()
+    }
+
+    /// The position of every vertex.
+    pub fn positions(&self) -> VertexAttribute<f32> {
+        self.collect_attribute(3, |vertex| Some(&vertex.position[..]))
+            .unwrap_or_else(|| VertexAttribute::new(vec![], 3).unwrap())
+    }
+
+    /// The normal of every vertex, or `None` if the vertices don't have normals.
+    pub fn normals(&self) -> Option<VertexAttribute<f32>> {
+        self.collect_attribute(3, |vertex| vertex.normal.as_ref().map(|n| &n[..]))
+    }
+
+    /// The uv of every vertex, or `None` if the vertices don't have uvs.
+    pub fn uvs(&self) -> Option<VertexAttribute<f32>> {
+        self.collect_attribute(2, |vertex| vertex.uv.as_ref().map(|uv| &uv[..]))
+    }
+
+    /// The face tangent of every vertex, or `None` if the vertices don't have face tangents.
+    pub fn face_tangents(&self) -> Option<VertexAttribute<f32>> {
+        self.collect_attribute(3, |vertex| vertex.face_tangent.as_ref().map(|t| &t[..]))
+    }
+
+    /// The four bone influences of every vertex, or `None` if the vertices aren't influenced by
+    /// bones.
+    pub fn bone_influences(&self) -> Option<BoneAttributes> {
+        if !self.present_attributes().bones {
+            return None;
+        }
+
+        let mut bone_influencers = vec![];
+        let mut bone_weights = vec![];
+
+        for bones in self.vertices.iter().filter_map(|vertex| vertex.bones) {
+            for bone in bones.iter() {
+                bone_influencers.push(bone.bone_idx);
+                bone_weights.push(bone.weight);
+            }
+        }
+
+        Some(BoneAttributes {
+            bone_influencers: VertexAttribute::new(bone_influencers, 4).unwrap(),
+            bone_weights: VertexAttribute::new(bone_weights, 4).unwrap(),
+        })
+    }
+
+    // Relies on every vertex having the same attributes, so we only need to check whether the
+    // first vertex has the attribute.
+    fn collect_attribute<'a>(
+        &'a self,
+        attribute_size: u8,
+        attribute: impl Fn(&'a Vertex) -> Option<&'a [f32]>,
+    ) -> Option<VertexAttribute<f32>> {
+        let first = self.vertices.first()?;
+        attribute(first)?;
+
+        let mut data = Vec::with_capacity(self.vertices.len() * attribute_size as usize);
+        for vertex in self.vertices.iter() {
+            data.extend_from_slice(attribute(vertex)?);
+        }
+
+        Some(VertexAttribute::new(data, attribute_size).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(position: [f32; 3], uv: Option<[f32; 2]>) -> Vertex {
+        Vertex {
+            position,
+            uv,
+            ..Vertex::default()
+        }
+    }
+
+    /// Verify that we only create vertex data that upholds its invariants.
+    #[test]
+    fn validate_single_indexed() {
+        let vertices = vec![
+            vertex([0., 0., 0.], Some([0., 0.])),
+            vertex([1., 0., 0.], Some([1., 0.])),
+            vertex([1., 1., 0.], Some([1., 1.])),
+        ];
+
+        assert!(SingleIndexedVertexAttributes::new(vec![0, 1, 2], vertices.clone()).is_ok());
+        assert_eq!(
+            SingleIndexedVertexAttributes::new(vec![0, 1], vertices.clone()),
+            Err(SingleIndexedError::NotTriangulated { index_count: 2 })
+        );
+        let edges = |indices| {
+            SingleIndexedVertexAttributes::new_with_kind(MeshKind::Edges, indices, vertices.clone())
+        };
+        assert!(edges(vec![0, 1]).is_ok());
+        assert_eq!(
+            edges(vec![0]),
+            Err(SingleIndexedError::NotEdges { index_count: 1 })
+        );
+        assert_eq!(
+            SingleIndexedVertexAttributes::new(vec![0, 1, 3], vertices.clone()),
+            Err(SingleIndexedError::IndexOutOfBounds {
+                index: 3,
+                vertex_count: 3
+            })
+        );
+
+        let mut inconsistent = vertices;
+        inconsistent[2].uv = None;
+        assert_eq!(
+            SingleIndexedVertexAttributes::new(vec![0, 1, 2], inconsistent),
+            Err(SingleIndexedError::InconsistentAttributes { vertex: 2 })
+        );
+    }
+
+    /// Verify that we get each attribute for all of the vertices at once.
+    #[test]
+    fn attributes_for_every_vertex() {
+        let single = SingleIndexedVertexAttributes::new(
+            vec![0, 1, 2],
+            vec![
+                vertex([0., 0., 0.], Some([0., 0.])),
+                vertex([1., 0., 0.], Some([1., 0.])),
+                vertex([1., 1., 0.], Some([1., 1.])),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            single.present_attributes(),
+            PresentAttributes {
+                uvs: true,
+                ..PresentAttributes::default()
+            }
+        );
+        assert_eq!(
+            single.positions().data(),
+            &vec![0., 0., 0., 1., 0., 0., 1., 1., 0.]
+        );
+        assert_eq!(single.uvs().unwrap().data(), &vec![0., 0., 1., 0., 1., 1.]);
+        assert_eq!(single.normals(), None);
+        assert_eq!(single.bone_influences(), None);
+    }
 }
@@ -0,0 +1,118 @@
+use crate::{MeshKind, SingleIndexedVertexAttributes};
+use std::collections::HashMap;
+
+/// The bits of a vertex's position, so that vertices that were split from the same position
+/// can be matched up.
+type PositionKey = [u32; 3];
+
+impl SingleIndexedVertexAttributes {
+    /// Indices for drawing the triangles along with their neighbours, such as with
+    /// `GL_TRIANGLES_ADJACENCY`, for geometry shaders that find silhouettes or extrude shadow
+    /// volumes.
+    ///
+    /// Every triangle `[a, b, c]` becomes the six indices `[a, ab, b, bc, c, ca]`, where `ab` is
+    /// the vertex across the edge from `a` to `b` in the triangle on the other side of that
+    /// edge.
+    ///
+    /// Triangles are neighbours if they share the positions at both ends of an edge, so vertices
+    /// that were split apart along uv seams or hard edges are still connected. If more than two
+    /// triangles share an edge then the first of the other triangles is used.
+    ///
+    /// Boundary edges don't have a neighbour, so their adjacent vertex is the triangle's own
+    /// vertex opposite the edge. A shader can find boundary edges by checking for an adjacent
+    /// vertex that is the same as the triangle's third vertex.
+    ///
+    /// Empty if the indices form points or edges instead of triangles.
+    pub fn triangle_adjacency_indices(&self) -> Vec<u16> {
+        if self.kind != MeshKind::Triangles {
+            return vec![];
+        }
+
+        let position = |index: u16| -> PositionKey {
+            let [x, y, z] = self.vertices[index as usize].position;
+            [x.to_bits(), y.to_bits(), z.to_bits()]
+        };
+        let edge_key = |start: u16, end: u16| {
+            let (start, end) = (position(start), position(end));
+            [start.min(end), start.max(end)]
+        };
+
+        // Edge -> the triangles that use it, along with each triangle's vertex opposite the edge
+        let mut edges: HashMap<[PositionKey; 2], Vec<(usize, u16)>> = HashMap::new();
+        for (triangle_idx, triangle) in self.indices.chunks(3).enumerate() {
+            for corner in 0..3 {
+                let (start, end) = (triangle[corner], triangle[(corner + 1) % 3]);
+                edges
+                    .entry(edge_key(start, end))
+                    .or_default()
+                    .push((triangle_idx, triangle[(corner + 2) % 3]));
+            }
+        }
+
+        let mut adjacency_indices = Vec::with_capacity(self.indices.len() * 2);
+        for (triangle_idx, triangle) in self.indices.chunks(3).enumerate() {
+            for corner in 0..3 {
+                let (start, end) = (triangle[corner], triangle[(corner + 1) % 3]);
+                let own_opposite = triangle[(corner + 2) % 3];
+
+                let adjacent = edges[&edge_key(start, end)]
+                    .iter()
+                    .find(|(other, _)| *other != triangle_idx)
+                    .map(|(_, opposite)| *opposite)
+                    .unwrap_or(own_opposite);
+
+                adjacency_indices.push(start);
+                adjacency_indices.push(adjacent);
+            }
+        }
+
+        adjacency_indices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Vertex;
+
+    fn vertex(position: [f32; 3]) -> Vertex {
+        Vertex {
+            position,
+            ..Vertex::default()
+        }
+    }
+
+    /// Verify that triangles are connected across edges whose vertices were split apart, and that
+    /// boundary edges use the triangle's own opposite vertex.
+    #[test]
+    fn adjacency_across_split_vertices() {
+        let vertices = vec![
+            vertex([0., 0., 0.]),
+            vertex([1., 0., 0.]),
+            vertex([0., 1., 0.]),
+            vertex([1., 1., 0.]),
+            // Split from vertices 1 and 2, such as along a uv seam
+            vertex([1., 0., 0.]),
+            vertex([0., 1., 0.]),
+        ];
+        let single = SingleIndexedVertexAttributes::new(vec![0, 1, 2, 4, 3, 5], vertices).unwrap();
+
+        assert_eq!(
+            single.triangle_adjacency_indices(),
+            vec![0, 2, 1, 3, 2, 1, 4, 5, 3, 4, 5, 0]
+        );
+    }
+
+    /// Verify that points and edges don't have triangle adjacency.
+    #[test]
+    fn no_adjacency_without_triangles() {
+        let single = SingleIndexedVertexAttributes::new_with_kind(
+            MeshKind::Points,
+            vec![0],
+            vec![vertex([0., 0., 0.])],
+        )
+        .unwrap();
+
+        assert!(single.triangle_adjacency_indices().is_empty());
+    }
+}
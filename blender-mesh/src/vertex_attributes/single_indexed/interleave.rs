@@ -96,7 +96,7 @@ mod tests {
     /// The lengths of all of the attributes should correspond to the same number of vertices
     #[test]
     fn error_if_incompatible_lengths() {
-        let positions = VertexAttribute::new(vec![0.], 3).unwrap();
+        let positions = VertexAttribute::new(vec![0., 1., 2.], 3).unwrap();
         let uvs = VertexAttribute::new(vec![50., 51., 52., 53.], 2).unwrap();
 
         match SingleIndexedVertexAttributes::interleave(&[&positions, &uvs]) {
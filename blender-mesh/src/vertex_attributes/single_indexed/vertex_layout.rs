@@ -0,0 +1,346 @@
+use crate::{Endianness, SingleIndexedVertexAttributes, Vertex};
+
+/// Describes how the data for each vertex is laid out in the buffer from
+/// [`SingleIndexedVertexAttributes.method#interleaved_bytes`].
+///
+/// Attributes are only included if the vertices have them, in the order
+/// position, normal, uv, face tangent, bone indices, bone weights.
+///
+/// Useful for telling your graphics API how to read the buffer without hard coding offsets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VertexLayout {
+    pub(crate) stride: usize,
+    pub(crate) attributes: Vec<VertexAttributeLayout>,
+}
+
+/// Where one attribute is within each vertex of an interleaved buffer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VertexAttributeLayout {
+    pub(crate) kind: LayoutAttribute,
+    pub(crate) component_type: ComponentType,
+    pub(crate) component_count: u8,
+    pub(crate) byte_offset: usize,
+    pub(crate) stride: usize,
+    pub(crate) normalized: bool,
+}
+
+/// The attributes that can be in a [`VertexLayout`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum LayoutAttribute {
+    Position,
+    Normal,
+    Uv,
+    FaceTangent,
+    BoneIndices,
+    BoneWeights,
+}
+
+/// The type of each component of an attribute.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum ComponentType {
+    F32,
+    U8,
+}
+
+impl LayoutAttribute {
+    /// A name for the attribute, such as "position" or "bone_weights".
+    pub fn name(&self) -> &'static str {
+        match self {
+            LayoutAttribute::Position => "position",
+            LayoutAttribute::Normal => "normal",
+            LayoutAttribute::Uv => "uv",
+            LayoutAttribute::FaceTangent => "face_tangent",
+            LayoutAttribute::BoneIndices => "bone_indices",
+            LayoutAttribute::BoneWeights => "bone_weights",
+        }
+    }
+
+    /// The shader location that the GPU integrations bind this attribute to.
+    ///
+    /// Locations don't depend on which attributes are present, so one shader can be used with
+    /// meshes that have different attributes.
+    ///
+    /// | Attribute    | Location |
+    /// | ---          | ---      |
+    /// | Position     | 0        |
+    /// | Normal       | 1        |
+    /// | Uv           | 2        |
+    /// | FaceTangent  | 3        |
+    /// | BoneIndices  | 4        |
+    /// | BoneWeights  | 5        |
+    pub fn shader_location(&self) -> u32 {
+        match self {
+            LayoutAttribute::Position => 0,
+            LayoutAttribute::Normal => 1,
+            LayoutAttribute::Uv => 2,
+            LayoutAttribute::FaceTangent => 3,
+            LayoutAttribute::BoneIndices => 4,
+            LayoutAttribute::BoneWeights => 5,
+        }
+    }
+}
+
+impl ComponentType {
+    /// The number of bytes that one component takes up.
+    pub fn size_in_bytes(&self) -> usize {
+        match self {
+            ComponentType::F32 => 4,
+            ComponentType::U8 => 1,
+        }
+    }
+}
+
+impl VertexLayout {
+    /// The number of bytes from the start of one vertex to the start of the next.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Every attribute in the order that they appear within a vertex.
+    pub fn attributes(&self) -> &Vec<VertexAttributeLayout> {
+        &self.attributes
+    }
+
+    /// The layout of a specific attribute, or `None` if the vertices don't have it.
+    pub fn attribute(&self, kind: LayoutAttribute) -> Option<&VertexAttributeLayout> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.kind == kind)
+    }
+
+    /// A layout of only the attributes that are kept, packed together in the same order.
+    pub(crate) fn subset(&self, keep: impl Fn(LayoutAttribute) -> bool) -> VertexLayout {
+        let layout = self
+            .attributes
+            .iter()
+            .filter(|attribute| keep(attribute.kind))
+            .map(|attribute| {
+                (
+                    attribute.kind,
+                    attribute.component_type,
+                    attribute.component_count,
+                )
+            })
+            .collect();
+
+        VertexLayout::packed(layout)
+    }
+
+    /// Pack the attributes one after the other in the order that they are given.
+    fn packed(layout: Vec<(LayoutAttribute, ComponentType, u8)>) -> VertexLayout {
+        let stride = layout
+            .iter()
+            .map(|(_, component_type, count)| component_type.size_in_bytes() * *count as usize)
+            .sum();
+
+        let mut byte_offset = 0;
+        let attributes = layout
+            .into_iter()
+            .map(|(kind, component_type, component_count)| {
+                let attribute = VertexAttributeLayout {
+                    kind,
+                    component_type,
+                    component_count,
+                    byte_offset,
+                    stride,
+                    normalized: false,
+                };
+                byte_offset += component_type.size_in_bytes() * component_count as usize;
+                attribute
+            })
+            .collect();
+
+        VertexLayout { stride, attributes }
+    }
+}
+
+impl VertexAttributeLayout {
+    /// Which attribute this is.
+    pub fn kind(&self) -> LayoutAttribute {
+        self.kind
+    }
+
+    /// See [`LayoutAttribute.method#name`]
+    pub fn name(&self) -> &'static str {
+        self.kind.name()
+    }
+
+    /// The type of each component.
+    pub fn component_type(&self) -> ComponentType {
+        self.component_type
+    }
+
+    /// The number of components, such as 3 for a position or 2 for a uv.
+    pub fn component_count(&self) -> u8 {
+        self.component_count
+    }
+
+    /// The number of bytes from the start of a vertex to the start of this attribute.
+    pub fn byte_offset(&self) -> usize {
+        self.byte_offset
+    }
+
+    /// The number of bytes from the start of one vertex to the start of the next.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Whether integer components should be normalized to \[0.0, 1.0\] when read by a shader.
+    pub fn normalized(&self) -> bool {
+        self.normalized
+    }
+}
+
+impl SingleIndexedVertexAttributes {
+    /// The layout of the buffer from [`SingleIndexedVertexAttributes.method#interleaved_bytes`],
+    /// based on which attributes the vertices have.
+    ///
+    /// ```
+    /// # use blender_mesh::{BlenderMesh, CreateSingleIndexConfig, LayoutAttribute};
+    /// let single_indexed = BlenderMesh::cube(1.)
+    ///     .combine_vertex_indices(&CreateSingleIndexConfig::default());
+    ///
+    /// let layout = single_indexed.vertex_layout();
+    /// assert_eq!(layout.stride(), 32);
+    /// assert_eq!(layout.attribute(LayoutAttribute::Uv).unwrap().byte_offset(), 24);
+    /// ```
+    pub fn vertex_layout(&self) -> VertexLayout {
+        let present = self.present_attributes();
+
+        let mut layout = vec![(LayoutAttribute::Position, ComponentType::F32, 3)];
+        if present.normals {
+            layout.push((LayoutAttribute::Normal, ComponentType::F32, 3));
+        }
+        if present.uvs {
+            layout.push((LayoutAttribute::Uv, ComponentType::F32, 2));
+        }
+        if present.face_tangents {
+            layout.push((LayoutAttribute::FaceTangent, ComponentType::F32, 3));
+        }
+        if present.bones {
+            layout.push((LayoutAttribute::BoneIndices, ComponentType::U8, 4));
+            layout.push((LayoutAttribute::BoneWeights, ComponentType::F32, 4));
+        }
+
+        VertexLayout::packed(layout)
+    }
+
+    /// All of the vertex data interleaved into one buffer of bytes, laid out as described by
+    /// [`SingleIndexedVertexAttributes.method#vertex_layout`].
+    ///
+    /// Values are in the platform's native byte order, ready to be uploaded to the GPU.
+    pub fn interleaved_bytes(&self) -> Vec<u8> {
+        self.interleaved_bytes_with_endianness(Endianness::native())
+    }
+
+    /// See [`SingleIndexedVertexAttributes.method#interleaved_bytes`]
+    pub(crate) fn interleaved_bytes_with_endianness(&self, endianness: Endianness) -> Vec<u8> {
+        self.interleaved_bytes_with_layout(&self.vertex_layout(), endianness)
+    }
+
+    /// The vertex data interleaved as described by a layout of some or all of the attributes
+    /// that the vertices have.
+    pub(crate) fn interleaved_bytes_with_layout(
+        &self,
+        layout: &VertexLayout,
+        endianness: Endianness,
+    ) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(layout.stride * self.vertices.len());
+        for vertex in self.vertices.iter() {
+            for attribute in layout.attributes.iter() {
+                push_attribute(&mut bytes, vertex, attribute.kind, endianness);
+            }
+        }
+
+        bytes
+    }
+
+    /// The indices as bytes in the platform's native byte order, ready to be uploaded to the GPU
+    /// as an index buffer of `u16`s.
+    pub fn index_bytes(&self) -> Vec<u8> {
+        self.indices
+            .iter()
+            .flat_map(|index| index.to_ne_bytes().to_vec())
+            .collect()
+    }
+}
+
+fn push_attribute(
+    bytes: &mut Vec<u8>,
+    vertex: &Vertex,
+    kind: LayoutAttribute,
+    endianness: Endianness,
+) {
+    let mut push_floats = |floats: &[f32]| {
+        for float in floats {
+            bytes.extend_from_slice(&endianness.f32_bytes(*float));
+        }
+    };
+
+    // Every vertex has the same attributes so the attributes in the layout are always present
+    match kind {
+        LayoutAttribute::Position => push_floats(&vertex.position),
+        LayoutAttribute::Normal => push_floats(&vertex.normal.unwrap()),
+        LayoutAttribute::Uv => push_floats(&vertex.uv.unwrap()),
+        LayoutAttribute::FaceTangent => push_floats(&vertex.face_tangent.unwrap()),
+        LayoutAttribute::BoneIndices => {
+            for bone in vertex.bones.unwrap().iter() {
+                bytes.push(bone.bone_idx);
+            }
+        }
+        LayoutAttribute::BoneWeights => {
+            for bone in vertex.bones.unwrap().iter() {
+                bytes.extend_from_slice(&endianness.f32_bytes(bone.weight));
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoneInfluence;
+
+    /// Verify that the layout only includes the attributes that are present and that the
+    /// interleaved bytes match it.
+    #[test]
+    fn layout_matches_interleaved_bytes() {
+        let bone = BoneInfluence {
+            bone_idx: 7,
+            weight: 0.25,
+        };
+        let vertex = Vertex {
+            position: [1., 2., 3.],
+            uv: Some([0.5, 0.75]),
+            bones: Some([bone; 4]),
+            ..Vertex::default()
+        };
+        let single = SingleIndexedVertexAttributes::new(vec![0, 0, 0], vec![vertex]).unwrap();
+
+        let layout = single.vertex_layout();
+        let offsets: Vec<(&str, usize)> = layout
+            .attributes()
+            .iter()
+            .map(|attribute| (attribute.name(), attribute.byte_offset()))
+            .collect();
+        assert_eq!(
+            offsets,
+            vec![
+                ("position", 0),
+                ("uv", 12),
+                ("bone_indices", 20),
+                ("bone_weights", 24)
+            ]
+        );
+        assert_eq!(layout.stride(), 40);
+        assert_eq!(layout.attribute(LayoutAttribute::Normal), None);
+
+        let bytes = single.interleaved_bytes();
+        assert_eq!(bytes.len(), layout.stride());
+        assert_eq!(&bytes[12..16], &0.5f32.to_ne_bytes());
+        assert_eq!(&bytes[20..24], &[7, 7, 7, 7]);
+        assert_eq!(&bytes[36..40], &0.25f32.to_ne_bytes());
+    }
+}
@@ -38,11 +38,37 @@ impl<T> From<(Vec<T>, u8)> for VertexAttribute<T> {
     }
 }
 
+/// An error while creating a vertex attribute
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum VertexAttributeError {
+    /// The data can't be split evenly into vertices, such as 7 values with an attribute size of 3
+    #[error("{data_len} values cannot be split into vertices of {attribute_size} values each")]
+    DataNotMultipleOfAttributeSize {
+        /// The number of values in the data
+        data_len: usize,
+        /// The number of values per vertex
+        attribute_size: u8,
+    },
+    /// Every vertex needs at least one value
+    #[error("The attribute size must be at least 1")]
+    ZeroAttributeSize,
+}
+
 impl<T> VertexAttribute<T> {
-    /// TODO: Introduce thiserror and add error handling to this library
-    pub fn new(data: Vec<T>, attribute_size: u8) -> Result<VertexAttribute<T>, ()> {
-        if attribute_size as usize % data.len() != 0 {
-            // Return an error ...
+    /// Create a vertex attribute, verifying that the data holds a whole number of vertices.
+    pub fn new(
+        data: Vec<T>,
+        attribute_size: u8,
+    ) -> Result<VertexAttribute<T>, VertexAttributeError> {
+        if attribute_size == 0 {
+            return Err(VertexAttributeError::ZeroAttributeSize);
+        }
+
+        if data.len() % attribute_size as usize != 0 {
+            return Err(VertexAttributeError::DataNotMultipleOfAttributeSize {
+                data_len: data.len(),
+                attribute_size,
+            });
         }
 
         Ok(VertexAttribute {
@@ -67,6 +93,43 @@ impl<T> VertexAttribute<T> {
     pub fn attribute_size(&self) -> u8 {
         self.attribute_size
     }
+
+    /// The number of vertices that there is data for.
+    pub fn len_vertices(&self) -> usize {
+        match self.attribute_size {
+            0 => 0,
+            attribute_size => self.data.len() / attribute_size as usize,
+        }
+    }
+
+    /// The data for a vertex, or `None` if the vertex is out of bounds.
+    pub fn vertex(&self, vertex_idx: usize) -> Option<&[T]> {
+        self.iter_vertices().nth(vertex_idx)
+    }
+
+    /// See [`VertexAttribute::vertex`]
+    pub fn vertex_mut(&mut self, vertex_idx: usize) -> Option<&mut [T]> {
+        self.iter_vertices_mut().nth(vertex_idx)
+    }
+
+    /// Iterate over the data for each vertex, such as the `[x, y, z]` of every position.
+    pub fn iter_vertices(&self) -> impl Iterator<Item = &[T]> {
+        self.data.chunks_exact(self.attribute_size.max(1) as usize)
+    }
+
+    /// See [`VertexAttribute::iter_vertices`]
+    pub fn iter_vertices_mut(&mut self) -> impl Iterator<Item = &mut [T]> {
+        self.data
+            .chunks_exact_mut(self.attribute_size.max(1) as usize)
+    }
+
+    /// Mutable access to the underlying data.
+    ///
+    /// This is a slice so that the number of values can't change, which would leave the data
+    /// without a whole number of vertices.
+    pub fn data_mut(&mut self) -> &mut [T] {
+        &mut self.data[..]
+    }
 }
 
 /// Used for vertex skinning
@@ -76,6 +139,18 @@ pub struct BoneAttributes {
     pub(crate) bone_weights: VertexAttribute<f32>,
 }
 
+impl BoneAttributes {
+    /// The indices of the bones that influence each vertex.
+    pub fn bone_influencers(&self) -> &VertexAttribute<u8> {
+        &self.bone_influencers
+    }
+
+    /// The weight of each of the bones in [`BoneAttributes.method#bone_influencers`].
+    pub fn bone_weights(&self) -> &VertexAttribute<f32> {
+        &self.bone_weights
+    }
+}
+
 impl<T> VertexAttribute<T> {
     /// Get the underlying data for this attribute.
     /// Useful for buffering vertex data onto the GPU
@@ -85,13 +160,58 @@ impl<T> VertexAttribute<T> {
 }
 
 impl<T> VertexAttribute<T> {
-    /// Given a vertex indexm return the data at that index.
+    /// Given a vertex index return the data at that index.
     ///
     /// If there are 3 attributes per vertex the size will be 3, if 2 then 2, etc.
-    pub(crate) fn data_at_idx(&self, vertex_idx: u16) -> &[T] {
+    pub(crate) fn data_at_idx(&self, vertex_idx: u32) -> &[T] {
         let attribute_size = self.attribute_size as usize;
         let idx = (vertex_idx as usize) * attribute_size;
 
         &self.data[idx..idx + attribute_size]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that we only create attributes that hold a whole number of vertices.
+    #[test]
+    fn validate_new_attribute() {
+        assert_eq!(
+            VertexAttribute::new(vec![0.; 7], 3),
+            Err(VertexAttributeError::DataNotMultipleOfAttributeSize {
+                data_len: 7,
+                attribute_size: 3
+            })
+        );
+        assert_eq!(
+            VertexAttribute::new(vec![0.; 3], 0),
+            Err(VertexAttributeError::ZeroAttributeSize)
+        );
+
+        let empty: VertexAttribute<f32> = VertexAttribute::new(vec![], 3).unwrap();
+        assert_eq!(empty.len_vertices(), 0);
+    }
+
+    /// Verify that we iterate over and modify the data one vertex at a time.
+    #[test]
+    fn access_vertices() {
+        let mut uvs = VertexAttribute::new(vec![0., 1., 2., 3., 4., 5.], 2).unwrap();
+
+        assert_eq!(uvs.len_vertices(), 3);
+        assert_eq!(
+            uvs.iter_vertices().collect::<Vec<_>>(),
+            vec![&[0., 1.][..], &[2., 3.], &[4., 5.]]
+        );
+        assert_eq!(uvs.vertex(1), Some(&[2., 3.][..]));
+        assert_eq!(uvs.vertex(3), None);
+
+        for uv in uvs.iter_vertices_mut() {
+            uv[1] = 1. - uv[1];
+        }
+        uvs.vertex_mut(0).unwrap()[0] = 10.;
+
+        assert_eq!(uvs.data(), &vec![10., 0., 2., -2., 4., -4.]);
+    }
+}
@@ -0,0 +1,37 @@
+use crate::BlenderMesh;
+
+/// Whether the object that a mesh was exported from is hidden in Blender, such as for helper
+/// geometry and reference objects that shouldn't end up in a game build.
+///
+/// `landon export --skip-hide-render` and `--skip-hide-viewport` leave these objects out
+/// entirely.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ObjectVisibility {
+    /// The object is disabled in renders.
+    #[serde(default)]
+    pub hide_render: bool,
+    /// The object is hidden or disabled in the viewport, either by itself or along with one of
+    /// its collections.
+    #[serde(default)]
+    pub hide_viewport: bool,
+}
+
+impl ObjectVisibility {
+    /// Whether the object is visible both in renders and in the viewport.
+    pub fn is_visible(&self) -> bool {
+        !self.hide_render && !self.hide_viewport
+    }
+}
+
+impl BlenderMesh {
+    /// Whether the mesh's object is hidden in Blender. Visible if the mesh was not exported from
+    /// Blender.
+    pub fn visibility(&self) -> ObjectVisibility {
+        self.visibility
+    }
+
+    /// Set whether the mesh's object is hidden.
+    pub fn set_visibility(&mut self, visibility: ObjectVisibility) {
+        self.visibility = visibility;
+    }
+}
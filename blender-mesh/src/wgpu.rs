@@ -0,0 +1,188 @@
+//! Create [wgpu] buffers from single indexed vertex data.
+//!
+//! Enabled by the `wgpu` feature.
+//!
+//! ```no_run
+//! # use blender_mesh::{BlenderMesh, CreateSingleIndexConfig};
+//! # fn example(device: &wgpu::Device) {
+//! let mesh = BlenderMesh::cube(1.).combine_vertex_indices(&CreateSingleIndexConfig::default());
+//!
+//! let buffers = blender_mesh::wgpu::create_vertex_buffers(device, &mesh);
+//! let layout = blender_mesh::wgpu::vertex_buffer_layout(&mesh);
+//!
+//! // Use `layout.buffer_layout()` when creating your render pipeline, then
+//! // `buffers.vertex_buffer()` and `buffers.index_buffer()` when drawing.
+//! # }
+//! ```
+//!
+//! [wgpu]: https://github.com/gfx-rs/wgpu
+
+use crate::{ComponentType, SingleIndexedVertexAttributes, VertexLayout};
+use ::wgpu::util::DeviceExt;
+
+/// The GPU buffers for a mesh.
+#[derive(Debug)]
+pub struct MeshBuffers {
+    pub(crate) vertex_buffer: ::wgpu::Buffer,
+    pub(crate) index_buffer: ::wgpu::Buffer,
+    pub(crate) index_count: u32,
+}
+
+impl MeshBuffers {
+    /// The interleaved vertex data, laid out as described by [`vertex_buffer_layout`].
+    pub fn vertex_buffer(&self) -> &::wgpu::Buffer {
+        &self.vertex_buffer
+    }
+
+    /// The mesh's indices. See [`MeshBuffers.method#index_format`]
+    pub fn index_buffer(&self) -> &::wgpu::Buffer {
+        &self.index_buffer
+    }
+
+    /// The number of indices in the index buffer.
+    pub fn index_count(&self) -> u32 {
+        self.index_count
+    }
+
+    /// The format of the indices in the index buffer.
+    pub fn index_format(&self) -> ::wgpu::IndexFormat {
+        ::wgpu::IndexFormat::Uint16
+    }
+}
+
+/// The vertex attributes of an interleaved vertex buffer.
+///
+/// `wgpu::VertexBufferLayout` borrows its attributes, so this owns them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedVertexBufferLayout {
+    pub(crate) array_stride: ::wgpu::BufferAddress,
+    pub(crate) attributes: Vec<::wgpu::VertexAttribute>,
+}
+
+impl OwnedVertexBufferLayout {
+    /// The layout to use when creating a render pipeline.
+    pub fn buffer_layout(&self) -> ::wgpu::VertexBufferLayout<'_> {
+        ::wgpu::VertexBufferLayout {
+            array_stride: self.array_stride,
+            step_mode: ::wgpu::VertexStepMode::Vertex,
+            attributes: &self.attributes,
+        }
+    }
+
+    /// Every attribute, each at its [`LayoutAttribute.method#shader_location`].
+    ///
+    /// [`LayoutAttribute.method#shader_location`]: ../enum.LayoutAttribute.html#method.shader_location
+    pub fn attributes(&self) -> &Vec<::wgpu::VertexAttribute> {
+        &self.attributes
+    }
+}
+
+/// Upload the mesh's interleaved vertex data and indices to the GPU.
+pub fn create_vertex_buffers(
+    device: &::wgpu::Device,
+    mesh: &SingleIndexedVertexAttributes,
+) -> MeshBuffers {
+    let vertex_buffer = device.create_buffer_init(&::wgpu::util::BufferInitDescriptor {
+        label: Some("blender-mesh vertex buffer"),
+        contents: &mesh.interleaved_bytes(),
+        usage: ::wgpu::BufferUsages::VERTEX,
+    });
+
+    let index_buffer = device.create_buffer_init(&::wgpu::util::BufferInitDescriptor {
+        label: Some("blender-mesh index buffer"),
+        contents: &mesh.index_bytes(),
+        usage: ::wgpu::BufferUsages::INDEX,
+    });
+
+    MeshBuffers {
+        vertex_buffer,
+        index_buffer,
+        index_count: mesh.indices().len() as u32,
+    }
+}
+
+/// The layout of the vertex buffer from [`create_vertex_buffers`].
+pub fn vertex_buffer_layout(mesh: &SingleIndexedVertexAttributes) -> OwnedVertexBufferLayout {
+    OwnedVertexBufferLayout::from(&mesh.vertex_layout())
+}
+
+impl From<&VertexLayout> for OwnedVertexBufferLayout {
+    fn from(layout: &VertexLayout) -> Self {
+        let attributes = layout
+            .attributes()
+            .iter()
+            .map(|attribute| ::wgpu::VertexAttribute {
+                format: vertex_format(
+                    attribute.component_type(),
+                    attribute.component_count(),
+                    attribute.normalized(),
+                ),
+                offset: attribute.byte_offset() as ::wgpu::BufferAddress,
+                shader_location: attribute.kind().shader_location(),
+            })
+            .collect();
+
+        OwnedVertexBufferLayout {
+            array_stride: layout.stride() as ::wgpu::BufferAddress,
+            attributes,
+        }
+    }
+}
+
+fn vertex_format(
+    component_type: ComponentType,
+    component_count: u8,
+    normalized: bool,
+) -> ::wgpu::VertexFormat {
+    use ::wgpu::VertexFormat;
+
+    match (component_type, component_count, normalized) {
+        (ComponentType::F32, 1, _) => VertexFormat::Float32,
+        (ComponentType::F32, 2, _) => VertexFormat::Float32x2,
+        (ComponentType::F32, 3, _) => VertexFormat::Float32x3,
+        (ComponentType::F32, 4, _) => VertexFormat::Float32x4,
+        (ComponentType::U8, 4, false) => VertexFormat::Uint8x4,
+        (ComponentType::U8, 4, true) => VertexFormat::Unorm8x4,
+        _ => unreachable!("Vertex layouts only contain attributes that wgpu supports"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoneInfluence, Vertex};
+
+    /// Verify that every attribute gets the right format, offset and shader location.
+    #[test]
+    fn wgpu_vertex_buffer_layout() {
+        let vertex = Vertex {
+            normal: Some([0., 0., 1.]),
+            bones: Some(
+                [BoneInfluence {
+                    bone_idx: 0,
+                    weight: 0.25,
+                }; 4],
+            ),
+            ..Vertex::default()
+        };
+        let mesh = SingleIndexedVertexAttributes::new(vec![0, 0, 0], vec![vertex]).unwrap();
+
+        let layout = vertex_buffer_layout(&mesh);
+        let attributes: Vec<_> = layout
+            .attributes()
+            .iter()
+            .map(|a| (a.format, a.offset, a.shader_location))
+            .collect();
+
+        assert_eq!(layout.buffer_layout().array_stride, 44);
+        assert_eq!(
+            attributes,
+            vec![
+                (::wgpu::VertexFormat::Float32x3, 0, 0),
+                (::wgpu::VertexFormat::Float32x3, 12, 1),
+                (::wgpu::VertexFormat::Uint8x4, 24, 4),
+                (::wgpu::VertexFormat::Float32x4, 28, 5),
+            ]
+        );
+    }
+}
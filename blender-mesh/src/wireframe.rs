@@ -0,0 +1,128 @@
+use crate::BlenderMesh;
+use nalgebra::Vector3;
+use std::collections::BTreeMap;
+
+impl BlenderMesh {
+    /// Every edge of the triangulated mesh once, as pairs of position indices that can be drawn
+    /// as a line list, followed by the mesh's loose edges.
+    ///
+    /// Edges are ordered by their position indices, with the smallest position index first, so
+    /// the same mesh always gives the same index buffer.
+    ///
+    /// Quads are split into triangles first, so their diagonals are included. Use
+    /// [`BlenderMesh.method#feature_edge_indices`] to leave out edges between faces that lie flat
+    /// against each other.
+    pub fn edge_indices(&self) -> Vec<u32> {
+        let mut edge_indices = vec![];
+
+        for edge in self.triangle_edges().keys() {
+            edge_indices.extend_from_slice(edge);
+        }
+        self.push_loose_edges(&mut edge_indices);
+
+        edge_indices
+    }
+
+    /// The edges of the triangulated mesh where its surface creases by more than `angle` radians,
+    /// along with its boundary edges, its non manifold edges and its loose edges.
+    ///
+    /// Useful for drawing outlines and CAD style wireframes without the edges that run across
+    /// flat or smooth surfaces.
+    ///
+    /// Ordered the same way as [`BlenderMesh.method#edge_indices`].
+    pub fn feature_edge_indices(&self, angle: f32) -> Vec<u32> {
+        let mut edge_indices = vec![];
+
+        for (edge, face_normals) in self.triangle_edges() {
+            let is_feature = match face_normals.as_slice() {
+                [a, b] => a.angle(b) > angle,
+                _ => true,
+            };
+
+            if is_feature {
+                edge_indices.extend_from_slice(&edge);
+            }
+        }
+        self.push_loose_edges(&mut edge_indices);
+
+        edge_indices
+    }
+
+    /// Every edge of the triangulated mesh, smallest position index first, along with the normals
+    /// of the triangles that share it.
+    fn triangle_edges(&self) -> BTreeMap<[u32; 2], Vec<Vector3<f32>>> {
+        let multi = &self.multi_indexed_vertex_attributes;
+        let positions = &multi.positions.attribute;
+
+        let mut edges: BTreeMap<[u32; 2], Vec<Vector3<f32>>> = BTreeMap::new();
+
+        for triangle in self.triangulate(&multi.positions.indices).chunks(3) {
+            let [a, b, c] = [
+                Vector3::from_column_slice(positions.data_at_idx(triangle[0])),
+                Vector3::from_column_slice(positions.data_at_idx(triangle[1])),
+                Vector3::from_column_slice(positions.data_at_idx(triangle[2])),
+            ];
+            let normal = (b - a).cross(&(c - a));
+
+            for idx in 0..3 {
+                let (start, end) = (triangle[idx], triangle[(idx + 1) % 3]);
+                if start == end {
+                    continue;
+                }
+
+                edges
+                    .entry([start.min(end), start.max(end)])
+                    .or_default()
+                    .push(normal);
+            }
+        }
+
+        edges
+    }
+
+    fn push_loose_edges(&self, edge_indices: &mut Vec<u32>) {
+        for [start, end] in self.edges.iter() {
+            edge_indices.extend_from_slice(&[*start.min(end), *start.max(end)]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    /// Verify that every edge of a triangulated cube is included once.
+    #[test]
+    fn cube_edges() {
+        let cube = BlenderMesh::cube(1.);
+
+        let edge_indices = cube.edge_indices();
+
+        // 12 edges along the sides of the cube and one diagonal across each of the 6 faces
+        assert_eq!(edge_indices.len(), 18 * 2);
+
+        let mut edges: Vec<&[u32]> = edge_indices.chunks(2).collect();
+        assert!(edges.iter().all(|edge| edge[0] < edge[1]));
+        edges.dedup();
+        assert_eq!(edges.len(), 18);
+    }
+
+    /// Verify that feature edges leave out the diagonals across flat faces but keep creases,
+    /// boundaries and loose edges.
+    #[test]
+    fn feature_edges() {
+        let cube = BlenderMesh::cube(1.);
+        assert_eq!(cube.feature_edge_indices(PI / 6.).len(), 12 * 2);
+        assert_eq!(cube.feature_edge_indices(PI).len(), 0);
+
+        // Positions (-1, -1), (1, -1), (-1, 1) and (1, 1)
+        let mut plane = BlenderMesh::plane(2., 2., 0);
+        plane.edges = vec![[3, 0]];
+
+        assert_eq!(
+            plane.feature_edge_indices(PI / 6.),
+            vec![0, 1, 0, 2, 1, 3, 2, 3, 0, 3]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use crate::{MeshKey, MeshesByFilename};
+use std::collections::BTreeMap;
+
+/// One cell of the grid that [`rebase_origins`] splits a large scene into, such as for streaming
+/// in the parts of an open world that are near the player.
+///
+/// [`rebase_origins`]: fn.rebase_origins.html
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorldCell {
+    pub(crate) cell: [i64; 3],
+    pub(crate) origin: [f64; 3],
+    pub(crate) meshes: BTreeMap<String, Vec<MeshKey>>,
+}
+
+impl WorldCell {
+    /// The cell's position in the grid, counting from the cell whose corner is at the world
+    /// origin.
+    pub fn cell(&self) -> [i64; 3] {
+        self.cell
+    }
+
+    /// The center of the cell, which the positions of all of its meshes are relative to.
+    pub fn origin(&self) -> [f64; 3] {
+        self.origin
+    }
+
+    /// The keys of the meshes in the cell, sorted, by the file that they were exported from.
+    pub fn meshes(&self) -> &BTreeMap<String, Vec<MeshKey>> {
+        &self.meshes
+    }
+}
+
+/// Split the meshes across all of the files into a grid of cells that are `cell_size` units
+/// wide, and rebase every mesh onto the center of the cell that the
+/// [`BlenderMesh.method#positions_center`] is in with [`BlenderMesh.method#rebase_origin`].
+///
+/// Engines with a floating origin can then place every cell relative to the camera in double
+/// precision while the meshes in it keep small, precise positions.
+///
+/// Returns the cells that have meshes in them, ordered by their position in the grid.
+///
+/// # Panics
+///
+/// Panics if `cell_size` isn't positive.
+pub fn rebase_origins(meshes_by_filename: &mut MeshesByFilename, cell_size: f64) -> Vec<WorldCell> {
+    assert!(cell_size > 0., "Cells must be larger than zero");
+
+    let mut cells: BTreeMap<[i64; 3], WorldCell> = BTreeMap::new();
+
+    for (filename, meshes) in meshes_by_filename.iter_mut() {
+        for (mesh_key, mesh) in meshes.iter_mut() {
+            let center = mesh.positions_center();
+            let cell_of = |axis: usize| (center[axis] / cell_size).floor() as i64;
+            let cell = [cell_of(0), cell_of(1), cell_of(2)];
+            let cell_center = |axis: usize| (cell[axis] as f64 + 0.5) * cell_size;
+
+            let world_cell = cells.entry(cell).or_insert_with(|| WorldCell {
+                cell,
+                origin: [cell_center(0), cell_center(1), cell_center(2)],
+                meshes: BTreeMap::new(),
+            });
+            world_cell
+                .meshes
+                .entry(filename.clone())
+                .or_default()
+                .push(mesh_key.clone());
+
+            mesh.rebase_origin(world_cell.origin);
+        }
+    }
+
+    let mut cells: Vec<WorldCell> = cells.into_values().collect();
+    for cell in cells.iter_mut() {
+        for keys in cell.meshes.values_mut() {
+            keys.sort();
+        }
+    }
+
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BlenderMesh;
+    use nalgebra::{Matrix4, Vector3};
+
+    /// Verify that meshes are grouped by the cell that their center is in and that their
+    /// positions stay where they were in the world.
+    #[test]
+    fn group_meshes_into_cells() {
+        let placed = |x: f64, y: f64| {
+            let mut cube = BlenderMesh::cube(1.);
+            cube.apply_transform_f64(&Matrix4::new_translation(&Vector3::new(x, y, 0.)));
+            cube
+        };
+
+        let mut meshes = MeshesByFilename::new();
+        meshes.insert(
+            "town.blend".to_string(),
+            vec![
+                ("Well".into(), placed(10., 10.)),
+                ("Barn".into(), placed(90., 10.)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        meshes.insert(
+            "forest.blend".to_string(),
+            vec![("Tree".into(), placed(5_000.5, -20.))]
+                .into_iter()
+                .collect(),
+        );
+
+        let cells = rebase_origins(&mut meshes, 100.);
+
+        assert_eq!(cells.len(), 2);
+        assert_eq!(cells[0].cell(), [0, 0, 0]);
+        assert_eq!(cells[0].origin(), [50., 50., 50.]);
+        assert_eq!(
+            cells[0].meshes()["town.blend"],
+            vec![MeshKey::from("Barn"), MeshKey::from("Well")]
+        );
+        assert_eq!(cells[1].cell(), [50, -1, 0]);
+
+        let tree = &meshes["forest.blend"]["Tree"];
+        assert_eq!(tree.space().origin(), [5_050., -50., 50.]);
+        assert_eq!(tree.positions_f64()[0..3], [5_000., -20.5, -0.5]);
+    }
+
+    /// Verify that a mesh is put in the cell that its positions are in, and not the cell that its
+    /// bounding box is in, which is in world space.
+    #[test]
+    fn cell_of_placed_object() {
+        let mut cube = BlenderMesh::cube(1.);
+        cube.apply_transform_f64(&Matrix4::new_translation(&Vector3::new(10., 10., 10.)));
+
+        let world_transform = Matrix4::new_translation(&Vector3::new(300., 0., 0.));
+        let mut world_cube = cube.clone();
+        world_cube.apply_transform(&world_transform.map(|value| value as f32));
+        cube.set_bounding_box(world_cube.bounding_box());
+        cube.set_world_transform(Some(world_transform));
+
+        let mut meshes = MeshesByFilename::new();
+        meshes.insert(
+            "town.blend".to_string(),
+            vec![("Well".into(), cube)].into_iter().collect(),
+        );
+
+        let cells = rebase_origins(&mut meshes, 100.);
+
+        assert_eq!(cells.len(), 1);
+        assert_eq!(cells[0].cell(), [0, 0, 0]);
+
+        let well = &meshes["town.blend"]["Well"];
+        assert_eq!(well.space().origin(), [50., 50., 50.]);
+        assert_eq!(well.positions_f64()[0..3], [9.5, 9.5, 9.5]);
+    }
+}
@@ -1,4 +1,6 @@
-use crate::BlenderMesh;
+use crate::simd;
+use crate::{BlenderMesh, UpAxis};
+use nalgebra::Matrix4;
 
 static Y: usize = 1;
 static Z: usize = 2;
@@ -7,6 +9,9 @@ impl BlenderMesh {
     /// Blender meshes get exported with a Z up coordinate system.
     /// Here we flip our coordinate system to be y up
     ///
+    /// This doesn't check whether the mesh is already y up, so check
+    /// [`SpaceConvention.method#up_axis`] first if the mesh might have already been converted.
+    ///
     /// @see https://gamedev.stackexchange.com/a/7932
     ///
     /// TODO: When we have bone data we'll need to change them to port change-mat4-coordinate-system
@@ -15,30 +20,14 @@ impl BlenderMesh {
     pub fn y_up(&mut self) {
         let vertex_attribs = &mut self.multi_indexed_vertex_attributes;
 
-        let positions = &mut vertex_attribs.positions.attribute.data;
-
-        let mut normals = match &mut vertex_attribs.normals {
-            None => None,
-            Some(normals) => Some(&mut normals.attribute.data),
-        };
+        simd::y_up(&mut vertex_attribs.positions.attribute.data);
 
-        let convert = |vert_num: usize, data: &mut Vec<f32>| {
-            let y_index = vert_num * 3 + 1;
-            let z_index = y_index + 1;
-
-            let new_z = -data[y_index];
-            data[y_index] = data[z_index];
-            data[z_index] = new_z;
-        };
-
-        for vert_num in 0..positions.len() / 3 {
-            convert(vert_num, positions);
+        if let Some(normals) = vertex_attribs.normals.as_mut() {
+            simd::y_up(&mut normals.attribute.data);
         }
 
-        if let Some(normals) = normals.as_mut() {
-            for vert_num in 0..normals.len() / 3 {
-                convert(vert_num, normals);
-            }
+        for shape_key in self.shape_keys.iter_mut() {
+            simd::y_up(&mut shape_key.position_deltas.data);
         }
 
         let new_z = -self.bounding_box.min_corner[Y];
@@ -48,6 +37,24 @@ impl BlenderMesh {
         let new_z = -self.bounding_box.max_corner[Y];
         self.bounding_box.max_corner[Y] = self.bounding_box.max_corner[Z];
         self.bounding_box.max_corner[Z] = new_z;
+
+        let [x, y, z] = self.space.origin;
+        self.space.origin = [x, z, -y];
+
+        // The new +Y axis is the old +Z axis and the new +Z axis is the old -Y axis
+        if let Some(world_transform) = self.world_transform.as_mut() {
+            let z_up_to_y_up = Matrix4::new(
+                1., 0., 0., 0., //
+                0., 0., 1., 0., //
+                0., -1., 0., 0., //
+                0., 0., 0., 1.,
+            );
+            *world_transform = z_up_to_y_up * *world_transform * z_up_to_y_up.transpose();
+        }
+
+        self.space.up_axis = UpAxis::Y;
+
+        self.invalidate_bvh();
     }
 }
 
@@ -57,6 +64,7 @@ mod tests {
     use crate::bounding_box::BoundingBox;
     use crate::indexed;
     use crate::vertex_attributes::MultiIndexedVertexAttributes;
+    use crate::SpaceConvention;
     use nalgebra::Point3;
 
     #[test]
@@ -87,9 +95,34 @@ mod tests {
                 min_corner: Point3::new(1.0, 3.0, -2.0),
                 max_corner: Point3::new(5.0, 7.0, -6.0),
             },
+            space: SpaceConvention {
+                up_axis: UpAxis::Y,
+                ..SpaceConvention::default()
+            },
             ..BlenderMesh::default()
         };
 
         assert_eq!(y_up_mesh, expected_mesh);
     }
+
+    /// Verify that the world transform places the y up positions where the z up positions were
+    /// placed, converted to y up.
+    #[test]
+    fn world_transform_to_y_up() {
+        let mut mesh = BlenderMesh::cube(1.);
+        let world_transform = Matrix4::new_translation(&nalgebra::Vector3::new(1., 2., 3.))
+            * Matrix4::new_rotation(nalgebra::Vector3::new(0., 0., 0.5));
+        mesh.set_world_transform(Some(world_transform));
+
+        let z_up_world = world_transform.transform_point(&Point3::new(0.5, 0.5, 0.5));
+        mesh.y_up();
+        let y_up_world = mesh
+            .world_transform()
+            .unwrap()
+            .transform_point(&Point3::new(0.5, 0.5, -0.5));
+
+        assert!(
+            (y_up_world - Point3::new(z_up_world.x, z_up_world.z, -z_up_world.y)).norm() < 1e-9
+        );
+    }
 }
@@ -0,0 +1,219 @@
+    # Print all of the transform actions for the active object, and the active camera's track, to stdout as JSON
+
+bl_info = {
+    "name": "Export Object Animation to JSON",
+    "category": "Import-Export",
+    "blender": (2, 80, 0)
+}
+
+import bpy
+import math
+import mathutils
+import json
+
+# The F-Curves that animate an object's transform
+TRANSFORM_PROPERTIES = [
+    "location", "rotation_euler", "rotation_quaternion", "rotation_axis_angle", "scale",
+    "delta_location", "delta_rotation_euler", "delta_rotation_quaternion", "delta_scale"
+]
+
+class ExportObjectAnimationToJSON(bpy.types.Operator):
+    """Given an active object, export the transform keyframes of its actions to a JSON file"""
+    # Unique identifier for the addon
+    bl_idname = 'import_export.objectanimation2json'
+    # Display name in the interface
+    bl_label = 'Export Object Animation to JSON'
+    bl_options = {'REGISTER'}
+    bl_category = 'Import-Export'
+
+    def execute(self, context):
+        def main():
+            activeObject = bpy.context.view_layer.objects.active
+
+            if activeObject is None:
+                return {'FINISHED'}
+
+            # A camera's lens can be animated without the camera object itself being animated
+            if activeObject.type == 'CAMERA':
+                exportCameraTrack(activeObject)
+
+            # Objects that aren't animated have nothing else to export
+            if activeObject.animation_data is None:
+                return {'FINISHED'}
+
+            animationData = activeObject.animation_data
+
+            objectJSON = {
+                'name': activeObject.name,
+                'parent': activeObject.parent.name if activeObject.parent is not None else None,
+                'actions': {},
+                'frames_per_second': round(bpy.context.scene.render.fps / bpy.context.scene.render.fps_base)
+            }
+
+            # The object's active action along with any actions in its NLA strips
+            actions = []
+            if animationData.action is not None:
+                actions.append(animationData.action)
+            for track in animationData.nla_tracks:
+                for strip in track.strips:
+                    if strip.action is not None and strip.action not in actions:
+                        actions.append(strip.action)
+
+            originalAction = animationData.action
+            originalFrame = bpy.context.scene.frame_current
+
+            for action in actions:
+                frames = getTransformKeyframesInAction(action)
+                # If this action doesn't animate the object's transform we skip it
+                if frames == []:
+                    continue
+
+                animationData.action = action
+
+                keyframes = []
+                hasScale = False
+
+                for frame in frames:
+                    bpy.context.scene.frame_set(frame)
+
+                    # The object's transform relative to its parent
+                    loc, rot, scale = activeObject.matrix_basis.decompose()
+
+                    mat_loc = mathutils.Matrix.Translation(loc)
+                    mat_rot = rot.to_matrix().to_4x4()
+
+                    # Scale is exported separately from the matrix since dual quaternions can't
+                    # represent non uniform scale
+                    if any(abs(axis - 1.0) > 1e-6 for axis in scale):
+                        hasScale = True
+
+                    keyframes.append({
+                        'frame': frame,
+                        'bone': {'Matrix': matrixToArray(mat_loc @ mat_rot)},
+                        'scale': [scale[0], scale[1], scale[2]]
+                    })
+
+                if not hasScale:
+                    for keyframe in keyframes:
+                        del keyframe['scale']
+
+                pose_markers = {}
+                for pose_marker in action.pose_markers:
+                    pose_markers[pose_marker.frame] = pose_marker.name
+
+                # Actions are stored the same way as armature actions, with the object's transform
+                # as the only bone
+                objectJSON['actions'][action.name] = {
+                    'bone_keyframes': {
+                        'frame_range_inclusive': [frames[0], frames[-1]],
+                        'keyframes': {0: keyframes}
+                    },
+                    'keyframes': [],
+                    'pose_markers': pose_markers
+                }
+
+            animationData.action = originalAction
+            bpy.context.scene.frame_set(originalFrame)
+
+            # START_OBJECT_ANIMATION_JSON $BLENDER_FILEPATH $OBJECT_NAME
+            # ... object animation json ...
+            # END_OBJECT_ANIMATION_JSON $BLENDER_FILEPATH $OBJECT_NAME
+            #
+            # NOTE: Intentionally done in one print statement so that other Blender output doesn't
+            # get mixed in with our JSON output
+            output = "START_OBJECT_ANIMATION_JSON " + bpy.data.filepath + " " + activeObject.name
+            output += "\n"
+            output += json.dumps(objectJSON)
+            output += "\n"
+            output += "END_OBJECT_ANIMATION_JSON " + bpy.data.filepath + " " + activeObject.name
+            print(output)
+
+            return {'FINISHED'}
+
+        # Sample the camera's world transform and lens on every frame of the scene so that
+        # parenting, constraints, drivers and animated lenses are all included.
+        def exportCameraTrack(cameraObject):
+            scene = bpy.context.scene
+            originalFrame = scene.frame_current
+
+            camera = cameraObject.data
+
+            aspectRatio = (scene.render.resolution_x * scene.render.pixel_aspect_x) / \
+                          (scene.render.resolution_y * scene.render.pixel_aspect_y)
+
+            cameraJSON = {
+                'name': cameraObject.name,
+                'frames_per_second': round(scene.render.fps / scene.render.fps_base),
+                'aspect_ratio': aspectRatio,
+                'frames': []
+            }
+
+            for frame in range(scene.frame_start, scene.frame_end + 1):
+                scene.frame_set(frame)
+
+                loc, rot, _scale = cameraObject.matrix_world.decompose()
+
+                cameraJSON['frames'].append({
+                    'frame': frame,
+                    'translation': [loc[0], loc[1], loc[2]],
+                    # w, x, y, z
+                    'rotation': [rot[0], rot[1], rot[2], rot[3]],
+                    'fov_y': verticalFieldOfView(camera, aspectRatio),
+                    'near': camera.clip_start,
+                    'far': camera.clip_end
+                })
+
+            scene.frame_set(originalFrame)
+
+            # START_CAMERA_TRACK_JSON $BLENDER_FILEPATH $CAMERA_NAME
+            # ... camera track json ...
+            # END_CAMERA_TRACK_JSON $BLENDER_FILEPATH $CAMERA_NAME
+            output = "START_CAMERA_TRACK_JSON " + bpy.data.filepath + " " + cameraObject.name
+            output += "\n"
+            output += json.dumps(cameraJSON)
+            output += "\n"
+            output += "END_CAMERA_TRACK_JSON " + bpy.data.filepath + " " + cameraObject.name
+            print(output)
+
+        # Blender's field of view applies to whichever side of the sensor is fitted to the render,
+        # so we convert it into a vertical field of view.
+        def verticalFieldOfView(camera, aspectRatio):
+            fitsWidth = camera.sensor_fit == 'HORIZONTAL' or (camera.sensor_fit == 'AUTO' and aspectRatio >= 1)
+
+            if camera.sensor_fit == 'VERTICAL':
+                return camera.angle_y
+            elif fitsWidth:
+                return 2 * math.atan(math.tan(camera.angle_x / 2) / aspectRatio)
+            else:
+                return camera.angle
+
+        # The sorted frames that any of the action's transform F-Curves have a keyframe on
+        def getTransformKeyframesInAction(action):
+            keyframes = set()
+            for fcurve in action.fcurves:
+                if fcurve.data_path not in TRANSFORM_PROPERTIES:
+                    continue
+
+                for keyframe in fcurve.keyframe_points:
+                    x, y = keyframe.co
+                    keyframes.add(math.ceil(x))
+            return sorted(keyframes)
+
+        def matrixToArray (matrix):
+            array = []
+            for row in range(0, 4):
+                for column in range(0, 4):
+                    array.append(matrix[row][column])
+            return array
+
+        # Run our objectanimation2json() add on
+        return main()
+
+def register():
+    bpy.utils.register_class(ExportObjectAnimationToJSON)
+
+def unregister():
+    bpy.utils.unregister_class(ExportObjectAnimationToJSON)
+
+if __name__ == "__main__":
+    register()
@@ -41,13 +41,15 @@ fn expected_mesh_data() -> String {
                     }
                 }
             },
-            "armature_name": null,
+            "armatures": [],
             "bounding_box": {
                 "min_corner": [-1.7881393432617188e-07, -2.980232238769531e-07, 0.0],
                 "max_corner": [1.000000238418579, 1.000000238418579, 1.0]
             },
             "materials": {},
-            "custom_properties": {}
+            "custom_properties": {},
+            "smoothing_groups": [ 0, 0, 0, 0, 0, 0 ],
+            "world_transform": [0.5, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.5, 0.5, 0.5, 1.0]
         }
     "#.to_string()
 }
@@ -86,7 +86,9 @@ fn expected_mesh_data() -> String {
                         {"String": "World"}
                     ]
                 }
-            }
+            },
+            "smoothing_groups": [ 0, 0, 0, 0, 0, 0 ],
+            "world_transform": [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0]
         }
     "#.to_string()
 }
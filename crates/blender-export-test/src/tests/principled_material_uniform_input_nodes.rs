@@ -63,7 +63,7 @@ fn expected_mesh_data() -> String {
                     }
                 }
             },        
-            "armature_name": null,
+            "armatures": [],
             "bounding_box": {
                 "min_corner": [-1.0000004, -1.0000006, -1.0],
                 "max_corner": [1.0000005, 1.0000004, 1.0]
@@ -74,7 +74,9 @@ fn expected_mesh_data() -> String {
                     "metallic": {"Uniform": 0.2},
                     "roughness": {"Uniform": 0.3}
                 }
-            }
+            },
+            "smoothing_groups": [ 0, 0, 0, 0, 0, 0 ],
+            "world_transform": [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0]
         }
     "#.to_string()
 }
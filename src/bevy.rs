@@ -0,0 +1,361 @@
+//! Load the JSON that `landon export` writes straight into [Bevy].
+//!
+//! Enabled by the `bevy` feature.
+//!
+//! ```ignore
+//! App::new()
+//!     .add_plugins(DefaultPlugins)
+//!     .add_plugin(LandonPlugin)
+//!     .add_startup_system(load);
+//!
+//! fn load(asset_server: Res<AssetServer>) {
+//!     // A single mesh
+//!     let mesh: Handle<Mesh> = asset_server.load("character.landon.json#Mesh/Body");
+//!     // Every mesh and armature in the file
+//!     let export: Handle<LandonExport> = asset_server.load("character.landon.json");
+//! }
+//! ```
+//!
+//! Files are matched by their `.landon.json` extension so that they don't clash with other
+//! JSON loaders.
+//!
+//! [Bevy]: https://bevyengine.org
+
+use ::bevy::app::{App, Plugin};
+use ::bevy::asset::{AddAsset, AssetLoader, Handle, LoadContext, LoadedAsset};
+use ::bevy::ecs::entity::Entity;
+use ::bevy::ecs::system::Commands;
+use ::bevy::math::{Mat4, Vec4};
+use ::bevy::reflect::TypeUuid;
+use ::bevy::render::mesh::skinning::{SkinnedMesh, SkinnedMeshInverseBindposes};
+use ::bevy::render::mesh::{Indices, Mesh};
+use ::bevy::render::render_resource::PrimitiveTopology;
+use ::bevy::transform::components::{GlobalTransform, Transform};
+use ::bevy::utils::BoxedFuture;
+use blender_armature::{ArmaturesByFilename, BlenderArmature, Bone};
+use blender_mesh::{
+    BlenderMesh, CombineIndicesError, CreateSingleIndexConfig, MeshKind, MeshesByFilename,
+    SingleIndexedVertexAttributes,
+};
+use std::collections::HashMap;
+
+/// Registers the [`LandonLoader`] and the [`LandonExport`] asset.
+#[derive(Debug, Default)]
+pub struct LandonPlugin;
+
+impl Plugin for LandonPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<LandonExport>()
+            .init_asset_loader::<LandonLoader>();
+    }
+}
+
+/// Loads `.landon.json` files that were written by `landon export`.
+///
+/// Every mesh is labeled `Mesh/{mesh name}` and every armature's inverse bind poses are labeled
+/// `InverseBindposes/{armature name}`, so mesh and armature names should be unique across all of
+/// the Blender files in one export.
+///
+/// Meshes and armatures are converted from Blender's Z up coordinate system to Bevy's Y up.
+#[derive(Debug, Default)]
+pub struct LandonLoader;
+
+/// Everything that was loaded from a `.landon.json` file.
+#[derive(Debug, TypeUuid)]
+#[uuid = "ba152e5f-fa6f-4adc-a25f-245c3061f80e"]
+pub struct LandonExport {
+    pub(crate) meshes: HashMap<String, LandonMesh>,
+    pub(crate) armatures: HashMap<String, LandonArmature>,
+}
+
+/// A mesh that was loaded from a landon export.
+#[derive(Debug, Clone)]
+pub struct LandonMesh {
+    pub(crate) mesh: Handle<Mesh>,
+    pub(crate) armature_name: Option<String>,
+}
+
+/// An armature that was loaded from a landon export.
+#[derive(Debug, Clone)]
+pub struct LandonArmature {
+    pub(crate) inverse_bindposes: Handle<SkinnedMeshInverseBindposes>,
+    pub(crate) bind_poses: Vec<Mat4>,
+}
+
+impl LandonExport {
+    /// Every mesh, by mesh name.
+    pub fn meshes(&self) -> &HashMap<String, LandonMesh> {
+        &self.meshes
+    }
+
+    /// Every armature, by armature name.
+    pub fn armatures(&self) -> &HashMap<String, LandonArmature> {
+        &self.armatures
+    }
+
+    /// The armature that deforms a mesh, if the mesh has one.
+    pub fn mesh_armature(&self, mesh_name: &str) -> Option<&LandonArmature> {
+        self.meshes
+            .get(mesh_name)?
+            .armature_name
+            .as_ref()
+            .and_then(|armature_name| self.armatures.get(armature_name))
+    }
+}
+
+impl LandonMesh {
+    /// The mesh, ready to be used in a `PbrBundle`.
+    pub fn mesh(&self) -> &Handle<Mesh> {
+        &self.mesh
+    }
+
+    /// The name of the armature that deforms this mesh.
+    pub fn armature_name(&self) -> Option<&String> {
+        self.armature_name.as_ref()
+    }
+}
+
+impl LandonArmature {
+    /// The inverse bind pose of every joint, ordered by joint index.
+    pub fn inverse_bindposes(&self) -> &Handle<SkinnedMeshInverseBindposes> {
+        &self.inverse_bindposes
+    }
+
+    /// Spawn an entity for every joint in its bind pose and return the [`SkinnedMesh`] that
+    /// deforms a mesh with them.
+    ///
+    /// Insert the returned component on the entity with your mesh, then animate the mesh by
+    /// setting the joints' `Transform`s. The joints aren't parented to each other, so their
+    /// transforms are in model space.
+    pub fn spawn_joints(&self, commands: &mut Commands) -> SkinnedMesh {
+        let joints: Vec<Entity> = self
+            .bind_poses
+            .iter()
+            .map(|bind_pose| {
+                commands
+                    .spawn_bundle((
+                        Transform::from_matrix(*bind_pose),
+                        GlobalTransform::identity(),
+                    ))
+                    .id()
+            })
+            .collect();
+
+        SkinnedMesh {
+            inverse_bindposes: self.inverse_bindposes.clone(),
+            joints,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LandonExportJson {
+    meshes: MeshesByFilename,
+    armatures: ArmaturesByFilename,
+}
+
+impl AssetLoader for LandonLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let export: LandonExportJson = serde_json::from_slice(bytes)?;
+
+            let mut meshes = HashMap::new();
+            for (mesh_name, blender_mesh) in export.meshes.values().flatten() {
+                let mesh = load_context.set_labeled_asset(
+                    &format!("Mesh/{}", mesh_name),
+                    LoadedAsset::new(to_bevy_mesh(blender_mesh)?),
+                );
+                let mesh = LandonMesh {
+                    mesh,
+                    armature_name: blender_mesh.armature_name().cloned(),
+                };
+
+                meshes.insert(mesh_name.to_string(), mesh);
+            }
+
+            let mut armatures = HashMap::new();
+            for (armature_name, armature) in export.armatures.values().flatten() {
+                let inverse_bindposes = y_up_inverse_bind_poses(armature);
+                let bind_poses = inverse_bindposes.iter().map(Mat4::inverse).collect();
+
+                let inverse_bindposes = load_context.set_labeled_asset(
+                    &format!("InverseBindposes/{}", armature_name),
+                    LoadedAsset::new(SkinnedMeshInverseBindposes::from(inverse_bindposes)),
+                );
+                let armature = LandonArmature {
+                    inverse_bindposes,
+                    bind_poses,
+                };
+
+                armatures.insert(armature_name.clone(), armature);
+            }
+
+            load_context.set_default_asset(LoadedAsset::new(LandonExport { meshes, armatures }));
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["landon.json"]
+    }
+}
+
+/// Convert a mesh from Blender's Z up coordinate system into a Y up Bevy mesh.
+///
+/// Tangents are included when the mesh has uvs and joint indices and weights are included when
+/// the mesh has bone influences, using up to four bones per vertex.
+///
+/// Returns an error if the mesh needs more vertices than fit in Bevy's u16 indices.
+pub fn to_bevy_mesh(mesh: &BlenderMesh) -> Result<Mesh, CombineIndicesError> {
+    let mut mesh = mesh.clone();
+    mesh.y_up();
+
+    let multi = mesh.multi_indexed_vertex_attributes();
+    let config = CreateSingleIndexConfig {
+        bone_influences_per_vertex: multi.bone_influences().map(|_| 4),
+        calculate_face_tangents: multi.uvs().is_some(),
+    };
+    let single_indexed = mesh.try_combine_vertex_indices(&config)?;
+
+    Ok(single_indexed_to_bevy_mesh(&single_indexed))
+}
+
+/// Convert single indexed vertex data into a Bevy mesh without changing its coordinate system.
+///
+/// Meshes without faces become point or line lists.
+pub fn single_indexed_to_bevy_mesh(single_indexed: &SingleIndexedVertexAttributes) -> Mesh {
+    let vertices = single_indexed.vertices();
+
+    let topology = match single_indexed.kind() {
+        MeshKind::Points => PrimitiveTopology::PointList,
+        MeshKind::Edges => PrimitiveTopology::LineList,
+        MeshKind::Triangles => PrimitiveTopology::TriangleList,
+    };
+    let mut mesh = Mesh::new(topology);
+
+    let positions: Vec<[f32; 3]> = vertices.iter().map(|v| v.position()).collect();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+
+    let normals: Option<Vec<[f32; 3]>> = vertices.iter().map(|v| v.normal()).collect();
+    if let Some(normals) = normals {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    }
+
+    let uvs: Option<Vec<[f32; 2]>> = vertices.iter().map(|v| v.uv()).collect();
+    if let Some(uvs) = uvs {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    }
+
+    // Bevy's tangents have a fourth component for the handedness of the bitangent
+    let tangents: Option<Vec<[f32; 4]>> = vertices
+        .iter()
+        .map(|v| v.face_tangent().map(|[x, y, z]| [x, y, z, 1.]))
+        .collect();
+    if let Some(tangents) = tangents {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_TANGENT, tangents);
+    }
+
+    let bones: Option<Vec<_>> = vertices.iter().map(|v| v.bones()).collect();
+    if let Some(bones) = bones {
+        let joint_indices: Vec<[u16; 4]> = bones
+            .iter()
+            .map(|bones| {
+                let mut indices = [0; 4];
+                for (index, bone) in indices.iter_mut().zip(bones.iter()) {
+                    *index = bone.bone_idx() as u16;
+                }
+                indices
+            })
+            .collect();
+        let joint_weights: Vec<[f32; 4]> = bones
+            .iter()
+            .map(|bones| {
+                let mut weights = [0.; 4];
+                for (weight, bone) in weights.iter_mut().zip(bones.iter()) {
+                    *weight = bone.weight();
+                }
+                weights
+            })
+            .collect();
+
+        mesh.insert_attribute(Mesh::ATTRIBUTE_JOINT_INDEX, joint_indices);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT, joint_weights);
+    }
+
+    mesh.set_indices(Some(Indices::U16(single_indexed.indices().clone())));
+
+    mesh
+}
+
+/// The armature's inverse bind poses, ordered by joint index, converted from Blender's Z up
+/// coordinate system to Y up.
+pub fn y_up_inverse_bind_poses(armature: &BlenderArmature) -> Vec<Mat4> {
+    // The new +Y axis is the old +Z axis and the new +Z axis is the old -Y axis
+    let z_up_to_y_up = Mat4::from_cols(Vec4::X, -Vec4::Z, Vec4::Y, Vec4::W);
+    let y_up_to_z_up = z_up_to_y_up.inverse();
+
+    armature
+        .inverse_bind_poses()
+        .iter()
+        .map(|bone| match BlenderArmature::dual_quat_to_matrix(bone) {
+            Bone::Matrix(matrix) => {
+                z_up_to_y_up * Mat4::from_cols_slice(matrix.as_slice()) * y_up_to_z_up
+            }
+            Bone::DualQuat(_) => unreachable!(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::bevy::math::Vec3;
+    use ::bevy::render::mesh::VertexAttributeValues;
+    use nalgebra::{Matrix4, Vector3};
+
+    /// Verify that a cube is converted into a y up triangle list with every attribute that it
+    /// has.
+    #[test]
+    fn cube_to_bevy_mesh() {
+        let mesh = to_bevy_mesh(&BlenderMesh::cube(1.)).unwrap();
+
+        assert!(mesh.attribute(Mesh::ATTRIBUTE_NORMAL).is_some());
+        assert!(mesh.attribute(Mesh::ATTRIBUTE_UV_0).is_some());
+        assert!(mesh.attribute(Mesh::ATTRIBUTE_TANGENT).is_some());
+        assert!(mesh.attribute(Mesh::ATTRIBUTE_JOINT_INDEX).is_none());
+
+        match mesh.indices() {
+            Some(Indices::U16(indices)) => assert_eq!(indices.len(), 36),
+            _ => unreachable!(),
+        };
+
+        match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float32x3(positions)) => {
+                assert_eq!(positions.len(), mesh.count_vertices())
+            }
+            _ => unreachable!(),
+        };
+    }
+
+    /// Verify that a bone that is 5 units above the origin in Blender is 5 units above the
+    /// origin in Bevy.
+    #[test]
+    fn inverse_bind_poses_y_up() {
+        let mut armature = BlenderArmature::default();
+        armature.set_inverse_bind_poses(vec![Bone::Matrix(Matrix4::new_translation(
+            &Vector3::new(0., 0., -5.),
+        ))]);
+
+        let inverse_bind_poses = y_up_inverse_bind_poses(&armature);
+
+        assert_eq!(
+            inverse_bind_poses[0].inverse().transform_point3(Vec3::ZERO),
+            Vec3::new(0., 5., 0.)
+        );
+    }
+}
@@ -0,0 +1,356 @@
+use blender_armature::BlenderArmature;
+use blender_mesh::{BlenderMesh, EvaluatePositionsError};
+use nalgebra::{Matrix4, Point3};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+/// An error while verifying that a mesh lines up with the armature that deforms it.
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum BindingError {
+    /// The mesh doesn't list the armature as one of the armatures that deform it.
+    #[error("{mesh} is not deformed by {armature}")]
+    NotDeformed {
+        /// The name of the mesh
+        mesh: String,
+        /// The name of the armature
+        armature: String,
+    },
+    /// The armature was exported without its bone rest poses, so its bind pose can't be skinned.
+    #[error("{armature} has {rest_poses} bone rest poses but {inverse_bind_poses} inverse bind poses, try exporting it again")]
+    MissingRestPoses {
+        /// The name of the armature
+        armature: String,
+        /// The number of bone rest poses
+        rest_poses: usize,
+        /// The number of inverse bind poses
+        inverse_bind_poses: usize,
+    },
+    /// The mesh's vertices could not be skinned.
+    #[error(transparent)]
+    Evaluate(#[from] EvaluatePositionsError),
+    /// Some of the armature's bones don't line up with the mesh.
+    #[error("{mesh} does not line up with {armature}: {}", format_bones(bones))]
+    Mismatch {
+        /// The name of the mesh
+        mesh: String,
+        /// The name of the armature
+        armature: String,
+        /// Every bone that doesn't line up, sorted by name
+        bones: Vec<BoneMismatch>,
+    },
+}
+
+/// A bone that doesn't line up with the mesh that it deforms, usually because the mesh and the
+/// armature were exported from different saves of a `.blend` file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BoneMismatch {
+    /// The mesh has a vertex group for a bone that the armature doesn't have.
+    MissingBone {
+        /// The name of the vertex group
+        bone: String,
+    },
+    /// Vertices are weighted to the bone's joint index without being in the bone's vertex group,
+    /// which happens when the mesh and the armature were exported with their bones in different
+    /// orders.
+    JointOrder {
+        /// The name of the bone
+        bone: String,
+        /// The number of vertices that are weighted to the bone but aren't in its vertex group
+        vertices: usize,
+    },
+    /// Skinning the bind pose moved vertices that are weighted to the bone further than the
+    /// tolerance, which happens when the bone's rest pose was changed after the mesh was bound.
+    Drift {
+        /// The name of the bone
+        bone: String,
+        /// The number of vertices that the bone moved further than the tolerance
+        vertices: usize,
+        /// The furthest that the bone moved one of the vertices
+        max_distance: f32,
+    },
+}
+
+impl BoneMismatch {
+    /// The name of the bone that doesn't line up.
+    pub fn bone(&self) -> &String {
+        match self {
+            BoneMismatch::MissingBone { bone } => bone,
+            BoneMismatch::JointOrder { bone, .. } => bone,
+            BoneMismatch::Drift { bone, .. } => bone,
+        }
+    }
+}
+
+impl Display for BoneMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoneMismatch::MissingBone { bone } => write!(f, "{} is missing", bone),
+            BoneMismatch::JointOrder { bone, vertices } => write!(
+                f,
+                "{} has a different joint index ({} vertices)",
+                bone, vertices
+            ),
+            BoneMismatch::Drift {
+                bone,
+                vertices,
+                max_distance,
+            } => write!(
+                f,
+                "{} moves {} vertices by up to {}",
+                bone, vertices, max_distance
+            ),
+        }
+    }
+}
+
+fn format_bones(bones: &[BoneMismatch]) -> String {
+    bones
+        .iter()
+        .map(|bone| bone.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Verify that a mesh lines up with the armature that deforms it by skinning the mesh with every
+/// bone in its rest pose, which should leave every vertex where it is.
+///
+/// A vertex that moves further than `tolerance` is blamed on every bone that moves it further
+/// than `tolerance`. Bones are also reported when the mesh has vertex groups that the armature
+/// doesn't have bones for, or when the mesh's joint indices point at bones whose vertex groups
+/// don't contain the vertex.
+///
+/// Like the renderers that draw landon exports, this expects the armature to be at the origin
+/// since the bone rest poses are in armature space while the inverse bind poses are in world
+/// space.
+pub fn verify_binding(
+    mesh: &BlenderMesh,
+    armature: &BlenderArmature,
+    tolerance: f32,
+) -> Result<(), BindingError> {
+    let inverse_bind_poses = armature.inverse_bind_poses();
+    let rest_poses = armature.bone_rest_poses();
+    if rest_poses.len() != inverse_bind_poses.len() {
+        return Err(BindingError::MissingRestPoses {
+            armature: armature.name().clone(),
+            rest_poses: rest_poses.len(),
+            inverse_bind_poses: inverse_bind_poses.len(),
+        });
+    }
+
+    let (first_joint, vertex_groups) = match mesh
+        .armatures()
+        .iter()
+        .find(|mesh_armature| &mesh_armature.name == armature.name())
+    {
+        Some(mesh_armature) => (
+            mesh_armature.first_joint as usize,
+            mesh_armature.vertex_groups.clone(),
+        ),
+        // Older exports don't list the armatures that deform the mesh
+        None if mesh.armatures().is_empty() => (0, vec![]),
+        None => {
+            return Err(BindingError::NotDeformed {
+                mesh: mesh.name().clone(),
+                armature: armature.name().clone(),
+            })
+        }
+    };
+    let joint_count = inverse_bind_poses.len();
+
+    let bone_names: HashMap<usize, &String> = armature
+        .joint_indices()
+        .iter()
+        .map(|(name, joint)| (*joint as usize, name))
+        .collect();
+
+    let mut mismatches: BTreeMap<String, BoneMismatch> = BTreeMap::new();
+
+    for bone in vertex_groups.iter() {
+        if !armature.joint_indices().contains_key(bone) {
+            mismatches.insert(
+                bone.clone(),
+                BoneMismatch::MissingBone { bone: bone.clone() },
+            );
+        }
+    }
+
+    let influences = match mesh.multi_indexed_vertex_attributes().bone_influences() {
+        Some(influences) => influences.influences_per_vertex(),
+        None => vec![],
+    };
+
+    let highest_joint = influences
+        .iter()
+        .flat_map(|(joints, _)| joints.iter())
+        .map(|joint| *joint as usize + 1)
+        .max()
+        .unwrap_or(0);
+
+    // Joints that belong to other armatures stay where they are
+    let mut pose = vec![Matrix4::identity(); highest_joint.max(first_joint + joint_count)];
+    for (joint, (rest_pose, inverse_bind_pose)) in
+        rest_poses.iter().zip(inverse_bind_poses.iter()).enumerate()
+    {
+        pose[first_joint + joint] = rest_pose.to_matrix() * inverse_bind_pose.to_matrix();
+    }
+
+    let morph_weights = vec![0.; mesh.shape_keys().len()];
+    let skinned = mesh.evaluate_vertex_positions(&pose, &morph_weights)?;
+
+    let groups: HashMap<&String, HashSet<u32>> = mesh
+        .vertex_groups()
+        .iter()
+        .map(|(name, positions)| (name, positions.iter().copied().collect()))
+        .collect();
+
+    let unskinned = mesh
+        .multi_indexed_vertex_attributes()
+        .positions()
+        .attribute();
+    for (vertex, ((position, skinned), (joints, weights))) in unskinned
+        .iter_vertices()
+        .zip(skinned.iter_vertices())
+        .zip(influences.iter())
+        .enumerate()
+    {
+        let position = Point3::new(position[0], position[1], position[2]);
+        let moved = (Point3::new(skinned[0], skinned[1], skinned[2]) - position).norm() > tolerance;
+
+        for (joint, weight) in joints.iter().zip(weights.iter()) {
+            let joint = *joint as usize;
+            if *weight <= 0. || joint < first_joint || joint >= first_joint + joint_count {
+                continue;
+            }
+
+            let bone = match bone_names.get(&(joint - first_joint)) {
+                Some(bone) => *bone,
+                None => continue,
+            };
+
+            let in_group = groups
+                .get(bone)
+                .map(|group| group.contains(&(vertex as u32)))
+                .unwrap_or(false);
+            if !in_group && !groups.is_empty() {
+                if let BoneMismatch::JointOrder { vertices, .. } = mismatches
+                    .entry(bone.clone())
+                    .or_insert_with(|| BoneMismatch::JointOrder {
+                        bone: bone.clone(),
+                        vertices: 0,
+                    })
+                {
+                    *vertices += 1;
+                }
+            }
+
+            if !moved {
+                continue;
+            }
+
+            let distance = (pose[joint].transform_point(&position) - position).norm();
+            if distance <= tolerance {
+                continue;
+            }
+
+            let mismatch = mismatches
+                .entry(bone.clone())
+                .or_insert_with(|| BoneMismatch::Drift {
+                    bone: bone.clone(),
+                    vertices: 0,
+                    max_distance: 0.,
+                });
+            if let BoneMismatch::Drift {
+                vertices,
+                max_distance,
+                ..
+            } = mismatch
+            {
+                *vertices += 1;
+                *max_distance = max_distance.max(distance);
+            }
+        }
+    }
+
+    match mismatches.is_empty() {
+        true => Ok(()),
+        false => Err(BindingError::Mismatch {
+            mesh: mesh.name().clone(),
+            armature: armature.name().clone(),
+            bones: mismatches.into_values().collect(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blender_armature::Bone;
+    use nalgebra::Vector3;
+
+    /// Verify that a mesh that lines up with its armature passes, and that a bone whose rest
+    /// pose drifted away from the mesh's bind pose is reported along with the vertices it moves.
+    #[test]
+    fn verify_drifted_binding() {
+        let mesh: BlenderMesh = serde_json::from_str(
+            r#"{
+                "name": "Leg",
+                "armatures": [{"name": "Rig", "first_joint": 0, "vertex_groups": ["Hip", "Knee"]}],
+                "bounding_box": {"min_corner": [0, 0, 0], "max_corner": [0, 0, 2]},
+                "materials": [],
+                "custom_properties": {},
+                "vertex_groups": {"Hip": [0, 1], "Knee": [1, 2]},
+                "multi_indexed_vertex_attributes": {
+                    "vertices_in_each_face": [3],
+                    "material_index": [0],
+                    "positions": {
+                        "indices": [0, 1, 2],
+                        "attribute": {"data": [0, 0, 0, 0, 0, 1, 0, 0, 2], "attribute_size": 3}
+                    },
+                    "bone_influences": {
+                        "bones_per_vertex": {"NonUniform": [1, 2, 1]},
+                        "bone_indices": [0, 0, 1, 1],
+                        "bone_weights": [1, 0.5, 0.5, 1]
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let bind_poses = [
+            Matrix4::new_translation(&Vector3::new(0., 0., 0.)),
+            Matrix4::new_translation(&Vector3::new(0., 0., 1.)),
+        ];
+        let mut armature = BlenderArmature::default();
+        armature.set_name("Rig".to_string());
+        armature.insert_joint_index("Hip".to_string(), 0);
+        armature.insert_joint_index("Knee".to_string(), 1);
+        armature.set_inverse_bind_poses(
+            bind_poses
+                .iter()
+                .map(|pose| Bone::Matrix(pose.try_inverse().unwrap()))
+                .collect(),
+        );
+        armature.set_bone_rest_poses(bind_poses.iter().map(|pose| Bone::Matrix(*pose)).collect());
+
+        assert_eq!(verify_binding(&mesh, &armature, 0.001), Ok(()));
+
+        // The knee was moved after the mesh was bound to it
+        armature.set_bone_rest_poses(vec![
+            Bone::Matrix(bind_poses[0]),
+            Bone::Matrix(Matrix4::new_translation(&Vector3::new(0., 0., 1.5))),
+        ]);
+
+        assert_eq!(
+            verify_binding(&mesh, &armature, 0.001),
+            Err(BindingError::Mismatch {
+                mesh: "Leg".to_string(),
+                armature: "Rig".to_string(),
+                bones: vec![BoneMismatch::Drift {
+                    bone: "Knee".to_string(),
+                    vertices: 2,
+                    max_distance: 0.5,
+                }],
+            })
+        );
+    }
+}
@@ -3,3 +3,28 @@ pub use self::install::*;
 
 mod export;
 pub use self::export::*;
+
+#[cfg(feature = "tokio")]
+mod export_async;
+#[cfg(feature = "tokio")]
+pub use self::export_async::*;
+
+#[cfg(feature = "rayon")]
+mod parse_parallel;
+#[cfg(feature = "rayon")]
+pub use self::parse_parallel::*;
+
+mod linked_libraries;
+pub use self::linked_libraries::*;
+
+mod export_warnings;
+pub use self::export_warnings::*;
+
+mod thumbnails;
+pub use self::thumbnails::*;
+
+mod action_previews;
+pub use self::action_previews::*;
+
+mod import;
+pub use self::import::*;
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The preview strips that were rendered for each action, keyed by the absolute path of the
+/// exported file, then by the armature's name and then by the action's exported name.
+pub type ActionPreviewsByFilename =
+    HashMap<String, HashMap<String, HashMap<String, ActionPreview>>>;
+
+/// Printed to stdout by [`EXPORT_BLENDER_DATA`] for every action preview that it renders.
+///
+/// [`EXPORT_BLENDER_DATA`]: static.EXPORT_BLENDER_DATA.html
+const ACTION_PREVIEW_MARKER: &str = "ACTION_PREVIEW_JSON ";
+
+/// A PNG of frames from an action laid out from left to right, each one as wide as it is tall.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ActionPreview {
+    pub(crate) path: PathBuf,
+    pub(crate) frames: Vec<i32>,
+}
+
+impl ActionPreview {
+    /// The absolute path of the PNG.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The Blender frame that was rendered for each cell of the strip, from left to right.
+    pub fn frames(&self) -> &Vec<i32> {
+        &self.frames
+    }
+}
+
+#[derive(Deserialize)]
+struct ActionPreviewJson {
+    blend_file: String,
+    armature: String,
+    action: String,
+    #[serde(flatten)]
+    preview: ActionPreview,
+}
+
+/// Find the action previews that were rendered when exporting with
+/// [`BlenderExportConfig.action_preview_dir`].
+///
+/// [`BlenderExportConfig.action_preview_dir`]: struct.BlenderExportConfig.html#structfield.action_preview_dir
+pub fn parse_action_previews_from_blender_stdout(blender_stdout: &str) -> ActionPreviewsByFilename {
+    let mut previews = ActionPreviewsByFilename::new();
+
+    for line in blender_stdout.lines() {
+        if !line.starts_with(ACTION_PREVIEW_MARKER) {
+            continue;
+        }
+
+        if let Ok(preview) =
+            serde_json::from_str::<ActionPreviewJson>(&line[ACTION_PREVIEW_MARKER.len()..])
+        {
+            previews
+                .entry(preview.blend_file)
+                .or_default()
+                .entry(preview.armature)
+                .or_default()
+                .insert(preview.action, preview.preview);
+        }
+    }
+
+    previews
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that previews are found per file, armature and action.
+    #[test]
+    fn parse_action_previews() {
+        let stdout = r#"Blender 2.93
+Fra:1 Mem:12.00M | Rendering 1 / 1 samples
+ACTION_PREVIEW_JSON {"blend_file": "/hero.blend", "armature": "Rig", "action": "Walk", "path": "/previews/hero_Rig_Walk.png", "frames": [1, 5, 9]}
+ACTION_PREVIEW_JSON {"blend_file": "/hero.blend", "armature": "Rig", "action": "Idle", "path": "/previews/hero_Rig_Idle.png", "frames": [0]}
+"#;
+
+        let previews = parse_action_previews_from_blender_stdout(stdout);
+
+        let walk = &previews["/hero.blend"]["Rig"]["Walk"];
+        assert_eq!(walk.path(), Path::new("/previews/hero_Rig_Walk.png"));
+        assert_eq!(walk.frames(), &vec![1, 5, 9]);
+        assert_eq!(previews["/hero.blend"]["Rig"].len(), 2);
+    }
+}
@@ -1,23 +1,266 @@
+use crate::blender::linked_libraries::unexported_libraries;
+use crate::{BlenderProcessError, LandonError};
+use blender_armature::ActionFilter;
+use std::collections::HashSet;
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Child, Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
 
-/// A script used to export meshes and armatures from Blender to stdout
+/// A script used to export meshes, armatures, object animations, camera tracks, grease pencil
+/// strokes, point caches, particle systems and mesh sequences from Blender to stdout
 pub static EXPORT_BLENDER_DATA: &'static str = r#"
 import bpy
+import json
+import math
+import os
+import shutil
+import tempfile
+from mathutils import Vector
 
 bpy.context.view_layer.objects.active = None
 
+# Report the libraries that this file links data from so that they can be tracked as dependencies
+# or exported themselves
+for library in bpy.data.libraries:
+    library_path = bpy.path.abspath(library.filepath, library=library.library)
+    print("LINKED_LIBRARY_JSON " + json.dumps({"blend_file": bpy.data.filepath, "library": library_path}))
+
+# When linked libraries are exported on their own we skip their objects here so that they aren't
+# exported twice
+skip_linked_objects = globals().get('landon_skip_linked_objects', False)
+
+# Export the geometry that modifiers such as geometry nodes generate instead of the base meshes
+evaluate_modifiers = globals().get('landon_evaluate_modifiers', False)
+
+# Which armature actions to export and what to rename them to, as JSON
+action_filter = globals().get('landon_action_filter', '{}')
+
+# Skip helper geometry and reference objects that are hidden in renders or in the viewport
+skip_hide_render = globals().get('landon_skip_hide_render', False)
+skip_hide_viewport = globals().get('landon_skip_hide_viewport', False)
+
+# Bake the positions on every frame of cloth and soft body simulations
+bake_point_caches = globals().get('landon_bake_point_caches', False)
+
+# Export fluid simulations and Alembic caches on every frame of the scene
+export_mesh_sequences = globals().get('landon_export_mesh_sequences', False)
+
+# Give meshes and bones ids that survive renames, stored as custom properties so that every
+# later export of the file uses the same ids
+assign_stable_ids = globals().get('landon_assign_stable_ids', False)
+
+# Only export these objects, such as a single prop that an artist is iterating on, as JSON
+object_names = set(json.loads(globals().get('landon_object_names', '[]')))
+
+# Render a small preview of every exported mesh into this directory, for asset browsers
+thumbnail_dir = globals().get('landon_thumbnail_dir', None)
+
+# Render a strip of frames from every exported action into this directory, for reviewing clips
+action_preview_dir = globals().get('landon_action_preview_dir', None)
+
+THUMBNAIL_SIZE = 128
+ACTION_PREVIEW_FRAMES = 8
+
+def preview_camera(scene, objs):
+    # Frame the objects from the front right and a little above, like the first frame of a
+    # turntable
+    corners = [obj.matrix_world @ Vector(corner) for obj in objs for corner in obj.bound_box]
+    center = sum(corners, Vector()) / len(corners)
+    radius = max([(corner - center).length for corner in corners] + [0.001])
+    direction = Vector((1, -1, 0.8)).normalized()
+
+    camera_data = bpy.data.cameras.new('landon_preview')
+    camera = bpy.data.objects.new('landon_preview', camera_data)
+    scene.collection.objects.link(camera)
+    distance = radius / math.sin(camera_data.angle / 2)
+    camera.location = center + direction * distance
+    camera.rotation_euler = direction.to_track_quat('Z', 'Y').to_euler()
+    camera_data.clip_end = distance + radius * 2
+
+    return camera
+
+def remove_preview_camera(camera):
+    camera_data = camera.data
+    bpy.data.objects.remove(camera)
+    bpy.data.cameras.remove(camera_data)
+
+def render_preview(scene, camera, objs, paths_and_frames):
+    render = scene.render
+
+    hide_render = {other: other.hide_render for other in scene.objects}
+    settings = (scene.camera, scene.frame_current, render.engine, render.resolution_x, render.resolution_y, render.resolution_percentage, render.film_transparent, render.filepath, render.image_settings.file_format)
+    try:
+        for other in scene.objects:
+            other.hide_render = other not in objs and other != camera
+        scene.camera = camera
+        # The workbench engine doesn't need any lights and renders quickly without a GPU
+        render.engine = 'BLENDER_WORKBENCH'
+        render.resolution_x = THUMBNAIL_SIZE
+        render.resolution_y = THUMBNAIL_SIZE
+        render.resolution_percentage = 100
+        render.film_transparent = True
+        render.image_settings.file_format = 'PNG'
+        for path, frame in paths_and_frames:
+            scene.frame_set(frame)
+            render.filepath = path
+            bpy.ops.render.render(write_still=True)
+    finally:
+        for other, hidden in hide_render.items():
+            other.hide_render = hidden
+        (scene.camera, frame_current, render.engine, render.resolution_x, render.resolution_y, render.resolution_percentage, render.film_transparent, render.filepath, render.image_settings.file_format) = settings
+        scene.frame_set(frame_current)
+
+def preview_path(directory, *names):
+    blend_name = os.path.splitext(os.path.basename(bpy.data.filepath))[0]
+    return os.path.join(os.path.abspath(directory), bpy.path.clean_name('_'.join((blend_name,) + names)) + '.png')
+
+def render_thumbnail(obj):
+    scene = bpy.context.scene
+    path = preview_path(thumbnail_dir, obj.name)
+
+    camera = preview_camera(scene, [obj])
+    try:
+        render_preview(scene, camera, [obj], [(path, scene.frame_current)])
+    finally:
+        remove_preview_camera(camera)
+
+    library = bpy.path.abspath(obj.library.filepath, library=obj.library.library) if obj.library else None
+    print("THUMBNAIL_JSON " + json.dumps({"blend_file": bpy.data.filepath, "object": obj.name, "library": library, "path": path}))
+
+def render_action_previews(armature):
+    scene = bpy.context.scene
+    # Armatures aren't rendered, so we render the meshes that they deform
+    meshes = [obj for obj in scene.objects if obj.type == 'MESH' and (obj.parent == armature or any(modifier.type == 'ARMATURE' and modifier.object == armature for modifier in obj.modifiers))]
+    if not meshes:
+        return
+
+    filter_json = json.loads(action_filter)
+    include = filter_json.get('include', [])
+    renames = filter_json.get('renames', {})
+    actions = [
+        action for action in bpy.data.actions
+        if (not include or action.name in include)
+        and not any(action.name.startswith(prefix) for prefix in filter_json.get('exclude_prefixes', []))
+        and any(len(fcurve.keyframe_points) > 0 for fcurve in action.fcurves)
+    ]
+
+    if armature.animation_data is None:
+        armature.animation_data_create()
+    original_action = armature.animation_data.action
+
+    # The camera stays where it is for every action so that clips can be compared side by side
+    camera = preview_camera(scene, meshes)
+    try:
+        for action in actions:
+            armature.animation_data.action = action
+            start, end = math.floor(action.frame_range[0]), math.floor(action.frame_range[1])
+            frames = sorted(set(round(start + (end - start) * idx / (ACTION_PREVIEW_FRAMES - 1)) for idx in range(ACTION_PREVIEW_FRAMES)))
+
+            action_name = renames.get(action.name, action.name)
+            path = preview_path(action_preview_dir, armature.name, action_name)
+            frame_dir = tempfile.mkdtemp()
+            frame_paths = [os.path.join(frame_dir, str(frame) + '.png') for frame in frames]
+
+            render_preview(scene, camera, meshes, list(zip(frame_paths, frames)))
+            save_preview_strip(frame_paths, path)
+            shutil.rmtree(frame_dir, ignore_errors=True)
+
+            print("ACTION_PREVIEW_JSON " + json.dumps({"blend_file": bpy.data.filepath, "armature": armature.name, "action": action_name, "path": path, "frames": frames}))
+    finally:
+        armature.animation_data.action = original_action
+        remove_preview_camera(camera)
+
+def save_preview_strip(frame_paths, path):
+    # Lay the frames out from left to right
+    width = THUMBNAIL_SIZE * len(frame_paths)
+    strip = bpy.data.images.new('landon_preview_strip', width=width, height=THUMBNAIL_SIZE, alpha=True)
+    pixels = [0.0] * (width * THUMBNAIL_SIZE * 4)
+    row_len = THUMBNAIL_SIZE * 4
+
+    for column, frame_path in enumerate(frame_paths):
+        frame = bpy.data.images.load(frame_path)
+        frame_pixels = frame.pixels[:]
+        for row in range(THUMBNAIL_SIZE):
+            start = (row * width + column * THUMBNAIL_SIZE) * 4
+            pixels[start:start + row_len] = frame_pixels[row * row_len:(row + 1) * row_len]
+        bpy.data.images.remove(frame)
+
+    strip.pixels[:] = pixels
+    strip.filepath_raw = path
+    strip.file_format = 'PNG'
+    strip.save()
+    bpy.data.images.remove(strip)
+
+def assign_landon_ids(items, counter_owner, counter_name):
+    # Duplicating an object or a bone copies its custom properties, so only the first item in
+    # name order keeps an id that is shared
+    taken = set()
+    unassigned = []
+    for item in sorted(items, key=lambda item: item.name):
+        stable_id = item.get('landon_id')
+        if isinstance(stable_id, int) and stable_id not in taken:
+            taken.add(stable_id)
+        else:
+            unassigned.append(item)
+
+    # The counter makes sure that the ids of deleted items are never handed out again
+    next_id = max([counter_owner.get(counter_name, 0)] + [stable_id + 1 for stable_id in taken])
+    for item in unassigned:
+        item['landon_id'] = next_id
+        next_id += 1
+    counter_owner[counter_name] = next_id
+
+    return len(unassigned) > 0
+
+# Linked data can't be edited, so it gets its ids when its library is exported
+if assign_stable_ids and bpy.data.filepath:
+    mesh_objects = [obj for obj in bpy.data.objects if obj.type == 'MESH' and obj.library is None]
+    assigned = assign_landon_ids(mesh_objects, bpy.context.scene, 'landon_next_id')
+    for armature in bpy.data.armatures:
+      if armature.library is None:
+        assigned = assign_landon_ids(armature.bones, armature, 'landon_next_bone_id') or assigned
+    if assigned:
+      bpy.ops.wm.save_mainfile()
+
 # Get the objects at the beginning so that we don't iterate over new ones that we
 # generate such as ik-to-fk converted rigs
 objects = list(bpy.context.scene.objects)
 
 for obj in objects:
+    if object_names and obj.name not in object_names:
+      continue
+    if skip_linked_objects and obj.library is not None:
+      continue
+    if skip_hide_render and obj.hide_render:
+      continue
+    if skip_hide_viewport and not obj.visible_get():
+      continue
     bpy.context.view_layer.objects.active = obj
     if obj.type == 'MESH':
-      bpy.ops.import_export.mesh2json()
+      bpy.ops.import_export.mesh2json(evaluate_modifiers=evaluate_modifiers, bake_point_caches=bake_point_caches)
+      if export_mesh_sequences:
+        bpy.ops.import_export.meshsequence2json()
+      if thumbnail_dir is not None:
+        render_thumbnail(obj)
     if obj.type == 'ARMATURE':
       bpy.ops.rigging.iktofk()
-      bpy.ops.import_export.armature2json()
+      bpy.ops.import_export.armature2json(action_filter=action_filter)
+      if action_preview_dir is not None:
+        render_action_previews(obj)
+    # Object transform animations, such as moving platforms, and camera tracks. Only exported if
+    # the object animation addon is installed.
+    is_animated = obj.animation_data is not None or obj.type == 'CAMERA'
+    if obj.type != 'ARMATURE' and is_animated and 'objectanimation2json' in dir(bpy.ops.import_export):
+      bpy.ops.import_export.objectanimation2json()
+    # Grease pencil strokes, such as gameplay volumes and markers that were blocked out by hand.
+    # Grease pencil objects were renamed in Blender 4.3
+    if obj.type in ('GPENCIL', 'GREASEPENCIL') and 'gpencil2json' in dir(bpy.ops.import_export):
+      bpy.ops.import_export.gpencil2json()
+
+if not object_names and bpy.context.scene.grease_pencil is not None and 'gpencil2json' in dir(bpy.ops.import_export):
+  bpy.ops.import_export.gpencil2json(annotations=True)
 "#;
 
 /// Write the meshes and armatures from a vector of Blender filenames to stdout.
@@ -32,21 +275,230 @@ for obj in objects:
 /// to parse the exported data into the data structures that you need.
 ///
 /// TODO: Integration test this
-pub fn export_blender_data(blender_files: &[PathBuf]) -> Result<String, anyhow::Error> {
-    let mut blender_process = Command::new("blender");
-    let blender_process = blender_process.arg("--background");
+pub fn export_blender_data(blender_files: &[PathBuf]) -> Result<String, LandonError> {
+    export_blender_data_with_config(blender_files, &BlenderExportConfig::default())
+}
+
+/// Configuration for exporting data from Blender.
+#[derive(Debug, Clone, Default)]
+pub struct BlenderExportConfig {
+    /// Kill Blender if a file hasn't finished exporting after this long, so that one corrupt
+    /// `.blend` file can't hang a whole batch of exports.
+    ///
+    /// When set every file is exported by its own Blender process. Otherwise all of the files
+    /// are exported by a single Blender process.
+    pub per_file_timeout: Option<Duration>,
+    /// Also export the `.blend` libraries that the files link objects from, and the libraries
+    /// that those libraries link from, and so on.
+    ///
+    /// Linked objects are then exported from their library instead of from every file that
+    /// links them. Every library is exported once, even if libraries link each other.
+    pub follow_linked_libraries: bool,
+    /// Export the meshes that the objects' modifiers generate, such as the realized geometry of a
+    /// geometry nodes tree along with its named attributes, instead of the objects' base meshes.
+    ///
+    /// Armature modifiers are skipped so that meshes are exported in their bind pose.
+    pub evaluate_modifiers: bool,
+    /// Which armature actions to export and what to name them.
+    ///
+    /// Applied by the export script, so actions that are filtered out aren't sampled at all.
+    pub action_filter: ActionFilter,
+    /// Skip the objects that are disabled in renders, such as helper geometry and reference
+    /// objects.
+    ///
+    /// Otherwise they are exported along with their visibility. See
+    /// `BlenderMesh::visibility`.
+    pub skip_hide_render: bool,
+    /// Skip the objects that are hidden or disabled in the viewport, either by themselves or along
+    /// with one of their collections.
+    pub skip_hide_viewport: bool,
+    /// Also export the positions on every frame of every mesh's cloth or soft body simulation,
+    /// such as a flag or a cape, so that it can be replayed without a simulator.
+    ///
+    /// See `blender_mesh::parse_point_caches_from_blender_stdout`.
+    pub bake_point_caches: bool,
+    /// Also export every frame of the meshes whose topology changes from frame to frame, such as
+    /// fluid simulations and Alembic caches.
+    ///
+    /// See `blender_mesh::parse_mesh_sequences_from_blender_stdout`.
+    pub export_mesh_sequences: bool,
+    /// Give every local mesh object and bone that doesn't have one a stable id, which is stored in
+    /// a `landon_id` custom property and exported alongside its name so that renames in Blender
+    /// don't break references to it.
+    ///
+    /// Files are saved when new ids are assigned to them. See `BlenderMesh::stable_id` and
+    /// `BlenderArmature::bone_stable_ids`.
+    pub assign_stable_ids: bool,
+    /// Only export the objects with these names, such as the one prop that an artist is tweaking
+    /// inside of a large library file. Every object is exported if this is empty.
+    ///
+    /// Blender still has to open the whole file, but none of the other objects are exported.
+    /// Armatures aren't exported along with the meshes that they deform unless they're named too.
+    /// See [`export_object`].
+    ///
+    /// [`export_object`]: fn.export_object.html
+    pub object_names: Vec<String>,
+    /// Render a small thumbnail of every exported mesh object to a PNG in this directory, named
+    /// after the file and the object, such as for showing previews in an asset browser.
+    ///
+    /// Thumbnails are rendered with the workbench engine from a three quarter view, with every
+    /// other object hidden. See [`parse_thumbnails_from_blender_stdout`].
+    ///
+    /// [`parse_thumbnails_from_blender_stdout`]: fn.parse_thumbnails_from_blender_stdout.html
+    pub thumbnail_dir: Option<PathBuf>,
+    /// Render a strip of frames from every exported action of every armature to a PNG in this
+    /// directory, named after the file, the armature and the action, so that exported clips can
+    /// be reviewed without opening Blender.
+    ///
+    /// The meshes that the armature deforms are rendered the same way as the thumbnails, from a
+    /// camera that stays in the same place for every action. See
+    /// [`parse_action_previews_from_blender_stdout`].
+    ///
+    /// [`parse_action_previews_from_blender_stdout`]: fn.parse_action_previews_from_blender_stdout.html
+    pub action_preview_dir: Option<PathBuf>,
+}
+
+/// Export a single object from a Blender file to stdout, which is much faster than exporting
+/// every object when iterating on one prop inside of a large library file.
+///
+/// See [`BlenderExportConfig.object_names`].
+///
+/// [`BlenderExportConfig.object_names`]: struct.BlenderExportConfig.html#structfield.object_names
+pub fn export_object(blender_file: &Path, object_name: &str) -> Result<String, LandonError> {
+    let config = BlenderExportConfig {
+        object_names: vec![object_name.to_string()],
+        ..BlenderExportConfig::default()
+    };
+
+    export_blender_data_with_config(&[blender_file.to_path_buf()], &config)
+}
+
+/// See [`export_blender_data`].
+///
+/// Blender is killed if this panics or if a file takes longer than the configured timeout, so no
+/// Blender processes are left behind.
+///
+/// [`export_blender_data`]: fn.export_blender_data.html
+pub fn export_blender_data_with_config(
+    blender_files: &[PathBuf],
+    config: &BlenderExportConfig,
+) -> Result<String, LandonError> {
+    let mut stdout = export_files(blender_files, config)?;
+
+    if config.follow_linked_libraries {
+        let mut exported = canonical_paths(blender_files);
+        loop {
+            let libraries = unexported_libraries(&stdout, &mut exported);
+            if libraries.is_empty() {
+                break;
+            }
+            stdout += &export_files(&libraries, config)?;
+        }
+    }
+
+    Ok(stdout)
+}
+
+fn export_files(
+    blender_files: &[PathBuf],
+    config: &BlenderExportConfig,
+) -> Result<String, LandonError> {
+    let timeout = match config.per_file_timeout {
+        Some(timeout) => timeout,
+        None => {
+            let output = Command::new("blender")
+                .args(export_blender_data_args(blender_files, config))
+                .output()
+                .map_err(BlenderProcessError::Spawn)?;
+
+            return exported_blender_data(output);
+        }
+    };
+
+    let mut stdout = String::new();
+    for blender_file in blender_files {
+        let mut command = Command::new("blender");
+        command.args(export_blender_data_args(
+            std::slice::from_ref(blender_file),
+            config,
+        ));
+
+        let output = output_with_timeout(&mut command, timeout)
+            .map_err(BlenderProcessError::Spawn)?
+            .ok_or_else(|| BlenderProcessError::timeout(blender_file, timeout))?;
+
+        stdout += &exported_blender_data(output)?;
+    }
+
+    Ok(stdout)
+}
+
+/// The arguments to `blender` that export every file to stdout.
+pub(crate) fn export_blender_data_args(
+    blender_files: &[PathBuf],
+    config: &BlenderExportConfig,
+) -> Vec<String> {
+    let mut args = vec!["--background".to_string()];
+
+    let export_script = format!(
+        "landon_skip_linked_objects = {}\nlandon_evaluate_modifiers = {}\nlandon_action_filter = {}\nlandon_skip_hide_render = {}\nlandon_skip_hide_viewport = {}\nlandon_bake_point_caches = {}\nlandon_export_mesh_sequences = {}\nlandon_assign_stable_ids = {}\nlandon_object_names = {}\nlandon_thumbnail_dir = {}\nlandon_action_preview_dir = {}\n{}",
+        python_bool(config.follow_linked_libraries),
+        python_bool(config.evaluate_modifiers),
+        python_string(&serde_json::to_string(&config.action_filter).unwrap()),
+        python_bool(config.skip_hide_render),
+        python_bool(config.skip_hide_viewport),
+        python_bool(config.bake_point_caches),
+        python_bool(config.export_mesh_sequences),
+        python_bool(config.assign_stable_ids),
+        python_string(&serde_json::to_string(&config.object_names).unwrap()),
+        python_path(config.thumbnail_dir.as_ref()),
+        python_path(config.action_preview_dir.as_ref()),
+        EXPORT_BLENDER_DATA
+    );
 
     for blender_file in blender_files {
-        blender_process
-            .arg("-noaudio")
-            .args(&["--python-expr", &open_blender_file(blender_file)])
-            .args(&["--python-expr", &EXPORT_BLENDER_DATA]);
+        args.push("-noaudio".to_string());
+        args.push("--python-expr".to_string());
+        args.push(open_blender_file(blender_file));
+        args.push("--python-expr".to_string());
+        args.push(export_script.clone());
+    }
+
+    args
+}
+
+fn python_bool(value: bool) -> &'static str {
+    match value {
+        true => "True",
+        false => "False",
     }
+}
+
+/// A Python string literal. JSON's string escapes are also valid in Python.
+fn python_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+/// A Python string literal of the path, or `None`.
+fn python_path(path: Option<&PathBuf>) -> String {
+    match path {
+        Some(path) => python_string(&path.to_string_lossy()),
+        None => "None".to_string(),
+    }
+}
 
-    let output = blender_process.output().unwrap();
+/// The canonical paths of the files that exist, used to avoid exporting a file twice.
+pub(crate) fn canonical_paths(blender_files: &[PathBuf]) -> HashSet<PathBuf> {
+    blender_files
+        .iter()
+        .filter_map(|file| std::fs::canonicalize(file).ok())
+        .collect()
+}
 
+/// Blender's stdout, or an error if Blender wrote anything to stderr.
+pub(crate) fn exported_blender_data(output: Output) -> Result<String, LandonError> {
     if output.stderr.len() > 0 {
-        return Err(BlenderExportError::Stderr(String::from_utf8(
+        return Err(BlenderProcessError::Export(String::from_utf8(
             output.stderr,
         )?))?;
     }
@@ -54,6 +506,63 @@ pub fn export_blender_data(blender_files: &[PathBuf]) -> Result<String, anyhow::
     Ok(String::from_utf8(output.stdout)?)
 }
 
+/// How often to check whether a child process has exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Run the command, returning `None` if it didn't finish before the timeout.
+fn output_with_timeout(command: &mut Command, timeout: Duration) -> io::Result<Option<Output>> {
+    let mut child = KillOnDrop(
+        command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?,
+    );
+
+    // Read the pipes while we wait, otherwise the child could block on a full pipe and never exit
+    let stdout = read_to_end_in_background(child.0.stdout.take());
+    let stderr = read_to_end_in_background(child.0.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.0.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    Ok(Some(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    }))
+}
+
+fn read_to_end_in_background(
+    pipe: Option<impl Read + Send + 'static>,
+) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = vec![];
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
+
+/// Kills and reaps the child process when dropped so that it doesn't outlive us as a zombie.
+struct KillOnDrop(Child);
+
+impl Drop for KillOnDrop {
+    fn drop(&mut self) {
+        // Fails if the child already exited, which is fine
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
 fn open_blender_file(file: &dyn AsRef<Path>) -> String {
     format!(
         r#"
@@ -64,10 +573,63 @@ bpy.ops.wm.open_mainfile(filepath="{}")
     )
 }
 
-/// An error while exporting data from Blender
-#[derive(Debug, thiserror::Error)]
-#[allow(missing_docs)]
-pub enum BlenderExportError {
-    #[error("Error while exporting data from blender: {0}")]
-    Stderr(String),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that the export options are passed to the export script.
+    #[test]
+    fn export_options_in_script() {
+        let config = BlenderExportConfig {
+            evaluate_modifiers: true,
+            skip_hide_render: true,
+            bake_point_caches: true,
+            assign_stable_ids: true,
+            object_names: vec!["Crate".to_string()],
+            thumbnail_dir: Some(PathBuf::from("thumbnails")),
+            ..BlenderExportConfig::default()
+        };
+
+        let args = export_blender_data_args(&[PathBuf::from("level.blend")], &config);
+
+        assert!(args[5]
+            .starts_with("landon_skip_linked_objects = False\nlandon_evaluate_modifiers = True\n"));
+        assert!(args[5].contains(
+            r#"landon_action_filter = "{\"include\":[],\"exclude_prefixes\":[],\"renames\":{}}""#
+        ));
+        assert!(
+            args[5].contains("landon_skip_hide_render = True\nlandon_skip_hide_viewport = False\n")
+        );
+        assert!(args[5].contains("landon_bake_point_caches = True\n"));
+        assert!(args[5].contains("landon_assign_stable_ids = True\n"));
+        assert!(args[5].contains(r#"landon_object_names = "[\"Crate\"]""#));
+        assert!(args[5]
+            .contains("landon_thumbnail_dir = \"thumbnails\"\nlandon_action_preview_dir = None\n"));
+    }
+
+    /// Verify that a process that takes too long gets killed.
+    #[test]
+    fn kill_hung_process() {
+        let started = Instant::now();
+
+        let output =
+            output_with_timeout(Command::new("sleep").arg("10"), Duration::from_millis(50))
+                .unwrap();
+
+        assert!(output.is_none());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    /// Verify that we get the output of a process that finishes in time.
+    #[test]
+    fn output_before_timeout() {
+        let output = output_with_timeout(
+            Command::new("echo").arg("exported"),
+            Duration::from_secs(10),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(output.stdout, b"exported\n");
+    }
 }
@@ -0,0 +1,76 @@
+use crate::blender::export::{canonical_paths, export_blender_data_args, exported_blender_data};
+use crate::blender::linked_libraries::unexported_libraries;
+use crate::{BlenderExportConfig, BlenderProcessError, LandonError};
+use std::path::PathBuf;
+use tokio::process::Command;
+
+/// Write the meshes and armatures from a vector of Blender filenames to stdout without blocking
+/// the current thread.
+///
+/// This is the async version of [`export_blender_data_with_config`] and must be called from
+/// within a tokio runtime.
+///
+/// To cancel an export drop the returned future, such as by using it in a `tokio::select!`.
+/// Blender gets killed when the future is dropped or when a file takes longer than the
+/// configured timeout.
+///
+/// [`export_blender_data_with_config`]: fn.export_blender_data_with_config.html
+pub async fn export_blend_file_async(
+    blender_files: &[PathBuf],
+    config: &BlenderExportConfig,
+) -> Result<String, LandonError> {
+    let mut stdout = export_files(blender_files, config).await?;
+
+    if config.follow_linked_libraries {
+        let mut exported = canonical_paths(blender_files);
+        loop {
+            let libraries = unexported_libraries(&stdout, &mut exported);
+            if libraries.is_empty() {
+                break;
+            }
+            stdout += &export_files(&libraries, config).await?;
+        }
+    }
+
+    Ok(stdout)
+}
+
+async fn export_files(
+    blender_files: &[PathBuf],
+    config: &BlenderExportConfig,
+) -> Result<String, LandonError> {
+    let timeout = match config.per_file_timeout {
+        Some(timeout) => timeout,
+        None => {
+            let output = blender_command(blender_files, config)
+                .output()
+                .await
+                .map_err(BlenderProcessError::Spawn)?;
+
+            return exported_blender_data(output);
+        }
+    };
+
+    let mut stdout = String::new();
+    for blender_file in blender_files {
+        let output = blender_command(std::slice::from_ref(blender_file), config).output();
+
+        let output = tokio::time::timeout(timeout, output)
+            .await
+            .map_err(|_| BlenderProcessError::timeout(blender_file, timeout))?
+            .map_err(BlenderProcessError::Spawn)?;
+
+        stdout += &exported_blender_data(output)?;
+    }
+
+    Ok(stdout)
+}
+
+fn blender_command(blender_files: &[PathBuf], config: &BlenderExportConfig) -> Command {
+    let mut command = Command::new("blender");
+    command
+        .args(export_blender_data_args(blender_files, config))
+        .kill_on_drop(true);
+
+    command
+}
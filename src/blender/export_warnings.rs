@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+/// The warnings that the export scripts reported for each object, keyed by the absolute path of
+/// the exported file and then by the object's name.
+pub type ExportWarningsByFilename = HashMap<String, HashMap<String, Vec<ExportWarning>>>;
+
+/// Printed to stdout by the export scripts whenever they skip or can't export something.
+const WARNING_MARKER: &str = "WARN_JSON ";
+
+/// Something that the export scripts skipped or couldn't export, so that data that would
+/// otherwise go missing without a trace gets reported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportWarning {
+    pub(crate) blend_file: String,
+    pub(crate) object: String,
+    pub(crate) kind: ExportWarningKind,
+    pub(crate) message: String,
+}
+
+/// What kind of data an [`ExportWarning`] is about.
+///
+/// [`ExportWarning`]: struct.ExportWarning.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ExportWarningKind {
+    /// A mesh has faces with more than 4 vertices, which can't be triangulated.
+    NGon,
+    /// A mesh has uv maps other than its active uv map and its lightmap uv map, which aren't
+    /// exported.
+    UnexportedUvLayers,
+    /// An object has constraints, which aren't exported. Armature actions include the effects
+    /// of IK constraints.
+    Constraints,
+    /// An object is mirrored by a negative scale, so its faces wind the other way once it is
+    /// placed in the world.
+    NegativeScale,
+    /// A shape key's driver reads something other than bone transforms or isn't linear, so the
+    /// shape key is exported without it. See `blender_mesh::ShapeKeyDriver`.
+    ShapeKeyDrivers,
+    /// A warning that this version of landon doesn't know about.
+    #[serde(other)]
+    Other,
+}
+
+impl ExportWarning {
+    /// The absolute path of the `.blend` file that the object was exported from.
+    pub fn blend_file(&self) -> &String {
+        &self.blend_file
+    }
+
+    /// The name of the object that the warning is about.
+    pub fn object(&self) -> &String {
+        &self.object
+    }
+
+    /// What kind of data the warning is about.
+    pub fn kind(&self) -> ExportWarningKind {
+        self.kind
+    }
+
+    /// A description of what was skipped.
+    pub fn message(&self) -> &String {
+        &self.message
+    }
+}
+
+/// Find the warnings that the export scripts reported while exporting every file, grouped by
+/// file and then by object.
+///
+/// Warnings in stdout look like:
+///
+/// WARN_JSON {"blend_file": "/path/to/file.blend", "object": "Rock", "kind": "NGon", "message": "..."}
+pub fn parse_export_warnings_from_blender_stdout(blender_stdout: &str) -> ExportWarningsByFilename {
+    let mut warnings = ExportWarningsByFilename::new();
+
+    for line in blender_stdout.lines() {
+        if !line.starts_with(WARNING_MARKER) {
+            continue;
+        }
+
+        if let Ok(warning) = serde_json::from_str::<ExportWarning>(&line[WARNING_MARKER.len()..]) {
+            warnings
+                .entry(warning.blend_file.clone())
+                .or_default()
+                .entry(warning.object.clone())
+                .or_default()
+                .push(warning);
+        }
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that warnings are grouped by file and object, including kinds that we don't know
+    /// about.
+    #[test]
+    fn parse_export_warnings() {
+        let stdout = r#"Blender 2.93
+WARN_JSON {"blend_file": "/level.blend", "object": "Rock", "kind": "NGon", "message": "2 faces have more than 4 vertices"}
+WARN_JSON {"blend_file": "/level.blend", "object": "Rock", "kind": "NegativeScale", "message": "Scaled by -1"}
+WARN_JSON {"blend_file": "/level.blend", "object": "Door", "kind": "Drivers", "message": "Drivers are not exported"}
+"#;
+
+        let warnings = parse_export_warnings_from_blender_stdout(stdout);
+        let level = &warnings["/level.blend"];
+
+        let kinds: Vec<ExportWarningKind> = level["Rock"].iter().map(|w| w.kind()).collect();
+        assert_eq!(
+            kinds,
+            vec![ExportWarningKind::NGon, ExportWarningKind::NegativeScale]
+        );
+        assert_eq!(level["Door"][0].kind(), ExportWarningKind::Other);
+    }
+}
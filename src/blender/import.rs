@@ -0,0 +1,64 @@
+use crate::{BlenderProcessError, LandonError};
+use blender_mesh::BlenderMesh;
+use std::env::temp_dir;
+use std::path::Path;
+use std::process::Command;
+
+/// A script that creates or updates a mesh object in a .blend file from landon mesh JSON.
+///
+/// Defines `import_mesh_from_json(json_filepath, blend_filepath)`.
+pub static IMPORT_MESH_FROM_JSON: &'static str = include_str!("../../blender-json-to-mesh.py");
+
+/// Write a mesh into a `.blend` file so that it can be reviewed inside of Blender.
+///
+/// If the `.blend` file does not exist it will be created. If the file already has an object
+/// with the same name as the mesh then that object's mesh data gets replaced, otherwise a new
+/// object is created.
+///
+/// This is the reverse of exporting - useful for meshes that were generated procedurally in Rust
+/// or were modified by landon's processing steps.
+pub fn import_mesh_into_blender_file(
+    mesh: &BlenderMesh,
+    blend_file: &dyn AsRef<Path>,
+) -> Result<(), LandonError> {
+    let mesh_json_path = temp_dir().join(format!(
+        "landon-import-{}-{}.json",
+        std::process::id(),
+        mesh.name()
+            .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+    ));
+    std::fs::write(&mesh_json_path, serde_json::to_string(mesh)?)
+        .map_err(BlenderProcessError::TempFile)?;
+
+    // The file paths are passed as arguments after `--`, which Blender leaves for our script,
+    // so that we don't need to escape them within the Python source
+    let import_script = format!(
+        r#"{}
+import_mesh_from_json(*sys.argv[sys.argv.index('--') + 1:])
+"#,
+        IMPORT_MESH_FROM_JSON,
+    );
+
+    let output = Command::new("blender")
+        .arg("--background")
+        .arg("-noaudio")
+        // Without this an exception in our script would still exit successfully
+        .args(&["--python-exit-code", "1"])
+        .args(&["--python-expr", &import_script])
+        .arg("--")
+        .arg(&mesh_json_path)
+        .arg(blend_file.as_ref())
+        .output();
+
+    let _ = std::fs::remove_file(&mesh_json_path);
+
+    let output = output.map_err(BlenderProcessError::Spawn)?;
+
+    if !output.status.success() {
+        return Err(BlenderProcessError::Import(String::from_utf8(
+            output.stderr,
+        )?))?;
+    }
+
+    Ok(())
+}
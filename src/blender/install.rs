@@ -1,3 +1,4 @@
+use crate::{BlenderProcessError, LandonError};
 use std::env::temp_dir;
 use std::fs::File;
 use std::io::Write;
@@ -6,13 +7,15 @@ use std::process::Command;
 /// Install the blender mesh exporter addon.
 ///
 /// This gives you access to `bpy.ops.import_export.mesh2json()` from Blender
-pub fn install_mesh_to_json() -> std::io::Result<()> {
+pub fn install_mesh_to_json() -> Result<(), LandonError> {
     // Write our addon to a tmp file. Our `install_mesh_to_json_script` will look for this tmp file
     // when installing the addon.
     let addon_file_path = temp_dir().join("blender-mesh-to-json.py");
     let mesh_to_json_addon = include_str!("../../blender-mesh-to-json.py");
-    let mut addon_file = File::create(&addon_file_path)?;
-    addon_file.write_all(mesh_to_json_addon.as_bytes()).unwrap();
+    let mut addon_file = File::create(&addon_file_path).map_err(BlenderProcessError::TempFile)?;
+    addon_file
+        .write_all(mesh_to_json_addon.as_bytes())
+        .map_err(BlenderProcessError::TempFile)?;
 
     let install_mesh_to_json_script = format!(
         r#"
@@ -41,9 +44,8 @@ bpy.ops.wm.save_userpref()
         // https://blenderartists.org/t/cannot-run-blender-on-ubuntu-server-12-04lts/614415
         .arg("-noaudio")
         .spawn()
-        .expect("blender must be in your $PATH")
-        .wait()
-        .unwrap();
+        .and_then(|mut blender| blender.wait())
+        .map_err(BlenderProcessError::Spawn)?;
 
     Ok(())
 }
@@ -51,13 +53,15 @@ bpy.ops.wm.save_userpref()
 /// Install the blender armature exporter addon.
 ///
 /// This gives you access to `bpy.ops.import_export.armature2json()` from Blender
-pub fn install_armature_to_json() -> std::io::Result<()> {
+pub fn install_armature_to_json() -> Result<(), LandonError> {
     // Write our addon to a tmp file. Our `install_armature_to_json_script` will look for this tmp file
     // when installing the addon.
     let addon_file_path = temp_dir().join("blender-armature-to-json.py");
     let armature_to_json = include_str!("../../blender-armature-to-json.py");
-    let mut addon_file = File::create(&addon_file_path)?;
-    addon_file.write_all(armature_to_json.as_bytes()).unwrap();
+    let mut addon_file = File::create(&addon_file_path).map_err(BlenderProcessError::TempFile)?;
+    addon_file
+        .write_all(armature_to_json.as_bytes())
+        .map_err(BlenderProcessError::TempFile)?;
 
     let install_armature_to_json_script = format!(
         r#"
@@ -82,9 +86,50 @@ bpy.ops.wm.save_userpref()
         // https://blenderartists.org/t/cannot-run-blender-on-ubuntu-server-12-04lts/614415
         .arg("-noaudio")
         .spawn()
-        .expect("blender must be in your $PATH")
-        .wait()
-        .unwrap();
+        .and_then(|mut blender| blender.wait())
+        .map_err(BlenderProcessError::Spawn)?;
+
+    Ok(())
+}
+
+/// Install the blender object animation exporter addon.
+///
+/// This gives you access to `bpy.ops.import_export.objectanimation2json()` from Blender
+pub fn install_object_animation_to_json() -> Result<(), LandonError> {
+    // Write our addon to a tmp file. Our `install_object_animation_to_json_script` will look for
+    // this tmp file when installing the addon.
+    let addon_file_path = temp_dir().join("blender-object-animation-to-json.py");
+    let object_animation_to_json = include_str!("../../blender-object-animation-to-json.py");
+    let mut addon_file = File::create(&addon_file_path).map_err(BlenderProcessError::TempFile)?;
+    addon_file
+        .write_all(object_animation_to_json.as_bytes())
+        .map_err(BlenderProcessError::TempFile)?;
+
+    let install_object_animation_to_json_script = format!(
+        r#"
+import bpy
+
+addonFilePath = r'{}'
+
+# Install the addon, enable it and save the user's preferences so that it
+# is available whenever Blender is opened in the future
+bpy.ops.preferences.addon_install(filepath=addonFilePath)
+bpy.ops.preferences.addon_enable(module='blender-object-animation-to-json')
+bpy.ops.wm.save_userpref()
+    "#,
+        addon_file_path.display()
+    );
+
+    // TODO: Support an environment variable to override the path to the executable
+    let blender_executable = "blender";
+    Command::new(blender_executable)
+        .arg("--background")
+        .args(&["--python-expr", &install_object_animation_to_json_script])
+        // https://blenderartists.org/t/cannot-run-blender-on-ubuntu-server-12-04lts/614415
+        .arg("-noaudio")
+        .spawn()
+        .and_then(|mut blender| blender.wait())
+        .map_err(BlenderProcessError::Spawn)?;
 
     Ok(())
 }
@@ -0,0 +1,123 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// The `.blend` libraries that each exported file links data from, keyed by the absolute path of
+/// the exported file.
+pub type LinkedLibrariesByFilename = HashMap<String, Vec<PathBuf>>;
+
+/// Printed to stdout by [`EXPORT_BLENDER_DATA`] for every library that a file links from.
+///
+/// [`EXPORT_BLENDER_DATA`]: static.EXPORT_BLENDER_DATA.html
+const LINKED_LIBRARY_MARKER: &str = "LINKED_LIBRARY_JSON ";
+
+#[derive(Deserialize)]
+struct LinkedLibraryJson {
+    blend_file: String,
+    library: PathBuf,
+}
+
+/// Find the libraries that every exported `.blend` file links data from.
+///
+/// Library paths are absolute, but the libraries might not exist if they were moved or deleted
+/// after being linked.
+pub fn parse_linked_libraries_from_blender_stdout(
+    blender_stdout: &str,
+) -> LinkedLibrariesByFilename {
+    let mut linked_libraries = LinkedLibrariesByFilename::new();
+
+    for line in blender_stdout.lines() {
+        if !line.starts_with(LINKED_LIBRARY_MARKER) {
+            continue;
+        }
+
+        if let Ok(linked) =
+            serde_json::from_str::<LinkedLibraryJson>(&line[LINKED_LIBRARY_MARKER.len()..])
+        {
+            let libraries = linked_libraries.entry(linked.blend_file).or_default();
+            if !libraries.contains(&linked.library) {
+                libraries.push(linked.library);
+            }
+        }
+    }
+
+    linked_libraries
+}
+
+/// The libraries that were linked in the stdout that exist and haven't been exported yet.
+///
+/// They get added to `exported`, which holds canonical paths, so that a library that
+/// (indirectly) links back to an earlier file doesn't get exported again.
+pub(crate) fn unexported_libraries(
+    blender_stdout: &str,
+    exported: &mut HashSet<PathBuf>,
+) -> Vec<PathBuf> {
+    let mut libraries: Vec<PathBuf> = parse_linked_libraries_from_blender_stdout(blender_stdout)
+        .into_values()
+        .flatten()
+        .filter_map(|library| std::fs::canonicalize(library).ok())
+        .filter(|library| exported.insert(library.clone()))
+        .collect();
+    libraries.sort();
+
+    libraries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that libraries are found per file, without duplicates.
+    #[test]
+    fn parse_linked_libraries() {
+        let stdout = r#"Blender 2.92
+LINKED_LIBRARY_JSON {"blend_file": "/level.blend", "library": "/props.blend"}
+LINKED_LIBRARY_JSON {"blend_file": "/level.blend", "library": "/props.blend"}
+LINKED_LIBRARY_JSON {"blend_file": "/level.blend", "library": "/characters.blend"}
+"#;
+
+        let linked = parse_linked_libraries_from_blender_stdout(stdout);
+
+        assert_eq!(
+            linked["/level.blend"],
+            vec![
+                PathBuf::from("/props.blend"),
+                PathBuf::from("/characters.blend")
+            ]
+        );
+    }
+
+    /// Verify that a library that links back to a file that was already exported doesn't get
+    /// exported again.
+    #[test]
+    fn libraries_that_link_each_other() {
+        let dir = std::env::temp_dir().join(format!("landon-linked-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let a = dir.join("a.blend");
+        let b = dir.join("b.blend");
+        std::fs::write(&a, b"").unwrap();
+        std::fs::write(&b, b"").unwrap();
+        let a = std::fs::canonicalize(a).unwrap();
+        let b = std::fs::canonicalize(b).unwrap();
+
+        let linked = |from: &PathBuf, to: &PathBuf| {
+            format!(
+                "LINKED_LIBRARY_JSON {{\"blend_file\": {:?}, \"library\": {:?}}}\n",
+                from, to
+            )
+        };
+
+        let mut exported = HashSet::new();
+        exported.insert(a.clone());
+
+        let a_stdout = linked(&a, &b) + &linked(&a, &dir.join("missing.blend"));
+        assert_eq!(
+            unexported_libraries(&a_stdout, &mut exported),
+            vec![b.clone()]
+        );
+
+        let b_stdout = a_stdout + &linked(&b, &a);
+        assert!(unexported_libraries(&b_stdout, &mut exported).is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,199 @@
+use blender_armature::{ArmaturesByFilename, BlenderArmature};
+use blender_mesh::{BlenderMesh, MeshesByFilename};
+use rayon::prelude::*;
+
+const MESH_MARKERS: (&str, &str) = ("START_MESH_JSON", "END_MESH_JSON");
+const ARMATURE_MARKERS: (&str, &str) = ("START_ARMATURE_JSON", "END_ARMATURE_JSON");
+
+/// The meshes and armatures that the export scripts wrote to Blender's stdout.
+#[derive(Debug, Default)]
+pub struct ParsedBlenderStdout {
+    /// The same as `blender_mesh::parse_meshes_from_blender_stdout`.
+    pub meshes: MeshesByFilename,
+    /// The same as `blender_armature::parse_armatures_from_blender_stdout`.
+    pub armatures: ArmaturesByFilename,
+}
+
+/// An error while parsing the meshes and armatures in Blender's stdout.
+#[derive(Debug, thiserror::Error)]
+pub enum BlenderStdoutError {
+    /// A block was started but never ended, usually because Blender crashed part way through
+    /// writing it.
+    #[error("{header} was never ended")]
+    Unterminated {
+        /// The line that started the block, such as `START_MESH_JSON /level.blend Rock`
+        header: String,
+    },
+    /// A block's JSON could not be deserialized.
+    #[error("Invalid JSON after {header}: {source}")]
+    Json {
+        /// The line that started the block, such as `START_MESH_JSON /level.blend Rock`
+        header: String,
+        /// Why the JSON could not be deserialized
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// A mesh or armature block within Blender's stdout.
+struct Block<'a> {
+    kind: BlockKind,
+    header: &'a str,
+    json: &'a str,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum BlockKind {
+    Mesh,
+    Armature,
+}
+
+enum ParsedBlock {
+    Mesh(String, Box<BlenderMesh>),
+    Armature(String, String, Box<BlenderArmature>),
+}
+
+/// Parse the meshes and armatures in Blender's stdout on rayon's thread pool, for batch exports
+/// whose stdout is large enough for deserializing it to hold up the pipeline.
+///
+/// Blocks are found on the calling thread and then deserialized in parallel. The result is the
+/// same as parsing the meshes and the armatures one after the other, except that invalid blocks
+/// are returned as errors instead of panicking.
+pub fn parse_blender_stdout_parallel(
+    blender_stdout: &str,
+) -> Result<ParsedBlenderStdout, BlenderStdoutError> {
+    let blocks = find_blocks(blender_stdout)?;
+
+    let parsed: Vec<ParsedBlock> = blocks
+        .par_iter()
+        .map(parse_block)
+        .collect::<Result<_, _>>()?;
+
+    // Later blocks replace earlier ones with the same name, the same as the sequential parsers
+    let mut output = ParsedBlenderStdout::default();
+    for block in parsed {
+        match block {
+            ParsedBlock::Mesh(filename, mesh) => {
+                output
+                    .meshes
+                    .entry(filename)
+                    .or_default()
+                    .insert(mesh.key(), *mesh);
+            }
+            ParsedBlock::Armature(filename, name, armature) => {
+                output
+                    .armatures
+                    .entry(filename)
+                    .or_default()
+                    .insert(name, *armature);
+            }
+        };
+    }
+
+    Ok(output)
+}
+
+fn find_blocks(blender_stdout: &str) -> Result<Vec<Block<'_>>, BlenderStdoutError> {
+    let mut blocks = vec![];
+    let mut index = 0;
+
+    loop {
+        let remaining = &blender_stdout[index..];
+        let next_mesh = remaining.find(MESH_MARKERS.0);
+        let next_armature = remaining.find(ARMATURE_MARKERS.0);
+
+        let (kind, start) = match (next_mesh, next_armature) {
+            (Some(mesh), Some(armature)) if armature < mesh => (BlockKind::Armature, armature),
+            (Some(mesh), _) => (BlockKind::Mesh, mesh),
+            (None, Some(armature)) => (BlockKind::Armature, armature),
+            (None, None) => return Ok(blocks),
+        };
+        let end_marker = match kind {
+            BlockKind::Mesh => MESH_MARKERS.1,
+            BlockKind::Armature => ARMATURE_MARKERS.1,
+        };
+
+        let block = &remaining[start..];
+        let header_end = block.find('\n').unwrap_or(block.len());
+        let header = block[..header_end].trim_end();
+
+        let end = block
+            .find(end_marker)
+            .ok_or_else(|| BlenderStdoutError::Unterminated {
+                header: header.to_string(),
+            })?;
+
+        blocks.push(Block {
+            kind,
+            header,
+            json: &block[header_end.min(end)..end],
+        });
+
+        index += start + end + end_marker.len();
+    }
+}
+
+fn parse_block(block: &Block) -> Result<ParsedBlock, BlenderStdoutError> {
+    let json_error = |source| BlenderStdoutError::Json {
+        header: block.header.to_string(),
+        source,
+    };
+
+    // START_MESH_JSON /path/to/file.blend name
+    let filename = block.header.split(' ').nth(1).unwrap_or("").to_string();
+
+    match block.kind {
+        BlockKind::Mesh => {
+            let mesh = serde_json::from_str(block.json).map_err(json_error)?;
+            Ok(ParsedBlock::Mesh(filename, mesh))
+        }
+        BlockKind::Armature => {
+            let name = block.header.rsplit(' ').next().unwrap_or("").to_string();
+            let armature = serde_json::from_str(block.json).map_err(json_error)?;
+            Ok(ParsedBlock::Armature(filename, name, armature))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blender_armature::parse_armatures_from_blender_stdout;
+    use blender_mesh::parse_meshes_from_blender_stdout;
+
+    /// Verify that parsing in parallel gives the same meshes and armatures as the sequential
+    /// parsers, and that a block that was cut off is an error.
+    #[test]
+    fn parallel_matches_sequential() {
+        let mut rock = BlenderMesh::cube(1.);
+        rock.set_name("Rock".to_string());
+        let mut tree = BlenderMesh::cube(2.);
+        tree.set_name("Tree".to_string());
+        let mut rig = BlenderArmature::default();
+        rig.set_name("Rig".to_string());
+
+        let stdout = format!(
+            "Blender 2.93\nSTART_MESH_JSON /level.blend Rock\n{}\nEND_MESH_JSON /level.blend Rock\nRead prefs\nSTART_ARMATURE_JSON /hero.blend Rig\n{}\nEND_ARMATURE_JSON /hero.blend Rig\nSTART_MESH_JSON /hero.blend Tree\n{}\nEND_MESH_JSON /hero.blend Tree\n",
+            serde_json::to_string(&rock).unwrap(),
+            serde_json::to_string(&rig).unwrap(),
+            serde_json::to_string(&tree).unwrap(),
+        );
+
+        let parsed = parse_blender_stdout_parallel(&stdout).unwrap();
+
+        assert_eq!(parsed.meshes, parse_meshes_from_blender_stdout(&stdout));
+        assert_eq!(
+            parsed.armatures,
+            parse_armatures_from_blender_stdout(&stdout)
+        );
+        assert_eq!(parsed.meshes["/hero.blend"]["Tree"], tree);
+
+        let cut_off = &stdout[..stdout.find("END_ARMATURE_JSON").unwrap()];
+        match parse_blender_stdout_parallel(cut_off) {
+            Err(BlenderStdoutError::Unterminated { header }) => {
+                assert_eq!(header, "START_ARMATURE_JSON /hero.blend Rig")
+            }
+            _ => unreachable!(),
+        };
+    }
+}
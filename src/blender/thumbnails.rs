@@ -0,0 +1,103 @@
+use blender_mesh::{MeshKey, MeshesByFilename};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// The thumbnail that was rendered for each object, keyed by the absolute path of the exported
+/// file and then by the object's `blender_mesh::MeshKey`.
+pub type ThumbnailsByFilename = HashMap<String, HashMap<MeshKey, PathBuf>>;
+
+/// Printed to stdout by [`EXPORT_BLENDER_DATA`] for every thumbnail that it renders.
+///
+/// [`EXPORT_BLENDER_DATA`]: static.EXPORT_BLENDER_DATA.html
+const THUMBNAIL_MARKER: &str = "THUMBNAIL_JSON ";
+
+#[derive(Deserialize)]
+struct ThumbnailJson {
+    blend_file: String,
+    object: String,
+    library: Option<String>,
+    path: PathBuf,
+}
+
+/// Find the thumbnails that were rendered when exporting with
+/// [`BlenderExportConfig.thumbnail_dir`].
+///
+/// [`BlenderExportConfig.thumbnail_dir`]: struct.BlenderExportConfig.html#structfield.thumbnail_dir
+pub fn parse_thumbnails_from_blender_stdout(blender_stdout: &str) -> ThumbnailsByFilename {
+    let mut thumbnails = ThumbnailsByFilename::new();
+
+    for line in blender_stdout.lines() {
+        if !line.starts_with(THUMBNAIL_MARKER) {
+            continue;
+        }
+
+        if let Ok(thumbnail) =
+            serde_json::from_str::<ThumbnailJson>(&line[THUMBNAIL_MARKER.len()..])
+        {
+            thumbnails.entry(thumbnail.blend_file).or_default().insert(
+                MeshKey::new(&thumbnail.object, thumbnail.library.as_deref()),
+                thumbnail.path,
+            );
+        }
+    }
+
+    thumbnails
+}
+
+/// Record the path of every mesh's thumbnail in the mesh. See `BlenderMesh::thumbnail`.
+pub fn set_mesh_thumbnails(meshes: &mut MeshesByFilename, thumbnails: &ThumbnailsByFilename) {
+    for (filename, meshes) in meshes.iter_mut() {
+        let thumbnails = match thumbnails.get(filename) {
+            Some(thumbnails) => thumbnails,
+            None => continue,
+        };
+
+        for (key, mesh) in meshes.iter_mut() {
+            if let Some(thumbnail) = thumbnails.get(key) {
+                mesh.set_thumbnail(Some(thumbnail.to_string_lossy().to_string()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blender_mesh::BlenderMesh;
+
+    /// Verify that thumbnails are found per file and object and recorded in their meshes.
+    #[test]
+    fn parse_and_set_thumbnails() {
+        let stdout = r#"Blender 2.93
+Fra:1 Mem:12.00M | Rendering 1 / 1 samples
+THUMBNAIL_JSON {"blend_file": "/props.blend", "object": "Crate", "library": null, "path": "/thumbs/props_Crate.png"}
+THUMBNAIL_JSON {"blend_file": "/level.blend", "object": "Crate", "library": "/props.blend", "path": "/thumbs/level_Crate.png"}
+"#;
+
+        let thumbnails = parse_thumbnails_from_blender_stdout(stdout);
+        assert_eq!(
+            thumbnails["/level.blend"]["Crate [/props.blend]"],
+            PathBuf::from("/thumbs/level_Crate.png")
+        );
+
+        let mut meshes = MeshesByFilename::new();
+        let mut crate_mesh = BlenderMesh::cube(1.);
+        crate_mesh.set_name("Crate".to_string());
+        meshes
+            .entry("/props.blend".to_string())
+            .or_default()
+            .insert(crate_mesh.key(), crate_mesh.clone());
+        meshes
+            .entry("/other.blend".to_string())
+            .or_default()
+            .insert(crate_mesh.key(), crate_mesh);
+
+        set_mesh_thumbnails(&mut meshes, &thumbnails);
+
+        assert_eq!(
+            meshes["/props.blend"]["Crate"].thumbnail(),
+            Some(&"/thumbs/props_Crate.png".to_string())
+        );
+        assert_eq!(meshes["/other.blend"]["Crate"].thumbnail(), None);
+    }
+}
@@ -0,0 +1,152 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+/// The bytes at the start of every gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// The bytes at the start of every zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Vertex heavy JSON is mostly repeated digits, so it usually compresses to a tenth of its size
+/// or less.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Compression {
+    /// Supported by every browser through `Content-Encoding: gzip`.
+    Gzip,
+    /// Smaller and much faster to decompress than gzip.
+    Zstd,
+}
+
+impl Compression {
+    /// The compression that the bytes start with, or `None` if they aren't compressed.
+    pub fn detect(bytes: &[u8]) -> Option<Compression> {
+        if bytes.starts_with(&GZIP_MAGIC) {
+            Some(Compression::Gzip)
+        } else if bytes.starts_with(&ZSTD_MAGIC) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+
+    /// The extension that is added to the names of compressed files, such as `exported.json.zst`.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "gz",
+            Compression::Zstd => "zst",
+        }
+    }
+
+    /// Compress the bytes.
+    pub fn compress(&self, bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+                encoder.write_all(bytes)?;
+                encoder.finish()
+            }
+            Compression::Zstd => zstd::encode_all(bytes, zstd::DEFAULT_COMPRESSION_LEVEL),
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(compression: &str) -> Result<Self, Self::Err> {
+        match compression {
+            "gzip" => Ok(Compression::Gzip),
+            "zstd" => Ok(Compression::Zstd),
+            _ => Err(format!(
+                "Unknown compression {}, expected gzip or zstd",
+                compression
+            )),
+        }
+    }
+}
+
+/// An error while compressing or decompressing JSON.
+#[derive(Debug, thiserror::Error)]
+pub enum CompressionError {
+    /// The bytes could not be compressed or decompressed.
+    #[error("Could not compress or decompress: {0}")]
+    Io(#[from] std::io::Error),
+    /// The JSON could not be serialized or deserialized.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Decompress gzip or zstd compressed bytes. Bytes that aren't compressed are returned as is, so
+/// that readers can load compressed and uncompressed exports alike.
+pub fn decompress(bytes: &[u8]) -> std::io::Result<Cow<'_, [u8]>> {
+    let decompressed = match Compression::detect(bytes) {
+        Some(Compression::Gzip) => {
+            let mut decompressed = vec![];
+            flate2::read::GzDecoder::new(bytes).read_to_end(&mut decompressed)?;
+            decompressed
+        }
+        Some(Compression::Zstd) => zstd::decode_all(bytes)?,
+        None => return Ok(Cow::Borrowed(bytes)),
+    };
+
+    Ok(Cow::Owned(decompressed))
+}
+
+/// Serialize to JSON and compress it, such as for the exports of web games where download size
+/// matters.
+///
+/// ```
+/// # use landon::{from_compressed_json, to_compressed_json, Compression};
+/// let positions = vec![0.5f32; 3000];
+///
+/// let compressed = to_compressed_json(&positions, Compression::Zstd).unwrap();
+/// assert!(compressed.len() < serde_json::to_vec(&positions).unwrap().len() / 10);
+///
+/// let decompressed: Vec<f32> = from_compressed_json(&compressed).unwrap();
+/// assert_eq!(decompressed, positions);
+/// ```
+pub fn to_compressed_json<T: Serialize>(
+    value: &T,
+    compression: Compression,
+) -> Result<Vec<u8>, CompressionError> {
+    let json = serde_json::to_vec(value)?;
+    Ok(compression.compress(&json)?)
+}
+
+/// Deserialize JSON that was compressed with gzip or zstd, detected from the first few bytes,
+/// or that wasn't compressed at all.
+pub fn from_compressed_json<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CompressionError> {
+    let json = decompress(bytes)?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blender_mesh::BlenderMesh;
+
+    /// Verify that meshes survive a round trip through both compressions, and that uncompressed
+    /// JSON can be read the same way.
+    #[test]
+    fn round_trip_compressed_json() {
+        let mesh = BlenderMesh::cube(1.);
+        let json = serde_json::to_vec(&mesh).unwrap();
+
+        for compression in [Compression::Gzip, Compression::Zstd].iter() {
+            let compressed = to_compressed_json(&mesh, *compression).unwrap();
+
+            assert_eq!(Compression::detect(&compressed), Some(*compression));
+            assert!(compressed.len() < json.len());
+            assert_eq!(
+                from_compressed_json::<BlenderMesh>(&compressed).unwrap(),
+                mesh
+            );
+        }
+
+        assert_eq!(Compression::detect(&json), None);
+        assert_eq!(from_compressed_json::<BlenderMesh>(&json).unwrap(), mesh);
+    }
+}
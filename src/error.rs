@@ -0,0 +1,268 @@
+use blender_armature::{FlattenArmatureError, MergeArmatureError, MirrorError, RestPoseError};
+use blender_mesh::{
+    BinaryError, CombineIndicesError, DebugColorError, EvaluatePositionsError, FlattenMeshError,
+    IntegrityError, InterleaveError, MeshBuilderError, MeshletError, PlyError, SubmeshError,
+    TangentError, TerrainError, VertexAttributeError, WeightedNormalsError,
+};
+use std::path::{Path, PathBuf};
+use std::string::FromUtf8Error;
+use std::time::Duration;
+
+/// Any error that can happen while using landon.
+///
+/// Errors are grouped by the category of failure so that you can, for example, retry when
+/// Blender fails to run while treating invalid data as a bug.
+#[derive(Debug, thiserror::Error)]
+pub enum LandonError {
+    /// Blender could not be run, or it failed while running.
+    #[error(transparent)]
+    Blender(#[from] BlenderProcessError),
+    /// Data could not be parsed.
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    /// Data did not uphold one of its invariants.
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+    /// Data could not be processed or written.
+    #[error("{context}: {source}")]
+    Processing {
+        /// What was being done when processing failed, such as "Writing FBX for Suzanne"
+        context: String,
+        /// Why processing failed
+        #[source]
+        source: ProcessingError,
+    },
+}
+
+/// An error while running a `blender` child process.
+#[derive(Debug, thiserror::Error)]
+pub enum BlenderProcessError {
+    /// The `blender` executable could not be started. It should be in your `$PATH`.
+    #[error("Could not run blender, is it in your $PATH? {0}")]
+    Spawn(#[source] std::io::Error),
+    /// A temporary file that Blender reads from could not be written.
+    #[error("Could not write a temporary file for Blender: {0}")]
+    TempFile(#[source] std::io::Error),
+    /// Blender wrote to stderr while exporting data.
+    #[error("Error while exporting data from blender: {0}")]
+    Export(String),
+    /// Blender exited unsuccessfully while importing data.
+    #[error("Error while importing data into blender: {0}")]
+    Import(String),
+    /// Blender was killed because a file took longer than the configured timeout to export.
+    #[error("Blender was killed after exporting {file:?} took longer than {seconds} seconds")]
+    Timeout {
+        /// The file that was being exported
+        file: PathBuf,
+        /// The timeout that was exceeded
+        seconds: f64,
+    },
+    /// Blender's stdout or stderr was not UTF-8.
+    #[error("Blender output was not valid UTF-8: {0}")]
+    InvalidUtf8(#[from] FromUtf8Error),
+}
+
+impl BlenderProcessError {
+    pub(crate) fn timeout(file: &Path, timeout: Duration) -> Self {
+        BlenderProcessError::Timeout {
+            file: file.to_path_buf(),
+            seconds: timeout.as_secs_f64(),
+        }
+    }
+}
+
+/// An error while parsing data.
+#[derive(Debug, thiserror::Error)]
+pub enum ParseError {
+    /// JSON could not be serialized or deserialized.
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A PLY file could not be read.
+    #[error(transparent)]
+    Ply(#[from] PlyError),
+    /// A binary mesh could not be read.
+    #[error(transparent)]
+    Binary(#[from] BinaryError),
+    /// Compressed JSON could not be read or written.
+    #[cfg(feature = "compression")]
+    #[error(transparent)]
+    Compression(#[from] crate::CompressionError),
+    /// The meshes and armatures in Blender's stdout could not be parsed.
+    #[cfg(feature = "rayon")]
+    #[error(transparent)]
+    BlenderStdout(#[from] crate::BlenderStdoutError),
+}
+
+/// Data that did not uphold one of its invariants.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum ValidationError {
+    #[error(transparent)]
+    VertexAttribute(#[from] VertexAttributeError),
+    #[error(transparent)]
+    FlattenMeshes(#[from] FlattenMeshError),
+    #[error(transparent)]
+    FlattenArmatures(#[from] FlattenArmatureError),
+    #[error(transparent)]
+    Submesh(#[from] SubmeshError),
+    #[error(transparent)]
+    Binding(#[from] crate::BindingError),
+    #[error(transparent)]
+    MeshBuilder(#[from] MeshBuilderError),
+    #[error(transparent)]
+    MergeArmatures(#[from] MergeArmatureError),
+    #[error(transparent)]
+    Integrity(#[from] IntegrityError),
+}
+
+/// An error from one of landon's processing steps.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum ProcessingError {
+    #[error(transparent)]
+    CombineIndices(#[from] CombineIndicesError),
+    #[error(transparent)]
+    Tangents(#[from] TangentError),
+    #[error(transparent)]
+    WeightedNormals(#[from] WeightedNormalsError),
+    #[error(transparent)]
+    Interleave(#[from] InterleaveError),
+    #[error(transparent)]
+    Terrain(#[from] TerrainError),
+    #[error(transparent)]
+    Meshlets(#[from] MeshletError),
+    #[error(transparent)]
+    DebugColors(#[from] DebugColorError),
+    #[error(transparent)]
+    EvaluatePositions(#[from] EvaluatePositionsError),
+    #[error(transparent)]
+    Mirror(#[from] MirrorError),
+    #[error(transparent)]
+    RestPose(#[from] RestPoseError),
+    #[cfg(feature = "fbx")]
+    #[error(transparent)]
+    Fbx(#[from] crate::FbxError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Adds context to a processing step's error, turning it into a [`LandonError`].
+///
+/// ```
+/// # use landon::{LandonError, ProcessingContext};
+/// # use std::io::Write;
+/// fn write_report(out: &mut dyn Write) -> Result<(), LandonError> {
+///     out.write_all(b"report")
+///         .processing_context(|| "Writing the report")?;
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// [`LandonError`]: enum.LandonError.html
+pub trait ProcessingContext<T> {
+    /// Wrap the error in a [`LandonError::Processing`] that describes what was being done.
+    ///
+    /// [`LandonError::Processing`]: enum.LandonError.html#variant.Processing
+    fn processing_context<C: Into<String>>(
+        self,
+        context: impl FnOnce() -> C,
+    ) -> Result<T, LandonError>;
+}
+
+impl<T, E: Into<ProcessingError>> ProcessingContext<T> for Result<T, E> {
+    fn processing_context<C: Into<String>>(
+        self,
+        context: impl FnOnce() -> C,
+    ) -> Result<T, LandonError> {
+        self.map_err(|source| LandonError::Processing {
+            context: context().into(),
+            source: source.into(),
+        })
+    }
+}
+
+/// Implements `From` for an error that belongs to one of the categories so that `?` can convert
+/// it straight into a `LandonError`.
+macro_rules! landon_error_from {
+    ($category:ident, $($error:ty),*) => {
+        $(
+            impl From<$error> for LandonError {
+                fn from(error: $error) -> Self {
+                    LandonError::$category(error.into())
+                }
+            }
+        )*
+    };
+}
+
+landon_error_from!(Blender, FromUtf8Error);
+landon_error_from!(Parse, serde_json::Error, PlyError, BinaryError);
+#[cfg(feature = "compression")]
+landon_error_from!(Parse, crate::CompressionError);
+#[cfg(feature = "rayon")]
+landon_error_from!(Parse, crate::BlenderStdoutError);
+landon_error_from!(
+    Validation,
+    VertexAttributeError,
+    FlattenMeshError,
+    FlattenArmatureError,
+    SubmeshError,
+    crate::BindingError,
+    MeshBuilderError,
+    MergeArmatureError,
+    IntegrityError
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that errors convert into their category and that context is kept.
+    #[test]
+    fn categorize_errors() {
+        let error: LandonError = VertexAttributeError::DataNotMultipleOfAttributeSize {
+            data_len: 7,
+            attribute_size: 3,
+        }
+        .into();
+        match error {
+            LandonError::Validation(ValidationError::VertexAttribute(_)) => {}
+            _ => unreachable!(),
+        };
+
+        let error: LandonError = MergeArmatureError::NoArmatures.into();
+        match error {
+            LandonError::Validation(ValidationError::MergeArmatures(_)) => {}
+            _ => unreachable!(),
+        };
+
+        let error: LandonError = BinaryError::UnknownEndianness(7).into();
+        match error {
+            LandonError::Parse(ParseError::Binary(_)) => {}
+            _ => unreachable!(),
+        };
+
+        let error = Err::<(), _>(TangentError::NoVertexUvs)
+            .processing_context(|| "Combining indices for Suzanne")
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Combining indices for Suzanne: Cannot calculate vertex tangents for a mesh with no uvs"
+        );
+
+        let error = Err::<(), _>(CombineIndicesError::TooManyVertices {
+            mesh: "Terrain".to_string(),
+            vertex_count: 70_000,
+        })
+        .processing_context(|| "Combining indices for Terrain")
+        .unwrap_err();
+        match error {
+            LandonError::Processing {
+                source: ProcessingError::CombineIndices(_),
+                ..
+            } => {}
+            _ => unreachable!(),
+        };
+    }
+}
@@ -0,0 +1,705 @@
+//! Write meshes and armatures to [FBX] files using FBX's ASCII format, for tools that can only
+//! ingest FBX.
+//!
+//! Geometry, materials, skeletons, skin deformers and bind poses are written. Animations are not
+//! yet supported.
+//!
+//! [FBX]: https://en.wikipedia.org/wiki/FBX
+
+use self::node::{p, Node, Property};
+use blender_armature::BlenderArmature;
+use blender_mesh::{BlenderMesh, MaterialInput};
+use nalgebra::{Matrix3, Matrix4, Rotation3, Vector3};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+
+mod node;
+
+/// Write meshes and the armatures that they are parented to as an ASCII FBX 7.4 document.
+///
+/// A mesh whose [`armature_name`] matches one of the armatures is written with a skin deformer
+/// and a bind pose for that armature's bones. Meshes that are deformed by more than one armature
+/// are only skinned to their first armature.
+///
+/// Meshes and armatures are written as they were exported from Blender, Z up and in meters.
+///
+/// Bone matrices are expected to be column major, so you'll want to call [`transpose_actions`]
+/// on armatures that came straight from Blender before writing them.
+///
+/// [`armature_name`]: blender_mesh::BlenderMesh::armature_name
+/// [`transpose_actions`]: blender_armature::BlenderArmature::transpose_actions
+pub fn write_ascii_fbx(
+    out: &mut dyn Write,
+    meshes: &[&BlenderMesh],
+    armatures: &[&BlenderArmature],
+) -> Result<(), FbxError> {
+    let mut document = FbxDocument::default();
+
+    let mut skeletons = HashMap::new();
+    for armature in armatures {
+        skeletons.insert(armature.name().as_str(), document.add_armature(armature)?);
+    }
+
+    for mesh in meshes {
+        let skeleton = match mesh.armature_name() {
+            Some(armature_name) => {
+                Some(skeletons.get(armature_name.as_str()).ok_or_else(|| {
+                    FbxError::MissingArmature {
+                        mesh: mesh.name().to_string(),
+                        armature: armature_name.to_string(),
+                    }
+                })?)
+            }
+            None => None,
+        };
+
+        document.add_mesh(mesh, skeleton)?;
+    }
+
+    document.write(out)
+}
+
+/// An error while writing an FBX document
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum FbxError {
+    /// A mesh is parented to an armature that was not provided.
+    #[error("Mesh {mesh} is parented to armature {armature}, which was not provided")]
+    MissingArmature { mesh: String, armature: String },
+    /// A vertex is influenced by a bone that its armature does not have.
+    #[error("Mesh {mesh} is influenced by bone {bone_idx}, which its armature does not have")]
+    InvalidBoneIndex { mesh: String, bone_idx: u8 },
+    /// A bone's inverse bind pose could not be inverted into its bind pose.
+    #[error("The inverse bind pose of bone {bone_idx} in armature {armature} is not invertible")]
+    NonInvertibleBindPose { armature: String, bone_idx: usize },
+    /// Failed to write the document.
+    #[error("Failed to write FBX document: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// The FBX objects that were created for an armature.
+struct Skeleton {
+    /// The id of every bone's LimbNode model
+    bone_ids: Vec<i64>,
+    /// Every bone's world space bind pose
+    bind_poses: Vec<Matrix4<f32>>,
+    /// The inverse of every bone's bind pose, as the armature stores it, so that it never has to
+    /// be inverted back
+    inverse_bind_poses: Vec<Matrix4<f32>>,
+}
+
+#[derive(Default)]
+struct FbxDocument {
+    objects: Vec<Node>,
+    connections: Vec<Node>,
+    /// The number of objects of each type, for the document's Definitions
+    object_counts: BTreeMap<&'static str, i64>,
+    last_id: i64,
+}
+
+impl FbxDocument {
+    fn next_id(&mut self) -> i64 {
+        self.last_id += 1;
+        // Ids are arbitrary but can not be 0, since 0 is the scene's root node
+        1_000_000 + self.last_id
+    }
+
+    fn add_object(&mut self, object_type: &'static str, object: Node) {
+        *self.object_counts.entry(object_type).or_insert(0) += 1;
+        self.objects.push(object);
+    }
+
+    fn connect(&mut self, child: i64, parent: i64) {
+        self.connections
+            .push(Node::new("C").prop("OO").prop(child).prop(parent));
+    }
+
+    /// Add a Null model for the armature with a LimbNode model beneath it for every bone.
+    fn add_armature(&mut self, armature: &BlenderArmature) -> Result<Skeleton, FbxError> {
+        let armature_id = self.next_id();
+        self.add_model(armature_id, armature.name(), "Null", &Matrix4::identity());
+        self.connect(armature_id, 0);
+
+        let mut bone_names = HashMap::new();
+        for (name, idx) in armature.joint_indices().iter() {
+            bone_names.insert(*idx as usize, name.as_str());
+        }
+
+        let inverse_bind_poses: Vec<Matrix4<f32>> = armature
+            .inverse_bind_poses()
+            .iter()
+            .map(|inverse_bind_pose| inverse_bind_pose.to_matrix())
+            .collect();
+
+        let mut bind_poses = vec![];
+        for (bone_idx, inverse_bind_pose) in inverse_bind_poses.iter().enumerate() {
+            let bind_pose =
+                inverse_bind_pose
+                    .try_inverse()
+                    .ok_or_else(|| FbxError::NonInvertibleBindPose {
+                        armature: armature.name().to_string(),
+                        bone_idx,
+                    })?;
+            bind_poses.push(bind_pose);
+        }
+
+        let bone_ids: Vec<i64> = bind_poses.iter().map(|_| self.next_id()).collect();
+
+        for (bone_idx, bind_pose) in bind_poses.iter().enumerate() {
+            let parent = armature
+                .bone_child_to_parent()
+                .get(&(bone_idx as u8))
+                .map(|parent| *parent as usize)
+                .filter(|parent| *parent < bone_ids.len());
+
+            let (parent_id, local_transform) = match parent {
+                Some(parent) => (bone_ids[parent], inverse_bind_poses[parent] * bind_pose),
+                None => (armature_id, *bind_pose),
+            };
+
+            let name = match bone_names.get(&bone_idx) {
+                Some(name) => name.to_string(),
+                None => format!("Bone{}", bone_idx),
+            };
+
+            let bone_id = bone_ids[bone_idx];
+            self.add_model(bone_id, &name, "LimbNode", &local_transform);
+            self.connect(bone_id, parent_id);
+        }
+
+        Ok(Skeleton {
+            bone_ids,
+            bind_poses,
+            inverse_bind_poses,
+        })
+    }
+
+    fn add_model(&mut self, id: i64, name: &str, kind: &'static str, transform: &Matrix4<f32>) {
+        let (translation, rotation, scale) = decompose(transform);
+
+        let properties = Node::new("Properties70")
+            .child(p(
+                "Lcl Translation",
+                "Lcl Translation",
+                "",
+                "A",
+                translation,
+            ))
+            .child(p("Lcl Rotation", "Lcl Rotation", "", "A", rotation))
+            .child(p("Lcl Scaling", "Lcl Scaling", "", "A", scale));
+
+        let model = Node::new("Model")
+            .prop(id)
+            .prop(format!("Model::{}", name))
+            .prop(kind)
+            .child(Node::new("Version").prop(232i64))
+            .child(properties);
+        self.add_object("Model", model);
+
+        let attribute_id = self.next_id();
+        let type_flags = match kind {
+            "LimbNode" => "Skeleton",
+            _ => kind,
+        };
+        let attribute = Node::new("NodeAttribute")
+            .prop(attribute_id)
+            .prop(format!("NodeAttribute::{}", name))
+            .prop(kind)
+            .child(Node::new("TypeFlags").prop(type_flags));
+        self.add_object("NodeAttribute", attribute);
+        self.connect(attribute_id, id);
+    }
+
+    fn add_mesh(
+        &mut self,
+        mesh: &BlenderMesh,
+        skeleton: Option<&Skeleton>,
+    ) -> Result<(), FbxError> {
+        let model_id = self.next_id();
+        let model = Node::new("Model")
+            .prop(model_id)
+            .prop(format!("Model::{}", mesh.name()))
+            .prop("Mesh")
+            .child(Node::new("Version").prop(232i64))
+            .child(Node::new("Properties70"))
+            .child(Node::new("Culling").prop("CullingOff"));
+        self.add_object("Model", model);
+        self.connect(model_id, 0);
+
+        let geometry_id = self.next_id();
+        self.add_object("Geometry", geometry(geometry_id, mesh));
+        self.connect(geometry_id, model_id);
+
+        for material in mesh.materials_vec().iter() {
+            let mut properties = Node::new("Properties70");
+            if let MaterialInput::Uniform(color) = material.base_color() {
+                let color = color.iter().map(|c| Property::Double(*c as f64)).collect();
+                properties.push(p("DiffuseColor", "Color", "", "A", color));
+            }
+
+            let material_id = self.next_id();
+            let material = Node::new("Material")
+                .prop(material_id)
+                .prop(format!("Material::{}", material.name()))
+                .prop("")
+                .child(Node::new("Version").prop(102i64))
+                .child(Node::new("ShadingModel").prop("phong"))
+                .child(Node::new("MultiLayer").prop(0i64))
+                .child(properties);
+            self.add_object("Material", material);
+            self.connect(material_id, model_id);
+        }
+
+        if let Some(skeleton) = skeleton {
+            self.add_skin(mesh, model_id, geometry_id, skeleton)?;
+        }
+
+        Ok(())
+    }
+
+    /// Add a skin deformer with a cluster for every bone that influences the mesh, along with a
+    /// bind pose.
+    fn add_skin(
+        &mut self,
+        mesh: &BlenderMesh,
+        model_id: i64,
+        geometry_id: i64,
+        skeleton: &Skeleton,
+    ) -> Result<(), FbxError> {
+        let bone_count = skeleton.bone_ids.len();
+
+        // The vertices and weights that each bone influences
+        let mut clusters: Vec<(Vec<i32>, Vec<f64>)> = vec![(vec![], vec![]); bone_count];
+
+        let influences = mesh
+            .multi_indexed_vertex_attributes()
+            .bone_influences()
+            .map(|influences| influences.influences_per_vertex())
+            .unwrap_or_default();
+
+        for (vertex, (bone_indices, weights)) in influences.into_iter().enumerate() {
+            for (bone_idx, weight) in bone_indices.iter().zip(weights.iter()) {
+                if *bone_idx as usize >= bone_count {
+                    return Err(FbxError::InvalidBoneIndex {
+                        mesh: mesh.name().to_string(),
+                        bone_idx: *bone_idx,
+                    });
+                }
+
+                if *weight > 0.0 {
+                    let cluster = &mut clusters[*bone_idx as usize];
+                    cluster.0.push(vertex as i32);
+                    cluster.1.push(*weight as f64);
+                }
+            }
+        }
+
+        let skin_id = self.next_id();
+        let skin = Node::new("Deformer")
+            .prop(skin_id)
+            .prop(format!("Deformer::{}", mesh.name()))
+            .prop("Skin")
+            .child(Node::new("Version").prop(101i64))
+            .child(Node::new("Link_DeformAcuracy").prop(50f64));
+        self.add_object("Deformer", skin);
+        self.connect(skin_id, geometry_id);
+
+        for (bone_idx, (indices, weights)) in clusters.into_iter().enumerate() {
+            if indices.is_empty() {
+                continue;
+            }
+
+            let bind_pose = &skeleton.bind_poses[bone_idx];
+            let inverse_bind_pose = &skeleton.inverse_bind_poses[bone_idx];
+
+            let cluster_id = self.next_id();
+            let cluster = Node::new("Deformer")
+                .prop(cluster_id)
+                .prop(format!("SubDeformer::{}", bone_idx))
+                .prop("Cluster")
+                .child(Node::new("Version").prop(100i64))
+                .child(Node::new("UserData").prop("").prop(""))
+                .child(Node::new("Indexes").prop(indices))
+                .child(Node::new("Weights").prop(weights))
+                // The mesh's transform relative to the bone. Our meshes don't have a transform of
+                // their own, so this is the inverse bind pose.
+                .child(Node::new("Transform").prop(matrix_array(inverse_bind_pose)))
+                .child(Node::new("TransformLink").prop(matrix_array(bind_pose)));
+            self.add_object("Deformer", cluster);
+            self.connect(cluster_id, skin_id);
+            self.connect(skeleton.bone_ids[bone_idx], cluster_id);
+        }
+
+        let mut pose_nodes = vec![(model_id, Matrix4::identity())];
+        for (bone_id, bind_pose) in skeleton.bone_ids.iter().zip(skeleton.bind_poses.iter()) {
+            pose_nodes.push((*bone_id, *bind_pose));
+        }
+
+        let mut pose = Node::new("Pose")
+            .prop(self.next_id())
+            .prop(format!("Pose::{}", mesh.name()))
+            .prop("BindPose")
+            .child(Node::new("Type").prop("BindPose"))
+            .child(Node::new("Version").prop(100i64))
+            .child(Node::new("NbPoseNodes").prop(pose_nodes.len() as i64));
+        for (node_id, matrix) in pose_nodes.iter() {
+            pose.push(
+                Node::new("PoseNode")
+                    .child(Node::new("Node").prop(*node_id))
+                    .child(Node::new("Matrix").prop(matrix_array(matrix))),
+            );
+        }
+        self.add_object("Pose", pose);
+
+        Ok(())
+    }
+
+    fn write(&self, out: &mut dyn Write) -> Result<(), FbxError> {
+        writeln!(out, "; FBX 7.4.0 project file")?;
+        writeln!(out, "; Created by landon")?;
+        writeln!(out)?;
+
+        Node::new("FBXHeaderExtension")
+            .child(Node::new("FBXHeaderVersion").prop(1003i64))
+            .child(Node::new("FBXVersion").prop(7400i64))
+            .child(Node::new("Creator").prop("landon"))
+            .write(out, 0)?;
+
+        global_settings().write(out, 0)?;
+
+        let mut definitions = Node::new("Definitions")
+            .child(Node::new("Version").prop(100i64))
+            .child(Node::new("Count").prop(1 + self.objects.len() as i64))
+            .child(
+                Node::new("ObjectType")
+                    .prop("GlobalSettings")
+                    .child(Node::new("Count").prop(1i64)),
+            );
+        for (object_type, count) in self.object_counts.iter() {
+            definitions.push(
+                Node::new("ObjectType")
+                    .prop(*object_type)
+                    .child(Node::new("Count").prop(*count)),
+            );
+        }
+        definitions.write(out, 0)?;
+
+        let mut objects = Node::new("Objects");
+        for object in self.objects.iter() {
+            objects.push(object.clone());
+        }
+        objects.write(out, 0)?;
+
+        let mut connections = Node::new("Connections");
+        for connection in self.connections.iter() {
+            connections.push(connection.clone());
+        }
+        connections.write(out, 0)?;
+
+        Ok(())
+    }
+}
+
+/// Blender's axes and units. Z up, -Y forward, right handed and in meters.
+fn global_settings() -> Node {
+    let int = |name: &str, value: i64| p(name, "int", "Integer", "", vec![value.into()]);
+
+    Node::new("GlobalSettings")
+        .child(Node::new("Version").prop(1000i64))
+        .child(
+            Node::new("Properties70")
+                .child(int("UpAxis", 2))
+                .child(int("UpAxisSign", 1))
+                .child(int("FrontAxis", 1))
+                .child(int("FrontAxisSign", -1))
+                .child(int("CoordAxis", 0))
+                .child(int("CoordAxisSign", 1))
+                .child(p(
+                    "UnitScaleFactor",
+                    "double",
+                    "Number",
+                    "",
+                    vec![100f64.into()],
+                )),
+        )
+}
+
+/// The mesh's polygons along with their normals, uvs, smoothing groups and materials.
+fn geometry(id: i64, mesh: &BlenderMesh) -> Node {
+    let multi = mesh.multi_indexed_vertex_attributes();
+    let positions = multi.positions();
+
+    // The last index of every polygon is stored as -(index + 1)
+    let mut polygon_vertex_index = vec![];
+    let mut corner = 0;
+    for vertex_count in multi.vertices_in_each_face().iter() {
+        let face = &positions.indices()[corner..corner + *vertex_count as usize];
+
+        for (idx, position_idx) in face.iter().enumerate() {
+            let position_idx = *position_idx as i32;
+            match idx + 1 == face.len() {
+                true => polygon_vertex_index.push(-(position_idx + 1)),
+                false => polygon_vertex_index.push(position_idx),
+            };
+        }
+
+        corner += *vertex_count as usize;
+    }
+
+    let face_count = multi.vertices_in_each_face().len();
+
+    let mut geometry = Node::new("Geometry")
+        .prop(id)
+        .prop(format!("Geometry::{}", mesh.name()))
+        .prop("Mesh")
+        .child(Node::new("Vertices").prop(doubles(positions.attribute().as_slice())))
+        .child(Node::new("PolygonVertexIndex").prop(polygon_vertex_index))
+        .child(Node::new("GeometryVersion").prop(124i64));
+
+    let mut layer = Node::new("Layer")
+        .prop(0i64)
+        .child(Node::new("Version").prop(100i64));
+    let mut add_layer_element = |geometry: &mut Node, element_type: &'static str, element: Node| {
+        geometry.push(element);
+        layer.push(
+            Node::new("LayerElement")
+                .child(Node::new("Type").prop(element_type))
+                .child(Node::new("TypedIndex").prop(0i64)),
+        );
+    };
+
+    if let Some(normals) = multi.normals() {
+        let attribute = normals.attribute();
+        let mut per_corner = vec![];
+        for normal_idx in normals.indices().iter() {
+            let start = *normal_idx as usize * 3;
+            per_corner.extend_from_slice(&attribute.as_slice()[start..start + 3]);
+        }
+
+        let element = layer_element("LayerElementNormal", 101, "ByPolygonVertex", "Direct")
+            .child(Node::new("Normals").prop(doubles(&per_corner)));
+        add_layer_element(&mut geometry, "LayerElementNormal", element);
+    }
+
+    if mesh.smoothing_groups().len() == face_count {
+        let smoothing = mesh
+            .smoothing_groups()
+            .iter()
+            .map(|group| *group as i32)
+            .collect::<Vec<i32>>();
+
+        let element = layer_element("LayerElementSmoothing", 102, "ByPolygon", "Direct")
+            .child(Node::new("Smoothing").prop(smoothing));
+        add_layer_element(&mut geometry, "LayerElementSmoothing", element);
+    }
+
+    if let Some(uvs) = multi.uvs() {
+        let uv_index: Vec<i32> = uvs.indices().iter().map(|idx| *idx as i32).collect();
+
+        let element = layer_element("LayerElementUV", 101, "ByPolygonVertex", "IndexToDirect")
+            .child(Node::new("UV").prop(doubles(uvs.attribute().as_slice())))
+            .child(Node::new("UVIndex").prop(uv_index));
+        add_layer_element(&mut geometry, "LayerElementUV", element);
+    }
+
+    if !mesh.materials_vec().is_empty() {
+        let materials: Vec<i32> = (0..face_count)
+            .map(|face| multi.material_index().get(face).copied().unwrap_or(0) as i32)
+            .collect();
+
+        let element = layer_element("LayerElementMaterial", 101, "ByPolygon", "IndexToDirect")
+            .child(Node::new("Materials").prop(materials));
+        add_layer_element(&mut geometry, "LayerElementMaterial", element);
+    }
+
+    geometry.child(layer)
+}
+
+fn layer_element(name: &'static str, version: i64, mapping: &str, reference: &str) -> Node {
+    Node::new(name)
+        .prop(0i64)
+        .child(Node::new("Version").prop(version))
+        .child(Node::new("Name").prop(""))
+        .child(Node::new("MappingInformationType").prop(mapping))
+        .child(Node::new("ReferenceInformationType").prop(reference))
+}
+
+/// Split a transform into its translation, XYZ euler rotation in degrees and scale.
+fn decompose(transform: &Matrix4<f32>) -> (Vec<Property>, Vec<Property>, Vec<Property>) {
+    let column = |col: usize| {
+        Vector3::new(
+            transform[(0, col)],
+            transform[(1, col)],
+            transform[(2, col)],
+        )
+    };
+
+    let scale = [column(0).norm(), column(1).norm(), column(2).norm()];
+    let rotation = Matrix3::from_columns(&[
+        column(0) / scale[0],
+        column(1) / scale[1],
+        column(2) / scale[2],
+    ]);
+    let (x, y, z) = Rotation3::from_matrix_unchecked(rotation).euler_angles();
+
+    let properties = |values: [f32; 3]| -> Vec<Property> {
+        values
+            .iter()
+            .map(|value| Property::Double(*value as f64))
+            .collect()
+    };
+
+    (
+        properties([transform[(0, 3)], transform[(1, 3)], transform[(2, 3)]]),
+        properties([x.to_degrees(), y.to_degrees(), z.to_degrees()]),
+        properties(scale),
+    )
+}
+
+/// FBX matrices are column major, the same as nalgebra's.
+fn matrix_array(matrix: &Matrix4<f32>) -> Vec<f64> {
+    doubles(matrix.as_slice())
+}
+
+fn doubles(values: &[f32]) -> Vec<f64> {
+    values.iter().map(|value| *value as f64).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blender_armature::Bone;
+
+    /// Verify that we write a mesh's polygons, normals and uvs.
+    #[test]
+    fn write_geometry() {
+        let fbx = write(&[&triangle_and_quad(None)], &[]).unwrap();
+
+        assert!(fbx.contains("PolygonVertexIndex: *7 {\n\t\t\ta: 0,1,-3,0,2,3,-5\n"));
+        assert!(fbx.contains(r#"Normals: *21 {"#));
+        assert!(fbx.contains("UVIndex: *7 {\n\t\t\t\ta: 0,1,2,0,2,3,4\n"));
+        assert!(fbx.contains("Smoothing: *2 {\n\t\t\t\ta: 1,0\n"));
+        assert!(fbx.contains(r#"C: "OO", 1000002, 1000001"#));
+        assert!(!fbx.contains("Deformer"));
+    }
+
+    /// Verify that we write a skin cluster for every influencing bone along with a bind pose for
+    /// the mesh and all of the bones.
+    #[test]
+    fn write_skin() {
+        let armature = two_bone_armature();
+        let fbx = write(&[&triangle_and_quad(Some("Armature"))], &[&armature]).unwrap();
+
+        assert_eq!(fbx.matches(r#""LimbNode" {"#).count(), 4);
+        assert_eq!(fbx.matches(r#""Cluster" {"#).count(), 2);
+        assert!(fbx.contains("Indexes: *3 {\n\t\t\ta: 0,1,4\n"));
+        assert!(fbx.contains("Weights: *3 {\n\t\t\ta: 1,0.5,1\n"));
+        assert!(fbx.contains("NbPoseNodes: 3\n"));
+        assert_eq!(fbx.matches("PoseNode:").count(), 3);
+
+        // The child bone is two units above its parent
+        assert!(fbx.contains(r#"P: "Lcl Translation", "Lcl Translation", "", "A", 0, 0, 2"#));
+    }
+
+    /// Verify that we error if a mesh's armature was not provided.
+    #[test]
+    fn missing_armature() {
+        match write(&[&triangle_and_quad(Some("Armature"))], &[]) {
+            Err(FbxError::MissingArmature { mesh, armature }) => {
+                assert_eq!(mesh, "Mesh");
+                assert_eq!(armature, "Armature");
+            }
+            _ => panic!("Expected a missing armature error"),
+        };
+    }
+
+    /// Verify that we error instead of panicking if a bone's bind pose can't be inverted, such
+    /// as when the bone was scaled to zero.
+    #[test]
+    fn non_invertible_bind_pose() {
+        let mut armature = two_bone_armature();
+        let mut inverse_bind_poses = armature.inverse_bind_poses().clone();
+        inverse_bind_poses[1] = Bone::Matrix(Matrix4::zeros());
+        armature.set_inverse_bind_poses(inverse_bind_poses);
+
+        match write(&[&triangle_and_quad(Some("Armature"))], &[&armature]) {
+            Err(FbxError::NonInvertibleBindPose { armature, bone_idx }) => {
+                assert_eq!(armature, "Armature");
+                assert_eq!(bone_idx, 1);
+            }
+            _ => panic!("Expected a non invertible bind pose error"),
+        };
+    }
+
+    fn write(meshes: &[&BlenderMesh], armatures: &[&BlenderArmature]) -> Result<String, FbxError> {
+        let mut out = vec![];
+        write_ascii_fbx(&mut out, meshes, armatures)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    fn triangle_and_quad(armature_name: Option<&str>) -> BlenderMesh {
+        let mut mesh: BlenderMesh = serde_json::from_str(
+            r#"{
+                "name": "Mesh",
+                "armatures": [],
+                "bounding_box": {"min_corner": [0, 0, 0], "max_corner": [2, 1, 0]},
+                "materials": [],
+                "custom_properties": {},
+                "smoothing_groups": [1, 0],
+                "multi_indexed_vertex_attributes": {
+                    "vertices_in_each_face": [3, 4],
+                    "material_index": [0, 0],
+                    "positions": {
+                        "indices": [0, 1, 2, 0, 2, 3, 4],
+                        "attribute": {
+                            "data": [0, 0, 0, 1, 0, 0, 1, 1, 0, 0, 1, 0, 2, 1, 0],
+                            "attribute_size": 3
+                        }
+                    },
+                    "normals": {
+                        "indices": [0, 0, 0, 0, 0, 0, 0],
+                        "attribute": {"data": [0, 0, 1], "attribute_size": 3}
+                    },
+                    "uvs": {
+                        "indices": [0, 1, 2, 0, 2, 3, 4],
+                        "attribute": {
+                            "data": [0, 0, 1, 0, 1, 1, 0, 1, 1, 0.5],
+                            "attribute_size": 2
+                        }
+                    },
+                    "bone_influences": {
+                        "bones_per_vertex": {"NonUniform": [1, 2, 1, 1, 1]},
+                        "bone_indices": [0, 0, 1, 1, 1, 0],
+                        "bone_weights": [1, 0.5, 0.5, 1, 1, 1]
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        mesh.set_armature_name(armature_name.map(str::to_string));
+
+        mesh
+    }
+
+    fn two_bone_armature() -> BlenderArmature {
+        let mut armature = BlenderArmature::default();
+        armature.set_name("Armature".to_string());
+
+        armature.insert_joint_index("Root".to_string(), 0);
+        armature.insert_joint_index("Child".to_string(), 1);
+        armature.insert_child_to_parent(1, 0);
+
+        let bind_poses = [
+            Matrix4::new_translation(&Vector3::new(0., 0., 1.)),
+            Matrix4::new_translation(&Vector3::new(0., 0., 3.)),
+        ];
+        armature.set_inverse_bind_poses(
+            bind_poses
+                .iter()
+                .map(|pose| Bone::Matrix(pose.try_inverse().unwrap()))
+                .collect(),
+        );
+
+        armature
+    }
+}
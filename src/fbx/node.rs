@@ -0,0 +1,181 @@
+use std::io::{self, Write};
+
+/// A node in an FBX document, such as `Model: 123, "Model::Cube", "Mesh" { ... }`.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Node {
+    name: &'static str,
+    properties: Vec<Property>,
+    children: Vec<Node>,
+}
+
+/// A value attached to a node.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Property {
+    Int(i64),
+    Double(f64),
+    String(String),
+    IntArray(Vec<i32>),
+    DoubleArray(Vec<f64>),
+}
+
+impl Node {
+    pub(super) fn new(name: &'static str) -> Self {
+        Node {
+            name,
+            properties: vec![],
+            children: vec![],
+        }
+    }
+
+    /// Append a property to the node.
+    pub(super) fn prop(mut self, property: impl Into<Property>) -> Self {
+        self.properties.push(property.into());
+        self
+    }
+
+    /// Append a child to the node.
+    pub(super) fn child(mut self, child: Node) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Append a child to the node.
+    pub(super) fn push(&mut self, child: Node) {
+        self.children.push(child);
+    }
+
+    /// Write the node and all of its children in FBX's ASCII format.
+    pub(super) fn write(&self, out: &mut dyn Write, depth: usize) -> io::Result<()> {
+        let indent = "\t".repeat(depth);
+        write!(out, "{}{}: ", indent, self.name)?;
+
+        // Arrays are always the only property of their node
+        match self.properties.as_slice() {
+            [Property::IntArray(values)] => return write_array(out, &indent, values),
+            [Property::DoubleArray(values)] => return write_array(out, &indent, values),
+            _ => {}
+        };
+
+        let properties: Vec<String> = self.properties.iter().map(Property::to_ascii).collect();
+        write!(out, "{}", properties.join(", "))?;
+
+        if self.children.is_empty() && !self.properties.is_empty() {
+            return writeln!(out);
+        }
+
+        writeln!(out, " {{")?;
+        for child in self.children.iter() {
+            child.write(out, depth + 1)?;
+        }
+        writeln!(out, "{}}}", indent)
+    }
+}
+
+/// A `P: "Name", "Type", "Label", "Flags", values...` entry within a `Properties70` node.
+pub(super) fn p(name: &str, kind: &str, label: &str, flags: &str, values: Vec<Property>) -> Node {
+    let mut node = Node::new("P").prop(name).prop(kind).prop(label).prop(flags);
+    node.properties.extend(values);
+
+    node
+}
+
+fn write_array<T: ToString>(out: &mut dyn Write, indent: &str, values: &[T]) -> io::Result<()> {
+    let values: Vec<String> = values.iter().map(T::to_string).collect();
+
+    writeln!(out, "*{} {{", values.len())?;
+    writeln!(out, "{}\ta: {}", indent, values.join(","))?;
+    writeln!(out, "{}}}", indent)
+}
+
+impl Property {
+    fn to_ascii(&self) -> String {
+        match self {
+            Property::Int(value) => value.to_string(),
+            Property::Double(value) => value.to_string(),
+            Property::String(value) => format!("\"{}\"", value.replace('"', "&quot;")),
+            Property::IntArray(_) | Property::DoubleArray(_) => {
+                unreachable!("Arrays are written by write_array")
+            }
+        }
+    }
+}
+
+impl From<i64> for Property {
+    fn from(value: i64) -> Self {
+        Property::Int(value)
+    }
+}
+
+impl From<f64> for Property {
+    fn from(value: f64) -> Self {
+        Property::Double(value)
+    }
+}
+
+impl From<&str> for Property {
+    fn from(value: &str) -> Self {
+        Property::String(value.to_string())
+    }
+}
+
+impl From<String> for Property {
+    fn from(value: String) -> Self {
+        Property::String(value)
+    }
+}
+
+impl From<Vec<i32>> for Property {
+    fn from(values: Vec<i32>) -> Self {
+        Property::IntArray(values)
+    }
+}
+
+impl From<Vec<f64>> for Property {
+    fn from(values: Vec<f64>) -> Self {
+        Property::DoubleArray(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that we write nested nodes, arrays and properties the way that FBX expects.
+    #[test]
+    fn write_nodes() {
+        let node = Node::new("Geometry")
+            .prop(5i64)
+            .prop("Geometry::\"Cube\"")
+            .prop("Mesh")
+            .child(Node::new("Vertices").prop(vec![0.5f64, 1.0]))
+            .child(Node::new("Properties70").child(p(
+                "Color",
+                "ColorRGB",
+                "Color",
+                "A",
+                vec![
+                    Property::Double(1.0),
+                    Property::Double(0.0),
+                    Property::Double(0.0),
+                ],
+            )))
+            .child(Node::new("Layer").prop(0i64));
+
+        let mut out = vec![];
+        node.write(&mut out, 0).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#"Geometry: 5, "Geometry::&quot;Cube&quot;", "Mesh" {
+	Vertices: *2 {
+		a: 0.5,1
+	}
+	Properties70:  {
+		P: "Color", "ColorRGB", "Color", "A", 1, 0, 0
+	}
+	Layer: 0
+}
+"#
+        );
+    }
+}
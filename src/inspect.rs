@@ -0,0 +1,249 @@
+use blender_armature::{Action, ArmaturesByFilename, ObjectAnimationsByFilename};
+use blender_mesh::{BlenderMesh, MeshesByFilename};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+
+/// What is inside of the JSON that `landon export` wrote, for quickly checking an export without
+/// writing a script.
+///
+/// Format it with `{}` for a human readable summary of every mesh, armature and object animation,
+/// grouped by the `.blend` file that they were exported from.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExportInspection {
+    #[serde(default)]
+    meshes: MeshesByFilename,
+    #[serde(default)]
+    armatures: ArmaturesByFilename,
+    #[serde(default)]
+    object_animations: ObjectAnimationsByFilename,
+}
+
+impl ExportInspection {
+    /// Parse the JSON that `landon export` wrote.
+    ///
+    /// Exports that are missing some of the top level keys, such as older exports, are treated as
+    /// if those keys were empty.
+    pub fn from_json(json: &[u8]) -> Result<ExportInspection, serde_json::Error> {
+        serde_json::from_slice(json)
+    }
+
+    /// The exported meshes, keyed by filename and then by mesh name.
+    pub fn meshes(&self) -> &MeshesByFilename {
+        &self.meshes
+    }
+
+    /// The exported armatures, keyed by filename and then by armature name.
+    pub fn armatures(&self) -> &ArmaturesByFilename {
+        &self.armatures
+    }
+
+    /// The exported object animations, keyed by filename and then by object name.
+    pub fn object_animations(&self) -> &ObjectAnimationsByFilename {
+        &self.object_animations
+    }
+
+    fn is_armature_exported(&self, armature_name: &str) -> bool {
+        self.armatures
+            .values()
+            .any(|armatures| armatures.contains_key(armature_name))
+    }
+}
+
+impl Display for ExportInspection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut filenames: Vec<&String> = self
+            .meshes
+            .keys()
+            .chain(self.armatures.keys())
+            .chain(self.object_animations.keys())
+            .collect();
+        filenames.sort();
+        filenames.dedup();
+
+        for filename in filenames {
+            writeln!(f, "{}", filename)?;
+
+            for (mesh_name, mesh) in sorted(self.meshes.get(filename)) {
+                self.fmt_mesh(f, mesh_name.as_str(), mesh)?;
+            }
+
+            for (armature_name, armature) in sorted(self.armatures.get(filename)) {
+                writeln!(
+                    f,
+                    "  Armature {}: {} bones",
+                    armature_name,
+                    armature.inverse_bind_poses().len()
+                )?;
+                fmt_actions(
+                    f,
+                    armature.bone_space_actions(),
+                    armature.frames_per_second(),
+                )?;
+            }
+
+            for (object_name, animation) in sorted(self.object_animations.get(filename)) {
+                match animation.parent() {
+                    Some(parent) => writeln!(
+                        f,
+                        "  Object animation {}: parented to {}",
+                        object_name, parent
+                    )?,
+                    None => writeln!(f, "  Object animation {}", object_name)?,
+                };
+                fmt_actions(f, animation.actions(), animation.frames_per_second())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ExportInspection {
+    fn fmt_mesh(
+        &self,
+        f: &mut Formatter<'_>,
+        mesh_name: &str,
+        mesh: &BlenderMesh,
+    ) -> std::fmt::Result {
+        let multi = mesh.multi_indexed_vertex_attributes();
+
+        writeln!(
+            f,
+            "  Mesh {}: {:?}, {} positions, {} faces, {} materials",
+            mesh_name,
+            mesh.kind(),
+            multi.positions().attribute().len_vertices(),
+            multi.vertices_in_each_face().len(),
+            mesh.materials_vec().len()
+        )?;
+
+        let mut attributes = vec!["positions".to_string()];
+        if multi.normals().is_some() {
+            attributes.push("normals".to_string());
+        }
+        if multi.uvs().is_some() {
+            attributes.push("uvs".to_string());
+        }
+        if multi.lightmap_uvs().is_some() {
+            attributes.push("lightmap uvs".to_string());
+        }
+        if multi.bone_influences().is_some() {
+            attributes.push("bone influences".to_string());
+        }
+        let mut custom_attributes: Vec<&String> = mesh.custom_attributes().keys().collect();
+        custom_attributes.sort();
+        attributes.extend(custom_attributes.into_iter().cloned());
+        writeln!(f, "    Attributes: {}", attributes.join(", "))?;
+
+        if !mesh.shape_keys().is_empty() {
+            let shape_keys: Vec<&str> = mesh
+                .shape_keys()
+                .iter()
+                .map(|shape_key| shape_key.name().as_str())
+                .collect();
+            writeln!(f, "    Shape keys: {}", shape_keys.join(", "))?;
+        }
+
+        for armature in mesh.armatures() {
+            match self.is_armature_exported(&armature.name) {
+                true => writeln!(f, "    Armature: {}", armature.name)?,
+                false => writeln!(f, "    Armature: {} (not exported)", armature.name)?,
+            };
+        }
+
+        Ok(())
+    }
+}
+
+fn fmt_actions(
+    f: &mut Formatter<'_>,
+    actions: &HashMap<String, Action>,
+    frames_per_second: Option<u8>,
+) -> std::fmt::Result {
+    for (action_name, action) in sorted(Some(actions)) {
+        let (first, last) = match action.bone_keyframes().frame_range_inclusive() {
+            Some(range) => range,
+            None => {
+                writeln!(f, "    Action {}: no keyframes", action_name)?;
+                continue;
+            }
+        };
+        let frames = last - first;
+
+        match frames_per_second {
+            Some(fps) if fps > 0 => writeln!(
+                f,
+                "    Action {}: frames {} to {}, {:.2} seconds at {} fps",
+                action_name,
+                first,
+                last,
+                frames as f32 / fps as f32,
+                fps
+            )?,
+            _ => writeln!(
+                f,
+                "    Action {}: frames {} to {}",
+                action_name, first, last
+            )?,
+        };
+    }
+
+    Ok(())
+}
+
+/// Sort by name so that the summary is the same every time.
+fn sorted<K: Ord, T>(map: Option<&HashMap<K, T>>) -> BTreeMap<&K, &T> {
+    map.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blender_armature::{BlenderArmature, Bone, BoneKeyframe};
+    use nalgebra::Matrix4;
+
+    /// Verify that the summary lists each file's meshes and armatures along with their actions
+    /// and whether a mesh's armature was exported.
+    #[test]
+    fn summarize_export() {
+        let mut crate_mesh = BlenderMesh::cube(1.);
+        crate_mesh.set_armature_name(Some("Rig".to_string()));
+        let mut barrel = BlenderMesh::cube(1.);
+        barrel.set_armature_name(Some("Missing".to_string()));
+
+        let mut walk = Action::new();
+        for frame in [1, 25].iter() {
+            walk.insert_bone_keyframe(
+                0,
+                BoneKeyframe::new(*frame, Bone::Matrix(Matrix4::identity())),
+            );
+        }
+        let mut rig = BlenderArmature::default();
+        rig.set_inverse_bind_poses(vec![Bone::Matrix(Matrix4::identity())]);
+        rig.set_frames_per_second(Some(24));
+        rig.insert_bone_space_action("Walk".to_string(), walk);
+
+        let mut inspection = ExportInspection::from_json(b"{}").unwrap();
+        let meshes = inspection
+            .meshes
+            .entry("level.blend".to_string())
+            .or_default();
+        meshes.insert("Crate".into(), crate_mesh);
+        meshes.insert("Barrel".into(), barrel);
+        inspection
+            .armatures
+            .entry("level.blend".to_string())
+            .or_default()
+            .insert("Rig".to_string(), rig);
+
+        let summary = inspection.to_string();
+
+        assert_eq!(summary.lines().next(), Some("level.blend"));
+        assert!(summary.contains("  Mesh Crate: Triangles, 8 positions, 6 faces, 0 materials\n"));
+        assert!(summary.contains("    Armature: Rig\n"));
+        assert!(summary.contains("    Armature: Missing (not exported)\n"));
+        assert!(summary.contains("  Armature Rig: 1 bones\n"));
+        assert!(summary.contains("    Action Walk: frames 1 to 25, 1.00 seconds at 24 fps\n"));
+        assert!(summary.find("Mesh Barrel").unwrap() < summary.find("Mesh Crate").unwrap());
+    }
+}
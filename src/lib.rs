@@ -17,9 +17,44 @@ extern crate structopt;
 #[macro_use]
 extern crate serde;
 
+mod binding;
 mod blender;
+mod error;
+mod inspect;
+mod manifest;
 
+pub use self::binding::*;
 pub use self::blender::*;
+pub use self::error::*;
+pub use self::inspect::*;
+pub use self::manifest::*;
+
+#[cfg(feature = "bevy")]
+mod bevy;
+
+#[cfg(feature = "bevy")]
+pub use self::bevy::{
+    single_indexed_to_bevy_mesh, to_bevy_mesh, y_up_inverse_bind_poses, LandonArmature,
+    LandonExport, LandonLoader, LandonMesh, LandonPlugin,
+};
+
+#[cfg(feature = "compression")]
+mod compression;
+
+#[cfg(feature = "compression")]
+pub use self::compression::*;
+
+#[cfg(feature = "fbx")]
+mod fbx;
+
+#[cfg(feature = "fbx")]
+pub use self::fbx::{write_ascii_fbx, FbxError};
+
+#[cfg(feature = "usd")]
+mod usda;
+
+#[cfg(feature = "usd")]
+pub use self::usda::write_usda;
 
 #[cfg(feature = "cli")]
 mod subcommands;
@@ -30,6 +65,7 @@ pub use self::cli::*;
 #[cfg(feature = "cli")]
 mod cli {
     use crate::subcommands::export::ExportCmd;
+    use crate::subcommands::inspect::InspectCmd;
     use crate::subcommands::install::InstallCmd;
     use structopt::StructOpt;
 
@@ -44,6 +80,7 @@ mod cli {
             let cmd: &dyn Subcommand = match self {
                 Landon::Export(cmd) => cmd,
                 Landon::Install(cmd) => cmd,
+                Landon::Inspect(cmd) => cmd,
             };
             cmd.run()
         }
@@ -58,6 +95,8 @@ mod cli {
         Export(ExportCmd),
         /// Install various Blender addons
         Install(InstallCmd),
+        /// Print a summary of exported JSON and binary meshes
+        Inspect(InspectCmd),
     }
 
     #[cfg(feature = "cli")]
@@ -0,0 +1,500 @@
+use crate::{
+    ActionPreviewsByFilename, ExportWarningsByFilename, LinkedLibrariesByFilename,
+    ThumbnailsByFilename,
+};
+use blender_armature::{ArmaturesByFilename, CameraTracksByFilename, ObjectAnimationsByFilename};
+use blender_mesh::MeshesByFilename;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Bumped whenever the manifest's format changes in a way that could break readers.
+pub const EXPORT_MANIFEST_VERSION: u32 = 1;
+
+/// A summary of everything that one export produced, so that build systems can track which
+/// outputs depend on which `.blend` files and skip exports whose inputs haven't changed.
+///
+/// Serialize it with `serde_json` to write it to disk.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub(crate) version: u32,
+    pub(crate) sources: Vec<ManifestSource>,
+    pub(crate) outputs: Vec<ManifestOutput>,
+    pub(crate) warnings: Vec<String>,
+    pub(crate) blender_seconds: f64,
+    pub(crate) total_seconds: f64,
+}
+
+/// A `.blend` file that was exported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestSource {
+    pub(crate) path: PathBuf,
+    pub(crate) modified_unix_seconds: Option<u64>,
+    pub(crate) sha256: String,
+    pub(crate) meshes: Vec<String>,
+    pub(crate) armatures: Vec<String>,
+    pub(crate) object_animations: Vec<String>,
+    pub(crate) camera_tracks: Vec<String>,
+    pub(crate) linked_libraries: Vec<PathBuf>,
+    #[serde(default)]
+    pub(crate) thumbnails: Vec<PathBuf>,
+    #[serde(default)]
+    pub(crate) action_previews: Vec<PathBuf>,
+}
+
+/// A file that the export wrote.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestOutput {
+    pub(crate) path: Option<PathBuf>,
+    pub(crate) sha256: String,
+    pub(crate) byte_len: usize,
+}
+
+impl ExportManifest {
+    /// Start a manifest for exporting the `.blend` files, recording each file's modified time and
+    /// content hash.
+    ///
+    /// Call this before exporting so that a file that is saved during the export is seen as out
+    /// of date the next time around.
+    pub fn new(blend_files: &[PathBuf]) -> std::io::Result<ExportManifest> {
+        let mut manifest = ExportManifest {
+            version: EXPORT_MANIFEST_VERSION,
+            sources: vec![],
+            outputs: vec![],
+            warnings: vec![],
+            blender_seconds: 0.,
+            total_seconds: 0.,
+        };
+
+        for path in blend_files {
+            manifest.add_source(path)?;
+        }
+
+        Ok(manifest)
+    }
+
+    /// Add a `.blend` file that was exported, such as a linked library that was followed.
+    ///
+    /// Does nothing if the file is already a source.
+    pub fn add_source(&mut self, path: &Path) -> std::io::Result<()> {
+        let absolute = std::fs::canonicalize(path)?;
+        if self
+            .sources
+            .iter()
+            .any(|source| std::fs::canonicalize(&source.path).ok().as_ref() == Some(&absolute))
+        {
+            return Ok(());
+        }
+
+        let modified_unix_seconds = std::fs::metadata(path)?
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|since_epoch| since_epoch.as_secs());
+
+        self.sources.push(ManifestSource {
+            path: path.to_path_buf(),
+            modified_unix_seconds,
+            sha256: sha256(&std::fs::read(path)?),
+            meshes: vec![],
+            armatures: vec![],
+            object_animations: vec![],
+            camera_tracks: vec![],
+            linked_libraries: vec![],
+            thumbnails: vec![],
+            action_previews: vec![],
+        });
+
+        Ok(())
+    }
+
+    /// Record the libraries that each source links data from, warning about libraries that
+    /// don't exist.
+    ///
+    /// Linked libraries are dependencies of the source, so a build system should re-export the
+    /// source when one of them changes.
+    pub fn record_linked_libraries(&mut self, linked_libraries: &LinkedLibrariesByFilename) {
+        let mut missing_libraries = vec![];
+
+        for source in self.sources.iter_mut() {
+            let is_source = source_matcher(&source.path);
+
+            let mut libraries: Vec<PathBuf> = linked_libraries
+                .iter()
+                .filter(|(filename, _)| is_source(filename))
+                .flat_map(|(_, libraries)| libraries.iter().cloned())
+                .collect();
+            libraries.sort();
+            libraries.dedup();
+
+            for library in libraries.iter() {
+                if !library.exists() {
+                    missing_libraries.push(format!(
+                        "{} links from library {} which does not exist",
+                        source.path.display(),
+                        library.display()
+                    ));
+                }
+            }
+
+            source.linked_libraries = libraries;
+        }
+
+        self.warnings.extend(missing_libraries);
+    }
+
+    /// Record the thumbnails that were rendered for each source's objects.
+    pub fn record_thumbnails(&mut self, thumbnails: &ThumbnailsByFilename) {
+        for source in self.sources.iter_mut() {
+            let is_source = source_matcher(&source.path);
+
+            let mut paths: Vec<PathBuf> = thumbnails
+                .iter()
+                .filter(|(filename, _)| is_source(filename))
+                .flat_map(|(_, thumbnails)| thumbnails.values().cloned())
+                .collect();
+            paths.sort();
+
+            source.thumbnails = paths;
+        }
+    }
+
+    /// Record the preview strips that were rendered for each source's actions.
+    pub fn record_action_previews(&mut self, action_previews: &ActionPreviewsByFilename) {
+        for source in self.sources.iter_mut() {
+            let is_source = source_matcher(&source.path);
+
+            let mut paths: Vec<PathBuf> = action_previews
+                .iter()
+                .filter(|(filename, _)| is_source(filename))
+                .flat_map(|(_, armatures)| armatures.values())
+                .flat_map(|actions| actions.values().map(|preview| preview.path.clone()))
+                .collect();
+            paths.sort();
+
+            source.action_previews = paths;
+        }
+    }
+
+    /// Warn about the bones that were renamed because their names conflicted with other bones.
+    ///
+    /// Takes `(armature name, [(old bone name, new bone name)])`.
+    pub fn record_renamed_bones(&mut self, renamed_bones: &[(String, Vec<(String, String)>)]) {
+        for (armature_name, renames) in renamed_bones.iter() {
+            for (old_name, new_name) in renames.iter() {
+                self.warnings.push(format!(
+                    "Bone {} of armature {} was renamed to {} because its name conflicts with another bone",
+                    old_name, armature_name, new_name
+                ));
+            }
+        }
+    }
+
+    /// Record the warnings that the export scripts reported about the objects that they skipped
+    /// or couldn't fully export.
+    pub fn record_export_warnings(&mut self, export_warnings: &ExportWarningsByFilename) {
+        let mut warnings: Vec<String> = export_warnings
+            .values()
+            .flat_map(|objects| objects.values().flatten())
+            .map(|warning| {
+                format!(
+                    "{} from {}: {}",
+                    warning.object(),
+                    warning.blend_file(),
+                    warning.message()
+                )
+            })
+            .collect();
+        warnings.sort();
+
+        self.warnings.extend(warnings);
+    }
+
+    /// Record the names of the objects that were exported from each source, warning about
+    /// sources that nothing was exported from and meshes whose armature wasn't exported.
+    pub fn record_exported_objects(
+        &mut self,
+        meshes: &MeshesByFilename,
+        armatures: &ArmaturesByFilename,
+        object_animations: &ObjectAnimationsByFilename,
+        camera_tracks: &CameraTracksByFilename,
+    ) {
+        for source in self.sources.iter_mut() {
+            let is_source = source_matcher(&source.path);
+
+            source.meshes = names_from_source(meshes, &is_source);
+            source.armatures = names_from_source(armatures, &is_source);
+            source.object_animations = names_from_source(object_animations, &is_source);
+            source.camera_tracks = names_from_source(camera_tracks, &is_source);
+
+            if source.meshes.is_empty()
+                && source.armatures.is_empty()
+                && source.object_animations.is_empty()
+                && source.camera_tracks.is_empty()
+            {
+                self.warnings.push(format!(
+                    "Nothing was exported from {}",
+                    source.path.display()
+                ));
+            }
+        }
+
+        let mut missing_armatures = vec![];
+        for (mesh_name, mesh) in meshes.values().flatten() {
+            for armature in mesh.armatures() {
+                if !armatures.values().any(|a| a.contains_key(&armature.name)) {
+                    missing_armatures.push(format!(
+                        "Mesh {} is parented to armature {} which was not exported",
+                        mesh_name, armature.name
+                    ));
+                }
+            }
+        }
+        missing_armatures.sort();
+        self.warnings.extend(missing_armatures);
+    }
+
+    /// Record a file that was written, or stdout if there is no path.
+    pub fn record_output(&mut self, path: Option<&Path>, contents: &[u8]) {
+        self.outputs.push(ManifestOutput {
+            path: path.map(Path::to_path_buf),
+            sha256: sha256(contents),
+            byte_len: contents.len(),
+        });
+    }
+
+    /// Record how long Blender spent exporting and how long the whole export took.
+    pub fn record_durations(&mut self, blender: Duration, total: Duration) {
+        self.blender_seconds = blender.as_secs_f64();
+        self.total_seconds = total.as_secs_f64();
+    }
+
+    /// See [`EXPORT_MANIFEST_VERSION`].
+    ///
+    /// [`EXPORT_MANIFEST_VERSION`]: constant.EXPORT_MANIFEST_VERSION.html
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Every `.blend` file that was exported, in the order that they were exported.
+    pub fn sources(&self) -> &Vec<ManifestSource> {
+        &self.sources
+    }
+
+    /// Every file that was written.
+    pub fn outputs(&self) -> &Vec<ManifestOutput> {
+        &self.outputs
+    }
+
+    /// Problems that didn't stop the export but likely need attention.
+    pub fn warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
+
+    /// The number of seconds that Blender spent exporting.
+    pub fn blender_seconds(&self) -> f64 {
+        self.blender_seconds
+    }
+
+    /// The number of seconds that the whole export took, including writing outputs.
+    pub fn total_seconds(&self) -> f64 {
+        self.total_seconds
+    }
+}
+
+impl ManifestSource {
+    /// The path of the `.blend` file, as it was passed to the export.
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// When the file was last modified, in seconds since the unix epoch, if the platform
+    /// supports modified times.
+    pub fn modified_unix_seconds(&self) -> Option<u64> {
+        self.modified_unix_seconds
+    }
+
+    /// The hex encoded SHA-256 hash of the file's contents.
+    pub fn sha256(&self) -> &String {
+        &self.sha256
+    }
+
+    /// The names of the meshes that were exported from the file, sorted.
+    pub fn meshes(&self) -> &Vec<String> {
+        &self.meshes
+    }
+
+    /// The names of the armatures that were exported from the file, sorted.
+    pub fn armatures(&self) -> &Vec<String> {
+        &self.armatures
+    }
+
+    /// The names of the objects whose animations were exported from the file, sorted.
+    pub fn object_animations(&self) -> &Vec<String> {
+        &self.object_animations
+    }
+
+    /// The names of the cameras whose tracks were exported from the file, sorted.
+    pub fn camera_tracks(&self) -> &Vec<String> {
+        &self.camera_tracks
+    }
+
+    /// The absolute paths of the `.blend` libraries that the file links data from, sorted.
+    pub fn linked_libraries(&self) -> &Vec<PathBuf> {
+        &self.linked_libraries
+    }
+
+    /// The paths of the thumbnails that were rendered for the file's objects, sorted.
+    pub fn thumbnails(&self) -> &Vec<PathBuf> {
+        &self.thumbnails
+    }
+
+    /// The paths of the preview strips that were rendered for the file's actions, sorted.
+    pub fn action_previews(&self) -> &Vec<PathBuf> {
+        &self.action_previews
+    }
+}
+
+impl ManifestOutput {
+    /// The path that was written to, or `None` for stdout.
+    pub fn path(&self) -> Option<&PathBuf> {
+        self.path.as_ref()
+    }
+
+    /// The hex encoded SHA-256 hash of what was written.
+    pub fn sha256(&self) -> &String {
+        &self.sha256
+    }
+
+    /// The number of bytes that were written.
+    pub fn byte_len(&self) -> usize {
+        self.byte_len
+    }
+}
+
+/// Whether a filename that Blender keyed exported data by refers to the source.
+///
+/// Blender keys exported data by the absolute path of the file that it opened.
+fn source_matcher(path: &Path) -> impl Fn(&String) -> bool {
+    let path = path.to_path_buf();
+    let absolute = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+
+    move |filename: &String| Path::new(filename) == absolute || Path::new(filename) == path
+}
+
+fn names_from_source<K: ToString, T>(
+    by_filename: &HashMap<String, HashMap<K, T>>,
+    is_source: &dyn Fn(&String) -> bool,
+) -> Vec<String> {
+    let mut names: Vec<String> = by_filename
+        .iter()
+        .filter(|(filename, _)| is_source(filename))
+        .flat_map(|(_, by_name)| by_name.keys().map(|name| name.to_string()))
+        .collect();
+    names.sort();
+
+    names
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blender_mesh::BlenderMesh;
+
+    /// Verify that exported objects are attributed to the file that they came from and that we
+    /// warn about anything that looks wrong.
+    #[test]
+    fn record_exported_objects() {
+        let dir = std::env::temp_dir().join(format!("landon-manifest-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let level = dir.join("level.blend");
+        let empty = dir.join("empty.blend");
+        std::fs::write(&level, b"level").unwrap();
+        std::fs::write(&empty, b"").unwrap();
+
+        let mut manifest = ExportManifest::new(&[level.clone(), empty.clone()]).unwrap();
+
+        let mut mesh = BlenderMesh::cube(1.);
+        mesh.set_armature_name(Some("Rig".to_string()));
+        let mut meshes = MeshesByFilename::new();
+        meshes
+            .entry(level.to_str().unwrap().to_string())
+            .or_default()
+            .insert("Crate".into(), mesh);
+
+        manifest.record_exported_objects(
+            &meshes,
+            &ArmaturesByFilename::new(),
+            &ObjectAnimationsByFilename::new(),
+            &CameraTracksByFilename::new(),
+        );
+        manifest.record_output(None, b"{}");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(manifest.sources()[0].meshes(), &vec!["Crate".to_string()]);
+        assert_eq!(
+            manifest.sources()[1].sha256(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            manifest.warnings(),
+            &vec![
+                format!("Nothing was exported from {}", empty.display()),
+                "Mesh Crate is parented to armature Rig which was not exported".to_string(),
+            ]
+        );
+        assert_eq!(manifest.outputs()[0].byte_len(), 2);
+    }
+
+    /// Verify that linked libraries are recorded as dependencies of the file that links them and
+    /// that followed libraries are only added as sources once.
+    #[test]
+    fn record_linked_libraries() {
+        let dir = std::env::temp_dir().join(format!("landon-libraries-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = std::fs::canonicalize(dir).unwrap();
+        let level = dir.join("level.blend");
+        let props = dir.join("props.blend");
+        let missing = dir.join("missing.blend");
+        std::fs::write(&level, b"level").unwrap();
+        std::fs::write(&props, b"props").unwrap();
+
+        let mut manifest = ExportManifest::new(std::slice::from_ref(&level)).unwrap();
+        manifest.add_source(&props).unwrap();
+        manifest.add_source(&level).unwrap();
+
+        let mut linked = LinkedLibrariesByFilename::new();
+        linked.insert(
+            level.to_str().unwrap().to_string(),
+            vec![props.clone(), missing.clone()],
+        );
+        linked.insert(props.to_str().unwrap().to_string(), vec![level.clone()]);
+        manifest.record_linked_libraries(&linked);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(manifest.sources().len(), 2);
+        assert_eq!(
+            manifest.sources()[0].linked_libraries(),
+            &vec![missing.clone(), props.clone()]
+        );
+        assert_eq!(
+            manifest.sources()[1].linked_libraries(),
+            &vec![level.clone()]
+        );
+        assert_eq!(
+            manifest.warnings(),
+            &vec![format!(
+                "{} links from library {} which does not exist",
+                level.display(),
+                missing.display()
+            )]
+        );
+    }
+}
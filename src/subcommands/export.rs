@@ -1,9 +1,30 @@
-use crate::{export_blender_data, Subcommand};
-use blender_armature::{parse_armatures_from_blender_stdout, ArmaturesByFilename};
-use blender_mesh::{parse_meshes_from_blender_stdout, MeshesByFilename};
-use std::path::PathBuf;
+use crate::{
+    export_blender_data_with_config, parse_action_previews_from_blender_stdout,
+    parse_blender_stdout_parallel, parse_export_warnings_from_blender_stdout,
+    parse_linked_libraries_from_blender_stdout, parse_thumbnails_from_blender_stdout,
+    set_mesh_thumbnails, to_compressed_json, BlenderExportConfig, Compression, ExportManifest,
+    ParsedBlenderStdout, Subcommand,
+};
+use blender_armature::{
+    parse_camera_tracks_from_blender_stdout, parse_object_animations_from_blender_stdout,
+    ActionFilter, ArmaturesByFilename, CameraTracksByFilename, ClipIndex,
+    ObjectAnimationsByFilename,
+};
+use blender_mesh::{
+    apply_export_options, deduplicate_meshes, parse_mesh_sequences_from_blender_stdout,
+    parse_particle_systems_from_blender_stdout, parse_point_caches_from_blender_stdout,
+    parse_polylines_from_blender_stdout, rebase_origins, sign_binary, CreateSingleIndexConfig,
+    Endianness, ExportOptions, MeshInstance, MeshScratch, MeshesByFilename,
+    ParticleSystemsByFilename, PointCachesByFilename, PolylinesByFilename, UvIsland, UvLayer,
+    WorldCell,
+};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-/// Export meshes and armatures from Blender files to stdout as JSON
+/// Export meshes, armatures, object animations, camera tracks, grease pencil strokes, point
+/// caches, particle systems and mesh sequences from Blender files to stdout as JSON
 #[derive(Debug, StructOpt)]
 #[structopt(usage = USAGE)]
 pub struct ExportCmd {
@@ -11,25 +32,478 @@ pub struct ExportCmd {
     /// Can be specified multiple times such as `-f foo.blend -f bar.blend`
     #[structopt(short = "f", long = "file")]
     files: Vec<PathBuf>,
+    /// Precompute a bounding volume hierarchy for every mesh and include it in the mesh's JSON.
+    /// Useful for large static meshes that you want to raycast against at runtime.
+    #[structopt(long = "bvh")]
+    bvh: bool,
+    /// Snap every mesh's positions to a grid of this size and weld the positions that end up in
+    /// the same place, so that modular kit pieces line up exactly at their tile boundaries.
+    #[structopt(long = "snap-grid")]
+    snap_grid: Option<f32>,
+    /// Only keep one of every group of identical meshes, such as a prop that was copied into
+    /// several files, and list the others in the JSON's `mesh_instances` as references to the
+    /// mesh that was kept.
+    #[structopt(long = "dedupe-meshes")]
+    dedupe_meshes: bool,
+    /// Store every mesh's positions relative to their own center, rounded to whole units, and
+    /// record that origin in the mesh's `space.origin` in double precision. Keeps meshes that are
+    /// far from the world origin, such as the pieces of a large level, precise.
+    #[structopt(long = "rebase-origins")]
+    rebase_origins: bool,
+    /// Split the meshes into a grid of cells this many units wide, store every mesh's positions
+    /// relative to the center of its cell and include the cells in the JSON's `world_cells`, for
+    /// streaming in the parts of an open world that are near the player.
+    #[structopt(long = "world-cell-size", parse(try_from_str = parse_cell_size))]
+    world_cell_size: Option<f64>,
+    /// Apply the uv transforms of the materials' mapping nodes to the uvs of the faces that use
+    /// them, for engines that sample every texture with the mesh's uvs as they are.
+    #[structopt(long = "bake-uv-transforms")]
+    bake_uv_transforms: bool,
+    /// Flip the V of every mesh's uvs, for engines such as Direct3D, Metal and Vulkan whose
+    /// textures start at the top left corner. Recorded in every mesh's `space.uv_origin`.
+    #[structopt(long = "flip-uv-v")]
+    flip_uv_v: bool,
+    /// Include the islands of every mesh's lightmap uvs, along with their seams and scale, for
+    /// light bakers that pack the islands themselves.
+    #[structopt(long = "lightmap-charts")]
+    lightmap_charts: bool,
+    /// Reduce texture paths to their file names, unless they are within a texture search path.
+    #[structopt(long = "strip-texture-dirs")]
+    strip_texture_dirs: bool,
+    /// A directory that textures live in. Textures within it are named by their path relative to
+    /// it. Can be specified multiple times and is searched in order.
+    #[structopt(long = "texture-search-path")]
+    texture_search_paths: Vec<PathBuf>,
+    /// Rename a texture after its path has been normalized, such as `wood.png=wood_albedo`.
+    /// Can be specified multiple times.
+    #[structopt(long = "rename-texture", parse(try_from_str = parse_rename))]
+    texture_renames: Vec<(String, String)>,
+    /// Kill Blender if a file takes longer than this many seconds to export. Each file is
+    /// exported by its own Blender process when this is set.
+    #[structopt(long = "timeout")]
+    timeout_seconds: Option<u64>,
+    /// Also export the `.blend` libraries that the files link objects from. Linked objects are
+    /// exported from their library instead of from every file that links them.
+    #[structopt(long = "follow-linked-libraries")]
+    follow_linked_libraries: bool,
+    /// Export the meshes that modifiers such as geometry nodes generate, along with their named
+    /// attributes, instead of the base meshes.
+    #[structopt(long = "evaluate-modifiers")]
+    evaluate_modifiers: bool,
+    /// Skip the objects that are disabled in renders, such as helper geometry and reference
+    /// objects. Otherwise they are exported along with their visibility.
+    #[structopt(long = "skip-hide-render")]
+    skip_hide_render: bool,
+    /// Skip the objects that are hidden or disabled in the viewport.
+    #[structopt(long = "skip-hide-viewport")]
+    skip_hide_viewport: bool,
+    /// Bake the positions on every frame of every mesh's cloth or soft body simulation, such as
+    /// a flag or a cape, so that it can be replayed without a simulator.
+    #[structopt(long = "bake-point-caches")]
+    bake_point_caches: bool,
+    /// Only export the armature actions with this name. Can be specified multiple times. Every
+    /// action is exported if this isn't specified.
+    #[structopt(long = "include-action")]
+    include_actions: Vec<String>,
+    /// Leave out the armature actions whose names start with this prefix, such as `WIP_`.
+    /// Can be specified multiple times.
+    #[structopt(long = "exclude-action-prefix")]
+    exclude_action_prefixes: Vec<String>,
+    /// Rename an armature action after it has been filtered, such as `Walk=walk_cycle`.
+    /// Can be specified multiple times.
+    #[structopt(long = "rename-action", parse(try_from_str = parse_rename))]
+    action_renames: Vec<(String, String)>,
+    /// Rename bones whose names only differ by case or separators, such as `hand.L` and
+    /// `hand_L`, and warn about it instead of failing the export.
+    #[structopt(long = "rename-conflicting-bones")]
+    rename_conflicting_bones: bool,
+    /// Give every mesh object and bone a stable id that is exported alongside its name, so that
+    /// renaming them in Blender doesn't break references in saved games or prefabs. The ids are
+    /// stored as custom properties, and files are saved when new ids are assigned to them.
+    #[structopt(long = "assign-stable-ids")]
+    assign_stable_ids: bool,
+    /// Only export the object with this name, such as the one prop that you're tweaking inside
+    /// of a large library file. Can be specified multiple times. Every object is exported if
+    /// this isn't specified.
+    #[structopt(long = "object")]
+    object_names: Vec<String>,
+    /// Render a small thumbnail of every exported mesh object to a PNG in this directory, for
+    /// asset browsers. The path of each thumbnail is recorded in its mesh and in the manifest.
+    #[structopt(long = "thumbnail-dir")]
+    thumbnail_dir: Option<PathBuf>,
+    /// Render a strip of frames from every exported action to a PNG in this directory, so that
+    /// clips can be reviewed without opening Blender. The path of each strip is recorded in the
+    /// manifest.
+    #[structopt(long = "action-preview-dir")]
+    action_preview_dir: Option<PathBuf>,
+    /// Write the JSON to this file instead of stdout.
+    #[structopt(short = "o", long = "output")]
+    output: Option<PathBuf>,
+    /// Compress the JSON and the clip files with `gzip` or `zstd`, for web games where download
+    /// size matters. The clip files get a `.gz` or `.zst` extension. Read them back with
+    /// `landon::from_compressed_json`.
+    #[structopt(long = "compress")]
+    compress: Option<Compression>,
+    /// Also write every mesh's combined vertex data to `<BLEND_FILE>/<MESH_NAME>.landon.bin` in
+    /// this directory, in the binary layout that `landon inspect` summarizes. Linked meshes are
+    /// named `<MESH_NAME> [<LIBRARY>]`.
+    #[structopt(long = "binary-dir")]
+    binary_dir: Option<PathBuf>,
+    /// Also write every armature to this directory without its actions, along with each action
+    /// as its own `<ARMATURE>_<ACTION>.json` file and an `index.json` that lists them, so that
+    /// engines can load clips on demand.
+    #[structopt(long = "clip-dir")]
+    clip_dir: Option<PathBuf>,
+    /// Write each binary mesh's positions, surface attributes and skinning attributes to
+    /// separate sections, so that engines can stream very large meshes in a piece at a time.
+    #[structopt(long = "chunked")]
+    chunked: bool,
+    /// Also write every frame of the meshes whose topology changes from frame to frame, such as
+    /// fluid simulations and Alembic caches, to `<BLEND_FILE>/<MESH_NAME>.landon.seq` in this
+    /// directory. Each file can be memory mapped and played back a frame at a time.
+    #[structopt(long = "mesh-sequence-dir")]
+    mesh_sequence_dir: Option<PathBuf>,
+    /// Write the binary meshes in big endian byte order instead of little endian.
+    #[structopt(long = "big-endian")]
+    big_endian: bool,
+    /// Sign the binary meshes with the key in this file, so that games can check that they
+    /// weren't tampered with using `blender_mesh::verify_integrity`. Without a key the binary
+    /// meshes are only hashed, which detects corruption. Only the files in `--binary-dir` are
+    /// signed, not the JSON or any of the other outputs.
+    #[structopt(long = "signing-key-file")]
+    signing_key_file: Option<PathBuf>,
+    /// Write a JSON manifest of the source files, exported objects, outputs, warnings and
+    /// durations to this file, for build systems that track dependencies.
+    #[structopt(long = "manifest")]
+    manifest: Option<PathBuf>,
 }
 
 impl Subcommand for ExportCmd {
     fn run(&self) -> Result<(), anyhow::Error> {
-        let blender_stdout = export_blender_data(&self.files)?;
+        let started = Instant::now();
 
-        let meshes = parse_meshes_from_blender_stdout(blender_stdout.as_str());
-        let armatures = parse_armatures_from_blender_stdout(blender_stdout.as_str());
+        // Hash the sources before exporting so that any changes saved during the export make
+        // the manifest out of date
+        let mut manifest = match self.manifest {
+            Some(_) => Some(ExportManifest::new(&self.files)?),
+            None => None,
+        };
 
-        serde_json::to_writer(
-            std::io::stdout(),
-            &MeshesAndArmaturesByFilename { meshes, armatures },
-        )?;
+        // Read before exporting so that a missing key doesn't waste a long export
+        let signing_key = match self.signing_key_file.as_ref() {
+            Some(signing_key_file) => Some(std::fs::read(signing_key_file)?),
+            None => None,
+        };
+
+        if let Some(thumbnail_dir) = self.thumbnail_dir.as_ref() {
+            std::fs::create_dir_all(thumbnail_dir)?;
+        }
+        if let Some(action_preview_dir) = self.action_preview_dir.as_ref() {
+            std::fs::create_dir_all(action_preview_dir)?;
+        }
+
+        let config = BlenderExportConfig {
+            per_file_timeout: self.timeout_seconds.map(Duration::from_secs),
+            follow_linked_libraries: self.follow_linked_libraries,
+            evaluate_modifiers: self.evaluate_modifiers,
+            action_filter: ActionFilter {
+                include: self.include_actions.clone(),
+                exclude_prefixes: self.exclude_action_prefixes.clone(),
+                renames: self.action_renames.iter().cloned().collect(),
+            },
+            skip_hide_render: self.skip_hide_render,
+            skip_hide_viewport: self.skip_hide_viewport,
+            bake_point_caches: self.bake_point_caches,
+            export_mesh_sequences: self.mesh_sequence_dir.is_some(),
+            assign_stable_ids: self.assign_stable_ids,
+            object_names: self.object_names.clone(),
+            thumbnail_dir: self.thumbnail_dir.clone(),
+            action_preview_dir: self.action_preview_dir.clone(),
+        };
+        let blender_stdout = export_blender_data_with_config(&self.files, &config)?;
+        let blender_duration = started.elapsed();
+
+        let export_warnings = parse_export_warnings_from_blender_stdout(&blender_stdout);
+        for (filename, objects) in export_warnings.iter() {
+            for (object_name, warnings) in objects.iter() {
+                for warning in warnings.iter() {
+                    eprintln!("{} from {}: {}", object_name, filename, warning.message());
+                }
+            }
+        }
+
+        let options = ExportOptions {
+            strip_texture_directories: self.strip_texture_dirs,
+            texture_search_paths: self.texture_search_paths.clone(),
+            texture_renames: self.texture_renames.iter().cloned().collect(),
+        };
+
+        let ParsedBlenderStdout {
+            mut meshes,
+            mut armatures,
+        } = parse_blender_stdout_parallel(blender_stdout.as_str())?;
+        apply_export_options(&mut meshes, &options);
+        let thumbnails = parse_thumbnails_from_blender_stdout(&blender_stdout);
+        set_mesh_thumbnails(&mut meshes, &thumbnails);
+        if let Some(grid_size) = self.snap_grid {
+            for mesh in meshes.values_mut().flat_map(|meshes| meshes.values_mut()) {
+                mesh.snap_positions(grid_size);
+            }
+        }
+        let mesh_instances = match self.dedupe_meshes {
+            true => deduplicate_meshes(&mut meshes),
+            false => vec![],
+        };
+        if self.rebase_origins {
+            for mesh in meshes.values_mut().flat_map(|meshes| meshes.values_mut()) {
+                let center = mesh.positions_center();
+                mesh.rebase_origin([center[0].round(), center[1].round(), center[2].round()]);
+            }
+        }
+        let world_cells = match self.world_cell_size {
+            Some(cell_size) => rebase_origins(&mut meshes, cell_size),
+            None => vec![],
+        };
+        if self.bvh {
+            for mesh in meshes.values_mut().flat_map(|meshes| meshes.values_mut()) {
+                mesh.precompute_bvh();
+            }
+        }
+        if self.bake_uv_transforms {
+            for (mesh_name, mesh) in meshes.values_mut().flat_map(|meshes| meshes.iter_mut()) {
+                for material_name in mesh.bake_uv_transforms() {
+                    eprintln!(
+                        "Did not bake the uv transforms of material {} on mesh {} because its textures have different uv transforms",
+                        material_name, mesh_name
+                    );
+                }
+            }
+        }
+        if self.flip_uv_v {
+            for mesh in meshes.values_mut().flat_map(|meshes| meshes.values_mut()) {
+                mesh.flip_uv_v();
+            }
+        }
+        let mut bone_renames = vec![];
+        for (armature_name, armature) in armatures.values_mut().flatten() {
+            match self.rename_conflicting_bones {
+                true => {
+                    let renames = armature.rename_conflicting_bones();
+                    if !renames.is_empty() {
+                        bone_renames.push((armature_name.clone(), renames));
+                    }
+                }
+                false => armature.validate_bone_names()?,
+            };
+        }
+        for (armature_name, renames) in bone_renames.iter() {
+            for (old_name, new_name) in renames.iter() {
+                eprintln!(
+                    "Renamed bone {} of armature {} to {} because its name conflicts with another bone",
+                    old_name, armature_name, new_name
+                );
+            }
+
+            let deformed_meshes = meshes
+                .values_mut()
+                .flat_map(|meshes| meshes.values_mut())
+                .filter(|mesh| {
+                    mesh.armatures()
+                        .iter()
+                        .any(|armature| &armature.name == armature_name)
+                });
+            for mesh in deformed_meshes {
+                for (old_name, new_name) in renames.iter() {
+                    mesh.rename_vertex_group(old_name, new_name.clone());
+                }
+            }
+        }
+        let object_animations =
+            parse_object_animations_from_blender_stdout(blender_stdout.as_str());
+        let camera_tracks = parse_camera_tracks_from_blender_stdout(blender_stdout.as_str());
+        let polylines = parse_polylines_from_blender_stdout(blender_stdout.as_str());
+        let point_caches = parse_point_caches_from_blender_stdout(blender_stdout.as_str());
+        let particle_systems = parse_particle_systems_from_blender_stdout(blender_stdout.as_str());
+
+        let mut lightmap_charts = LightmapChartsByFilename::new();
+        if self.lightmap_charts {
+            for (filename, meshes) in meshes.iter() {
+                for (mesh_name, mesh) in meshes.iter() {
+                    let multi = mesh.multi_indexed_vertex_attributes();
+                    if multi.lightmap_uvs().is_none() {
+                        continue;
+                    }
+
+                    lightmap_charts
+                        .entry(filename.clone())
+                        .or_default()
+                        .insert(mesh_name.to_string(), mesh.uv_islands(UvLayer::Lightmap));
+                }
+            }
+        }
+
+        if let Some(manifest) = manifest.as_mut() {
+            let linked_libraries = parse_linked_libraries_from_blender_stdout(&blender_stdout);
+
+            if self.follow_linked_libraries {
+                let mut followed: Vec<&PathBuf> = linked_libraries
+                    .values()
+                    .flatten()
+                    .filter(|library| library.exists())
+                    .collect();
+                followed.sort();
+
+                for library in followed {
+                    manifest.add_source(library)?;
+                }
+            }
+
+            manifest.record_linked_libraries(&linked_libraries);
+            manifest.record_thumbnails(&thumbnails);
+            manifest.record_action_previews(&parse_action_previews_from_blender_stdout(
+                &blender_stdout,
+            ));
+            manifest.record_renamed_bones(&bone_renames);
+            manifest.record_export_warnings(&export_warnings);
+            manifest.record_exported_objects(
+                &meshes,
+                &armatures,
+                &object_animations,
+                &camera_tracks,
+            );
+        }
+
+        let mut binary_meshes = vec![];
+        if let Some(binary_dir) = self.binary_dir.as_ref() {
+            let endianness = match self.big_endian {
+                true => Endianness::Big,
+                false => Endianness::Little,
+            };
+
+            std::fs::create_dir_all(binary_dir)?;
+            let mut scratch = MeshScratch::default();
+            for (filename, mesh_name, mesh) in meshes.iter().flat_map(|(filename, meshes)| {
+                meshes
+                    .iter()
+                    .map(move |(mesh_name, mesh)| (filename, mesh_name, mesh))
+            }) {
+                let multi = mesh.multi_indexed_vertex_attributes();
+                let config = CreateSingleIndexConfig {
+                    bone_influences_per_vertex: multi.bone_influences().map(|_| 4),
+                    calculate_face_tangents: multi.uvs().is_some(),
+                };
+                let single_indexed = mesh
+                    .clone()
+                    .try_combine_vertex_indices_with_scratch(&config, &mut scratch)?;
+
+                // Linked objects are told apart by the library that they came from
+                let file_name = match mesh_name.library() {
+                    Some(library) => format!("{} [{}]", mesh_name.object(), file_stem(library)),
+                    None => mesh_name.object().to_string(),
+                };
+                let path = mesh_file_path(binary_dir, filename, &file_name, "landon.bin")?;
+
+                let mesh_name = mesh_name.object();
+                let bytes = match self.chunked {
+                    true => single_indexed.to_chunked_binary(mesh_name, endianness),
+                    false => single_indexed.to_binary(mesh_name, endianness),
+                };
+                let bytes = sign_binary(&bytes, signing_key.as_deref())?;
+                std::fs::write(&path, &bytes)?;
+
+                binary_meshes.push((path, bytes));
+            }
+        }
+
+        let mut mesh_sequence_files = vec![];
+        if let Some(mesh_sequence_dir) = self.mesh_sequence_dir.as_ref() {
+            std::fs::create_dir_all(mesh_sequence_dir)?;
+
+            let mesh_sequences = parse_mesh_sequences_from_blender_stdout(&blender_stdout);
+            for (filename, sequences) in mesh_sequences.iter() {
+                for (mesh_name, sequence) in sequences.iter() {
+                    let path =
+                        mesh_file_path(mesh_sequence_dir, filename, mesh_name, "landon.seq")?;
+                    let bytes = sequence.to_bytes();
+                    std::fs::write(&path, &bytes)?;
+
+                    mesh_sequence_files.push((path, bytes));
+                }
+            }
+        }
+
+        let mut clip_files = vec![];
+        if let Some(clip_dir) = self.clip_dir.as_ref() {
+            std::fs::create_dir_all(clip_dir)?;
+
+            let extension = match self.compress {
+                Some(compression) => format!("json.{}", compression.extension()),
+                None => "json".to_string(),
+            };
+
+            let (index, files) = ClipIndex::split(&armatures, &extension);
+            for (file, contents) in files {
+                clip_files.push((clip_dir.join(file), self.to_json(&contents)?));
+            }
+            clip_files.push((
+                clip_dir.join(format!("index.{}", extension)),
+                self.to_json(&index)?,
+            ));
+
+            for (path, bytes) in clip_files.iter() {
+                std::fs::write(path, bytes)?;
+            }
+        }
+
+        let json = self.to_json(&MeshesAndArmaturesByFilename {
+            meshes,
+            armatures,
+            object_animations,
+            camera_tracks,
+            polylines,
+            point_caches,
+            particle_systems,
+            lightmap_charts,
+            mesh_instances,
+            world_cells,
+        })?;
+
+        match self.output.as_ref() {
+            Some(output) => std::fs::write(output, &json)?,
+            None => std::io::stdout().write_all(&json)?,
+        };
+
+        if let (Some(mut manifest), Some(manifest_path)) = (manifest, self.manifest.as_ref()) {
+            manifest.record_output(self.output.as_deref(), &json);
+            let files = binary_meshes
+                .iter()
+                .chain(mesh_sequence_files.iter())
+                .chain(clip_files.iter());
+            for (path, bytes) in files {
+                manifest.record_output(Some(path), bytes);
+            }
+            manifest.record_durations(blender_duration, started.elapsed());
+
+            std::fs::write(manifest_path, serde_json::to_vec(&manifest)?)?;
+        }
 
         Ok(())
     }
 }
 
-const USAGE: &'static str = r#"# Prints mesh and armature data to stdout as JSON.
+impl ExportCmd {
+    /// Serialize to JSON, compressed if requested.
+    fn to_json<T: serde::Serialize>(&self, value: &T) -> Result<Vec<u8>, anyhow::Error> {
+        let json = match self.compress {
+            Some(compression) => to_compressed_json(value, compression)?,
+            None => serde_json::to_vec(value)?,
+        };
+
+        Ok(json)
+    }
+}
+
+const USAGE: &'static str = r#"# Prints mesh, armature, object animation, camera track, grease pencil, point cache and particle system data to stdout as JSON.
 
 # Export to stdout
 landon export -f /path/to/file1.blend -f /path/to/file2.blend
@@ -37,12 +511,139 @@ landon export -f /path/to/file1.blend -f /path/to/file2.blend
 # Export to file
 landon export -f /path/to/fil3.blend > some-file.json
 
+# Include a precomputed BVH with every mesh
+landon export --bvh -f /path/to/file1.blend
+
+# Snap positions to a 1cm grid so that modular pieces line up exactly
+landon export --snap-grid 0.01 -f /path/to/kit.blend
+
+# Only include the geometry of props that were copied into several files once
+landon export --dedupe-meshes -f /path/to/level1.blend -f /path/to/level2.blend
+
+# Keep the pieces of a large level precise by storing their positions relative to their own centers
+landon export --rebase-origins -f /path/to/level.blend
+
+# Split an open world into 256 meter cells that can be streamed in around the player
+landon export --world-cell-size 256 -f /path/to/world.blend
+
+# Apply the uv transforms of material mapping nodes to the uvs so that tiled textures tile the same way
+landon export --bake-uv-transforms -f /path/to/level.blend
+
+# Include the islands of every mesh's lightmap uvs for an external light baker
+landon export --lightmap-charts -f /path/to/file1.blend
+
+# Give up on any file that takes longer than 5 minutes to export
+landon export --timeout 300 -f /path/to/file1.blend -f /path/to/file2.blend
+
+# Also export the objects that the files link from other .blend files
+landon export --follow-linked-libraries -f /path/to/level.blend
+
+# Export the geometry that geometry nodes generate instead of the base meshes
+landon export --evaluate-modifiers -f /path/to/file1.blend
+
+# Bake cloth and soft body simulations, such as flags and capes, so that they can be replayed
+landon export --bake-point-caches -f /path/to/castle.blend
+
+# Quickly re-export the one prop that you're tweaking inside of a large library file
+landon export --object Crate -f /path/to/props.blend
+
+# Render a thumbnail of every mesh for an asset browser
+landon export --thumbnail-dir thumbnails -f /path/to/props.blend -o props.json
+
+# Render a strip of frames from every action to review a character's clips
+landon export --action-preview-dir previews --manifest manifest.json -f /path/to/hero.blend
+
+# Leave out objects that are disabled in renders, such as reference images and helper geometry
+landon export --skip-hide-render -f /path/to/file1.blend
+
+# Rename bones such as hand.L and hand_L that would otherwise fail the export
+landon export --rename-conflicting-bones -f /path/to/rig.blend
+
+# Write the JSON to a file along with a manifest for your build system
+landon export -f /path/to/file1.blend -o exported.json --manifest exported.manifest.json
+
+# Also write every mesh's vertex data in landon's binary layout
+landon export -f /path/to/file1.blend -o exported.json --binary-dir meshes
+
+# Write the binary meshes in chunks that can be streamed in, positions first
+landon export -f /path/to/level.blend -o exported.json --binary-dir meshes --chunked
+
+# Sign the binary meshes so that the game can reject files that were tampered with
+landon export -f /path/to/level.blend -o exported.json --binary-dir meshes --signing-key-file project.key
+
+# Compress the JSON for a web game
+landon export -f /path/to/level.blend -o exported.json.zst --compress zstd
+
+# Also write every frame of fluid simulations and Alembic caches to their own files
+landon export -f /path/to/fountain.blend -o exported.json --mesh-sequence-dir sequences
+
+# Also write every action to its own file so that the game can stream clips in on demand
+landon export -f /path/to/character.blend -o exported.json --clip-dir clips
+
+# Leave out work in progress actions and rename the rest to the clip names that the game expects
+landon export --exclude-action-prefix WIP_ --rename-action Walk=walk_cycle -f /path/to/character.blend
+
+# Name textures relative to an assets directory
+landon export --strip-texture-dirs --texture-search-path /path/to/assets -f /path/to/file1.blend
+
 # Full help documentation
 landon export --help
 "#;
 
+/// Where to write a file for one of the meshes in `dir`, in a directory named after the file
+/// that the mesh was exported from so that objects with the same name in different files don't
+/// overwrite each other.
+fn mesh_file_path(
+    dir: &Path,
+    filename: &str,
+    mesh_name: &str,
+    extension: &str,
+) -> std::io::Result<PathBuf> {
+    let dir = dir.join(file_stem(filename));
+    std::fs::create_dir_all(&dir)?;
+
+    Ok(dir.join(format!("{}.{}", mesh_name, extension)))
+}
+
+/// The name of a .blend file without its directory or extension.
+fn file_stem(filename: &str) -> String {
+    Path::new(filename)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| filename.to_string())
+}
+
+fn parse_rename(rename: &str) -> Result<(String, String), String> {
+    let mut split = rename.splitn(2, '=');
+
+    match (split.next(), split.next()) {
+        (Some(from), Some(to)) if !from.is_empty() && !to.is_empty() => {
+            Ok((from.to_string(), to.to_string()))
+        }
+        _ => Err(format!("Expected OLD_NAME=NEW_NAME, got {}", rename)),
+    }
+}
+
+fn parse_cell_size(cell_size: &str) -> Result<f64, String> {
+    match cell_size.parse::<f64>() {
+        Ok(cell_size) if cell_size > 0. => Ok(cell_size),
+        _ => Err(format!("Expected a positive cell size, got {}", cell_size)),
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct MeshesAndArmaturesByFilename {
     meshes: MeshesByFilename,
     armatures: ArmaturesByFilename,
+    object_animations: ObjectAnimationsByFilename,
+    camera_tracks: CameraTracksByFilename,
+    polylines: PolylinesByFilename,
+    point_caches: PointCachesByFilename,
+    particle_systems: ParticleSystemsByFilename,
+    lightmap_charts: LightmapChartsByFilename,
+    mesh_instances: Vec<MeshInstance>,
+    world_cells: Vec<WorldCell>,
 }
+
+/// The islands of each mesh's lightmap uvs, keyed by filename and then by mesh name.
+type LightmapChartsByFilename = HashMap<String, HashMap<String, Vec<UvIsland>>>;
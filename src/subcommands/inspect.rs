@@ -0,0 +1,55 @@
+use crate::{decompress, ExportInspection, Subcommand};
+use anyhow::Context;
+use blender_mesh::{inspect_binary, BINARY_MAGIC};
+use std::path::PathBuf;
+
+/// Print what is inside of exported JSON and binary meshes
+#[derive(Debug, StructOpt)]
+#[structopt(usage = USAGE)]
+pub struct InspectCmd {
+    /// The JSON files written by `landon export`, which can be compressed, or binary meshes
+    /// written by `landon export --binary-dir` to inspect.
+    #[structopt(parse(from_os_str))]
+    files: Vec<PathBuf>,
+}
+
+impl Subcommand for InspectCmd {
+    fn run(&self) -> Result<(), anyhow::Error> {
+        for file in self.files.iter() {
+            let bytes = std::fs::read(file)
+                .with_context(|| format!("Could not read {}", file.display()))?;
+
+            println!("{}", file.display());
+            match bytes.starts_with(&BINARY_MAGIC) {
+                true => {
+                    let inspection = inspect_binary(&bytes).with_context(|| {
+                        format!("{} is not a valid binary mesh", file.display())
+                    })?;
+                    println!("{}", inspection);
+                }
+                false => {
+                    let json = decompress(&bytes)
+                        .with_context(|| format!("Could not decompress {}", file.display()))?;
+                    let inspection = ExportInspection::from_json(&json).with_context(|| {
+                        format!("{} is not JSON written by landon export", file.display())
+                    })?;
+                    println!("{}", inspection);
+                }
+            };
+        }
+
+        Ok(())
+    }
+}
+
+const USAGE: &'static str = r#"# Prints a human readable summary of exported JSON and binary meshes.
+
+# Print the meshes, armatures, actions and object animations in an export
+landon inspect exported.json
+
+# Print the header, sections and vertex layout of binary meshes
+landon inspect /path/to/meshes/level/Cube.landon.bin /path/to/meshes/level/Sphere.landon.bin
+
+# Full help documentation
+landon inspect --help
+"#;
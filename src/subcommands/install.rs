@@ -1,4 +1,6 @@
-use crate::{install_armature_to_json, install_mesh_to_json, Subcommand};
+use crate::{
+    install_armature_to_json, install_mesh_to_json, install_object_animation_to_json, Subcommand,
+};
 
 #[derive(Debug, StructOpt)]
 pub struct InstallCmd {
@@ -6,6 +8,8 @@ pub struct InstallCmd {
     mesh_to_json: bool,
     #[structopt(short = "a", long = "armature-to-json")]
     armature_to_json: bool,
+    #[structopt(short = "o", long = "object-animation-to-json")]
+    object_animation_to_json: bool,
 }
 
 impl Subcommand for InstallCmd {
@@ -18,6 +22,10 @@ impl Subcommand for InstallCmd {
             install_armature_to_json()?;
         }
 
+        if self.object_animation_to_json {
+            install_object_animation_to_json()?;
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,328 @@
+//! Write static meshes to [USDA] files (the text form of USD) so that they can be reviewed in
+//! USD based tools such as usdview.
+//!
+//! Every mesh is written with its normals, uvs and a `UsdPreviewSurface` material for each of
+//! its materials. Image textures are not yet written, so textured material inputs fall back to
+//! `UsdPreviewSurface`'s defaults.
+//!
+//! [USDA]: https://graphics.pixar.com/usd/docs/index.html
+
+use blender_mesh::{BlenderMesh, IndexedAttribute, MaterialInput, PrincipledBSDF};
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+/// Write meshes to a USDA stage.
+///
+/// Meshes are written beneath a `/Root` Xform and their materials beneath `/Root/Materials`.
+/// Meshes and materials are written as they were exported from Blender, Z up and in meters.
+///
+/// Prim names can only contain letters, numbers and underscores, so any other characters in
+/// mesh and material names are replaced with underscores.
+pub fn write_usda(out: &mut dyn Write, meshes: &[&BlenderMesh]) -> io::Result<()> {
+    writeln!(out, "#usda 1.0")?;
+    writeln!(out, "(")?;
+    writeln!(out, "    defaultPrim = \"Root\"")?;
+    writeln!(out, "    metersPerUnit = 1")?;
+    writeln!(out, "    upAxis = \"Z\"")?;
+    writeln!(out, ")")?;
+    writeln!(out)?;
+    writeln!(out, "def Xform \"Root\"")?;
+    writeln!(out, "{{")?;
+
+    let mut materials = vec![];
+    let mut material_names = HashSet::new();
+
+    for mesh in meshes {
+        write_mesh(out, mesh)?;
+
+        for material in mesh.materials_vec().iter() {
+            if material_names.insert(prim_name(material.name())) {
+                materials.push(material);
+            }
+        }
+    }
+
+    if !materials.is_empty() {
+        writeln!(out, "    def Scope \"Materials\"")?;
+        writeln!(out, "    {{")?;
+        for material in materials {
+            write_material(out, material)?;
+        }
+        writeln!(out, "    }}")?;
+    }
+
+    writeln!(out, "}}")
+}
+
+fn write_mesh(out: &mut dyn Write, mesh: &BlenderMesh) -> io::Result<()> {
+    let multi = mesh.multi_indexed_vertex_attributes();
+    let materials = mesh.materials_vec();
+
+    let face_vertex_counts: Vec<String> = multi
+        .vertices_in_each_face()
+        .iter()
+        .map(|count| count.to_string())
+        .collect();
+    let bounding_box = mesh.bounding_box();
+
+    writeln!(out, "    def Mesh \"{}\" (", prim_name(mesh.name()))?;
+    writeln!(out, "        prepend apiSchemas = [\"MaterialBindingAPI\"]")?;
+    writeln!(out, "    )")?;
+    writeln!(out, "    {{")?;
+    writeln!(out, "        uniform token subdivisionScheme = \"none\"")?;
+    writeln!(
+        out,
+        "        int[] faceVertexCounts = [{}]",
+        face_vertex_counts.join(", ")
+    )?;
+    writeln!(
+        out,
+        "        int[] faceVertexIndices = [{}]",
+        join(multi.positions().indices())
+    )?;
+    writeln!(
+        out,
+        "        point3f[] points = [{}]",
+        tuples(multi.positions().attribute().as_slice(), 3)
+    )?;
+    writeln!(
+        out,
+        "        float3[] extent = [({}, {}, {}), ({}, {}, {})]",
+        bounding_box.min_corner.x,
+        bounding_box.min_corner.y,
+        bounding_box.min_corner.z,
+        bounding_box.max_corner.x,
+        bounding_box.max_corner.y,
+        bounding_box.max_corner.z,
+    )?;
+
+    if let Some(normals) = multi.normals() {
+        write_primvar(out, "normal3f[]", "normals", normals, 3)?;
+    }
+    if let Some(uvs) = multi.uvs() {
+        write_primvar(out, "texCoord2f[]", "st", uvs, 2)?;
+    }
+
+    match materials.len() {
+        0 => {}
+        1 => {
+            writeln!(
+                out,
+                "        rel material:binding = <{}>",
+                material_path(&materials[0])
+            )?;
+        }
+        _ => {
+            // Faces are bound to their materials using one subset per material
+            writeln!(
+                out,
+                "        uniform token subsetFamily:materialBind:familyType = \"partition\""
+            )?;
+
+            for (material_idx, material) in materials.iter().enumerate() {
+                let faces: Vec<u16> = multi
+                    .material_index()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, idx)| **idx as usize == material_idx)
+                    .map(|(face, _)| face as u16)
+                    .collect();
+                if faces.is_empty() {
+                    continue;
+                }
+
+                writeln!(out)?;
+                writeln!(
+                    out,
+                    "        def GeomSubset \"{}\" (",
+                    prim_name(material.name())
+                )?;
+                writeln!(
+                    out,
+                    "            prepend apiSchemas = [\"MaterialBindingAPI\"]"
+                )?;
+                writeln!(out, "        )")?;
+                writeln!(out, "        {{")?;
+                writeln!(out, "            uniform token elementType = \"face\"")?;
+                writeln!(
+                    out,
+                    "            uniform token familyName = \"materialBind\""
+                )?;
+                writeln!(out, "            int[] indices = [{}]", join(&faces))?;
+                writeln!(
+                    out,
+                    "            rel material:binding = <{}>",
+                    material_path(material)
+                )?;
+                writeln!(out, "        }}")?;
+            }
+        }
+    };
+
+    writeln!(out, "    }}")?;
+    writeln!(out)
+}
+
+/// Write an indexed, face varying primvar.
+fn write_primvar(
+    out: &mut dyn Write,
+    type_name: &str,
+    name: &str,
+    attribute: &IndexedAttribute,
+    size: usize,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "        {} primvars:{} = [{}] (",
+        type_name,
+        name,
+        tuples(attribute.attribute().as_slice(), size)
+    )?;
+    writeln!(out, "            interpolation = \"faceVarying\"")?;
+    writeln!(out, "        )")?;
+    writeln!(
+        out,
+        "        int[] primvars:{}:indices = [{}]",
+        name,
+        join(attribute.indices())
+    )
+}
+
+fn write_material(out: &mut dyn Write, material: &PrincipledBSDF) -> io::Result<()> {
+    writeln!(
+        out,
+        "        def Material \"{}\"",
+        prim_name(material.name())
+    )?;
+    writeln!(out, "        {{")?;
+    writeln!(
+        out,
+        "            token outputs:surface.connect = <{}/PreviewSurface.outputs:surface>",
+        material_path(material)
+    )?;
+    writeln!(out)?;
+    writeln!(out, "            def Shader \"PreviewSurface\"")?;
+    writeln!(out, "            {{")?;
+    writeln!(
+        out,
+        "                uniform token info:id = \"UsdPreviewSurface\""
+    )?;
+    if let MaterialInput::Uniform([r, g, b]) = material.base_color() {
+        writeln!(
+            out,
+            "                color3f inputs:diffuseColor = ({}, {}, {})",
+            r, g, b
+        )?;
+    }
+    if let MaterialInput::Uniform(roughness) = material.roughness() {
+        writeln!(
+            out,
+            "                float inputs:roughness = {}",
+            roughness
+        )?;
+    }
+    if let MaterialInput::Uniform(metallic) = material.metallic() {
+        writeln!(out, "                float inputs:metallic = {}", metallic)?;
+    }
+    writeln!(out, "                token outputs:surface")?;
+    writeln!(out, "            }}")?;
+    writeln!(out, "        }}")
+}
+
+fn material_path(material: &PrincipledBSDF) -> String {
+    format!("/Root/Materials/{}", prim_name(material.name()))
+}
+
+/// Replace every character that can't be used in a prim name with an underscore.
+fn prim_name(name: &str) -> String {
+    let mut prim_name: String = name
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c,
+            false => '_',
+        })
+        .collect();
+
+    if prim_name.is_empty() || prim_name.starts_with(|c: char| c.is_ascii_digit()) {
+        prim_name.insert(0, '_');
+    }
+
+    prim_name
+}
+
+/// `[1, 2, 3, 4, 5, 6]` with a size of 3 becomes `(1, 2, 3), (4, 5, 6)`
+fn tuples(data: &[f32], size: usize) -> String {
+    let tuples: Vec<String> = data
+        .chunks(size)
+        .map(|chunk| format!("({})", join(chunk)))
+        .collect();
+
+    tuples.join(", ")
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    let values: Vec<String> = values.iter().map(T::to_string).collect();
+    values.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify that we write a mesh's topology, points and face varying primvars.
+    #[test]
+    fn write_mesh_data() {
+        let usda = write(&[&BlenderMesh::plane(2.0, 2.0, 0)]);
+
+        assert!(usda.starts_with("#usda 1.0\n"));
+        assert!(usda.contains("int[] faceVertexCounts = [4]\n"));
+        assert!(usda.contains("interpolation = \"faceVarying\""));
+        assert!(usda.contains("int[] primvars:st:indices = [0, 1, 3, 2]\n"));
+        assert!(usda.contains("float3[] extent = [(-1, -1, 0), (1, 1, 0)]\n"));
+        assert!(!usda.contains("Materials"));
+    }
+
+    /// Verify that a mesh with multiple materials binds them using geom subsets.
+    #[test]
+    fn material_bindings() {
+        let mut mesh = BlenderMesh::plane(2.0, 2.0, 1);
+        mesh.materials_mut()
+            .push(material("Red Paint", [1., 0., 0.]));
+        mesh.materials_mut().push(material("Blue", [0., 0., 1.]));
+
+        let usda = write(&[&mesh]);
+
+        assert!(usda.contains("def GeomSubset \"Red_Paint\""));
+        assert!(usda.contains("rel material:binding = </Root/Materials/Red_Paint>\n"));
+        assert!(usda.contains("def Material \"Red_Paint\""));
+        assert!(usda.contains("color3f inputs:diffuseColor = (1, 0, 0)\n"));
+
+        // Every face uses the first material
+        assert!(!usda.contains("def GeomSubset \"Blue\""));
+        assert!(usda.contains("def Material \"Blue\""));
+    }
+
+    /// Verify that we turn names into valid prim names.
+    #[test]
+    fn prim_names() {
+        assert_eq!(prim_name("Cube.001"), "Cube_001");
+        assert_eq!(prim_name("1Cube"), "_1Cube");
+        assert_eq!(prim_name(""), "_");
+    }
+
+    fn write(meshes: &[&BlenderMesh]) -> String {
+        let mut out = vec![];
+        write_usda(&mut out, meshes).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn material(name: &str, color: [f32; 3]) -> PrincipledBSDF {
+        PrincipledBSDF::new(
+            name.to_string(),
+            MaterialInput::Uniform(color),
+            MaterialInput::Uniform(0.5),
+            MaterialInput::Uniform(0.),
+            None,
+        )
+    }
+}